@@ -46,6 +46,9 @@ pub struct AddressInfo {
     /// created endorsements
     pub created_endorsements: Vec<EndorsementId>,
 
+    /// number of operations sent by the address pending in the pool
+    pub pending_operation_count: usize,
+
     /// cycle information
     pub cycle_infos: Vec<ExecutionAddressCycleInfo>,
 }
@@ -75,6 +78,11 @@ impl std::fmt::Display for AddressInfo {
                 )?;
             }
         }
+        writeln!(
+            f,
+            "\tPending operations in pool: {}",
+            self.pending_operation_count
+        )?;
         writeln!(f, "\tCycle infos:")?;
         for cycle_info in &self.cycle_infos {
             writeln!(
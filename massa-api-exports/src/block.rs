@@ -1,6 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_models::{address::Address, block::Block, block_id::BlockId, slot::Slot};
+use massa_consensus_exports::block_fitness::BlockFitnessInfo;
+use massa_models::{
+    address::Address, block::Block, block_id::BlockId, operation::SecureShareOperation, slot::Slot,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -26,6 +29,8 @@ pub struct BlockInfoContent {
     pub is_candidate: bool,
     /// true if discarded
     pub is_discarded: bool,
+    /// fitness of the block and progress of its finalization, if active
+    pub fitness: Option<BlockFitnessInfo>,
     /// block
     pub block: Block,
 }
@@ -42,6 +47,26 @@ impl std::fmt::Display for BlockInfo {
                 display_if_true(content.is_in_blockclique, " (blockclique)"),
                 display_if_true(content.is_discarded, " (discarded)"),
             )?;
+            if let Some(fitness) = &content.fitness {
+                write!(
+                    f,
+                    "Fitness: {} ({} endorsements)",
+                    fitness.fitness, fitness.endorsement_count
+                )?;
+                if let (Some(descendants_fitness), Some(descendants_endorsement_fitness)) = (
+                    fitness.descendants_fitness,
+                    fitness.descendants_endorsement_fitness,
+                ) {
+                    write!(
+                        f,
+                        ", descendants fitness: {}/{} needed for finality ({} from endorsements)",
+                        descendants_fitness,
+                        fitness.finality_threshold,
+                        descendants_endorsement_fitness
+                    )?;
+                }
+                writeln!(f)?;
+            }
             writeln!(f, "Block: {}", content.block)?;
         } else {
             writeln!(f, "Block {} not found", self.id)?;
@@ -50,6 +75,35 @@ impl std::fmt::Display for BlockInfo {
     }
 }
 
+/// Batched block request
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BlocksBatchRequest {
+    /// ids of the blocks
+    pub ids: Vec<BlockId>,
+    /// whether to include the operations of the blocks. Default false
+    #[serde(default)]
+    pub include_operations: bool,
+    /// whether to include the endorsements in the block headers. Default true.
+    /// Without them, the signature of the headers cannot be verified
+    #[serde(default = "default_include_endorsements")]
+    pub include_endorsements: bool,
+}
+
+fn default_include_endorsements() -> bool {
+    true
+}
+
+/// Block of a batched block request
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BlockBatchInfo {
+    /// block id
+    pub id: BlockId,
+    /// status and content of the block, None if the block is unknown
+    pub content: Option<BlockInfoContent>,
+    /// operations of the block still known by the node, in block order. None if they were not requested
+    pub operations: Option<Vec<SecureShareOperation>>,
+}
+
 /// A block resume (without the block itself)
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BlockSummary {
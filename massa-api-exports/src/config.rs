@@ -13,8 +13,14 @@ use serde::Deserialize;
 pub struct APIConfig {
     /// when looking for next draw we want to look at max `draw_lookahead_period_count`
     pub draw_lookahead_period_count: u64,
+    /// whether `get_status` includes the production schedule of the staking addresses of the node
+    pub status_staking_summary: bool,
     /// bind for the private API
     pub bind_private: SocketAddr,
+    /// Unix domain socket on which the private API listens instead of `bind_private`, if any
+    pub bind_private_unix_socket: Option<PathBuf>,
+    /// permissions of the Unix domain socket file, restricting which local users can connect
+    pub unix_socket_permissions: u32,
     /// bind for the public API
     pub bind_public: SocketAddr,
     /// bind for the Massa API
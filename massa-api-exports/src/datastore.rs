@@ -1,6 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::page::{PageRequest, PagedVecV2};
 use massa_models::address::Address;
+use massa_models::slot::Slot;
+use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 
 /// Datastore entry query input structure
@@ -28,3 +31,41 @@ impl std::fmt::Display for DatastoreEntryOutput {
         Ok(())
     }
 }
+
+/// Datastore keys query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreKeysInput {
+    /// address owning the datastore
+    pub address: Address,
+    /// only the keys starting with this prefix
+    #[serde(default)]
+    pub prefix: Vec<u8>,
+    /// list the keys of the final datastore instead of the candidate one
+    #[serde(default)]
+    pub is_final: bool,
+    /// page of the sorted keys to return, all the keys if missing
+    pub page_request: Option<PageRequest>,
+}
+
+/// Datastore keys query output structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreKeysOutput {
+    /// address owning the datastore
+    pub address: Address,
+    /// requested page of the sorted keys, with the total number of matching keys.
+    /// `None` if the address does not exist
+    pub keys: Option<PagedVecV2<Vec<u8>>>,
+}
+
+/// State view pinning the final and candidate datastores for successive queries
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct StateViewInfo {
+    /// id of the view, to pass to the datastore queries
+    pub view_id: u64,
+    /// time at which the view is closed
+    pub expires_at: MassaTime,
+    /// last executed candidate slot seen by the view
+    pub candidate_cursor: Slot,
+    /// last executed final slot seen by the view
+    pub final_cursor: Slot,
+}
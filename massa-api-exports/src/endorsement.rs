@@ -1,8 +1,10 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    address::Address,
     block_id::BlockId,
     endorsement::{EndorsementId, SecureShareEndorsement},
+    slot::Slot,
 };
 use serde::{Deserialize, Serialize};
 
@@ -41,3 +43,16 @@ impl std::fmt::Display for EndorsementInfo {
         Ok(())
     }
 }
+
+/// Filter used to retrieve the endorsements created by an address
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EndorsementsByCreatorFilter {
+    /// creator of the endorsements
+    pub creator_address: Address,
+    /// optional start slot (included)
+    pub start: Option<Slot>,
+    /// optional end slot (excluded)
+    pub end: Option<Slot>,
+    /// optional filter to retrieve only candidate or final endorsements
+    pub is_final: Option<bool>,
+}
@@ -7,6 +7,7 @@ use massa_consensus_exports::error::ConsensusError;
 use massa_execution_exports::ExecutionError;
 use massa_hash::MassaHashError;
 use massa_models::error::ModelsError;
+use massa_models::error_code::{ErrorCategory, ErrorCode, ErrorCodeInfo};
 use massa_protocol_exports::ProtocolError;
 use massa_time::TimeError;
 use massa_versioning::versioning_factory::FactoryError;
@@ -75,6 +76,34 @@ impl From<ApiError> for ErrorObjectOwned {
             ApiError::FactoryError(_) => -32020,
         };
 
-        ErrorObject::owned(code, err.to_string(), None::<()>)
+        // the stable code of the error is sent in the data of the response
+        let error_code = err.error_code();
+        ErrorObject::owned(code, err.to_string(), Some(error_code))
+    }
+}
+
+impl ErrorCode for ApiError {
+    fn error_code(&self) -> ErrorCodeInfo {
+        let index = match self {
+            ApiError::ConsensusError(err) => return err.error_code(),
+            ApiError::ExecutionError(err) => return err.error_code(),
+            ApiError::ProtocolError(err) => return err.error_code(),
+            ApiError::ModelsError(err) => return err.error_code(),
+            ApiError::SendChannelError(_) => 1,
+            ApiError::ReceiveChannelError(_) => 2,
+            ApiError::MassaHashError(_) => 3,
+            ApiError::TimeError(_) => 4,
+            ApiError::WalletError(_) => 5,
+            ApiError::NotFound => 6,
+            ApiError::InconsistencyError(_) => 7,
+            ApiError::MissingCommandSender(_) => 8,
+            ApiError::MissingConfig(_) => 9,
+            ApiError::WrongAPI => 10,
+            ApiError::BadRequest(_) => 11,
+            ApiError::InternalServerError(_) => 12,
+            ApiError::FactoryError(_) => 13,
+            // 14 to 17 are used by the gRPC errors
+        };
+        ErrorCodeInfo::new(ErrorCategory::Api, index)
     }
 }
@@ -1,7 +1,8 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+pub use massa_execution_exports::{OffChainTask, OffChainTaskAction, OffChainTaskTrigger};
 use massa_final_state::StateChanges;
-use massa_models::{address::Address, output_event::SCOutputEvent, slot::Slot};
+use massa_models::{address::Address, amount::Amount, output_event::SCOutputEvent, slot::Slot};
 use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, fmt::Display};
 
@@ -81,7 +82,19 @@ pub struct ReadOnlyCall {
     pub parameter: Vec<u8>,
     /// caller's address, optional
     pub caller_address: Option<Address>,
+    /// coins sent to the target by the caller, minted for the simulation. Default none
+    #[serde(default)]
+    pub coins: Option<Amount>,
     /// whether to start execution from final or active state. Default false
     #[serde(default)]
     pub is_final: bool,
 }
+
+/// Node-local task triggered by final execution outputs, registered through the private API
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OffChainTaskInfo {
+    /// id of the task
+    pub id: u64,
+    /// the task
+    pub task: OffChainTask,
+}
@@ -1,7 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_hash::Hash;
+use massa_models::address::Address;
 use massa_models::amount::Amount;
 use massa_models::ledger::LedgerData;
+use massa_models::ledger_proof::LedgerEntryProof;
+use massa_models::slot::Slot;
 
 use serde::{Deserialize, Serialize};
 
@@ -28,3 +32,34 @@ impl std::fmt::Display for LedgerInfo {
         Ok(())
     }
 }
+
+/// Merkle proof of a final ledger entry
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LedgerProofOutput {
+    /// address owning the entry
+    pub address: Address,
+    /// proven datastore key, the balance is proven if missing
+    pub key: Option<Vec<u8>>,
+    /// final slot at which the ledger is committed by `root`
+    pub slot: Slot,
+    /// root of the commitment over the final ledger
+    pub root: Hash,
+    /// serialized ledger key and value, with the Merkle proof linking them to `root`
+    pub proof: LedgerEntryProof,
+}
+
+impl std::fmt::Display for LedgerProofOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        match &self.key {
+            Some(key) => writeln!(f, "Datastore key: {:?}", key)?,
+            None => writeln!(f, "Balance")?,
+        }
+        writeln!(f, "Final slot: {}", self.slot)?;
+        writeln!(f, "Ledger root: {}", self.root)?;
+        writeln!(f, "Value: {:?}", self.proof.value)?;
+        writeln!(f, "Proof depth: {}", self.proof.proof.siblings.len())?;
+        writeln!(f, "Valid: {}", self.proof.verify(&self.root))?;
+        Ok(())
+    }
+}
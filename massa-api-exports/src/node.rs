@@ -1,8 +1,18 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_consensus_exports::finality::FinalityStats;
+use massa_hash::Hash;
+use massa_models::address::Address;
+use massa_models::composite::PubkeySig;
 use massa_models::node::NodeId;
 use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats};
-use massa_models::{config::CompactConfig, slot::Slot, version::Version};
+use massa_models::{
+    config::CompactConfig,
+    slot::{IndexedSlot, Slot},
+    version::Version,
+};
+pub use massa_protocol_exports::ProtocolTraceEvent;
+use massa_protocol_exports::{PeerBandwidthStats, ProtocolTrafficStats};
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -33,6 +43,8 @@ pub struct NodeStatus {
     pub next_slot: Slot,
     /// consensus stats
     pub consensus_stats: ConsensusStats,
+    /// latest final period of each thread and finalization latency stats
+    pub finality_stats: FinalityStats,
     /// pool stats (operation count and endorsement count)
     pub pool_stats: (usize, usize),
     /// network stats
@@ -41,6 +53,8 @@ pub struct NodeStatus {
     pub execution_stats: ExecutionStats,
     /// compact configuration
     pub config: CompactConfig,
+    /// production schedule of the staking addresses of the node, if enabled in the API settings
+    pub staking_summary: Option<StakingSummary>,
 }
 
 impl std::fmt::Display for NodeStatus {
@@ -67,6 +81,8 @@ impl std::fmt::Display for NodeStatus {
 
         writeln!(f, "{}", self.consensus_stats)?;
 
+        writeln!(f, "{}", self.finality_stats)?;
+
         writeln!(f, "Pool stats:")?;
         writeln!(f, "\tOperations count: {}", self.pool_stats.0)?;
         writeln!(f, "\tEndorsements count: {}", self.pool_stats.1)?;
@@ -76,6 +92,10 @@ impl std::fmt::Display for NodeStatus {
 
         writeln!(f, "{}", self.execution_stats)?;
 
+        if let Some(staking_summary) = &self.staking_summary {
+            writeln!(f, "{}", staking_summary)?;
+        }
+
         writeln!(f, "Connected nodes:")?;
         for (node_id, (ip_addr, is_outgoing)) in &self.connected_nodes {
             writeln!(
@@ -89,3 +109,278 @@ impl std::fmt::Display for NodeStatus {
         Ok(())
     }
 }
+
+/// Production schedule of the staking addresses of the node
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StakingSummary {
+    /// elapsed part of the current cycle, in percent
+    pub current_cycle_progress: f64,
+    /// draws and production results of each staking address (wallet and watch-only addresses)
+    pub addresses: Vec<StakingAddressStatus>,
+}
+
+impl std::fmt::Display for StakingSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Staking summary:")?;
+        writeln!(
+            f,
+            "\tCurrent cycle progress: {:.2}%",
+            self.current_cycle_progress
+        )?;
+        for address_status in &self.addresses {
+            write!(f, "{}", address_status)?;
+        }
+        Ok(())
+    }
+}
+
+/// Block production result of an address at a slot
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProductionResult {
+    /// slot at which the address was drawn to produce a block
+    pub slot: Slot,
+    /// true if the blockclique contains a block at that slot
+    pub produced: bool,
+}
+
+/// Draws and production results of a staking address
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StakingAddressStatus {
+    /// staking address
+    pub address: Address,
+    /// next slot at which the address is drawn to produce a block, within the draw lookahead
+    pub next_block_draw: Option<Slot>,
+    /// time until the next block draw
+    pub next_block_draw_in: Option<MassaTime>,
+    /// next slot at which the address is drawn to endorse a block, within the draw lookahead
+    pub next_endorsement_draw: Option<IndexedSlot>,
+    /// time until the next endorsement draw
+    pub next_endorsement_draw_in: Option<MassaTime>,
+    /// result of the last block draw of the address, within the draw lookahead
+    pub last_production: Option<ProductionResult>,
+    /// number of blocks produced by the address during the current cycle
+    pub current_cycle_ok_count: u64,
+    /// number of blocks missed by the address during the current cycle
+    pub current_cycle_nok_count: u64,
+    /// number of additional misses during the current cycle that would trigger the implicit sale of the rolls,
+    /// 0 if the sale is already due. None if misses cannot trigger the sale
+    pub current_cycle_misses_before_roll_sale: Option<u64>,
+}
+
+impl std::fmt::Display for StakingAddressStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "\tAddress: {}", self.address)?;
+        match (self.next_block_draw, self.next_block_draw_in) {
+            (Some(slot), Some(time)) => writeln!(
+                f,
+                "\t\tNext block draw: {} (in {})",
+                slot,
+                time.format_duration().unwrap_or_else(|_| time.to_string())
+            )?,
+            _ => writeln!(f, "\t\tNo upcoming block draw")?,
+        }
+        match (&self.next_endorsement_draw, self.next_endorsement_draw_in) {
+            (Some(indexed_slot), Some(time)) => writeln!(
+                f,
+                "\t\tNext endorsement draw: {} (in {})",
+                indexed_slot,
+                time.format_duration().unwrap_or_else(|_| time.to_string())
+            )?,
+            _ => writeln!(f, "\t\tNo upcoming endorsement draw")?,
+        }
+        if let Some(last_production) = &self.last_production {
+            writeln!(
+                f,
+                "\t\tLast block draw: {} ({})",
+                last_production.slot,
+                if last_production.produced {
+                    "produced"
+                } else {
+                    "missed"
+                }
+            )?;
+        }
+        writeln!(
+            f,
+            "\t\tCurrent cycle: {} produced, {} missed",
+            self.current_cycle_ok_count, self.current_cycle_nok_count
+        )?;
+        match self.current_cycle_misses_before_roll_sale {
+            Some(0) => writeln!(
+                f,
+                "\t\tWarning: the miss ratio is above the limit, the rolls will be implicitly sold unless more blocks are produced"
+            ),
+            Some(misses) => writeln!(
+                f,
+                "\t\tThe rolls will be implicitly sold after {} more misses in the current cycle",
+                misses
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Block production statistics of an address over a final cycle, signed by the key of the node,
+/// so that a staking service can prove its production to third parties
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProductionReport {
+    /// producer address
+    pub address: Address,
+    /// final cycle
+    pub cycle: u64,
+    /// number of slots at which the address was drawn to produce a block
+    pub opportunities: u64,
+    /// number of blocks produced by the address
+    pub successes: u64,
+    /// public key of the node and its signature of the hash of `ProductionReport::signed_content`
+    pub node_signature: PubkeySig,
+}
+
+impl ProductionReport {
+    /// Bytes signed by the node: prefixed address bytes, then cycle, opportunities and successes as big-endian u64
+    pub fn signed_content(
+        address: &Address,
+        cycle: u64,
+        opportunities: u64,
+        successes: u64,
+    ) -> Vec<u8> {
+        let mut content = address.to_prefixed_bytes();
+        content.extend(cycle.to_be_bytes());
+        content.extend(opportunities.to_be_bytes());
+        content.extend(successes.to_be_bytes());
+        content
+    }
+
+    /// Checks that the report was signed by the node key it mentions
+    pub fn verify(&self) -> bool {
+        let content = Self::signed_content(
+            &self.address,
+            self.cycle,
+            self.opportunities,
+            self.successes,
+        );
+        self.node_signature
+            .public_key
+            .verify_signature(
+                &Hash::compute_from(&content),
+                &self.node_signature.signature,
+            )
+            .is_ok()
+    }
+}
+
+impl std::fmt::Display for ProductionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        writeln!(f, "	Cycle: {}", self.cycle)?;
+        writeln!(
+            f,
+            "	Produced blocks: {} out of {} draws",
+            self.successes, self.opportunities
+        )?;
+        writeln!(f, "	Node public key: {}", self.node_signature.public_key)?;
+        writeln!(f, "	Signature: {}", self.node_signature.signature)
+    }
+}
+
+/// Bytes exchanged with a connected node at the protocol level
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NodeBandwidthInfo {
+    /// id of the connected node
+    pub node_id: NodeId,
+    /// bytes received from and sent to the node, per message type
+    pub stats: PeerBandwidthStats,
+}
+
+impl std::fmt::Display for NodeBandwidthInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bytes_in = &self.stats.bytes_in;
+        let bytes_out = &self.stats.bytes_out;
+        writeln!(f, "Node's ID: {}", self.node_id)?;
+        writeln!(
+            f,
+            "\tReceived bytes: {} (blocks: {}, endorsements: {}, operations: {}, peers: {})",
+            bytes_in.total(),
+            bytes_in.block,
+            bytes_in.endorsement,
+            bytes_in.operation,
+            bytes_in.peer_management
+        )?;
+        writeln!(
+            f,
+            "\tSent bytes: {} (blocks: {}, endorsements: {}, operations: {}, peers: {})",
+            bytes_out.total(),
+            bytes_out.block,
+            bytes_out.endorsement,
+            bytes_out.operation,
+            bytes_out.peer_management
+        )?;
+        writeln!(f, "\tThrottled messages: {}", self.stats.throttled_messages)?;
+        writeln!(
+            f,
+            "\tRate limit violations: {}",
+            self.stats.rate_limit_violations
+        )
+    }
+}
+
+/// Messages exchanged with all the nodes at the protocol level since the node started
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NodeTrafficInfo {
+    /// messages received and sent, and their bytes, per message type
+    pub stats: ProtocolTrafficStats,
+}
+
+impl std::fmt::Display for NodeTrafficInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (direction, traffic) in [
+            ("Received", &self.stats.received),
+            ("Sent", &self.stats.sent),
+        ] {
+            let total = traffic.total();
+            writeln!(
+                f,
+                "{} messages: {} ({} bytes)",
+                direction, total.messages, total.bytes
+            )?;
+            for (message_type, message_traffic) in traffic.by_type() {
+                writeln!(
+                    f,
+                    "\t{}: {} ({} bytes)",
+                    message_type.replace('_', " "),
+                    message_traffic.messages,
+                    message_traffic.bytes
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_production_report_signature() {
+        let node_keypair = KeyPair::generate(0).unwrap();
+        let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let content = ProductionReport::signed_content(&address, 3, 10, 9);
+        let mut report = ProductionReport {
+            address,
+            cycle: 3,
+            opportunities: 10,
+            successes: 9,
+            node_signature: PubkeySig {
+                public_key: node_keypair.get_public_key(),
+                signature: node_keypair.sign(&Hash::compute_from(&content)).unwrap(),
+            },
+        };
+        assert!(report.verify());
+
+        // tampering with the statistics invalidates the signature
+        report.successes = 10;
+        assert!(!report.verify());
+    }
+}
@@ -3,7 +3,9 @@
 use massa_models::{
     block_id::BlockId,
     operation::{OperationId, SecureShareOperation},
+    slot::Slot,
 };
+use massa_time::MassaTime;
 
 use massa_signature::{PublicKey, Signature};
 use serde::{Deserialize, Serialize};
@@ -65,6 +67,91 @@ impl std::fmt::Display for OperationInfo {
     }
 }
 
+/// Draft operation whose validity window is checked before it is signed
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+pub struct OperationValidityInput {
+    /// thread of the operation, which is the thread of its creator address
+    pub thread: u8,
+    /// period after which the operation expires
+    pub expire_period: u64,
+    /// max gas of the operation, for smart contract executions and calls
+    pub max_gas: Option<u64>,
+    /// time the operation takes to be signed and sent, counted from now
+    pub signing_delay: Option<MassaTime>,
+}
+
+/// Outcome of the validity check of a draft operation, with suggested adjustments
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OperationValidityCheck {
+    /// thread of the operation
+    pub thread: u8,
+    /// period after which the operation expires
+    pub expire_period: u64,
+    /// latest slot at the time the operation is sent, None before genesis
+    pub sent_at_slot: Option<Slot>,
+    /// first period of the thread in which a block including the operation can still be produced once it is sent
+    pub next_period: u64,
+    /// last final period of the thread
+    pub final_period: u64,
+    /// first period in which the operation can be included
+    pub validity_start_period: u64,
+    /// number of periods of the thread in which the operation can be included once it is sent
+    pub inclusion_periods: u64,
+    /// reasons why the operation would not be included, empty if it can be
+    pub errors: Vec<String>,
+    /// expiry period giving the operation the longest inclusion window once it is sent
+    pub suggested_expire_period: u64,
+    /// max gas accepted in a block, if the max gas of the operation exceeds it
+    pub suggested_max_gas: Option<u64>,
+}
+
+impl std::fmt::Display for OperationValidityCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Operation expiring after period {} in thread {}",
+            self.expire_period, self.thread
+        )?;
+        if let Some(slot) = self.sent_at_slot {
+            writeln!(f, "	Sent at slot: {}", slot)?;
+        }
+        writeln!(
+            f,
+            "	Valid from period {}, includable in {} periods from period {} (final period: {})",
+            self.validity_start_period, self.inclusion_periods, self.next_period, self.final_period
+        )?;
+        if self.errors.is_empty() {
+            writeln!(f, "	The operation can be included")?;
+        }
+        for error in &self.errors {
+            writeln!(f, "	Error: {}", error)?;
+        }
+        writeln!(
+            f,
+            "	Suggested expire period: {}",
+            self.suggested_expire_period
+        )?;
+        if let Some(max_gas) = self.suggested_max_gas {
+            writeln!(f, "	Suggested max gas: {}", max_gas)?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of periods in which an operation expiring after `expire_period`,
+/// valid during `validity_periods` periods before it, can be included from `next_period` on
+///
+/// ```
+/// # use massa_api_exports::operation::inclusion_periods;
+/// assert_eq!(inclusion_periods(20, 10, 15), 6);
+/// assert_eq!(inclusion_periods(20, 10, 5), 11);
+/// assert_eq!(inclusion_periods(20, 10, 21), 0);
+/// ```
+pub fn inclusion_periods(expire_period: u64, validity_periods: u64, next_period: u64) -> u64 {
+    let first_period = next_period.max(expire_period.saturating_sub(validity_periods));
+    expire_period.saturating_add(1).saturating_sub(first_period)
+}
+
 #[cfg(test)]
 mod tests {
     use jsonrpsee::core::__reexports::serde_json::{self, Value};
@@ -44,7 +44,7 @@ impl<T: Serialize> Serialize for PagedVec<T> {
 }
 
 /// Represents the request inputs for a PagedVec
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PageRequest {
     /// The limit of elements in a page
     pub limit: usize,
@@ -53,7 +53,7 @@ pub struct PageRequest {
 }
 
 /// Represents the request inputs for a PagedVecV2
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PagedVecV2<T> {
     content: Vec<T>,
     total_count: usize,
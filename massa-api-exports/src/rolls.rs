@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_models::{address::Address, slot::Slot};
 use serde::{Deserialize, Serialize};
 
 /// Roll counts
@@ -21,3 +22,118 @@ impl std::fmt::Display for RollsInfo {
         Ok(())
     }
 }
+
+/// Prospective roll operation of an address
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+pub struct RollOperationPreviewInput {
+    /// address buying or selling the rolls
+    pub address: Address,
+    /// number of rolls to buy if positive, to sell if negative
+    pub roll_delta: i64,
+}
+
+/// Outcome of a dry-run of a roll operation, with an estimate of the draws at the resulting roll count
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RollOperationPreview {
+    /// address buying or selling the rolls
+    pub address: Address,
+    /// slot at which the operation was simulated
+    pub executed_at: Slot,
+    /// reason why the operation would fail, None if it would succeed
+    pub error: Option<String>,
+    /// candidate roll count before the operation
+    pub candidate_rolls: u64,
+    /// candidate roll count after the operation
+    pub resulting_rolls: u64,
+    /// first cycle whose draws take the resulting roll count into account
+    pub effective_cycle: u64,
+    /// total number of active rolls the estimate is based on, including the resulting rolls of the address
+    pub total_rolls: u64,
+    /// expected number of block draws of the address per cycle
+    pub expected_block_draws_per_cycle: f64,
+    /// expected number of endorsement draws of the address per cycle
+    pub expected_endorsement_draws_per_cycle: f64,
+}
+
+impl std::fmt::Display for RollOperationPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        writeln!(f, "\tSimulated at slot: {}", self.executed_at)?;
+        match &self.error {
+            Some(error) => writeln!(f, "\tOperation would fail: {}", error)?,
+            None => writeln!(f, "\tOperation would succeed")?,
+        }
+        writeln!(
+            f,
+            "\tCandidate rolls: {} -> {}",
+            self.candidate_rolls, self.resulting_rolls
+        )?;
+        writeln!(
+            f,
+            "\tEstimated draws per cycle from cycle {} ({} rolls in total): {:.2} blocks / {:.2} endorsements",
+            self.effective_cycle,
+            self.total_rolls,
+            self.expected_block_draws_per_cycle,
+            self.expected_endorsement_draws_per_cycle
+        )?;
+        Ok(())
+    }
+}
+
+/// Range of cycles over which the roll count of an address is requested
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+pub struct RollHistoryInput {
+    /// address whose roll count is requested
+    pub address: Address,
+    /// first cycle of the range
+    pub from_cycle: u64,
+    /// last cycle of the range, included
+    pub to_cycle: u64,
+}
+
+/// Final roll count of an address at a cycle
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+pub struct CycleRollCount {
+    /// cycle
+    pub cycle: u64,
+    /// final roll count of the address at the end of the cycle, or at the latest final slot for the ongoing cycle
+    pub roll_count: u64,
+}
+
+/// Roll counts of an address over the requested cycles kept in the cycle history, oldest first
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RollHistory {
+    /// address
+    pub address: Address,
+    /// roll count at each cycle of the requested range that is still in the cycle history
+    pub roll_counts: Vec<CycleRollCount>,
+}
+
+impl std::fmt::Display for RollHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        for cycle_roll_count in &self.roll_counts {
+            writeln!(
+                f,
+                "\tCycle {}: {} rolls",
+                cycle_roll_count.cycle, cycle_roll_count.roll_count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Expected number of draws per cycle of an address holding `rolls` out of `total_rolls`,
+/// among `draws_per_cycle` independent draws
+///
+/// ```
+/// # use massa_api_exports::rolls::expected_draws_per_cycle;
+/// assert_eq!(expected_draws_per_cycle(10, 40, 128 * 32), 1024.0);
+/// assert_eq!(expected_draws_per_cycle(10, 0, 128 * 32), 0.0);
+/// ```
+pub fn expected_draws_per_cycle(rolls: u64, total_rolls: u64, draws_per_cycle: u64) -> f64 {
+    if total_rolls == 0 {
+        return 0.0;
+    }
+    rolls as f64 / total_rolls as f64 * draws_per_cycle as f64
+}
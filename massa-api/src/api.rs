@@ -14,7 +14,7 @@ use massa_api_exports::error::ApiError;
 use massa_api_exports::page::{PageRequest, PagedVec, PagedVecV2};
 use massa_api_exports::ApiRequest;
 use massa_consensus_exports::{ConsensusChannels, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{ExecutionChannels, ExecutionController};
 use massa_models::address::Address;
 use massa_models::block_id::BlockId;
 use massa_models::slot::Slot;
@@ -31,6 +31,7 @@ impl API<ApiV2> {
         consensus_controller: Box<dyn ConsensusController>,
         consensus_channels: ConsensusChannels,
         execution_controller: Box<dyn ExecutionController>,
+        execution_channels: ExecutionChannels,
         pool_channels: PoolChannels,
         api_settings: APIConfig,
         version: Version,
@@ -39,6 +40,7 @@ impl API<ApiV2> {
             consensus_controller,
             consensus_channels,
             execution_controller,
+            execution_channels,
             pool_channels,
             api_settings,
             version,
@@ -151,6 +153,10 @@ impl MassaApiServer for API<ApiV2> {
     ) -> SubscriptionResult {
         broadcast_via_ws(self.0.pool_channels.operation_sender.clone(), pending).await
     }
+
+    async fn subscribe_pos_events(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+        broadcast_via_ws(self.0.execution_channels.pos_event_sender.clone(), pending).await
+    }
 }
 
 // Brodcast the stream(sender) content via a WebSocket
@@ -57,4 +57,12 @@ pub trait MassaApi {
 		item = Operation
 	)]
     async fn subscribe_new_operations(&self) -> SubscriptionResult;
+
+    /// Proof-of-stake events, such as cycle completions.
+    #[subscription(
+		name = "subscribe_pos_events" => "pos_events",
+		unsubscribe = "unsubscribe_pos_events",
+		item = PoSEvent
+	)]
+    async fn subscribe_pos_events(&self) -> SubscriptionResult;
 }
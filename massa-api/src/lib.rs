@@ -13,19 +13,27 @@ use jsonrpsee::server::{BatchRequestConfig, ServerBuilder, ServerHandle};
 use jsonrpsee::RpcModule;
 use massa_api_exports::{
     address::AddressInfo,
-    block::{BlockInfo, BlockSummary},
+    block::{BlockBatchInfo, BlockInfo, BlockSummary, BlocksBatchRequest},
     config::APIConfig,
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
-    endorsement::EndorsementInfo,
+    datastore::{
+        DatastoreEntryInput, DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput,
+        StateViewInfo,
+    },
+    endorsement::{EndorsementInfo, EndorsementsByCreatorFilter},
     error::ApiError::WrongAPI,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    execution::{
+        ExecuteReadOnlyResponse, OffChainTask, OffChainTaskInfo, ReadOnlyBytecodeExecution,
+        ReadOnlyCall,
+    },
+    ledger::LedgerProofOutput,
+    node::{NodeBandwidthInfo, NodeStatus, NodeTrafficInfo, ProductionReport, ProtocolTraceEvent},
+    operation::{OperationInfo, OperationInput, OperationValidityCheck, OperationValidityInput},
     page::{PageRequest, PagedVec},
+    rolls::{RollHistory, RollHistoryInput, RollOperationPreview, RollOperationPreviewInput},
     TimeInterval,
 };
 use massa_consensus_exports::{ConsensusChannels, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{ExecutionChannels, ExecutionController};
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
 use massa_models::node::NodeId;
@@ -45,6 +53,7 @@ use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use serde_json::Value;
 use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
 use std::sync::{Arc, Condvar, Mutex};
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, warn};
@@ -78,6 +87,8 @@ pub struct Public {
     pub node_id: NodeId,
     /// keypair factory
     pub keypair_factory: KeyPairFactory,
+    /// User wallet, used to report the production schedule of the staking addresses
+    pub node_wallet: Arc<RwLock<Wallet>>,
 }
 
 /// Private API content
@@ -103,6 +114,8 @@ pub struct ApiV2 {
     pub consensus_channels: ConsensusChannels,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
+    /// link(channels) to the execution component
+    pub execution_channels: ExecutionChannels,
     /// link(channels) to the pool component
     pub pool_channels: PoolChannels,
     /// API settings
@@ -136,11 +149,8 @@ pub trait ApiServer: MassaApiServer {
     ) -> Result<StopHandle, JsonRpseeError>;
 }
 
-async fn serve<T>(
-    api: RpcModule<T>,
-    url: &SocketAddr,
-    api_config: &APIConfig,
-) -> Result<StopHandle, JsonRpseeError> {
+/// Server builder with the limits and transports of the API configuration
+fn server_builder(api_config: &APIConfig) -> ServerBuilder {
     let mut server_builder = ServerBuilder::new()
         .max_request_body_size(api_config.max_request_body_size)
         .max_response_body_size(api_config.max_response_body_size)
@@ -159,6 +169,15 @@ async fn serve<T>(
     } else if !api_config.enable_http && !api_config.enable_ws {
         panic!("wrong server configuration, you can't disable both http and ws");
     }
+    server_builder
+}
+
+async fn serve<T>(
+    api: RpcModule<T>,
+    url: &SocketAddr,
+    api_config: &APIConfig,
+) -> Result<StopHandle, JsonRpseeError> {
+    let server_builder = server_builder(api_config);
 
     let cors = CorsLayer::new()
         // Allow `POST` and `OPTIONS` when accessing the resource
@@ -195,6 +214,90 @@ async fn serve<T>(
     Ok(stop_handler)
 }
 
+/// Serves the API on a Unix domain socket instead of a TCP port.
+/// Access is controlled by the permissions of the socket file, so host filtering and CORS do not apply.
+#[cfg(unix)]
+async fn serve_unix<T: Send + Sync + 'static>(
+    api: RpcModule<T>,
+    path: &Path,
+    api_config: &APIConfig,
+) -> Result<StopHandle, JsonRpseeError> {
+    use jsonrpsee::server::stop_channel;
+    use jsonrpsee::Methods;
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::UnixListener;
+    use tracing::debug;
+
+    let socket_error = |err: std::io::Error| {
+        JsonRpseeError::Custom(format!("API socket {}: {}", path.display(), err))
+    };
+
+    // a socket file left by a previous run prevents binding
+    if path.exists() {
+        std::fs::remove_file(path).map_err(socket_error)?;
+    }
+    let listener = UnixListener::bind(path).map_err(socket_error)?;
+    std::fs::set_permissions(
+        path,
+        std::fs::Permissions::from_mode(api_config.unix_socket_permissions),
+    )
+    .map_err(socket_error)?;
+
+    let service_builder = server_builder(api_config).to_service_builder();
+    let methods: Methods = api.into();
+    let (stop_handle, server_handler) = stop_channel();
+    let path = path.to_path_buf();
+    tokio::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(err) => {
+                        warn!(
+                            "API socket {}: could not accept connection: {}",
+                            path.display(),
+                            err
+                        );
+                        continue;
+                    }
+                },
+                _ = stop_handle.clone().shutdown() => break,
+            };
+            let service = service_builder
+                .clone()
+                .build(methods.clone(), stop_handle.clone());
+            let connection_stop_handle = stop_handle.clone();
+            tokio::spawn(async move {
+                let connection = hyper::server::conn::Http::new()
+                    .serve_connection(stream, service)
+                    .with_upgrades();
+                tokio::select! {
+                    res = connection => {
+                        if let Err(err) = res {
+                            debug!("API socket connection error: {}", err);
+                        }
+                    },
+                    _ = connection_stop_handle.shutdown() => {},
+                }
+            });
+        }
+        let _ = std::fs::remove_file(&path);
+    });
+
+    Ok(StopHandle { server_handler })
+}
+
+#[cfg(not(unix))]
+async fn serve_unix<T: Send + Sync + 'static>(
+    _api: RpcModule<T>,
+    _path: &Path,
+    _api_config: &APIConfig,
+) -> Result<StopHandle, JsonRpseeError> {
+    Err(JsonRpseeError::Custom(
+        "Unix domain sockets are not supported on this platform".to_string(),
+    ))
+}
+
 /// Used to be able to stop the API
 pub struct StopHandle {
     server_handler: ServerHandle,
@@ -211,6 +314,11 @@ impl StopHandle {
         }
         self.server_handler.stopped().await;
     }
+
+    /// returns true if the API server stopped
+    pub fn is_stopped(&self) -> bool {
+        self.server_handler.is_stopped()
+    }
 }
 
 /// Exposed API methods
@@ -253,6 +361,20 @@ pub trait MassaRpc {
     #[method(name = "get_staking_addresses")]
     async fn get_staking_addresses(&self) -> RpcResult<PreHashSet<Address>>;
 
+    /// Add addresses staking on other machines, tracked without their keys.
+    /// They are not persisted across restarts.
+    #[method(name = "add_watch_only_addresses")]
+    async fn add_watch_only_addresses(&self, arg: Vec<Address>) -> RpcResult<()>;
+
+    /// Stop tracking watch-only addresses.
+    /// No confirmation to expect.
+    #[method(name = "remove_watch_only_addresses")]
+    async fn remove_watch_only_addresses(&self, arg: Vec<Address>) -> RpcResult<()>;
+
+    /// Return hash set of watch-only addresses.
+    #[method(name = "get_watch_only_addresses")]
+    async fn get_watch_only_addresses(&self) -> RpcResult<PreHashSet<Address>>;
+
     /// Bans given IP address(es).
     /// No confirmation to expect.
     #[method(name = "node_ban_by_ip")]
@@ -318,6 +440,32 @@ pub trait MassaRpc {
     #[method(name = "node_unban_by_id")]
     async fn node_unban_by_id(&self, arg: Vec<NodeId>) -> RpcResult<()>;
 
+    /// Registers a node-local task run when final events or datastore changes match its trigger.
+    /// Returns the id of the task.
+    #[method(name = "node_add_offchain_task")]
+    async fn node_add_offchain_task(&self, arg: OffChainTask) -> RpcResult<u64>;
+
+    /// Unregisters node-local tasks by id.
+    /// No confirmation to expect.
+    #[method(name = "node_remove_offchain_tasks")]
+    async fn node_remove_offchain_tasks(&self, arg: Vec<u64>) -> RpcResult<()>;
+
+    /// Returns the registered node-local tasks.
+    #[method(name = "node_get_offchain_tasks")]
+    async fn node_get_offchain_tasks(&self) -> RpcResult<Vec<OffChainTaskInfo>>;
+
+    /// Returns the bytes exchanged with each connected node, per message type.
+    #[method(name = "node_get_peers_bandwidth")]
+    async fn node_get_peers_bandwidth(&self) -> RpcResult<Vec<NodeBandwidthInfo>>;
+
+    /// Returns the messages exchanged with all the nodes since the node started, per message type and direction.
+    #[method(name = "node_get_protocol_traffic")]
+    async fn node_get_protocol_traffic(&self) -> RpcResult<NodeTrafficInfo>;
+
+    /// Returns the latest traced commands and events of the protocol worker, with their queue timestamps.
+    #[method(name = "node_get_protocol_trace")]
+    async fn node_get_protocol_trace(&self) -> RpcResult<Vec<ProtocolTraceEvent>>;
+
     /// Summary of the current state: time, last final blocks (hash, thread, slot, timestamp), clique count, connected nodes count.
     #[method(name = "get_status")]
     async fn get_status(&self) -> RpcResult<NodeStatus>;
@@ -341,10 +489,31 @@ pub trait MassaRpc {
     #[method(name = "get_endorsements")]
     async fn get_endorsements(&self, arg: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>>;
 
+    /// Returns information about the endorsements endorsing a given list of block(s) ID(s)
+    #[method(name = "get_endorsements_by_endorsed_block")]
+    async fn get_endorsements_by_endorsed_block(
+        &self,
+        arg: Vec<BlockId>,
+    ) -> RpcResult<Vec<EndorsementInfo>>;
+
+    /// Returns information about the endorsements created by an address, optionally filtered by:
+    /// * start slot
+    /// * end slot
+    /// * finality
+    #[method(name = "get_endorsements_by_creator")]
+    async fn get_endorsements_by_creator(
+        &self,
+        arg: EndorsementsByCreatorFilter,
+    ) -> RpcResult<Vec<EndorsementInfo>>;
+
     /// Returns block(s) information associated to a given list of block(s) ID(s)
     #[method(name = "get_blocks")]
     async fn get_blocks(&self, arg: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>>;
 
+    /// Returns blocks in the order of the requested IDs, optionally with their operations and without their endorsements.
+    #[method(name = "get_blocks_batch")]
+    async fn get_blocks_batch(&self, arg: BlocksBatchRequest) -> RpcResult<Vec<BlockBatchInfo>>;
+
     /// Get information on the block at a slot in the blockclique.
     /// If there is no block at this slot a `None` is returned.
     #[method(name = "get_blockclique_block_by_slot")]
@@ -355,17 +524,70 @@ pub trait MassaRpc {
     #[method(name = "get_graph_interval")]
     async fn get_graph_interval(&self, arg: TimeInterval) -> RpcResult<Vec<BlockSummary>>;
 
-    /// Get multiple datastore entries.
+    /// Opens a view pinning the current final and candidate datastores,
+    /// so that the datastore queries referencing it see the same state until it expires.
+    /// Private API only, as each view copies the candidate state and makes each final slot record its reverse changes.
+    #[method(name = "open_state_view")]
+    async fn open_state_view(&self) -> RpcResult<StateViewInfo>;
+
+    /// Closes a state view before it expires.
+    /// No confirmation to expect.
+    #[method(name = "close_state_view")]
+    async fn close_state_view(&self, arg: u64) -> RpcResult<()>;
+
+    /// Get multiple datastore entries, optionally in a state view.
     #[method(name = "get_datastore_entries")]
     async fn get_datastore_entries(
         &self,
         arg: Vec<DatastoreEntryInput>,
+        view_id: Option<u64>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>>;
 
+    /// List the keys of datastores, filtered by prefix and paginated, optionally in a state view.
+    #[method(name = "get_datastore_keys")]
+    async fn get_datastore_keys(
+        &self,
+        arg: Vec<DatastoreKeysInput>,
+        view_id: Option<u64>,
+    ) -> RpcResult<Vec<DatastoreKeysOutput>>;
+
+    /// Get a Merkle proof of the final balance of an address, or of one of its final datastore entries if a key is given,
+    /// against the commitment over the final ledger.
+    #[method(name = "get_ledger_proof")]
+    async fn get_ledger_proof(
+        &self,
+        address: Address,
+        key: Option<Vec<u8>>,
+    ) -> RpcResult<LedgerProofOutput>;
+
     /// Get addresses.
     #[method(name = "get_addresses")]
     async fn get_addresses(&self, arg: Vec<Address>) -> RpcResult<Vec<AddressInfo>>;
 
+    /// Returns the block production statistics of addresses over the final cycles kept by the node,
+    /// signed with the node's key.
+    #[method(name = "get_production_reports")]
+    async fn get_production_reports(&self, arg: Vec<Address>) -> RpcResult<Vec<ProductionReport>>;
+
+    /// Returns the final roll counts of addresses at each cycle of the requested ranges still kept in the cycle history.
+    #[method(name = "get_roll_history")]
+    async fn get_roll_history(&self, arg: Vec<RollHistoryInput>) -> RpcResult<Vec<RollHistory>>;
+
+    /// Simulates roll buys or sells and estimates the draws per cycle of the addresses at the resulting roll counts.
+    #[method(name = "get_roll_operation_previews")]
+    async fn get_roll_operation_previews(
+        &self,
+        arg: Vec<RollOperationPreviewInput>,
+    ) -> RpcResult<Vec<RollOperationPreview>>;
+
+    /// Checks the validity window of draft operations against the current slots and the pool policy,
+    /// and suggests an expiry period and a max gas under which they can be included.
+    #[method(name = "check_operation_validity")]
+    async fn check_operation_validity(
+        &self,
+        arg: Vec<OperationValidityInput>,
+    ) -> RpcResult<Vec<OperationValidityCheck>>;
+
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
     #[method(name = "send_operations")]
     async fn send_operations(&self, arg: Vec<OperationInput>) -> RpcResult<Vec<OperationId>>;
@@ -6,15 +6,23 @@ use async_trait::async_trait;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
     address::AddressInfo,
-    block::{BlockInfo, BlockSummary},
+    block::{BlockBatchInfo, BlockInfo, BlockSummary, BlocksBatchRequest},
     config::APIConfig,
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
-    endorsement::EndorsementInfo,
+    datastore::{
+        DatastoreEntryInput, DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput,
+        StateViewInfo,
+    },
+    endorsement::{EndorsementInfo, EndorsementsByCreatorFilter},
     error::ApiError,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    execution::{
+        ExecuteReadOnlyResponse, OffChainTask, OffChainTaskInfo, ReadOnlyBytecodeExecution,
+        ReadOnlyCall,
+    },
+    ledger::LedgerProofOutput,
+    node::{NodeBandwidthInfo, NodeStatus, NodeTrafficInfo, ProductionReport, ProtocolTraceEvent},
+    operation::{OperationInfo, OperationInput, OperationValidityCheck, OperationValidityInput},
     page::{PageRequest, PagedVec},
+    rolls::{RollHistory, RollHistoryInput, RollOperationPreview, RollOperationPreviewInput},
     ListType, ScrudOperation, TimeInterval,
 };
 use massa_execution_exports::ExecutionController;
@@ -29,7 +37,7 @@ use massa_signature::KeyPair;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::net::{IpAddr, SocketAddr};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::{collections::BTreeSet, sync::Mutex};
@@ -55,6 +63,16 @@ impl API<Private> {
             node_wallet,
         })
     }
+
+    /// Start the private API on a Unix domain socket, with the permissions `unix_socket_permissions`,
+    /// instead of a TCP port
+    pub async fn serve_unix(
+        self,
+        path: &Path,
+        settings: &APIConfig,
+    ) -> Result<StopHandle, JsonRpseeError> {
+        crate::serve_unix(self.into_rpc(), path, settings).await
+    }
 }
 
 #[async_trait]
@@ -147,6 +165,26 @@ impl MassaRpcServer for API<Private> {
         Ok(w_wallet.get_wallet_address_list())
     }
 
+    async fn add_watch_only_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
+        self.0
+            .node_wallet
+            .write()
+            .add_watch_only_addresses(addresses);
+        Ok(())
+    }
+
+    async fn remove_watch_only_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
+        self.0
+            .node_wallet
+            .write()
+            .remove_watch_only_addresses(&addresses);
+        Ok(())
+    }
+
+    async fn get_watch_only_addresses(&self) -> RpcResult<PreHashSet<Address>> {
+        Ok(self.0.node_wallet.read().get_watch_only_address_list())
+    }
+
     async fn node_ban_by_ip(&self, _ips: Vec<IpAddr>) -> RpcResult<()> {
         //TODO: Reinvoke
         // let network_command_sender = self.0.network_command_sender.clone();
@@ -183,6 +221,60 @@ impl MassaRpcServer for API<Private> {
             .map_err(|e| ApiError::ProtocolError(e).into())
     }
 
+    async fn node_add_offchain_task(&self, task: OffChainTask) -> RpcResult<u64> {
+        self.0
+            .execution_controller
+            .add_offchain_task(task)
+            .map_err(|e| ApiError::ExecutionError(e).into())
+    }
+
+    async fn node_remove_offchain_tasks(&self, ids: Vec<u64>) -> RpcResult<()> {
+        self.0.execution_controller.remove_offchain_tasks(&ids);
+        Ok(())
+    }
+
+    async fn node_get_offchain_tasks(&self) -> RpcResult<Vec<OffChainTaskInfo>> {
+        Ok(self
+            .0
+            .execution_controller
+            .get_offchain_tasks()
+            .into_iter()
+            .map(|(id, task)| OffChainTaskInfo { id, task })
+            .collect())
+    }
+
+    async fn node_get_peers_bandwidth(&self) -> RpcResult<Vec<NodeBandwidthInfo>> {
+        let stats = self
+            .0
+            .protocol_controller
+            .get_peer_bandwidth_stats()
+            .map_err(ApiError::ProtocolError)?;
+        Ok(stats
+            .into_iter()
+            .map(|(peer_id, stats)| NodeBandwidthInfo {
+                //TODO: Change when unify node id and peer id
+                node_id: NodeId::new(peer_id.get_public_key()),
+                stats,
+            })
+            .collect())
+    }
+
+    async fn node_get_protocol_traffic(&self) -> RpcResult<NodeTrafficInfo> {
+        let stats = self
+            .0
+            .protocol_controller
+            .get_protocol_traffic_stats()
+            .map_err(ApiError::ProtocolError)?;
+        Ok(NodeTrafficInfo { stats })
+    }
+
+    async fn node_get_protocol_trace(&self) -> RpcResult<Vec<ProtocolTraceEvent>> {
+        self.0
+            .protocol_controller
+            .get_protocol_trace()
+            .map_err(|e| ApiError::ProtocolError(e).into())
+    }
+
     async fn node_unban_by_ip(&self, _ips: Vec<IpAddr>) -> RpcResult<()> {
         //TODO: Reinvoke
         // let network_command_sender = self.0.network_command_sender.clone();
@@ -215,10 +307,28 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<EndorsementInfo>>()
     }
 
+    async fn get_endorsements_by_endorsed_block(
+        &self,
+        _: Vec<BlockId>,
+    ) -> RpcResult<Vec<EndorsementInfo>> {
+        crate::wrong_api::<Vec<EndorsementInfo>>()
+    }
+
+    async fn get_endorsements_by_creator(
+        &self,
+        _: EndorsementsByCreatorFilter,
+    ) -> RpcResult<Vec<EndorsementInfo>> {
+        crate::wrong_api::<Vec<EndorsementInfo>>()
+    }
+
     async fn get_blocks(&self, _: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
         crate::wrong_api::<Vec<BlockInfo>>()
     }
 
+    async fn get_blocks_batch(&self, _: BlocksBatchRequest) -> RpcResult<Vec<BlockBatchInfo>> {
+        crate::wrong_api::<Vec<BlockBatchInfo>>()
+    }
+
     async fn get_blockclique_block_by_slot(&self, _: Slot) -> RpcResult<Option<Block>> {
         crate::wrong_api::<Option<Block>>()
     }
@@ -227,17 +337,75 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<BlockSummary>>()
     }
 
+    async fn open_state_view(&self) -> RpcResult<StateViewInfo> {
+        let view = self
+            .0
+            .execution_controller
+            .open_state_view()
+            .map_err(ApiError::ExecutionError)?;
+        Ok(StateViewInfo {
+            view_id: view.view_id,
+            expires_at: view.expires_at,
+            candidate_cursor: view.candidate_cursor,
+            final_cursor: view.final_cursor,
+        })
+    }
+
+    async fn close_state_view(&self, view_id: u64) -> RpcResult<()> {
+        self.0.execution_controller.close_state_view(view_id);
+        Ok(())
+    }
+
     async fn get_datastore_entries(
         &self,
         _: Vec<DatastoreEntryInput>,
+        _: Option<u64>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>> {
         crate::wrong_api()
     }
 
+    async fn get_datastore_keys(
+        &self,
+        _: Vec<DatastoreKeysInput>,
+        _: Option<u64>,
+    ) -> RpcResult<Vec<DatastoreKeysOutput>> {
+        crate::wrong_api()
+    }
+
+    async fn get_ledger_proof(
+        &self,
+        _: Address,
+        _: Option<Vec<u8>>,
+    ) -> RpcResult<LedgerProofOutput> {
+        crate::wrong_api::<LedgerProofOutput>()
+    }
+
     async fn get_addresses(&self, _: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         crate::wrong_api::<Vec<AddressInfo>>()
     }
 
+    async fn get_production_reports(&self, _: Vec<Address>) -> RpcResult<Vec<ProductionReport>> {
+        crate::wrong_api::<Vec<ProductionReport>>()
+    }
+
+    async fn get_roll_history(&self, _: Vec<RollHistoryInput>) -> RpcResult<Vec<RollHistory>> {
+        crate::wrong_api::<Vec<RollHistory>>()
+    }
+
+    async fn get_roll_operation_previews(
+        &self,
+        _: Vec<RollOperationPreviewInput>,
+    ) -> RpcResult<Vec<RollOperationPreview>> {
+        crate::wrong_api::<Vec<RollOperationPreview>>()
+    }
+
+    async fn check_operation_validity(
+        &self,
+        _: Vec<OperationValidityInput>,
+    ) -> RpcResult<Vec<OperationValidityCheck>> {
+        crate::wrong_api::<Vec<OperationValidityCheck>>()
+    }
+
     async fn send_operations(&self, _: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
         crate::wrong_api::<Vec<OperationId>>()
     }
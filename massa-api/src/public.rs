@@ -7,30 +7,50 @@ use itertools::{izip, Itertools};
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
     address::AddressInfo,
-    block::{BlockInfo, BlockInfoContent, BlockSummary},
+    block::{BlockBatchInfo, BlockInfo, BlockInfoContent, BlockSummary, BlocksBatchRequest},
     config::APIConfig,
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
-    endorsement::EndorsementInfo,
+    datastore::{
+        DatastoreEntryInput, DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput,
+        StateViewInfo,
+    },
+    endorsement::{EndorsementInfo, EndorsementsByCreatorFilter},
     error::ApiError,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyResult},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
-    page::{PageRequest, PagedVec},
+    execution::{
+        ExecuteReadOnlyResponse, OffChainTask, OffChainTaskInfo, ReadOnlyBytecodeExecution,
+        ReadOnlyCall, ReadOnlyResult,
+    },
+    ledger::LedgerProofOutput,
+    node::{
+        NodeBandwidthInfo, NodeStatus, NodeTrafficInfo, ProductionReport, ProductionResult,
+        ProtocolTraceEvent, StakingAddressStatus, StakingSummary,
+    },
+    operation::{
+        inclusion_periods, OperationInfo, OperationInput, OperationValidityCheck,
+        OperationValidityInput,
+    },
+    page::{PageRequest, PagedVec, PagedVecV2},
+    rolls::{
+        expected_draws_per_cycle, CycleRollCount, RollHistory, RollHistoryInput,
+        RollOperationPreview, RollOperationPreviewInput,
+    },
     slot::SlotAmount,
     TimeInterval,
 };
 use massa_consensus_exports::block_status::DiscardReason;
 use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::{
-    ExecutionController, ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionController, ExecutionQueryError, ExecutionQueryRequest, ExecutionQueryRequestItem,
+    ExecutionQueryResponseItem, ExecutionStackElement, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
 };
+use massa_hash::Hash;
 use massa_models::{
     address::Address,
     block::{Block, BlockGraphStatus},
     block_id::BlockId,
     clique::Clique,
     composite::PubkeySig,
-    config::CompactConfig,
+    config::{CompactConfig, ENDORSEMENT_COUNT, OPERATION_VALIDITY_PERIODS, POS_LOOKBACK_CYCLES},
     datastore::DatastoreDeserializer,
     endorsement::EndorsementId,
     endorsement::SecureShareEndorsement,
@@ -58,8 +78,11 @@ use massa_versioning::versioning_factory::FactoryStrategy;
 use massa_versioning::{
     keypair_factory::KeyPairFactory, versioning::MipStore, versioning_factory::VersioningFactory,
 };
+use massa_wallet::Wallet;
+use parking_lot::RwLock;
 use std::collections::BTreeMap;
 use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
 
 impl API<Public> {
     /// generate a new public API
@@ -75,6 +98,7 @@ impl API<Public> {
         node_id: NodeId,
         storage: Storage,
         mip_store: MipStore,
+        node_wallet: Arc<RwLock<Wallet>>,
     ) -> Self {
         API(Public {
             consensus_controller,
@@ -88,6 +112,118 @@ impl API<Public> {
             protocol_config,
             storage,
             keypair_factory: KeyPairFactory { mip_store },
+            node_wallet,
+        })
+    }
+
+    /// Combines the draws of the staking addresses of the node, within the draw lookahead before and after `last_slot`,
+    /// with their production results
+    fn get_staking_summary(
+        &self,
+        now: MassaTime,
+        last_slot: Option<Slot>,
+        current_cycle: u64,
+        current_cycle_progress: f64,
+    ) -> Result<StakingSummary, ApiError> {
+        let api_settings = &self.0.api_settings;
+        let addresses: Vec<Address> = self
+            .0
+            .node_wallet
+            .read()
+            .get_tracked_address_list()
+            .into_iter()
+            .sorted()
+            .collect();
+        let last_slot = last_slot.unwrap_or_else(|| Slot::new(0, 0));
+
+        let selections = self
+            .0
+            .selector_controller
+            .get_available_selections_in_range(
+                Slot::new(
+                    last_slot
+                        .period
+                        .saturating_sub(api_settings.draw_lookahead_period_count),
+                    last_slot.thread,
+                )
+                    ..=Slot::new(
+                        last_slot
+                            .period
+                            .saturating_add(api_settings.draw_lookahead_period_count),
+                        last_slot.thread,
+                    ),
+                Some(&addresses.iter().copied().collect()),
+            )
+            .unwrap_or_default();
+        let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
+        let time_until = |slot: Slot| -> Result<MassaTime, ApiError> {
+            let timestamp = timeslots::get_block_slot_timestamp(
+                api_settings.thread_count,
+                api_settings.t0,
+                api_settings.genesis_timestamp,
+                slot,
+            )?;
+            Ok(timestamp.saturating_sub(now))
+        };
+
+        let mut statuses = Vec::with_capacity(addresses.len());
+        for (address, execution_info) in addresses.into_iter().zip(execution_infos) {
+            let next_block_draw = selections
+                .iter()
+                .find(|(slot, selection)| **slot > last_slot && selection.producer == address)
+                .map(|(slot, _)| *slot);
+            let next_endorsement_draw = selections
+                .iter()
+                .filter(|(slot, _)| **slot > last_slot)
+                .find_map(|(slot, selection)| {
+                    selection
+                        .endorsements
+                        .iter()
+                        .position(|endorser| *endorser == address)
+                        .map(|index| IndexedSlot { slot: *slot, index })
+                });
+            // the block of the last slot may not have been received yet
+            let last_production = selections
+                .iter()
+                .rev()
+                .find(|(slot, selection)| **slot < last_slot && selection.producer == address)
+                .map(|(slot, _)| ProductionResult {
+                    slot: *slot,
+                    produced: self
+                        .0
+                        .consensus_controller
+                        .get_blockclique_block_at_slot(*slot)
+                        .is_some(),
+                });
+            let current_cycle_info = execution_info
+                .cycle_infos
+                .iter()
+                .find(|cycle_info| cycle_info.cycle == current_cycle);
+            let (current_cycle_ok_count, current_cycle_nok_count) = current_cycle_info
+                .map_or((0, 0), |cycle_info| {
+                    (cycle_info.ok_count, cycle_info.nok_count)
+                });
+            let current_cycle_misses_before_roll_sale =
+                current_cycle_info.and_then(|cycle_info| cycle_info.misses_before_roll_sale);
+            statuses.push(StakingAddressStatus {
+                address,
+                next_block_draw,
+                next_block_draw_in: next_block_draw.map(time_until).transpose()?,
+                next_endorsement_draw_in: next_endorsement_draw
+                    .as_ref()
+                    .map(|indexed_slot| time_until(indexed_slot.slot))
+                    .transpose()?,
+                next_endorsement_draw,
+                last_production,
+                current_cycle_ok_count,
+                current_cycle_nok_count,
+                current_cycle_misses_before_roll_sale,
+            });
+        }
+
+        Ok(StakingSummary {
+            current_cycle_progress,
+            addresses: statuses,
         })
     }
 }
@@ -185,6 +321,7 @@ impl MassaRpcServer for API<Public> {
                     owned_addresses: vec![address],
                     operation_datastore: op_datastore,
                 }],
+                coins: None,
                 is_final,
             };
 
@@ -229,6 +366,7 @@ impl MassaRpcServer for API<Public> {
             target_function,
             parameter,
             caller_address,
+            coins,
             is_final,
         } in reqs
         {
@@ -273,6 +411,7 @@ impl MassaRpcServer for API<Public> {
                         operation_datastore: None, // should always be None
                     },
                 ],
+                coins,
                 is_final,
             };
 
@@ -310,6 +449,18 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<PreHashSet<Address>>()
     }
 
+    async fn add_watch_only_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn remove_watch_only_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn get_watch_only_addresses(&self) -> RpcResult<PreHashSet<Address>> {
+        crate::wrong_api::<PreHashSet<Address>>()
+    }
+
     async fn node_ban_by_ip(&self, _: Vec<IpAddr>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -326,6 +477,30 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    async fn node_add_offchain_task(&self, _: OffChainTask) -> RpcResult<u64> {
+        crate::wrong_api::<u64>()
+    }
+
+    async fn node_remove_offchain_tasks(&self, _: Vec<u64>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_get_offchain_tasks(&self) -> RpcResult<Vec<OffChainTaskInfo>> {
+        crate::wrong_api::<Vec<OffChainTaskInfo>>()
+    }
+
+    async fn node_get_peers_bandwidth(&self) -> RpcResult<Vec<NodeBandwidthInfo>> {
+        crate::wrong_api::<Vec<NodeBandwidthInfo>>()
+    }
+
+    async fn node_get_protocol_traffic(&self) -> RpcResult<NodeTrafficInfo> {
+        crate::wrong_api::<NodeTrafficInfo>()
+    }
+
+    async fn node_get_protocol_trace(&self) -> RpcResult<Vec<ProtocolTraceEvent>> {
+        crate::wrong_api::<Vec<ProtocolTraceEvent>>()
+    }
+
     async fn get_status(&self) -> RpcResult<NodeStatus> {
         let execution_controller = self.0.execution_controller.clone();
         let consensus_controller = self.0.consensus_controller.clone();
@@ -358,6 +533,10 @@ impl MassaRpcServer for API<Public> {
             Ok(consensus_stats) => consensus_stats,
             Err(e) => return Err(ApiError::ConsensusError(e).into()),
         };
+        let finality_stats = match consensus_controller.get_finality_stats() {
+            Ok(finality_stats) => finality_stats,
+            Err(e) => return Err(ApiError::ConsensusError(e).into()),
+        };
 
         let (network_stats, peers) = match protocol_controller.get_stats() {
             Ok((stats, peers)) => (stats, peers),
@@ -420,6 +599,18 @@ impl MassaRpcServer for API<Public> {
             Err(e) => return Err(ApiError::TimeError(e).into()),
         };
 
+        let staking_summary = if api_settings.status_staking_summary {
+            let current_cycle_progress = now.saturating_sub(current_cycle_time).to_millis() as f64
+                * 100.0
+                / cycle_duration.to_millis().max(1) as f64;
+            match self.get_staking_summary(now, last_slot, current_cycle, current_cycle_progress) {
+                Ok(staking_summary) => Some(staking_summary),
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            None
+        };
+
         Ok(NodeStatus {
             node_id,
             node_ip: protocol_config.routable_ip,
@@ -432,10 +623,12 @@ impl MassaRpcServer for API<Public> {
             next_slot,
             execution_stats,
             consensus_stats,
+            finality_stats,
             network_stats,
             pool_stats,
             config,
             current_cycle,
+            staking_summary,
         })
     }
 
@@ -641,6 +834,71 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    async fn get_endorsements_by_endorsed_block(
+        &self,
+        block_ids: Vec<BlockId>,
+    ) -> RpcResult<Vec<EndorsementInfo>> {
+        let max_arguments = self.0.api_settings.max_arguments;
+        if block_ids.len() as u64 > max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        // get the endorsements of the blocks from the storage index, ordered by slot and index
+        let eds: Vec<EndorsementId> = {
+            let read_endos = self.0.storage.read_endorsements();
+            block_ids
+                .iter()
+                .filter_map(|block_id| read_endos.get_endorsements_by_endorsed_block(block_id))
+                .flatten()
+                .filter_map(|id| read_endos.get(id))
+                .sorted_by_key(|ed| (ed.content.slot, ed.content.index))
+                .map(|ed| ed.id)
+                .collect()
+        };
+        if eds.len() as u64 > max_arguments {
+            return Err(ApiError::BadRequest(
+                "too many endorsements found, request less blocks".into(),
+            )
+            .into());
+        }
+
+        self.get_endorsements(eds).await
+    }
+
+    async fn get_endorsements_by_creator(
+        &self,
+        filter: EndorsementsByCreatorFilter,
+    ) -> RpcResult<Vec<EndorsementInfo>> {
+        // get the endorsements of the creator in the slot range from the storage index, ordered by slot and index
+        let eds: Vec<EndorsementId> = {
+            let read_endos = self.0.storage.read_endorsements();
+            read_endos
+                .get_endorsements_created_by(&filter.creator_address)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| read_endos.get(id))
+                .filter(|ed| {
+                    filter.start.map_or(true, |start| ed.content.slot >= start)
+                        && filter.end.map_or(true, |end| ed.content.slot < end)
+                })
+                .sorted_by_key(|ed| (ed.content.slot, ed.content.index))
+                .map(|ed| ed.id)
+                .collect()
+        };
+        if eds.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest(
+                "too many endorsements found, narrow the slot range".into(),
+            )
+            .into());
+        }
+
+        let mut res = self.get_endorsements(eds).await?;
+        if let Some(is_final) = filter.is_final {
+            res.retain(|info| info.is_final == is_final);
+        }
+        Ok(res)
+    }
+
     /// gets a block(s). Returns nothing if not found
     /// only active blocks are returned
     async fn get_blocks(&self, ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
@@ -664,6 +922,11 @@ impl MassaRpcServer for API<Public> {
                     let is_candidate = graph_status == BlockGraphStatus::ActiveInBlockclique
                         || graph_status == BlockGraphStatus::ActiveInAlternativeCliques;
                     let is_discarded = graph_status == BlockGraphStatus::Discarded;
+                    let fitness = consensus_controller
+                        .get_block_fitness_infos(&[id])
+                        .into_iter()
+                        .next()
+                        .flatten();
 
                     return Some(BlockInfo {
                         id,
@@ -672,6 +935,7 @@ impl MassaRpcServer for API<Public> {
                             is_in_blockclique,
                             is_candidate,
                             is_discarded,
+                            fitness,
                             block: content,
                         }),
                     });
@@ -684,6 +948,72 @@ impl MassaRpcServer for API<Public> {
         Ok(blocks)
     }
 
+    async fn get_blocks_batch(&self, req: BlocksBatchRequest) -> RpcResult<Vec<BlockBatchInfo>> {
+        let BlocksBatchRequest {
+            ids,
+            include_operations,
+            include_endorsements,
+        } = req;
+        if ids.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        // query the graph status and fitness of all the blocks at once
+        let graph_statuses = self.0.consensus_controller.get_block_statuses(&ids);
+        let fitnesses = self.0.consensus_controller.get_block_fitness_infos(&ids);
+
+        // then read the blocks and their operations with a single lock of each storage index
+        let blocks: Vec<Option<Block>> = {
+            let read_blocks = self.0.storage.read_blocks();
+            ids.iter()
+                .map(|id| read_blocks.get(id).map(|block| block.content.clone()))
+                .collect()
+        };
+        let operations: Vec<Option<Vec<SecureShareOperation>>> = if include_operations {
+            let read_operations = self.0.storage.read_operations();
+            blocks
+                .iter()
+                .map(|block| {
+                    block.as_ref().map(|block| {
+                        block
+                            .operations
+                            .iter()
+                            .filter_map(|op_id| read_operations.get(op_id).cloned())
+                            .collect()
+                    })
+                })
+                .collect()
+        } else {
+            vec![None; blocks.len()]
+        };
+
+        let blocks = izip!(ids, graph_statuses, fitnesses, blocks, operations)
+            .map(
+                |(id, graph_status, fitness, block, operations)| BlockBatchInfo {
+                    id,
+                    content: block.map(|mut block| {
+                        if !include_endorsements {
+                            block.header.content.endorsements.clear();
+                        }
+                        BlockInfoContent {
+                            is_final: graph_status == BlockGraphStatus::Final,
+                            is_in_blockclique: graph_status
+                                == BlockGraphStatus::ActiveInBlockclique,
+                            is_candidate: graph_status == BlockGraphStatus::ActiveInBlockclique
+                                || graph_status == BlockGraphStatus::ActiveInAlternativeCliques,
+                            is_discarded: graph_status == BlockGraphStatus::Discarded,
+                            fitness,
+                            block,
+                        }
+                    }),
+                    operations,
+                },
+            )
+            .collect();
+
+        Ok(blocks)
+    }
+
     async fn get_blockclique_block_by_slot(&self, slot: Slot) -> RpcResult<Option<Block>> {
         let consensus_controller = self.0.consensus_controller.clone();
         let block_id_option = consensus_controller.get_blockclique_block_at_slot(slot);
@@ -760,10 +1090,61 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    async fn open_state_view(&self) -> RpcResult<StateViewInfo> {
+        crate::wrong_api::<StateViewInfo>()
+    }
+
+    async fn close_state_view(&self, _: u64) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
     async fn get_datastore_entries(
         &self,
         entries: Vec<DatastoreEntryInput>,
+        view_id: Option<u64>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>> {
+        if let Some(view_id) = view_id {
+            // query the final and candidate values of each entry in the view
+            let requests = entries
+                .iter()
+                .flat_map(|input| {
+                    let addr = input.address;
+                    let key = input.key.clone();
+                    [
+                        ExecutionQueryRequestItem::AddressDatastoreValueFinal {
+                            addr,
+                            key: key.clone(),
+                        },
+                        ExecutionQueryRequestItem::AddressDatastoreValueCandidate { addr, key },
+                    ]
+                })
+                .collect();
+            let response = self
+                .0
+                .execution_controller
+                .query_state(ExecutionQueryRequest {
+                    requests,
+                    view_id: Some(view_id),
+                });
+            let values = response
+                .responses
+                .into_iter()
+                .map(|response| match response {
+                    Ok(ExecutionQueryResponseItem::DatastoreValue(value)) => Ok(Some(value)),
+                    Err(ExecutionQueryError::NotFound(_)) | Ok(_) => Ok(None),
+                    Err(err) => Err(ApiError::BadRequest(err.to_string())),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(values
+                .into_iter()
+                .tuples()
+                .map(|(final_value, candidate_value)| DatastoreEntryOutput {
+                    final_value,
+                    candidate_value,
+                })
+                .collect());
+        }
+
         let execution_controller = self.0.execution_controller.clone();
         Ok(execution_controller
             .get_final_and_active_data_entry(
@@ -780,6 +1161,79 @@ impl MassaRpcServer for API<Public> {
             .collect())
     }
 
+    async fn get_datastore_keys(
+        &self,
+        entries: Vec<DatastoreKeysInput>,
+        view_id: Option<u64>,
+    ) -> RpcResult<Vec<DatastoreKeysOutput>> {
+        if entries.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        // query all the datastores at once so that they are listed at the same slot
+        let requests = entries
+            .iter()
+            .map(|input| {
+                let addr = input.address;
+                let prefix = input.prefix.clone();
+                if input.is_final {
+                    ExecutionQueryRequestItem::AddressDatastoreKeysFinal { addr, prefix }
+                } else {
+                    ExecutionQueryRequestItem::AddressDatastoreKeysCandidate { addr, prefix }
+                }
+            })
+            .collect();
+        let response = self
+            .0
+            .execution_controller
+            .query_state(ExecutionQueryRequest { requests, view_id });
+
+        Ok(entries
+            .into_iter()
+            .zip(response.responses)
+            .map(|(input, response)| {
+                if let Err(err @ ExecutionQueryError::StateViewNotFound(_)) = response {
+                    return Err(ApiError::BadRequest(err.to_string()));
+                }
+                // the only other possible error is an address not found
+                let keys = match response {
+                    Ok(ExecutionQueryResponseItem::KeyList(keys)) => Some(PagedVecV2::from(
+                        PagedVec::new(keys.into_iter().collect(), input.page_request),
+                    )),
+                    _ => None,
+                };
+                Ok(DatastoreKeysOutput {
+                    address: input.address,
+                    keys,
+                })
+            })
+            .collect::<Result<_, ApiError>>()?)
+    }
+
+    async fn get_ledger_proof(
+        &self,
+        address: Address,
+        key: Option<Vec<u8>>,
+    ) -> RpcResult<LedgerProofOutput> {
+        let Some((slot, root, proof)) = self
+            .0
+            .execution_controller
+            .get_ledger_proof(&address, key.as_deref())
+        else {
+            return Err(ApiError::BadRequest(
+                "the ledger commitment is disabled on this node".into(),
+            )
+            .into());
+        };
+        Ok(LedgerProofOutput {
+            address,
+            key,
+            slot,
+            root,
+            proof,
+        })
+    }
+
     async fn get_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         // get info from storage about which blocks the addresses have created
         let created_blocks: Vec<PreHashSet<BlockId>> = {
@@ -820,6 +1274,16 @@ impl MassaRpcServer for API<Public> {
                 .collect()
         };
 
+        // get the number of operations of each address pending in the pool
+        let pending_operation_counts: Vec<usize> = addresses
+            .iter()
+            .map(|address| {
+                self.0
+                    .pool_command_sender
+                    .get_sender_pending_operation_count(address)
+            })
+            .collect();
+
         // get execution info
         let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
 
@@ -877,6 +1341,7 @@ impl MassaRpcServer for API<Public> {
             created_blocks.into_iter(),
             created_operations.into_iter(),
             created_endorsements.into_iter(),
+            pending_operation_counts.into_iter(),
             execution_infos.into_iter(),
             selection_draws.into_iter(),
         );
@@ -885,6 +1350,7 @@ impl MassaRpcServer for API<Public> {
             created_blocks,
             created_operations,
             created_endorsements,
+            pending_operation_count,
             execution_infos,
             (next_block_draws, next_endorsement_draws),
         ) in iterator
@@ -926,6 +1392,9 @@ impl MassaRpcServer for API<Public> {
                 created_endorsements: created_endorsements.into_iter().collect::<Vec<_>>(),
                 created_operations: created_operations.into_iter().collect::<Vec<_>>(),
 
+                // pool info
+                pending_operation_count,
+
                 // cycle infos
                 cycle_infos: execution_infos.cycle_infos,
             });
@@ -934,6 +1403,247 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    async fn get_production_reports(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<ProductionReport>> {
+        let keypair = &self.0.api_settings.keypair;
+        if addresses.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
+        let mut reports = Vec::new();
+        for (address, execution_info) in addresses.into_iter().zip(execution_infos) {
+            // only the final cycles have definitive statistics
+            for cycle_info in execution_info
+                .cycle_infos
+                .into_iter()
+                .filter(|cycle_info| cycle_info.is_final)
+            {
+                let opportunities = cycle_info.ok_count.saturating_add(cycle_info.nok_count);
+                let content = ProductionReport::signed_content(
+                    &address,
+                    cycle_info.cycle,
+                    opportunities,
+                    cycle_info.ok_count,
+                );
+                let signature = keypair.sign(&Hash::compute_from(&content)).map_err(|e| {
+                    ApiError::InconsistencyError(format!("error signing report: {}", e))
+                })?;
+                reports.push(ProductionReport {
+                    address,
+                    cycle: cycle_info.cycle,
+                    opportunities,
+                    successes: cycle_info.ok_count,
+                    node_signature: PubkeySig {
+                        public_key: keypair.get_public_key(),
+                        signature,
+                    },
+                });
+            }
+        }
+        Ok(reports)
+    }
+
+    async fn get_roll_history(&self, inputs: Vec<RollHistoryInput>) -> RpcResult<Vec<RollHistory>> {
+        if inputs.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        Ok(inputs
+            .into_iter()
+            .map(|input| RollHistory {
+                address: input.address,
+                roll_counts: self
+                    .0
+                    .execution_controller
+                    .get_roll_history(&input.address, input.from_cycle, input.to_cycle)
+                    .into_iter()
+                    .map(|(cycle, roll_count)| CycleRollCount { cycle, roll_count })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    async fn get_roll_operation_previews(
+        &self,
+        inputs: Vec<RollOperationPreviewInput>,
+    ) -> RpcResult<Vec<RollOperationPreview>> {
+        let api_settings = &self.0.api_settings;
+        if inputs.len() as u64 > api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let addresses: Vec<Address> = inputs.iter().map(|input| input.address).collect();
+        let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
+        let mut previews = Vec::with_capacity(inputs.len());
+        for (input, execution_info) in inputs.into_iter().zip(execution_infos) {
+            let roll_count = input.roll_delta.unsigned_abs();
+            let operation = match input.roll_delta {
+                delta if delta > 0 => OperationType::RollBuy { roll_count },
+                delta if delta < 0 => OperationType::RollSell { roll_count },
+                _ => return Err(ApiError::BadRequest("roll delta must not be zero".into()).into()),
+            };
+
+            // dry-run the operation on top of the candidate state
+            let req = ReadOnlyExecutionRequest {
+                max_gas: 0,
+                target: ReadOnlyExecutionTarget::RollOperation(operation),
+                call_stack: vec![ExecutionStackElement {
+                    address: input.address,
+                    coins: Default::default(),
+                    owned_addresses: vec![input.address],
+                    operation_datastore: None,
+                }],
+                coins: None,
+                is_final: false,
+            };
+            let candidate_rolls = execution_info.candidate_roll_count;
+            let (executed_at, error, resulting_rolls) =
+                match self.0.execution_controller.execute_readonly_request(req) {
+                    Ok(output) => {
+                        let resulting_rolls = output
+                            .out
+                            .state_changes
+                            .pos_changes
+                            .roll_changes
+                            .get(&input.address)
+                            .copied()
+                            .unwrap_or(candidate_rolls);
+                        (output.out.slot, None, resulting_rolls)
+                    }
+                    Err(err) => (Slot::new(0, 0), Some(err.to_string()), candidate_rolls),
+                };
+
+            // the selector draws each slot proportionally to the active rolls of the cycle:
+            // estimate the draws with the current distribution, replacing the rolls of the address
+            let current_cycle = executed_at.get_cycle(api_settings.periods_per_cycle);
+            let active_rolls = self
+                .0
+                .execution_controller
+                .get_cycle_active_rolls(current_cycle);
+            let total_rolls = active_rolls
+                .values()
+                .sum::<u64>()
+                .saturating_sub(
+                    active_rolls
+                        .get(&input.address)
+                        .copied()
+                        .unwrap_or_default(),
+                )
+                .saturating_add(resulting_rolls);
+            let block_draws_per_cycle =
+                api_settings.periods_per_cycle * api_settings.thread_count as u64;
+            previews.push(RollOperationPreview {
+                address: input.address,
+                executed_at,
+                error,
+                candidate_rolls,
+                resulting_rolls,
+                effective_cycle: current_cycle + POS_LOOKBACK_CYCLES + 1,
+                total_rolls,
+                expected_block_draws_per_cycle: expected_draws_per_cycle(
+                    resulting_rolls,
+                    total_rolls,
+                    block_draws_per_cycle,
+                ),
+                expected_endorsement_draws_per_cycle: expected_draws_per_cycle(
+                    resulting_rolls,
+                    total_rolls,
+                    block_draws_per_cycle * ENDORSEMENT_COUNT as u64,
+                ),
+            });
+        }
+        Ok(previews)
+    }
+
+    async fn check_operation_validity(
+        &self,
+        inputs: Vec<OperationValidityInput>,
+    ) -> RpcResult<Vec<OperationValidityCheck>> {
+        let api_settings = &self.0.api_settings;
+        if inputs.len() as u64 > api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let now = MassaTime::now().map_err(|e| {
+            ApiError::InconsistencyError(format!("Unable to get current time: {}", e))
+        })?;
+        let final_periods = self.0.pool_command_sender.get_final_cs_periods().clone();
+
+        let mut checks = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            let Some(final_period) = final_periods.get(input.thread as usize).copied() else {
+                return Err(
+                    ApiError::BadRequest(format!("invalid thread {}", input.thread)).into(),
+                );
+            };
+            let sent_at_slot = get_latest_block_slot_at_timestamp(
+                api_settings.thread_count,
+                api_settings.t0,
+                api_settings.genesis_timestamp,
+                now.saturating_add(input.signing_delay.unwrap_or(MassaTime::from_millis(0))),
+            )
+            .map_err(ApiError::ModelsError)?;
+
+            // the block of the thread at the period of the sending slot is already produced
+            // if the thread is not after the thread of the sending slot
+            let next_period = match sent_at_slot {
+                Some(slot) if input.thread <= slot.thread => slot.period.saturating_add(1),
+                Some(slot) => slot.period,
+                None => 0,
+            }
+            .max(final_period.saturating_add(1))
+            .max(api_settings.last_start_period.saturating_add(1));
+            let validity_start_period = input
+                .expire_period
+                .saturating_sub(OPERATION_VALIDITY_PERIODS);
+            let inclusion_periods =
+                inclusion_periods(input.expire_period, OPERATION_VALIDITY_PERIODS, next_period);
+
+            let mut errors = Vec::new();
+            if input.expire_period <= final_period {
+                errors.push(format!(
+                    "the operation expires at or before the last final period {} of its thread",
+                    final_period
+                ));
+            } else if inclusion_periods == 0 {
+                errors.push(format!(
+                    "the operation expires before period {}, the next period of its thread in which it can be included",
+                    next_period
+                ));
+            }
+            if validity_start_period > next_period {
+                errors.push(format!(
+                    "the operation is not valid before period {}, the pools drop the operations they cannot include soon",
+                    validity_start_period
+                ));
+            }
+            let suggested_max_gas = input
+                .max_gas
+                .filter(|max_gas| *max_gas > api_settings.max_gas_per_block)
+                .map(|_| api_settings.max_gas_per_block);
+            if suggested_max_gas.is_some() {
+                errors.push(
+                    "the max gas of the operation is higher than the block gas limit".to_string(),
+                );
+            }
+
+            checks.push(OperationValidityCheck {
+                thread: input.thread,
+                expire_period: input.expire_period,
+                sent_at_slot,
+                next_period,
+                final_period,
+                validity_start_period,
+                inclusion_periods,
+                errors,
+                suggested_expire_period: next_period.saturating_add(OPERATION_VALIDITY_PERIODS),
+                suggested_max_gas,
+            });
+        }
+        Ok(checks)
+    }
+
     async fn send_operations(&self, ops: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
         let mut cmd_sender = self.0.pool_command_sender.clone();
         let protocol_sender = self.0.protocol_controller.clone();
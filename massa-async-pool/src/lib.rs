@@ -87,6 +87,7 @@
 
 mod changes;
 mod config;
+#[cfg(feature = "grpc")]
 mod mapping_grpc;
 mod message;
 mod pool;
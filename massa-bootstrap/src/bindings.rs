@@ -1,5 +1,6 @@
 mod client;
 mod server;
+use massa_metrics::MassaMetrics;
 use std::{
     io::{self, ErrorKind},
     time::{Duration, Instant},
@@ -8,6 +9,27 @@ use std::{
 pub(crate) use client::*;
 pub(crate) use server::*;
 
+/// Messages exchanged through a binder, and their bytes (handshake included), in each direction
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct BindingTraffic {
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    pub messages_received: u64,
+    pub bytes_received: u64,
+}
+
+impl BindingTraffic {
+    /// Adds the traffic of a bootstrap session to the metrics
+    pub(crate) fn report(&self, massa_metrics: &MassaMetrics) {
+        massa_metrics.inc_bootstrap_traffic("sent", self.messages_sent, self.bytes_sent);
+        massa_metrics.inc_bootstrap_traffic(
+            "received",
+            self.messages_received,
+            self.bytes_received,
+        );
+    }
+}
+
 trait BindingReadExact: io::Read {
     /// similar to std::io::Read::read_exact, but with a timeout that is function-global instead of per-individual-read
     fn read_exact_timeout(
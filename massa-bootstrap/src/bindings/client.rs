@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::bindings::{BindingReadExact, BindingWriteExact};
+use crate::bindings::{BindingReadExact, BindingTraffic, BindingWriteExact};
 use crate::error::BootstrapError;
 use crate::messages::{
     BootstrapClientMessage, BootstrapClientMessageSerializer, BootstrapServerMessage,
@@ -27,6 +27,7 @@ pub struct BootstrapClientBinder {
     prev_message: Option<Hash>,
     version_serializer: VersionSerializer,
     cfg: BootstrapClientConfig,
+    traffic: BindingTraffic,
 }
 
 const KNOWN_PREFIX_LEN: usize = SIGNATURE_DESER_SIZE + MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES;
@@ -58,9 +59,15 @@ impl BootstrapClientBinder {
             prev_message: None,
             version_serializer: VersionSerializer::new(),
             cfg,
+            traffic: BindingTraffic::default(),
         }
     }
 
+    /// Messages exchanged with the server so far, and their bytes
+    pub(crate) fn get_traffic(&self) -> BindingTraffic {
+        self.traffic
+    }
+
     /// Performs a handshake. Should be called after connection
     /// NOT cancel-safe
     pub fn handshake(&mut self, version: Version) -> Result<(), BootstrapError> {
@@ -145,6 +152,7 @@ impl BootstrapClientBinder {
                 msg
             }
         };
+        self.traffic.messages_received = self.traffic.messages_received.saturating_add(1);
         Ok(message)
     }
 
@@ -192,6 +200,7 @@ impl BootstrapClientBinder {
         // And send it off
         self.write_all_timeout(&write_buf, deadline)
             .map_err(|(e, _)| e)?;
+        self.traffic.messages_sent = self.traffic.messages_sent.saturating_add(1);
         Ok(())
     }
 
@@ -224,7 +233,9 @@ impl crate::bindings::BindingReadExact for BootstrapClientBinder {
 
 impl std::io::Read for BootstrapClientBinder {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
-        self.duplex.read(buf)
+        let read = self.duplex.read(buf)?;
+        self.traffic.bytes_received = self.traffic.bytes_received.saturating_add(read as u64);
+        Ok(read)
     }
 }
 
@@ -239,7 +250,9 @@ impl crate::bindings::BindingWriteExact for BootstrapClientBinder {
 
 impl std::io::Write for BootstrapClientBinder {
     fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
-        self.duplex.write(buf)
+        let written = self.duplex.write(buf)?;
+        self.traffic.bytes_sent = self.traffic.bytes_sent.saturating_add(written as u64);
+        Ok(written)
     }
 
     fn flush(&mut self) -> Result<(), std::io::Error> {
@@ -27,7 +27,7 @@ use std::{
 use stream_limiter::{Limiter, LimiterOptions};
 use tracing::error;
 
-use super::BindingWriteExact;
+use super::{BindingTraffic, BindingWriteExact};
 
 const KNOWN_PREFIX_LEN: usize = HASH_SIZE_BYTES + MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES;
 /// The known-length component of a message to be received.
@@ -48,6 +48,7 @@ pub struct BootstrapServerBinder {
     version_serializer: VersionSerializer,
     version_deserializer: VersionDeserializer,
     write_error_timeout: MassaTime,
+    traffic: BindingTraffic,
 }
 
 impl BootstrapServerBinder {
@@ -88,8 +89,15 @@ impl BootstrapServerBinder {
             version_serializer: VersionSerializer::new(),
             version_deserializer: VersionDeserializer::new(),
             write_error_timeout,
+            traffic: BindingTraffic::default(),
         }
     }
+
+    /// Messages exchanged with the client so far, and their bytes
+    pub(crate) fn get_traffic(&self) -> BindingTraffic {
+        self.traffic
+    }
+
     /// Performs a handshake. Should be called after connection
     /// MUST always be followed by a send of the `BootstrapMessage::BootstrapTime`
     pub fn handshake_timeout(
@@ -229,6 +237,7 @@ impl BootstrapServerBinder {
 
         // update prev sig
         self.prev_message = Some(Hash::compute_from(&sig.to_bytes()));
+        self.traffic.messages_sent = self.traffic.messages_sent.saturating_add(1);
 
         Ok(())
     }
@@ -284,6 +293,7 @@ impl BootstrapServerBinder {
         )
         .deserialize::<DeserializeError>(&msg_bytes)
         .map_err(|err| BootstrapError::GeneralError(format!("{}", err)))?;
+        self.traffic.messages_received = self.traffic.messages_received.saturating_add(1);
 
         Ok(msg)
     }
@@ -320,7 +330,9 @@ impl BootstrapServerBinder {
 
 impl io::Read for BootstrapServerBinder {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.duplex.read(buf)
+        let read = self.duplex.read(buf)?;
+        self.traffic.bytes_received = self.traffic.bytes_received.saturating_add(read as u64);
+        Ok(read)
     }
 }
 
@@ -335,7 +347,9 @@ impl crate::bindings::BindingReadExact for BootstrapServerBinder {
 
 impl io::Write for BootstrapServerBinder {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.duplex.write(buf)
+        let written = self.duplex.write(buf)?;
+        self.traffic.bytes_sent = self.traffic.bytes_sent.saturating_add(written as u64);
+        Ok(written)
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -140,6 +140,7 @@ fn stream_final_state_and_consensus(
                         last_versioning_step,
                         last_consensus_step,
                         send_last_start_period: false,
+                        supports_change_history: true,
                     };
 
                     // Logs for an easier diagnostic if needed
@@ -148,6 +149,31 @@ fn stream_final_state_and_consensus(
                         next_bootstrap_message
                     );
                 }
+                BootstrapServerMessage::ChangeHistoryPart {
+                    slot,
+                    state_changes,
+                    versioning_changes,
+                } => {
+                    // Apply the changes and move the slot forward, keeping the streaming cursors
+                    let write_final_state = global_bootstrap_state.final_state.write();
+                    let mut db = write_final_state.db.write();
+                    let db_slot = db.get_change_id().map_err(|e| {
+                        BootstrapError::GeneralError(format!("Cannot get the state slot: {}", e))
+                    })?;
+                    if slot <= db_slot {
+                        return Err(BootstrapError::GeneralError(format!(
+                            "Received changes of slot {} while the state is at slot {}",
+                            slot, db_slot
+                        )));
+                    }
+                    db.write_batch(state_changes, versioning_changes, Some(slot));
+                    if let BootstrapClientMessage::AskBootstrapPart { last_slot, .. } =
+                        next_bootstrap_message
+                    {
+                        *last_slot = Some(slot);
+                    }
+                    debug!("client caught up to slot {} from the change history", slot);
+                }
                 BootstrapServerMessage::BootstrapFinished => {
                     info!("State bootstrap complete");
                     // Set next bootstrap message
@@ -173,6 +199,7 @@ fn stream_final_state_and_consensus(
                         last_versioning_step: StreamingStep::Started,
                         last_consensus_step: StreamingStep::Started,
                         send_last_start_period: true,
+                        supports_change_history: true,
                     };
                     let mut write_final_state = global_bootstrap_state.final_state.write();
                     write_final_state.reset();
@@ -475,14 +502,44 @@ pub fn get_state(
     // we filter the bootstrap list to keep only the ip addresses we are compatible with
     let filtered_bootstrap_list = get_bootstrap_list_iter(bootstrap_config)?;
 
-    let mut next_bootstrap_message: BootstrapClientMessage =
-        BootstrapClientMessage::AskBootstrapPart {
-            last_slot: None,
-            last_state_step: StreamingStep::Started,
-            last_versioning_step: StreamingStep::Started,
-            last_consensus_step: StreamingStep::Started,
-            send_last_start_period: true,
-        };
+    let from_scratch = BootstrapClientMessage::AskBootstrapPart {
+        last_slot: None,
+        last_state_step: StreamingStep::Started,
+        last_versioning_step: StreamingStep::Started,
+        last_consensus_step: StreamingStep::Started,
+        send_last_start_period: true,
+        supports_change_history: true,
+    };
+
+    // resume from the latest cycle snapshot, if any:
+    // only the final state changes since its slot are streamed
+    let mut resumed_from_snapshot = false;
+    let mut next_bootstrap_message = if bootstrap_config.keep_ledger {
+        from_scratch.clone()
+    } else {
+        match final_state.write().load_latest_cycle_snapshot() {
+            Ok(Some(slot)) => {
+                info!(
+                    "Resuming bootstrap from the final state snapshot of slot {}",
+                    slot
+                );
+                resumed_from_snapshot = true;
+                BootstrapClientMessage::AskBootstrapPart {
+                    last_slot: Some(slot),
+                    last_state_step: StreamingStep::Finished(None),
+                    last_versioning_step: StreamingStep::Finished(None),
+                    last_consensus_step: StreamingStep::Started,
+                    send_last_start_period: true,
+                    supports_change_history: true,
+                }
+            }
+            Ok(None) => from_scratch.clone(),
+            Err(err) => {
+                warn!("Could not load the final state snapshot: {}", err);
+                from_scratch.clone()
+            }
+        }
+    };
     let mut global_bootstrap_state = GlobalBootstrapState::new(final_state);
 
     let limit = bootstrap_config.rate_limit;
@@ -517,6 +574,7 @@ pub fn get_state(
                         &mut global_bootstrap_state,
                         version,
                     );
+                    client.get_traffic().report(&massa_metrics);
                     // cancellable
                     match bs {
                         Err(BootstrapError::ReceivedError(error)) => {
@@ -534,6 +592,16 @@ pub fn get_state(
                         }
                         Ok(()) => return Ok(global_bootstrap_state),
                     }
+                    // the server may not have the changes since the snapshot anymore
+                    if resumed_from_snapshot {
+                        warn!(
+                            "Could not resume bootstrap from the final state snapshot, bootstrapping the whole state"
+                        );
+                        resumed_from_snapshot = false;
+                        global_bootstrap_state.final_state.write().reset();
+                        global_bootstrap_state.graph = None;
+                        next_bootstrap_message = from_scratch.clone();
+                    }
                 }
                 Err(e) => {
                     warn!("Error while connecting to bootstrap server: {}", e);
@@ -576,6 +644,58 @@ pub fn get_state(
     }
 }
 
+/// Bootstraps once from a single server, without retrying on failure.
+///
+/// Used to check the integrity of the state served by a bootstrap server out of band:
+/// the messages of the server are authenticated with its node ID as during a normal bootstrap.
+pub fn get_state_from_server(
+    bootstrap_config: &BootstrapConfig,
+    final_state: Arc<RwLock<FinalState>>,
+    mut connector: impl BSConnector,
+    version: Version,
+    addr: &SocketAddr,
+    node_id: &NodeId,
+) -> Result<GlobalBootstrapState, BootstrapError> {
+    massa_trace!("bootstrap.lib.get_state_from_server", {});
+    let mut next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
+        last_slot: None,
+        last_state_step: StreamingStep::Started,
+        last_versioning_step: StreamingStep::Started,
+        last_consensus_step: StreamingStep::Started,
+        send_last_start_period: true,
+        supports_change_history: true,
+    };
+    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state);
+
+    info!("Start bootstrapping from {}", addr);
+    let mut client = connect_to_server(
+        &mut connector,
+        bootstrap_config,
+        addr,
+        &node_id.get_public_key(),
+        Some(bootstrap_config.rate_limit),
+    )?;
+    if let Err(err) = bootstrap_from_server(
+        bootstrap_config,
+        &mut client,
+        &mut next_bootstrap_message,
+        &mut global_bootstrap_state,
+        version,
+    ) {
+        if !matches!(err, BootstrapError::ReceivedError(_)) {
+            // we don't care if the error can't be sent, the socket is closed anyway
+            let _ = client.send_timeout(
+                &BootstrapClientMessage::BootstrapError {
+                    error: err.to_string(),
+                },
+                Some(bootstrap_config.write_error_timeout.into()),
+            );
+        }
+        return Err(err);
+    }
+    Ok(global_bootstrap_state)
+}
+
 fn get_bootstrap_list_iter(
     bootstrap_config: &BootstrapConfig,
 ) -> Result<Vec<(SocketAddr, NodeId)>, BootstrapError> {
@@ -31,7 +31,7 @@ mod tools;
 /// white/black list
 pub mod white_black_list;
 
-pub use client::{get_state, DefaultConnector};
+pub use client::{get_state, get_state_from_server, DefaultConnector};
 pub use listener::BootstrapTcpListener;
 pub use messages::{
     BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer,
@@ -4,7 +4,7 @@ use crate::settings::BootstrapServerMessageDeserializerArgs;
 use massa_consensus_exports::bootstrapable_graph::{
     BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
 };
-use massa_db_exports::StreamBatch;
+use massa_db_exports::{DBBatch, StreamBatch};
 use massa_models::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
 use massa_models::prehash::PreHashSet;
 use massa_models::serialization::{
@@ -69,6 +69,16 @@ pub enum BootstrapServerMessage {
         /// Last Slot before downtime for network restart management
         last_slot_before_downtime: Option<Option<Slot>>,
     },
+    /// Changes of the final state from the change history kept on disk,
+    /// sent to a client whose slot is behind the changes kept in memory
+    ChangeHistoryPart {
+        /// Slot the state changes are attached to
+        slot: Slot,
+        /// Changes of the state
+        state_changes: DBBatch,
+        /// Changes of the state specific to versioning
+        versioning_changes: DBBatch,
+    },
     /// Message sent when the final state and consensus bootstrap are finished
     BootstrapFinished,
     /// Slot sent to get state changes is too old
@@ -86,6 +96,7 @@ impl ToString for BootstrapServerMessage {
             BootstrapServerMessage::BootstrapTime { .. } => "BootstrapTime".to_string(),
             BootstrapServerMessage::BootstrapPeers { .. } => "BootstrapPeers".to_string(),
             BootstrapServerMessage::BootstrapPart { .. } => "BootstrapPart".to_string(),
+            BootstrapServerMessage::ChangeHistoryPart { .. } => "ChangeHistoryPart".to_string(),
             BootstrapServerMessage::BootstrapFinished => "BootstrapFinished".to_string(),
             BootstrapServerMessage::SlotTooOld => "SlotTooOld".to_string(),
             BootstrapServerMessage::BootstrapError { error } => {
@@ -104,6 +115,7 @@ enum MessageServerTypeId {
     FinalStateFinished = 3u32,
     SlotTooOld = 4u32,
     BootstrapError = 5u32,
+    ChangeHistoryPart = 6u32,
 }
 
 /// Serializer for `BootstrapServerMessage`
@@ -249,6 +261,23 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                 self.opt_last_slot_before_downtime_serializer
                     .serialize(last_slot_before_downtime, buffer)?;
             }
+            BootstrapServerMessage::ChangeHistoryPart {
+                slot,
+                state_changes,
+                versioning_changes,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::ChangeHistoryPart), buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+                for changes in [state_changes, versioning_changes] {
+                    self.u64_serializer
+                        .serialize(&(changes.len() as u64), buffer)?;
+                    for (key, value) in changes.iter() {
+                        self.vec_u8_serializer.serialize(key, buffer)?;
+                        self.opt_vec_u8_serializer.serialize(value, buffer)?;
+                    }
+                }
+            }
             BootstrapServerMessage::BootstrapFinished => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageServerTypeId::FinalStateFinished), buffer)?;
@@ -561,6 +590,43 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     },
                 )
                 .parse(input),
+                MessageServerTypeId::ChangeHistoryPart => tuple((
+                    context("Failed slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context(
+                        "Failed state_changes deserialization",
+                        length_count(
+                            context("Failed length deserialization", |input| {
+                                self.state_updates_length_deserializer.deserialize(input)
+                            }),
+                            tuple((
+                                |input| self.vec_u8_deserializer.deserialize(input),
+                                |input| self.opt_vec_u8_deserializer.deserialize(input),
+                            )),
+                        ),
+                    ),
+                    context(
+                        "Failed versioning_changes deserialization",
+                        length_count(
+                            context("Failed length deserialization", |input| {
+                                self.state_updates_length_deserializer.deserialize(input)
+                            }),
+                            tuple((
+                                |input| self.vec_u8_deserializer.deserialize(input),
+                                |input| self.opt_vec_u8_deserializer.deserialize(input),
+                            )),
+                        ),
+                    ),
+                ))
+                .map(|(slot, state_changes, versioning_changes)| {
+                    BootstrapServerMessage::ChangeHistoryPart {
+                        slot,
+                        state_changes: state_changes.into_iter().collect(),
+                        versioning_changes: versioning_changes.into_iter().collect(),
+                    }
+                })
+                .parse(input),
                 MessageServerTypeId::FinalStateFinished => {
                     Ok((input, BootstrapServerMessage::BootstrapFinished))
                 }
@@ -599,6 +665,9 @@ pub enum BootstrapClientMessage {
         last_consensus_step: StreamingStep<PreHashSet<BlockId>>,
         /// Should be true only for the first part, false later
         send_last_start_period: bool,
+        /// Whether the client decodes `BootstrapServerMessage::ChangeHistoryPart`.
+        /// Only sent along a slot, after the other fields, so that the servers not knowing it ignore it
+        supports_change_history: bool,
     },
     /// Bootstrap error
     BootstrapError {
@@ -681,6 +750,7 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
                 last_versioning_step,
                 last_consensus_step,
                 send_last_start_period,
+                supports_change_history,
             } => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageClientTypeId::AskFinalStatePart), buffer)?;
@@ -694,6 +764,8 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
                         .serialize(last_consensus_step, buffer)?;
                     self.bool_serializer
                         .serialize(send_last_start_period, buffer)?;
+                    self.bool_serializer
+                        .serialize(supports_change_history, buffer)?;
                 }
             }
             BootstrapClientMessage::BootstrapError { error } => {
@@ -811,6 +883,7 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                                 last_versioning_step: StreamingStep::Started,
                                 last_consensus_step: StreamingStep::Started,
                                 send_last_start_period: true,
+                                supports_change_history: false,
                             },
                         ))
                     } else {
@@ -830,6 +903,14 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                             context("Failed send_last_start_period deserialization", |input| {
                                 self.bool_deserializer.deserialize(input)
                             }),
+                            // absent from the messages of the clients not knowing it
+                            context("Failed supports_change_history deserialization", |input| {
+                                if input.is_empty() {
+                                    Ok((input, false))
+                                } else {
+                                    self.bool_deserializer.deserialize(input)
+                                }
+                            }),
                         ))
                         .map(
                             |(
@@ -838,6 +919,7 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                                 last_versioning_step,
                                 last_consensus_step,
                                 send_last_start_period,
+                                supports_change_history,
                             )| {
                                 BootstrapClientMessage::AskBootstrapPart {
                                     last_slot: Some(last_slot),
@@ -845,6 +927,7 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                                     last_versioning_step,
                                     last_consensus_step,
                                     send_last_start_period,
+                                    supports_change_history,
                                 }
                             },
                         )
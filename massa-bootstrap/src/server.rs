@@ -28,7 +28,7 @@
 use crossbeam::channel::tick;
 use humantime::format_duration;
 use massa_consensus_exports::{bootstrapable_graph::BootstrapableGraph, ConsensusController};
-use massa_db_exports::CHANGE_ID_DESER_ERROR;
+use massa_db_exports::{DBBatch, CHANGE_ID_DESER_ERROR};
 use massa_final_state::FinalState;
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
@@ -467,6 +467,7 @@ fn run_bootstrap_session(
             massa_metrics.inc_bootstrap_peers_success();
         }
     }
+    server.get_traffic().report(&massa_metrics);
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -479,6 +480,7 @@ pub fn stream_bootstrap_information(
     mut last_versioning_step: StreamingStep<Vec<u8>>,
     mut last_consensus_step: StreamingStep<PreHashSet<BlockId>>,
     mut send_last_start_period: bool,
+    supports_change_history: bool,
     bs_deadline: &Instant,
     write_timeout: Duration,
 ) -> Result<(), BootstrapError> {
@@ -495,7 +497,64 @@ pub fn stream_bootstrap_information(
         let last_start_period;
         let last_slot_before_downtime;
 
-        let slot_too_old = false;
+        let mut slot_too_old = false;
+        let mut history_page = None;
+
+        // A client whose slot is behind the changes kept in memory by the database
+        // catches up from the change history kept on disk, page by page,
+        // if it announced that it decodes the pages
+        if let (Some(slot), true) = (last_slot, supports_change_history) {
+            let lagging_change_history = {
+                let final_state_read = final_state.read();
+                let db = final_state_read.db.read();
+                let lagging = slot < db.get_change_id().expect(CHANGE_ID_DESER_ERROR)
+                    && db.get_changes_at(slot).is_none();
+                lagging.then(|| final_state_read.get_change_history())
+            };
+            // the page is read from the disk once the final state is unlocked
+            if let Some(change_history) = lagging_change_history {
+                let page = match change_history {
+                    Some(history) => history.get_page(slot).map_err(|err| err.to_string()),
+                    None => Err(String::from("the change history is disabled")),
+                };
+                match page {
+                    Ok(page) if page.slot > slot => history_page = Some(page),
+                    Ok(_) => slot_too_old = true,
+                    Err(err) => {
+                        debug!(
+                            "cannot serve slot {} from the change history: {}",
+                            slot, err
+                        );
+                        slot_too_old = true;
+                    }
+                }
+            }
+        }
+
+        if slot_too_old {
+            return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
+        }
+
+        if let Some(mut page) = history_page {
+            // changes of the elements not streamed yet are streamed with them later on
+            retain_streamed_changes(&mut page.state_changes, &last_state_step);
+            retain_streamed_changes(&mut page.versioning_changes, &last_versioning_step);
+            let Some(write_timeout) = step_timeout_duration(bs_deadline, &write_timeout) else {
+                return Err(BootstrapError::Interupted(
+                    "insufficient time left to provide next change history part".to_string(),
+                ));
+            };
+            server.send_msg(
+                write_timeout,
+                BootstrapServerMessage::ChangeHistoryPart {
+                    slot: page.slot,
+                    state_changes: page.state_changes,
+                    versioning_changes: page.versioning_changes,
+                },
+            )?;
+            last_slot = Some(page.slot);
+            continue;
+        }
 
         // Scope of the final state read
         {
@@ -512,9 +571,10 @@ pub fn stream_bootstrap_information(
                 None
             };
 
-            state_part = final_state_read
-                .db
-                .read()
+            // all the parts are read from the same snapshot, so that they are attached to the same slot
+            let snapshot = final_state_read.snapshot()?;
+
+            state_part = snapshot
                 .get_batch_to_stream(&last_state_step, last_slot)
                 .map_err(|e| {
                     BootstrapError::GeneralError(format!("Error get_batch_to_stream: {}", e))
@@ -553,9 +613,7 @@ pub fn stream_bootstrap_information(
                 },
             };
 
-            versioning_part = final_state_read
-                .db
-                .read()
+            versioning_part = snapshot
                 .get_versioning_batch_to_stream(&last_versioning_step, last_slot)
                 .map_err(|e| {
                     BootstrapError::GeneralError(format!(
@@ -599,11 +657,7 @@ pub fn stream_bootstrap_information(
                 }
             };
 
-            let db_slot = final_state_read
-                .db
-                .read()
-                .get_change_id()
-                .expect(CHANGE_ID_DESER_ERROR);
+            let db_slot = snapshot.slot();
 
             if let Some(slot) = last_slot {
                 if slot > db_slot {
@@ -621,10 +675,6 @@ pub fn stream_bootstrap_information(
             send_last_start_period = false;
         }
 
-        if slot_too_old {
-            return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
-        }
-
         // Setup final state global cursor
         let final_state_global_step =
             if last_state_step.finished() && last_versioning_step.finished() {
@@ -689,6 +739,15 @@ pub fn stream_bootstrap_information(
     Ok(())
 }
 
+// keeps the changes of the elements already streamed to the client
+fn retain_streamed_changes(changes: &mut DBBatch, step: &StreamingStep<Vec<u8>>) {
+    match step {
+        StreamingStep::Started => changes.clear(),
+        StreamingStep::Ongoing(last_key) => changes.retain(|key, _| key <= last_key),
+        StreamingStep::Finished(_) => {}
+    }
+}
+
 // derives the duration allowed for a step in the bootstrap process.
 // Returns None if the deadline for the entire bs-process has been reached
 fn step_timeout_duration(bs_deadline: &Instant, step_timeout: &Duration) -> Option<Duration> {
@@ -789,6 +848,7 @@ fn manage_bootstrap(
                     last_versioning_step,
                     last_consensus_step,
                     send_last_start_period,
+                    supports_change_history,
                 } => {
                     stream_bootstrap_information(
                         server,
@@ -799,6 +859,7 @@ fn manage_bootstrap(
                         last_versioning_step,
                         last_consensus_step,
                         send_last_start_period,
+                        supports_change_history,
                         &deadline,
                         bootstrap_config.write_timeout.to_duration(),
                     )?;
@@ -0,0 +1,56 @@
+use crate::messages::{
+    BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer,
+};
+use massa_models::config::{MAX_BOOTSTRAP_BLOCKS, MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT};
+use massa_models::{slot::Slot, streaming_step::StreamingStep};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+
+fn deserialize(bytes: &[u8]) -> BootstrapClientMessage {
+    let (rest, message) = BootstrapClientMessageDeserializer::new(
+        THREAD_COUNT,
+        MAX_DATASTORE_KEY_LENGTH,
+        MAX_BOOTSTRAP_BLOCKS.into(),
+    )
+    .deserialize::<DeserializeError>(bytes)
+    .expect("could not deserialize the client message");
+    assert!(rest.is_empty());
+    message
+}
+
+#[test]
+fn test_ask_bootstrap_part_change_history_support() {
+    let mut bytes = Vec::new();
+    BootstrapClientMessageSerializer::new()
+        .serialize(
+            &BootstrapClientMessage::AskBootstrapPart {
+                last_slot: Some(Slot::new(10, 2)),
+                last_state_step: StreamingStep::Ongoing(b"key".to_vec()),
+                last_versioning_step: StreamingStep::Finished(None),
+                last_consensus_step: StreamingStep::Started,
+                send_last_start_period: false,
+                supports_change_history: true,
+            },
+            &mut bytes,
+        )
+        .unwrap();
+    assert!(matches!(
+        deserialize(&bytes),
+        BootstrapClientMessage::AskBootstrapPart {
+            last_slot: Some(slot),
+            send_last_start_period: false,
+            supports_change_history: true,
+            ..
+        } if slot == Slot::new(10, 2)
+    ));
+
+    // the clients not knowing the change history end their message before the flag
+    bytes.pop();
+    assert!(matches!(
+        deserialize(&bytes),
+        BootstrapClientMessage::AskBootstrapPart {
+            last_slot: Some(_),
+            supports_change_history: false,
+            ..
+        }
+    ));
+}
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 mod binders;
+mod messages;
 mod scenarios;
 pub(crate) mod tools;
@@ -22,6 +22,7 @@ use massa_consensus_exports::{
 };
 use massa_db_exports::{DBBatch, MassaDBConfig, MassaDBController};
 use massa_db_worker::MassaDB;
+use massa_deferred_calls::DeferredCallsConfig;
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
 use massa_final_state::{
     test_exports::{assert_eq_final_state, assert_eq_final_state_hash},
@@ -31,8 +32,11 @@ use massa_ledger_exports::LedgerConfig;
 use massa_metrics::MassaMetrics;
 use massa_models::config::{
     DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, GENESIS_TIMESTAMP,
-    KEEP_EXECUTED_HISTORY_EXTRA_PERIODS, MAX_DEFERRED_CREDITS_LENGTH,
-    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, T0,
+    KEEP_EXECUTED_HISTORY_EXTRA_PERIODS, MAX_DEFERRED_CALL_CHANGES, MAX_DEFERRED_CREDITS_LENGTH,
+    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_FUNCTION_NAME_LENGTH, MAX_PARAMETERS_SIZE,
+    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, PRUNED_OPS_FILTER_CAPACITY,
+    PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2, PRUNED_OPS_FILTER_RANGE_COUNT,
+    PRUNED_OPS_FILTER_RANGE_PERIODS, T0,
 };
 use massa_models::{
     address::Address, config::MAX_DATASTORE_VALUE_LENGTH, node::NodeId, slot::Slot,
@@ -40,7 +44,8 @@ use massa_models::{
 };
 use massa_models::{
     config::{
-        MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_DATASTORE_KEY_LENGTH, POS_SAVED_CYCLES,
+        MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_DATASTORE_KEY_LENGTH,
+        POS_LOOKBACK_CYCLES, POS_SAVED_CYCLES,
     },
     prehash::PreHashSet,
 };
@@ -94,9 +99,17 @@ fn mock_bootstrap_manager(
     mocked1.expect_clone_box().return_once(move || mocked2);
 
     // start proof-of-stake selectors
-    let (server_selector_manager, server_selector_controller) =
-        start_selector_worker(selector_local_config.clone())
-            .expect("could not start server selector controller");
+    let (server_selector_manager, server_selector_controller) = start_selector_worker(
+        selector_local_config.clone(),
+        MassaMetrics::new(
+            false,
+            "0.0.0.0:31248".parse().unwrap(),
+            selector_local_config.thread_count,
+            Duration::from_secs(5),
+        )
+        .0,
+    )
+    .expect("could not start server selector controller");
 
     // setup final state local config
     let temp_dir = TempDir::new().unwrap();
@@ -105,6 +118,7 @@ fn mock_bootstrap_manager(
         max_history_length: 10,
         max_new_elements: 100,
         thread_count: 2,
+        block_cache_size: 0,
     };
     let db = Arc::new(RwLock::new(
         Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -113,9 +127,12 @@ fn mock_bootstrap_manager(
         ledger_config: LedgerConfig {
             thread_count,
             initial_ledger_path: "".into(),
+            initial_genesis_sc_path: None,
             disk_ledger_path: temp_dir.path().to_path_buf(),
+            max_initial_ledger_entries: 1_000_000,
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            commitment_enabled: false,
         },
         async_pool_config: AsyncPoolConfig {
             thread_count,
@@ -123,10 +140,17 @@ fn mock_bootstrap_manager(
             max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
             max_key_length: MAX_DATASTORE_KEY_LENGTH as u32,
         },
+        deferred_calls_config: DeferredCallsConfig {
+            thread_count,
+            max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+            max_parameters_size: MAX_PARAMETERS_SIZE,
+            max_deferred_call_changes: MAX_DEFERRED_CALL_CHANGES,
+        },
         pos_config: PoSConfig {
             periods_per_cycle,
             thread_count,
             cycle_history_length: POS_SAVED_CYCLES,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
             max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
             max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
             max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
@@ -135,6 +159,10 @@ fn mock_bootstrap_manager(
         executed_ops_config: ExecutedOpsConfig {
             thread_count,
             keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+            pruned_ops_filter_capacity: PRUNED_OPS_FILTER_CAPACITY,
+            pruned_ops_filter_false_positive_log2: PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+            pruned_ops_filter_range_periods: PRUNED_OPS_FILTER_RANGE_PERIODS,
+            pruned_ops_filter_range_count: PRUNED_OPS_FILTER_RANGE_COUNT,
         },
         final_history_length: 100,
         initial_seed_string: "".into(),
@@ -152,6 +180,12 @@ fn mock_bootstrap_manager(
         max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
+        final_state_wal_path: None,
+        cycle_snapshot_path: None,
+        cycle_snapshot_retention: 0,
+        change_history_path: None,
+        change_history_length: 0,
+        change_history_page_max_bytes: 0,
     };
 
     let final_state_server = Arc::new(RwLock::new(get_random_final_state_bootstrap(
@@ -239,6 +273,7 @@ fn test_bootstrap_server() {
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        block_cache_size: 0,
     };
     let db_server = Arc::new(RwLock::new(
         Box::new(MassaDB::new(db_server_config)) as Box<(dyn MassaDBController + 'static)>
@@ -249,6 +284,7 @@ fn test_bootstrap_server() {
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        block_cache_size: 0,
     };
     let db_client = Arc::new(RwLock::new(
         Box::new(MassaDB::new(db_client_config)) as Box<(dyn MassaDBController + 'static)>
@@ -257,9 +293,12 @@ fn test_bootstrap_server() {
         ledger_config: LedgerConfig {
             thread_count,
             initial_ledger_path: "".into(),
+            initial_genesis_sc_path: None,
             disk_ledger_path: temp_dir_server.path().to_path_buf(),
+            max_initial_ledger_entries: 1_000_000,
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            commitment_enabled: false,
         },
         async_pool_config: AsyncPoolConfig {
             thread_count,
@@ -267,10 +306,17 @@ fn test_bootstrap_server() {
             max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
             max_key_length: MAX_DATASTORE_KEY_LENGTH as u32,
         },
+        deferred_calls_config: DeferredCallsConfig {
+            thread_count,
+            max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+            max_parameters_size: MAX_PARAMETERS_SIZE,
+            max_deferred_call_changes: MAX_DEFERRED_CALL_CHANGES,
+        },
         pos_config: PoSConfig {
             periods_per_cycle,
             thread_count,
             cycle_history_length: POS_SAVED_CYCLES,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
             max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
             max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
             max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
@@ -279,6 +325,10 @@ fn test_bootstrap_server() {
         executed_ops_config: ExecutedOpsConfig {
             thread_count,
             keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+            pruned_ops_filter_capacity: PRUNED_OPS_FILTER_CAPACITY,
+            pruned_ops_filter_false_positive_log2: PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+            pruned_ops_filter_range_periods: PRUNED_OPS_FILTER_RANGE_PERIODS,
+            pruned_ops_filter_range_count: PRUNED_OPS_FILTER_RANGE_COUNT,
         },
         executed_denunciations_config: ExecutedDenunciationsConfig {
             denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
@@ -296,6 +346,12 @@ fn test_bootstrap_server() {
         max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
+        final_state_wal_path: None,
+        cycle_snapshot_path: None,
+        cycle_snapshot_retention: 0,
+        change_history_path: None,
+        change_history_length: 0,
+        change_history_page_max_bytes: 0,
     };
 
     // setup selector local config
@@ -307,12 +363,28 @@ fn test_bootstrap_server() {
     };
 
     // start proof-of-stake selectors
-    let (mut server_selector_manager, server_selector_controller) =
-        start_selector_worker(selector_local_config.clone())
-            .expect("could not start server selector controller");
-    let (mut client_selector_manager, client_selector_controller) =
-        start_selector_worker(selector_local_config)
-            .expect("could not start client selector controller");
+    let (mut server_selector_manager, server_selector_controller) = start_selector_worker(
+        selector_local_config.clone(),
+        MassaMetrics::new(
+            false,
+            "0.0.0.0:31248".parse().unwrap(),
+            selector_local_config.thread_count,
+            Duration::from_secs(5),
+        )
+        .0,
+    )
+    .expect("could not start server selector controller");
+    let (mut client_selector_manager, client_selector_controller) = start_selector_worker(
+        selector_local_config,
+        MassaMetrics::new(
+            false,
+            "0.0.0.0:31248".parse().unwrap(),
+            selector_local_config.thread_count,
+            Duration::from_secs(5),
+        )
+        .0,
+    )
+    .expect("could not start client selector controller");
 
     let pos_server = PoSFinalState::new(
         final_state_local_config.pos_config.clone(),
@@ -340,6 +412,7 @@ fn test_bootstrap_server() {
             pos_changes: get_random_pos_changes(10),
             ledger_changes: get_random_ledger_changes(10),
             async_pool_changes: get_random_async_pool_changes(10, thread_count),
+            deferred_call_changes: Default::default(),
             executed_ops_changes: get_random_executed_ops_changes(10),
             executed_denunciations_changes: get_random_executed_de_changes(10),
             execution_trail_hash_change: get_random_execution_trail_hash_change(true),
@@ -362,6 +435,7 @@ fn test_bootstrap_server() {
         final_write.executed_ops.apply_changes_to_batch(
             changes.executed_ops_changes.clone(),
             next,
+            false,
             &mut batch,
         );
         final_write.executed_denunciations.apply_changes_to_batch(
@@ -497,6 +571,7 @@ fn test_bootstrap_server() {
                     pos_changes: get_random_pos_changes(10),
                     ledger_changes: get_random_ledger_changes(10),
                     async_pool_changes: get_random_async_pool_changes(10, thread_count),
+                    deferred_call_changes: Default::default(),
                     executed_ops_changes: get_random_executed_ops_changes(10),
                     executed_denunciations_changes: get_random_executed_de_changes(10),
                     execution_trail_hash_change: get_random_execution_trail_hash_change(true),
@@ -517,6 +592,7 @@ fn test_bootstrap_server() {
                 final_write.executed_ops.apply_changes_to_batch(
                     changes.executed_ops_changes.clone(),
                     next,
+                    false,
                     &mut batch,
                 );
                 final_write.executed_denunciations.apply_changes_to_batch(
@@ -632,6 +708,7 @@ fn test_bootstrap_accept_err() {
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        block_cache_size: 0,
     };
     let db_server = Arc::new(RwLock::new(
         Box::new(MassaDB::new(db_server_config)) as Box<(dyn MassaDBController + 'static)>
@@ -640,9 +717,12 @@ fn test_bootstrap_accept_err() {
         ledger_config: LedgerConfig {
             thread_count,
             initial_ledger_path: "".into(),
+            initial_genesis_sc_path: None,
             disk_ledger_path: temp_dir_server.path().to_path_buf(),
+            max_initial_ledger_entries: 1_000_000,
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            commitment_enabled: false,
         },
         async_pool_config: AsyncPoolConfig {
             thread_count,
@@ -650,10 +730,17 @@ fn test_bootstrap_accept_err() {
             max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
             max_key_length: MAX_DATASTORE_KEY_LENGTH as u32,
         },
+        deferred_calls_config: DeferredCallsConfig {
+            thread_count,
+            max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+            max_parameters_size: MAX_PARAMETERS_SIZE,
+            max_deferred_call_changes: MAX_DEFERRED_CALL_CHANGES,
+        },
         pos_config: PoSConfig {
             periods_per_cycle,
             thread_count,
             cycle_history_length: POS_SAVED_CYCLES,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
             max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
             max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
             max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
@@ -662,6 +749,10 @@ fn test_bootstrap_accept_err() {
         executed_ops_config: ExecutedOpsConfig {
             thread_count,
             keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+            pruned_ops_filter_capacity: PRUNED_OPS_FILTER_CAPACITY,
+            pruned_ops_filter_false_positive_log2: PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+            pruned_ops_filter_range_periods: PRUNED_OPS_FILTER_RANGE_PERIODS,
+            pruned_ops_filter_range_count: PRUNED_OPS_FILTER_RANGE_COUNT,
         },
         executed_denunciations_config: ExecutedDenunciationsConfig {
             denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
@@ -679,6 +770,12 @@ fn test_bootstrap_accept_err() {
         max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
+        final_state_wal_path: None,
+        cycle_snapshot_path: None,
+        cycle_snapshot_retention: 0,
+        change_history_path: None,
+        change_history_length: 0,
+        change_history_page_max_bytes: 0,
     };
 
     // setup selector local config
@@ -690,8 +787,17 @@ fn test_bootstrap_accept_err() {
     };
 
     // start proof-of-stake selectors
-    let (_, server_selector_controller) = start_selector_worker(selector_local_config.clone())
-        .expect("could not start server selector controller");
+    let (_, server_selector_controller) = start_selector_worker(
+        selector_local_config.clone(),
+        MassaMetrics::new(
+            false,
+            "0.0.0.0:31248".parse().unwrap(),
+            selector_local_config.thread_count,
+            Duration::from_secs(5),
+        )
+        .0,
+    )
+    .expect("could not start server selector controller");
 
     let pos_server = PoSFinalState::new(
         final_state_local_config.pos_config.clone(),
@@ -172,6 +172,7 @@ fn get_random_pos_state(r_limit: u64, mut pos: PoSFinalState) -> PoSFinalState {
         roll_changes: roll_counts.into_iter().collect(),
         production_stats,
         deferred_credits,
+        roll_sales: Default::default(),
     };
 
     let mut batch = DBBatch::new();
@@ -199,6 +200,7 @@ pub fn get_random_pos_changes(r_limit: u64) -> PoSChanges {
         roll_changes: roll_counts.into_iter().collect(),
         production_stats,
         deferred_credits,
+        roll_sales: Default::default(),
     }
 }
 
@@ -211,8 +213,7 @@ pub fn get_random_async_pool_changes(r_limit: u64, thread_count: u8) -> AsyncPoo
             .insert(message.compute_id(), SetUpdateOrDelete::Set(message));
     }
     for _ in (r_limit / 2)..r_limit {
-        let message =
-            get_random_message(Some(Amount::from_str("1_000_000").unwrap()), thread_count);
+        let message = get_random_message(Some(Amount::from_str("1000000").unwrap()), thread_count);
         changes
             .0
             .insert(message.compute_id(), SetUpdateOrDelete::Set(message));
@@ -228,7 +229,12 @@ pub fn get_random_executed_ops(
 ) -> ExecutedOps {
     let mut executed_ops = ExecutedOps::new(config.clone(), db.clone());
     let mut batch = DBBatch::new();
-    executed_ops.apply_changes_to_batch(get_random_executed_ops_changes(10), slot, &mut batch);
+    executed_ops.apply_changes_to_batch(
+        get_random_executed_ops_changes(10),
+        slot,
+        false,
+        &mut batch,
+    );
     db.write().write_batch(batch, Default::default(), None);
     executed_ops
 }
@@ -500,6 +506,7 @@ pub fn get_boot_state() -> BootstrapableGraph {
                         .unwrap(),
                     ],
                     denunciations: vec![],
+                    reward_recipient: None,
                 },
                 BlockHeaderSerializer::new(),
                 &keypair,
@@ -517,6 +524,7 @@ pub fn get_boot_state() -> BootstrapableGraph {
         block,
         parents: vec![(get_dummy_block_id("b1"), 4777); THREAD_COUNT as usize],
         is_final: true,
+        body_omitted: false,
     };
 
     let boot_graph = BootstrapableGraph {
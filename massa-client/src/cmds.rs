@@ -1,6 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::display::Output;
+use crate::payouts::{
+    send_batch_payout, BatchPayout, FeeStrategy, DEFAULT_PAYOUT_OPERATIONS_PER_SLOT,
+};
 use crate::{client_warning, rpc_error};
 use anyhow::{anyhow, bail, Result};
 use console::style;
@@ -12,7 +15,7 @@ use massa_api_exports::{
 };
 use massa_models::node::NodeId;
 use massa_models::prehash::PreHashMap;
-use massa_models::timeslots::get_current_latest_block_slot;
+use massa_models::timeslots::{get_block_slot_timestamp, get_current_latest_block_slot};
 use massa_models::{
     address::Address,
     amount::Amount,
@@ -114,6 +117,27 @@ pub enum Command {
     )]
     node_stop_staking,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "tracks the staking of the given addresses, whose keys are on other machines"
+    )]
+    node_add_watch_only_addresses,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "stops tracking the given watch-only addresses"
+    )]
+    node_remove_watch_only_addresses,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show watch-only addresses"
+    )]
+    node_get_watch_only_addresses,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address discord_id"),
@@ -142,6 +166,27 @@ pub enum Command {
     )]
     node_peers_whitelist,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the bytes received from and sent to each connected node, per message type"
+    )]
+    node_peers_bandwidth,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the messages received from and sent to all the nodes since the node started, per message type"
+    )]
+    node_protocol_traffic,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the latest commands and events of the protocol worker, with the time they spent queued"
+    )]
+    node_protocol_trace,
+
     #[strum(
         ascii_case_insensitive,
         props(pwd_not_needed = "true"),
@@ -156,6 +201,13 @@ pub enum Command {
     )]
     get_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ...", pwd_not_needed = "true"),
+        message = "get the block production reports of a list of addresses over the final cycles, signed by the node"
+    )]
+    get_production_reports,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address Key", pwd_not_needed = "true"),
@@ -263,6 +315,13 @@ pub enum Command {
     )]
     send_transaction,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SenderAddress PathToCsv Fee PathToProgressFile [OperationsPerSlot]"),
+        message = "send coins from a wallet address to each (address, amount) row of a CSV file, Fee being a fixed amount or a percentage of each amount (e.g. 0.5%), resumable using the progress file"
+    )]
+    wallet_batch_payout,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress PathToBytecode MaxGas MaxCoins Fee"),
@@ -535,6 +594,13 @@ impl Command {
                 }
             }
 
+            Command::node_get_watch_only_addresses => {
+                match client.private.get_watch_only_addresses().await {
+                    Ok(watch_only_addresses) => Ok(Box::new(watch_only_addresses)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::node_testnet_rewards_program_ownership_proof => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
@@ -581,6 +647,14 @@ impl Command {
                 }
             }
 
+            Command::get_production_reports => {
+                let addresses = parse_vec::<Address>(parameters)?;
+                match client.public.get_production_reports(addresses).await {
+                    Ok(reports) => Ok(Box::new(reports)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_datastore_entry => {
                 if parameters.len() != 2 {
                     bail!("invalid number of parameters");
@@ -779,6 +853,38 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_add_watch_only_addresses => {
+                if parameters.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                let addresses = parse_vec::<Address>(parameters)?;
+                match client.private.add_watch_only_addresses(addresses).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Addresses successfully added!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_remove_watch_only_addresses => {
+                if parameters.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                let addresses = parse_vec::<Address>(parameters)?;
+                match client.private.remove_watch_only_addresses(addresses).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Addresses successfully removed!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
             Command::wallet_generate_secret_key => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
@@ -978,6 +1084,29 @@ impl Command {
                 )
                 .await
             }
+            Command::wallet_batch_payout => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                if parameters.len() != 4 && parameters.len() != 5 {
+                    bail!("wrong number of parameters");
+                }
+                let payout = BatchPayout {
+                    sender_address: parameters[0].parse::<Address>()?,
+                    csv_path: parameters[1].parse::<PathBuf>()?,
+                    fee_strategy: parameters[2].parse::<FeeStrategy>()?,
+                    progress_path: parameters[3].parse::<PathBuf>()?,
+                    operations_per_slot: match parameters.get(4) {
+                        Some(count) => count.parse::<usize>()?,
+                        None => DEFAULT_PAYOUT_OPERATIONS_PER_SLOT,
+                    },
+                };
+
+                let operation_ids = send_batch_payout(client, wallet, &payout, json).await?;
+                if !json {
+                    println!("Sent operation IDs:");
+                }
+                Ok(Box::new(operation_ids))
+            }
             Command::when_episode_ends => {
                 let end = match client.public.get_status().await {
                     Ok(node_status) => node_status.config.end_timestamp,
@@ -1185,6 +1314,7 @@ impl Command {
                         target_function,
                         parameter,
                         max_gas,
+                        coins: None,
                         is_final,
                     })
                     .await
@@ -1368,6 +1498,22 @@ impl Command {
                     res
                 }
             }
+            Command::node_peers_bandwidth => {
+                match client.private.node_get_peers_bandwidth().await {
+                    Ok(peers_bandwidth) => Ok(Box::new(peers_bandwidth)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+            Command::node_protocol_traffic => {
+                match client.private.node_get_protocol_traffic().await {
+                    Ok(traffic) => Ok(Box::new(traffic)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+            Command::node_protocol_trace => match client.private.node_get_protocol_trace().await {
+                Ok(trace) => Ok(Box::new(trace)),
+                Err(e) => rpc_error!(e),
+            },
             Command::exit => {
                 std::process::exit(0);
             }
@@ -1397,14 +1543,24 @@ async fn send_operation(
         expire_period += 1;
     };
 
-    let op = wallet.create_operation(
-        Operation {
-            fee,
-            expire_period,
-            op,
-        },
-        addr,
-    )?;
+    let content = Operation {
+        fee,
+        expire_period,
+        op,
+    };
+    let mut preview = wallet.preview_operation(&content, addr)?;
+    preview.expire_timestamp = get_block_slot_timestamp(
+        cfg.thread_count,
+        cfg.t0,
+        cfg.genesis_timestamp,
+        Slot::new(expire_period, addr.get_thread(cfg.thread_count)),
+    )
+    .ok();
+    if !json {
+        println!("Signing operation:\n{}", preview);
+    }
+
+    let op = wallet.create_operation(content, addr)?;
 
     match client
         .public
@@ -1466,5 +1622,8 @@ where
 /// Display the help of all commands
 pub fn help() {
     println!("HELP of Massa client (list of available commands):");
-    Command::iter().map(|c| c.help()).collect()
+    Command::iter().for_each(|c| c.help());
+    println!(
+        "Amounts and fees are in MAS, or suffixed with their unit: e.g. 1.5 = 1.5MAS = 1500000000nMAS"
+    );
 }
@@ -4,8 +4,12 @@ use crate::cmds::ExtendedWallet;
 use console::style;
 use erased_serde::{Serialize, Serializer};
 use massa_api_exports::{
-    address::AddressInfo, block::BlockInfo, datastore::DatastoreEntryOutput,
-    endorsement::EndorsementInfo, execution::ExecuteReadOnlyResponse, node::NodeStatus,
+    address::AddressInfo,
+    block::BlockInfo,
+    datastore::DatastoreEntryOutput,
+    endorsement::EndorsementInfo,
+    execution::ExecuteReadOnlyResponse,
+    node::{NodeBandwidthInfo, NodeStatus, NodeTrafficInfo, ProductionReport, ProtocolTraceEvent},
     operation::OperationInfo,
 };
 use massa_models::composite::PubkeySig;
@@ -246,6 +250,7 @@ impl Output for NodeStatus {
         println!();
 
         self.consensus_stats.pretty_print();
+        println!("{}", self.finality_stats);
 
         println!("Pool stats:");
         println!(
@@ -261,6 +266,10 @@ impl Output for NodeStatus {
         self.network_stats.pretty_print();
         self.execution_stats.pretty_print();
 
+        if let Some(staking_summary) = &self.staking_summary {
+            println!("{}", staking_summary);
+        }
+
         if !self.connected_nodes.is_empty() {
             println!("Connected nodes:");
             for (node_id, (ip_addr, is_outgoing)) in &self.connected_nodes {
@@ -294,6 +303,27 @@ impl Output for ExecutionStats {
             "\tFinal executed operation count: {}",
             Style::Protocol.style(self.final_executed_operations_count)
         );
+        println!(
+            "\tFinal applied/failed operation count: {}/{}",
+            Style::Good.style(self.final_applied_operations_count),
+            Style::Bad.style(self.final_failed_operations_count)
+        );
+        println!(
+            "\tAverage gas per final block: {}",
+            Style::Protocol.style(self.average_gas_per_block)
+        );
+        println!(
+            "\tFinal executed async message count: {}",
+            Style::Protocol.style(self.final_executed_async_messages_count)
+        );
+        let format_ratio = |ratio: &Option<f64>| {
+            ratio.map_or_else(|| "-".to_string(), |ratio| format!("{:.2}%", ratio * 100.0))
+        };
+        println!(
+            "\tModule cache hit ratio: RAM {} / disk {}",
+            Style::Protocol.style(format_ratio(&self.module_cache_ram_hit_ratio)),
+            Style::Protocol.style(format_ratio(&self.module_cache_hd_hit_ratio))
+        );
         println!(
             "\tActive cursor: {}",
             Style::Protocol.style(self.active_cursor)
@@ -302,6 +332,14 @@ impl Output for ExecutionStats {
             "\tFinal cursor: {}",
             Style::Protocol.style(self.final_cursor)
         );
+        println!(
+            "\tEarliest final event slot: {}",
+            Style::Protocol.style(self.earliest_final_event_slot)
+        );
+        println!(
+            "\tEarliest executed operation slot: {}",
+            Style::Protocol.style(self.earliest_executed_op_slot)
+        );
     }
 }
 
@@ -483,6 +521,36 @@ impl Output for Vec<EndorsementInfo> {
     }
 }
 
+impl Output for Vec<NodeBandwidthInfo> {
+    fn pretty_print(&self) {
+        for node_bandwidth in self {
+            println!("{}", node_bandwidth);
+        }
+    }
+}
+
+impl Output for NodeTrafficInfo {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for Vec<ProtocolTraceEvent> {
+    fn pretty_print(&self) {
+        for event in self {
+            println!("{}", event);
+        }
+    }
+}
+
+impl Output for Vec<ProductionReport> {
+    fn pretty_print(&self) {
+        for report in self {
+            println!("{}", report);
+        }
+    }
+}
+
 impl Output for Vec<IpAddr> {
     fn pretty_print(&self) {
         for ips in self {
@@ -8,6 +8,7 @@ use atty::Stream;
 use cmds::Command;
 use console::style;
 use dialoguer::Password;
+use display::Output;
 use massa_sdk::{Client, ClientConfig, HttpConfig};
 use massa_wallet::Wallet;
 use serde::Serialize;
@@ -19,6 +20,7 @@ use structopt::StructOpt;
 
 mod cmds;
 mod display;
+mod payouts;
 mod repl;
 mod settings;
 
@@ -59,6 +61,9 @@ struct Args {
     /// Enable a mode where input/output are serialized as JSON
     #[structopt(short = "j", long = "json")]
     json: bool,
+    /// Run the commands read from stdin, one per line, and stop at the first failing one
+    #[structopt(short = "s", long = "script")]
+    script: bool,
     #[structopt(short = "p", long = "pwd")]
     /// Wallet password
     password: Option<String>,
@@ -86,6 +91,36 @@ pub(crate) fn ask_password(wallet_path: &Path) -> String {
     }
 }
 
+/// Prints the output of a command, or its error, as JSON if `json` is set.
+/// Returns whether the command succeeded.
+pub(crate) fn print_command_result(result: Result<Box<dyn Output>>, json: bool) -> bool {
+    match result {
+        Ok(output) => {
+            if json {
+                output
+                    .stdout_json()
+                    .expect("fail to serialize to JSON command output");
+                println!();
+            } else {
+                output.pretty_print();
+            }
+            true
+        }
+        Err(e) => {
+            if json {
+                let error = serde_json::to_string(&JsonError {
+                    error: format!("{:?}", e),
+                })
+                .expect("fail to serialize to JSON error");
+                println!("{}", error);
+            } else {
+                println!("{}", style(format!("Error: {}", e)).red());
+            }
+            false
+        }
+    }
+}
+
 #[paw::main]
 fn main(args: Args) -> anyhow::Result<()> {
     let tokio_rt = tokio::runtime::Builder::new_multi_thread()
@@ -161,7 +196,12 @@ async fn run(args: Args) -> Result<()> {
         &http_config,
     )
     .await?;
-    if atty::is(Stream::Stdout) && args.command == Command::help && !args.json {
+    if args.script {
+        // Scripting mode: exit with an error code if a command failed
+        if !repl::run_script(&mut client, &args.wallet, args.password, args.json).await? {
+            std::process::exit(1);
+        }
+    } else if atty::is(Stream::Stdout) && args.command == Command::help && !args.json {
         // Interactive mode
         repl::run(&mut client, &args.wallet, args.password).await?;
     } else {
@@ -182,31 +222,12 @@ async fn run(args: Args) -> Result<()> {
             false => None,
         };
 
-        match args
+        let result = args
             .command
             .run(&mut client, &mut wallet_opt, &args.parameters, args.json)
-            .await
-        {
-            Ok(output) => {
-                if args.json {
-                    output
-                        .stdout_json()
-                        .expect("fail to serialize to JSON command output")
-                } else {
-                    output.pretty_print();
-                }
-            }
-            Err(e) => {
-                if args.json {
-                    let error = serde_json::to_string(&JsonError {
-                        error: format!("{:?}", e),
-                    })
-                    .expect("fail to serialize to JSON error");
-                    println!("{}", error);
-                } else {
-                    println!("{}", style(format!("Error: {}", e)).red());
-                }
-            }
+            .await;
+        if !print_command_result(result, args.json) {
+            std::process::exit(1);
         }
     }
     Ok(())
@@ -0,0 +1,496 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Batch payouts: sends one transfer operation per row of a CSV file of `(address, amount)`,
+//! for example to distribute staking rewards.
+//!
+//! Operations are sent by chunks, one chunk per slot of the sender thread,
+//! and every transfer is recorded in a progress file before being sent so that an interrupted payout can be resumed
+//! without paying a row twice.
+
+use crate::rpc_error;
+use anyhow::{anyhow, bail, Result};
+use massa_api_exports::operation::{OperationInfo, OperationInput};
+use massa_hash::Hash;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    operation::{Operation, OperationId, OperationType},
+    slot::Slot,
+    timeslots::{get_block_slot_timestamp, get_current_latest_block_slot},
+};
+use massa_sdk::Client;
+use massa_time::MassaTime;
+use massa_wallet::Wallet;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::io::AsyncWriteExt;
+
+/// Default number of transfers sent per slot of the sender thread
+pub const DEFAULT_PAYOUT_OPERATIONS_PER_SLOT: usize = 100;
+
+/// How the fee of each transfer of a payout is computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeStrategy {
+    /// the same fee for every transfer
+    Fixed(Amount),
+    /// a percentage of the transferred amount (e.g. `0.5%`)
+    Proportional(Amount),
+}
+
+impl FromStr for FeeStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.strip_suffix('%') {
+            Some(percent) => Ok(FeeStrategy::Proportional(
+                percent
+                    .parse::<Amount>()
+                    .map_err(|e| anyhow!("invalid fee percentage \"{}\": {}", s, e))?,
+            )),
+            None => Ok(FeeStrategy::Fixed(
+                s.parse::<Amount>()
+                    .map_err(|e| anyhow!("invalid fee \"{}\": {}", s, e))?,
+            )),
+        }
+    }
+}
+
+impl FeeStrategy {
+    /// Computes the fee of a transfer of a given amount
+    pub fn fee_for(&self, amount: Amount) -> Result<Amount> {
+        match self {
+            FeeStrategy::Fixed(fee) => Ok(*fee),
+            FeeStrategy::Proportional(percent) => {
+                // amount * (percent / 100), computed on raw values
+                let hundred = Amount::const_init(100, 0).to_raw() as u128;
+                let fee = (amount.to_raw() as u128) * (percent.to_raw() as u128) / hundred;
+                Ok(Amount::from_raw(u64::try_from(fee).map_err(|_| {
+                    anyhow!("overflow when computing the fee of {}", amount)
+                })?))
+            }
+        }
+    }
+}
+
+/// A transfer of a payout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayoutEntry {
+    /// index of the row in the CSV file (data rows only, starting at 0)
+    pub row: usize,
+    /// recipient of the transfer
+    pub recipient_address: Address,
+    /// transferred amount
+    pub amount: Amount,
+    /// identifier of the row in the progress file, see `payout_row_key`
+    pub key: Hash,
+}
+
+/// Identifies a row of a payout by its content rather than by its position,
+/// so that the progress of a payout still applies after rows are added, removed or reordered in the CSV file.
+/// Identical rows are told apart by their rank among them.
+fn payout_row_key(recipient_address: &Address, amount: &Amount, rank: u64) -> Hash {
+    Hash::compute_from(format!("{},{},{}", recipient_address, amount, rank).as_bytes())
+}
+
+/// Parses a payout CSV file with one `address,amount` row per transfer.
+/// Empty lines, lines starting with `#` and an `address,amount` header are ignored.
+pub fn parse_payout_csv(content: &str) -> Result<Vec<PayoutEntry>> {
+    let mut entries = Vec::new();
+    let mut ranks: HashMap<(Address, u64), u64> = HashMap::new();
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if entries.is_empty() && fields.first() == Some(&"address") {
+            continue;
+        }
+        let [address, amount] = fields[..] else {
+            bail!(
+                "line {}: expected \"address,amount\", got \"{}\"",
+                line_index + 1,
+                line
+            );
+        };
+        let recipient_address: Address = address
+            .parse()
+            .map_err(|e| anyhow!("line {}: invalid address: {}", line_index + 1, e))?;
+        let amount: Amount = amount
+            .parse()
+            .map_err(|e| anyhow!("line {}: invalid amount: {}", line_index + 1, e))?;
+        let rank = ranks
+            .entry((recipient_address, amount.to_raw()))
+            .or_default();
+        entries.push(PayoutEntry {
+            row: entries.len(),
+            recipient_address,
+            amount,
+            key: payout_row_key(&recipient_address, &amount, *rank),
+        });
+        *rank += 1;
+    }
+    Ok(entries)
+}
+
+/// A transfer recorded in the progress file before its operation is sent.
+/// Each line of the progress file is `row_key,operation_id,expire_period,address,amount`,
+/// the address and the amount being only there for the reader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProgressRecord {
+    /// key of the paid row, see `payout_row_key`
+    row_key: Hash,
+    /// operation paying the row
+    operation_id: OperationId,
+    /// last period in which the operation can be included
+    expire_period: u64,
+}
+
+impl ProgressRecord {
+    fn to_line(&self, entry: &PayoutEntry) -> String {
+        format!(
+            "{},{},{},{},{}\n",
+            self.row_key,
+            self.operation_id,
+            self.expire_period,
+            entry.recipient_address,
+            entry.amount
+        )
+    }
+
+    fn from_line(line: &str) -> Result<Self> {
+        let invalid = || anyhow!("invalid progress line \"{}\"", line);
+        let mut fields = line.split(',').map(str::trim);
+        let mut next_field = || fields.next().ok_or_else(invalid);
+        Ok(ProgressRecord {
+            row_key: next_field()?.parse().map_err(|_| invalid())?,
+            operation_id: next_field()?.parse().map_err(|_| invalid())?,
+            expire_period: next_field()?.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Reads the transfers recorded in a progress file
+async fn read_progress(path: &Path) -> Result<Vec<ProgressRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    tokio::fs::read_to_string(path)
+        .await?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(ProgressRecord::from_line)
+        .collect()
+}
+
+/// What the node knows of the operation of a recorded transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecordedOperationState {
+    /// in the pool or in blocks of the node, not executed yet
+    Pending,
+    /// executed, successfully or not
+    Executed(bool),
+}
+
+impl From<&OperationInfo> for RecordedOperationState {
+    fn from(info: &OperationInfo) -> Self {
+        match info.op_exec_status {
+            Some(success) => RecordedOperationState::Executed(success),
+            None => RecordedOperationState::Pending,
+        }
+    }
+}
+
+/// Finds the rows of a payout that still have to be paid, given the transfers recorded in the progress file,
+/// the state of their operations known by the node (missing if unknown) and the latest period.
+///
+/// A row is paid if one of its operations was executed successfully or may still be.
+/// It is paid again if its operations all failed or expired without the node knowing them.
+/// An operation unknown to the node that did not expire may still be included in a block:
+/// the payout can then only be resumed once it expired.
+fn reconcile_progress(
+    entries: Vec<PayoutEntry>,
+    records: &[ProgressRecord],
+    operation_states: &HashMap<OperationId, RecordedOperationState>,
+    latest_period: u64,
+) -> Result<Vec<PayoutEntry>> {
+    let mut records_by_row: HashMap<Hash, Vec<&ProgressRecord>> = HashMap::new();
+    for record in records {
+        records_by_row
+            .entry(record.row_key)
+            .or_default()
+            .push(record);
+    }
+    let mut pending = Vec::new();
+    for entry in entries {
+        let Some(row_records) = records_by_row.get(&entry.key) else {
+            pending.push(entry);
+            continue;
+        };
+        let paid = row_records.iter().any(|record| {
+            matches!(
+                operation_states.get(&record.operation_id),
+                Some(RecordedOperationState::Pending)
+                    | Some(RecordedOperationState::Executed(true))
+            )
+        });
+        if paid {
+            continue;
+        }
+        if let Some(record) = row_records.iter().find(|record| {
+            !operation_states.contains_key(&record.operation_id)
+                && record.expire_period >= latest_period
+        }) {
+            bail!(
+                "the status of the transfer of {} to {} (operation {}) is unknown: resume the payout after period {}, once it cannot be included anymore",
+                entry.amount,
+                entry.recipient_address,
+                record.operation_id,
+                record.expire_period
+            );
+        }
+        pending.push(entry);
+    }
+    Ok(pending)
+}
+
+/// Parameters of a batch payout
+#[derive(Debug, Clone)]
+pub struct BatchPayout {
+    /// wallet address paying the transfers
+    pub sender_address: Address,
+    /// path of the CSV file listing the transfers
+    pub csv_path: PathBuf,
+    /// fee of the transfers
+    pub fee_strategy: FeeStrategy,
+    /// path of the progress file used to resume the payout
+    pub progress_path: PathBuf,
+    /// maximum number of transfers sent per slot of the sender thread
+    pub operations_per_slot: usize,
+}
+
+/// Generates, signs and sends the transfers of a payout CSV file.
+/// Each transfer is recorded in the progress file before being sent, and the rows
+/// whose recorded transfers were executed or may still be are skipped, see `reconcile_progress`.
+///
+/// Returns the IDs of the operations sent by this call.
+pub async fn send_batch_payout(
+    client: &Client,
+    wallet: &Wallet,
+    payout: &BatchPayout,
+    json: bool,
+) -> Result<Vec<OperationId>> {
+    let BatchPayout {
+        sender_address,
+        csv_path,
+        fee_strategy,
+        progress_path,
+        operations_per_slot,
+    } = payout;
+    if *operations_per_slot == 0 {
+        bail!("the number of operations per slot must be positive");
+    }
+    let entries = parse_payout_csv(&tokio::fs::read_to_string(csv_path).await?)?;
+    let records = read_progress(progress_path).await?;
+
+    let cfg = match client.public.get_status().await {
+        Ok(node_status) => node_status,
+        Err(e) => rpc_error!(e),
+    }
+    .config;
+    let sender_thread = sender_address.get_thread(cfg.thread_count);
+
+    // ask the node what became of the recorded transfers
+    let operation_states: HashMap<OperationId, RecordedOperationState> = if records.is_empty() {
+        HashMap::new()
+    } else {
+        let operation_ids = records.iter().map(|record| record.operation_id).collect();
+        match client.public.get_operations(operation_ids).await {
+            Ok(operations) => operations
+                .iter()
+                .map(|info| (info.id, RecordedOperationState::from(info)))
+                .collect(),
+            Err(e) => rpc_error!(e),
+        }
+    };
+    let latest_period =
+        get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp)?
+            .map_or(0, |slot| slot.period);
+    let entry_count = entries.len();
+    let pending = reconcile_progress(entries, &records, &operation_states, latest_period)?;
+    if !json {
+        println!(
+            "{} transfers already sent, {} remaining",
+            entry_count - pending.len(),
+            pending.len()
+        );
+    }
+
+    let mut progress_file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(progress_path)
+        .await?;
+    let mut operation_ids = Vec::with_capacity(pending.len());
+    let mut previous_sender_slot: Option<Slot> = None;
+    for chunk in pending.chunks(*operations_per_slot) {
+        // target the next slot of the sender thread, and a different one for each chunk
+        // so that each chunk can fit in a different block
+        let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp)?
+            .unwrap_or_else(|| Slot::new(0, 0));
+        let mut next_sender_slot = if slot.thread < sender_thread {
+            Slot::new(slot.period, sender_thread)
+        } else {
+            Slot::new(slot.period + 1, sender_thread)
+        };
+        if let Some(previous_slot) = previous_sender_slot {
+            if next_sender_slot <= previous_slot {
+                next_sender_slot = Slot::new(previous_slot.period + 1, sender_thread);
+            }
+            // wait for the slot of the previous chunk to be produced before sending the next chunk
+            let previous_slot_timestamp = get_block_slot_timestamp(
+                cfg.thread_count,
+                cfg.t0,
+                cfg.genesis_timestamp,
+                previous_slot,
+            )?;
+            let wait = previous_slot_timestamp.saturating_sub(MassaTime::now()?);
+            tokio::time::sleep(wait.to_duration()).await;
+        }
+        let expire_period = next_sender_slot.period + cfg.operation_validity_periods;
+
+        let mut operations = Vec::with_capacity(chunk.len());
+        let mut progress = String::new();
+        for entry in chunk {
+            let op = wallet.create_operation(
+                Operation {
+                    fee: fee_strategy.fee_for(entry.amount)?,
+                    expire_period,
+                    op: OperationType::Transaction {
+                        recipient_address: entry.recipient_address,
+                        amount: entry.amount,
+                    },
+                },
+                *sender_address,
+            )?;
+            let record = ProgressRecord {
+                row_key: entry.key,
+                operation_id: op.id,
+                expire_period,
+            };
+            progress.push_str(&record.to_line(entry));
+            operations.push(OperationInput {
+                creator_public_key: op.content_creator_pub_key,
+                serialized_content: op.serialized_data,
+                signature: op.signature,
+            });
+        }
+
+        // record the chunk before sending it, so that an interruption during the sending
+        // cannot lead to paying its rows again on resume
+        progress_file.write_all(progress.as_bytes()).await?;
+        progress_file.sync_data().await?;
+
+        let chunk_operation_ids = match client.public.send_operations(operations).await {
+            Ok(operation_ids) => operation_ids,
+            Err(e) => rpc_error!(e),
+        };
+
+        if !json {
+            println!(
+                "Sent {} transfers in slot {} ({}/{})",
+                chunk_operation_ids.len(),
+                next_sender_slot,
+                operation_ids.len() + chunk_operation_ids.len(),
+                pending.len()
+            );
+        }
+        operation_ids.extend(chunk_operation_ids);
+        previous_sender_slot = Some(next_sender_slot);
+    }
+
+    Ok(operation_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::secure_share::Id;
+    use massa_signature::KeyPair;
+
+    fn address() -> Address {
+        Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    fn record(entry: &PayoutEntry, seed: &[u8], expire_period: u64) -> ProgressRecord {
+        ProgressRecord {
+            row_key: entry.key,
+            operation_id: OperationId::new(Hash::compute_from(seed)),
+            expire_period,
+        }
+    }
+
+    #[test]
+    fn test_payout_row_keys() {
+        let (a, b) = (address(), address());
+        let entries =
+            parse_payout_csv(&format!("address,amount\n{a},1\n# comment\n{b},2\n{a},1\n")).unwrap();
+        assert_eq!(entries.len(), 3);
+        // identical rows have different keys
+        assert_ne!(entries[0].key, entries[2].key);
+
+        // the keys do not depend on the position of the rows
+        let edited = parse_payout_csv(&format!("{b},3\n{b},2\n{a},1\n{a},1\n")).unwrap();
+        assert_eq!(edited[1].key, entries[1].key);
+        assert_eq!(edited[2].key, entries[0].key);
+        assert_eq!(edited[3].key, entries[2].key);
+        assert!(entries.iter().all(|entry| entry.key != edited[0].key));
+    }
+
+    #[test]
+    fn test_progress_line_round_trip() {
+        let entries = parse_payout_csv(&format!("{},1.5", address())).unwrap();
+        let record = record(&entries[0], b"op", 42);
+        let line = record.to_line(&entries[0]);
+        assert_eq!(ProgressRecord::from_line(line.trim_end()).unwrap(), record);
+        assert!(ProgressRecord::from_line("0,invalid,42").is_err());
+    }
+
+    #[test]
+    fn test_reconcile_progress() {
+        let entries = parse_payout_csv(&format!(
+            "{},1\n{},2\n{},3\n",
+            address(),
+            address(),
+            address()
+        ))
+        .unwrap();
+        let executed = record(&entries[0], b"executed", 10);
+        let failed = record(&entries[1], b"failed", 10);
+        let expired = record(&entries[2], b"expired", 10);
+        let operation_states = HashMap::from([
+            (
+                executed.operation_id,
+                RecordedOperationState::Executed(true),
+            ),
+            (failed.operation_id, RecordedOperationState::Executed(false)),
+        ]);
+        let records = vec![executed.clone(), failed.clone(), expired.clone()];
+
+        // only the failed and expired transfers are sent again
+        let pending = reconcile_progress(entries.clone(), &records, &operation_states, 11).unwrap();
+        assert_eq!(pending, entries[1..].to_vec());
+
+        // an unknown transfer that did not expire may still be included
+        assert!(reconcile_progress(entries.clone(), &records, &operation_states, 10).is_err());
+
+        // a row is paid once one of its transfers may be executed
+        let resent = record(&entries[1], b"resent", 20);
+        let mut operation_states = operation_states;
+        operation_states.insert(resent.operation_id, RecordedOperationState::Pending);
+        let records = vec![executed, failed, expired, resent];
+        let pending = reconcile_progress(entries.clone(), &records, &operation_states, 11).unwrap();
+        assert_eq!(pending, entries[2..].to_vec());
+    }
+}
@@ -3,6 +3,7 @@
 use crate::ask_password;
 use crate::cmds::Command;
 use crate::massa_fancy_ascii_art_logo;
+use crate::print_command_result;
 use crate::settings::SETTINGS;
 use anyhow::Result;
 use console::style;
@@ -209,3 +210,45 @@ impl Completer for MassaCompleter {
         }
     }
 }
+
+/// Runs the commands read from stdin, one per line, until the first failing one.
+/// Empty lines and lines starting with `#` are ignored.
+/// Returns whether all the commands succeeded.
+pub(crate) async fn run_script(
+    client: &mut Client,
+    wallet_path: &Path,
+    args_password: Option<String>,
+    json: bool,
+) -> Result<bool> {
+    let mut wallet_opt = None;
+    for line in std::io::stdin().lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let input: Vec<String> =
+            group_parameters(line.split_whitespace().map(|x| x.to_string()).collect());
+        let result = match input[0].parse::<Command>() {
+            Ok(command) => {
+                if command.is_pwd_needed() && wallet_opt.is_none() {
+                    let password = match (args_password.clone(), env::var("MASSA_CLIENT_PASSWORD"))
+                    {
+                        (Some(pwd), _) => pwd,
+                        (_, Ok(pwd)) => pwd,
+                        _ => ask_password(wallet_path),
+                    };
+                    wallet_opt = Some(Wallet::new(wallet_path.to_path_buf(), password)?);
+                }
+                command
+                    .run(client, &mut wallet_opt, &input[1..], json)
+                    .await
+            }
+            Err(_) => Err(anyhow::anyhow!("command not found: {}", input[0])),
+        };
+        if !print_command_result(result, json) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
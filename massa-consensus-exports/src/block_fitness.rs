@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// Fitness of an active block and progress of its finalization
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BlockFitnessInfo {
+    /// fitness of the block: 1 plus its number of endorsements
+    pub fitness: u64,
+    /// number of endorsements included in the block
+    pub endorsement_count: u64,
+    /// total fitness of the descendants of the block in the clique of highest fitness containing it.
+    /// The block becomes final when it exceeds `finality_threshold`. None if the block is already final
+    pub descendants_fitness: Option<u64>,
+    /// part of `descendants_fitness` brought by the endorsements included in the descendants
+    pub descendants_endorsement_fitness: Option<u64>,
+    /// fitness the descendants of a block must exceed for the block to become final
+    pub finality_threshold: u64,
+}
@@ -88,6 +88,22 @@ impl StorageOrBlock {
         }
     }
 
+    /// Return the number of endorsements included in the underlying block
+    pub fn endorsement_count(&self, block_id: &BlockId) -> usize {
+        match self {
+            StorageOrBlock::Storage(storage) => storage
+                .read_blocks()
+                .get(block_id)
+                .expect("block absent from its own storage")
+                .content
+                .header
+                .content
+                .endorsements
+                .len(),
+            StorageOrBlock::Block(block) => block.content.header.content.endorsements.len(),
+        }
+    }
+
     /// Convert any StorageOrBlock variant into a StorageOrBlock::Block variant.
     /// This effectively drops the operations of the block.
     pub fn strip_to_block(&mut self, block_id: &BlockId) {
@@ -1,4 +1,6 @@
+use crate::block_fitness::BlockFitnessInfo;
 use crate::block_graph_export::BlockGraphExport;
+use crate::finality::FinalityStats;
 use crate::{bootstrapable_graph::BootstrapableGraph, error::ConsensusError};
 use massa_models::prehash::PreHashSet;
 use massa_models::streaming_step::StreamingStep;
@@ -33,6 +35,15 @@ pub trait ConsensusController: Send + Sync {
     /// The statuses of the blocks sorted by the order of the input list
     fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus>;
 
+    /// Get the fitness and the finalization progress of a list of blocks
+    ///
+    /// # Arguments
+    /// * `ids`: the list of block ids to get the fitness of
+    ///
+    /// # Returns
+    /// The fitness infos of the blocks sorted by the order of the input list, None for the blocks that are not active
+    fn get_block_fitness_infos(&self, ids: &[BlockId]) -> Vec<Option<BlockFitnessInfo>>;
+
     /// Get all the cliques of the graph
     ///
     /// # Returns
@@ -70,6 +81,13 @@ pub trait ConsensusController: Send + Sync {
     /// The stats of the consensus
     fn get_stats(&self) -> Result<ConsensusStats, ConsensusError>;
 
+    /// Get the latest final block of each thread and the finalization latency stats
+    ///
+    /// # Returns
+    /// The latest final period of each thread with the time it became final,
+    /// and the finalization latency stats over the stats timespan
+    fn get_finality_stats(&self) -> Result<FinalityStats, ConsensusError>;
+
     /// Get the best parents for the next block to be produced
     ///
     /// # Returns
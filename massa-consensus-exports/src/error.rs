@@ -2,6 +2,7 @@
 use displaydoc::Display;
 use massa_execution_exports::ExecutionError;
 use massa_models::error::ModelsError;
+use massa_models::error_code::{ErrorCategory, ErrorCode, ErrorCodeInfo};
 use massa_protocol_exports::ProtocolError;
 use massa_time::TimeError;
 use std::array::TryFromSliceError;
@@ -72,3 +73,28 @@ pub enum LedgerError {
     /// serde error
     SerdeError(#[from] serde_json::Error),
 }
+
+impl ErrorCode for ConsensusError {
+    fn error_code(&self) -> ErrorCodeInfo {
+        let index = match self {
+            ConsensusError::ExecutionError(err) => return err.error_code(),
+            ConsensusError::ModelsError(err) => return err.error_code(),
+            ConsensusError::ProtocolError(err) => return err.error_code(),
+            ConsensusError::SerializationError(_) => 1,
+            ConsensusError::GenesisCreationError(_) => 2,
+            ConsensusError::MissingBlock(_) => 3,
+            ConsensusError::MissingOperation(_) => 4,
+            ConsensusError::ContainerInconsistency(_) => 5,
+            ConsensusError::FitnessOverflow => 6,
+            ConsensusError::InvalidLedgerChange(_) => 7,
+            ConsensusError::IOError(_) => 8,
+            ConsensusError::SerdeError(_) => 9,
+            ConsensusError::PosCycleUnavailable(_) => 10,
+            ConsensusError::LedgerError(_) => 11,
+            ConsensusError::MassaTimeError(_) => 12,
+            ConsensusError::TransactionError(_) => 13,
+            ConsensusError::InvalidTransition(_) => 14,
+        };
+        ErrorCodeInfo::new(ErrorCategory::Consensus, index)
+    }
+}
@@ -1,10 +1,10 @@
 use crate::{block_status::StorageOrBlock, error::ConsensusError};
 use massa_models::{
     active_block::ActiveBlock,
-    block::{Block, BlockDeserializer, BlockDeserializerArgs, SecureShareBlock},
+    block::{Block, BlockDeserializer, BlockDeserializerArgs, BlockSerializer, SecureShareBlock},
     block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer},
     prehash::PreHashMap,
-    secure_share::{SecureShareDeserializer, SecureShareSerializer},
+    secure_share::{SecureShare, SecureShareDeserializer, SecureShareSerializer},
 };
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
@@ -31,6 +31,8 @@ pub struct ExportActiveBlock {
     pub parents: Vec<(BlockId, u64)>,
     /// for example has its fitness reached the given threshold
     pub is_final: bool,
+    /// whether the body of the block (its list of operation ids) was omitted and only the header is known
+    pub body_omitted: bool,
 }
 
 impl ExportActiveBlock {
@@ -41,9 +43,36 @@ impl ExportActiveBlock {
             parents: a_block.parents.clone(),
             is_final: a_block.is_final,
             block: storage_or_block.clone_block(&a_block.block_id),
+            body_omitted: false,
         }
     }
 
+    /// Drop the body of the block, keeping only its header.
+    /// The block id and signature only depend on the header, so they remain valid.
+    pub fn strip_body(&mut self) -> Result<(), ConsensusError> {
+        if self.body_omitted {
+            return Ok(());
+        }
+        let block = Block {
+            header: self.block.content.header.clone(),
+            operations: Vec::new(),
+        };
+        let mut serialized_data = Vec::new();
+        BlockSerializer::new()
+            .serialize(&block, &mut serialized_data)
+            .map_err(|err| ConsensusError::SerializationError(err.to_string()))?;
+        self.block = SecureShare {
+            signature: self.block.signature,
+            content_creator_pub_key: self.block.content_creator_pub_key,
+            content_creator_address: self.block.content_creator_address,
+            id: self.block.id,
+            content: block,
+            serialized_data,
+        };
+        self.body_omitted = true;
+        Ok(())
+    }
+
     /// consuming conversion from `ExportActiveBlock` to `ActiveBlock`
     pub fn to_active_block(
         self,
@@ -105,6 +134,9 @@ impl Serializer<ExportActiveBlock> for ExportActiveBlockSerializer {
         // finality
         buffer.push(u8::from(value.is_final));
 
+        // body marker
+        buffer.push(u8::from(value.body_omitted));
+
         Ok(())
     }
 }
@@ -182,7 +214,8 @@ impl Deserializer<ExportActiveBlock> for ExportActiveBlockDeserializer {
     ///             )
     ///             .unwrap(),
     ///         ],
-    ///     denunciations: vec![],},
+    ///     denunciations: vec![],
+    ///     reward_recipient: None,},
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
     /// )
@@ -199,16 +232,30 @@ impl Deserializer<ExportActiveBlock> for ExportActiveBlockDeserializer {
     ///    block: full_block.clone(),
     ///    parents: vec![],
     ///    is_final: false,
+    ///    body_omitted: false,
     /// };
     ///
     /// let mut serialized = Vec::new();
     /// ExportActiveBlockSerializer::new().serialize(&export_active_block, &mut serialized).unwrap();
     /// let args = BlockDeserializerArgs {
     ///   thread_count: 32, max_operations_per_block: 16, endorsement_count: 1000,max_denunciations_per_block_header: 128,last_start_period: Some(0),};
-    /// let (rest, export_deserialized) = ExportActiveBlockDeserializer::new(args).deserialize::<DeserializeError>(&serialized).unwrap();
+    /// let deserializer = ExportActiveBlockDeserializer::new(args);
+    /// let (rest, export_deserialized) = deserializer.deserialize::<DeserializeError>(&serialized).unwrap();
     /// assert_eq!(export_deserialized.block.id, export_active_block.block.id);
     /// assert_eq!(export_deserialized.block.serialized_data, export_active_block.block.serialized_data);
     /// assert_eq!(rest.len(), 0);
+    ///
+    /// // the body of the block can be omitted, keeping it verifiable
+    /// let mut header_only = export_active_block.clone();
+    /// header_only.strip_body().unwrap();
+    /// let mut serialized = Vec::new();
+    /// ExportActiveBlockSerializer::new().serialize(&header_only, &mut serialized).unwrap();
+    /// let (rest, export_deserialized) = deserializer.deserialize::<DeserializeError>(&serialized).unwrap();
+    /// assert!(export_deserialized.body_omitted);
+    /// assert_eq!(export_deserialized.block.id, export_active_block.block.id);
+    /// assert!(export_deserialized.block.content.operations.is_empty());
+    /// export_deserialized.block.content.header.verify_signature().unwrap();
+    /// assert_eq!(rest.len(), 0);
     /// ```
     fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
         &self,
@@ -247,13 +294,21 @@ impl Deserializer<ExportActiveBlock> for ExportActiveBlockDeserializer {
                     "Failed is_final deserialization",
                     alt((value(true, tag(&[1])), value(false, tag(&[0])))),
                 ),
+                // body marker
+                context(
+                    "Failed body_omitted deserialization",
+                    alt((value(true, tag(&[1])), value(false, tag(&[0])))),
+                ),
             )),
         )
-        .map(|(block, parents, is_final)| ExportActiveBlock {
-            block,
-            parents,
-            is_final,
-        })
+        .map(
+            |(block, parents, is_final, body_omitted)| ExportActiveBlock {
+                block,
+                parents,
+                is_final,
+                body_omitted,
+            },
+        )
         .parse(buffer)
     }
 }
@@ -0,0 +1,81 @@
+use massa_models::block_id::BlockId;
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Latest final block of a thread
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ThreadFinality {
+    /// thread of the block
+    pub thread: u8,
+    /// id of the latest final block of the thread
+    pub block_id: BlockId,
+    /// period of the latest final block of the thread
+    pub period: u64,
+    /// time at which the block became final on this node.
+    /// None if the block was already final when the node started
+    pub finalized_at: Option<MassaTime>,
+}
+
+/// Latest final periods and finalization latency over the stats timespan.
+///
+/// The finalization latency of a block is the time elapsed between the start of its slot
+/// and the moment it became final on this node.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FinalityStats {
+    /// latest final block of each thread
+    pub threads: Vec<ThreadFinality>,
+    /// start of the timespan of the latency stats
+    pub start_timespan: MassaTime,
+    /// end of the timespan of the latency stats
+    pub end_timespan: MassaTime,
+    /// number of blocks that became final during the timespan
+    pub finalized_block_count: u64,
+    /// average finalization latency, None if no block became final during the timespan
+    pub average_latency: Option<MassaTime>,
+    /// median finalization latency
+    pub median_latency: Option<MassaTime>,
+    /// 95th percentile of the finalization latency
+    pub p95_latency: Option<MassaTime>,
+    /// highest finalization latency
+    pub max_latency: Option<MassaTime>,
+}
+
+impl std::fmt::Display for FinalityStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format_latency = |latency: &Option<MassaTime>| {
+            latency.map_or_else(|| "-".to_string(), |latency| format!("{} ms", latency))
+        };
+        writeln!(f, "Finality stats:")?;
+        writeln!(
+            f,
+            "\tStart stats timespan time: {}",
+            self.start_timespan.format_instant()
+        )?;
+        writeln!(
+            f,
+            "\tEnd stats timespan time: {}",
+            self.end_timespan.format_instant()
+        )?;
+        writeln!(f, "\tFinalized block count: {}", self.finalized_block_count)?;
+        writeln!(
+            f,
+            "\tFinalization latency: average {} / median {} / p95 {} / max {}",
+            format_latency(&self.average_latency),
+            format_latency(&self.median_latency),
+            format_latency(&self.p95_latency),
+            format_latency(&self.max_latency)
+        )?;
+        for thread in &self.threads {
+            write!(
+                f,
+                "\tThread {}: latest final period {} ({})",
+                thread.thread, thread.period, thread.block_id
+            )?;
+            match thread.finalized_at {
+                Some(time) => writeln!(f, " finalized at {}", time.format_instant())?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
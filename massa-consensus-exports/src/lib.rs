@@ -5,12 +5,15 @@ mod channels;
 mod controller_trait;
 mod settings;
 
+pub mod block_fitness;
 pub mod block_graph_export;
 pub mod block_status;
 pub mod bootstrapable_graph;
 pub mod error;
 pub mod events;
 pub mod export_active_block;
+pub mod finality;
+pub mod parent_selection;
 
 pub use channels::ConsensusChannels;
 pub use controller_trait::{ConsensusController, ConsensusManager};
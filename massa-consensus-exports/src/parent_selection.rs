@@ -0,0 +1,140 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Policies selecting the best parents of the next blocks among the blocks of the blockclique.
+//!
+//! The default policy selects, in each thread, the blockclique block with the highest period.
+//! Other policies are meant for research and private networks: they must still select parents
+//! that are valid for the other nodes, otherwise the produced blocks will be rejected.
+
+use std::fmt::Debug;
+
+use massa_models::{block_id::BlockId, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// A block that can be selected as best parent in its thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParentCandidate {
+    /// id of the block
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// number of endorsements included in the block
+    pub endorsement_count: usize,
+    /// fitness of the block
+    pub fitness: u64,
+}
+
+/// Rule used to choose between two candidates with the same score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParentTieBreak {
+    /// keep the candidate with the highest period
+    #[default]
+    HighestPeriod,
+    /// keep the candidate with the lowest block id
+    LowestBlockId,
+}
+
+impl ParentTieBreak {
+    /// Returns true if `candidate` wins the tie against `current`
+    pub fn prefers(&self, candidate: &ParentCandidate, current: &ParentCandidate) -> bool {
+        match self {
+            ParentTieBreak::HighestPeriod => candidate.slot.period > current.slot.period,
+            ParentTieBreak::LowestBlockId => candidate.block_id < current.block_id,
+        }
+    }
+}
+
+/// Selection of the best parent of a thread
+pub trait ParentSelectionPolicy: Debug + Send + Sync {
+    /// Score of a candidate: the candidate with the highest score is selected as best parent of its thread
+    fn score(&self, candidate: &ParentCandidate) -> u64;
+
+    /// Rule used to choose between candidates with the same score
+    fn tie_break(&self) -> ParentTieBreak;
+
+    /// Returns true if `candidate` should replace `current` as best parent of their thread
+    fn prefers(&self, candidate: &ParentCandidate, current: &ParentCandidate) -> bool {
+        let (candidate_score, current_score) = (self.score(candidate), self.score(current));
+        candidate_score > current_score
+            || (candidate_score == current_score && self.tie_break().prefers(candidate, current))
+    }
+}
+
+/// Built-in parent selection policies, selectable from the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParentSelectionRule {
+    /// select the candidate with the highest period (default rule of the network)
+    #[default]
+    LatestPeriod,
+    /// select the candidate including the most endorsements
+    MostEndorsements,
+}
+
+/// Parent selection policy built from the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConfiguredParentSelection {
+    /// scoring rule
+    pub rule: ParentSelectionRule,
+    /// tie-breaking rule
+    pub tie_break: ParentTieBreak,
+}
+
+impl ParentSelectionPolicy for ConfiguredParentSelection {
+    fn score(&self, candidate: &ParentCandidate) -> u64 {
+        match self.rule {
+            ParentSelectionRule::LatestPeriod => candidate.slot.period,
+            ParentSelectionRule::MostEndorsements => candidate.endorsement_count as u64,
+        }
+    }
+
+    fn tie_break(&self) -> ParentTieBreak {
+        self.tie_break
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::secure_share::Id;
+
+    fn candidate(seed: &[u8], period: u64, endorsement_count: usize) -> ParentCandidate {
+        ParentCandidate {
+            block_id: BlockId::new(Hash::compute_from(seed)),
+            slot: Slot::new(period, 0),
+            endorsement_count,
+            fitness: 1,
+        }
+    }
+
+    #[test]
+    fn test_configured_parent_selection() {
+        let older = candidate(b"older", 1, 16);
+        let newer = candidate(b"newer", 2, 3);
+
+        let default_policy = ConfiguredParentSelection::default();
+        assert!(default_policy.prefers(&newer, &older));
+        assert!(!default_policy.prefers(&older, &newer));
+        assert!(!default_policy.prefers(&newer, &newer));
+
+        let endorsements_policy = ConfiguredParentSelection {
+            rule: ParentSelectionRule::MostEndorsements,
+            tie_break: ParentTieBreak::HighestPeriod,
+        };
+        assert!(endorsements_policy.prefers(&older, &newer));
+
+        // same score: ties are broken by the configured rule
+        let equal = candidate(b"equal", 2, 16);
+        assert!(endorsements_policy.prefers(&equal, &older));
+        let by_id_policy = ConfiguredParentSelection {
+            rule: ParentSelectionRule::MostEndorsements,
+            tie_break: ParentTieBreak::LowestBlockId,
+        };
+        assert_eq!(
+            by_id_policy.prefers(&equal, &older),
+            equal.block_id < older.block_id
+        );
+    }
+}
@@ -2,6 +2,8 @@ use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 
+use crate::parent_selection::{ParentSelectionRule, ParentTieBreak};
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ConsensusConfig {
     /// Genesis timestamp
@@ -42,6 +44,8 @@ pub struct ConsensusConfig {
     pub channel_size: usize,
     /// size of a consensus bootstrap streaming part
     pub bootstrap_part_size: u64,
+    /// final blocks older than this number of periods behind the latest final block of their thread are bootstrapped without their body
+    pub bootstrap_full_block_periods: u64,
     /// whether broadcast is enabled
     pub broadcast_enabled: bool,
     /// blocks headers channel capacity
@@ -52,4 +56,8 @@ pub struct ConsensusConfig {
     pub broadcast_filled_blocks_channel_capacity: usize,
     /// last start period
     pub last_start_period: u64,
+    /// rule selecting the best parent of each thread
+    pub parent_selection_rule: ParentSelectionRule,
+    /// rule choosing between best parent candidates with the same score
+    pub parent_tie_break: ParentTieBreak,
 }
@@ -7,7 +7,10 @@ use massa_models::config::{
 };
 use massa_time::MassaTime;
 
-use crate::ConsensusConfig;
+use crate::{
+    parent_selection::{ParentSelectionRule, ParentTieBreak},
+    ConsensusConfig,
+};
 
 impl Default for ConsensusConfig {
     fn default() -> Self {
@@ -31,11 +34,14 @@ impl Default for ConsensusConfig {
             stats_timespan: MassaTime::from_millis(60000),
             channel_size: CHANNEL_SIZE,
             bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+            bootstrap_full_block_periods: 128,
             broadcast_enabled: true,
             broadcast_blocks_headers_channel_capacity: 128,
             broadcast_blocks_channel_capacity: 128,
             broadcast_filled_blocks_channel_capacity: 128,
             last_start_period: 0,
+            parent_selection_rule: ParentSelectionRule::LatestPeriod,
+            parent_tie_break: ParentTieBreak::HighestPeriod,
         }
     }
 }
@@ -14,8 +14,9 @@ use massa_storage::Storage;
 use massa_time::MassaTime;
 
 use crate::{
-    block_graph_export::BlockGraphExport, bootstrapable_graph::BootstrapableGraph,
-    error::ConsensusError, ConsensusController,
+    block_fitness::BlockFitnessInfo, block_graph_export::BlockGraphExport,
+    bootstrapable_graph::BootstrapableGraph, error::ConsensusError, finality::FinalityStats,
+    ConsensusController,
 };
 
 /// Test tool to mock graph controller responses
@@ -31,6 +32,10 @@ pub enum MockConsensusControllerMessage {
         block_ids: Vec<BlockId>,
         response_tx: mpsc::Sender<Vec<BlockGraphStatus>>,
     },
+    GetBlockFitnessInfos {
+        block_ids: Vec<BlockId>,
+        response_tx: mpsc::Sender<Vec<Option<BlockFitnessInfo>>>,
+    },
     GetBlockGraphStatuses {
         start_slot: Option<Slot>,
         end_slot: Option<Slot>,
@@ -56,6 +61,9 @@ pub enum MockConsensusControllerMessage {
     GetStats {
         response_tx: mpsc::Sender<Result<ConsensusStats, ConsensusError>>,
     },
+    GetFinalityStats {
+        response_tx: mpsc::Sender<Result<FinalityStats, ConsensusError>>,
+    },
     GetBestParents {
         response_tx: mpsc::Sender<Vec<(BlockId, u64)>>,
     },
@@ -105,6 +113,8 @@ mockall::mock! {
 
         fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus>;
 
+        fn get_block_fitness_infos(&self, ids: &[BlockId]) -> Vec<Option<BlockFitnessInfo>>;
+
         fn get_cliques(&self) -> Vec<Clique>;
 
         fn get_bootstrap_part(
@@ -122,6 +132,8 @@ mockall::mock! {
 
         fn get_stats(&self) -> Result<ConsensusStats, ConsensusError>;
 
+        fn get_finality_stats(&self) -> Result<FinalityStats, ConsensusError>;
+
         fn get_best_parents(&self) -> Vec<(BlockId, u64)>;
 
         fn get_blockclique_block_at_slot(&self, slot: Slot) -> Option<BlockId>;
@@ -200,6 +212,19 @@ impl ConsensusController for ConsensusControllerImpl {
         response_rx.recv().unwrap()
     }
 
+    fn get_block_fitness_infos(&self, ids: &[BlockId]) -> Vec<Option<BlockFitnessInfo>> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockConsensusControllerMessage::GetBlockFitnessInfos {
+                block_ids: ids.to_vec(),
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_cliques(&self) -> Vec<Clique> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -245,6 +270,16 @@ impl ConsensusController for ConsensusControllerImpl {
         response_rx.recv().unwrap()
     }
 
+    fn get_finality_stats(&self) -> Result<FinalityStats, ConsensusError> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockConsensusControllerMessage::GetFinalityStats { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_best_parents(&self) -> Vec<(BlockId, u64)> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -1,8 +1,9 @@
 use massa_channel::sender::MassaSender;
 use massa_consensus_exports::{
-    block_graph_export::BlockGraphExport, block_status::BlockStatus,
-    bootstrapable_graph::BootstrapableGraph, error::ConsensusError,
-    export_active_block::ExportActiveBlock, ConsensusChannels, ConsensusController,
+    block_fitness::BlockFitnessInfo, block_graph_export::BlockGraphExport,
+    block_status::BlockStatus, bootstrapable_graph::BootstrapableGraph, error::ConsensusError,
+    export_active_block::ExportActiveBlock, finality::FinalityStats, ConsensusChannels,
+    ConsensusController,
 };
 use massa_models::denunciation::DenunciationPrecursor;
 use massa_models::{
@@ -91,6 +92,17 @@ impl ConsensusController for ConsensusControllerImpl {
             .collect()
     }
 
+    /// Get the fitness and the finalization progress of a list of blocks.
+    ///
+    /// # Returns:
+    /// The fitness infos of the blocks, None for the blocks that are not active
+    fn get_block_fitness_infos(&self, ids: &[BlockId]) -> Vec<Option<BlockFitnessInfo>> {
+        let read_shared_state = self.shared_state.read();
+        ids.iter()
+            .map(|id| read_shared_state.get_block_fitness_info(id))
+            .collect()
+    }
+
     /// Get all the cliques possible in the block graph.
     ///
     /// # Returns:
@@ -173,7 +185,19 @@ impl ConsensusController for ConsensusControllerImpl {
                     _ => (),
                 }
                 if a_block.is_final {
-                    let export = ExportActiveBlock::from_active_block(a_block, storage_or_block);
+                    let mut export =
+                        ExportActiveBlock::from_active_block(a_block, storage_or_block);
+                    // old final blocks are sent header-only to keep the bootstrap payload small
+                    let (_, latest_final_period) =
+                        read_shared_state.latest_final_blocks_periods[a_block.slot.thread as usize];
+                    if read_shared_state.active_index_without_body.contains(b_id)
+                        || a_block.slot.period
+                            < latest_final_period.saturating_sub(
+                                read_shared_state.config.bootstrap_full_block_periods,
+                            )
+                    {
+                        export.strip_body()?;
+                    }
                     final_blocks.push(export);
                     retrieved_ids.insert(*b_id);
                 }
@@ -196,6 +220,11 @@ impl ConsensusController for ConsensusControllerImpl {
         self.shared_state.read().get_stats()
     }
 
+    /// Get the latest final block of each thread and the finalization latency stats
+    fn get_finality_stats(&self) -> Result<FinalityStats, ConsensusError> {
+        self.shared_state.read().get_finality_stats()
+    }
+
     /// Get the current best parents for a block creation
     ///
     /// # Returns:
@@ -31,7 +31,7 @@ mod manager;
 mod state;
 mod worker;
 
-pub use worker::start_consensus_worker;
+pub use worker::{start_consensus_worker, start_consensus_worker_with_parent_selection};
 
 #[cfg(test)]
 pub mod tests;
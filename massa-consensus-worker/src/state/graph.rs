@@ -10,8 +10,10 @@ use massa_models::{
     clique::Clique,
     prehash::PreHashSet,
     slot::Slot,
+    timeslots::get_block_slot_timestamp,
 };
 use massa_serialization::Serializer;
+use massa_time::MassaTime;
 
 use super::ConsensusState;
 
@@ -285,6 +287,7 @@ impl ConsensusState {
         add_block_id: &BlockId,
         final_blocks: PreHashSet<BlockId>,
     ) -> Result<(), ConsensusError> {
+        let now = MassaTime::now()?;
         for block_id in final_blocks.into_iter() {
             // remove from gi_head
             if let Some(other_incomps) = self.gi_head.remove(&block_id) {
@@ -327,7 +330,17 @@ impl ConsensusState {
                 {
                     self.latest_final_blocks_periods[final_block.slot.thread as usize] =
                         (block_id, final_block.slot.period);
+                    self.latest_final_blocks_times[final_block.slot.thread as usize] = Some(now);
                 }
+                // add to finalization latency stats
+                let slot_timestamp = get_block_slot_timestamp(
+                    self.config.thread_count,
+                    self.config.t0,
+                    self.config.genesis_timestamp,
+                    final_block.slot,
+                )?;
+                self.finalization_latency_stats
+                    .push_back((now, now.saturating_sub(slot_timestamp)));
                 // update new final blocks list
                 self.new_final_blocks.insert(block_id);
             } else {
@@ -1,12 +1,15 @@
 use std::{
     collections::{HashMap, VecDeque},
+    sync::Arc,
     vec,
 };
 
 use massa_consensus_exports::{
+    block_fitness::BlockFitnessInfo,
     block_graph_export::BlockGraphExport,
     block_status::{BlockStatus, ExportCompiledBlock, HeaderOrBlock, StorageOrBlock},
     error::ConsensusError,
+    parent_selection::ParentSelectionPolicy,
     ConsensusChannels, ConsensusConfig,
 };
 use massa_execution_exports::ExecutionBlockMetadata;
@@ -54,14 +57,20 @@ pub struct ConsensusState {
     pub max_cliques: Vec<Clique>,
     /// ids of active blocks without ops
     pub active_index_without_ops: PreHashSet<BlockId>,
+    /// ids of final blocks bootstrapped without their body, waiting for it to be backfilled from peers
+    pub active_index_without_body: PreHashSet<BlockId>,
     /// Save of latest periods
     pub save_final_periods: Vec<u64>,
     /// One (block id, period) per thread
     pub latest_final_blocks_periods: Vec<(BlockId, u64)>,
+    /// Time at which the latest final block of each thread became final, None if it was final at startup
+    pub latest_final_blocks_times: Vec<Option<MassaTime>>,
     /// All the blocks we know about and their status
     pub blocks_state: BlocksState,
     /// One `(block id, period)` per thread TODO not sure I understand the difference with `latest_final_blocks_periods`
     pub best_parents: Vec<(BlockId, u64)>,
+    /// Policy selecting the best parent of each thread among the blockclique blocks
+    pub parent_selection_policy: Arc<dyn ParentSelectionPolicy>,
     /// Blocks that need to be propagated
     pub to_propagate: PreHashMap<BlockId, Storage>,
     /// List of block ids we think are attack attempts
@@ -78,6 +87,8 @@ pub struct ConsensusState {
     pub protocol_blocks: VecDeque<(MassaTime, BlockId)>,
     /// Stale block timestamp
     pub stale_block_stats: VecDeque<MassaTime>,
+    /// Finalization latency stats `(time, latency)`: time elapsed between the start of the slot of a block and its finalization
+    pub finalization_latency_stats: VecDeque<(MassaTime, MassaTime)>,
     /// the time span considered for stats
     pub stats_history_timespan: MassaTime,
     /// the time span considered for desynchronization detection
@@ -192,6 +203,48 @@ impl ConsensusState {
         best_block_id
     }
 
+    /// Gets the fitness of an active block and the fitness of its descendants
+    /// in the clique of highest fitness containing it, which makes it final once above `delta_f0`
+    pub fn get_block_fitness_info(&self, block_id: &BlockId) -> Option<BlockFitnessInfo> {
+        let Some(BlockStatus::Active { a_block, .. }) = self.blocks_state.get(block_id) else {
+            return None;
+        };
+        let mut descendants_fitness = None;
+        let mut descendants_endorsement_fitness = None;
+        if !a_block.is_final {
+            if let Some(clique) = self
+                .max_cliques
+                .iter()
+                .filter(|clique| clique.block_ids.contains(block_id))
+                .max_by_key(|clique| clique.fitness)
+            {
+                let fitnesses: Vec<u64> = a_block
+                    .descendants
+                    .intersection(&clique.block_ids)
+                    .filter_map(|id| match self.blocks_state.get(id) {
+                        Some(BlockStatus::Active { a_block, .. }) => Some(a_block.fitness),
+                        _ => None,
+                    })
+                    .collect();
+                descendants_fitness = Some(fitnesses.iter().sum());
+                // the fitness of a block is 1 plus its number of endorsements
+                descendants_endorsement_fitness = Some(
+                    fitnesses
+                        .iter()
+                        .map(|fitness| fitness.saturating_sub(1))
+                        .sum(),
+                );
+            }
+        }
+        Some(BlockFitnessInfo {
+            fitness: a_block.fitness,
+            endorsement_count: a_block.fitness.saturating_sub(1),
+            descendants_fitness,
+            descendants_endorsement_fitness,
+            finality_threshold: self.config.delta_f0,
+        })
+    }
+
     pub fn get_block_status(&self, block_id: &BlockId) -> BlockGraphStatus {
         match self.blocks_state.get(block_id) {
             None => BlockGraphStatus::NotFound,
@@ -518,6 +571,16 @@ impl ConsensusState {
                 }
             }
         }
+        // lazily backfill the body of the final blocks that were bootstrapped header-only
+        for block_id in self.active_index_without_body.iter() {
+            if let Some(BlockStatus::Active {
+                storage_or_block: StorageOrBlock::Block(block),
+                ..
+            }) = self.blocks_state.get(block_id)
+            {
+                wishlist.insert(*block_id, Some(block.content.header.clone()));
+            }
+        }
 
         Ok(wishlist)
     }
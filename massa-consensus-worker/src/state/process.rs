@@ -6,6 +6,7 @@ use std::{
 use massa_consensus_exports::{
     block_status::{BlockStatus, DiscardReason, HeaderOrBlock, StorageOrBlock},
     error::ConsensusError,
+    parent_selection::ParentCandidate,
 };
 use massa_execution_exports::ExecutionBlockMetadata;
 use massa_logging::massa_trace;
@@ -511,20 +512,51 @@ impl ConsensusState {
         );
         {
             let blockclique = &self.max_cliques[position_blockclique];
+            let get_candidate = |block_id: &BlockId| {
+                match self.blocks_state.get(block_id) {
+                Some(BlockStatus::Active {
+                    a_block,
+                    storage_or_block,
+                }) => Ok(ParentCandidate {
+                    block_id: *block_id,
+                    slot: a_block.slot,
+                    endorsement_count: storage_or_block.endorsement_count(block_id),
+                    fitness: a_block.fitness,
+                }),
+                _ => Err(ConsensusError::ContainerInconsistency(format!("inconsistency inside block statuses updating best parents while adding {} - missing {}", add_block_id, block_id))),
+            }
+            };
 
             // init best parents as latest_final_blocks_periods
-            self.best_parents = self.latest_final_blocks_periods.clone();
+            let mut best_parents: Vec<ParentCandidate> = self
+                .latest_final_blocks_periods
+                .iter()
+                .enumerate()
+                .map(|(thread, (block_id, period))| {
+                    get_candidate(block_id).unwrap_or_else(|_| ParentCandidate {
+                        block_id: *block_id,
+                        slot: Slot::new(*period, thread as u8),
+                        endorsement_count: 0,
+                        fitness: 0,
+                    })
+                })
+                .collect();
             // for each blockclique block, set it as best_parent in its own thread
-            // if its period is higher than the current best_parent in that thread
+            // if the parent selection policy prefers it to the current best_parent in that thread
             for block_h in blockclique.block_ids.iter() {
-                let b_slot = match self.blocks_state.get(block_h) {
-                    Some(BlockStatus::Active { a_block, .. }) => a_block.slot,
-                    _ => return Err(ConsensusError::ContainerInconsistency(format!("inconsistency inside block statuses updating best parents while adding {} - missing {}", add_block_id, block_h))),
-                };
-                if b_slot.period > self.best_parents[b_slot.thread as usize].1 {
-                    self.best_parents[b_slot.thread as usize] = (*block_h, b_slot.period);
+                let candidate = get_candidate(block_h)?;
+                let best_parent = &mut best_parents[candidate.slot.thread as usize];
+                if self
+                    .parent_selection_policy
+                    .prefers(&candidate, best_parent)
+                {
+                    *best_parent = candidate;
                 }
             }
+            self.best_parents = best_parents
+                .into_iter()
+                .map(|candidate| (candidate.block_id, candidate.slot.period))
+                .collect();
         }
 
         // list stale blocks
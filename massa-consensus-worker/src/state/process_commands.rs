@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 
 use massa_consensus_exports::{
-    block_status::{BlockStatus, DiscardReason, HeaderOrBlock},
+    block_status::{BlockStatus, DiscardReason, HeaderOrBlock, StorageOrBlock},
     error::ConsensusError,
 };
 use massa_logging::massa_trace;
@@ -77,6 +77,25 @@ impl ConsensusState {
             return Ok(());
         }
 
+        // backfill the body of a final block that was bootstrapped header-only
+        if self.active_index_without_body.remove(&block_id) {
+            debug!(
+                "backfilled body of final block {} for slot {}",
+                block_id, slot
+            );
+            let keep_ops = !self.active_index_without_ops.contains(&block_id);
+            if let Some(BlockStatus::Active {
+                storage_or_block, ..
+            }) = self.blocks_state.get_mut(&block_id)
+            {
+                *storage_or_block = StorageOrBlock::Storage(storage);
+                if !keep_ops {
+                    storage_or_block.strip_to_block(&block_id);
+                }
+            }
+            return Ok(());
+        }
+
         // Block is coming from protocol mark it for desync calculation
         if !created {
             let now = MassaTime::now()?;
@@ -61,6 +61,7 @@ impl ConsensusState {
             .copied()
             .collect();
         for discard_active_h in to_remove {
+            self.active_index_without_body.remove(&discard_active_h);
             let sequence_number = self.blocks_state.sequence_counter();
             self.blocks_state.transition_map(&discard_active_h, |block_status, block_statuses| {
                 if let Some(
@@ -1,5 +1,6 @@
 use super::ConsensusState;
 use massa_consensus_exports::error::ConsensusError;
+use massa_consensus_exports::finality::{FinalityStats, ThreadFinality};
 use massa_models::stats::ConsensusStats;
 use massa_time::MassaTime;
 use std::cmp::max;
@@ -38,6 +39,58 @@ impl ConsensusState {
         })
     }
 
+    /// Get the latest final block of each thread and the finalization latency stats over the stats timespan
+    pub fn get_finality_stats(&self) -> Result<FinalityStats, ConsensusError> {
+        let timespan_end = max(self.launch_time, MassaTime::now()?);
+        let timespan_start = max(
+            timespan_end.saturating_sub(self.config.stats_timespan),
+            self.launch_time,
+        );
+        let mut latencies: Vec<MassaTime> = self
+            .finalization_latency_stats
+            .iter()
+            .filter(|(t, _)| *t >= timespan_start && *t < timespan_end)
+            .map(|(_, latency)| *latency)
+            .collect();
+        latencies.sort_unstable();
+        // nearest-rank percentile of the sorted latencies
+        let percentile = |p: usize| {
+            latencies
+                .get(((latencies.len() * p + 99) / 100).saturating_sub(1))
+                .copied()
+        };
+        let average_latency = if latencies.is_empty() {
+            None
+        } else {
+            let total: u64 = latencies.iter().map(|latency| latency.to_millis()).sum();
+            Some(MassaTime::from_millis(total / latencies.len() as u64))
+        };
+        let threads = self
+            .latest_final_blocks_periods
+            .iter()
+            .zip(self.latest_final_blocks_times.iter())
+            .enumerate()
+            .map(
+                |(thread, ((block_id, period), finalized_at))| ThreadFinality {
+                    thread: thread as u8,
+                    block_id: *block_id,
+                    period: *period,
+                    finalized_at: *finalized_at,
+                },
+            )
+            .collect();
+        Ok(FinalityStats {
+            threads,
+            start_timespan: timespan_start,
+            end_timespan: timespan_end,
+            finalized_block_count: latencies.len() as u64,
+            average_latency,
+            median_latency: percentile(50),
+            p95_latency: percentile(95),
+            max_latency: latencies.last().copied(),
+        })
+    }
+
     /// Must be called each tick to update stats. Will detect if a desynchronization happened
     pub fn stats_tick(&mut self) -> Result<(), ConsensusError> {
         #[cfg(not(feature = "sandbox"))]
@@ -98,6 +151,13 @@ impl ConsensusState {
                 break;
             }
         }
+        while let Some((t, _)) = self.finalization_latency_stats.front() {
+            if t < &start_time {
+                self.finalization_latency_stats.pop_front();
+            } else {
+                break;
+            }
+        }
         while let Some((t, _)) = self.protocol_blocks.front() {
             if t < &start_time {
                 self.protocol_blocks.pop_front();
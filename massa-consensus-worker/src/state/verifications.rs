@@ -2,7 +2,8 @@ use super::{process::BlockInfos, ConsensusState};
 use massa_consensus_exports::block_status::{BlockStatus, DiscardReason, HeaderOrBlock};
 use massa_logging::massa_trace;
 use massa_models::{
-    block_header::SecuredHeader, block_id::BlockId, prehash::PreHashSet, slot::Slot,
+    address::Address, block_header::SecuredHeader, block_id::BlockId, prehash::PreHashSet,
+    slot::Slot,
 };
 use tracing::warn;
 
@@ -157,6 +158,7 @@ impl ConsensusState {
     /// - Valid thread.
     /// - Check that the block is older than the latest final one in thread.
     /// - Check if it was the creator's turn to create this block.
+    /// - Check that the reward recipient, if any, is a user address.
     /// - Check parents are present.
     /// - Check the topological consistency of the parents.
     /// - Check endorsements.
@@ -205,6 +207,14 @@ impl ConsensusState {
             )));
         }
 
+        // check that the rewards of the block are not redirected to a smart contract
+        if let Some(recipient @ Address::SC(_)) = header.content.reward_recipient {
+            return HeaderCheckOutcome::Discard(DiscardReason::Invalid(format!(
+                "Block reward recipient {} is not a user address",
+                recipient
+            )));
+        }
+
         // check if block is in the future: queue it
         // note: do it after testing signature + draw to prevent queue flooding/DoS
         // note: Some(x) > None
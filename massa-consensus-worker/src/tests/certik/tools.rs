@@ -132,6 +132,7 @@ pub fn create_block_with_merkle_root(
             parents: best_parents,
             operation_merkle_root,
             endorsements: Vec::new(),
+            reward_recipient: None,
         },
         BlockHeaderSerializer::new(),
         creator,
@@ -1,4 +1,4 @@
-use massa_consensus_exports::ConsensusConfig;
+use massa_consensus_exports::{block_fitness::BlockFitnessInfo, ConsensusConfig};
 use massa_models::{address::Address, block::BlockGraphStatus, slot::Slot};
 use massa_signature::KeyPair;
 use massa_storage::Storage;
@@ -95,6 +95,20 @@ fn test_tts_latest_blocks_as_parents() {
                 "incorrect block statuses"
             );
 
+            // the descendants of block_1_0 have not exceeded delta_f0 yet
+            assert_eq!(
+                tc.consensus_controller
+                    .get_block_fitness_infos(&[block_1_0.id]),
+                [Some(BlockFitnessInfo {
+                    fitness: 1,
+                    endorsement_count: 0,
+                    descendants_fitness: Some(4),
+                    descendants_endorsement_fitness: Some(0),
+                    finality_threshold: 4,
+                })],
+                "incorrect block fitness"
+            );
+
             // Period 3, thread 1.
             let block_3_1 = register_block_and_process_with_tc(
                 Slot::new(3, 1),
@@ -119,6 +133,24 @@ fn test_tts_latest_blocks_as_parents() {
                 "incorrect block statuses"
             );
 
+            // the latest final period of thread 0 moved to block_1_0, thread 1 is still at genesis
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            let finality_stats = tc
+                .consensus_controller
+                .get_finality_stats()
+                .expect("could not get finality stats");
+            assert_eq!(finality_stats.threads.len(), 2);
+            assert_eq!(finality_stats.threads[0].block_id, block_1_0.id);
+            assert_eq!(finality_stats.threads[0].period, 1);
+            assert!(finality_stats.threads[0].finalized_at.is_some());
+            assert_eq!(finality_stats.threads[1].block_id, genesis[1]);
+            assert_eq!(finality_stats.threads[1].finalized_at, None);
+            assert_eq!(finality_stats.finalized_block_count, 1);
+            assert_eq!(
+                finality_stats.median_latency, finality_stats.max_latency,
+                "incorrect finalization latency stats"
+            );
+
             // Period 4, thread 0.
             let block_4_0 = register_block_and_process_with_tc(
                 Slot::new(4, 0),
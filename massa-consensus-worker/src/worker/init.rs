@@ -13,7 +13,7 @@ use massa_models::{
     block::{Block, BlockSerializer, SecureShareBlock},
     block_header::{BlockHeader, BlockHeaderSerializer},
     block_id::BlockId,
-    prehash::PreHashMap,
+    prehash::{PreHashMap, PreHashSet},
     secure_share::SecureShareContent,
     slot::Slot,
     timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
@@ -54,6 +54,7 @@ pub fn create_genesis_block(
             operation_merkle_root: Hash::compute_from(&Vec::new()),
             endorsements: Vec::new(),
             denunciations: Vec::new(),
+            reward_recipient: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -202,10 +203,16 @@ impl ConsensusWorker {
         // If the node starts after the genesis timestamp then it has to initialize its graph
         // with already produced blocks received from the bootstrap.
         if let Some(BootstrapableGraph { final_blocks }) = init_graph {
-            // load final blocks
+            // load final blocks, noting the ones that were bootstrapped without their body
+            let mut blocks_without_body: PreHashSet<BlockId> = PreHashSet::default();
             let final_blocks: Vec<(ActiveBlock, StorageOrBlock)> = final_blocks
                 .into_iter()
-                .map(|export_b| export_b.to_active_block(config.thread_count))
+                .map(|export_b| {
+                    if export_b.body_omitted {
+                        blocks_without_body.insert(export_b.block.id);
+                    }
+                    export_b.to_active_block(config.thread_count)
+                })
                 .collect::<Result<_, ConsensusError>>()?;
 
             // compute latest_final_blocks_periods
@@ -235,6 +242,7 @@ impl ConsensusWorker {
                         },
                     );
                 }
+                write_shared_state.active_index_without_body = blocks_without_body;
                 write_shared_state.final_block_stats = final_block_stats;
             }
 
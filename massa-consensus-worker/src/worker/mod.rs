@@ -1,7 +1,8 @@
 use massa_channel::{receiver::MassaReceiver, MassaChannel};
 use massa_consensus_exports::{
-    bootstrapable_graph::BootstrapableGraph, ConsensusChannels, ConsensusConfig,
-    ConsensusController, ConsensusManager,
+    bootstrapable_graph::BootstrapableGraph,
+    parent_selection::{ConfiguredParentSelection, ParentSelectionPolicy},
+    ConsensusChannels, ConsensusConfig, ConsensusController, ConsensusManager,
 };
 use massa_metrics::MassaMetrics;
 use massa_models::block_id::BlockId;
@@ -57,6 +58,32 @@ pub fn start_consensus_worker(
     init_graph: Option<BootstrapableGraph>,
     storage: Storage,
     massa_metrics: MassaMetrics,
+) -> (Box<dyn ConsensusController>, Box<dyn ConsensusManager>) {
+    let parent_selection_policy = Arc::new(ConfiguredParentSelection {
+        rule: config.parent_selection_rule,
+        tie_break: config.parent_tie_break,
+    });
+    start_consensus_worker_with_parent_selection(
+        config,
+        channels,
+        init_graph,
+        storage,
+        massa_metrics,
+        parent_selection_policy,
+    )
+}
+
+/// Create a new consensus worker thread selecting the best parents with a custom policy.
+///
+/// Same as `start_consensus_worker`, except that `parent_selection_rule` and `parent_tie_break`
+/// of the configuration are ignored in favor of `parent_selection_policy`.
+pub fn start_consensus_worker_with_parent_selection(
+    config: ConsensusConfig,
+    channels: ConsensusChannels,
+    init_graph: Option<BootstrapableGraph>,
+    storage: Storage,
+    massa_metrics: MassaMetrics,
+    parent_selection_policy: Arc<dyn ParentSelectionPolicy>,
 ) -> (Box<dyn ConsensusController>, Box<dyn ConsensusManager>) {
     let (tx, rx) = MassaChannel::new("consensus_command".to_string(), Some(CHANNEL_SIZE));
     // desync detection timespan
@@ -78,13 +105,17 @@ pub fn start_consensus_worker(
         new_final_blocks: Default::default(),
         new_stale_blocks: Default::default(),
         active_index_without_ops: Default::default(),
+        active_index_without_body: Default::default(),
         save_final_periods: Default::default(),
         latest_final_blocks_periods: Default::default(),
+        latest_final_blocks_times: vec![None; config.thread_count as usize],
         best_parents: Default::default(),
+        parent_selection_policy,
         genesis_hashes: Default::default(),
         gi_head: Default::default(),
         final_block_stats: Default::default(),
         stale_block_stats: Default::default(),
+        finalization_latency_stats: Default::default(),
         protocol_blocks: Default::default(),
         wishlist: Default::default(),
         launch_time: MassaTime::now().unwrap(),
@@ -23,7 +23,9 @@ pub const STATE_HASH_ERROR: &str = "critical: saved state hash is corrupted";
 pub const CYCLE_HISTORY_PREFIX: &str = "cycle_history/";
 pub const DEFERRED_CREDITS_PREFIX: &str = "deferred_credits/";
 pub const ASYNC_POOL_PREFIX: &str = "async_pool/";
+pub const DEFERRED_CALLS_PREFIX: &str = "deferred_calls/";
 pub const EXECUTED_OPS_PREFIX: &str = "executed_ops/";
+pub const EXECUTED_OPS_FILTER_PREFIX: &str = "executed_ops_filter/";
 pub const EXECUTED_DENUNCIATIONS_PREFIX: &str = "executed_denunciations/";
 pub const LEDGER_PREFIX: &str = "ledger/";
 pub const MIP_STORE_PREFIX: &str = "versioning/";
@@ -36,6 +38,10 @@ pub const MESSAGE_SER_ERROR: &str = "critical: message serialization failed";
 pub const MESSAGE_ID_DESER_ERROR: &str = "critical: message_id deserialization failed";
 pub const MESSAGE_ID_SER_ERROR: &str = "critical: message_id serialization failed";
 
+// Deferred calls
+pub const DEFERRED_CALL_DESER_ERROR: &str = "critical: deferred_call deserialization failed";
+pub const DEFERRED_CALL_SER_ERROR: &str = "critical: deferred_call serialization failed";
+
 // PosState
 pub const CYCLE_HISTORY_DESER_ERROR: &str = "critical: cycle_history deserialization failed";
 pub const CYCLE_HISTORY_SER_ERROR: &str = "critical: cycle_history serialization failed";
@@ -83,6 +83,10 @@ pub trait MassaDBController: Send + Sync + Debug {
         last_change_id: Option<Slot>,
     ) -> Result<StreamBatch<Slot>, MassaDBError>;
 
+    /// Get the changes made to the state and versioning columns at the given change_id,
+    /// None if they are no longer in the changes history kept in memory
+    fn get_changes_at(&self, change_id: Slot) -> Option<(DBBatch, DBBatch)>;
+
     /// To be called just after bootstrap
     fn recompute_db_hash(&mut self) -> Result<(), MassaDBError>;
 }
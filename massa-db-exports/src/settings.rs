@@ -11,4 +11,6 @@ pub struct MassaDBConfig {
     pub max_new_elements: usize,
     /// Thread count for slot serialization
     pub thread_count: u8,
+    /// Size in bytes of the block cache shared by the column families, 0 keeps the default cache of RocksDB
+    pub block_cache_size: usize,
 }
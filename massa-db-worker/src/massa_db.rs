@@ -14,8 +14,8 @@ use massa_models::{
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use parking_lot::Mutex;
 use rocksdb::{
-    checkpoint::Checkpoint, ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch,
-    DB,
+    checkpoint::Checkpoint, BlockBasedOptions, Cache, ColumnFamilyDescriptor, Direction,
+    IteratorMode, Options, WriteBatch, DB,
 };
 use std::{
     collections::BTreeMap,
@@ -116,7 +116,8 @@ where
                             .change_history
                             .range((Bound::Included(&last_change_id), Bound::Unbounded));
 
-                        if cursor.next().is_none() {
+                        // the changes since last_change_id are only known if it is still in the history
+                        if cursor.next().map(|(change_id, _)| change_id) != Some(&last_change_id) {
                             return Err(MassaDBError::TimeError(String::from(
                                 "all our changes are strictly after last_change_id, we can't be sure we did not miss any",
                             )));
@@ -226,7 +227,8 @@ where
                             .change_history_versioning
                             .range((Bound::Included(&last_change_id), Unbounded));
 
-                        if cursor.next().is_none() {
+                        // the changes since last_change_id are only known if it is still in the history
+                        if cursor.next().map(|(change_id, _)| change_id) != Some(&last_change_id) {
                             return Err(MassaDBError::TimeError(String::from(
                                 "all our changes are strictly after last_change_id, we can't be sure we did not miss any",
                             )));
@@ -579,13 +581,22 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
         db_opts.create_if_missing(true);
         db_opts.create_missing_column_families(true);
 
+        // the column families share a single block cache, so that its size bounds the memory used to cache reads
+        let mut cf_opts = Options::default();
+        if config.block_cache_size > 0 {
+            let cache = Cache::new_lru_cache(config.block_cache_size);
+            let mut table_opts = BlockBasedOptions::default();
+            table_opts.set_block_cache(&cache);
+            cf_opts.set_block_based_table_factory(&table_opts);
+        }
+
         let db = DB::open_cf_descriptors(
             &db_opts,
             &config.path,
             vec![
-                ColumnFamilyDescriptor::new(STATE_CF, Options::default()),
-                ColumnFamilyDescriptor::new(METADATA_CF, Options::default()),
-                ColumnFamilyDescriptor::new(VERSIONING_CF, Options::default()),
+                ColumnFamilyDescriptor::new(STATE_CF, cf_opts.clone()),
+                ColumnFamilyDescriptor::new(METADATA_CF, cf_opts.clone()),
+                ColumnFamilyDescriptor::new(VERSIONING_CF, cf_opts),
             ],
         )
         .expect(OPEN_ERROR);
@@ -832,6 +843,18 @@ impl MassaDBController for RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
         self.get_versioning_batch_to_stream(last_versioning_step, last_change_id)
     }
 
+    /// Get the changes made to the state and versioning columns at the given change_id,
+    /// None if they are no longer in the changes history kept in memory
+    fn get_changes_at(&self, change_id: Slot) -> Option<(DBBatch, DBBatch)> {
+        let changes = self.change_history.get(&change_id)?;
+        let versioning_changes = self
+            .change_history_versioning
+            .get(&change_id)
+            .cloned()
+            .unwrap_or_default();
+        Some((changes.clone(), versioning_changes))
+    }
+
     /// To be called just after bootstrap
     fn recompute_db_hash(&mut self) -> Result<(), MassaDBError> {
         self.recompute_db_hash()
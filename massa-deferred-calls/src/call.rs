@@ -0,0 +1,372 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines the structure of a deferred call and of its identifier.
+
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::{
+    address::{Address, AddressDeserializer, AddressSerializer},
+    amount::{Amount, AmountDeserializer, AmountSerializer},
+    error::ModelsError,
+    serialization::{StringDeserializer, StringSerializer, VecU8Deserializer, VecU8Serializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer, SLOT_KEY_SIZE},
+};
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U16VarIntDeserializer, U16VarIntSerializer,
+    U64VarIntDeserializer, U64VarIntSerializer,
+};
+use nom::{
+    error::{context, ContextError, ParseError},
+    sequence::tuple,
+    IResult, Parser,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::{
+    Bound::{Excluded, Included},
+    RangeInclusive,
+};
+use std::str::FromStr;
+
+/// Prefix of the string representation of a deferred call id
+pub const DEFERRED_CALL_ID_PREFIX: char = 'D';
+
+/// Size in bytes of a serialized deferred call id
+pub const DEFERRED_CALL_ID_SIZE_BYTES: usize = SLOT_KEY_SIZE + HASH_SIZE_BYTES;
+
+/// Identifier of a deferred call.
+///
+/// Ids are sorted by target slot first, so that the calls of a slot are contiguous in the registry.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, std::hash::Hash, Deserialize, Serialize,
+)]
+pub struct DeferredCallId {
+    /// slot at which the call is executed
+    pub target_slot: Slot,
+    /// hash distinguishing the call from the other calls of the slot
+    pub hash: Hash,
+}
+
+impl DeferredCallId {
+    /// Creates the id of the call registered at `registration_slot` with index `index` in that slot
+    pub fn new(target_slot: Slot, registration_slot: Slot, index: u64) -> Self {
+        Self {
+            target_slot,
+            hash: Hash::compute_from_tuple(&[
+                &registration_slot.to_bytes_key(),
+                &index.to_be_bytes(),
+            ]),
+        }
+    }
+
+    /// Range containing the ids of all the calls targeting a given slot
+    pub fn slot_range(slot: Slot) -> RangeInclusive<DeferredCallId> {
+        DeferredCallId {
+            target_slot: slot,
+            hash: Hash::from_bytes(&[u8::MIN; HASH_SIZE_BYTES]),
+        }..=DeferredCallId {
+            target_slot: slot,
+            hash: Hash::from_bytes(&[u8::MAX; HASH_SIZE_BYTES]),
+        }
+    }
+
+    /// Fixed-size sortable binary representation of the id, used as database key
+    pub fn to_bytes(&self) -> [u8; DEFERRED_CALL_ID_SIZE_BYTES] {
+        let mut res = [0u8; DEFERRED_CALL_ID_SIZE_BYTES];
+        res[..SLOT_KEY_SIZE].copy_from_slice(&self.target_slot.to_bytes_key());
+        res[SLOT_KEY_SIZE..].copy_from_slice(self.hash.to_bytes());
+        res
+    }
+
+    /// Reads an id from its binary representation (see `to_bytes`)
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ModelsError> {
+        if data.len() != DEFERRED_CALL_ID_SIZE_BYTES {
+            return Err(ModelsError::DeserializeError(format!(
+                "invalid deferred call id length: {}",
+                data.len()
+            )));
+        }
+        let slot_bytes: &[u8; SLOT_KEY_SIZE] = data[..SLOT_KEY_SIZE].try_into().unwrap();
+        let hash_bytes: &[u8; HASH_SIZE_BYTES] = data[SLOT_KEY_SIZE..].try_into().unwrap();
+        Ok(Self {
+            target_slot: Slot::from_bytes_key(slot_bytes),
+            hash: Hash::from_bytes(hash_bytes),
+        })
+    }
+}
+
+impl std::fmt::Display for DeferredCallId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            DEFERRED_CALL_ID_PREFIX,
+            bs58::encode(self.to_bytes()).with_check().into_string()
+        )
+    }
+}
+
+impl FromStr for DeferredCallId {
+    type Err = ModelsError;
+    /// ## Example
+    /// ```rust
+    /// # use massa_deferred_calls::DeferredCallId;
+    /// # use massa_models::slot::Slot;
+    /// # use std::str::FromStr;
+    /// let id = DeferredCallId::new(Slot::new(10, 3), Slot::new(2, 1), 0);
+    /// let res = DeferredCallId::from_str(&id.to_string()).unwrap();
+    /// assert_eq!(id, res);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match chars.next() {
+            Some(prefix) if prefix == DEFERRED_CALL_ID_PREFIX => {
+                let data = chars.collect::<String>();
+                let decoded_bs58_check =
+                    bs58::decode(data)
+                        .with_check(None)
+                        .into_vec()
+                        .map_err(|_| {
+                            ModelsError::DeserializeError("invalid deferred call id".into())
+                        })?;
+                DeferredCallId::from_bytes(&decoded_bs58_check)
+            }
+            _ => Err(ModelsError::DeserializeError(
+                "invalid deferred call id prefix".into(),
+            )),
+        }
+    }
+}
+
+/// Call registered by a smart contract to one of its own functions, executed at the beginning of a future slot.
+///
+/// The coins and the fee of the call are debited from the contract at registration.
+/// The fee is burned, the coins are credited back to the contract when the call is executed or cancelled.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeferredCall {
+    /// address of the smart contract that registered the call and whose function is called
+    pub sender_address: Address,
+    /// slot at the beginning of which the call is executed
+    pub target_slot: Slot,
+    /// name of the called function
+    pub target_function: String,
+    /// parameters passed to the called function
+    pub parameters: Vec<u8>,
+    /// coins transferred to the contract with the call
+    pub coins: Amount,
+    /// gas booked for the call in its target slot
+    pub max_gas: u64,
+    /// fee paid for the booking
+    pub fee: Amount,
+}
+
+/// `DeferredCall` serializer
+pub struct DeferredCallSerializer {
+    address_serializer: AddressSerializer,
+    slot_serializer: SlotSerializer,
+    function_serializer: StringSerializer<U16VarIntSerializer, u16>,
+    parameters_serializer: VecU8Serializer,
+    amount_serializer: AmountSerializer,
+    u64_serializer: U64VarIntSerializer,
+}
+
+impl DeferredCallSerializer {
+    /// Creates a `DeferredCallSerializer`
+    pub fn new() -> Self {
+        Self {
+            address_serializer: AddressSerializer::new(),
+            slot_serializer: SlotSerializer::new(),
+            function_serializer: StringSerializer::new(U16VarIntSerializer::new()),
+            parameters_serializer: VecU8Serializer::new(),
+            amount_serializer: AmountSerializer::new(),
+            u64_serializer: U64VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Default for DeferredCallSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<DeferredCall> for DeferredCallSerializer {
+    fn serialize(&self, value: &DeferredCall, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        self.address_serializer
+            .serialize(&value.sender_address, buffer)?;
+        self.slot_serializer.serialize(&value.target_slot, buffer)?;
+        self.function_serializer
+            .serialize(&value.target_function, buffer)?;
+        self.parameters_serializer
+            .serialize(&value.parameters, buffer)?;
+        self.amount_serializer.serialize(&value.coins, buffer)?;
+        self.u64_serializer.serialize(&value.max_gas, buffer)?;
+        self.amount_serializer.serialize(&value.fee, buffer)?;
+        Ok(())
+    }
+}
+
+/// `DeferredCall` deserializer
+pub struct DeferredCallDeserializer {
+    address_deserializer: AddressDeserializer,
+    slot_deserializer: SlotDeserializer,
+    function_deserializer: StringDeserializer<U16VarIntDeserializer, u16>,
+    parameters_deserializer: VecU8Deserializer,
+    amount_deserializer: AmountDeserializer,
+    u64_deserializer: U64VarIntDeserializer,
+}
+
+impl DeferredCallDeserializer {
+    /// Creates a `DeferredCallDeserializer`
+    pub fn new(thread_count: u8, max_function_name_length: u16, max_parameters_size: u32) -> Self {
+        Self {
+            address_deserializer: AddressDeserializer::new(),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            ),
+            function_deserializer: StringDeserializer::new(U16VarIntDeserializer::new(
+                Included(0),
+                Included(max_function_name_length),
+            )),
+            parameters_deserializer: VecU8Deserializer::new(
+                Included(0),
+                Included(max_parameters_size as u64),
+            ),
+            amount_deserializer: AmountDeserializer::new(
+                Included(Amount::MIN),
+                Included(Amount::MAX),
+            ),
+            u64_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        }
+    }
+}
+
+impl Deserializer<DeferredCall> for DeferredCallDeserializer {
+    /// ## Example
+    /// ```
+    /// use massa_deferred_calls::{DeferredCall, DeferredCallSerializer, DeferredCallDeserializer};
+    /// use massa_models::{address::Address, amount::Amount, slot::Slot};
+    /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
+    /// use std::str::FromStr;
+    ///
+    /// let call = DeferredCall {
+    ///     sender_address: Address::from_str("AS12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap(),
+    ///     target_slot: Slot::new(10, 2),
+    ///     target_function: String::from("wake_up"),
+    ///     parameters: vec![1, 2, 3],
+    ///     coins: Amount::from_str("1").unwrap(),
+    ///     max_gas: 1_000_000,
+    ///     fee: Amount::from_str("0.001").unwrap(),
+    /// };
+    /// let mut serialized = Vec::new();
+    /// DeferredCallSerializer::new().serialize(&call, &mut serialized).unwrap();
+    /// let (rest, deserialized) = DeferredCallDeserializer::new(32, 255, 1000)
+    ///     .deserialize::<DeserializeError>(&serialized)
+    ///     .unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(call, deserialized);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], DeferredCall, E> {
+        context(
+            "Failed DeferredCall deserialization",
+            tuple((
+                context("Failed sender_address deserialization", |input| {
+                    self.address_deserializer.deserialize(input)
+                }),
+                context("Failed target_slot deserialization", |input| {
+                    self.slot_deserializer.deserialize(input)
+                }),
+                context("Failed target_function deserialization", |input| {
+                    self.function_deserializer.deserialize(input)
+                }),
+                context("Failed parameters deserialization", |input| {
+                    self.parameters_deserializer.deserialize(input)
+                }),
+                context("Failed coins deserialization", |input| {
+                    self.amount_deserializer.deserialize(input)
+                }),
+                context("Failed max_gas deserialization", |input| {
+                    self.u64_deserializer.deserialize(input)
+                }),
+                context("Failed fee deserialization", |input| {
+                    self.amount_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(
+            |(sender_address, target_slot, target_function, parameters, coins, max_gas, fee)| {
+                DeferredCall {
+                    sender_address,
+                    target_slot,
+                    target_function,
+                    parameters,
+                    coins,
+                    max_gas,
+                    fee,
+                }
+            },
+        )
+        .parse(buffer)
+    }
+}
+
+/// `DeferredCallId` serializer
+#[derive(Default, Clone)]
+pub struct DeferredCallIdSerializer;
+
+impl DeferredCallIdSerializer {
+    /// Creates a `DeferredCallIdSerializer`
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Serializer<DeferredCallId> for DeferredCallIdSerializer {
+    fn serialize(
+        &self,
+        value: &DeferredCallId,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        buffer.extend(value.to_bytes());
+        Ok(())
+    }
+}
+
+/// `DeferredCallId` deserializer
+#[derive(Clone)]
+pub struct DeferredCallIdDeserializer {
+    thread_count: u8,
+}
+
+impl DeferredCallIdDeserializer {
+    /// Creates a `DeferredCallIdDeserializer`
+    pub fn new(thread_count: u8) -> Self {
+        Self { thread_count }
+    }
+}
+
+impl Deserializer<DeferredCallId> for DeferredCallIdDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], DeferredCallId, E> {
+        if buffer.len() < DEFERRED_CALL_ID_SIZE_BYTES {
+            return Err(nom::Err::Error(ContextError::add_context(
+                buffer,
+                "Failed DeferredCallId deserialization",
+                ParseError::from_error_kind(buffer, nom::error::ErrorKind::LengthValue),
+            )));
+        }
+        let (id_bytes, rest) = buffer.split_at(DEFERRED_CALL_ID_SIZE_BYTES);
+        match DeferredCallId::from_bytes(id_bytes) {
+            Ok(id) if id.target_slot.thread < self.thread_count => Ok((rest, id)),
+            _ => Err(nom::Err::Error(ContextError::add_context(
+                buffer,
+                "Failed DeferredCallId deserialization",
+                ParseError::from_error_kind(buffer, nom::error::ErrorKind::Verify),
+            ))),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file provides structures representing changes to the deferred call registry
+
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    ops::Bound::Included,
+};
+
+use crate::call::{
+    DeferredCall, DeferredCallDeserializer, DeferredCallId, DeferredCallIdDeserializer,
+    DeferredCallIdSerializer, DeferredCallSerializer,
+};
+use massa_ledger_exports::{
+    Applicable, SetOrDelete, SetOrDeleteDeserializer, SetOrDeleteSerializer,
+};
+use massa_models::slot::Slot;
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use nom::{
+    error::{context, ContextError, ParseError},
+    multi::length_count,
+    sequence::tuple,
+    IResult, Parser,
+};
+use serde::{Deserialize, Serialize};
+
+/// Consolidated changes to the deferred call registry
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeferredCallRegistryChanges(pub BTreeMap<DeferredCallId, SetOrDelete<DeferredCall>>);
+
+impl Applicable<DeferredCallRegistryChanges> for DeferredCallRegistryChanges {
+    /// extends the current `DeferredCallRegistryChanges` with another one
+    fn apply(&mut self, changes: DeferredCallRegistryChanges) {
+        for (id, call_change) in changes.0 {
+            match self.0.entry(id) {
+                Entry::Occupied(mut occ) => {
+                    // apply incoming change if a change on this entry already exists
+                    occ.get_mut().apply(call_change);
+                }
+                Entry::Vacant(vac) => {
+                    // otherwise insert the incoming change
+                    vac.insert(call_change);
+                }
+            }
+        }
+    }
+}
+
+impl DeferredCallRegistryChanges {
+    /// Pushes a call registration to the list of changes
+    pub fn push_new_call(&mut self, id: DeferredCallId, call: DeferredCall) {
+        self.0.insert(id, SetOrDelete::Set(call));
+    }
+
+    /// Pushes a call deletion to the list of changes
+    pub fn push_delete(&mut self, id: DeferredCallId) {
+        self.0.insert(id, SetOrDelete::Delete);
+    }
+
+    /// Iterates over the changes of the calls targeting a given slot
+    pub fn get_slot_changes(
+        &self,
+        slot: Slot,
+    ) -> impl Iterator<Item = (&DeferredCallId, &SetOrDelete<DeferredCall>)> {
+        self.0.range(DeferredCallId::slot_range(slot))
+    }
+}
+
+/// `DeferredCallRegistryChanges` serializer
+pub struct DeferredCallRegistryChangesSerializer {
+    u64_serializer: U64VarIntSerializer,
+    id_serializer: DeferredCallIdSerializer,
+    set_or_delete_call_serializer: SetOrDeleteSerializer<DeferredCall, DeferredCallSerializer>,
+}
+
+impl DeferredCallRegistryChangesSerializer {
+    /// Creates a `DeferredCallRegistryChangesSerializer`
+    pub fn new() -> Self {
+        Self {
+            u64_serializer: U64VarIntSerializer::new(),
+            id_serializer: DeferredCallIdSerializer::new(),
+            set_or_delete_call_serializer: SetOrDeleteSerializer::new(DeferredCallSerializer::new()),
+        }
+    }
+}
+
+impl Default for DeferredCallRegistryChangesSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<DeferredCallRegistryChanges> for DeferredCallRegistryChangesSerializer {
+    fn serialize(
+        &self,
+        value: &DeferredCallRegistryChanges,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.u64_serializer.serialize(
+            &(value.0.len().try_into().map_err(|_| {
+                SerializeError::GeneralError("Fail to transform usize to u64".to_string())
+            })?),
+            buffer,
+        )?;
+        for (id, change) in &value.0 {
+            self.id_serializer.serialize(id, buffer)?;
+            self.set_or_delete_call_serializer
+                .serialize(change, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// `DeferredCallRegistryChanges` deserializer
+pub struct DeferredCallRegistryChangesDeserializer {
+    changes_length_deserializer: U64VarIntDeserializer,
+    id_deserializer: DeferredCallIdDeserializer,
+    set_or_delete_call_deserializer:
+        SetOrDeleteDeserializer<DeferredCall, DeferredCallDeserializer>,
+}
+
+impl DeferredCallRegistryChangesDeserializer {
+    /// Creates a `DeferredCallRegistryChangesDeserializer`
+    pub fn new(
+        thread_count: u8,
+        max_deferred_call_changes: u64,
+        max_function_name_length: u16,
+        max_parameters_size: u32,
+    ) -> Self {
+        Self {
+            changes_length_deserializer: U64VarIntDeserializer::new(
+                Included(u64::MIN),
+                Included(max_deferred_call_changes),
+            ),
+            id_deserializer: DeferredCallIdDeserializer::new(thread_count),
+            set_or_delete_call_deserializer: SetOrDeleteDeserializer::new(
+                DeferredCallDeserializer::new(
+                    thread_count,
+                    max_function_name_length,
+                    max_parameters_size,
+                ),
+            ),
+        }
+    }
+}
+
+impl Deserializer<DeferredCallRegistryChanges> for DeferredCallRegistryChangesDeserializer {
+    /// ## Example
+    /// ```
+    /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
+    /// use massa_models::{address::Address, amount::Amount, slot::Slot};
+    /// use massa_deferred_calls::{
+    ///     DeferredCall, DeferredCallId, DeferredCallRegistryChanges,
+    ///     DeferredCallRegistryChangesSerializer, DeferredCallRegistryChangesDeserializer,
+    /// };
+    /// use std::str::FromStr;
+    ///
+    /// let call = DeferredCall {
+    ///     sender_address: Address::from_str("AS12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap(),
+    ///     target_slot: Slot::new(10, 2),
+    ///     target_function: String::from("wake_up"),
+    ///     parameters: vec![1, 2, 3],
+    ///     coins: Amount::from_str("1").unwrap(),
+    ///     max_gas: 1_000_000,
+    ///     fee: Amount::from_str("0.001").unwrap(),
+    /// };
+    /// let mut changes = DeferredCallRegistryChanges::default();
+    /// changes.push_new_call(DeferredCallId::new(Slot::new(10, 2), Slot::new(1, 0), 0), call);
+    /// changes.push_delete(DeferredCallId::new(Slot::new(11, 0), Slot::new(1, 0), 1));
+    /// let mut serialized = Vec::new();
+    /// DeferredCallRegistryChangesSerializer::new().serialize(&changes, &mut serialized).unwrap();
+    /// let (rest, changes_deser) = DeferredCallRegistryChangesDeserializer::new(32, 1000, 255, 1000)
+    ///     .deserialize::<DeserializeError>(&serialized)
+    ///     .unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(changes, changes_deser);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], DeferredCallRegistryChanges, E> {
+        context(
+            "Failed DeferredCallRegistryChanges deserialization",
+            length_count(
+                context("Failed length deserialization", |input| {
+                    self.changes_length_deserializer.deserialize(input)
+                }),
+                |input: &'a [u8]| {
+                    tuple((
+                        context("Failed id deserialization", |input| {
+                            self.id_deserializer.deserialize(input)
+                        }),
+                        context("Failed set_or_delete_call deserialization", |input| {
+                            self.set_or_delete_call_deserializer.deserialize(input)
+                        }),
+                    ))(input)
+                },
+            ),
+        )
+        .map(|vec| DeferredCallRegistryChanges(vec.into_iter().collect()))
+        .parse(buffer)
+    }
+}
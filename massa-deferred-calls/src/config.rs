@@ -0,0 +1,16 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines a configuration structure containing all settings for the deferred call registry
+
+/// Deferred call registry configuration
+#[derive(Debug, Clone)]
+pub struct DeferredCallsConfig {
+    /// thread count
+    pub thread_count: u8,
+    /// max length of the name of the called function
+    pub max_function_name_length: u16,
+    /// max size of the parameters of a call
+    pub max_parameters_size: u32,
+    /// max number of registry changes (for bootstrap limits)
+    pub max_deferred_call_changes: u64,
+}
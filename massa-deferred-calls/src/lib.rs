@@ -0,0 +1,33 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! # General description
+//!
+//! This crate implements the registry of deferred calls (`DeferredCallRegistry`).
+//!
+//! A deferred call is registered by a smart contract to call one of its own functions
+//! at the beginning of a chosen future slot ("call me later").
+//! The gas of the call is booked in its target slot at registration time,
+//! in exchange for a fee that grows with the amount of gas already booked in that slot.
+//! Registered calls can be cancelled by the contract until they are executed.
+//!
+//! `DeferredCallRegistry` is used in conjunction with `FinalLedger` within the `FinalState`,
+//! but also as a speculative copy for speculative execution.
+
+#![warn(missing_docs)]
+
+mod call;
+mod changes;
+mod config;
+mod registry;
+
+pub use call::{
+    DeferredCall, DeferredCallDeserializer, DeferredCallId, DeferredCallIdDeserializer,
+    DeferredCallIdSerializer, DeferredCallSerializer, DEFERRED_CALL_ID_PREFIX,
+    DEFERRED_CALL_ID_SIZE_BYTES,
+};
+pub use changes::{
+    DeferredCallRegistryChanges, DeferredCallRegistryChangesDeserializer,
+    DeferredCallRegistryChangesSerializer,
+};
+pub use config::DeferredCallsConfig;
+pub use registry::DeferredCallRegistry;
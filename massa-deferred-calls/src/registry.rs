@@ -0,0 +1,246 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines the final deferred call registry, stored in the final state database
+
+use crate::{
+    call::{DeferredCall, DeferredCallDeserializer, DeferredCallId, DeferredCallSerializer},
+    changes::DeferredCallRegistryChanges,
+    config::DeferredCallsConfig,
+};
+use massa_db_exports::{
+    DBBatch, ShareableMassaDBController, CRUD_ERROR, DEFERRED_CALLS_PREFIX,
+    DEFERRED_CALL_DESER_ERROR, DEFERRED_CALL_SER_ERROR, STATE_CF,
+};
+use massa_ledger_exports::SetOrDelete;
+use massa_models::slot::Slot;
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use std::collections::BTreeMap;
+
+/// Deferred call database key formatting macro
+macro_rules! deferred_call_key {
+    ($id:expr) => {
+        [DEFERRED_CALLS_PREFIX.as_bytes(), &$id.to_bytes()[..]].concat()
+    };
+}
+
+/// Deferred call slot prefix formatting macro
+macro_rules! deferred_call_slot_prefix {
+    ($slot:expr) => {
+        [DEFERRED_CALLS_PREFIX.as_bytes(), &$slot.to_bytes_key()[..]].concat()
+    };
+}
+
+#[derive(Clone)]
+/// Registry of the deferred calls that are not yet executed.
+/// The final registry is attached to the output of the latest final slot within the context of massa-final-state.
+pub struct DeferredCallRegistry {
+    /// Deferred call registry configuration
+    pub config: DeferredCallsConfig,
+    /// Database handle
+    pub db: ShareableMassaDBController,
+    call_serializer: DeferredCallSerializer,
+    call_deserializer: DeferredCallDeserializer,
+}
+
+impl DeferredCallRegistry {
+    /// Creates an empty `DeferredCallRegistry`
+    pub fn new(config: DeferredCallsConfig, db: ShareableMassaDBController) -> Self {
+        Self {
+            call_serializer: DeferredCallSerializer::new(),
+            call_deserializer: DeferredCallDeserializer::new(
+                config.thread_count,
+                config.max_function_name_length,
+                config.max_parameters_size,
+            ),
+            config,
+            db,
+        }
+    }
+
+    /// Resets the registry to its initial state
+    ///
+    /// USED ONLY FOR BOOTSTRAP
+    pub fn reset(&mut self) {
+        self.db
+            .write()
+            .delete_prefix(DEFERRED_CALLS_PREFIX, STATE_CF, None);
+    }
+
+    /// Queries a call from the database
+    pub fn get_call(&self, id: &DeferredCallId) -> Option<DeferredCall> {
+        let db = self.db.read();
+        let serialized_call = db
+            .get_cf(STATE_CF, deferred_call_key!(id))
+            .expect(CRUD_ERROR)?;
+        let (_, call) = self
+            .call_deserializer
+            .deserialize::<DeserializeError>(&serialized_call)
+            .expect(DEFERRED_CALL_DESER_ERROR);
+        Some(call)
+    }
+
+    /// Queries all the calls targeting a given slot, sorted by id
+    pub fn get_slot_calls(&self, slot: &Slot) -> BTreeMap<DeferredCallId, DeferredCall> {
+        let db = self.db.read();
+        let prefix = deferred_call_slot_prefix!(slot);
+        let mut calls = BTreeMap::new();
+        for (serialized_key, serialized_value) in db.prefix_iterator_cf(STATE_CF, &prefix) {
+            if !serialized_key.starts_with(&prefix) {
+                break;
+            }
+            let id = DeferredCallId::from_bytes(&serialized_key[DEFERRED_CALLS_PREFIX.len()..])
+                .expect(DEFERRED_CALL_DESER_ERROR);
+            let (_, call) = self
+                .call_deserializer
+                .deserialize::<DeserializeError>(&serialized_value)
+                .expect(DEFERRED_CALL_DESER_ERROR);
+            calls.insert(id, call);
+        }
+        calls
+    }
+
+    /// Applies `DeferredCallRegistryChanges` to the registry
+    ///
+    /// # arguments
+    /// * `changes`: `DeferredCallRegistryChanges` listing the registered and removed calls
+    /// * `batch`: the given operation batch to update
+    pub fn apply_changes_to_batch(
+        &mut self,
+        changes: &DeferredCallRegistryChanges,
+        batch: &mut DBBatch,
+    ) {
+        let db = self.db.read();
+        for (id, change) in changes.0.iter() {
+            match change {
+                SetOrDelete::Set(call) => {
+                    let mut serialized_call = Vec::new();
+                    self.call_serializer
+                        .serialize(call, &mut serialized_call)
+                        .expect(DEFERRED_CALL_SER_ERROR);
+                    db.put_or_update_entry_value(batch, deferred_call_key!(id), &serialized_call);
+                }
+                SetOrDelete::Delete => {
+                    db.delete_key(batch, deferred_call_key!(id));
+                }
+            }
+        }
+    }
+
+    /// Deserializes the key and value, useful after bootstrap
+    pub fn is_key_value_valid(&self, serialized_key: &[u8], serialized_value: &[u8]) -> bool {
+        if !serialized_key.starts_with(DEFERRED_CALLS_PREFIX.as_bytes()) {
+            return false;
+        }
+        let Ok(id) = DeferredCallId::from_bytes(&serialized_key[DEFERRED_CALLS_PREFIX.len()..])
+        else {
+            return false;
+        };
+        if id.target_slot.thread >= self.config.thread_count {
+            return false;
+        }
+        let Ok((rest, call)) = self
+            .call_deserializer
+            .deserialize::<DeserializeError>(serialized_value)
+        else {
+            return false;
+        };
+        rest.is_empty() && call.target_slot == id.target_slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_db_exports::{MassaDBConfig, MassaDBController};
+    use massa_db_worker::MassaDB;
+    use massa_models::{address::Address, amount::Amount};
+    use parking_lot::RwLock;
+    use std::{str::FromStr, sync::Arc};
+    use tempfile::TempDir;
+
+    fn call(target_slot: Slot) -> DeferredCall {
+        DeferredCall {
+            sender_address: Address::from_str(
+                "AS12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G",
+            )
+            .unwrap(),
+            target_slot,
+            target_function: String::from("wake_up"),
+            parameters: vec![42],
+            coins: Amount::from_str("1").unwrap(),
+            max_gas: 1_000_000,
+            fee: Amount::from_str("0.01").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_registry_changes_and_slot_queries() {
+        let thread_count = 2;
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let db_config = MassaDBConfig {
+            path: tempdir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count,
+            block_cache_size: 0,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+        let mut registry = DeferredCallRegistry::new(
+            DeferredCallsConfig {
+                thread_count,
+                max_function_name_length: 255,
+                max_parameters_size: 1000,
+                max_deferred_call_changes: 1000,
+            },
+            db.clone(),
+        );
+
+        let slot_a = Slot::new(5, 1);
+        let slot_b = Slot::new(6, 0);
+        let registration_slot = Slot::new(1, 0);
+        let id_a1 = DeferredCallId::new(slot_a, registration_slot, 0);
+        let id_a2 = DeferredCallId::new(slot_a, registration_slot, 1);
+        let id_b = DeferredCallId::new(slot_b, registration_slot, 2);
+
+        let mut changes = DeferredCallRegistryChanges::default();
+        changes.push_new_call(id_a1, call(slot_a));
+        changes.push_new_call(id_a2, call(slot_a));
+        changes.push_new_call(id_b, call(slot_b));
+        let mut batch = DBBatch::new();
+        registry.apply_changes_to_batch(&changes, &mut batch);
+        db.write().write_batch(batch, Default::default(), None);
+
+        assert_eq!(registry.get_call(&id_b), Some(call(slot_b)));
+        let slot_a_calls = registry.get_slot_calls(&slot_a);
+        assert_eq!(
+            slot_a_calls.keys().copied().collect::<Vec<_>>(),
+            vec![id_a1.min(id_a2), id_a1.max(id_a2)]
+        );
+        for (key, value) in db
+            .read()
+            .prefix_iterator_cf(STATE_CF, DEFERRED_CALLS_PREFIX.as_bytes())
+        {
+            assert!(registry.is_key_value_valid(&key, &value));
+        }
+
+        let mut changes = DeferredCallRegistryChanges::default();
+        changes.push_delete(id_a1);
+        let mut batch = DBBatch::new();
+        registry.apply_changes_to_batch(&changes, &mut batch);
+        db.write().write_batch(batch, Default::default(), None);
+
+        assert_eq!(registry.get_call(&id_a1), None);
+        assert_eq!(
+            registry
+                .get_slot_calls(&slot_a)
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec![id_a2]
+        );
+
+        registry.reset();
+        assert!(registry.get_slot_calls(&slot_b).is_empty());
+    }
+}
@@ -6,6 +6,14 @@ pub struct ExecutedOpsConfig {
     pub thread_count: u8,
     /// Number of extra periods to keep executed denunciations
     pub keep_executed_history_extra_periods: u64,
+    /// Expected number of pruned operations per filter, 0 disables the filters
+    pub pruned_ops_filter_capacity: u64,
+    /// The filters target a false positive rate of 2^-x
+    pub pruned_ops_filter_false_positive_log2: u32,
+    /// Number of expiry periods covered by each filter
+    pub pruned_ops_filter_range_periods: u64,
+    /// Number of filters kept
+    pub pruned_ops_filter_range_count: u64,
 }
 
 #[derive(Debug, Clone)]
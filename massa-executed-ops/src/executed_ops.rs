@@ -3,10 +3,12 @@
 //! This file defines a structure to list and prune previously executed operations.
 //! Used to detect operation reuse.
 
-use crate::{ops_changes::ExecutedOpsChanges, ExecutedOpsConfig};
+use crate::{
+    ops_changes::ExecutedOpsChanges, pruned_ops_filter::PrunedOpsFilter, ExecutedOpsConfig,
+};
 use massa_db_exports::{
-    DBBatch, ShareableMassaDBController, CRUD_ERROR, EXECUTED_OPS_ID_DESER_ERROR,
-    EXECUTED_OPS_ID_SER_ERROR, EXECUTED_OPS_PREFIX, STATE_CF,
+    DBBatch, ShareableMassaDBController, CRUD_ERROR, EXECUTED_OPS_FILTER_PREFIX,
+    EXECUTED_OPS_ID_DESER_ERROR, EXECUTED_OPS_ID_SER_ERROR, EXECUTED_OPS_PREFIX, STATE_CF,
 };
 use massa_models::{
     operation::{OperationId, OperationIdDeserializer, OperationIdSerializer},
@@ -47,6 +49,8 @@ pub struct ExecutedOps {
     pub sorted_ops: BTreeMap<Slot, PreHashSet<OperationId>>,
     /// execution status of operations (true: success, false: fail)
     pub op_exec_status: HashMap<OperationId, bool>,
+    /// bloom filters over the pruned operations
+    pruned_ops_filter: PrunedOpsFilter,
     operation_id_deserializer: OperationIdDeserializer,
     operation_id_serializer: OperationIdSerializer,
     bool_deserializer: BoolDeserializer,
@@ -62,11 +66,13 @@ impl ExecutedOps {
             (Included(u64::MIN), Included(u64::MAX)),
             (Included(0), Excluded(config.thread_count)),
         );
+        let pruned_ops_filter = PrunedOpsFilter::new(&config);
         Self {
             config,
             db,
             sorted_ops: BTreeMap::new(),
             op_exec_status: HashMap::new(),
+            pruned_ops_filter,
             operation_id_deserializer: OperationIdDeserializer::new(),
             operation_id_serializer: OperationIdSerializer::new(),
             bool_deserializer: BoolDeserializer::new(),
@@ -92,6 +98,7 @@ impl ExecutedOps {
         self.op_exec_status.clear();
 
         let db = self.db.read();
+        self.pruned_ops_filter.load(db.as_ref());
 
         for (serialized_op_id, serialized_value) in
             db.prefix_iterator_cf(STATE_CF, EXECUTED_OPS_PREFIX.as_bytes())
@@ -135,15 +142,22 @@ impl ExecutedOps {
         self.db
             .write()
             .delete_prefix(EXECUTED_OPS_PREFIX, STATE_CF, None);
+        self.db
+            .write()
+            .delete_prefix(EXECUTED_OPS_FILTER_PREFIX, STATE_CF, None);
 
         self.recompute_sorted_ops_and_op_exec_status();
     }
 
     /// Apply speculative operations changes to the final executed operations state
+    ///
+    /// `filter_pruned_ops` tells whether the operations pruned at this slot are recorded in the pruned operations
+    /// filters. It must only be set once the network version enabling them is active, as the filters are hashed.
     pub fn apply_changes_to_batch(
         &mut self,
         changes: ExecutedOpsChanges,
         slot: Slot,
+        filter_pruned_ops: bool,
         batch: &mut DBBatch,
     ) {
         for (id, value) in changes.iter() {
@@ -164,10 +178,12 @@ impl ExecutedOps {
             self.op_exec_status.insert(op_id, op_exec_success);
         }
 
-        self.prune_to_batch(slot, batch);
+        self.prune_to_batch(slot, filter_pruned_ops, batch);
     }
 
-    /// Check if an operation was executed
+    /// Check if an operation was executed.
+    /// Operations already pruned are only known through the pruned operations filters,
+    /// which can report operations that were never executed with a low probability.
     pub fn contains(&self, op_id: &OperationId) -> bool {
         let db = self.db.read();
 
@@ -179,21 +195,39 @@ impl ExecutedOps {
         db.get_cf(STATE_CF, op_id_key!(serialized_op_id))
             .expect(CRUD_ERROR)
             .is_some()
+            || self.pruned_ops_filter.contains(op_id)
     }
 
-    /// Prune all expired operations
-    fn prune_to_batch(&mut self, slot: Slot, batch: &mut DBBatch) {
+    /// Slot before which the operations expired are pruned once `slot` is final.
+    /// The operations executed at or after this slot are still known, as they expire after their execution.
+    /// Returns `None` if nothing is pruned yet.
+    ///
+    /// This horizon is part of the consensus state: it is the same on all the nodes.
+    pub fn get_pruning_cutoff(&self, slot: Slot) -> Option<Slot> {
         // Force-keep `keep_executed_history_extra_periods` for API polling safety
-        let cutoff_slot = match slot
-            .period
+        slot.period
             .checked_sub(self.config.keep_executed_history_extra_periods)
-        {
-            Some(cutoff_slot) => Slot::new(cutoff_slot, slot.thread),
-            None => return,
+            .map(|cutoff_period| Slot::new(cutoff_period, slot.thread))
+    }
+
+    /// Prune all expired operations
+    fn prune_to_batch(&mut self, slot: Slot, filter_pruned_ops: bool, batch: &mut DBBatch) {
+        let Some(cutoff_slot) = self.get_pruning_cutoff(slot) else {
+            return;
         };
 
         let kept = self.sorted_ops.split_off(&cutoff_slot);
         let removed = std::mem::take(&mut self.sorted_ops);
+        if filter_pruned_ops {
+            self.pruned_ops_filter.apply_pruning_to_batch(
+                self.db.read().as_ref(),
+                removed
+                    .iter()
+                    .flat_map(|(slot, ids)| ids.iter().map(move |op_id| (slot, op_id))),
+                cutoff_slot,
+                batch,
+            );
+        }
         for (_, ids) in removed {
             for op_id in ids {
                 self.op_exec_status.remove(&op_id);
@@ -245,6 +279,11 @@ impl ExecutedOps {
 
     /// Deserializes the key and value, useful after bootstrap
     pub fn is_key_value_valid(&self, serialized_key: &[u8], serialized_value: &[u8]) -> bool {
+        if serialized_key.starts_with(EXECUTED_OPS_FILTER_PREFIX.as_bytes()) {
+            return self
+                .pruned_ops_filter
+                .is_key_value_valid(serialized_key, serialized_value);
+        }
         if !serialized_key.starts_with(EXECUTED_OPS_PREFIX.as_bytes()) {
             return false;
         }
@@ -293,6 +332,10 @@ fn test_executed_ops_hash_computing() {
     let config = ExecutedOpsConfig {
         thread_count,
         keep_executed_history_extra_periods: 2,
+        pruned_ops_filter_capacity: 0,
+        pruned_ops_filter_false_positive_log2: 0,
+        pruned_ops_filter_range_periods: 0,
+        pruned_ops_filter_range_count: 0,
     };
     let tempdir_a = TempDir::new().expect("cannot create temp directory");
     let tempdir_c = TempDir::new().expect("cannot create temp directory");
@@ -301,12 +344,14 @@ fn test_executed_ops_hash_computing() {
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        block_cache_size: 0,
     };
     let db_c_config = MassaDBConfig {
         path: tempdir_c.path().to_path_buf(),
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        block_cache_size: 0,
     };
 
     let db_a = Arc::new(RwLock::new(
@@ -352,15 +397,15 @@ fn test_executed_ops_hash_computing() {
     };
 
     let mut batch_a = DBBatch::new();
-    a.apply_changes_to_batch(change_a, apply_slot, &mut batch_a);
+    a.apply_changes_to_batch(change_a, apply_slot, false, &mut batch_a);
     db_a.write().write_batch(batch_a, Default::default(), None);
 
     let mut batch_b = DBBatch::new();
-    a.apply_changes_to_batch(change_b, apply_slot, &mut batch_b);
+    a.apply_changes_to_batch(change_b, apply_slot, false, &mut batch_b);
     db_a.write().write_batch(batch_b, Default::default(), None);
 
     let mut batch_c = DBBatch::new();
-    c.apply_changes_to_batch(change_c, apply_slot, &mut batch_c);
+    c.apply_changes_to_batch(change_c, apply_slot, false, &mut batch_c);
     db_c.write().write_batch(batch_c, Default::default(), None);
 
     // check that a.hash ^ $(change_b) = c.hash
@@ -380,7 +425,7 @@ fn test_executed_ops_hash_computing() {
         thread: 0,
     };
     let mut batch_a = DBBatch::new();
-    a.prune_to_batch(prune_slot, &mut batch_a);
+    a.prune_to_batch(prune_slot, false, &mut batch_a);
     db_a.write().write_batch(batch_a, Default::default(), None);
 
     // at this point the hash should have been reset to its original value
@@ -391,6 +436,99 @@ fn test_executed_ops_hash_computing() {
     );
 }
 
+#[test]
+fn test_pruned_ops_filter() {
+    use massa_db_exports::{MassaDBConfig, MassaDBController};
+    use massa_db_worker::MassaDB;
+    use massa_hash::Hash;
+    use massa_models::prehash::PreHashMap;
+    use massa_models::secure_share::Id;
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    let thread_count = 2;
+    let config = ExecutedOpsConfig {
+        thread_count,
+        keep_executed_history_extra_periods: 2,
+        pruned_ops_filter_capacity: 100,
+        pruned_ops_filter_false_positive_log2: 16,
+        pruned_ops_filter_range_periods: 4,
+        pruned_ops_filter_range_count: 2,
+    };
+    let tempdir = TempDir::new().expect("cannot create temp directory");
+    let db_config = MassaDBConfig {
+        path: tempdir.path().to_path_buf(),
+        max_history_length: 10,
+        max_new_elements: 100,
+        thread_count,
+        block_cache_size: 0,
+    };
+    let db = Arc::new(RwLock::new(
+        Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+    ));
+    let mut executed_ops = ExecutedOps::new(config.clone(), db.clone());
+
+    // operation i expires at period i
+    let op_ids: Vec<OperationId> = (0u8..20)
+        .map(|i| OperationId::new(Hash::compute_from(&[i])))
+        .collect();
+    let mut changes = PreHashMap::default();
+    for (period, op_id) in op_ids.iter().enumerate() {
+        changes.insert(*op_id, (true, Slot::new(period as u64, 0)));
+    }
+    let mut batch = DBBatch::new();
+    executed_ops.apply_changes_to_batch(changes, Slot::new(0, 0), true, &mut batch);
+    db.write().write_batch(batch, Default::default(), None);
+    assert!(op_ids.iter().all(|op_id| executed_ops.contains(op_id)));
+
+    // prune the operations expired before period 10:
+    // the ones expired in periods 4 to 9 are still known through the filters of the 2 latest ranges
+    let mut batch = DBBatch::new();
+    executed_ops.prune_to_batch(Slot::new(12, 0), true, &mut batch);
+    db.write().write_batch(batch, Default::default(), None);
+    let check_contains = |executed_ops: &ExecutedOps| {
+        for (period, op_id) in op_ids.iter().enumerate() {
+            assert_eq!(
+                executed_ops.contains(op_id),
+                period >= 4,
+                "period {}",
+                period
+            );
+        }
+    };
+    check_contains(&executed_ops);
+    assert!(executed_ops
+        .get_ops_exec_status(&op_ids[..10])
+        .iter()
+        .all(Option::is_none));
+
+    // the filters are reloaded from the database and are valid bootstrap entries
+    let mut reloaded = ExecutedOps::new(config, db.clone());
+    reloaded.recompute_sorted_ops_and_op_exec_status();
+    check_contains(&reloaded);
+    let filter_entries: Vec<_> = db
+        .read()
+        .prefix_iterator_cf(STATE_CF, EXECUTED_OPS_FILTER_PREFIX.as_bytes())
+        .take_while(|(key, _)| key.starts_with(EXECUTED_OPS_FILTER_PREFIX.as_bytes()))
+        .collect();
+    assert!(!filter_entries.is_empty());
+    assert!(filter_entries
+        .iter()
+        .all(|(key, value)| reloaded.is_key_value_valid(key, value)));
+    assert!(!reloaded.is_key_value_valid(&filter_entries[0].0, &[0u8; 3]));
+
+    // once all the ranges are too old, the filters are removed
+    let mut batch = DBBatch::new();
+    executed_ops.prune_to_batch(Slot::new(30, 0), true, &mut batch);
+    db.write().write_batch(batch, Default::default(), None);
+    assert!(op_ids.iter().all(|op_id| !executed_ops.contains(op_id)));
+    assert!(db
+        .read()
+        .prefix_iterator_cf(STATE_CF, EXECUTED_OPS_FILTER_PREFIX.as_bytes())
+        .all(|(key, _)| !key.starts_with(EXECUTED_OPS_FILTER_PREFIX.as_bytes())));
+}
+
 /// `ExecutedOps` Serializer
 pub struct ExecutedOpsSerializer {
     slot_serializer: SlotSerializer,
@@ -5,6 +5,7 @@ mod denunciations_changes;
 mod executed_denunciations;
 mod executed_ops;
 mod ops_changes;
+mod pruned_ops_filter;
 
 pub use config::*;
 pub use denunciations_changes::*;
@@ -0,0 +1,195 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines bloom filters over the pruned executed operations.
+//!
+//! Executed operations are only listed until their expiry, plus a few extra periods.
+//! When they are pruned, their ids are added to the bloom filter of the range of periods in which they expired,
+//! so that reusing them is still rejected, with a false positive rate targeted by the configuration.
+//! The filters of the latest ranges are part of the final state: they are stored by blocks of bits,
+//! only the blocks having bits set being written.
+
+use crate::ExecutedOpsConfig;
+use massa_db_exports::{DBBatch, MassaDBController, EXECUTED_OPS_FILTER_PREFIX, STATE_CF};
+use massa_models::{operation::OperationId, secure_share::Id, slot::Slot};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Size in bytes of the stored blocks of a filter
+const FILTER_BLOCK_BYTES: usize = 64;
+
+/// Bloom filters over the ids of the pruned executed operations, one per range of expiry periods
+#[derive(Clone)]
+pub(crate) struct PrunedOpsFilter {
+    /// number of bits set per operation
+    hash_count: u32,
+    /// number of periods covered by each filter
+    range_periods: u64,
+    /// number of filters kept
+    range_count: u64,
+    /// number of blocks of each filter
+    block_count: u64,
+    /// bits of the filters, by range index
+    ranges: BTreeMap<u64, Vec<u8>>,
+}
+
+/// Key of a block of the filter of a given range
+fn block_key(range: u64, block: u64) -> Vec<u8> {
+    [
+        EXECUTED_OPS_FILTER_PREFIX.as_bytes(),
+        &range.to_be_bytes(),
+        &block.to_be_bytes(),
+    ]
+    .concat()
+}
+
+/// Range index and block index of a block key
+fn parse_block_key(key: &[u8]) -> Option<(u64, u64)> {
+    let rest = key.strip_prefix(EXECUTED_OPS_FILTER_PREFIX.as_bytes())?;
+    if rest.len() != 16 {
+        return None;
+    }
+    Some((
+        u64::from_be_bytes(rest[..8].try_into().ok()?),
+        u64::from_be_bytes(rest[8..].try_into().ok()?),
+    ))
+}
+
+impl PrunedOpsFilter {
+    /// Creates the filters of the given configuration, sized so that a filter holding
+    /// `pruned_ops_filter_capacity` operations has a false positive rate of `2^-pruned_ops_filter_false_positive_log2`
+    pub fn new(config: &ExecutedOpsConfig) -> Self {
+        // the optimal filter uses log2(1/p) hashes and log2(1/p) / ln(2) bits per element
+        let bit_count = (config.pruned_ops_filter_capacity as u128)
+            * (config.pruned_ops_filter_false_positive_log2 as u128)
+            * 1443
+            / 1000;
+        let block_bits = (FILTER_BLOCK_BYTES * 8) as u128;
+        let block_count =
+            u64::try_from((bit_count + block_bits - 1) / block_bits).unwrap_or(u64::MAX);
+        PrunedOpsFilter {
+            hash_count: config.pruned_ops_filter_false_positive_log2,
+            range_periods: config.pruned_ops_filter_range_periods,
+            range_count: config.pruned_ops_filter_range_count,
+            block_count,
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// Whether the configuration enables the filters
+    pub fn is_enabled(&self) -> bool {
+        self.hash_count > 0
+            && self.range_periods > 0
+            && self.range_count > 0
+            && self.block_count > 0
+    }
+
+    /// Positions of the bits of an operation in a filter, by double hashing of its id
+    fn bit_positions(&self, op_id: &OperationId) -> impl Iterator<Item = u64> {
+        let hash = op_id.get_hash().to_bytes();
+        let h1 = u64::from_be_bytes(hash[..8].try_into().expect("hash is too short"));
+        let h2 = u64::from_be_bytes(hash[8..16].try_into().expect("hash is too short")) | 1;
+        let bit_count = self.block_count * (FILTER_BLOCK_BYTES as u64) * 8;
+        (0..self.hash_count as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bit_count)
+    }
+
+    /// Whether an operation was probably added to one of the filters
+    pub fn contains(&self, op_id: &OperationId) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+        self.ranges.values().any(|bits| {
+            self.bit_positions(op_id)
+                .all(|pos| bits[(pos / 8) as usize] & (1 << (pos % 8)) != 0)
+        })
+    }
+
+    /// Index of the oldest range kept once the operations expired before `cutoff` are pruned
+    fn oldest_kept_range(&self, cutoff: Slot) -> u64 {
+        (cutoff.period / self.range_periods).saturating_sub(self.range_count - 1)
+    }
+
+    /// Adds pruned operations to the filters of the ranges of their expiry slot,
+    /// then removes the filters that are too old once the operations expired before `cutoff` are pruned
+    pub fn apply_pruning_to_batch<'a>(
+        &mut self,
+        db: &dyn MassaDBController,
+        pruned_ops: impl Iterator<Item = (&'a Slot, &'a OperationId)>,
+        cutoff: Slot,
+        batch: &mut DBBatch,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let oldest_kept_range = self.oldest_kept_range(cutoff);
+
+        let byte_count = self.block_count as usize * FILTER_BLOCK_BYTES;
+        let mut modified_blocks = BTreeSet::new();
+        for (expiry_slot, op_id) in pruned_ops {
+            let range = expiry_slot.period / self.range_periods;
+            if range < oldest_kept_range {
+                continue;
+            }
+            let positions: Vec<u64> = self.bit_positions(op_id).collect();
+            let bits = self
+                .ranges
+                .entry(range)
+                .or_insert_with(|| vec![0u8; byte_count]);
+            for pos in positions {
+                bits[(pos / 8) as usize] |= 1 << (pos % 8);
+                modified_blocks.insert((range, pos / 8 / FILTER_BLOCK_BYTES as u64));
+            }
+        }
+        for (range, block) in modified_blocks {
+            let start = block as usize * FILTER_BLOCK_BYTES;
+            db.put_or_update_entry_value(
+                batch,
+                block_key(range, block),
+                &self.ranges[&range][start..start + FILTER_BLOCK_BYTES],
+            );
+        }
+
+        // only the blocks having bits set are stored
+        let kept = self.ranges.split_off(&oldest_kept_range);
+        for (range, bits) in std::mem::replace(&mut self.ranges, kept) {
+            for (block, bytes) in bits.chunks(FILTER_BLOCK_BYTES).enumerate() {
+                if bytes.iter().any(|byte| *byte != 0) {
+                    db.delete_key(batch, block_key(range, block as u64));
+                }
+            }
+        }
+    }
+
+    /// Loads the filters from the database
+    pub fn load(&mut self, db: &dyn MassaDBController) {
+        self.ranges.clear();
+        let byte_count = self.block_count as usize * FILTER_BLOCK_BYTES;
+        let prefix = EXECUTED_OPS_FILTER_PREFIX.as_bytes();
+        for (key, value) in db.prefix_iterator_cf(STATE_CF, prefix) {
+            if !key.starts_with(prefix) {
+                break;
+            }
+            let Some((range, block)) = parse_block_key(&key) else {
+                continue;
+            };
+            if block >= self.block_count || value.len() != FILTER_BLOCK_BYTES {
+                continue;
+            }
+            let start = block as usize * FILTER_BLOCK_BYTES;
+            self.ranges
+                .entry(range)
+                .or_insert_with(|| vec![0u8; byte_count])[start..start + FILTER_BLOCK_BYTES]
+                .copy_from_slice(&value);
+        }
+    }
+
+    /// Checks a stored block of a filter, useful after bootstrap
+    pub fn is_key_value_valid(&self, serialized_key: &[u8], serialized_value: &[u8]) -> bool {
+        match parse_block_key(serialized_key) {
+            Some((_range, block)) => {
+                self.is_enabled()
+                    && block < self.block_count
+                    && serialized_value.len() == FILTER_BLOCK_BYTES
+            }
+            None => false,
+        }
+    }
+}
@@ -1,10 +1,13 @@
 // Copyright (c) 2023 MASSA LABS <info@massa.net>
 
 use crate::types::SlotExecutionOutput;
+use massa_pos_exports::PoSEvent;
 
 /// channels used by the execution worker
 #[derive(Clone)]
 pub struct ExecutionChannels {
     /// Broadcast channel for new slot execution outputs
     pub slot_execution_output_sender: tokio::sync::broadcast::Sender<SlotExecutionOutput>,
+    /// Broadcast channel for PoS events emitted when final slots are applied
+    pub pos_event_sender: tokio::sync::broadcast::Sender<PoSEvent>,
 }
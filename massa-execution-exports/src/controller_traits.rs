@@ -3,15 +3,21 @@
 //! This module exports generic traits representing interfaces for interacting with the Execution worker
 
 use crate::types::{
-    ExecutionBlockMetadata, ExecutionQueryRequest, ExecutionQueryResponse, ReadOnlyExecutionRequest,
+    ExecutionBlockMetadata, ExecutionQueryRequest, ExecutionQueryRequestItem,
+    ExecutionQueryResponse, ReadOnlyExecutionRequest,
 };
 use crate::ExecutionError;
-use crate::{ExecutionAddressInfo, ReadOnlyExecutionOutput};
+use crate::{
+    AddressCycleGasAccounting, ExecutionAddressInfo, OffChainTask, OperationReceipt,
+    ReadOnlyExecutionOutput, StateViewInfo, TransferInfo,
+};
+use massa_hash::Hash;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
 use massa_models::block_id::BlockId;
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::execution::EventFilter;
+use massa_models::ledger_proof::LedgerEntryProof;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
@@ -75,12 +81,48 @@ pub trait ExecutionController: Send + Sync {
         input: Vec<(Address, Vec<u8>)>,
     ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>;
 
+    /// List a page of the datastore keys of an address starting with `prefix`, in ascending order,
+    /// both in the final and the candidate ledger, without loading the whole datastore.
+    /// Only the keys strictly greater than `cursor` are listed, typically the last key of the previous page.
+    ///
+    /// # Return value
+    /// * `(final_keys, candidate_keys)`, each containing at most `limit` keys, or `None` if the address does not exist
+    #[allow(clippy::type_complexity)]
+    fn get_final_and_candidate_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> (Option<Vec<Vec<u8>>>, Option<Vec<Vec<u8>>>);
+
+    /// Get a Merkle proof of the final balance of an address,
+    /// or of one of its final datastore entries if `key` is given.
+    ///
+    /// # Return value
+    /// * `(slot, root, proof)` where `root` commits to the final ledger at `slot`,
+    ///   or `None` if the ledger commitment is disabled
+    fn get_ledger_proof(
+        &self,
+        addr: &Address,
+        key: Option<&[u8]>,
+    ) -> Option<(Slot, Hash, LedgerEntryProof)>;
+
     /// Returns for a given cycle the stakers taken into account
     /// by the selector. That correspond to the `roll_counts` in `cycle - 3`.
     ///
     /// By default it returns an empty map.
     fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64>;
 
+    /// Returns the final roll count of an address at the end of each cycle of `from_cycle..=to_cycle`.
+    /// Cycles that are not in the cycle history anymore are omitted.
+    fn get_roll_history(
+        &self,
+        addr: &Address,
+        from_cycle: u64,
+        to_cycle: u64,
+    ) -> BTreeMap<u64, u64>;
+
     /// Execute read-only SC function call without causing modifications to the consensus state
     ///
     /// # arguments
@@ -104,9 +146,56 @@ pub trait ExecutionController: Send + Sync {
     /// Gets information about a batch of addresses
     fn get_addresses_infos(&self, addresses: &[Address]) -> Vec<ExecutionAddressInfo>;
 
+    /// Gets the gas consumed and fees paid by the operations of a batch of addresses
+    /// in the final slots of the last saved cycles, oldest cycle first
+    fn get_gas_accounting(&self, addresses: &[Address]) -> Vec<Vec<AddressCycleGasAccounting>>;
+
+    /// Gets the coin transfers debiting or crediting an address in the final slots of the kept transfer history,
+    /// from slot `start` (included) to slot `end` (excluded), in execution order
+    fn get_transfers(
+        &self,
+        address: &Address,
+        start: Option<Slot>,
+        end: Option<Slot>,
+    ) -> Vec<TransferInfo>;
+
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats;
 
+    /// Register a node-local task triggered by final execution outputs
+    ///
+    /// # returns
+    /// The id of the registered task, or an error if too many tasks are registered.
+    fn add_offchain_task(&self, task: OffChainTask) -> Result<u64, ExecutionError>;
+
+    /// Unregister node-local tasks by id. Unknown ids are ignored.
+    fn remove_offchain_tasks(&self, task_ids: &[u64]);
+
+    /// Get the registered node-local tasks, indexed by id
+    fn get_offchain_tasks(&self) -> BTreeMap<u64, OffChainTask>;
+
+    /// Get the receipt of an operation executed in a candidate or final block,
+    /// or None if it was not executed or if its receipt was dropped from the final receipts
+    fn get_operation_receipt(&self, operation_id: &OperationId) -> Option<OperationReceipt>;
+
+    /// Open a state view pinning the current final and candidate ledger,
+    /// so that successive queries referencing it see the same state until it expires
+    ///
+    /// # returns
+    /// The view, or an error if too many views are open.
+    fn open_state_view(&self) -> Result<StateViewInfo, ExecutionError>;
+
+    /// Close a state view before it expires. Unknown ids are ignored.
+    fn close_state_view(&self, view_id: u64);
+
+    /// Query the final ledger as it was at a past final slot, within the kept history.
+    /// Only the ledger queries are supported, the candidate ones being answered with the final ledger at `slot`.
+    fn query_state_at_slot(
+        &self,
+        slot: Slot,
+        requests: Vec<ExecutionQueryRequestItem>,
+    ) -> ExecutionQueryResponse;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ExecutionController>`.
     fn clone_box(&self) -> Box<dyn ExecutionController>;
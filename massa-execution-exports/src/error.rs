@@ -3,6 +3,10 @@
 //! this file defines all possible execution error categories
 
 use displaydoc::Display;
+use massa_models::address::Address;
+use massa_models::amount::Amount;
+use massa_models::error_code::{ErrorCategory, ErrorCode, ErrorCodeInfo};
+use massa_models::slot::Slot;
 use massa_module_cache::error::CacheError;
 use massa_sc_runtime::VMError;
 use massa_versioning::versioning_factory::FactoryError;
@@ -48,12 +52,18 @@ pub enum ExecutionError {
     /// Given gas is above the threshold: {0}
     TooMuchGas(String),
 
+    /// Invalid gas configuration: {0}
+    GasConfigError(String),
+
     /// Include operation error: {0}
     IncludeOperationError(String),
 
     /// Include denunciation error: {0}
     IncludeDenunciationError(String),
 
+    /// Off-chain task error: {0}
+    OffChainTaskError(String),
+
     /// VM Error in {context} context: {error}
     VMError {
         /// execution context in which the error happened
@@ -67,6 +77,62 @@ pub enum ExecutionError {
 
     /// Factory error: {0}
     FactoryError(#[from] FactoryError),
+
+    /// Out of gas: {0}
+    OutOfGas(String),
+
+    /// Stack overflow at call depth {depth}
+    StackOverflow {
+        /// depth of the call stack when the overflow happened
+        depth: usize,
+    },
+
+    /// Ledger entry of address {address} not found
+    MissingLedgerEntry {
+        /// address without ledger entry
+        address: Address,
+    },
+
+    /// Call depth exceeded
+    CallDepthExceeded,
+
+    /// Bytecode of {size} bytes exceeds the maximum size of {max} bytes
+    BytecodeTooLarge {
+        /// size of the bytecode
+        size: usize,
+        /// maximum size of a bytecode
+        max: u64,
+    },
+
+    /// Datastore key of {length} bytes is not in the allowed range of 1 to {max} bytes
+    DatastoreKeyLength {
+        /// length of the key
+        length: usize,
+        /// maximum length of a datastore key
+        max: u8,
+    },
+
+    /// Datastore value of {size} bytes exceeds the maximum size of {max} bytes
+    DatastoreValueTooLarge {
+        /// size of the value
+        size: usize,
+        /// maximum size of a datastore value
+        max: u64,
+    },
+
+    /// Insufficient balance: {needed} coins needed but only {available} available
+    InsufficientBalance {
+        /// amount of coins needed
+        needed: Amount,
+        /// balance available
+        available: Amount,
+    },
+
+    /// State view error: {0}
+    StateViewError(String),
+
+    /// Deferred calls error: {0}
+    DeferredCallsError(String),
 }
 
 /// Execution query errors
@@ -74,4 +140,50 @@ pub enum ExecutionError {
 pub enum ExecutionQueryError {
     /// Not found: {0}
     NotFound(String),
+
+    /// State view {0} not found or expired
+    StateViewNotFound(u64),
+
+    /// Only the ledger queries are supported in a state view
+    NotSupportedInStateView,
+
+    /// Slot {0} is not in the kept history of the final ledger
+    SlotNotInHistory(Slot),
+}
+
+impl ErrorCode for ExecutionError {
+    fn error_code(&self) -> ErrorCodeInfo {
+        let index = match self {
+            ExecutionError::ModelsError(err) => return err.error_code(),
+            ExecutionError::ChannelError(_) => 1,
+            ExecutionError::RuntimeError(_) => 2,
+            ExecutionError::MassaHashError(_) => 3,
+            ExecutionError::RollBuyError(_) => 4,
+            ExecutionError::RollSellError(_) => 5,
+            ExecutionError::SlashError(_) => 6,
+            ExecutionError::TransactionError(_) => 7,
+            ExecutionError::BlockGasError(_) => 8,
+            ExecutionError::InvalidSlotRange => 9,
+            ExecutionError::NotEnoughGas(_) => 10,
+            ExecutionError::TooMuchGas(_) => 11,
+            ExecutionError::IncludeOperationError(_) => 12,
+            ExecutionError::IncludeDenunciationError(_) => 13,
+            ExecutionError::OffChainTaskError(_) => 14,
+            ExecutionError::VMError { .. } => 15,
+            ExecutionError::CacheError(_) => 16,
+            ExecutionError::FactoryError(_) => 17,
+            ExecutionError::GasConfigError(_) => 18,
+            ExecutionError::OutOfGas(_) => 19,
+            ExecutionError::StackOverflow { .. } => 20,
+            ExecutionError::MissingLedgerEntry { .. } => 21,
+            ExecutionError::CallDepthExceeded => 22,
+            ExecutionError::BytecodeTooLarge { .. } => 23,
+            ExecutionError::InsufficientBalance { .. } => 24,
+            ExecutionError::StateViewError(_) => 25,
+            ExecutionError::DeferredCallsError(_) => 26,
+            ExecutionError::DatastoreKeyLength { .. } => 27,
+            ExecutionError::DatastoreValueTooLarge { .. } => 28,
+        };
+        ErrorCodeInfo::new(ErrorCategory::Execution, index)
+    }
 }
@@ -3,8 +3,10 @@
 //! This module represents an event store allowing to store, search and retrieve
 //! a config-limited number of execution-generated events
 
+use massa_models::address::Address;
 use massa_models::execution::EventFilter;
 use massa_models::output_event::SCOutputEvent;
+use massa_models::slot::Slot;
 use std::collections::VecDeque;
 
 /// Store for events emitted by smart contracts
@@ -27,11 +29,48 @@ impl EventStore {
         self.0.clear()
     }
 
-    /// Prune the event store if its size is over the given limit
-    pub fn prune(&mut self, max_events: usize) {
+    /// Prune the event store if its size is over the given limit.
+    /// Returns the slot of the last pruned event, if any
+    pub fn prune(&mut self, max_events: usize) -> Option<Slot> {
+        let mut last_pruned_slot = None;
         while self.0.len() > max_events {
-            self.0.pop_front();
+            last_pruned_slot = self.0.pop_front().map(|event| event.context.slot);
         }
+        last_pruned_slot
+    }
+
+    /// Approximate memory size of the events of the store, in bytes
+    pub fn size(&self) -> usize {
+        self.0.iter().map(event_size).sum()
+    }
+
+    /// Prune the oldest events while the total memory size of the store is over the given limit.
+    /// Returns the slot of the last pruned event, if any
+    pub fn prune_size(&mut self, max_size: usize) -> Option<Slot> {
+        let mut size = self.size();
+        let mut last_pruned_slot = None;
+        while size > max_size {
+            let Some(event) = self.0.pop_front() else {
+                break;
+            };
+            size -= event_size(&event);
+            last_pruned_slot = Some(event.context.slot);
+        }
+        last_pruned_slot
+    }
+
+    /// Prune the events emitted before the given slot.
+    /// The events are expected to be sorted by slot, as they are when they become final.
+    /// Returns the slot of the last pruned event, if any
+    pub fn prune_before(&mut self, slot: Slot) -> Option<Slot> {
+        let mut last_pruned_slot = None;
+        while let Some(event) = self.0.front() {
+            if event.context.slot >= slot {
+                break;
+            }
+            last_pruned_slot = self.0.pop_front().map(|event| event.context.slot);
+        }
+        last_pruned_slot
     }
 
     /// Extend the event store with another store
@@ -99,6 +138,13 @@ impl EventStore {
     }
 }
 
+/// Approximate memory size of an event, in bytes
+fn event_size(event: &SCOutputEvent) -> usize {
+    std::mem::size_of::<SCOutputEvent>()
+        + event.data.len()
+        + event.context.call_stack.len() * std::mem::size_of::<Address>()
+}
+
 #[test]
 fn test_prune() {
     use massa_models::output_event::{EventExecutionContext, SCOutputEvent};
@@ -121,9 +167,20 @@ fn test_prune() {
         });
     }
     assert_eq!(store.0.len(), 10);
-    store.prune(3);
+    assert_eq!(store.prune(3), Some(Slot::new(6, 0)));
     assert_eq!(store.0.len(), 3);
     assert_eq!(store.0[2].data, "9");
     assert_eq!(store.0[1].data, "8");
     assert_eq!(store.0[0].data, "7");
+
+    assert_eq!(store.prune_before(Slot::new(9, 0)), Some(Slot::new(8, 0)));
+    assert_eq!(store.0.len(), 1);
+    assert_eq!(store.0[0].data, "9");
+    assert_eq!(store.prune_before(Slot::new(9, 0)), None);
+    assert_eq!(store.prune(3), None);
+
+    let event_size = event_size(&store.0[0]);
+    assert_eq!(store.prune_size(event_size), None);
+    assert_eq!(store.prune_size(event_size - 1), Some(Slot::new(9, 0)));
+    assert!(store.0.is_empty());
 }
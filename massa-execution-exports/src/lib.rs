@@ -41,18 +41,28 @@
 //!
 //! When the crate feature `testing` is enabled, tooling useful for testing purposes is exported.
 //! See `test_exports/mod.rs` for details.
+//!
+//! ## Crate features
+//!
+//! The `grpc` feature enables the conversions to the gRPC API types, and the `channels` feature
+//! the `ExecutionChannels` broadcast channels, which require tokio. Both are enabled by default:
+//! tooling only needing the types and their serializers can disable the default features.
 
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
+#[cfg(feature = "channels")]
 mod channels;
 mod controller_traits;
 mod error;
 mod event_store;
 /// mapping grpc
+#[cfg(feature = "grpc")]
 pub mod mapping_grpc;
+mod offchain_tasks;
 mod settings;
 mod types;
 
+#[cfg(feature = "channels")]
 pub use channels::ExecutionChannels;
 #[cfg(any(test, feature = "testing"))]
 pub use controller_traits::MockExecutionController;
@@ -60,13 +70,19 @@ pub use controller_traits::{ExecutionController, ExecutionManager};
 pub use error::{ExecutionError, ExecutionQueryError};
 pub use event_store::EventStore;
 pub use massa_sc_runtime::GasCosts;
-pub use settings::{ExecutionConfig, StorageCostsConstants};
+pub use offchain_tasks::{
+    OffChainDatastoreChange, OffChainDeferredCredit, OffChainNotification,
+    OffChainProductionResult, OffChainTask, OffChainTaskAction, OffChainTaskTrigger,
+};
+pub use settings::{ExecutionConfig, SpeculativeCacheEviction, StorageCostsConstants};
 pub use types::{
-    ExecutedBlockInfo, ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionOutput,
-    ExecutionQueryCycleInfos, ExecutionQueryExecutionStatus, ExecutionQueryRequest,
-    ExecutionQueryRequestItem, ExecutionQueryResponse, ExecutionQueryResponseItem,
-    ExecutionQueryStakerInfo, ExecutionStackElement, ReadOnlyCallRequest, ReadOnlyExecutionOutput,
-    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, SlotExecutionOutput,
+    AddressCycleGasAccounting, ExecutedBlockInfo, ExecutionAddressInfo, ExecutionBlockMetadata,
+    ExecutionOutput, ExecutionQueryCycleInfos, ExecutionQueryExecutionStatus,
+    ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionQueryResponse,
+    ExecutionQueryResponseItem, ExecutionQueryStakerInfo, ExecutionStackElement,
+    ExecutionTraceEntry, OperationBalanceChange, OperationReceipt, ReadOnlyCallRequest,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    SlotExecutionOutput, StateViewInfo, TransferInfo,
 };
 
 #[cfg(any(feature = "testing", feature = "gas_calibration"))]
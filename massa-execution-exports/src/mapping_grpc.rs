@@ -349,6 +349,15 @@ impl From<ExecutionQueryError> for grpc_model::Error {
                 code: 404,
                 message: error,
             },
+            err @ (ExecutionQueryError::StateViewNotFound(_)
+            | ExecutionQueryError::SlotNotInHistory(_)) => grpc_model::Error {
+                code: 404,
+                message: err.to_string(),
+            },
+            err @ ExecutionQueryError::NotSupportedInStateView => grpc_model::Error {
+                code: 400,
+                message: err.to_string(),
+            },
         }
     }
 }
@@ -0,0 +1,110 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines the node-local tasks triggered by final execution outputs (off-chain workers).
+//!
+//! Node operators register tasks through the private API. Each time a slot becomes final,
+//! the events, datastore changes or staking results matching the trigger of a task are sent to its action:
+//! a local program receiving a JSON notification on its standard input, or an HTTP webhook.
+//! Tasks have no effect on consensus and are not persisted across restarts.
+
+use massa_models::{address::Address, amount::Amount, output_event::SCOutputEvent, slot::Slot};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Condition triggering an off-chain task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OffChainTaskTrigger {
+    /// final smart contract events
+    ScEvent {
+        /// only the events emitted by this address (last address of the call stack)
+        emitter_address: Option<Address>,
+        /// only the events whose data contains this string
+        data_contains: Option<String>,
+    },
+    /// final datastore changes of an address
+    DatastoreChange {
+        /// address owning the datastore
+        address: Address,
+        /// only the keys starting with this prefix
+        key_prefix: Vec<u8>,
+    },
+    /// final block production results and deferred credits of the staking addresses of the node,
+    /// including the watch-only ones
+    Staking,
+}
+
+/// Action run when an off-chain task is triggered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OffChainTaskAction {
+    /// run a local program, the JSON notification is written on its standard input
+    Command {
+        /// path of the program, relative to the off-chain programs directory of the node or absolute within it
+        program: PathBuf,
+        /// arguments of the program
+        args: Vec<String>,
+    },
+    /// POST the JSON notification to an `http://` URL
+    Webhook {
+        /// URL of the webhook
+        url: String,
+    },
+}
+
+/// Node-local task triggered by final execution outputs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffChainTask {
+    /// condition triggering the task
+    pub trigger: OffChainTaskTrigger,
+    /// action run when the task is triggered
+    pub action: OffChainTaskAction,
+}
+
+/// Final change of a datastore entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffChainDatastoreChange {
+    /// address owning the datastore
+    pub address: Address,
+    /// key of the entry
+    pub key: Vec<u8>,
+    /// new value of the entry, `None` if it was deleted
+    pub value: Option<Vec<u8>>,
+}
+
+/// Final block production result of a staking address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffChainProductionResult {
+    /// address drawn to produce the block
+    pub address: Address,
+    /// whether the block was produced or missed
+    pub produced: bool,
+}
+
+/// Final deferred credit of a staking address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffChainDeferredCredit {
+    /// credited address
+    pub address: Address,
+    /// slot at which the address will be credited
+    pub slot: Slot,
+    /// credited amount, zero if the credit was cancelled
+    pub amount: Amount,
+}
+
+/// Notification sent to the action of a triggered off-chain task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OffChainNotification {
+    /// id of the triggered task
+    pub task_id: u64,
+    /// final slot that triggered the task
+    pub slot: Slot,
+    /// matching events of the slot
+    pub events: Vec<SCOutputEvent>,
+    /// matching datastore changes of the slot
+    pub datastore_changes: Vec<OffChainDatastoreChange>,
+    /// block production results of the matching addresses at the slot
+    pub production_results: Vec<OffChainProductionResult>,
+    /// deferred credits of the matching addresses set by the slot
+    pub deferred_credits: Vec<OffChainDeferredCredit>,
+}
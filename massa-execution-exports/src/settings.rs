@@ -2,10 +2,12 @@
 
 //! This module provides the structures used to provide configuration parameters to the Execution system
 
+use crate::ExecutionError;
 use massa_models::amount::Amount;
 use massa_sc_runtime::GasCosts;
 use massa_time::MassaTime;
 use num::rational::Ratio;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 /// Storage cost constants
@@ -19,6 +21,17 @@ pub struct StorageCostsConstants {
     pub ledger_entry_datastore_base_cost: Amount,
 }
 
+/// Outputs evicted first when the speculative cache is full after a blockclique change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeculativeCacheEviction {
+    /// evict the outputs cancelled the longest time ago
+    #[default]
+    Oldest,
+    /// evict the outputs of the slots the farthest ahead of the final cursor
+    FarthestAhead,
+}
+
 /// Execution module configuration
 #[derive(Debug, Clone)]
 pub struct ExecutionConfig {
@@ -26,14 +39,31 @@ pub struct ExecutionConfig {
     pub readonly_queue_length: usize,
     /// maximum number of SC output events kept in cache
     pub max_final_events: usize,
+    /// number of periods before the last final slot for which the final SC output events are kept,
+    /// 0 to only limit them by `max_final_events`
+    pub final_events_retention_periods: u64,
+    /// maximum approximate memory size of the SC output events kept in cache, in bytes,
+    /// 0 to only limit them by `max_final_events` and `final_events_retention_periods`
+    pub max_final_events_size: usize,
     /// maximum available gas for asynchronous messages execution
     pub max_async_gas: u64,
+    /// maximum gas of a single asynchronous message
+    pub max_gas_per_async_message: u64,
+    /// maximum gas that deferred calls can book in a single slot
+    pub max_deferred_call_gas_per_slot: u64,
+    /// maximum number of periods between the registration of a deferred call and its target slot
+    pub deferred_call_max_future_periods: u64,
+    /// fee per booked gas of a deferred call targeting a slot with no booked gas
+    pub deferred_call_base_fee_per_gas: Amount,
     /// maximum gas per block
     pub max_gas_per_block: u64,
     /// number of threads
     pub thread_count: u8,
     /// price of a roll inside the network
     pub roll_price: Amount,
+    /// minimal number of cycles between two roll sales of the same address,
+    /// at most the PoS cycle history length (0 for no cooldown)
+    pub roll_sale_cooldown_cycles: u64,
     /// extra lag to add on the execution cursor to improve performance
     pub cursor_delay: MassaTime,
     /// genesis timestamp
@@ -62,6 +92,8 @@ pub struct ExecutionConfig {
     pub max_bytecode_size: u64,
     /// Max datastore value size
     pub max_datastore_value_size: u64,
+    /// Max number of keys in the datastore of an address
+    pub max_datastore_keys_per_address: u64,
     /// Storage cost constants
     pub storage_costs_constants: StorageCostsConstants,
     /// Max gas for read only executions
@@ -74,6 +106,8 @@ pub struct ExecutionConfig {
     pub hd_cache_path: PathBuf,
     /// Maximum number of entries we want to keep in the LRU cache
     pub lru_cache_size: u32,
+    /// Maximum memory in bytes of the LRU cache, estimated from the bytecode size of the modules (0 for no limit)
+    pub lru_cache_max_memory: usize,
     /// Maximum number of entries we want to keep in the HD cache
     pub hd_cache_size: usize,
     /// Amount of entries removed when `hd_cache_size` is reached
@@ -86,6 +120,68 @@ pub struct ExecutionConfig {
     pub broadcast_enabled: bool,
     /// slot execution outputs channel capacity
     pub broadcast_slot_execution_output_channel_capacity: usize,
+    /// PoS events channel capacity
+    pub broadcast_pos_events_channel_capacity: usize,
     /// max size of event data, in bytes
     pub max_event_size: usize,
+    /// max number of off-chain tasks registered on the node
+    pub max_offchain_tasks: usize,
+    /// max number of off-chain task notifications waiting to be run
+    pub offchain_notification_queue_size: usize,
+    /// max duration of the run of an off-chain task action
+    pub offchain_task_timeout: MassaTime,
+    /// directory of the programs that off-chain tasks are allowed to run (None disables program actions)
+    pub offchain_programs_dir: Option<PathBuf>,
+    /// max number of candidate slot outputs cancelled by blockclique changes kept for reuse (0 disables the cache)
+    pub speculative_cache_size: usize,
+    /// max number of slots ahead of the final cursor for which cancelled candidate outputs are kept (0 for no limit)
+    pub speculative_cache_max_slots_ahead: u64,
+    /// maximum approximate memory size of the cancelled candidate outputs kept, in bytes (0 for no limit)
+    pub speculative_cache_max_memory: usize,
+    /// outputs evicted first when the speculative cache is full
+    pub speculative_cache_eviction: SpeculativeCacheEviction,
+    /// file to which the trace of the final slots is written, for differential testing (None disables it)
+    pub execution_trace_path: Option<PathBuf>,
+    /// max number of final operation receipts kept in memory, the oldest ones being dropped first
+    pub max_operation_receipts: usize,
+    /// max number of state views opened through the private API at the same time
+    pub max_state_views: usize,
+    /// duration after which a state view is closed
+    pub state_view_ttl: MassaTime,
+    /// max number of final slots whose reverse ledger changes are kept to query the final ledger at past final slots (0 disables it)
+    pub max_historical_state_slots: usize,
+    /// max number of final slots whose coin transfers are indexed by address (0 disables the transfer history)
+    pub max_transfer_history_slots: usize,
+}
+
+impl ExecutionConfig {
+    /// Checks that the gas limits are consistent with each other and with the gas costs
+    pub fn check(&self) -> Result<(), ExecutionError> {
+        if self.max_gas_per_async_message == 0
+            || self.max_gas_per_async_message > self.max_async_gas
+        {
+            return Err(ExecutionError::GasConfigError(format!(
+                "max_gas_per_async_message ({}) must be between 1 and max_async_gas ({})",
+                self.max_gas_per_async_message, self.max_async_gas
+            )));
+        }
+        if self.max_deferred_call_gas_per_slot == 0 {
+            return Err(ExecutionError::GasConfigError(
+                "max_deferred_call_gas_per_slot must be greater than 0".to_string(),
+            ));
+        }
+        if self.gas_costs.sp_compilation_cost > self.max_gas_per_block {
+            return Err(ExecutionError::GasConfigError(format!(
+                "the compilation cost ({}) exceeds max_gas_per_block ({})",
+                self.gas_costs.sp_compilation_cost, self.max_gas_per_block
+            )));
+        }
+        if self.gas_costs.sp_compilation_cost > self.max_read_only_gas {
+            return Err(ExecutionError::GasConfigError(format!(
+                "the compilation cost ({}) exceeds max_read_only_gas ({})",
+                self.gas_costs.sp_compilation_cost, self.max_read_only_gas
+            )));
+        }
+        Ok(())
+    }
 }
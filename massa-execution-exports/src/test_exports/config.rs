@@ -22,9 +22,16 @@ impl Default for ExecutionConfig {
         Self {
             readonly_queue_length: 100,
             max_final_events: 1000,
+            final_events_retention_periods: 0,
+            max_final_events_size: 0,
             max_async_gas: MAX_ASYNC_GAS,
+            max_gas_per_async_message: MAX_GAS_PER_ASYNC_MESSAGE,
+            max_deferred_call_gas_per_slot: DEFERRED_CALL_MAX_SLOT_GAS,
+            deferred_call_max_future_periods: DEFERRED_CALL_MAX_FUTURE_PERIODS,
+            deferred_call_base_fee_per_gas: DEFERRED_CALL_BASE_FEE_PER_GAS,
             thread_count: THREAD_COUNT,
             roll_price: ROLL_PRICE,
+            roll_sale_cooldown_cycles: ROLL_SALE_COOLDOWN_CYCLES,
             cursor_delay: MassaTime::from_millis(0),
             block_reward: BLOCK_REWARD,
             endorsement_count: ENDORSEMENT_COUNT as u64,
@@ -39,6 +46,7 @@ impl Default for ExecutionConfig {
             max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_bytecode_size: MAX_BYTECODE_LENGTH,
             max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
+            max_datastore_keys_per_address: MAX_DATASTORE_KEYS_PER_ADDRESS,
             storage_costs_constants,
             max_read_only_gas: 100_000_000,
             gas_costs: GasCosts::new(
@@ -57,13 +65,29 @@ impl Default for ExecutionConfig {
             last_start_period: 0,
             hd_cache_path: TempDir::new().unwrap().path().to_path_buf(),
             lru_cache_size: 1000,
+            lru_cache_max_memory: 0,
             hd_cache_size: 10_000,
             snip_amount: 10,
             roll_count_to_slash_on_denunciation: 1,
             denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
             broadcast_enabled: true,
             broadcast_slot_execution_output_channel_capacity: 5000,
+            broadcast_pos_events_channel_capacity: 100,
             max_event_size: 50_000,
+            max_offchain_tasks: 10,
+            offchain_notification_queue_size: 100,
+            offchain_task_timeout: MassaTime::from_millis(1000),
+            offchain_programs_dir: None,
+            speculative_cache_size: 100,
+            speculative_cache_max_slots_ahead: 0,
+            speculative_cache_max_memory: 0,
+            speculative_cache_eviction: Default::default(),
+            execution_trace_path: None,
+            max_operation_receipts: 1000,
+            max_state_views: 10,
+            state_view_ttl: MassaTime::from_millis(60000),
+            max_historical_state_slots: 100,
+            max_transfer_history_slots: 0,
             max_function_length: 1000,
             max_parameter_length: 1000,
         }
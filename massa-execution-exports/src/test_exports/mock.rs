@@ -2,11 +2,13 @@
 
 //! This file defines utilities to mock the crate for testing purposes
 
-use crate::types::{ExecutionQueryRequest, ExecutionQueryResponse};
+use crate::types::{ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionQueryResponse};
 use crate::{
-    ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionController, ExecutionError,
-    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    AddressCycleGasAccounting, ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionController,
+    ExecutionError, OffChainTask, OperationReceipt, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest, StateViewInfo, TransferInfo,
 };
+use massa_hash::Hash;
 use massa_ledger_exports::LedgerEntry;
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::{
@@ -14,6 +16,7 @@ use massa_models::{
     amount::Amount,
     block_id::BlockId,
     execution::EventFilter,
+    ledger_proof::LedgerEntryProof,
     operation::OperationId,
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
@@ -128,8 +131,16 @@ impl ExecutionController for MockExecutionController {
             time_window_end: MassaTime::now().unwrap(),
             final_block_count: 0,
             final_executed_operations_count: 0,
+            final_applied_operations_count: 0,
+            final_failed_operations_count: 0,
+            average_gas_per_block: 0,
+            final_executed_async_messages_count: 0,
+            module_cache_ram_hit_ratio: None,
+            module_cache_hd_hit_ratio: None,
             active_cursor: Slot::new(0, 0),
             final_cursor: Slot::new(0, 0),
+            earliest_final_event_slot: Slot::new(0, 0),
+            earliest_executed_op_slot: Slot::new(0, 0),
         }
     }
 
@@ -191,14 +202,54 @@ impl ExecutionController for MockExecutionController {
         Vec::default()
     }
 
+    fn get_final_and_candidate_datastore_keys_page(
+        &self,
+        _addr: &Address,
+        _prefix: &[u8],
+        _cursor: Option<&[u8]>,
+        _limit: usize,
+    ) -> (Option<Vec<Vec<u8>>>, Option<Vec<Vec<u8>>>) {
+        (None, None)
+    }
+
+    fn get_ledger_proof(
+        &self,
+        _addr: &Address,
+        _key: Option<&[u8]>,
+    ) -> Option<(Slot, Hash, LedgerEntryProof)> {
+        None
+    }
+
     fn get_addresses_infos(&self, _addresses: &[Address]) -> Vec<ExecutionAddressInfo> {
         Vec::default()
     }
 
+    fn get_transfers(
+        &self,
+        _address: &Address,
+        _start: Option<Slot>,
+        _end: Option<Slot>,
+    ) -> Vec<TransferInfo> {
+        Vec::new()
+    }
+
+    fn get_gas_accounting(&self, addresses: &[Address]) -> Vec<Vec<AddressCycleGasAccounting>> {
+        vec![Vec::new(); addresses.len()]
+    }
+
     fn get_cycle_active_rolls(&self, _cycle: u64) -> BTreeMap<Address, u64> {
         BTreeMap::default()
     }
 
+    fn get_roll_history(
+        &self,
+        _addr: &Address,
+        _from_cycle: u64,
+        _to_cycle: u64,
+    ) -> BTreeMap<u64, u64> {
+        BTreeMap::default()
+    }
+
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
@@ -238,4 +289,32 @@ impl ExecutionController for MockExecutionController {
     fn get_ops_exec_status(&self, batch: &[OperationId]) -> Vec<(Option<bool>, Option<bool>)> {
         vec![(None, None); batch.len()]
     }
+
+    fn add_offchain_task(&self, _task: OffChainTask) -> Result<u64, ExecutionError> {
+        Ok(0)
+    }
+
+    fn remove_offchain_tasks(&self, _task_ids: &[u64]) {}
+
+    fn get_offchain_tasks(&self) -> BTreeMap<u64, OffChainTask> {
+        BTreeMap::new()
+    }
+
+    fn get_operation_receipt(&self, _operation_id: &OperationId) -> Option<OperationReceipt> {
+        None
+    }
+
+    fn open_state_view(&self) -> Result<StateViewInfo, ExecutionError> {
+        unimplemented!("mocked execution controller does not support state views for now");
+    }
+
+    fn close_state_view(&self, _view_id: u64) {}
+
+    fn query_state_at_slot(
+        &self,
+        _slot: Slot,
+        _requests: Vec<ExecutionQueryRequestItem>,
+    ) -> ExecutionQueryResponse {
+        unimplemented!("mocked execution controller does not support query_state_at_slot for now");
+    }
 }
@@ -10,8 +10,9 @@ use massa_models::block_id::BlockId;
 use massa_models::bytecode::Bytecode;
 use massa_models::datastore::Datastore;
 use massa_models::denunciation::DenunciationIndex;
+use massa_models::error_code::ErrorCodeInfo;
 use massa_models::execution::EventFilter;
-use massa_models::operation::OperationId;
+use massa_models::operation::{OperationId, OperationType};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::{
@@ -19,6 +20,7 @@ use massa_models::{
 };
 use massa_pos_exports::ProductionStats;
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use std::collections::{BTreeMap, BTreeSet};
 
 /// Metadata needed to execute the block
@@ -34,6 +36,21 @@ pub struct ExecutionBlockMetadata {
 pub struct ExecutionQueryRequest {
     /// List of requests
     pub requests: Vec<ExecutionQueryRequestItem>,
+    /// State view in which the queries are executed, the current state if None
+    pub view_id: Option<u64>,
+}
+
+/// State view pinning the final and candidate ledger for successive queries
+#[derive(Debug, Clone, Copy)]
+pub struct StateViewInfo {
+    /// id of the view, to pass in the queries
+    pub view_id: u64,
+    /// time at which the view is closed
+    pub expires_at: MassaTime,
+    /// last executed candidate slot seen by the view
+    pub candidate_cursor: Slot,
+    /// last executed final slot seen by the view
+    pub final_cursor: Slot,
 }
 
 /// Response to a list of execution queries
@@ -235,6 +252,116 @@ pub struct ExecutionOutput {
     pub state_changes: StateChanges,
     /// events emitted by the execution step
     pub events: EventStore,
+    /// coin transfers and gas charges of the execution step, in execution order.
+    /// Only recorded if the execution trace is enabled
+    pub trace: Vec<ExecutionTraceEntry>,
+    /// receipts of the operations executed in the block of the slot, in execution order
+    pub operation_receipts: Vec<OperationReceipt>,
+    /// coin transfers of the slot, in execution order.
+    /// Only recorded if the transfer history is enabled
+    pub transfers: Vec<TransferInfo>,
+    /// number of asynchronous messages executed at the slot
+    pub executed_async_message_count: usize,
+}
+
+/// Outcome of the execution of an operation included in a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationReceipt {
+    /// operation id
+    pub operation_id: OperationId,
+    /// slot of the block in which the operation was executed
+    pub slot: Slot,
+    /// block in which the operation was executed
+    pub block_id: BlockId,
+    /// true if the operation was executed successfully, false if its effects were reverted
+    pub success: bool,
+    /// address of the creator of the operation, who paid its fee and gas
+    pub caller: Address,
+    /// fee paid by the caller for the inclusion of the operation
+    pub fee: Amount,
+    /// gas used by the execution of the operation
+    pub gas_used: u64,
+    /// error that reverted the effects of the operation, if any
+    pub error: Option<String>,
+    /// stable code of the error that reverted the effects of the operation, if any
+    pub error_code: Option<ErrorCodeInfo>,
+    /// number of events emitted during the execution of the operation
+    pub event_count: u64,
+    /// coins credited to and debited from each address by the operation, fees included
+    pub balance_changes: BTreeMap<Address, OperationBalanceChange>,
+    /// true if the slot of the block is final
+    pub is_final: bool,
+}
+
+/// Coins credited to and debited from an address during the execution of an operation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationBalanceChange {
+    /// total amount credited to the address
+    pub credited: Amount,
+    /// total amount debited from the address
+    pub debited: Amount,
+}
+
+/// Gas consumed and fees paid by the operations of an address in the final slots of a cycle
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddressCycleGasAccounting {
+    /// cycle
+    pub cycle: u64,
+    /// number of operations created by the address and executed in the cycle
+    pub operation_count: u64,
+    /// number of these operations whose effects were reverted
+    pub failed_operation_count: u64,
+    /// total gas used by these operations
+    pub gas_used: u64,
+    /// total fees paid for these operations
+    pub fees_paid: Amount,
+}
+
+/// Coin transfer recorded in the transfer history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferInfo {
+    /// slot at which the transfer was executed
+    pub slot: Slot,
+    /// index of the transfer among the ones of the slot
+    pub index: u64,
+    /// debited address, `None` meaning coins created
+    pub from: Option<Address>,
+    /// credited address, `None` meaning coins destroyed
+    pub to: Option<Address>,
+    /// transferred amount
+    pub amount: Amount,
+    /// operation whose execution made the transfer, fees included, if any
+    pub operation_id: Option<OperationId>,
+}
+
+/// Coin transfer or gas charge recorded in the execution trace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionTraceEntry {
+    /// coins transferred, `None` addresses meaning coins created or destroyed
+    Transfer {
+        /// debited address
+        from: Option<Address>,
+        /// credited address
+        to: Option<Address>,
+        /// transferred amount
+        amount: Amount,
+    },
+    /// gas of an operation charged to the block gas
+    OperationGas {
+        /// operation id
+        operation_id: OperationId,
+        /// charged gas
+        gas: u64,
+    },
+    /// gas of an asynchronous message charged to the asynchronous gas of the slot
+    AsyncMessageGas {
+        /// slot at which the message was emitted
+        emission_slot: Slot,
+        /// index of the message among the ones emitted at that slot
+        emission_index: u64,
+        /// charged gas
+        gas: u64,
+    },
 }
 
 /// structure describing the output of a read only execution
@@ -257,6 +384,9 @@ pub struct ReadOnlyExecutionRequest {
     pub call_stack: Vec<ExecutionStackElement>,
     /// Target of the request
     pub target: ReadOnlyExecutionTarget,
+    /// Coins credited to the last element of the call stack before the execution, as if sent by its caller.
+    /// They are minted for the simulation only.
+    pub coins: Option<Amount>,
     /// execution start state
     ///
     /// Whether to start execution from final or active state
@@ -278,6 +408,9 @@ pub enum ReadOnlyExecutionTarget {
         /// Parameter to pass to the target function
         parameter: Vec<u8>,
     },
+
+    /// Execute a `RollBuy` or `RollSell` operation on behalf of the last element of the call stack
+    RollOperation(OperationType),
 }
 
 /// structure describing a read-only call
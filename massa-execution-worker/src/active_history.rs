@@ -1,17 +1,21 @@
 use massa_async_pool::{AsyncMessage, AsyncMessageId, AsyncMessageUpdate};
+use massa_deferred_calls::{DeferredCall, DeferredCallId};
 use massa_execution_exports::ExecutionOutput;
 use massa_ledger_exports::{
-    Applicable, LedgerEntry, LedgerEntryUpdate, SetOrDelete, SetOrKeep, SetUpdateOrDelete,
+    Applicable, LedgerChanges, LedgerController, LedgerEntry, LedgerEntryUpdate, SetOrDelete,
+    SetOrKeep, SetUpdateOrDelete,
 };
+use massa_models::datastore::get_prefix_bounds;
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::prehash::{CapacityAllocator, PreHashMap, PreHashSet};
 use massa_models::{
     address::Address, amount::Amount, bytecode::Bytecode, operation::OperationId, slot::Slot,
 };
 use massa_pos_exports::DeferredCredits;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ops::Bound;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 /// History of the outputs of recently executed slots.
 /// Slots should be consecutive, oldest at the beginning and latest at the back.
 pub(crate) struct ActiveHistory(pub VecDeque<ExecutionOutput>);
@@ -37,12 +41,12 @@ pub enum SlotIndexPosition {
 }
 
 impl ActiveHistory {
-    /// Remove `slot` and the slots after it from history
-    pub fn truncate_from(&mut self, slot: &Slot, thread_count: u8) {
+    /// Remove `slot` and the slots after it from history, and return their outputs
+    pub fn truncate_from(&mut self, slot: &Slot, thread_count: u8) -> Vec<ExecutionOutput> {
         match self.get_slot_index(slot, thread_count) {
-            SlotIndexPosition::Past => self.0.clear(),
-            SlotIndexPosition::Found(index) => self.0.truncate(index),
-            _ => {}
+            SlotIndexPosition::Past => self.0.drain(..).collect(),
+            SlotIndexPosition::Found(index) => self.0.split_off(index).into(),
+            _ => Vec::new(),
         }
     }
 
@@ -112,6 +116,44 @@ impl ActiveHistory {
         HistorySearchResult::NoInfo
     }
 
+    /// Lazily query (from end to beginning) the active list of deferred call registry changes.
+    ///
+    /// Returns a `HistorySearchResult`.
+    pub fn fetch_deferred_call(&self, id: &DeferredCallId) -> HistorySearchResult<DeferredCall> {
+        for history_element in self.0.iter().rev() {
+            match history_element
+                .state_changes
+                .deferred_call_changes
+                .0
+                .get(id)
+            {
+                Some(SetOrDelete::Set(call)) => return HistorySearchResult::Present(call.clone()),
+                Some(SetOrDelete::Delete) => return HistorySearchResult::Absent,
+                None => (),
+            }
+        }
+        HistorySearchResult::NoInfo
+    }
+
+    /// Gets the deferred call registry changes of the active history targeting a given slot,
+    /// the latest change of each call overriding the previous ones
+    pub fn get_deferred_call_slot_changes(
+        &self,
+        slot: Slot,
+    ) -> BTreeMap<DeferredCallId, SetOrDelete<DeferredCall>> {
+        let mut changes = BTreeMap::new();
+        for history_element in self.0.iter() {
+            changes.extend(
+                history_element
+                    .state_changes
+                    .deferred_call_changes
+                    .get_slot_changes(slot)
+                    .map(|(id, change)| (*id, change.clone())),
+            );
+        }
+        changes
+    }
+
     /// Lazily query (from end to beginning) the active balance of an address after a given index.
     ///
     /// Returns a `HistorySearchResult`.
@@ -197,6 +239,18 @@ impl ActiveHistory {
         })
     }
 
+    /// Lazily query (from end to beginning) the slot of the latest roll sale of an address
+    pub fn fetch_last_roll_sale(&self, addr: &Address) -> Option<Slot> {
+        self.0.iter().rev().find_map(|output| {
+            output
+                .state_changes
+                .pos_changes
+                .roll_sales
+                .get(addr)
+                .cloned()
+        })
+    }
+
     /// Gets all the deferred credits that will be credited until a given slot (included)
     pub fn get_all_deferred_credits_until(&self, slot: &Slot) -> DeferredCredits {
         self.0.iter().fold(DeferredCredits::new(), |mut acc, e| {
@@ -348,3 +402,112 @@ impl ActiveHistory {
             .collect()
     }
 }
+
+/// Applies the ledger changes of an address to the set of its datastore keys within `range`.
+/// A `None` set means that the ledger entry does not exist.
+pub(crate) fn apply_datastore_key_changes(
+    keys: &mut Option<BTreeSet<Vec<u8>>>,
+    change: Option<&SetUpdateOrDelete<LedgerEntry, LedgerEntryUpdate>>,
+    range: (Bound<&Vec<u8>>, Bound<&Vec<u8>>),
+) {
+    match change {
+        // address absent from the changes
+        None => (),
+
+        // address ledger entry being reset to an absolute new list of keys
+        Some(SetUpdateOrDelete::Set(new_ledger_entry)) => {
+            *keys = Some(
+                new_ledger_entry
+                    .datastore
+                    .range::<Vec<u8>, _>(range)
+                    .map(|(k, _v)| k.clone())
+                    .collect(),
+            );
+        }
+
+        // address ledger entry being updated
+        Some(SetUpdateOrDelete::Update(entry_updates)) => {
+            let keys = keys.get_or_insert_with(Default::default);
+            for (ds_key, ds_update) in entry_updates.datastore.range::<Vec<u8>, _>(range) {
+                match ds_update {
+                    SetOrDelete::Set(_) => keys.insert(ds_key.clone()),
+                    SetOrDelete::Delete => keys.remove(ds_key),
+                };
+            }
+        }
+
+        // address ledger entry being deleted
+        Some(SetUpdateOrDelete::Delete) => {
+            *keys = None;
+        }
+    }
+}
+
+/// Lists a page of the datastore keys of an address starting with `prefix` and strictly greater than `cursor`,
+/// in ascending order, in the final `ledger` with the successive `changes` applied on top of it.
+///
+/// The final keys are fetched one page at a time, and the changes are applied to the key range of each page,
+/// so that the whole datastore is never loaded.
+///
+/// # Returns
+/// At most `limit` keys, or `None` if the ledger entry does not exist
+pub(crate) fn get_datastore_keys_page<'a>(
+    ledger: &dyn LedgerController,
+    changes: impl Iterator<Item = &'a LedgerChanges> + Clone,
+    addr: &Address,
+    prefix: &[u8],
+    cursor: Option<&[u8]>,
+    limit: usize,
+) -> Option<Vec<Vec<u8>>> {
+    let (prefix_start, prefix_end) = get_prefix_bounds(prefix);
+    let mut keys = Vec::new();
+    let mut after: Option<Vec<u8>> = cursor.map(|cursor| cursor.to_vec());
+    loop {
+        let final_page = ledger.get_datastore_keys_page(addr, prefix, after.as_deref(), limit);
+
+        // range of the keys covered by the final page
+        let start = match &after {
+            Some(after) if after.as_slice() >= prefix => Bound::Excluded(after),
+            _ => prefix_start.as_ref(),
+        };
+        let page_last = final_page
+            .as_ref()
+            .filter(|page| limit > 0 && page.len() == limit)
+            .and_then(|page| page.last().cloned());
+        let end = match &page_last {
+            Some(last) => Bound::Included(last),
+            None => prefix_end.as_ref(),
+        };
+        let is_empty = match (start, end) {
+            (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+            _ => false,
+        };
+
+        let mut page_keys: Option<BTreeSet<Vec<u8>>> =
+            final_page.map(|page| page.into_iter().collect());
+        if !is_empty {
+            for ledger_changes in changes.clone() {
+                apply_datastore_key_changes(&mut page_keys, ledger_changes.get(addr), (start, end));
+            }
+        } else {
+            // no key can be listed: only track whether the ledger entry exists
+            for ledger_changes in changes.clone() {
+                match ledger_changes.get(addr) {
+                    Some(SetUpdateOrDelete::Delete) => page_keys = None,
+                    Some(_) => {
+                        page_keys.get_or_insert_with(Default::default);
+                    }
+                    None => (),
+                }
+            }
+        }
+        let page_keys = page_keys?;
+        keys.extend(page_keys.into_iter().take(limit - keys.len()));
+
+        // continue after the final page if it was full and the changes deleted some of its keys
+        match end {
+            Bound::Included(last) if keys.len() < limit => after = Some(last.clone()),
+            _ => return Some(keys),
+        }
+    }
+}
@@ -9,16 +9,18 @@
 
 use crate::active_history::HistorySearchResult;
 use crate::speculative_async_pool::SpeculativeAsyncPool;
+use crate::speculative_deferred_calls::SpeculativeDeferredCallRegistry;
 use crate::speculative_executed_denunciations::SpeculativeExecutedDenunciations;
 use crate::speculative_executed_ops::SpeculativeExecutedOps;
 use crate::speculative_ledger::SpeculativeLedger;
 use crate::{active_history::ActiveHistory, speculative_roll_state::SpeculativeRollState};
 use massa_async_pool::{AsyncMessage, AsyncPoolChanges};
 use massa_async_pool::{AsyncMessageId, AsyncMessageInfo};
+use massa_deferred_calls::{DeferredCall, DeferredCallId, DeferredCallRegistryChanges};
 use massa_executed_ops::{ExecutedDenunciationsChanges, ExecutedOpsChanges};
 use massa_execution_exports::{
     EventStore, ExecutedBlockInfo, ExecutionConfig, ExecutionError, ExecutionOutput,
-    ExecutionStackElement,
+    ExecutionStackElement, ExecutionTraceEntry, OperationReceipt, TransferInfo,
 };
 use massa_final_state::{FinalState, StateChanges};
 use massa_hash::Hash;
@@ -26,6 +28,7 @@ use massa_ledger_exports::{LedgerChanges, SetOrKeep};
 use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::block_id::BlockIdSerializer;
 use massa_models::bytecode::Bytecode;
+use massa_models::config::{DATASTORE_KEY_COUNT_GAS_COST, MAX_DATASTORE_KEYS_PER_ADDRESS_VERSION};
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_models::{
@@ -61,6 +64,9 @@ pub struct ExecutionContextSnapshot {
     /// the associated message infos for the speculative async pool
     pub message_infos: BTreeMap<AsyncMessageId, AsyncMessageInfo>,
 
+    /// speculative deferred call registrations and deletions so far in the context
+    pub deferred_call_changes: DeferredCallRegistryChanges,
+
     /// speculative list of operations executed
     pub executed_ops: ExecutedOpsChanges,
 
@@ -79,12 +85,24 @@ pub struct ExecutionContextSnapshot {
     /// counter of async messages emitted so far in this execution
     pub created_message_index: u64,
 
+    /// counter of deferred calls registered so far in this execution
+    pub created_deferred_call_index: u64,
+
     /// address call stack, most recent is at the back
     pub stack: Vec<ExecutionStackElement>,
 
     /// generated events during this execution, with multiple indexes
     pub events: EventStore,
 
+    /// number of execution trace entries recorded so far in the context
+    pub trace_len: usize,
+
+    /// number of coin transfers recorded so far for the current operation
+    pub operation_transfers_len: usize,
+
+    /// number of coin transfers recorded so far in the transfer history of the slot
+    pub transfers_len: usize,
+
     /// Unsafe random state
     pub unsafe_rng: Xoshiro256PlusPlus,
 }
@@ -115,6 +133,10 @@ pub struct ExecutionContext {
     /// as seen after everything that happened so far in the context
     speculative_async_pool: SpeculativeAsyncPool,
 
+    /// speculative deferred call registry,
+    /// as seen after everything that happened so far in the context
+    speculative_deferred_calls: SpeculativeDeferredCallRegistry,
+
     /// speculative roll state,
     /// as seen after everything that happened so far in the context
     speculative_roll_state: SpeculativeRollState,
@@ -128,6 +150,9 @@ pub struct ExecutionContext {
     /// max gas for this execution
     pub max_gas: u64,
 
+    /// gas charged by the node during this execution for the work not metered by the VM
+    pub host_gas_used: u64,
+
     /// minimal balance allowed for the creator of the operation after its execution
     pub creator_min_balance: Option<Amount>,
 
@@ -143,6 +168,9 @@ pub struct ExecutionContext {
     /// counter of newly created messages so far during this execution
     pub created_message_index: u64,
 
+    /// counter of newly registered deferred calls so far during this execution
+    pub created_deferred_call_index: u64,
+
     /// block Id, if one is present at the execution slot
     pub opt_block_id: Option<BlockId>,
 
@@ -155,6 +183,21 @@ pub struct ExecutionContext {
     /// generated events during this execution, with multiple indexes
     pub events: EventStore,
 
+    /// coin transfers and gas charges recorded if the execution trace is enabled
+    pub trace: Vec<ExecutionTraceEntry>,
+
+    /// coin transfers (from, to, amount) of the operation being executed, used to build its receipt
+    pub operation_transfers: Vec<(Option<Address>, Option<Address>, Amount)>,
+
+    /// receipts of the operations executed so far in the block
+    pub operation_receipts: Vec<OperationReceipt>,
+
+    /// coin transfers of the slot recorded if the transfer history is enabled
+    pub transfers: Vec<TransferInfo>,
+
+    /// number of asynchronous messages taken for execution at the current slot
+    pub executed_async_message_count: usize,
+
     /// Unsafe random state (can be predicted and manipulated)
     pub unsafe_rng: Xoshiro256PlusPlus,
 
@@ -206,6 +249,10 @@ impl ExecutionContext {
                 final_state.clone(),
                 active_history.clone(),
             ),
+            speculative_deferred_calls: SpeculativeDeferredCallRegistry::new(
+                final_state.clone(),
+                active_history.clone(),
+            ),
             speculative_roll_state: SpeculativeRollState::new(
                 final_state.clone(),
                 active_history.clone(),
@@ -219,15 +266,22 @@ impl ExecutionContext {
                 active_history,
             ),
             max_gas: Default::default(),
+            host_gas_used: Default::default(),
             creator_min_balance: Default::default(),
             slot: Slot::new(0, 0),
             created_addr_index: Default::default(),
             created_event_index: Default::default(),
             created_message_index: Default::default(),
+            created_deferred_call_index: Default::default(),
             opt_block_id: Default::default(),
             stack: Default::default(),
             read_only: Default::default(),
             events: Default::default(),
+            trace: Default::default(),
+            operation_transfers: Default::default(),
+            operation_receipts: Default::default(),
+            transfers: Default::default(),
+            executed_async_message_count: Default::default(),
             unsafe_rng: init_prng(&execution_trail_hash),
             creator_address: Default::default(),
             origin_operation_id: Default::default(),
@@ -246,14 +300,19 @@ impl ExecutionContext {
             ledger_changes: self.speculative_ledger.get_snapshot(),
             async_pool_changes,
             message_infos,
+            deferred_call_changes: self.speculative_deferred_calls.get_snapshot(),
             pos_changes: self.speculative_roll_state.get_snapshot(),
             executed_ops: self.speculative_executed_ops.get_snapshot(),
             executed_denunciations: self.speculative_executed_denunciations.get_snapshot(),
             created_addr_index: self.created_addr_index,
             created_event_index: self.created_event_index,
             created_message_index: self.created_message_index,
+            created_deferred_call_index: self.created_deferred_call_index,
             stack: self.stack.clone(),
             events: self.events.clone(),
+            trace_len: self.trace.len(),
+            operation_transfers_len: self.operation_transfers.len(),
+            transfers_len: self.transfers.len(),
             unsafe_rng: self.unsafe_rng.clone(),
         }
     }
@@ -271,6 +330,8 @@ impl ExecutionContext {
             .reset_to_snapshot(snapshot.ledger_changes);
         self.speculative_async_pool
             .reset_to_snapshot((snapshot.async_pool_changes, snapshot.message_infos));
+        self.speculative_deferred_calls
+            .reset_to_snapshot(snapshot.deferred_call_changes);
         self.speculative_roll_state
             .reset_to_snapshot(snapshot.pos_changes);
         self.speculative_executed_ops
@@ -280,7 +341,12 @@ impl ExecutionContext {
         self.created_addr_index = snapshot.created_addr_index;
         self.created_event_index = snapshot.created_event_index;
         self.created_message_index = snapshot.created_message_index;
+        self.created_deferred_call_index = snapshot.created_deferred_call_index;
         self.stack = snapshot.stack;
+        self.trace.truncate(snapshot.trace_len);
+        self.operation_transfers
+            .truncate(snapshot.operation_transfers_len);
+        self.transfers.truncate(snapshot.transfers_len);
         self.unsafe_rng = snapshot.unsafe_rng;
 
         // For events, set snapshot delta to error events.
@@ -328,7 +394,7 @@ impl ExecutionContext {
             generate_execution_trail_hash(&prev_execution_trail_hash, &slot, None, true);
 
         // return readonly context
-        ExecutionContext {
+        let mut context = ExecutionContext {
             max_gas,
             slot,
             stack: call_stack,
@@ -341,7 +407,9 @@ impl ExecutionContext {
                 mip_store,
                 execution_trail_hash,
             )
-        }
+        };
+        context.apply_versioned_rules();
+        context
     }
 
     /// This function takes a batch of asynchronous operations to execute, removing them from the speculative pool.
@@ -357,10 +425,31 @@ impl ExecutionContext {
         &mut self,
         max_gas: u64,
     ) -> Vec<(Option<Bytecode>, AsyncMessage)> {
-        self.speculative_async_pool
+        let batch: Vec<_> = self
+            .speculative_async_pool
             .take_batch_to_execute(self.slot, max_gas)
             .into_iter()
             .map(|(_id, msg)| (self.get_bytecode(&msg.destination), msg))
+            .collect();
+        self.executed_async_message_count += batch.len();
+        batch
+    }
+
+    /// Takes the deferred calls targeting the current slot, removing them from the speculative registry.
+    ///
+    /// # Returns
+    /// A vector of `(Option<Bytecode>, DeferredCallId, DeferredCall)` tuples sorted by call id, where
+    /// `Option<Bytecode>` is the bytecode of the contract that registered the call (or `None` if not found)
+    pub(crate) fn take_deferred_calls(
+        &mut self,
+    ) -> Vec<(Option<Bytecode>, DeferredCallId, DeferredCall)> {
+        let calls = self.speculative_deferred_calls.get_slot_calls(self.slot);
+        calls
+            .into_iter()
+            .map(|(id, call)| {
+                self.speculative_deferred_calls.delete_call(id);
+                (self.get_bytecode(&call.sender_address), id, call)
+            })
             .collect()
     }
 
@@ -398,7 +487,7 @@ impl ExecutionContext {
         );
 
         // return active slot execution context
-        ExecutionContext {
+        let mut context = ExecutionContext {
             slot,
             opt_block_id,
             ..ExecutionContext::new(
@@ -409,7 +498,44 @@ impl ExecutionContext {
                 mip_store,
                 execution_trail_hash,
             )
+        };
+        context.apply_versioned_rules();
+        context
+    }
+
+    /// Applies the execution rules enabled by the network version active at the execution slot
+    fn apply_versioned_rules(&mut self) {
+        let slot_timestamp = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            self.slot,
+        )
+        .expect("could not compute current slot timestamp");
+        let network_version = self
+            .address_factory
+            .mip_store
+            .get_network_version_active_at(slot_timestamp);
+
+        self.speculative_ledger.set_max_datastore_keys_per_address(
+            (network_version >= MAX_DATASTORE_KEYS_PER_ADDRESS_VERSION)
+                .then_some(self.config.max_datastore_keys_per_address),
+        );
+    }
+
+    /// Charges gas for work done by the node on behalf of the execution and not metered by the VM.
+    /// Fails if the gas charged by the node alone exceeds the max gas of the execution,
+    /// the total gas used being checked against it once the VM returns.
+    pub fn charge_host_gas(&mut self, gas: u64) -> Result<(), ExecutionError> {
+        let host_gas_used = self.host_gas_used.saturating_add(gas);
+        if host_gas_used > self.max_gas {
+            return Err(ExecutionError::OutOfGas(format!(
+                "{} gas charged by the node exceeds the max gas of the execution ({})",
+                host_gas_used, self.max_gas
+            )));
         }
+        self.host_gas_used = host_gas_used;
+        Ok(())
     }
 
     /// Gets the address at the top of the call stack, if any
@@ -574,9 +700,22 @@ impl ExecutionContext {
             )));
         }
 
-        // set data entry
-        self.speculative_ledger
-            .set_data_entry(&self.get_current_address()?, address, key, data)
+        // set data entry, charging the update of the key count of the datastore
+        let created = self.speculative_ledger.set_data_entry(
+            &self.get_current_address()?,
+            address,
+            key,
+            data,
+        )?;
+        if created
+            && self
+                .speculative_ledger
+                .get_max_datastore_keys_per_address()
+                .is_some()
+        {
+            self.charge_host_gas(DATASTORE_KEY_COUNT_GAS_COST)?;
+        }
+        Ok(())
     }
 
     /// Appends data to a datastore entry for an address in the speculative ledger.
@@ -618,6 +757,7 @@ impl ExecutionContext {
         // set data entry
         self.speculative_ledger
             .set_data_entry(&self.get_current_address()?, address, key, res_data)
+            .map(|_created| ())
     }
 
     /// Deletes a datastore entry for an address.
@@ -685,7 +825,34 @@ impl ExecutionContext {
 
         // do the transfer
         self.speculative_ledger
-            .transfer_coins(from_addr, to_addr, amount)
+            .transfer_coins(from_addr, to_addr, amount)?;
+        self.record_trace(ExecutionTraceEntry::Transfer {
+            from: from_addr,
+            to: to_addr,
+            amount,
+        });
+        if !self.read_only {
+            self.operation_transfers.push((from_addr, to_addr, amount));
+            if self.config.max_transfer_history_slots > 0 {
+                self.transfers.push(TransferInfo {
+                    slot: self.slot,
+                    index: self.transfers.len() as u64,
+                    from: from_addr,
+                    to: to_addr,
+                    amount,
+                    operation_id: None,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Records an entry of the execution trace, if the trace is enabled.
+    /// Nothing is recorded during read-only executions.
+    pub fn record_trace(&mut self, entry: ExecutionTraceEntry) {
+        if !self.read_only && self.config.execution_trace_path.is_some() {
+            self.trace.push(entry);
+        }
     }
 
     /// Add a new asynchronous message to speculative pool
@@ -709,6 +876,165 @@ impl ExecutionContext {
         }
     }
 
+    /// Computes the fee of a deferred call booking `max_gas` in `target_slot`.
+    ///
+    /// The fee grows with the gas already booked in the target slot:
+    /// `base_fee_per_gas * max_gas * (capacity + booked_gas) / capacity`.
+    /// Fails if the target slot is not in the allowed future range or cannot host the booking.
+    pub fn deferred_call_quote(
+        &self,
+        target_slot: Slot,
+        max_gas: u64,
+    ) -> Result<Amount, ExecutionError> {
+        if target_slot.thread >= self.config.thread_count {
+            return Err(ExecutionError::DeferredCallsError(format!(
+                "target slot thread {} exceeds the thread count",
+                target_slot.thread
+            )));
+        }
+        if target_slot <= self.slot {
+            return Err(ExecutionError::DeferredCallsError(format!(
+                "target slot {} is not after the current slot {}",
+                target_slot, self.slot
+            )));
+        }
+        let max_period = self
+            .slot
+            .period
+            .saturating_add(self.config.deferred_call_max_future_periods);
+        if target_slot.period > max_period {
+            return Err(ExecutionError::DeferredCallsError(format!(
+                "target slot {} is more than {} periods in the future",
+                target_slot, self.config.deferred_call_max_future_periods
+            )));
+        }
+        if max_gas == 0 {
+            return Err(ExecutionError::DeferredCallsError(
+                "a deferred call must book some gas".into(),
+            ));
+        }
+
+        // check that the target slot can host the booking
+        let capacity = self.config.max_deferred_call_gas_per_slot;
+        let booked_gas = self
+            .speculative_deferred_calls
+            .get_slot_booked_gas(target_slot);
+        if booked_gas.saturating_add(max_gas) > capacity {
+            return Err(ExecutionError::DeferredCallsError(format!(
+                "not enough gas left in slot {}: {} booked out of {}, {} requested",
+                target_slot, booked_gas, capacity, max_gas
+            )));
+        }
+
+        // compute the fee, with intermediate values on 128 bits to avoid overflows
+        let raw_fee = (self.config.deferred_call_base_fee_per_gas.to_raw() as u128)
+            .saturating_mul(max_gas as u128)
+            .saturating_mul((capacity as u128).saturating_add(booked_gas as u128))
+            / (capacity as u128);
+        let raw_fee = u64::try_from(raw_fee)
+            .map_err(|_| ExecutionError::DeferredCallsError("deferred call fee overflow".into()))?;
+        Ok(Amount::from_raw(raw_fee))
+    }
+
+    /// Registers a call of the current smart contract to one of its own functions at a future slot.
+    /// The coins and the fee of the call are debited from the contract.
+    ///
+    /// # Arguments
+    /// * `target_slot`: slot at the beginning of which the call is executed
+    /// * `target_function`: name of the called function
+    /// * `parameters`: parameters passed to the called function
+    /// * `coins`: coins credited back to the contract with the call
+    /// * `max_gas`: gas booked for the call
+    ///
+    /// # Returns
+    /// The id of the registered call
+    pub fn deferred_call_register(
+        &mut self,
+        target_slot: Slot,
+        target_function: String,
+        parameters: Vec<u8>,
+        coins: Amount,
+        max_gas: u64,
+    ) -> Result<DeferredCallId, ExecutionError> {
+        let sender_address = self.get_current_address()?;
+        if !matches!(sender_address, Address::SC(..)) {
+            return Err(ExecutionError::DeferredCallsError(
+                "only smart contracts can register deferred calls".into(),
+            ));
+        }
+        if target_function.len() > self.config.max_function_length as usize {
+            return Err(ExecutionError::DeferredCallsError(
+                "function name is too large".into(),
+            ));
+        }
+        if parameters.len() > self.config.max_parameter_length as usize {
+            return Err(ExecutionError::DeferredCallsError(
+                "parameters are too large".into(),
+            ));
+        }
+
+        // pay the coins and the fee of the call
+        let fee = self.deferred_call_quote(target_slot, max_gas)?;
+        let total = coins.checked_add(fee).ok_or_else(|| {
+            ExecutionError::DeferredCallsError("overflow on deferred call coins and fee".into())
+        })?;
+        self.transfer_coins(Some(sender_address), None, total, true)?;
+
+        let id = DeferredCallId::new(target_slot, self.slot, self.created_deferred_call_index);
+        self.created_deferred_call_index += 1;
+        self.speculative_deferred_calls.push_new_call(
+            id,
+            DeferredCall {
+                sender_address,
+                target_slot,
+                target_function,
+                parameters,
+                coins,
+                max_gas,
+                fee,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Checks whether a deferred call is registered and not yet executed nor cancelled
+    pub fn deferred_call_exists(&self, id: &DeferredCallId) -> bool {
+        self.speculative_deferred_calls.get_call(id).is_some()
+    }
+
+    /// Cancels a deferred call registered by the current smart contract.
+    /// The coins of the call are reimbursed to the contract, the fee is not.
+    pub fn deferred_call_cancel(&mut self, id: &DeferredCallId) -> Result<(), ExecutionError> {
+        let call = self
+            .speculative_deferred_calls
+            .get_call(id)
+            .ok_or_else(|| {
+                ExecutionError::DeferredCallsError(format!("deferred call {} not found", id))
+            })?;
+        if call.sender_address != self.get_current_address()? {
+            return Err(ExecutionError::DeferredCallsError(format!(
+                "deferred call {} can only be cancelled by {}",
+                id, call.sender_address
+            )));
+        }
+        self.speculative_deferred_calls.delete_call(*id);
+        self.cancel_deferred_call(&call);
+        Ok(())
+    }
+
+    /// Reimburses the coins of a deferred call that was cancelled or failed
+    ///
+    /// # Arguments
+    /// * `call`: the cancelled deferred call
+    pub fn cancel_deferred_call(&mut self, call: &DeferredCall) {
+        if let Err(e) = self.transfer_coins(None, Some(call.sender_address), call.coins, false) {
+            debug!(
+                "deferred call cancel: reimbursement of {} failed: {}",
+                call.sender_address, e
+            );
+        }
+    }
+
     /// Add `roll_count` rolls to the buyer address.
     /// Validity checks must be performed _outside_ of this function.
     ///
@@ -737,6 +1063,7 @@ impl ExecutionContext {
             self.config.periods_per_cycle,
             self.config.thread_count,
             self.config.roll_price,
+            self.config.roll_sale_cooldown_cycles,
         )
     }
 
@@ -891,6 +1218,7 @@ impl ExecutionContext {
         let state_changes = StateChanges {
             ledger_changes,
             async_pool_changes: self.speculative_async_pool.take(),
+            deferred_call_changes: self.speculative_deferred_calls.take(),
             pos_changes: self.speculative_roll_state.take(),
             executed_ops_changes: self.speculative_executed_ops.take(),
             executed_denunciations_changes: self.speculative_executed_denunciations.take(),
@@ -898,11 +1226,16 @@ impl ExecutionContext {
         };
 
         std::mem::take(&mut self.opt_block_id);
+        self.operation_transfers.clear();
         ExecutionOutput {
             slot,
             block_info,
             state_changes,
             events: std::mem::take(&mut self.events),
+            trace: std::mem::take(&mut self.trace),
+            operation_receipts: std::mem::take(&mut self.operation_receipts),
+            transfers: std::mem::take(&mut self.transfers),
+            executed_async_message_count: std::mem::take(&mut self.executed_async_message_count),
         }
     }
 
@@ -1015,8 +1348,12 @@ impl ExecutionContext {
         address: &Address,
         periods_per_cycle: u64,
     ) -> Vec<ExecutionAddressCycleInfo> {
-        self.speculative_roll_state
-            .get_address_cycle_infos(address, periods_per_cycle, self.slot)
+        self.speculative_roll_state.get_address_cycle_infos(
+            address,
+            periods_per_cycle,
+            self.slot,
+            self.config.max_miss_ratio,
+        )
     }
 
     /// Get future deferred credits of an address
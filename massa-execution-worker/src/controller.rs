@@ -4,16 +4,20 @@
 //! See `massa-execution-exports/controller_traits.rs` for functional details.
 
 use crate::execution::ExecutionState;
+use crate::offchain_tasks::OffChainTasks;
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
 use massa_channel::MassaChannel;
 use massa_execution_exports::{
-    ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionConfig, ExecutionController,
-    ExecutionError, ExecutionManager, ExecutionQueryError, ExecutionQueryExecutionStatus,
-    ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionQueryResponse,
-    ExecutionQueryResponseItem, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    AddressCycleGasAccounting, ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionConfig,
+    ExecutionController, ExecutionError, ExecutionManager, ExecutionQueryError,
+    ExecutionQueryExecutionStatus, ExecutionQueryRequest, ExecutionQueryRequestItem,
+    ExecutionQueryResponse, ExecutionQueryResponseItem, OffChainTask, OperationReceipt,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, StateViewInfo, TransferInfo,
 };
+use massa_hash::Hash;
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::execution::EventFilter;
+use massa_models::ledger_proof::LedgerEntryProof;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
 use massa_models::stats::ExecutionStats;
@@ -96,6 +100,8 @@ pub struct ExecutionControllerImpl {
     pub(crate) input_data: Arc<(Condvar, Mutex<ExecutionInputData>)>,
     /// current execution state (see execution.rs for details)
     pub(crate) execution_state: Arc<RwLock<ExecutionState>>,
+    /// node-local tasks triggered by final execution outputs
+    pub(crate) offchain_tasks: Arc<OffChainTasks>,
 }
 
 impl ExecutionController for ExecutionControllerImpl {
@@ -132,6 +138,9 @@ impl ExecutionController for ExecutionControllerImpl {
     /// Atomically query the execution state with multiple requests
     fn query_state(&self, req: ExecutionQueryRequest) -> ExecutionQueryResponse {
         let execution_lock = self.execution_state.read();
+        if let Some(view_id) = req.view_id {
+            return execution_lock.query_state_view(view_id, req.requests);
+        }
         let mut resp: ExecutionQueryResponse = ExecutionQueryResponse {
             responses: Vec::with_capacity(req.requests.len()),
             candidate_cursor: execution_lock.active_cursor,
@@ -374,11 +383,51 @@ impl ExecutionController for ExecutionControllerImpl {
         result
     }
 
+    /// List a page of the final and candidate datastore keys of an address
+    ///
+    /// # Return value
+    /// * `(final_keys, candidate_keys)`
+    fn get_final_and_candidate_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> (Option<Vec<Vec<u8>>>, Option<Vec<Vec<u8>>>) {
+        self.execution_state
+            .read()
+            .get_final_and_candidate_datastore_keys_page(addr, prefix, cursor, limit)
+    }
+
+    /// Get a Merkle proof of a final ledger entry
+    ///
+    /// # Return value
+    /// * `(slot, root, proof)`
+    fn get_ledger_proof(
+        &self,
+        addr: &Address,
+        key: Option<&[u8]>,
+    ) -> Option<(Slot, Hash, LedgerEntryProof)> {
+        self.execution_state.read().get_ledger_proof(addr, key)
+    }
+
     /// Return the active rolls distribution for the given `cycle`
     fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64> {
         self.execution_state.read().get_cycle_active_rolls(cycle)
     }
 
+    /// Return the final roll count of an address at the end of each cycle of `from_cycle..=to_cycle`
+    fn get_roll_history(
+        &self,
+        addr: &Address,
+        from_cycle: u64,
+        to_cycle: u64,
+    ) -> BTreeMap<u64, u64> {
+        self.execution_state
+            .read()
+            .get_roll_history(addr, from_cycle, to_cycle)
+    }
+
     /// Executes a read-only request
     /// Read-only requests do not modify consensus state
     fn execute_readonly_request(
@@ -455,11 +504,75 @@ impl ExecutionController for ExecutionControllerImpl {
         res
     }
 
+    /// Gets the gas consumed and fees paid by the final operations of a batch of addresses, per cycle
+    fn get_gas_accounting(&self, addresses: &[Address]) -> Vec<Vec<AddressCycleGasAccounting>> {
+        let exec_state = self.execution_state.read();
+        addresses
+            .iter()
+            .map(|addr| exec_state.get_gas_accounting(addr))
+            .collect()
+    }
+
+    /// Gets the final transfers debiting or crediting an address
+    fn get_transfers(
+        &self,
+        address: &Address,
+        start: Option<Slot>,
+        end: Option<Slot>,
+    ) -> Vec<TransferInfo> {
+        self.execution_state
+            .read()
+            .get_transfers(address, start, end)
+    }
+
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats {
         self.execution_state.read().get_stats()
     }
 
+    /// Register a node-local task triggered by final execution outputs
+    fn add_offchain_task(&self, task: OffChainTask) -> Result<u64, ExecutionError> {
+        self.offchain_tasks.add(task)
+    }
+
+    /// Unregister node-local tasks by id
+    fn remove_offchain_tasks(&self, task_ids: &[u64]) {
+        self.offchain_tasks.remove(task_ids)
+    }
+
+    /// Get the registered node-local tasks
+    fn get_offchain_tasks(&self) -> BTreeMap<u64, OffChainTask> {
+        self.offchain_tasks.get_all()
+    }
+
+    /// Get the receipt of an operation executed in a candidate or final block
+    fn get_operation_receipt(&self, operation_id: &OperationId) -> Option<OperationReceipt> {
+        self.execution_state
+            .read()
+            .get_operation_receipt(operation_id)
+    }
+
+    /// Open a state view pinning the current final and candidate ledger
+    fn open_state_view(&self) -> Result<StateViewInfo, ExecutionError> {
+        self.execution_state.write().open_state_view()
+    }
+
+    /// Close a state view before it expires
+    fn close_state_view(&self, view_id: u64) {
+        self.execution_state.write().state_views.close(view_id)
+    }
+
+    /// Query the final ledger at a past final slot
+    fn query_state_at_slot(
+        &self,
+        slot: Slot,
+        requests: Vec<ExecutionQueryRequestItem>,
+    ) -> ExecutionQueryResponse {
+        self.execution_state
+            .read()
+            .query_state_at_slot(slot, requests)
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn ExecutionController>`,
     /// see `massa-execution-exports/controller_traits.rs`
@@ -8,25 +8,40 @@
 //! * the VM is called for execution within this context
 //! * the output of the execution is extracted from the context
 
-use crate::active_history::{ActiveHistory, HistorySearchResult};
+use crate::active_history::{
+    apply_datastore_key_changes, get_datastore_keys_page, ActiveHistory, HistorySearchResult,
+};
 use crate::context::{ExecutionContext, ExecutionContextSnapshot};
+use crate::execution_trace::ExecutionTraceWriter;
+use crate::gas_accounting::GasAccounting;
+use crate::historical_state::HistoricalStates;
 use crate::interface_impl::InterfaceImpl;
-use crate::stats::ExecutionStatsCounter;
+use crate::offchain_tasks::OffChainTasks;
+use crate::operation_receipts::OperationReceiptStore;
+use crate::speculative_cache::{output_content, SpeculativeCache};
+use crate::state_views::StateViews;
+use crate::stats::{ExecutionStatsCounter, FinalBlockStats};
+use crate::transfer_history::TransferHistory;
 use massa_async_pool::AsyncMessage;
+use massa_deferred_calls::{DeferredCall, DeferredCallId};
 use massa_execution_exports::{
-    EventStore, ExecutedBlockInfo, ExecutionBlockMetadata, ExecutionChannels, ExecutionConfig,
-    ExecutionError, ExecutionOutput, ExecutionQueryCycleInfos, ExecutionQueryStakerInfo,
-    ExecutionStackElement, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
-    ReadOnlyExecutionTarget, SlotExecutionOutput,
+    AddressCycleGasAccounting, EventStore, ExecutedBlockInfo, ExecutionBlockMetadata,
+    ExecutionChannels, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionQueryCycleInfos,
+    ExecutionQueryError, ExecutionQueryRequestItem, ExecutionQueryResponse,
+    ExecutionQueryStakerInfo, ExecutionStackElement, ExecutionTraceEntry, OperationBalanceChange,
+    OperationReceipt, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    SlotExecutionOutput, StateViewInfo, TransferInfo,
 };
 use massa_final_state::FinalState;
-use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
 use massa_metrics::MassaMetrics;
 use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::bytecode::Bytecode;
+use massa_models::config::DEFERRED_CALLS_VERSION;
 use massa_models::datastore::get_prefix_bounds;
 use massa_models::denunciation::{Denunciation, DenunciationIndex};
+use massa_models::error_code::ErrorCode;
 use massa_models::execution::EventFilter;
+use massa_models::ledger_proof::LedgerEntryProof;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::stats::ExecutionStats;
@@ -66,12 +81,26 @@ pub(crate) struct ExecutionState {
     // its output is popped from the front of active_history and applied to the final state.
     // It has atomic R/W access.
     active_history: Arc<RwLock<ActiveHistory>>,
+    // outputs of the candidate slots cancelled by blockclique changes, reused if the blockclique switches back
+    speculative_cache: SpeculativeCache,
     // a cursor pointing to the highest executed slot
     pub active_cursor: Slot,
     // a cursor pointing to the highest executed final slot
     pub final_cursor: Slot,
     // store containing execution events that became final
     final_events: EventStore,
+    // earliest slot from which all the final events are in `final_events`
+    final_events_start_slot: Slot,
+    // receipts of the most recent final operations
+    final_operation_receipts: OperationReceiptStore,
+    // gas and fees of the final operations per caller address and cycle
+    gas_accounting: GasAccounting,
+    // state views opened through the API
+    pub(crate) state_views: StateViews,
+    // reverse changes of the last final slots, to query the final ledger at past final slots
+    historical_states: HistoricalStates,
+    // coin transfers of the last final slots, indexed by address
+    transfer_history: TransferHistory,
     // final state with atomic R/W access
     final_state: Arc<RwLock<FinalState>>,
     // execution context (see documentation in context.rs)
@@ -92,6 +121,10 @@ pub(crate) struct ExecutionState {
     channels: ExecutionChannels,
     /// prometheus metrics
     massa_metrics: MassaMetrics,
+    /// node-local tasks triggered by final execution outputs
+    pub(crate) offchain_tasks: Arc<OffChainTasks>,
+    /// writer of the trace of the final slots, if enabled
+    execution_trace: Option<ExecutionTraceWriter>,
 }
 
 impl ExecutionState {
@@ -120,8 +153,23 @@ impl ExecutionState {
             let final_state_read = final_state.read();
             last_final_slot = final_state_read.get_slot();
             execution_trail_hash = final_state_read.get_execution_trail_hash();
+
+            // the previous roll sale of an address is looked up in the cycle history
+            let cycle_history_length = final_state_read.pos_state.config.cycle_history_length;
+            assert!(
+                config.roll_sale_cooldown_cycles <= cycle_history_length as u64,
+                "the roll sale cooldown ({} cycles) exceeds the cycle history length ({} cycles)",
+                config.roll_sale_cooldown_cycles,
+                cycle_history_length
+            );
         }
 
+        // Open the execution trace file
+        let execution_trace = config.execution_trace_path.as_ref().map(|path| {
+            ExecutionTraceWriter::new(path, last_final_slot, execution_trail_hash)
+                .expect("could not open the execution trace file")
+        });
+
         // Create default active history
         let active_history: Arc<RwLock<ActiveHistory>> = Default::default();
 
@@ -131,6 +179,7 @@ impl ExecutionState {
             gas_costs: config.gas_costs.clone(),
             compilation_gas: config.max_gas_per_block,
             lru_cache_size: config.lru_cache_size,
+            lru_cache_max_memory: config.lru_cache_max_memory,
             hd_cache_size: config.hd_cache_size,
             snip_amount: config.snip_amount,
             max_module_length: config.max_bytecode_size,
@@ -159,19 +208,72 @@ impl ExecutionState {
             execution_interface,
             // empty execution output history: it is not recovered through bootstrap
             active_history,
+            speculative_cache: SpeculativeCache::new(
+                config.speculative_cache_size,
+                config.speculative_cache_max_slots_ahead,
+                config.speculative_cache_max_memory,
+                config.speculative_cache_eviction,
+            ),
             // empty final event store: it is not recovered through bootstrap
             final_events: Default::default(),
+            final_events_start_slot: last_final_slot
+                .get_next_slot(config.thread_count)
+                .expect("slot overflow when computing the first slot of the final events"),
+            // empty final receipt store: it is not recovered through bootstrap
+            final_operation_receipts: OperationReceiptStore::new(config.max_operation_receipts),
+            // empty gas accounting: it is not recovered through bootstrap
+            gas_accounting: Default::default(),
+            state_views: StateViews::new(&config),
+            // empty history: it is not recovered through bootstrap
+            historical_states: HistoricalStates::new(config.max_historical_state_slots),
+            // empty transfer history: it is not recovered through bootstrap
+            transfer_history: TransferHistory::new(config.max_transfer_history_slots),
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
             stats_counter: ExecutionStatsCounter::new(config.stats_time_window_duration),
             module_cache,
-            config,
             mip_store,
             selector,
             channels,
             wallet,
             massa_metrics,
+            offchain_tasks: Arc::new(OffChainTasks::new(&config)),
+            execution_trace,
+            config,
+        }
+    }
+
+    /// Prunes the final events over `max_final_events` or `max_final_events_size`,
+    /// or older than `final_events_retention_periods`, and moves the start of the kept events accordingly
+    fn prune_final_events(&mut self) {
+        let mut last_pruned_slot = self.final_events.prune(self.config.max_final_events);
+        if self.config.max_final_events_size > 0 {
+            last_pruned_slot = self
+                .final_events
+                .prune_size(self.config.max_final_events_size)
+                .max(last_pruned_slot);
+        }
+        if self.config.final_events_retention_periods > 0 {
+            if let Some(horizon_period) = self
+                .final_cursor
+                .period
+                .checked_sub(self.config.final_events_retention_periods)
+            {
+                let horizon = Slot::new(horizon_period, self.final_cursor.thread);
+                last_pruned_slot = self
+                    .final_events
+                    .prune_before(horizon)
+                    .max(last_pruned_slot);
+                self.final_events_start_slot = self.final_events_start_slot.max(horizon);
+            }
+        }
+        // the other events of the slot of the last pruned event may have been pruned too
+        if let Some(last_pruned_slot) = last_pruned_slot {
+            let next_slot = last_pruned_slot
+                .get_next_slot(self.config.thread_count)
+                .expect("slot overflow when pruning the final events");
+            self.final_events_start_slot = self.final_events_start_slot.max(next_slot);
         }
     }
 
@@ -180,10 +282,110 @@ impl ExecutionState {
         self.final_state.read().get_fingerprint()
     }
 
+    /// Opens a state view pinning the current final and candidate ledger
+    pub fn open_state_view(&mut self) -> Result<StateViewInfo, ExecutionError> {
+        let final_state_fingerprint = self.get_final_state_fingerprint();
+        let active_history = self.active_history.read().clone();
+        self.state_views.open(
+            self.final_cursor,
+            self.active_cursor,
+            final_state_fingerprint,
+            active_history,
+        )
+    }
+
+    /// Executes a batch of queries in a state view.
+    /// All the queries fail if the view does not exist or has expired.
+    pub fn query_state_view(
+        &self,
+        view_id: u64,
+        requests: Vec<ExecutionQueryRequestItem>,
+    ) -> ExecutionQueryResponse {
+        let Some(view) = self.state_views.get(view_id) else {
+            return ExecutionQueryResponse {
+                responses: requests
+                    .iter()
+                    .map(|_| Err(ExecutionQueryError::StateViewNotFound(view_id)))
+                    .collect(),
+                candidate_cursor: self.active_cursor,
+                final_cursor: self.final_cursor,
+                final_state_fingerprint: self.get_final_state_fingerprint(),
+            };
+        };
+        let final_state = self.final_state.read();
+        ExecutionQueryResponse {
+            responses: requests
+                .into_iter()
+                .map(|request| view.query(final_state.ledger.as_ref(), request))
+                .collect(),
+            candidate_cursor: view.candidate_cursor,
+            final_cursor: view.final_cursor,
+            final_state_fingerprint: view.final_state_fingerprint,
+        }
+    }
+
+    /// Executes a batch of queries against the final ledger at a past final slot.
+    /// All the queries fail if the slot is not in the kept history of the final ledger.
+    pub fn query_state_at_slot(
+        &self,
+        slot: Slot,
+        requests: Vec<ExecutionQueryRequestItem>,
+    ) -> ExecutionQueryResponse {
+        let final_state = self.final_state.read();
+        let Some(view) =
+            self.historical_states
+                .view_at(slot, self.final_cursor, final_state.get_fingerprint())
+        else {
+            return ExecutionQueryResponse {
+                responses: requests
+                    .iter()
+                    .map(|_| Err(ExecutionQueryError::SlotNotInHistory(slot)))
+                    .collect(),
+                candidate_cursor: self.active_cursor,
+                final_cursor: self.final_cursor,
+                final_state_fingerprint: final_state.get_fingerprint(),
+            };
+        };
+        ExecutionQueryResponse {
+            responses: requests
+                .into_iter()
+                .map(|request| view.query(final_state.ledger.as_ref(), request))
+                .collect(),
+            candidate_cursor: view.candidate_cursor,
+            final_cursor: view.final_cursor,
+            final_state_fingerprint: view.final_state_fingerprint,
+        }
+    }
+
+    /// Gets the final transfers debiting or crediting an address from slot `start` (included) to slot `end` (excluded)
+    pub fn get_transfers(
+        &self,
+        address: &Address,
+        start: Option<Slot>,
+        end: Option<Slot>,
+    ) -> Vec<TransferInfo> {
+        self.transfer_history.get(address, start, end)
+    }
+
+    /// Gets the gas consumed and fees paid by the final operations of an address, per cycle
+    pub fn get_gas_accounting(&self, address: &Address) -> Vec<AddressCycleGasAccounting> {
+        self.gas_accounting.get(address)
+    }
+
     /// Get execution statistics
     pub fn get_stats(&self) -> ExecutionStats {
-        self.stats_counter
-            .get_stats(self.active_cursor, self.final_cursor)
+        let earliest_executed_op_slot = self
+            .final_state
+            .read()
+            .executed_ops
+            .get_pruning_cutoff(self.final_cursor)
+            .unwrap_or_else(|| Slot::new(0, 0));
+        self.stats_counter.get_stats(
+            self.active_cursor,
+            self.final_cursor,
+            self.final_events_start_slot,
+            earliest_executed_op_slot,
+        )
     }
 
     /// Applies the output of an execution to the final execution state.
@@ -205,18 +407,69 @@ impl ExecutionState {
             self.stats_counter.register_final_executed_denunciations(
                 exec_out.state_changes.executed_denunciations_changes.len(),
             );
+            let mut block_stats = FinalBlockStats::default();
+            for receipt in &exec_out.operation_receipts {
+                if receipt.success {
+                    block_stats.applied_operations += 1;
+                } else {
+                    block_stats.failed_operations += 1;
+                }
+                block_stats.gas_used = block_stats.gas_used.saturating_add(receipt.gas_used);
+            }
+            self.stats_counter.register_final_block_stats(block_stats);
+            self.gas_accounting.record(
+                exec_out.slot.get_cycle(self.config.periods_per_cycle),
+                &exec_out.operation_receipts,
+            );
         }
+        self.stats_counter
+            .register_final_executed_async_messages(exec_out.executed_async_message_count);
 
         // Update versioning stats
         // This will update the MIP store and must be called before final state write
         // as it will also write the MIP store on disk
         self.update_versioning_stats(&exec_out.block_info, &exec_out.slot);
 
+        // write the trace of the slot before its state changes are consumed
+        if let Some(execution_trace) = self.execution_trace.as_mut() {
+            if let Err(err) = execution_trace.write_slot(&exec_out) {
+                warn!(
+                    "could not write the execution trace of slot {}: {}",
+                    exec_out.slot, err
+                );
+            }
+        }
+
         let exec_out_2 = exec_out.clone();
-        // apply state changes to the final ledger
-        self.final_state
+        // keep the open state views on the ledger they pinned and the history of the final ledger.
+        // The reverse changes are read from the final ledger before the changes are applied to it,
+        // under a read lock: the final state is only written by the execution thread
+        {
+            let final_state = self.final_state.read();
+            self.state_views.record_final_changes(
+                &exec_out.state_changes.ledger_changes,
+                final_state.ledger.as_ref(),
+            );
+            self.historical_states.record_final_changes(
+                self.final_cursor,
+                &exec_out.state_changes.ledger_changes,
+                &final_state,
+            );
+        }
+
+        // apply state changes to the final ledger.
+        // A final slot cannot be skipped: the node stops if its changes cannot be applied
+        let slot = exec_out.slot;
+        if let Err(err) = self
+            .final_state
             .write()
-            .finalize(exec_out.slot, exec_out.state_changes);
+            .finalize(slot, exec_out.state_changes)
+        {
+            panic!(
+                "could not apply the state changes of final slot {}: {}",
+                slot, err
+            );
+        }
 
         // update the final ledger's slot
         self.final_cursor = exec_out.slot;
@@ -227,10 +480,39 @@ impl ExecutionState {
             self.active_cursor = self.final_cursor;
         }
 
+        // notify subscribers if this slot completed a PoS cycle.
+        // This is not gated by `broadcast_enabled` because it happens once per cycle and feeds node metrics.
+        if exec_out
+            .slot
+            .is_last_of_cycle(self.config.periods_per_cycle, self.config.thread_count)
+        {
+            let cycle = exec_out.slot.get_cycle(self.config.periods_per_cycle);
+            let cycle_completed_event = self
+                .final_state
+                .read()
+                .pos_state
+                .get_cycle_completed_event(cycle);
+            if let Some(event) = cycle_completed_event {
+                if let Err(err) = self.channels.pos_event_sender.send(event) {
+                    trace!(
+                        "error, failed to broadcast completion of cycle {} due to: {}",
+                        cycle,
+                        err
+                    );
+                }
+            }
+        }
+
         // append generated events to the final event store
         exec_out.events.finalize();
         self.final_events.extend(exec_out.events);
-        self.final_events.prune(self.config.max_final_events);
+        self.prune_final_events();
+
+        // append the operation receipts to the final receipt store
+        self.final_operation_receipts
+            .extend(exec_out.operation_receipts);
+        self.transfer_history
+            .extend(exec_out.slot, &exec_out.transfers);
 
         // update the prometheus metrics
         self.massa_metrics
@@ -255,6 +537,17 @@ impl ExecutionState {
             self.massa_metrics.inc_executed_final_slot_with_block();
         }
 
+        let module_cache_stats = self.module_cache.write().take_stats();
+        self.massa_metrics
+            .inc_module_cache_hits_and_misses(module_cache_stats.hits, module_cache_stats.misses);
+        self.stats_counter
+            .register_module_cache_stats(module_cache_stats);
+
+        // trigger the node-local tasks matching the final output
+        let staking_addresses = self.wallet.read().get_tracked_address_list();
+        self.offchain_tasks
+            .notify_final_output(&exec_out_2, &staking_addresses);
+
         // Broadcast a final slot execution output to active channel subscribers.
         if self.config.broadcast_enabled {
             let slot_exec_out = SlotExecutionOutput::FinalizedSlot(exec_out_2);
@@ -348,6 +641,7 @@ impl ExecutionState {
 
         // set the context max gas to match the one defined in the operation
         context.max_gas = operation.get_gas_usage();
+        context.host_gas_used = 0;
 
         // set the creator address
         context.creator_address = Some(operation.content_creator_address);
@@ -408,6 +702,13 @@ impl ExecutionState {
         // Add fee from operation.
         let new_block_credits = block_credits.saturating_add(operation.content.fee);
 
+        // only keep the transfers and events of this operation for its receipt, fees included
+        let (events_start, transfers_start) = {
+            let mut context = context_guard!(self);
+            context.operation_transfers.clear();
+            (context.events.0.len(), context.transfers.len())
+        };
+
         let context_snapshot = self.prepare_operation_for_execution(operation, sender_addr)?;
 
         // update block gas
@@ -417,6 +718,7 @@ impl ExecutionState {
         *block_credits = new_block_credits;
 
         // Call the execution process specific to the operation type.
+        // On success, get the gas actually used by the operation.
         let mut execution_result = match &operation.content.op {
             OperationType::ExecuteSC { .. } => {
                 self.execute_executesc_op(&operation.content.op, sender_addr)
@@ -424,15 +726,15 @@ impl ExecutionState {
             OperationType::CallSC { .. } => {
                 self.execute_callsc_op(&operation.content.op, sender_addr)
             }
-            OperationType::RollBuy { .. } => {
-                self.execute_roll_buy_op(&operation.content.op, sender_addr)
-            }
-            OperationType::RollSell { .. } => {
-                self.execute_roll_sell_op(&operation.content.op, sender_addr)
-            }
-            OperationType::Transaction { .. } => {
-                self.execute_transaction_op(&operation.content.op, sender_addr)
-            }
+            OperationType::RollBuy { .. } => self
+                .execute_roll_buy_op(&operation.content.op, sender_addr)
+                .map(|_| op_gas),
+            OperationType::RollSell { .. } => self
+                .execute_roll_sell_op(&operation.content.op, sender_addr)
+                .map(|_| op_gas),
+            OperationType::Transaction { .. } => self
+                .execute_transaction_op(&operation.content.op, sender_addr)
+                .map(|_| op_gas),
         };
 
         {
@@ -454,22 +756,31 @@ impl ExecutionState {
                 }
             }
 
+            // the operation gas is charged to the block whatever the outcome
+            context.record_trace(ExecutionTraceEntry::OperationGas {
+                operation_id,
+                gas: op_gas,
+            });
+
             // check execution results
-            match execution_result {
-                Ok(_) => {
+            let (gas_used, error, error_code) = match execution_result {
+                Ok(gas_used) => {
                     context.insert_executed_op(
                         operation_id,
                         true,
                         Slot::new(operation.content.expire_period, op_thread),
                     );
+                    (gas_used, None, None)
                 }
                 Err(err) => {
                     // an error occurred: emit error event and reset context to snapshot
+                    let error_code = err.error_code();
                     let err = ExecutionError::RuntimeError(format!(
                         "runtime error when executing operation {}: {}",
                         operation_id, &err
                     ));
                     debug!("{}", &err);
+                    let error = err.to_string();
                     context.reset_to_snapshot(context_snapshot, err);
 
                     // Insert op AFTER the context has been restored (otherwise it would be overwritten)
@@ -477,8 +788,44 @@ impl ExecutionState {
                         operation_id,
                         false,
                         Slot::new(operation.content.expire_period, op_thread),
-                    )
+                    );
+                    (op_gas, Some(error), Some(error_code))
                 }
+            };
+
+            // attribute the transfers that were kept to the operation
+            for transfer in context.transfers.iter_mut().skip(transfers_start) {
+                transfer.operation_id = Some(operation_id);
+            }
+
+            // build the receipt of the operation from the transfers and events that were kept
+            let mut balance_changes: BTreeMap<Address, OperationBalanceChange> = BTreeMap::new();
+            for (from, to, amount) in std::mem::take(&mut context.operation_transfers) {
+                if let Some(from) = from {
+                    let change = balance_changes.entry(from).or_default();
+                    change.debited = change.debited.saturating_add(amount);
+                }
+                if let Some(to) = to {
+                    let change = balance_changes.entry(to).or_default();
+                    change.credited = change.credited.saturating_add(amount);
+                }
+            }
+            if let Some(block_id) = context.opt_block_id {
+                let receipt = OperationReceipt {
+                    operation_id,
+                    slot: block_slot,
+                    block_id,
+                    caller: sender_addr,
+                    fee: operation.content.fee,
+                    success: error.is_none(),
+                    gas_used,
+                    error,
+                    error_code,
+                    event_count: context.events.0.len().saturating_sub(events_start) as u64,
+                    balance_changes,
+                    is_final: false,
+                };
+                context.operation_receipts.push(receipt);
             }
         }
 
@@ -615,13 +962,16 @@ impl ExecutionState {
             }
         }
 
-        if self
-            .wallet
-            .read()
-            .get_wallet_address_list()
+        let wallet = self.wallet.read();
+        if wallet.get_wallet_address_list().contains(&addr_denounced) {
+            panic!("You are being slashed at slot {} for double-staking using address {}. The node is stopping to prevent any further loss", block_slot, addr_denounced);
+        }
+        if wallet
+            .get_watch_only_address_list()
             .contains(&addr_denounced)
         {
-            panic!("You are being slashed at slot {} for double-staking using address {}. The node is stopping to prevent any further loss", block_slot, addr_denounced);
+            // the keys are on another machine, which has to be stopped by its operator
+            warn!("Watch-only address {} is being slashed at slot {} for double-staking. Stop the node staking with it to prevent any further loss", addr_denounced, block_slot);
         }
 
         Ok(())
@@ -772,11 +1122,14 @@ impl ExecutionState {
     /// # Arguments
     /// * `operation`: the `WrappedOperation` to process, must be an `ExecuteSC`
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The gas used by the execution
     pub fn execute_executesc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process ExecuteSC operations only
         let (bytecode, max_gas, datastore) = match &operation {
             OperationType::ExecuteSC {
@@ -812,22 +1165,19 @@ impl ExecutionState {
         // sub tmp module compilation cost
         let remaining_gas = max_gas
             .checked_sub(self.config.gas_costs.sp_compilation_cost)
-            .ok_or(ExecutionError::RuntimeError(
+            .ok_or(ExecutionError::OutOfGas(
                 "not enough gas to pay for singlepass compilation".to_string(),
             ))?;
         // run the VM
-        massa_sc_runtime::run_main(
+        let response = massa_sc_runtime::run_main(
             &*self.execution_interface,
             module,
             remaining_gas,
             self.config.gas_costs.clone(),
         )
-        .map_err(|error| ExecutionError::VMError {
-            context: "ExecuteSC".to_string(),
-            error,
-        })?;
+        .map_err(|error| self.vm_error("ExecuteSC", error))?;
 
-        Ok(())
+        self.get_gas_used(*max_gas, response.remaining_gas)
     }
 
     /// Execute an operation of type `CallSC`
@@ -838,11 +1188,14 @@ impl ExecutionState {
     /// * `block_creator_addr`: address of the block creator
     /// * `operation_id`: ID of the operation
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The gas used by the execution
     pub fn execute_callsc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process CallSC operations only
         let (max_gas, target_addr, target_func, param, coins) = match &operation {
             OperationType::CallSC {
@@ -899,7 +1252,7 @@ impl ExecutionState {
 
             // quit if there is no function to be called
             if target_func.is_empty() {
-                return Ok(());
+                return Ok(0);
             }
 
             // Load bytecode. Assume empty bytecode if not found.
@@ -926,11 +1279,45 @@ impl ExecutionState {
             }
             _ => (),
         }
-        response.map_err(|error| ExecutionError::VMError {
-            context: "CallSC".to_string(),
-            error,
-        })?;
-        Ok(())
+        let response = response.map_err(|error| self.vm_error("CallSC", error))?;
+        self.get_gas_used(max_gas, response.remaining_gas)
+    }
+
+    /// Gets the gas used by an execution: the gas metered by the VM and the gas charged by the node.
+    /// Fails if it exceeds the max gas of the execution.
+    /// Must be called without holding the execution context lock.
+    fn get_gas_used(&self, max_gas: u64, remaining_gas: u64) -> Result<u64, ExecutionError> {
+        let gas_used = max_gas
+            .saturating_sub(remaining_gas)
+            .saturating_add(context_guard!(self).host_gas_used);
+        if gas_used > max_gas {
+            return Err(ExecutionError::OutOfGas(format!(
+                "{} gas used, including the gas charged by the node, exceeds the max gas ({})",
+                gas_used, max_gas
+            )));
+        }
+        Ok(gas_used)
+    }
+
+    /// Converts an error of the VM raised while executing `context`.
+    ///
+    /// Gas exhaustion and stack overflows are reported with their own variants,
+    /// the latter with the depth of the call stack at the time of the overflow.
+    /// Must be called without holding the execution context lock.
+    fn vm_error(&self, context: &str, error: VMError) -> ExecutionError {
+        let message = error.to_string();
+        if message.contains("call stack exhausted") {
+            ExecutionError::StackOverflow {
+                depth: context_guard!(self).stack.len(),
+            }
+        } else if message.contains("Not enough gas") {
+            ExecutionError::OutOfGas(message)
+        } else {
+            ExecutionError::VMError {
+                context: context.to_string(),
+                error,
+            }
+        }
     }
 
     /// Tries to execute an asynchronous message
@@ -948,8 +1335,15 @@ impl ExecutionState {
         let context_snapshot;
         let bytecode = {
             let mut context = context_guard!(self);
+            // the message gas was charged to the slot when the message was taken for execution
+            context.record_trace(ExecutionTraceEntry::AsyncMessageGas {
+                emission_slot: message.emission_slot,
+                emission_index: message.emission_index,
+                gas: message.max_gas,
+            });
             context_snapshot = context.get_snapshot();
             context.max_gas = message.max_gas;
+            context.host_gas_used = 0;
             context.creator_address = None;
             context.creator_min_balance = None;
             context.stack = vec![
@@ -967,6 +1361,17 @@ impl ExecutionState {
                 },
             ];
 
+            // if the message asks for more gas than a message can use: fail
+            if message.max_gas > self.config.max_gas_per_async_message {
+                let err = ExecutionError::TooMuchGas(format!(
+                    "message max gas {} exceeds the maximum gas of an asynchronous message ({})",
+                    message.max_gas, self.config.max_gas_per_async_message
+                ));
+                context.reset_to_snapshot(context_snapshot, err.clone());
+                context.cancel_async_message(&message);
+                return Err(err);
+            }
+
             // if the target address is not SC: fail
             if !matches!(message.destination, Address::SC(..)) {
                 let err = ExecutionError::RuntimeError(
@@ -1019,12 +1424,17 @@ impl ExecutionState {
             message.max_gas,
             self.config.gas_costs.clone(),
         );
-        match response {
-            Ok(Response { init_gas_cost, .. }) => {
+        let result = match response {
+            Ok(Response {
+                init_gas_cost,
+                remaining_gas,
+                ..
+            }) => {
                 self.module_cache
                     .write()
                     .set_init_cost(&bytecode, init_gas_cost);
-                Ok(())
+                self.get_gas_used(message.max_gas, remaining_gas)
+                    .map(|_| ())
             }
             Err(error) => {
                 if let VMError::ExecutionError { init_gas_cost, .. } = error {
@@ -1032,17 +1442,117 @@ impl ExecutionState {
                         .write()
                         .set_init_cost(&bytecode, init_gas_cost);
                 }
-                // execution failed: reset context to snapshot and reimburse sender
-                let err = ExecutionError::VMError {
-                    context: "Asynchronous Message".to_string(),
-                    error,
-                };
-                let mut context = context_guard!(self);
+                Err(self.vm_error("Asynchronous Message", error))
+            }
+        };
+        if let Err(err) = &result {
+            // execution failed: reset context to snapshot and reimburse sender
+            let mut context = context_guard!(self);
+            context.reset_to_snapshot(context_snapshot, err.clone());
+            context.cancel_async_message(&message);
+        }
+        result
+    }
+
+    /// Executes a deferred call registered by a smart contract for the current slot.
+    /// The call was removed from the registry when it was taken for execution.
+    ///
+    /// # Arguments
+    /// * `id`: id of the deferred call
+    /// * `call`: the deferred call to execute
+    /// * `bytecode`: bytecode of the smart contract that registered the call, if any
+    pub fn execute_deferred_call(
+        &self,
+        id: DeferredCallId,
+        call: DeferredCall,
+        bytecode: Option<Bytecode>,
+    ) -> Result<(), ExecutionError> {
+        // prepare execution context
+        let context_snapshot;
+        let bytecode = {
+            let mut context = context_guard!(self);
+            context_snapshot = context.get_snapshot();
+            context.max_gas = call.max_gas;
+            context.host_gas_used = 0;
+            context.creator_address = None;
+            context.creator_min_balance = None;
+            context.stack = vec![ExecutionStackElement {
+                address: call.sender_address,
+                coins: call.coins,
+                owned_addresses: vec![call.sender_address],
+                operation_datastore: None,
+            }];
+
+            // if there is no bytecode: fail
+            let bytecode = match bytecode {
+                Some(bytecode) => bytecode,
+                None => {
+                    let err = ExecutionError::DeferredCallsError(format!(
+                        "no bytecode found for the target of deferred call {}",
+                        id
+                    ));
+                    context.reset_to_snapshot(context_snapshot, err.clone());
+                    context.cancel_deferred_call(&call);
+                    return Err(err);
+                }
+            };
+
+            // credit the coins of the call back to the contract
+            if let Err(err) =
+                context.transfer_coins(None, Some(call.sender_address), call.coins, false)
+            {
+                let err = ExecutionError::DeferredCallsError(format!(
+                    "could not credit coins to the target of deferred call {}: {}",
+                    id, err
+                ));
                 context.reset_to_snapshot(context_snapshot, err.clone());
-                context.cancel_async_message(&message);
-                Err(err)
+                return Err(err);
             }
+
+            bytecode.0
+        };
+
+        // load and execute the compiled module
+        // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
+        let module = self
+            .module_cache
+            .write()
+            .load_module(&bytecode, call.max_gas)?;
+        let response = massa_sc_runtime::run_function(
+            &*self.execution_interface,
+            module,
+            &call.target_function,
+            &call.parameters,
+            call.max_gas,
+            self.config.gas_costs.clone(),
+        );
+        let result = match response {
+            Ok(Response {
+                init_gas_cost,
+                remaining_gas,
+                ..
+            }) => {
+                self.module_cache
+                    .write()
+                    .set_init_cost(&bytecode, init_gas_cost);
+                self.get_gas_used(call.max_gas, remaining_gas).map(|_| ())
+            }
+            Err(error) => {
+                if let VMError::ExecutionError { init_gas_cost, .. } = error {
+                    self.module_cache
+                        .write()
+                        .set_init_cost(&bytecode, init_gas_cost);
+                }
+                Err(self.vm_error("Deferred Call", error))
+            }
+        };
+        if let Err(err) = &result {
+            // execution failed: reset context to snapshot and reimburse the contract
+            let mut context = context_guard!(self);
+            context.reset_to_snapshot(context_snapshot, err.clone());
+            context.cancel_deferred_call(&call);
         }
+        result
     }
 
     /// Executes a full slot (with or without a block inside) without causing any changes to the state,
@@ -1072,6 +1582,26 @@ impl ExecutionState {
             self.mip_store.clone(),
         );
 
+        // Get the deferred calls registered for this slot, once the network version executing them is active
+        let slot_ts = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            *slot,
+        )
+        .expect("could not compute current slot timestamp");
+        let deferred_calls =
+            if self.mip_store.get_network_version_active_at(slot_ts) >= DEFERRED_CALLS_VERSION {
+                execution_context.take_deferred_calls()
+            } else {
+                Vec::new()
+            };
+        debug!(
+            "executing {} deferred calls at slot {}",
+            deferred_calls.len(),
+            slot
+        );
+
         // Get asynchronous messages to execute
         let messages = execution_context.take_async_batch(self.config.max_async_gas);
         debug!("executing {} messages at slot {}", messages.len(), slot);
@@ -1079,6 +1609,14 @@ impl ExecutionState {
         // Apply the created execution context for slot execution
         *context_guard!(self) = execution_context;
 
+        // Try executing the deferred calls first, as their gas was booked in advance.
+        // Effects are cancelled on failure and the coins of the call are reimbursed.
+        for (opt_bytecode, id, call) in deferred_calls {
+            if let Err(err) = self.execute_deferred_call(id, call, opt_bytecode) {
+                debug!("failed executing deferred call {}: {}", id, err);
+            }
+        }
+
         // Try executing asynchronous messages.
         // Effects are cancelled on failure and the sender is reimbursed.
         for (opt_bytecode, message) in messages {
@@ -1178,6 +1716,14 @@ impl ExecutionState {
             // Get block creator address
             let block_creator_addr = stored_block.content_creator_address;
 
+            // Get the address credited with the block rewards: the recipient set in the header, if any
+            let block_reward_addr = stored_block
+                .content
+                .header
+                .content
+                .reward_recipient
+                .unwrap_or(block_creator_addr);
+
             // acquire lock on execution context
             let mut context = context_guard!(self);
 
@@ -1227,13 +1773,13 @@ impl ExecutionState {
                 }
             }
 
-            // Credit block creator with remaining_credit
+            // Credit block creator (or its reward recipient) with remaining_credit
             if let Err(err) =
-                context.transfer_coins(None, Some(block_creator_addr), remaining_credit, false)
+                context.transfer_coins(None, Some(block_reward_addr), remaining_credit, false)
             {
                 debug!(
-                    "failed to credit {} coins to block creator {} on block execution: {}",
-                    remaining_credit, block_creator_addr, err
+                    "failed to credit {} coins to block reward recipient {} on block execution: {}",
+                    remaining_credit, block_reward_addr, err
                 )
             }
         } else {
@@ -1248,6 +1794,14 @@ impl ExecutionState {
         let exec_out = context_guard!(self).settle_slot(block_info);
 
         // Broadcast a slot execution output to active channel subscribers.
+        self.broadcast_executed_slot(&exec_out);
+
+        // Return the execution output
+        exec_out
+    }
+
+    /// Broadcasts a slot execution output to active channel subscribers, if enabled
+    fn broadcast_executed_slot(&self, exec_out: &ExecutionOutput) {
         if self.config.broadcast_enabled {
             let slot_exec_out = SlotExecutionOutput::ExecutedSlot(exec_out.clone());
             if let Err(err) = self
@@ -1257,14 +1811,11 @@ impl ExecutionState {
             {
                 trace!(
                     "error, failed to broadcast execution output for slot {} due to: {}",
-                    exec_out.slot.clone(),
+                    exec_out.slot,
                     err
                 );
             }
         }
-
-        // Return the execution output
-        exec_out
     }
 
     /// Execute a candidate slot
@@ -1287,19 +1838,58 @@ impl ExecutionState {
             );
         }
 
+        // contents of the executed candidate slots, on which the outputs of the next ones depend
+        let mut lineage: Vec<_> = if self.speculative_cache.is_enabled() {
+            self.active_history
+                .read()
+                .0
+                .iter()
+                .map(output_content)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         // if the slot was already executed, truncate active history to cancel the slot and all the ones after
         if &self.active_cursor >= slot {
             debug!(
                 "execute_candidate_slot: truncating down from slot {}",
                 self.active_cursor
             );
-            self.active_history
+            let truncated = self
+                .active_history
                 .write()
                 .truncate_from(slot, self.config.thread_count);
             self.active_cursor = slot
                 .get_prev_slot(self.config.thread_count)
                 .expect("overflow when iterating on slots");
+            self.massa_metrics
+                .observe_speculative_rebase_depth(truncated.len());
+            // keep the cancelled outputs in case the blockclique switches back to them
+            let remaining = lineage.len().saturating_sub(truncated.len());
+            self.speculative_cache.insert_truncated(&lineage, truncated);
+            lineage.truncate(remaining);
+        }
+
+        // reuse the output of the slot if it was already executed with the same lineage
+        if self.speculative_cache.is_enabled() {
+            lineage.push((*slot, target_id));
+            let cached_output = self.speculative_cache.take(&lineage);
+            self.massa_metrics
+                .set_speculative_cache_memory(self.speculative_cache.memory());
+            if let Some(exec_out) = cached_output {
+                debug!(
+                    "execute_candidate_slot: reusing the cached output of slot {}",
+                    slot
+                );
+                self.massa_metrics.inc_speculative_cache_hits();
+                self.broadcast_executed_slot(&exec_out);
+                self.apply_active_execution_output(exec_out);
+                return;
+            }
+            self.massa_metrics.inc_speculative_cache_misses();
         }
+
         let exec_out = self.execute_slot(slot, exec_target, selector);
 
         // apply execution output to active state
@@ -1328,6 +1918,9 @@ impl ExecutionState {
             return;
         }
 
+        // the cached candidate outputs must descend from the new final slot
+        self.speculative_cache.on_final_slot(&(*slot, target_id));
+
         // check if the final slot execution result is already cached at the front of the speculative execution history
         let first_exec_output = self.active_history.write().0.pop_front();
         if let Some(exec_out) = first_exec_output {
@@ -1406,7 +1999,7 @@ impl ExecutionState {
         };
 
         // create a readonly execution context
-        let execution_context = ExecutionContext::readonly(
+        let mut execution_context = ExecutionContext::readonly(
             self.config.clone(),
             slot,
             req.max_gas,
@@ -1417,8 +2010,17 @@ impl ExecutionState {
             self.mip_store.clone(),
         );
 
+        // credit the simulated coins to the callee
+        if let Some(coins) = req.coins {
+            if let Some(callee) = execution_context.stack.last_mut() {
+                callee.coins = coins;
+                let address = callee.address;
+                execution_context.transfer_coins(None, Some(address), coins, false)?;
+            }
+        }
+
         // run the interpreter according to the target type
-        let exec_response = match req.target {
+        let (remaining_gas, call_result) = match req.target {
             ReadOnlyExecutionTarget::BytecodeExecution(bytecode) => {
                 // set the execution context
                 *context_guard!(self) = execution_context;
@@ -1429,16 +2031,16 @@ impl ExecutionState {
                     .read()
                     .load_tmp_module(&bytecode, req.max_gas)?;
                 // run the VM
-                massa_sc_runtime::run_main(
+                let response = massa_sc_runtime::run_main(
                     &*self.execution_interface,
                     module,
                     req.max_gas,
                     self.config.gas_costs.clone(),
                 )
-                .map_err(|error| ExecutionError::VMError {
-                    context: "ReadOnlyExecutionTarget::BytecodeExecution".to_string(),
-                    error,
-                })?
+                .map_err(|error| {
+                    self.vm_error("ReadOnlyExecutionTarget::BytecodeExecution", error)
+                })?;
+                (response.remaining_gas, response.ret)
             }
             ReadOnlyExecutionTarget::FunctionCall {
                 target_addr,
@@ -1477,19 +2079,48 @@ impl ExecutionState {
                     }
                     _ => (),
                 }
-                response.map_err(|error| ExecutionError::VMError {
-                    context: "ReadOnlyExecutionTarget::FunctionCall".to_string(),
-                    error,
-                })?
+                let response = response.map_err(|error| {
+                    self.vm_error("ReadOnlyExecutionTarget::FunctionCall", error)
+                })?;
+                (response.remaining_gas, response.ret)
+            }
+            ReadOnlyExecutionTarget::RollOperation(operation) => {
+                // the operation is executed on behalf of the last element of the call stack
+                let Some(sender_addr) = execution_context.stack.last().map(|elem| elem.address)
+                else {
+                    return Err(ExecutionError::RuntimeError(
+                        "empty call stack in read-only roll operation".to_string(),
+                    ));
+                };
+
+                // set the execution context
+                *context_guard!(self) = execution_context;
+
+                // roll operations do not consume gas
+                match operation {
+                    OperationType::RollBuy { .. } => {
+                        self.execute_roll_buy_op(&operation, sender_addr)?
+                    }
+                    OperationType::RollSell { .. } => {
+                        self.execute_roll_sell_op(&operation, sender_addr)?
+                    }
+                    _ => {
+                        return Err(ExecutionError::RuntimeError(
+                            "read-only roll operation must be a RollBuy or a RollSell".to_string(),
+                        ))
+                    }
+                }
+                (req.max_gas, Vec::new())
             }
         };
 
         // return the execution output
+        let gas_cost = self.get_gas_used(req.max_gas, remaining_gas)?;
         let execution_output = context_guard!(self).settle_slot(None);
         Ok(ReadOnlyExecutionOutput {
             out: execution_output,
-            gas_cost: req.max_gas.saturating_sub(exec_response.remaining_gas),
-            call_result: exec_response.ret,
+            gas_cost,
+            call_result,
         })
     }
 
@@ -1580,44 +2211,53 @@ impl ExecutionState {
 
         // traverse the history from oldest to newest, applying additions and deletions
         for output in &self.active_history.read().0 {
-            match output.state_changes.ledger_changes.get(addr) {
-                // address absent from the changes
-                None => (),
-
-                // address ledger entry being reset to an absolute new list of keys
-                Some(SetUpdateOrDelete::Set(new_ledger_entry)) => {
-                    candidate_keys = Some(
-                        new_ledger_entry
-                            .datastore
-                            .range::<Vec<u8>, _>(range_ref)
-                            .map(|(k, _v)| k.clone())
-                            .collect(),
-                    );
-                }
+            apply_datastore_key_changes(
+                &mut candidate_keys,
+                output.state_changes.ledger_changes.get(addr),
+                range_ref,
+            );
+        }
 
-                // address ledger entry being updated
-                Some(SetUpdateOrDelete::Update(entry_updates)) => {
-                    let c_k = candidate_keys.get_or_insert_with(Default::default);
-                    for (ds_key, ds_update) in
-                        entry_updates.datastore.range::<Vec<u8>, _>(range_ref)
-                    {
-                        match ds_update {
-                            SetOrDelete::Set(_) => c_k.insert(ds_key.clone()),
-                            SetOrDelete::Delete => c_k.remove(ds_key),
-                        };
-                    }
-                }
+        (final_keys, candidate_keys)
+    }
 
-                // address ledger entry being deleted
-                Some(SetUpdateOrDelete::Delete) => {
-                    candidate_keys = None;
-                }
-            }
-        }
+    /// Get a page of the final and active datastore keys of the given address,
+    /// strictly greater than `cursor` and in ascending order
+    #[allow(clippy::type_complexity)]
+    pub fn get_final_and_candidate_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> (Option<Vec<Vec<u8>>>, Option<Vec<Vec<u8>>>) {
+        let final_state = self.final_state.read();
+        let ledger = final_state.ledger.as_ref();
+        let final_keys = ledger.get_datastore_keys_page(addr, prefix, cursor, limit);
+
+        // the candidate keys are the final ones with the changes of the active history applied on top of them
+        let active_history = self.active_history.read();
+        let changes = active_history
+            .0
+            .iter()
+            .map(|output| &output.state_changes.ledger_changes);
+        let candidate_keys = get_datastore_keys_page(ledger, changes, addr, prefix, cursor, limit);
 
         (final_keys, candidate_keys)
     }
 
+    /// Get a Merkle proof of the final balance of the given address, or of one of its final datastore entries,
+    /// along with the final slot at which it was computed
+    pub fn get_ledger_proof(
+        &self,
+        addr: &Address,
+        key: Option<&[u8]>,
+    ) -> Option<(Slot, massa_hash::Hash, LedgerEntryProof)> {
+        let final_state = self.final_state.read();
+        let (root, proof) = final_state.ledger.get_ledger_proof(addr, key)?;
+        Some((final_state.get_slot(), root, proof))
+    }
+
     pub fn get_address_cycle_infos(&self, address: &Address) -> Vec<ExecutionAddressCycleInfo> {
         context_guard!(self).get_address_cycle_infos(address, self.config.periods_per_cycle)
     }
@@ -1633,6 +2273,20 @@ impl ExecutionState {
             .get_all_active_rolls(cycle)
     }
 
+    /// Returns the final roll count of an address at the end of each cycle of `from_cycle..=to_cycle`
+    /// that is still in the cycle history
+    pub fn get_roll_history(
+        &self,
+        addr: &Address,
+        from_cycle: u64,
+        to_cycle: u64,
+    ) -> BTreeMap<u64, u64> {
+        self.final_state
+            .read()
+            .pos_state
+            .get_roll_history(addr, from_cycle, to_cycle)
+    }
+
     /// Gets execution events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -1828,6 +2482,25 @@ impl ExecutionState {
             .collect()
     }
 
+    /// Gets the receipt of an operation executed in a candidate or final block.
+    /// Candidate receipts are looked up first, from the most recent slot.
+    pub fn get_operation_receipt(&self, operation_id: &OperationId) -> Option<OperationReceipt> {
+        let candidate_receipt = self
+            .active_history
+            .read()
+            .0
+            .iter()
+            .rev()
+            .find_map(|output| {
+                output
+                    .operation_receipts
+                    .iter()
+                    .find(|receipt| &receipt.operation_id == operation_id)
+                    .cloned()
+            });
+        candidate_receipt.or_else(|| self.final_operation_receipts.get(operation_id).cloned())
+    }
+
     /// Update MipStore with block header stats
     pub fn update_versioning_stats(&mut self, block_info: &Option<ExecutedBlockInfo>, slot: &Slot) {
         let slot_ts = get_block_slot_timestamp(
@@ -0,0 +1,253 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Export of a deterministic trace of the final slots, for differential testing.
+//!
+//! For each final slot, the ledger changes, the coin transfers and the gas charges are written
+//! to the trace file in a canonical text form: ledger changes are sorted by address and datastore key,
+//! transfers and gas charges are kept in execution order, and bytecodes and datastore values are replaced by their hash.
+//! Each slot ends with the hash of its content and a hash chaining it to the previous slots,
+//! seeded with the execution trail hash of the final state the node started from.
+//! Diffing the traces of two nodes started from the same state shows the first divergent slot.
+
+use massa_execution_exports::{ExecutionOutput, ExecutionTraceEntry};
+use massa_hash::Hash;
+use massa_ledger_exports::{SetOrDelete, SetOrKeep, SetUpdateOrDelete};
+use massa_models::address::Address;
+use massa_models::slot::Slot;
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Writer of the execution trace file
+pub(crate) struct ExecutionTraceWriter {
+    file: BufWriter<File>,
+    /// hash chaining all the slots written so far
+    chain_hash: Hash,
+}
+
+impl ExecutionTraceWriter {
+    /// Opens the trace file in append mode and writes a header with the slot and execution trail hash the node starts from
+    pub(crate) fn new(
+        path: &Path,
+        start_slot: Slot,
+        execution_trail_hash: Hash,
+    ) -> std::io::Result<Self> {
+        let mut file = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        writeln!(
+            file,
+            "start {} {} {}",
+            start_slot.period, start_slot.thread, execution_trail_hash
+        )?;
+        file.flush()?;
+        Ok(Self {
+            file,
+            chain_hash: execution_trail_hash,
+        })
+    }
+
+    /// Writes the trace of a final slot
+    pub(crate) fn write_slot(&mut self, exec_out: &ExecutionOutput) -> std::io::Result<()> {
+        let content = format_slot(exec_out);
+        let slot_hash = Hash::compute_from(content.as_bytes());
+        self.chain_hash =
+            Hash::compute_from_tuple(&[self.chain_hash.to_bytes(), slot_hash.to_bytes()]);
+        self.file.write_all(content.as_bytes())?;
+        writeln!(self.file, "hash {} {}", slot_hash, self.chain_hash)?;
+        self.file.flush()
+    }
+}
+
+fn format_address(address: &Option<Address>) -> String {
+    address.map_or_else(|| "none".to_string(), |address| address.to_string())
+}
+
+/// Canonical text form of the ledger changes, transfers and gas charges of a slot
+fn format_slot(exec_out: &ExecutionOutput) -> String {
+    let mut content = String::new();
+    let block = exec_out
+        .block_info
+        .as_ref()
+        .map_or_else(|| "miss".to_string(), |info| info.block_id.to_string());
+    // writing to a String cannot fail
+    let _ = writeln!(
+        content,
+        "slot {} {} {}",
+        exec_out.slot.period, exec_out.slot.thread, block
+    );
+
+    let mut ledger_changes: Vec<_> = exec_out.state_changes.ledger_changes.0.iter().collect();
+    ledger_changes.sort_unstable_by_key(|(address, _)| **address);
+    for (address, change) in ledger_changes {
+        match change {
+            SetUpdateOrDelete::Set(entry) => {
+                let _ = writeln!(
+                    content,
+                    "ledger {} set balance {} bytecode {}",
+                    address,
+                    entry.balance,
+                    Hash::compute_from(&entry.bytecode.0)
+                );
+                for (key, value) in &entry.datastore {
+                    let _ = writeln!(
+                        content,
+                        "ledger {} datastore {} set {}",
+                        address,
+                        bs58::encode(key).into_string(),
+                        Hash::compute_from(value)
+                    );
+                }
+            }
+            SetUpdateOrDelete::Update(update) => {
+                if let SetOrKeep::Set(balance) = &update.balance {
+                    let _ = writeln!(content, "ledger {} balance {}", address, balance);
+                }
+                if let SetOrKeep::Set(bytecode) = &update.bytecode {
+                    let _ = writeln!(
+                        content,
+                        "ledger {} bytecode {}",
+                        address,
+                        Hash::compute_from(&bytecode.0)
+                    );
+                }
+                for (key, value) in &update.datastore {
+                    let key = bs58::encode(key).into_string();
+                    let _ = match value {
+                        SetOrDelete::Set(value) => writeln!(
+                            content,
+                            "ledger {} datastore {} set {}",
+                            address,
+                            key,
+                            Hash::compute_from(value)
+                        ),
+                        SetOrDelete::Delete => {
+                            writeln!(content, "ledger {} datastore {} delete", address, key)
+                        }
+                    };
+                }
+            }
+            SetUpdateOrDelete::Delete => {
+                let _ = writeln!(content, "ledger {} delete", address);
+            }
+        }
+    }
+
+    for entry in &exec_out.trace {
+        let _ = match entry {
+            ExecutionTraceEntry::Transfer { from, to, amount } => writeln!(
+                content,
+                "transfer {} {} {}",
+                format_address(from),
+                format_address(to),
+                amount
+            ),
+            ExecutionTraceEntry::OperationGas { operation_id, gas } => {
+                writeln!(content, "gas operation {} {}", operation_id, gas)
+            }
+            ExecutionTraceEntry::AsyncMessageGas {
+                emission_slot,
+                emission_index,
+                gas,
+            } => writeln!(
+                content,
+                "gas message {} {} {} {}",
+                emission_slot.period, emission_slot.thread, emission_index, gas
+            ),
+        };
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_ledger_exports::{LedgerEntry, LedgerEntryUpdate};
+    use massa_models::amount::Amount;
+    use std::str::FromStr;
+
+    fn output(slot: Slot, addresses: &[Address]) -> ExecutionOutput {
+        let recipient =
+            Address::from_str("AU12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap();
+        let mut exec_out = ExecutionOutput {
+            slot,
+            block_info: None,
+            state_changes: Default::default(),
+            events: Default::default(),
+            trace: vec![ExecutionTraceEntry::Transfer {
+                from: None,
+                to: Some(recipient),
+                amount: Amount::from_str("1.5").unwrap(),
+            }],
+            operation_receipts: Default::default(),
+            transfers: Default::default(),
+            executed_async_message_count: 0,
+        };
+        for address in addresses {
+            exec_out.state_changes.ledger_changes.0.insert(
+                *address,
+                SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                    balance: SetOrKeep::Set(Amount::from_str("10").unwrap()),
+                    ..Default::default()
+                }),
+            );
+        }
+        exec_out
+            .state_changes
+            .ledger_changes
+            .0
+            .insert(recipient, SetUpdateOrDelete::Set(LedgerEntry::default()));
+        exec_out
+    }
+
+    #[test]
+    fn test_execution_trace() {
+        let first =
+            Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+        let second =
+            Address::from_str("AU12cMW9zRKFDS43Z2W88VCmdQFxmHjAo54XvuVV34UzJeXRLXW9M").unwrap();
+
+        // the content of a slot does not depend on the insertion order of the ledger changes
+        let content = format_slot(&output(Slot::new(1, 0), &[first, second]));
+        assert_eq!(
+            content,
+            format_slot(&output(Slot::new(1, 0), &[second, first]))
+        );
+        assert!(content.starts_with("slot 1 0 miss\n"));
+        assert!(content.ends_with("1.5\n"));
+
+        // two nodes applying the same slots write the same trace
+        let dir = tempfile::tempdir().unwrap();
+        let trace = |name: &str, balance: &str| {
+            let path = dir.path().join(name);
+            let mut writer =
+                ExecutionTraceWriter::new(&path, Slot::new(0, 0), Hash::compute_from(b"trail"))
+                    .unwrap();
+            writer
+                .write_slot(&output(Slot::new(1, 0), &[first]))
+                .unwrap();
+            let mut diverging = output(Slot::new(1, 1), &[first]);
+            diverging.trace.push(ExecutionTraceEntry::Transfer {
+                from: Some(first),
+                to: None,
+                amount: Amount::from_str(balance).unwrap(),
+            });
+            writer.write_slot(&diverging).unwrap();
+            std::fs::read_to_string(path).unwrap()
+        };
+        let trace_a = trace("a", "1");
+        assert_eq!(trace_a, trace("b", "1"));
+
+        // the first divergent slot is the first one whose hashes differ
+        let trace_c = trace("c", "2");
+        let hashes = |trace: &str| -> Vec<String> {
+            trace
+                .lines()
+                .filter(|line| line.starts_with("hash "))
+                .map(str::to_string)
+                .collect()
+        };
+        assert_eq!(hashes(&trace_a).len(), 2);
+        assert_eq!(hashes(&trace_a)[0], hashes(&trace_c)[0]);
+        assert_ne!(hashes(&trace_a)[1], hashes(&trace_c)[1]);
+    }
+}
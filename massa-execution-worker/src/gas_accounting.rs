@@ -0,0 +1,131 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Accounting of the gas consumed and fees paid by each caller address.
+//!
+//! The receipts of the operations executed in final slots are aggregated per creator address and per cycle.
+//! Only the last `POS_SAVED_CYCLES` cycles are kept, like the cycle infos of the proof-of-stake state.
+
+use massa_execution_exports::{AddressCycleGasAccounting, OperationReceipt};
+use massa_models::address::Address;
+use massa_models::config::POS_SAVED_CYCLES;
+use massa_models::prehash::PreHashMap;
+use std::collections::BTreeMap;
+
+/// Gas and fees of the final operations, per cycle and per caller address
+#[derive(Default)]
+pub(crate) struct GasAccounting {
+    /// accounting of each caller address, indexed by cycle
+    cycles: BTreeMap<u64, PreHashMap<Address, AddressCycleGasAccounting>>,
+}
+
+impl GasAccounting {
+    /// Accounts the receipts of the operations of a final slot of `cycle`,
+    /// dropping the cycles that are too old to be kept
+    pub(crate) fn record<'a>(
+        &mut self,
+        cycle: u64,
+        receipts: impl IntoIterator<Item = &'a OperationReceipt>,
+    ) {
+        let addresses = self.cycles.entry(cycle).or_default();
+        for receipt in receipts {
+            let accounting =
+                addresses
+                    .entry(receipt.caller)
+                    .or_insert_with(|| AddressCycleGasAccounting {
+                        cycle,
+                        ..Default::default()
+                    });
+            accounting.operation_count += 1;
+            if !receipt.success {
+                accounting.failed_operation_count += 1;
+            }
+            accounting.gas_used = accounting.gas_used.saturating_add(receipt.gas_used);
+            accounting.fees_paid = accounting.fees_paid.saturating_add(receipt.fee);
+        }
+        while self.cycles.len() > POS_SAVED_CYCLES {
+            self.cycles.pop_first();
+        }
+    }
+
+    /// Gets the accounting of an address for each kept cycle in which it executed operations, oldest first
+    pub(crate) fn get(&self, address: &Address) -> Vec<AddressCycleGasAccounting> {
+        self.cycles
+            .values()
+            .filter_map(|addresses| addresses.get(address).cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::amount::Amount;
+    use massa_models::block_id::BlockId;
+    use massa_models::operation::OperationId;
+    use massa_models::slot::Slot;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    fn receipt(caller: Address, success: bool, gas_used: u64, fee: &str) -> OperationReceipt {
+        OperationReceipt {
+            operation_id: OperationId::new(Hash::compute_from(&gas_used.to_be_bytes())),
+            slot: Slot::new(0, 0),
+            block_id: BlockId::generate_from_hash(Hash::compute_from(b"block")),
+            caller,
+            fee: Amount::from_str(fee).unwrap(),
+            success,
+            gas_used,
+            error: None,
+            error_code: None,
+            event_count: 0,
+            balance_changes: Default::default(),
+            is_final: true,
+        }
+    }
+
+    #[test]
+    fn test_gas_accounting_per_cycle() {
+        let alice = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let bob = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let mut accounting = GasAccounting::default();
+        accounting.record(
+            1,
+            &[
+                receipt(alice, true, 100, "0.1"),
+                receipt(alice, false, 50, "0.2"),
+                receipt(bob, true, 10, "1"),
+            ],
+        );
+        accounting.record(2, &[receipt(alice, true, 30, "0.01")]);
+
+        let alice_accounting = accounting.get(&alice);
+        assert_eq!(
+            alice_accounting,
+            vec![
+                AddressCycleGasAccounting {
+                    cycle: 1,
+                    operation_count: 2,
+                    failed_operation_count: 1,
+                    gas_used: 150,
+                    fees_paid: Amount::from_str("0.3").unwrap(),
+                },
+                AddressCycleGasAccounting {
+                    cycle: 2,
+                    operation_count: 1,
+                    failed_operation_count: 0,
+                    gas_used: 30,
+                    fees_paid: Amount::from_str("0.01").unwrap(),
+                },
+            ]
+        );
+        assert_eq!(accounting.get(&bob).len(), 1);
+
+        // old cycles are dropped
+        for cycle in 3..3 + POS_SAVED_CYCLES as u64 {
+            accounting.record(cycle, &[]);
+        }
+        assert!(accounting.get(&alice).is_empty());
+        assert!(accounting.get(&bob).is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Final ledger at past final slots.
+//!
+//! For each of the last final slots, the changes reverting the final ledger to its state before the slot are kept.
+//! The final ledger at a past final slot is queried through a state view
+//! reverting the current final ledger with the changes of all the slots after it, newest first.
+
+use crate::state_views::{undo_changes, StateView};
+use massa_final_state::FinalState;
+use massa_hash::Hash;
+use massa_ledger_exports::{Applicable, LedgerChanges};
+use massa_models::slot::Slot;
+use std::collections::VecDeque;
+
+/// Changes reverting the final ledger to its state before a final slot
+struct SlotUndo {
+    /// final slot preceding the reverted slot
+    previous_slot: Slot,
+    /// final state fingerprint at `previous_slot`
+    previous_fingerprint: Hash,
+    /// changes reverting the final ledger to its state at `previous_slot`
+    undo: LedgerChanges,
+}
+
+/// Reverse changes of the last final slots, oldest first
+pub(crate) struct HistoricalStates {
+    /// max number of final slots whose changes are kept, 0 disables the history
+    max_slots: usize,
+    /// reverse changes of the last final slots, oldest first
+    undos: VecDeque<SlotUndo>,
+}
+
+impl HistoricalStates {
+    /// Creates an empty history keeping at most `max_slots` slots
+    pub(crate) fn new(max_slots: usize) -> Self {
+        Self {
+            max_slots,
+            undos: VecDeque::new(),
+        }
+    }
+
+    /// Records the final ledger changes about to be applied to `final_state`,
+    /// whose last executed slot is `previous_slot`
+    pub(crate) fn record_final_changes(
+        &mut self,
+        previous_slot: Slot,
+        changes: &LedgerChanges,
+        final_state: &FinalState,
+    ) {
+        if self.max_slots == 0 {
+            return;
+        }
+        self.undos.push_back(SlotUndo {
+            previous_slot,
+            previous_fingerprint: final_state.get_fingerprint(),
+            undo: undo_changes(changes, final_state.ledger.as_ref()),
+        });
+        while self.undos.len() > self.max_slots {
+            self.undos.pop_front();
+        }
+    }
+
+    /// Gets a view of the final ledger at `slot`, or None if `slot` is not in the history.
+    /// `final_cursor` and `final_fingerprint` describe the current final state.
+    pub(crate) fn view_at(
+        &self,
+        slot: Slot,
+        final_cursor: Slot,
+        final_fingerprint: Hash,
+    ) -> Option<StateView> {
+        if slot == final_cursor {
+            return Some(StateView::at_final_slot(
+                slot,
+                final_fingerprint,
+                LedgerChanges::default(),
+            ));
+        }
+        let index = self
+            .undos
+            .iter()
+            .position(|slot_undo| slot_undo.previous_slot == slot)?;
+        // the reverse of the newest slot is applied first
+        let mut final_undo = LedgerChanges::default();
+        for slot_undo in self.undos.iter().skip(index).rev() {
+            final_undo.apply(slot_undo.undo.clone());
+        }
+        Some(StateView::at_final_slot(
+            slot,
+            self.undos[index].previous_fingerprint,
+            final_undo,
+        ))
+    }
+}
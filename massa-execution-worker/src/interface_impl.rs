@@ -98,6 +98,7 @@ impl InterfaceImpl {
             gas_costs: config.gas_costs.clone(),
             compilation_gas: config.max_gas_per_block,
             lru_cache_size: config.lru_cache_size,
+            lru_cache_max_memory: config.lru_cache_max_memory,
             hd_cache_size: config.hd_cache_size,
             snip_amount: config.snip_amount,
             max_module_length: config.max_bytecode_size,
@@ -1144,6 +1145,13 @@ impl Interface for InterfaceImpl {
         if validity_end.1 >= self.config.thread_count {
             bail!("validity end thread exceeds the configuration thread count")
         }
+        if max_gas > self.config.max_gas_per_async_message {
+            bail!(
+                "max gas {} exceeds the maximum gas of an asynchronous message ({})",
+                max_gas,
+                self.config.max_gas_per_async_message
+            )
+        }
         let target_addr = Address::from_str(target_address)?;
 
         // check that the target address is an SC address
@@ -1964,6 +1972,56 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_deferred_call_register_and_cancel() {
+        let sc_addr =
+            Address::from_str("AS12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap();
+        let interface = InterfaceImpl::new_default(sc_addr, None);
+        let mut context = context_guard!(interface);
+        let initial_balance = context.get_balance(&sc_addr).unwrap();
+
+        // the current slot can not be targeted
+        assert!(context.deferred_call_quote(Slot::new(0, 0), 1_000).is_err());
+        let fee = context.deferred_call_quote(Slot::new(1, 0), 1_000).unwrap();
+
+        let id = context
+            .deferred_call_register(
+                Slot::new(1, 0),
+                "wake_up".to_string(),
+                vec![42],
+                Amount::from_raw(1_000_000),
+                1_000,
+            )
+            .unwrap();
+        assert!(context.deferred_call_exists(&id));
+
+        // booking more gas in the same slot is more expensive
+        assert!(context.deferred_call_quote(Slot::new(1, 0), 1_000).unwrap() >= fee);
+
+        // cancelling reimburses the coins but not the fee
+        context.deferred_call_cancel(&id).unwrap();
+        assert!(!context.deferred_call_exists(&id));
+        assert_eq!(
+            context.get_balance(&sc_addr).unwrap(),
+            initial_balance.saturating_sub(fee)
+        );
+        assert!(context.deferred_call_cancel(&id).is_err());
+        drop(context);
+
+        // user addresses can not register deferred calls
+        let user_addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let interface = InterfaceImpl::new_default(user_addr, None);
+        assert!(context_guard!(interface)
+            .deferred_call_register(
+                Slot::new(1, 0),
+                "wake_up".to_string(),
+                vec![],
+                Amount::zero(),
+                1_000
+            )
+            .is_err());
+    }
 }
 
 #[test]
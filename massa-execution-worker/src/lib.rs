@@ -69,6 +69,9 @@
 //! ## `speculative_executed_ops.rs`
 //! A speculative (non-final) list of previously executed operations to prevent reuse.
 //!
+//! ## `speculative_deferred_calls.rs`
+//! A speculative (non-final) registry of the calls that smart contracts scheduled for future slots.
+//!
 //! ## `request_queue.rs`
 //! This module contains the implementation of a generic finite-size execution request queue.
 //! It handles requests that come with an MPSC to send back the result of their execution once it's done.
@@ -83,15 +86,24 @@ mod active_history;
 mod context;
 mod controller;
 mod execution;
+mod execution_trace;
+mod gas_accounting;
+mod historical_state;
 mod interface_impl;
+mod offchain_tasks;
+mod operation_receipts;
 mod request_queue;
 mod slot_sequencer;
 mod speculative_async_pool;
+mod speculative_cache;
+mod speculative_deferred_calls;
 mod speculative_executed_denunciations;
 mod speculative_executed_ops;
 mod speculative_ledger;
 mod speculative_roll_state;
+mod state_views;
 mod stats;
+mod transfer_history;
 mod worker;
 
 use massa_db_exports as _;
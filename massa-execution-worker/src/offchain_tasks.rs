@@ -0,0 +1,539 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Registry and runner of the node-local tasks triggered by final execution outputs.
+//!
+//! The execution thread matches each final output against the registered tasks
+//! and queues the resulting notifications. A dedicated thread runs the actions of the tasks
+//! so that slow programs or webhooks never delay execution.
+//! If the queue is full, notifications are dropped with a warning.
+//!
+//! Programs can only be run from the off-chain programs directory of the node configuration,
+//! so that the private API cannot be used to run arbitrary commands on the host.
+
+use massa_execution_exports::{
+    ExecutionConfig, ExecutionError, ExecutionOutput, OffChainDatastoreChange,
+    OffChainDeferredCredit, OffChainNotification, OffChainProductionResult, OffChainTask,
+    OffChainTaskAction, OffChainTaskTrigger,
+};
+use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
+use massa_models::address::Address;
+use massa_models::output_event::SCOutputEvent;
+use massa_models::prehash::PreHashSet;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Registered tasks and the id of the next one
+#[derive(Default)]
+struct OffChainTaskRegistry {
+    next_id: u64,
+    tasks: BTreeMap<u64, OffChainTask>,
+}
+
+/// Node-local tasks triggered by final execution outputs
+pub(crate) struct OffChainTasks {
+    /// registered tasks
+    registry: RwLock<OffChainTaskRegistry>,
+    /// queue of the notifications to run
+    notification_sender: SyncSender<(OffChainTaskAction, OffChainNotification)>,
+    /// max number of registered tasks
+    max_tasks: usize,
+    /// directory of the programs that tasks are allowed to run
+    programs_dir: Option<PathBuf>,
+}
+
+impl OffChainTasks {
+    /// Creates an empty registry and spawns the thread running the triggered actions.
+    /// The thread stops once the registry is dropped.
+    pub fn new(config: &ExecutionConfig) -> Self {
+        let (notification_sender, notification_receiver) =
+            sync_channel(config.offchain_notification_queue_size);
+        let timeout = config.offchain_task_timeout;
+        let programs_dir = config.offchain_programs_dir.clone();
+        thread::Builder::new()
+            .name("execution offchain tasks".into())
+            .spawn({
+                let programs_dir = programs_dir.clone();
+                move || run_notifications(notification_receiver, programs_dir, timeout)
+            })
+            .expect("failed to spawn thread : execution offchain tasks");
+        OffChainTasks {
+            registry: Default::default(),
+            notification_sender,
+            max_tasks: config.max_offchain_tasks,
+            programs_dir,
+        }
+    }
+
+    /// Registers a task and returns its id.
+    /// The program of a command action is replaced by its resolved path in the programs directory.
+    pub fn add(&self, mut task: OffChainTask) -> Result<u64, ExecutionError> {
+        match &mut task.action {
+            OffChainTaskAction::Command { program, .. } => {
+                *program = resolve_program(self.programs_dir.as_deref(), program)
+                    .map_err(ExecutionError::OffChainTaskError)?;
+            }
+            OffChainTaskAction::Webhook { url } => {
+                parse_http_url(url).map_err(ExecutionError::OffChainTaskError)?;
+            }
+        }
+        let mut registry = self.registry.write();
+        if registry.tasks.len() >= self.max_tasks {
+            return Err(ExecutionError::OffChainTaskError(format!(
+                "too many off-chain tasks, the maximum is {}",
+                self.max_tasks
+            )));
+        }
+        let task_id = registry.next_id;
+        registry.next_id += 1;
+        registry.tasks.insert(task_id, task);
+        Ok(task_id)
+    }
+
+    /// Unregisters tasks by id
+    pub fn remove(&self, task_ids: &[u64]) {
+        let mut registry = self.registry.write();
+        for task_id in task_ids {
+            registry.tasks.remove(task_id);
+        }
+    }
+
+    /// Gets the registered tasks
+    pub fn get_all(&self) -> BTreeMap<u64, OffChainTask> {
+        self.registry.read().tasks.clone()
+    }
+
+    /// Queues the notifications of the tasks triggered by a final execution output.
+    /// `staking_addresses` are the wallet and watch-only addresses of the node.
+    pub fn notify_final_output(
+        &self,
+        exec_out: &ExecutionOutput,
+        staking_addresses: &PreHashSet<Address>,
+    ) {
+        let registry = self.registry.read();
+        for (task_id, task) in registry.tasks.iter() {
+            let Some(notification) =
+                get_notification(*task_id, &task.trigger, exec_out, staking_addresses)
+            else {
+                continue;
+            };
+            match self
+                .notification_sender
+                .try_send((task.action.clone(), notification))
+            {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => warn!(
+                    "off-chain task notification queue full, dropping the notification of task {} for slot {}",
+                    task_id, exec_out.slot
+                ),
+                Err(TrySendError::Disconnected(_)) => {
+                    warn!("off-chain task thread stopped, dropping notifications");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Gets the events, datastore changes and staking results of an execution output matching a trigger, if any
+fn get_notification(
+    task_id: u64,
+    trigger: &OffChainTaskTrigger,
+    exec_out: &ExecutionOutput,
+    staking_addresses: &PreHashSet<Address>,
+) -> Option<OffChainNotification> {
+    let mut events: Vec<SCOutputEvent> = Vec::new();
+    let mut datastore_changes = Vec::new();
+    let mut production_results = Vec::new();
+    let mut deferred_credits = Vec::new();
+    match trigger {
+        OffChainTaskTrigger::ScEvent {
+            emitter_address,
+            data_contains,
+        } => {
+            events = exec_out
+                .events
+                .0
+                .iter()
+                .filter(|event| {
+                    emitter_address.map_or(true, |address| {
+                        event.context.call_stack.back() == Some(&address)
+                    }) && data_contains
+                        .as_ref()
+                        .map_or(true, |pattern| event.data.contains(pattern.as_str()))
+                })
+                .cloned()
+                .collect();
+        }
+        OffChainTaskTrigger::DatastoreChange {
+            address,
+            key_prefix,
+        } => {
+            // deleted ledger entries are not reported because their keys are not part of the changes
+            match exec_out.state_changes.ledger_changes.0.get(address) {
+                Some(SetUpdateOrDelete::Set(entry)) => {
+                    datastore_changes.extend(
+                        entry
+                            .datastore
+                            .iter()
+                            .filter(|(key, _)| key.starts_with(key_prefix))
+                            .map(|(key, value)| OffChainDatastoreChange {
+                                address: *address,
+                                key: key.clone(),
+                                value: Some(value.clone()),
+                            }),
+                    );
+                }
+                Some(SetUpdateOrDelete::Update(update)) => {
+                    datastore_changes.extend(
+                        update
+                            .datastore
+                            .iter()
+                            .filter(|(key, _)| key.starts_with(key_prefix))
+                            .map(|(key, change)| OffChainDatastoreChange {
+                                address: *address,
+                                key: key.clone(),
+                                value: match change {
+                                    SetOrDelete::Set(value) => Some(value.clone()),
+                                    SetOrDelete::Delete => None,
+                                },
+                            }),
+                    );
+                }
+                Some(SetUpdateOrDelete::Delete) | None => {}
+            }
+        }
+        OffChainTaskTrigger::Staking => {
+            let pos_changes = &exec_out.state_changes.pos_changes;
+            for (address, stats) in pos_changes.production_stats.iter() {
+                if !staking_addresses.contains(address) {
+                    continue;
+                }
+                let produced = std::iter::repeat(true).take(stats.block_success_count as usize);
+                let missed = std::iter::repeat(false).take(stats.block_failure_count as usize);
+                production_results.extend(produced.chain(missed).map(|produced| {
+                    OffChainProductionResult {
+                        address: *address,
+                        produced,
+                    }
+                }));
+            }
+            for (slot, credits) in pos_changes.deferred_credits.credits.iter() {
+                deferred_credits.extend(
+                    credits
+                        .iter()
+                        .filter(|(address, _)| staking_addresses.contains(address))
+                        .map(|(address, amount)| OffChainDeferredCredit {
+                            address: *address,
+                            slot: *slot,
+                            amount: *amount,
+                        }),
+                );
+            }
+        }
+    }
+    if events.is_empty()
+        && datastore_changes.is_empty()
+        && production_results.is_empty()
+        && deferred_credits.is_empty()
+    {
+        return None;
+    }
+    Some(OffChainNotification {
+        task_id,
+        slot: exec_out.slot,
+        events,
+        datastore_changes,
+        production_results,
+        deferred_credits,
+    })
+}
+
+/// Runs the queued actions until the queue is disconnected
+fn run_notifications(
+    receiver: Receiver<(OffChainTaskAction, OffChainNotification)>,
+    programs_dir: Option<PathBuf>,
+    timeout: MassaTime,
+) {
+    while let Ok((action, notification)) = receiver.recv() {
+        let payload = match serde_json::to_vec(&notification) {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!("could not serialize off-chain task notification: {}", err);
+                continue;
+            }
+        };
+        let result = match &action {
+            // the program is resolved again as the directory may have changed since the registration
+            OffChainTaskAction::Command { program, args } => {
+                resolve_program(programs_dir.as_deref(), program).and_then(|program| {
+                    run_command(&program, args, &payload, timeout.to_duration())
+                })
+            }
+            OffChainTaskAction::Webhook { url } => {
+                call_webhook(url, &payload, timeout.to_duration())
+            }
+        };
+        match result {
+            Ok(()) => debug!(
+                "off-chain task {} run for slot {}",
+                notification.task_id, notification.slot
+            ),
+            Err(err) => warn!(
+                "off-chain task {} failed for slot {}: {}",
+                notification.task_id, notification.slot, err
+            ),
+        }
+    }
+}
+
+/// Resolves the path of a program, following symbolic links,
+/// and checks that it is a file located in the programs directory
+fn resolve_program(programs_dir: Option<&Path>, program: &Path) -> Result<PathBuf, String> {
+    let programs_dir =
+        programs_dir.ok_or_else(|| "off-chain programs are disabled on this node".to_string())?;
+    let programs_dir = programs_dir.canonicalize().map_err(|err| {
+        format!(
+            "could not resolve the off-chain programs directory {}: {}",
+            programs_dir.display(),
+            err
+        )
+    })?;
+    // relative paths are relative to the programs directory, absolute paths are kept
+    let resolved = programs_dir
+        .join(program)
+        .canonicalize()
+        .map_err(|err| format!("could not resolve {}: {}", program.display(), err))?;
+    if !resolved.starts_with(&programs_dir) || !resolved.is_file() {
+        return Err(format!(
+            "{} is not a program of the off-chain programs directory {}",
+            program.display(),
+            programs_dir.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Runs a program with the payload on its standard input, killing it after `timeout`
+fn run_command(
+    program: &Path,
+    args: &[String],
+    payload: &[u8],
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("could not run {}: {}", program.display(), err))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the program may exit without reading its input
+        let _ = stdin.write_all(payload);
+    }
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                return Err(format!("{} exited with {}", program.display(), status))
+            }
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} timed out", program.display()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(err) => return Err(format!("could not wait {}: {}", program.display(), err)),
+        }
+    }
+}
+
+/// Splits an `http://host[:port][/path]` URL into its host, port and path
+fn parse_http_url(url: &str) -> Result<(String, u16, String), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("webhook URL {} must start with http://", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse::<u16>()
+                .map_err(|_| format!("invalid port in webhook URL {}", url))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("missing host in webhook URL {}", url));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+/// POSTs the payload to a webhook and checks that it answers with a success status
+fn call_webhook(url: &str, payload: &[u8], timeout: Duration) -> Result<(), String> {
+    let (host, port, path) = parse_http_url(url)?;
+    let address = (host.as_str(), port)
+        .to_socket_addrs()
+        .map_err(|err| format!("could not resolve {}: {}", host, err))?
+        .next()
+        .ok_or_else(|| format!("could not resolve {}", host))?;
+    let mut stream = TcpStream::connect_timeout(&address, timeout)
+        .map_err(|err| format!("could not connect to {}: {}", url, err))?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .map_err(|err| err.to_string())?;
+    let header = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        path,
+        host,
+        payload.len()
+    );
+    stream
+        .write_all(header.as_bytes())
+        .and_then(|_| stream.write_all(payload))
+        .map_err(|err| format!("could not send to {}: {}", url, err))?;
+    // only the status line of the response is needed
+    let mut response = [0u8; 12];
+    stream
+        .read_exact(&mut response)
+        .map_err(|err| format!("could not read the response of {}: {}", url, err))?;
+    match std::str::from_utf8(&response[9..12]) {
+        Ok(status) if status.starts_with('2') => Ok(()),
+        _ => Err(format!(
+            "{} answered {}",
+            url,
+            String::from_utf8_lossy(&response)
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_http_url() {
+        assert_eq!(
+            parse_http_url("http://localhost:8080/hooks/massa").unwrap(),
+            ("localhost".to_string(), 8080, "/hooks/massa".to_string())
+        );
+        assert_eq!(
+            parse_http_url("http://example.com").unwrap(),
+            ("example.com".to_string(), 80, "/".to_string())
+        );
+        assert!(parse_http_url("https://example.com").is_err());
+        assert!(parse_http_url("http://:80/").is_err());
+        assert!(parse_http_url("http://localhost:port/").is_err());
+    }
+
+    #[test]
+    fn test_resolve_program() {
+        let programs_dir = tempfile::tempdir().unwrap();
+        let other_dir = tempfile::tempdir().unwrap();
+        std::fs::write(programs_dir.path().join("notify.sh"), "").unwrap();
+        std::fs::write(other_dir.path().join("outside.sh"), "").unwrap();
+        let expected = programs_dir
+            .path()
+            .join("notify.sh")
+            .canonicalize()
+            .unwrap();
+
+        assert_eq!(
+            resolve_program(Some(programs_dir.path()), Path::new("notify.sh")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            resolve_program(Some(programs_dir.path()), &expected).unwrap(),
+            expected
+        );
+        assert!(resolve_program(None, &expected).is_err());
+        assert!(resolve_program(Some(programs_dir.path()), Path::new("missing.sh")).is_err());
+        assert!(resolve_program(
+            Some(programs_dir.path()),
+            &other_dir.path().join("outside.sh")
+        )
+        .is_err());
+        let escaping = Path::new("..")
+            .join(other_dir.path().file_name().unwrap())
+            .join("outside.sh");
+        assert!(resolve_program(Some(programs_dir.path()), &escaping).is_err());
+        assert!(resolve_program(Some(programs_dir.path()), Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn test_staking_notification() {
+        use massa_models::{amount::Amount, slot::Slot};
+        use massa_pos_exports::ProductionStats;
+        use massa_signature::KeyPair;
+
+        let watched = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let other = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let mut exec_out = ExecutionOutput {
+            slot: Slot::new(1, 0),
+            block_info: None,
+            state_changes: Default::default(),
+            events: Default::default(),
+            trace: Default::default(),
+            operation_receipts: Default::default(),
+            transfers: Default::default(),
+            executed_async_message_count: 0,
+        };
+        let pos_changes = &mut exec_out.state_changes.pos_changes;
+        for address in [watched, other] {
+            pos_changes.production_stats.insert(
+                address,
+                ProductionStats {
+                    block_success_count: 0,
+                    block_failure_count: 1,
+                },
+            );
+            pos_changes
+                .deferred_credits
+                .insert(Slot::new(10, 0), address, Amount::from_raw(100));
+        }
+        let staking_addresses: PreHashSet<Address> = [watched].into_iter().collect();
+
+        let notification = get_notification(
+            0,
+            &OffChainTaskTrigger::Staking,
+            &exec_out,
+            &staking_addresses,
+        )
+        .unwrap();
+        assert_eq!(
+            notification.production_results,
+            vec![OffChainProductionResult {
+                address: watched,
+                produced: false,
+            }]
+        );
+        assert_eq!(
+            notification.deferred_credits,
+            vec![OffChainDeferredCredit {
+                address: watched,
+                slot: Slot::new(10, 0),
+                amount: Amount::from_raw(100),
+            }]
+        );
+        assert!(get_notification(
+            0,
+            &OffChainTaskTrigger::Staking,
+            &exec_out,
+            &PreHashSet::default()
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,107 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Store of the receipts of the final operations.
+//!
+//! The receipts of the operations executed in final slots are kept in a ring buffer indexed by operation id:
+//! when the store is full, the oldest receipts are dropped first.
+
+use massa_execution_exports::OperationReceipt;
+use massa_models::operation::OperationId;
+use massa_models::prehash::PreHashMap;
+use std::collections::VecDeque;
+
+/// Ring buffer of the receipts of the final operations
+pub(crate) struct OperationReceiptStore {
+    /// max number of receipts kept, 0 disables the store
+    max_receipts: usize,
+    /// receipts indexed by operation id
+    receipts: PreHashMap<OperationId, OperationReceipt>,
+    /// operation ids in insertion order, oldest at the front
+    order: VecDeque<OperationId>,
+}
+
+impl OperationReceiptStore {
+    /// Creates an empty store keeping at most `max_receipts` receipts
+    pub(crate) fn new(max_receipts: usize) -> Self {
+        Self {
+            max_receipts,
+            receipts: Default::default(),
+            order: Default::default(),
+        }
+    }
+
+    /// Adds the receipts of a final slot, marking them as final and dropping the oldest receipts if the store is full
+    pub(crate) fn extend(&mut self, receipts: impl IntoIterator<Item = OperationReceipt>) {
+        if self.max_receipts == 0 {
+            return;
+        }
+        for mut receipt in receipts {
+            receipt.is_final = true;
+            if self
+                .receipts
+                .insert(receipt.operation_id, receipt.clone())
+                .is_none()
+            {
+                self.order.push_back(receipt.operation_id);
+            }
+        }
+        while self.order.len() > self.max_receipts {
+            if let Some(operation_id) = self.order.pop_front() {
+                self.receipts.remove(&operation_id);
+            }
+        }
+    }
+
+    /// Gets the receipt of a final operation, if it is still in the store
+    pub(crate) fn get(&self, operation_id: &OperationId) -> Option<&OperationReceipt> {
+        self.receipts.get(operation_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::address::Address;
+    use massa_models::amount::Amount;
+    use massa_models::block_id::BlockId;
+    use massa_models::slot::Slot;
+    use massa_signature::KeyPair;
+
+    fn receipt(index: u64) -> OperationReceipt {
+        OperationReceipt {
+            operation_id: OperationId::new(Hash::compute_from(&index.to_be_bytes())),
+            slot: Slot::new(index, 0),
+            block_id: BlockId::generate_from_hash(Hash::compute_from(b"block")),
+            caller: Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key()),
+            fee: Amount::zero(),
+            success: true,
+            gas_used: index,
+            error: None,
+            error_code: None,
+            event_count: 0,
+            balance_changes: Default::default(),
+            is_final: false,
+        }
+    }
+
+    #[test]
+    fn test_operation_receipt_store() {
+        let mut store = OperationReceiptStore::new(2);
+        store.extend([receipt(1), receipt(2)]);
+        let stored = store.get(&receipt(1).operation_id).unwrap();
+        assert!(stored.is_final);
+        assert_eq!(stored.gas_used, 1);
+
+        // the oldest receipt is dropped first
+        store.extend([receipt(3)]);
+        assert!(store.get(&receipt(1).operation_id).is_none());
+        assert!(store.get(&receipt(2).operation_id).is_some());
+        assert!(store.get(&receipt(3).operation_id).is_some());
+
+        // a disabled store keeps nothing
+        let mut store = OperationReceiptStore::new(0);
+        store.extend([receipt(1)]);
+        assert!(store.get(&receipt(1).operation_id).is_none());
+    }
+}
@@ -732,19 +732,12 @@ impl SlotSequencer {
 
     /// Gets the instant of the slot just after the latest slot in the sequence.
     /// Note that `config.cursor_delay` is taken into account.
-    pub fn get_next_slot_deadline(&self) -> MassaTime {
-        // The slot sequence is empty.
-        // This means that we are still waiting for `Self::update` to be called for the first time.
-        // To avoid CPU-intensive loops upstream, just register a wake-up after a single slot delay (t0/T).
+    ///
+    /// Returns `None` if the slot sequence is empty:
+    /// there is nothing to sequence until `Self::update` is called for the first time.
+    pub fn get_next_slot_deadline(&self) -> Option<MassaTime> {
         if self.sequence.is_empty() {
-            return MassaTime::now()
-                .expect("could not get current time")
-                .saturating_add(
-                    self.config
-                        .t0
-                        .checked_div_u64(self.config.thread_count as u64)
-                        .unwrap(),
-                );
+            return None;
         }
 
         // Compute the next slot after the current time cursor.
@@ -754,13 +747,15 @@ impl SlotSequencer {
             .expect("slot overflow in slot deadline computation");
 
         // Return the timestamp of that slot, shifted by the cursor delay.
-        get_block_slot_timestamp(
-            self.config.thread_count,
-            self.config.t0,
-            self.config.genesis_timestamp,
-            next_slot,
+        Some(
+            get_block_slot_timestamp(
+                self.config.thread_count,
+                self.config.t0,
+                self.config.genesis_timestamp,
+                next_slot,
+            )
+            .expect("could not compute slot timestamp")
+            .saturating_add(self.config.cursor_delay),
         )
-        .expect("could not compute slot timestamp")
-        .saturating_add(self.config.cursor_delay)
     }
 }
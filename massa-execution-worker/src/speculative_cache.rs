@@ -0,0 +1,258 @@
+//! Cache of the outputs of the candidate slots cancelled by blockclique changes.
+//!
+//! The output of a candidate slot only depends on the final state and on the contents
+//! of the candidate slots from the one after the final cursor up to it: its lineage.
+//! When the blockclique changes, the active history is truncated from the first divergent slot.
+//! The truncated outputs are kept here with their lineage, so that the slots are not executed again
+//! if the blockclique switches back to them.
+
+use std::collections::VecDeque;
+
+use massa_execution_exports::{
+    ExecutionOutput, ExecutionTraceEntry, OperationReceipt, SpeculativeCacheEviction,
+};
+use massa_final_state::StateChangesSerializer;
+use massa_models::{block_id::BlockId, slot::Slot};
+use massa_serialization::Serializer;
+
+/// Slot and block executed at that slot (None for a miss)
+pub(crate) type SlotContent = (Slot, Option<BlockId>);
+
+/// Content of the slot of an execution output
+pub(crate) fn output_content(output: &ExecutionOutput) -> SlotContent {
+    (
+        output.slot,
+        output.block_info.as_ref().map(|info| info.block_id),
+    )
+}
+
+/// Approximate memory size of an execution output, in bytes.
+/// The state changes are counted with their serialized size.
+fn output_size(output: &ExecutionOutput) -> usize {
+    let mut buffer = Vec::new();
+    let state_changes_size = StateChangesSerializer::new()
+        .serialize(&output.state_changes, &mut buffer)
+        .map_or(0, |_| buffer.len());
+    std::mem::size_of::<ExecutionOutput>()
+        + state_changes_size
+        + output.events.size()
+        + output.trace.len() * std::mem::size_of::<ExecutionTraceEntry>()
+        + output.operation_receipts.len() * std::mem::size_of::<OperationReceipt>()
+}
+
+/// Cancelled output with the contents of the slots it was executed on
+struct CachedOutput {
+    lineage: Vec<SlotContent>,
+    output: ExecutionOutput,
+    size: usize,
+}
+
+pub(crate) struct SpeculativeCache {
+    max_size: usize,
+    /// max lineage length of the kept outputs (0 for no limit)
+    max_slots_ahead: u64,
+    /// max approximate memory size of the kept outputs (0 for no limit)
+    max_memory: usize,
+    eviction: SpeculativeCacheEviction,
+    /// cancelled outputs, oldest first
+    entries: VecDeque<CachedOutput>,
+    /// approximate memory size of the kept outputs
+    memory: usize,
+}
+
+impl SpeculativeCache {
+    pub(crate) fn new(
+        max_size: usize,
+        max_slots_ahead: u64,
+        max_memory: usize,
+        eviction: SpeculativeCacheEviction,
+    ) -> Self {
+        Self {
+            max_size,
+            max_slots_ahead,
+            max_memory,
+            eviction,
+            entries: VecDeque::new(),
+            memory: 0,
+        }
+    }
+
+    /// Whether cancelled outputs are kept
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.max_size > 0
+    }
+
+    /// Approximate memory size of the kept outputs, in bytes
+    pub(crate) fn memory(&self) -> usize {
+        self.memory
+    }
+
+    /// Keeps the outputs truncated from the active history.
+    /// `history` holds the contents of the whole active history before the truncation,
+    /// and `truncated` the outputs removed from its end.
+    pub(crate) fn insert_truncated(
+        &mut self,
+        history: &[SlotContent],
+        truncated: Vec<ExecutionOutput>,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let first_index = history.len().saturating_sub(truncated.len());
+        for (index, output) in truncated.into_iter().enumerate() {
+            let lineage = history[..=first_index + index].to_vec();
+            // the next outputs are even farther ahead
+            if self.max_slots_ahead > 0 && lineage.len() as u64 > self.max_slots_ahead {
+                break;
+            }
+            if let Some(position) = self
+                .entries
+                .iter()
+                .position(|entry| entry.lineage == lineage)
+            {
+                self.remove(position);
+            }
+            let size = output_size(&output);
+            if self.max_memory > 0 && size > self.max_memory {
+                continue;
+            }
+            self.memory += size;
+            self.entries.push_back(CachedOutput {
+                lineage,
+                output,
+                size,
+            });
+        }
+        while self.entries.len() > self.max_size
+            || (self.max_memory > 0 && self.memory > self.max_memory)
+        {
+            let position = match self.eviction {
+                SpeculativeCacheEviction::Oldest => 0,
+                SpeculativeCacheEviction::FarthestAhead => self
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, entry)| entry.lineage.len())
+                    .map_or(0, |(position, _)| position),
+            };
+            self.remove(position);
+        }
+    }
+
+    /// Removes the entry at `position`
+    fn remove(&mut self, position: usize) -> Option<ExecutionOutput> {
+        let entry = self.entries.remove(position)?;
+        self.memory -= entry.size;
+        Some(entry.output)
+    }
+
+    /// Takes the output of the last slot of `lineage`, if it was executed with that lineage before
+    pub(crate) fn take(&mut self, lineage: &[SlotContent]) -> Option<ExecutionOutput> {
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.lineage == lineage)?;
+        self.remove(position)
+    }
+
+    /// Drops the outputs that do not descend from the newly final slot,
+    /// and removes that slot from the lineage of the others
+    pub(crate) fn on_final_slot(&mut self, content: &SlotContent) {
+        let mut dropped_size = 0;
+        self.entries.retain_mut(|entry| {
+            if entry.lineage.len() < 2 || &entry.lineage[0] != content {
+                dropped_size += entry.size;
+                return false;
+            }
+            entry.lineage.remove(0);
+            true
+        });
+        self.memory -= dropped_size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+
+    fn output(slot: Slot) -> ExecutionOutput {
+        ExecutionOutput {
+            slot,
+            block_info: None,
+            state_changes: Default::default(),
+            events: Default::default(),
+            trace: Default::default(),
+            operation_receipts: Default::default(),
+            transfers: Default::default(),
+            executed_async_message_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_speculative_cache() {
+        let block_a = BlockId::generate_from_hash(Hash::compute_from(b"a"));
+        let block_b = BlockId::generate_from_hash(Hash::compute_from(b"b"));
+        let slot_1 = Slot::new(1, 0);
+        let slot_2 = Slot::new(1, 1);
+        let mut cache = SpeculativeCache::new(10, 0, 0, SpeculativeCacheEviction::Oldest);
+
+        // the blockclique changes at slot 1: the outputs of slots 1 and 2 are cancelled
+        let history = vec![(slot_1, Some(block_a)), (slot_2, None)];
+        cache.insert_truncated(&history, vec![output(slot_1), output(slot_2)]);
+
+        // a different lineage does not match
+        assert!(cache.take(&[(slot_1, Some(block_b))]).is_none());
+
+        // the blockclique switches back
+        assert_eq!(cache.take(&history[..1]).unwrap().slot, slot_1);
+        assert!(cache.take(&history[..1]).is_none());
+
+        // slot 1 becomes final with another block: slot 2 does not descend from it anymore
+        cache.insert_truncated(&history, vec![output(slot_2)]);
+        cache.on_final_slot(&(slot_1, Some(block_b)));
+        assert!(cache.take(&history[1..]).is_none());
+
+        // slot 1 becomes final with the cached block
+        cache.insert_truncated(&history, vec![output(slot_2)]);
+        cache.on_final_slot(&(slot_1, Some(block_a)));
+        assert_eq!(cache.take(&history[1..]).unwrap().slot, slot_2);
+
+        // a disabled cache keeps nothing
+        let mut cache = SpeculativeCache::new(0, 0, 0, SpeculativeCacheEviction::Oldest);
+        cache.insert_truncated(&history, vec![output(slot_1), output(slot_2)]);
+        assert!(cache.take(&history[..1]).is_none());
+    }
+
+    #[test]
+    fn test_speculative_cache_limits() {
+        let history: Vec<SlotContent> = (0..4).map(|thread| (Slot::new(1, thread), None)).collect();
+        let outputs = || history.iter().map(|(slot, _)| output(*slot)).collect();
+
+        // the outputs more than 2 slots ahead of the final cursor are not kept
+        let mut cache = SpeculativeCache::new(10, 2, 0, SpeculativeCacheEviction::Oldest);
+        cache.insert_truncated(&history, outputs());
+        assert!(cache.take(&history[..2]).is_some());
+        assert!(cache.take(&history[..3]).is_none());
+
+        // the oldest outputs are evicted first
+        let mut cache = SpeculativeCache::new(2, 0, 0, SpeculativeCacheEviction::Oldest);
+        cache.insert_truncated(&history, outputs());
+        assert!(cache.take(&history[..1]).is_none());
+        assert!(cache.take(&history[..4]).is_some());
+
+        // the outputs the farthest ahead are evicted first
+        let mut cache = SpeculativeCache::new(2, 0, 0, SpeculativeCacheEviction::FarthestAhead);
+        cache.insert_truncated(&history, outputs());
+        assert!(cache.take(&history[..1]).is_some());
+        assert!(cache.take(&history[..4]).is_none());
+
+        // the memory cap is enforced
+        let size = output_size(&output(Slot::new(1, 0)));
+        let mut cache = SpeculativeCache::new(10, 0, 2 * size, SpeculativeCacheEviction::Oldest);
+        cache.insert_truncated(&history, outputs());
+        assert_eq!(cache.memory(), 2 * size);
+        assert!(cache.take(&history[..3]).is_some());
+        assert_eq!(cache.memory(), size);
+    }
+}
@@ -0,0 +1,128 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Speculative registry of the deferred calls registered by smart contracts and not executed yet.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use parking_lot::RwLock;
+
+use crate::active_history::{ActiveHistory, HistorySearchResult};
+use massa_deferred_calls::{DeferredCall, DeferredCallId, DeferredCallRegistryChanges};
+use massa_final_state::FinalState;
+use massa_ledger_exports::SetOrDelete;
+use massa_models::slot::Slot;
+
+/// Speculative state of the deferred call registry
+pub(crate) struct SpeculativeDeferredCallRegistry {
+    /// Thread-safe shared access to the final state. For reading only.
+    final_state: Arc<RwLock<FinalState>>,
+
+    /// History of the outputs of recently executed slots.
+    /// Slots should be consecutive, newest at the back.
+    active_history: Arc<RwLock<ActiveHistory>>,
+
+    /// list of call registrations and deletions since the creation of this object
+    deferred_call_changes: DeferredCallRegistryChanges,
+}
+
+impl SpeculativeDeferredCallRegistry {
+    /// Creates a new `SpeculativeDeferredCallRegistry`
+    ///
+    /// # Arguments
+    /// * `final_state`: thread-safe shared access the the final state
+    /// * `active_history`: thread-safe shared access the speculative execution history
+    pub fn new(
+        final_state: Arc<RwLock<FinalState>>,
+        active_history: Arc<RwLock<ActiveHistory>>,
+    ) -> Self {
+        Self {
+            final_state,
+            active_history,
+            deferred_call_changes: Default::default(),
+        }
+    }
+
+    /// Returns the changes caused to the `SpeculativeDeferredCallRegistry` since its creation,
+    /// and resets their local value to nothing
+    pub fn take(&mut self) -> DeferredCallRegistryChanges {
+        std::mem::take(&mut self.deferred_call_changes)
+    }
+
+    /// Takes a snapshot (clone) of the changes since its creation
+    pub fn get_snapshot(&self) -> DeferredCallRegistryChanges {
+        self.deferred_call_changes.clone()
+    }
+
+    /// Resets the `SpeculativeDeferredCallRegistry` to a snapshot (see `get_snapshot` method)
+    pub fn reset_to_snapshot(&mut self, snapshot: DeferredCallRegistryChanges) {
+        self.deferred_call_changes = snapshot;
+    }
+
+    /// Gets a registered call that was not executed nor cancelled yet
+    pub fn get_call(&self, id: &DeferredCallId) -> Option<DeferredCall> {
+        // check in the current changes
+        match self.deferred_call_changes.0.get(id) {
+            Some(SetOrDelete::Set(call)) => return Some(call.clone()),
+            Some(SetOrDelete::Delete) => return None,
+            None => {}
+        }
+
+        // check in the active history, backwards
+        match self.active_history.read().fetch_deferred_call(id) {
+            HistorySearchResult::Present(call) => return Some(call),
+            HistorySearchResult::Absent => return None,
+            HistorySearchResult::NoInfo => {}
+        }
+
+        // check in the final state
+        self.final_state.read().deferred_call_registry.get_call(id)
+    }
+
+    /// Gets all the registered calls targeting a given slot, sorted by id
+    pub fn get_slot_calls(&self, slot: Slot) -> BTreeMap<DeferredCallId, DeferredCall> {
+        // start from the final state
+        let mut calls = self
+            .final_state
+            .read()
+            .deferred_call_registry
+            .get_slot_calls(&slot);
+
+        // apply the changes of the active history, then the current changes
+        let history_changes = self
+            .active_history
+            .read()
+            .get_deferred_call_slot_changes(slot);
+        let current_changes = self
+            .deferred_call_changes
+            .get_slot_changes(slot)
+            .map(|(id, change)| (*id, change.clone()));
+        for (id, change) in history_changes.into_iter().chain(current_changes) {
+            match change {
+                SetOrDelete::Set(call) => {
+                    calls.insert(id, call);
+                }
+                SetOrDelete::Delete => {
+                    calls.remove(&id);
+                }
+            }
+        }
+        calls
+    }
+
+    /// Gets the total gas booked by the registered calls targeting a given slot
+    pub fn get_slot_booked_gas(&self, slot: Slot) -> u64 {
+        self.get_slot_calls(slot)
+            .values()
+            .fold(0u64, |acc, call| acc.saturating_add(call.max_gas))
+    }
+
+    /// Registers a new call
+    pub fn push_new_call(&mut self, id: DeferredCallId, call: DeferredCall) {
+        self.deferred_call_changes.push_new_call(id, call);
+    }
+
+    /// Removes a call from the registry, once executed or cancelled
+    pub fn delete_call(&mut self, id: DeferredCallId) {
+        self.deferred_call_changes.push_delete(id);
+    }
+}
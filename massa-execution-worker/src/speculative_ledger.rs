@@ -12,10 +12,11 @@ use massa_final_state::FinalState;
 use massa_ledger_exports::{Applicable, LedgerChanges, SetOrDelete, SetUpdateOrDelete};
 use massa_models::bytecode::Bytecode;
 use massa_models::datastore::get_prefix_bounds;
+use massa_models::prehash::PreHashMap;
 use massa_models::{address::Address, amount::Amount};
 use parking_lot::RwLock;
 use std::cmp::Ordering;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use tracing::debug;
 
@@ -56,6 +57,13 @@ pub(crate) struct SpeculativeLedger {
     /// Max bytecode size
     max_bytecode_size: u64,
 
+    /// Max number of keys in the datastore of an address, `None` if the number of keys is not capped
+    max_datastore_keys_per_address: Option<u64>,
+
+    /// Number of datastore keys of the addresses whose datastore was written to under the cap,
+    /// computed on first write and kept in sync with `added_changes`
+    datastore_key_counts: PreHashMap<Address, u64>,
+
     /// storage cost constants
     storage_costs_constants: StorageCostsConstants,
 }
@@ -81,13 +89,27 @@ impl SpeculativeLedger {
             max_datastore_key_length,
             max_datastore_value_size,
             max_bytecode_size,
+            max_datastore_keys_per_address: None,
+            datastore_key_counts: Default::default(),
             storage_costs_constants,
         }
     }
 
+    /// Caps the number of keys of each datastore, or lifts the cap with `None`
+    pub fn set_max_datastore_keys_per_address(&mut self, max: Option<u64>) {
+        self.max_datastore_keys_per_address = max;
+        self.datastore_key_counts.clear();
+    }
+
+    /// Gets the max number of keys of each datastore, `None` if the number of keys is not capped
+    pub fn get_max_datastore_keys_per_address(&self) -> Option<u64> {
+        self.max_datastore_keys_per_address
+    }
+
     /// Returns the changes caused to the `SpeculativeLedger` since its creation,
     /// and resets their local value to nothing.
     pub fn take(&mut self) -> LedgerChanges {
+        self.datastore_key_counts.clear();
         std::mem::take(&mut self.added_changes)
     }
 
@@ -98,6 +120,8 @@ impl SpeculativeLedger {
 
     /// Resets the `SpeculativeLedger` to a snapshot (see `get_snapshot` method)
     pub fn reset_to_snapshot(&mut self, snapshot: LedgerChanges) {
+        // the counts may include keys added after the snapshot
+        self.datastore_key_counts.clear();
         self.added_changes = snapshot;
     }
 
@@ -158,11 +182,11 @@ impl SpeculativeLedger {
         if let Some(from_addr) = from_addr {
             let new_balance = self
                 .get_balance(&from_addr)
-                .ok_or_else(|| ExecutionError::RuntimeError(format!("spending address {} not found", from_addr)))?
+                .ok_or(ExecutionError::MissingLedgerEntry { address: from_addr })?
                 .checked_sub(amount)
-                .ok_or_else(|| {
-                    ExecutionError::RuntimeError(format!("failed to transfer {} from spending address {} due to insufficient balance {}", amount, from_addr, self
-                    .get_balance(&from_addr).unwrap_or_default()))
+                .ok_or_else(|| ExecutionError::InsufficientBalance {
+                    needed: amount,
+                    available: self.get_balance(&from_addr).unwrap_or_default(),
                 })?;
 
             // update the balance of the sender address
@@ -238,10 +262,9 @@ impl SpeculativeLedger {
     ) -> Result<(), ExecutionError> {
         // check for address existence
         if !self.entry_exists(&creator_address) {
-            return Err(ExecutionError::RuntimeError(format!(
-                "could not create SC address {}: creator address {} does not exist",
-                addr, creator_address
-            )));
+            return Err(ExecutionError::MissingLedgerEntry {
+                address: creator_address,
+            });
         }
 
         // check that we don't collide with existing address
@@ -253,10 +276,10 @@ impl SpeculativeLedger {
         }
 
         if bytecode.0.len() > self.max_bytecode_size as usize {
-            return Err(ExecutionError::RuntimeError(format!(
-                "could not create SC address {}: bytecode size exceeds maximum allowed size",
-                addr
-            )));
+            return Err(ExecutionError::BytecodeTooLarge {
+                size: bytecode.0.len(),
+                max: self.max_bytecode_size,
+            });
         }
 
         // calculate the cost of storing the address and bytecode
@@ -303,17 +326,14 @@ impl SpeculativeLedger {
     ) -> Result<(), ExecutionError> {
         // check for address existence
         if !self.entry_exists(addr) {
-            return Err(ExecutionError::RuntimeError(format!(
-                "could not set bytecode for address {}: address does not exist",
-                addr
-            )));
+            return Err(ExecutionError::MissingLedgerEntry { address: *addr });
         }
 
         if bytecode.0.len() > self.max_bytecode_size as usize {
-            return Err(ExecutionError::RuntimeError(format!(
-                "could not set bytecode for address {}: bytecode size exceeds maximum allowed size",
-                addr
-            )));
+            return Err(ExecutionError::BytecodeTooLarge {
+                size: bytecode.0.len(),
+                max: self.max_bytecode_size,
+            });
         }
 
         if let Some(old_bytecode_size) = self.get_bytecode(addr).map(|b| b.0.len()) {
@@ -578,52 +598,64 @@ impl SpeculativeLedger {
     /// * `addr`: target address
     /// * `key`: datastore key
     /// * `data`: value to associate to the datastore key
+    ///
+    /// # Returns
+    /// Whether the datastore key was created
     pub fn set_data_entry(
         &mut self,
         caller_addr: &Address,
         addr: &Address,
         key: Vec<u8>,
         value: Vec<u8>,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<bool, ExecutionError> {
         // check for address existence
         if !self.entry_exists(addr) {
-            return Err(ExecutionError::RuntimeError(format!(
-                "could not set data for address {}: address does not exist",
-                addr
-            )));
+            return Err(ExecutionError::MissingLedgerEntry { address: *addr });
         }
 
         // check key correctness
         let key_length = key.len();
         if key_length == 0 || key_length > self.max_datastore_key_length as usize {
-            return Err(ExecutionError::RuntimeError(format!(
-                "key length is {}, but it must be in [0..={}]",
-                key_length, self.max_datastore_key_length
-            )));
+            return Err(ExecutionError::DatastoreKeyLength {
+                length: key_length,
+                max: self.max_datastore_key_length,
+            });
         }
 
         if value.len() > self.max_datastore_value_size as usize {
-            return Err(ExecutionError::RuntimeError(format!(
-                "value length is {}, but it must be in [0..={}]",
-                value.len(),
-                self.max_datastore_value_size
-            )));
+            return Err(ExecutionError::DatastoreValueTooLarge {
+                size: value.len(),
+                max: self.max_datastore_value_size,
+            });
         }
 
-        // charge the storage costs of the entry change
-        {
-            let prev_value = self.get_data_entry(addr, &key);
-            self.charge_datastore_entry_change_storage(
-                caller_addr,
-                prev_value.as_ref().map(|v| (&key[..], &v[..])),
-                Some((&key, &value)),
-            )?;
+        // check the number of keys of the datastore and charge the storage costs of the entry change
+        let prev_value = self.get_data_entry(addr, &key);
+        let created = prev_value.is_none();
+        if let (true, Some(max)) = (created, self.max_datastore_keys_per_address) {
+            let key_count = self.get_datastore_key_count(addr);
+            if key_count >= max {
+                return Err(ExecutionError::RuntimeError(format!(
+                    "could not set data for address {}: its datastore already has {} keys, the maximum is {}",
+                    addr, key_count, max
+                )));
+            }
         }
+        self.charge_datastore_entry_change_storage(
+            caller_addr,
+            prev_value.as_ref().map(|v| (&key[..], &v[..])),
+            Some((&key, &value)),
+        )?;
 
         // set data
+        if created {
+            if let Some(count) = self.datastore_key_counts.get_mut(addr) {
+                *count += 1;
+            }
+        }
         self.added_changes.set_data_entry(*addr, key, value);
 
-        Ok(())
+        Ok(created)
     }
 
     /// Deletes a datastore entry for a given address.
@@ -651,8 +683,75 @@ impl SpeculativeLedger {
         }
 
         // delete entry
+        if let Some(count) = self.datastore_key_counts.get_mut(addr) {
+            *count = count.saturating_sub(1);
+        }
         self.added_changes.delete_data_entry(*addr, key.to_owned());
 
         Ok(())
     }
+
+    /// Gets the number of keys in the datastore of an address, 0 if the address does not exist.
+    /// The keys are never listed: the count maintained by the final ledger is only updated
+    /// with the datastore changes of the active history and of this `SpeculativeLedger`.
+    fn get_datastore_key_count(&mut self, addr: &Address) -> u64 {
+        if let Some(count) = self.datastore_key_counts.get(addr) {
+            return *count;
+        }
+
+        let final_state = self.final_state.read();
+        let mut count = final_state.ledger.get_datastore_key_count(addr);
+        // whether the keys written so far exist, the other keys being found in the final ledger
+        // until the entry is set or deleted
+        let mut written_keys: BTreeMap<Vec<u8>, bool> = BTreeMap::new();
+        let mut in_final_ledger = true;
+        let active_history = self.active_history.read();
+        let changes_iterator = active_history
+            .0
+            .iter()
+            .map(|item| &item.state_changes.ledger_changes)
+            .chain(std::iter::once(&self.added_changes));
+        for ledger_changes in changes_iterator {
+            match ledger_changes.get(addr) {
+                None => (),
+                Some(SetUpdateOrDelete::Set(new_ledger_entry)) => {
+                    count = new_ledger_entry.datastore.len() as u64;
+                    written_keys = new_ledger_entry
+                        .datastore
+                        .keys()
+                        .map(|key| (key.clone(), true))
+                        .collect();
+                    in_final_ledger = false;
+                }
+                Some(SetUpdateOrDelete::Update(entry_updates)) => {
+                    for (key, update) in entry_updates.datastore.iter() {
+                        let existed = match written_keys.get(key) {
+                            Some(exists) => *exists,
+                            None => {
+                                in_final_ledger
+                                    && final_state.ledger.get_data_entry(addr, key).is_some()
+                            }
+                        };
+                        let exists = matches!(update, SetOrDelete::Set(_));
+                        match (existed, exists) {
+                            (false, true) => count += 1,
+                            (true, false) => count = count.saturating_sub(1),
+                            _ => (),
+                        }
+                        written_keys.insert(key.clone(), exists);
+                    }
+                }
+                Some(SetUpdateOrDelete::Delete) => {
+                    count = 0;
+                    written_keys.clear();
+                    in_final_ledger = false;
+                }
+            }
+        }
+        drop(active_history);
+        drop(final_state);
+
+        self.datastore_key_counts.insert(*addr, count);
+        count
+    }
 }
@@ -74,6 +74,21 @@ impl SpeculativeRollState {
             })
     }
 
+    /// Get the slot of the latest roll sale of an address, if it is still recorded
+    fn get_last_roll_sale(&self, addr: &Address) -> Option<Slot> {
+        self.added_changes
+            .roll_sales
+            .get(addr)
+            .copied()
+            .or_else(|| self.active_history.read().fetch_last_roll_sale(addr))
+            .or_else(|| {
+                self.final_state
+                    .read()
+                    .pos_state
+                    .get_address_last_roll_sale(addr)
+            })
+    }
+
     /// Add `roll_count` rolls to the buyer address.
     /// Validity checks must be performed _outside_ of this function.
     ///
@@ -99,6 +114,8 @@ impl SpeculativeRollState {
     /// # Arguments
     /// * `seller_addr`: address to sell the rolls from
     /// * `roll_count`: number of rolls to sell
+    /// * `sale_cooldown_cycles`: minimal number of cycles since the previous sale of the seller (0 for no cooldown)
+    #[allow(clippy::too_many_arguments)]
     pub fn try_sell_rolls(
         &mut self,
         seller_addr: &Address,
@@ -107,6 +124,7 @@ impl SpeculativeRollState {
         periods_per_cycle: u64,
         thread_count: u8,
         roll_price: Amount,
+        sale_cooldown_cycles: u64,
     ) -> Result<(), ExecutionError> {
         // fetch the roll count from: current changes > active history > final state
         let owned_count = self.get_rolls(seller_addr);
@@ -121,6 +139,21 @@ impl SpeculativeRollState {
 
         // compute deferred credit slot
         let cur_cycle = slot.get_cycle(periods_per_cycle);
+
+        // verify that the previous sale is old enough.
+        // The sales are recorded in the cycle history, which covers the cooldown (checked at startup).
+        if sale_cooldown_cycles > 0 {
+            if let Some(last_sale_slot) = self.get_last_roll_sale(seller_addr) {
+                let last_sale_cycle = last_sale_slot.get_cycle(periods_per_cycle);
+                let next_sale_cycle = last_sale_cycle.saturating_add(sale_cooldown_cycles);
+                if cur_cycle < next_sale_cycle {
+                    return Err(ExecutionError::RollSellError(format!(
+                        "{} sold rolls at slot {} and cannot sell again before cycle {}",
+                        seller_addr, last_sale_slot, next_sale_cycle
+                    )));
+                }
+            }
+        }
         let target_slot = Slot::new_last_of_cycle(
             cur_cycle
                 .checked_add(3)
@@ -146,6 +179,11 @@ impl SpeculativeRollState {
             .deferred_credits
             .insert(target_slot, *seller_addr, new_deferred_credits);
 
+        // Record the sale for the cooldown
+        if sale_cooldown_cycles > 0 {
+            self.added_changes.roll_sales.insert(*seller_addr, slot);
+        }
+
         Ok(())
     }
 
@@ -376,12 +414,14 @@ impl SpeculativeRollState {
         None
     }
 
-    /// Get the production statistics for a given address at a given cycle.
+    /// Get the production statistics for a given address at a given cycle,
+    /// with the misses left before the implicit roll sale for the cycles that are not final.
     pub fn get_address_cycle_infos(
         &self,
         address: &Address,
         periods_per_cycle: u64,
         cur_slot: Slot,
+        max_miss_ratio: Ratio<u64>,
     ) -> Vec<ExecutionAddressCycleInfo> {
         let mut res: Vec<ExecutionAddressCycleInfo> = Vec::new();
 
@@ -399,7 +439,8 @@ impl SpeculativeRollState {
                     is_final: c.1,
                     ok_count: 0,
                     nok_count: 0,
-                    active_rolls: None, // will be filled afterwards
+                    active_rolls: None,            // will be filled afterwards
+                    misses_before_roll_sale: None, // will be filled afterwards
                 };
                 if let Some(prod_stats) = final_state
                     .pos_state
@@ -425,7 +466,8 @@ impl SpeculativeRollState {
                         is_final: false,
                         ok_count: 0,
                         nok_count: 0,
-                        active_rolls: None, // will be filled afterwards
+                        active_rolls: None,            // will be filled afterwards
+                        misses_before_roll_sale: None, // will be filled afterwards
                     });
                 }
 
@@ -458,7 +500,8 @@ impl SpeculativeRollState {
                     is_final: false,
                     ok_count: 0,
                     nok_count: 0,
-                    active_rolls: None, // will be filled afterwards
+                    active_rolls: None,            // will be filled afterwards
+                    misses_before_roll_sale: None, // will be filled afterwards
                 });
             }
 
@@ -472,11 +515,18 @@ impl SpeculativeRollState {
             }
         }
 
-        // add active roll counts
+        // add active roll counts, and the misses left before the implicit roll sale of the ongoing cycles
         for itm in res.iter_mut() {
             itm.active_rolls = final_state
                 .pos_state
                 .get_address_active_rolls(address, itm.cycle);
+            if !itm.is_final {
+                itm.misses_before_roll_sale = ProductionStats {
+                    block_success_count: itm.ok_count,
+                    block_failure_count: itm.nok_count,
+                }
+                .misses_before_roll_sale(&max_miss_ratio);
+            }
         }
 
         res
@@ -0,0 +1,374 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! State views opened through the private API.
+//!
+//! A state view pins the final and candidate ledger as they were when it was opened,
+//! so that the successive queries of a client referencing it see the same state.
+//! The active history is copied when the view is opened. The final ledger is not:
+//! each view keeps the changes reverting the final ledger to its pinned state,
+//! extended with the reverse of every final slot applied while the view is open.
+//! Views are closed by their client, or when their time to live is over.
+//! The reverse changes of the final slots are only computed while views are open.
+
+use crate::active_history::{apply_datastore_key_changes, ActiveHistory, HistorySearchResult};
+use massa_execution_exports::{
+    ExecutionConfig, ExecutionError, ExecutionQueryError, ExecutionQueryRequestItem,
+    ExecutionQueryResponseItem, StateViewInfo,
+};
+use massa_hash::Hash;
+use massa_ledger_exports::{
+    Applicable, LedgerChanges, LedgerController, LedgerEntry, LedgerEntryUpdate, SetOrDelete,
+    SetOrKeep, SetUpdateOrDelete,
+};
+use massa_models::address::Address;
+use massa_models::amount::Amount;
+use massa_models::bytecode::Bytecode;
+use massa_models::datastore::get_prefix_bounds;
+use massa_models::slot::Slot;
+use massa_time::MassaTime;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Final and candidate ledger pinned when the view was opened
+pub(crate) struct StateView {
+    /// time at which the view is closed
+    expires_at: MassaTime,
+    /// last executed final slot when the view was opened
+    pub final_cursor: Slot,
+    /// last executed candidate slot when the view was opened
+    pub candidate_cursor: Slot,
+    /// final state fingerprint when the view was opened
+    pub final_state_fingerprint: Hash,
+    /// changes reverting the current final ledger to the pinned one
+    final_undo: LedgerChanges,
+    /// active history when the view was opened
+    active_history: ActiveHistory,
+}
+
+/// State views opened through the API, indexed by id
+pub(crate) struct StateViews {
+    /// max number of views open at the same time
+    max_views: usize,
+    /// duration after which a view is closed
+    ttl: MassaTime,
+    /// id of the next view
+    next_id: u64,
+    /// open views
+    views: BTreeMap<u64, StateView>,
+}
+
+impl StateViews {
+    /// Creates an empty set of views
+    pub(crate) fn new(config: &ExecutionConfig) -> Self {
+        Self {
+            max_views: config.max_state_views,
+            ttl: config.state_view_ttl,
+            next_id: 0,
+            views: BTreeMap::new(),
+        }
+    }
+
+    /// Opens a view pinning the current final ledger and `active_history`
+    pub(crate) fn open(
+        &mut self,
+        final_cursor: Slot,
+        candidate_cursor: Slot,
+        final_state_fingerprint: Hash,
+        active_history: ActiveHistory,
+    ) -> Result<StateViewInfo, ExecutionError> {
+        let now = MassaTime::now().expect("could not get current time");
+        self.views.retain(|_, view| view.expires_at > now);
+        if self.views.len() >= self.max_views {
+            return Err(ExecutionError::StateViewError(format!(
+                "too many state views open, the maximum is {}",
+                self.max_views
+            )));
+        }
+        let view_id = self.next_id;
+        self.next_id += 1;
+        let expires_at = now.saturating_add(self.ttl);
+        self.views.insert(
+            view_id,
+            StateView {
+                expires_at,
+                final_cursor,
+                candidate_cursor,
+                final_state_fingerprint,
+                final_undo: LedgerChanges::default(),
+                active_history,
+            },
+        );
+        Ok(StateViewInfo {
+            view_id,
+            expires_at,
+            candidate_cursor,
+            final_cursor,
+        })
+    }
+
+    /// Closes a view. Unknown ids are ignored.
+    pub(crate) fn close(&mut self, view_id: u64) {
+        self.views.remove(&view_id);
+    }
+
+    /// Gets an open view, or None if it does not exist or has expired
+    pub(crate) fn get(&self, view_id: u64) -> Option<&StateView> {
+        let now = MassaTime::now().expect("could not get current time");
+        self.views
+            .get(&view_id)
+            .filter(|view| view.expires_at > now)
+    }
+
+    /// Records in the open views the final ledger changes about to be applied to `ledger`,
+    /// so that the views keep seeing the ledger they pinned
+    pub(crate) fn record_final_changes(
+        &mut self,
+        changes: &LedgerChanges,
+        ledger: &dyn LedgerController,
+    ) {
+        let now = MassaTime::now().expect("could not get current time");
+        self.views.retain(|_, view| view.expires_at > now);
+        if self.views.is_empty() {
+            return;
+        }
+        let undo = undo_changes(changes, ledger);
+        for view in self.views.values_mut() {
+            // the reverse of the new changes is applied first, then the previous ones
+            let mut final_undo = undo.clone();
+            final_undo.apply(std::mem::take(&mut view.final_undo));
+            view.final_undo = final_undo;
+        }
+    }
+}
+
+/// Computes the changes reverting `changes` once applied to `ledger`
+pub(crate) fn undo_changes(
+    changes: &LedgerChanges,
+    ledger: &dyn LedgerController,
+) -> LedgerChanges {
+    let mut undo = LedgerChanges::default();
+    for (addr, change) in &changes.0 {
+        let Some(balance) = ledger.get_balance(addr) else {
+            // the entry did not exist before the changes
+            undo.0.insert(*addr, SetUpdateOrDelete::Delete);
+            continue;
+        };
+        let undo_change = match change {
+            SetUpdateOrDelete::Set(_) | SetUpdateOrDelete::Delete => {
+                let datastore = ledger
+                    .get_datastore_keys(addr, &[])
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|key| {
+                        let value = ledger.get_data_entry(addr, &key)?;
+                        Some((key, value))
+                    })
+                    .collect();
+                SetUpdateOrDelete::Set(LedgerEntry {
+                    balance,
+                    bytecode: ledger.get_bytecode(addr).unwrap_or_default(),
+                    datastore,
+                })
+            }
+            SetUpdateOrDelete::Update(update) => SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: match update.balance {
+                    SetOrKeep::Set(_) => SetOrKeep::Set(balance),
+                    SetOrKeep::Keep => SetOrKeep::Keep,
+                },
+                bytecode: match update.bytecode {
+                    SetOrKeep::Set(_) => {
+                        SetOrKeep::Set(ledger.get_bytecode(addr).unwrap_or_default())
+                    }
+                    SetOrKeep::Keep => SetOrKeep::Keep,
+                },
+                datastore: update
+                    .datastore
+                    .keys()
+                    .map(|key| {
+                        let value = match ledger.get_data_entry(addr, key) {
+                            Some(value) => SetOrDelete::Set(value),
+                            None => SetOrDelete::Delete,
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            }),
+        };
+        undo.0.insert(*addr, undo_change);
+    }
+    undo
+}
+
+impl StateView {
+    /// Creates a view of the final ledger at a past final slot, from the changes reverting the current final ledger to it.
+    /// The candidate ledger of the view is its final ledger.
+    pub(crate) fn at_final_slot(
+        slot: Slot,
+        final_state_fingerprint: Hash,
+        final_undo: LedgerChanges,
+    ) -> Self {
+        StateView {
+            expires_at: MassaTime::now().expect("could not get current time"),
+            final_cursor: slot,
+            candidate_cursor: slot,
+            final_state_fingerprint,
+            final_undo,
+            active_history: ActiveHistory::default(),
+        }
+    }
+
+    /// Gets a balance both at the pinned final and candidate slots
+    fn get_final_and_candidate_balance(
+        &self,
+        ledger: &dyn LedgerController,
+        addr: &Address,
+    ) -> (Option<Amount>, Option<Amount>) {
+        let final_balance = self
+            .final_undo
+            .get_balance_or_else(addr, || ledger.get_balance(addr));
+        let candidate_balance = match self.active_history.fetch_balance(addr) {
+            HistorySearchResult::Present(balance) => Some(balance),
+            HistorySearchResult::NoInfo => final_balance,
+            HistorySearchResult::Absent => None,
+        };
+        (final_balance, candidate_balance)
+    }
+
+    /// Gets a bytecode both at the pinned final and candidate slots
+    fn get_final_and_candidate_bytecode(
+        &self,
+        ledger: &dyn LedgerController,
+        addr: &Address,
+    ) -> (Option<Bytecode>, Option<Bytecode>) {
+        let final_bytecode = self
+            .final_undo
+            .get_bytecode_or_else(addr, || ledger.get_bytecode(addr));
+        let candidate_bytecode = match self.active_history.fetch_bytecode(addr) {
+            HistorySearchResult::Present(bytecode) => Some(bytecode),
+            HistorySearchResult::NoInfo => final_bytecode.clone(),
+            HistorySearchResult::Absent => None,
+        };
+        (final_bytecode, candidate_bytecode)
+    }
+
+    /// Gets a datastore value both at the pinned final and candidate slots
+    fn get_final_and_candidate_data_entry(
+        &self,
+        ledger: &dyn LedgerController,
+        addr: &Address,
+        key: &[u8],
+    ) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+        let final_entry = self
+            .final_undo
+            .get_data_entry_or_else(addr, key, || ledger.get_data_entry(addr, key));
+        let candidate_entry = match self
+            .active_history
+            .fetch_active_history_data_entry(addr, key)
+        {
+            HistorySearchResult::Present(entry) => Some(entry),
+            HistorySearchResult::NoInfo => final_entry.clone(),
+            HistorySearchResult::Absent => None,
+        };
+        (final_entry, candidate_entry)
+    }
+
+    /// Gets the datastore keys starting with `prefix` both at the pinned final and candidate slots
+    #[allow(clippy::type_complexity)]
+    fn get_final_and_candidate_datastore_keys(
+        &self,
+        ledger: &dyn LedgerController,
+        addr: &Address,
+        prefix: &[u8],
+    ) -> (Option<BTreeSet<Vec<u8>>>, Option<BTreeSet<Vec<u8>>>) {
+        let prefix_range = get_prefix_bounds(prefix);
+        let range_ref = (prefix_range.0.as_ref(), prefix_range.1.as_ref());
+
+        let mut final_keys = ledger.get_datastore_keys(addr, prefix);
+        apply_datastore_key_changes(&mut final_keys, self.final_undo.get(addr), range_ref);
+        let mut candidate_keys = final_keys.clone();
+        for output in &self.active_history.0 {
+            apply_datastore_key_changes(
+                &mut candidate_keys,
+                output.state_changes.ledger_changes.get(addr),
+                range_ref,
+            );
+        }
+        (final_keys, candidate_keys)
+    }
+
+    /// Executes a query in the view. Only the ledger queries are supported.
+    pub(crate) fn query(
+        &self,
+        ledger: &dyn LedgerController,
+        request: ExecutionQueryRequestItem,
+    ) -> Result<ExecutionQueryResponseItem, ExecutionQueryError> {
+        let account_not_found =
+            |addr: &Address| ExecutionQueryError::NotFound(format!("Account {}", addr));
+        match request {
+            ExecutionQueryRequestItem::AddressExistsCandidate(addr) => {
+                Ok(ExecutionQueryResponseItem::Boolean(
+                    self.get_final_and_candidate_balance(ledger, &addr)
+                        .1
+                        .is_some(),
+                ))
+            }
+            ExecutionQueryRequestItem::AddressExistsFinal(addr) => {
+                Ok(ExecutionQueryResponseItem::Boolean(
+                    self.get_final_and_candidate_balance(ledger, &addr)
+                        .0
+                        .is_some(),
+                ))
+            }
+            ExecutionQueryRequestItem::AddressBalanceCandidate(addr) => self
+                .get_final_and_candidate_balance(ledger, &addr)
+                .1
+                .map(ExecutionQueryResponseItem::Amount)
+                .ok_or_else(|| account_not_found(&addr)),
+            ExecutionQueryRequestItem::AddressBalanceFinal(addr) => self
+                .get_final_and_candidate_balance(ledger, &addr)
+                .0
+                .map(ExecutionQueryResponseItem::Amount)
+                .ok_or_else(|| account_not_found(&addr)),
+            ExecutionQueryRequestItem::AddressBytecodeCandidate(addr) => self
+                .get_final_and_candidate_bytecode(ledger, &addr)
+                .1
+                .map(ExecutionQueryResponseItem::Bytecode)
+                .ok_or_else(|| account_not_found(&addr)),
+            ExecutionQueryRequestItem::AddressBytecodeFinal(addr) => self
+                .get_final_and_candidate_bytecode(ledger, &addr)
+                .0
+                .map(ExecutionQueryResponseItem::Bytecode)
+                .ok_or_else(|| account_not_found(&addr)),
+            ExecutionQueryRequestItem::AddressDatastoreKeysCandidate { addr, prefix } => self
+                .get_final_and_candidate_datastore_keys(ledger, &addr, &prefix)
+                .1
+                .map(ExecutionQueryResponseItem::KeyList)
+                .ok_or_else(|| account_not_found(&addr)),
+            ExecutionQueryRequestItem::AddressDatastoreKeysFinal { addr, prefix } => self
+                .get_final_and_candidate_datastore_keys(ledger, &addr, &prefix)
+                .0
+                .map(ExecutionQueryResponseItem::KeyList)
+                .ok_or_else(|| account_not_found(&addr)),
+            ExecutionQueryRequestItem::AddressDatastoreValueCandidate { addr, key } => self
+                .get_final_and_candidate_data_entry(ledger, &addr, &key)
+                .1
+                .map(ExecutionQueryResponseItem::DatastoreValue)
+                .ok_or_else(|| {
+                    ExecutionQueryError::NotFound(format!(
+                        "Account {} datastore entry {:?}",
+                        addr, key
+                    ))
+                }),
+            ExecutionQueryRequestItem::AddressDatastoreValueFinal { addr, key } => self
+                .get_final_and_candidate_data_entry(ledger, &addr, &key)
+                .0
+                .map(ExecutionQueryResponseItem::DatastoreValue)
+                .ok_or_else(|| {
+                    ExecutionQueryError::NotFound(format!(
+                        "Account {} datastore entry {:?}",
+                        addr, key
+                    ))
+                }),
+            _ => Err(ExecutionQueryError::NotSupportedInStateView),
+        }
+    }
+}
@@ -2,9 +2,21 @@
 
 use massa_models::slot::Slot;
 use massa_models::stats::ExecutionStats;
+use massa_module_cache::types::ModuleCacheStats;
 use massa_time::MassaTime;
 use std::collections::VecDeque;
 
+/// Outcome of the execution of a final block, registered in the stats
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FinalBlockStats {
+    /// number of operations executed successfully
+    pub applied_operations: usize,
+    /// number of operations whose effects were reverted
+    pub failed_operations: usize,
+    /// gas used by the operations of the block
+    pub gas_used: u64,
+}
+
 /// Execution statistics counter
 pub struct ExecutionStatsCounter {
     /// duration of the time window
@@ -15,6 +27,32 @@ pub struct ExecutionStatsCounter {
     final_executed_ops: VecDeque<(usize, MassaTime)>,
     /// final denunciations executed in the time window (count, instant)
     final_executed_denunciations: VecDeque<(usize, MassaTime)>,
+    /// outcome of the final blocks executed in the time window (stats, instant)
+    final_block_stats: VecDeque<(FinalBlockStats, MassaTime)>,
+    /// async messages executed in the final slots of the time window (count, instant)
+    final_executed_async_messages: VecDeque<(usize, MassaTime)>,
+    /// module cache hits and misses in the time window (stats, instant)
+    module_cache_stats: VecDeque<(ModuleCacheStats, MassaTime)>,
+}
+
+/// remove the records of a queue that are older than `start_time`
+fn prune<T>(queue: &mut VecDeque<(T, MassaTime)>, start_time: MassaTime) {
+    while let Some((_, t)) = queue.front() {
+        if t < &start_time {
+            queue.pop_front();
+        } else {
+            break;
+        }
+    }
+}
+
+/// compute a ratio, None if the total is zero
+fn ratio(count: u64, total: u64) -> Option<f64> {
+    if total == 0 {
+        None
+    } else {
+        Some(count as f64 / total as f64)
+    }
 }
 
 impl ExecutionStatsCounter {
@@ -25,30 +63,21 @@ impl ExecutionStatsCounter {
             final_blocks: Default::default(),
             final_executed_ops: Default::default(),
             final_executed_denunciations: Default::default(),
+            final_block_stats: Default::default(),
+            final_executed_async_messages: Default::default(),
+            module_cache_stats: Default::default(),
         }
     }
 
     /// refresh the counters and delete old records
     fn refresh(&mut self, current_time: MassaTime) {
         let start_time = current_time.saturating_sub(self.time_window_duration);
-
-        // prune final blocks
-        while let Some((_, t)) = self.final_blocks.front() {
-            if t < &start_time {
-                self.final_blocks.pop_front();
-            } else {
-                break;
-            }
-        }
-
-        // prune final executed ops
-        while let Some((_, t)) = self.final_executed_ops.front() {
-            if t < &start_time {
-                self.final_executed_ops.pop_front();
-            } else {
-                break;
-            }
-        }
+        prune(&mut self.final_blocks, start_time);
+        prune(&mut self.final_executed_ops, start_time);
+        prune(&mut self.final_executed_denunciations, start_time);
+        prune(&mut self.final_block_stats, start_time);
+        prune(&mut self.final_executed_async_messages, start_time);
+        prune(&mut self.module_cache_stats, start_time);
     }
 
     /// register final blocks
@@ -73,25 +102,134 @@ impl ExecutionStatsCounter {
         self.refresh(current_time);
     }
 
+    /// register the outcome of the execution of a final block
+    pub fn register_final_block_stats(&mut self, stats: FinalBlockStats) {
+        let current_time = MassaTime::now().expect("could not get current time");
+        self.final_block_stats.push_back((stats, current_time));
+        self.refresh(current_time);
+    }
+
+    /// register final executed async messages
+    pub fn register_final_executed_async_messages(&mut self, count: usize) {
+        let current_time = MassaTime::now().expect("could not get current time");
+        self.final_executed_async_messages
+            .push_back((count, current_time));
+        self.refresh(current_time);
+    }
+
+    /// register module cache hits and misses
+    pub fn register_module_cache_stats(&mut self, stats: ModuleCacheStats) {
+        let current_time = MassaTime::now().expect("could not get current time");
+        self.module_cache_stats.push_back((stats, current_time));
+        self.refresh(current_time);
+    }
+
     /// get statistics
-    pub fn get_stats(&self, active_cursor: Slot, final_cursor: Slot) -> ExecutionStats {
+    pub fn get_stats(
+        &self,
+        active_cursor: Slot,
+        final_cursor: Slot,
+        earliest_final_event_slot: Slot,
+        earliest_executed_op_slot: Slot,
+    ) -> ExecutionStats {
         let current_time = MassaTime::now().expect("could not get current time");
         let start_time = current_time.saturating_sub(self.time_window_duration);
+        let in_window = |t: &MassaTime| t >= &start_time && t <= &current_time;
         let map_func = |pair: &(usize, MassaTime)| -> usize {
             let (cnt, t) = pair;
-            if t >= &start_time && t <= &current_time {
+            if in_window(t) {
                 *cnt
             } else {
                 0
             }
         };
+
+        let mut block_stats = FinalBlockStats::default();
+        let mut block_stats_count: u64 = 0;
+        for (stats, _) in self.final_block_stats.iter().filter(|(_, t)| in_window(t)) {
+            block_stats.applied_operations += stats.applied_operations;
+            block_stats.failed_operations += stats.failed_operations;
+            block_stats.gas_used = block_stats.gas_used.saturating_add(stats.gas_used);
+            block_stats_count += 1;
+        }
+
+        let mut cache_stats = ModuleCacheStats::default();
+        for (stats, _) in self.module_cache_stats.iter().filter(|(_, t)| in_window(t)) {
+            cache_stats.hits += stats.hits;
+            cache_stats.misses += stats.misses;
+            cache_stats.hd_hits += stats.hd_hits;
+        }
+
         ExecutionStats {
             final_block_count: self.final_blocks.iter().map(map_func).sum(),
             final_executed_operations_count: self.final_executed_ops.iter().map(map_func).sum(),
+            final_applied_operations_count: block_stats.applied_operations,
+            final_failed_operations_count: block_stats.failed_operations,
+            average_gas_per_block: block_stats
+                .gas_used
+                .checked_div(block_stats_count)
+                .unwrap_or_default(),
+            final_executed_async_messages_count: self
+                .final_executed_async_messages
+                .iter()
+                .map(map_func)
+                .sum(),
+            module_cache_ram_hit_ratio: ratio(
+                cache_stats.hits,
+                cache_stats.hits + cache_stats.misses,
+            ),
+            module_cache_hd_hit_ratio: ratio(cache_stats.hd_hits, cache_stats.misses),
             time_window_start: start_time,
             time_window_end: current_time,
             active_cursor,
             final_cursor,
+            earliest_final_event_slot,
+            earliest_executed_op_slot,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_counter_aggregates_window() {
+        let mut counter = ExecutionStatsCounter::new(MassaTime::from_millis(60_000));
+        let slot = Slot::new(0, 0);
+
+        let stats = counter.get_stats(slot, slot, slot, slot);
+        assert_eq!(stats.average_gas_per_block, 0);
+        assert_eq!(stats.module_cache_ram_hit_ratio, None);
+        assert_eq!(stats.module_cache_hd_hit_ratio, None);
+
+        counter.register_final_blocks(1);
+        counter.register_final_block_stats(FinalBlockStats {
+            applied_operations: 3,
+            failed_operations: 1,
+            gas_used: 1_000,
+        });
+        counter.register_final_blocks(1);
+        counter.register_final_block_stats(FinalBlockStats {
+            applied_operations: 1,
+            failed_operations: 0,
+            gas_used: 3_000,
+        });
+        counter.register_final_executed_async_messages(2);
+        counter.register_final_executed_async_messages(5);
+        counter.register_module_cache_stats(ModuleCacheStats {
+            hits: 3,
+            misses: 1,
+            hd_hits: 1,
+        });
+
+        let stats = counter.get_stats(slot, slot, slot, slot);
+        assert_eq!(stats.final_block_count, 2);
+        assert_eq!(stats.final_applied_operations_count, 4);
+        assert_eq!(stats.final_failed_operations_count, 1);
+        assert_eq!(stats.average_gas_per_block, 2_000);
+        assert_eq!(stats.final_executed_async_messages_count, 7);
+        assert_eq!(stats.module_cache_ram_hit_ratio, Some(0.75));
+        assert_eq!(stats.module_cache_hd_hit_ratio, Some(1.0));
+    }
+}
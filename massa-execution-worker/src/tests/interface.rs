@@ -3,20 +3,99 @@
 #[cfg(test)]
 mod tests {
     use hex_literal::hex;
+    use massa_execution_exports::ExecutionConfig;
+    use massa_hash::Hash;
     use massa_models::address::Address;
     use massa_sc_runtime::Interface;
+    use massa_signature::KeyPair;
     use std::str::FromStr;
 
     use crate::interface_impl::InterfaceImpl;
-    #[test]
-    fn test_hash_sha256() {
-        let interface = InterfaceImpl::new_default(
+
+    fn default_interface() -> InterfaceImpl {
+        InterfaceImpl::new_default(
             Address::from_str("AU12cMW9zRKFDS43Z2W88VCmdQFxmHjAo54XvuVV34UzJeXRLXW9M").unwrap(),
             None,
-        );
+        )
+    }
+
+    #[test]
+    fn test_hash_sha256() {
+        let interface = default_interface();
         let actual_hash = interface.hash_sha256(b"something").unwrap();
         let expected_hash =
             &hex!("3fc9b689459d738f8c88a3a48aa9e33542016b7a4052e001aaa536fca74813cb")[..];
         assert_eq!(actual_hash, expected_hash);
     }
+
+    #[test]
+    fn test_hash_blake3() {
+        let interface = default_interface();
+        let actual_hash = interface.hash_blake3(b"").unwrap();
+        let expected_hash =
+            &hex!("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262")[..];
+        assert_eq!(actual_hash, expected_hash);
+    }
+
+    #[test]
+    fn test_signature_verify() {
+        let interface = default_interface();
+        let keypair = KeyPair::generate(0).unwrap();
+        let public_key = keypair.get_public_key().to_string();
+        let data = b"message to sign";
+        let signature = keypair
+            .sign(&Hash::compute_from(data))
+            .unwrap()
+            .to_bs58_check();
+
+        assert!(interface
+            .signature_verify(data, &signature, &public_key)
+            .unwrap());
+        assert!(!interface
+            .signature_verify(b"another message", &signature, &public_key)
+            .unwrap());
+        // malformed signatures and keys fail the verification without erroring
+        assert!(!interface
+            .signature_verify(data, "invalid", &public_key)
+            .unwrap());
+        assert!(!interface
+            .signature_verify(data, &signature, "invalid")
+            .unwrap());
+    }
+
+    #[test]
+    fn test_address_from_public_key() {
+        let interface = default_interface();
+        let keypair = KeyPair::generate(0).unwrap();
+        let address = interface
+            .address_from_public_key(&keypair.get_public_key().to_string())
+            .unwrap();
+        assert_eq!(
+            address,
+            Address::from_public_key(&keypair.get_public_key()).to_string()
+        );
+        assert!(interface.address_from_public_key("invalid").is_err());
+    }
+
+    #[test]
+    fn test_send_message_max_gas() {
+        let interface = default_interface();
+        let max_gas = ExecutionConfig::default().max_gas_per_async_message;
+        let err = interface
+            .send_message(
+                "AS12mzL2UWroPV7zzHpwHFnF74op3pVEBPYEJSapRtdrr7XNS9RLT",
+                "receive",
+                (1, 0),
+                (2, 0),
+                max_gas + 1,
+                0,
+                0,
+                b"",
+                None,
+            )
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("exceeds the maximum gas of an asynchronous message"));
+    }
 }
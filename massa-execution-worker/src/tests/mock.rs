@@ -5,6 +5,7 @@ use massa_final_state::{FinalState, FinalStateConfig};
 use massa_hash::Hash;
 use massa_ledger_exports::{LedgerConfig, LedgerController, LedgerEntry, LedgerError};
 use massa_ledger_worker::FinalLedger;
+use massa_metrics::MassaMetrics;
 use massa_models::config::{ENDORSEMENT_COUNT, GENESIS_TIMESTAMP, T0};
 use massa_models::denunciation::Denunciation;
 use massa_models::{
@@ -84,6 +85,7 @@ pub fn get_sample_state(
         max_history_length: 10,
         max_new_elements: 100,
         thread_count: THREAD_COUNT,
+        block_cache_size: 0,
     };
     let db = Arc::new(RwLock::new(
         Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -95,6 +97,7 @@ pub fn get_sample_state(
     let cfg = FinalStateConfig {
         ledger_config,
         async_pool_config: default_config.async_pool_config,
+        deferred_calls_config: default_config.deferred_calls_config,
         pos_config: default_config.pos_config,
         executed_ops_config: default_config.executed_ops_config,
         executed_denunciations_config: default_config.executed_denunciations_config,
@@ -108,9 +111,24 @@ pub fn get_sample_state(
         max_denunciations_per_block_header: 0,
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
+        final_state_wal_path: None,
+        cycle_snapshot_path: None,
+        cycle_snapshot_retention: 0,
+        change_history_path: None,
+        change_history_length: 0,
+        change_history_page_max_bytes: 0,
     };
-    let (_, selector_controller) = start_selector_worker(SelectorConfig::default())
-        .expect("could not start selector controller");
+    let (_, selector_controller) = start_selector_worker(
+        SelectorConfig::default(),
+        MassaMetrics::new(
+            false,
+            "0.0.0.0:9898".parse().unwrap(),
+            THREAD_COUNT,
+            std::time::Duration::from_secs(5),
+        )
+        .0,
+    )
+    .expect("could not start selector controller");
     let mip_store = MipStore::try_from((
         [],
         MipStatsConfig {
@@ -179,6 +197,7 @@ pub fn create_block(
             operation_merkle_root,
             endorsements: vec![],
             denunciations,
+            reward_recipient: None,
         },
         BlockHeaderSerializer::new(),
         &creator_keypair,
@@ -14,6 +14,9 @@ mod scenarios_mandatories;
 #[cfg(all(not(feature = "gas_calibration"), not(feature = "benchmarking")))]
 mod tests_active_history;
 
+#[cfg(all(not(feature = "gas_calibration"), not(feature = "benchmarking")))]
+mod tests_speculative_ledger;
+
 mod interface;
 
 #[cfg(any(
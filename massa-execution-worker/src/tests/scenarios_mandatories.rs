@@ -9,12 +9,15 @@ mod tests {
     use massa_db_exports::DBBatch;
     use massa_execution_exports::{
         ExecutionBlockMetadata, ExecutionChannels, ExecutionConfig, ExecutionController,
-        ExecutionError, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+        ExecutionError, ExecutionQueryError, ExecutionQueryRequest, ExecutionQueryRequestItem,
+        ExecutionQueryResponseItem, ExecutionStackElement, ReadOnlyExecutionRequest,
+        ReadOnlyExecutionTarget,
     };
     use massa_hash::Hash;
     use massa_metrics::MassaMetrics;
     use massa_models::config::{
-        LEDGER_ENTRY_BASE_COST, LEDGER_ENTRY_DATASTORE_BASE_SIZE, MIP_STORE_STATS_BLOCK_CONSIDERED,
+        BLOCK_HEADER_REWARD_RECIPIENT_VERSION, LEDGER_ENTRY_BASE_COST,
+        LEDGER_ENTRY_DATASTORE_BASE_SIZE, MIP_STORE_STATS_BLOCK_CONSIDERED,
     };
     use massa_models::prehash::PreHashMap;
     use massa_models::test_exports::gen_endorsements_for_denunciation;
@@ -24,6 +27,8 @@ mod tests {
         slot::Slot,
     };
     use massa_models::{
+        block::{Block, BlockSerializer},
+        block_header::{BlockHeader, BlockHeaderSerializer},
         block_id::BlockId,
         datastore::Datastore,
         denunciation::Denunciation,
@@ -64,6 +69,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
@@ -100,6 +106,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
@@ -150,6 +157,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -179,6 +187,7 @@ mod tests {
                 target: ReadOnlyExecutionTarget::BytecodeExecution(
                     include_bytes!("./wasm/event_test.wasm").to_vec(),
                 ),
+                coins: None,
                 is_final: true,
             })
             .expect("readonly execution failed");
@@ -194,12 +203,70 @@ mod tests {
                 target: ReadOnlyExecutionTarget::BytecodeExecution(
                     include_bytes!("./wasm/event_test.wasm").to_vec(),
                 ),
+                coins: None,
                 is_final: false,
             })
             .expect("readonly execution failed");
 
         assert!(res.out.slot.period > 8);
 
+        // the simulated coins are credited to the callee, without being committed
+        let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let res = controller
+            .execute_readonly_request(ReadOnlyExecutionRequest {
+                max_gas: 1_000_000,
+                call_stack: vec![ExecutionStackElement {
+                    address,
+                    coins: Amount::zero(),
+                    owned_addresses: vec![address],
+                    operation_datastore: None,
+                }],
+                target: ReadOnlyExecutionTarget::BytecodeExecution(
+                    include_bytes!("./wasm/event_test.wasm").to_vec(),
+                ),
+                coins: Some(Amount::from_str("100").unwrap()),
+                is_final: false,
+            })
+            .expect("readonly execution failed");
+        assert!(res
+            .out
+            .state_changes
+            .ledger_changes
+            .0
+            .contains_key(&address));
+        assert_eq!(
+            controller.get_final_and_candidate_balance(&[address]),
+            vec![(None, None)]
+        );
+
+        // roll operations can be dry-run on behalf of the last element of the call stack
+        let roll_request = |coins: Option<Amount>| ReadOnlyExecutionRequest {
+            max_gas: 0,
+            call_stack: vec![ExecutionStackElement {
+                address,
+                coins: Amount::zero(),
+                owned_addresses: vec![address],
+                operation_datastore: None,
+            }],
+            target: ReadOnlyExecutionTarget::RollOperation(OperationType::RollBuy {
+                roll_count: 1,
+            }),
+            coins,
+            is_final: false,
+        };
+        assert!(matches!(
+            controller.execute_readonly_request(roll_request(None)),
+            Err(ExecutionError::RollBuyError(_))
+        ));
+        let res = controller
+            .execute_readonly_request(roll_request(Some(exec_cfg.roll_price)))
+            .expect("readonly roll buy failed");
+        assert_eq!(res.gas_cost, 0);
+        assert_eq!(
+            res.out.state_changes.pos_changes.roll_changes.get(&address),
+            Some(&1)
+        );
+
         manager.stop();
     }
 
@@ -272,6 +339,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -456,6 +524,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -625,6 +694,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // init the storage
@@ -750,6 +820,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -863,6 +934,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -994,6 +1066,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -1119,6 +1192,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -1303,6 +1377,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -1387,6 +1462,204 @@ mod tests {
         manager.stop();
     }
 
+    #[test]
+    #[serial]
+    pub fn state_view_pins_ledger() {
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: MassaTime::from_millis(100),
+            cursor_delay: MassaTime::from_millis(0),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+
+        // init the MIP store
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            warn_announced_version_ratio: Ratio::new_raw(30, 100),
+        };
+        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+
+        let slot_execution_output_sender = broadcast::channel(5000).0;
+
+        let channels = ExecutionChannels {
+            slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
+        };
+
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            mip_store,
+            channels,
+            Arc::new(RwLock::new(create_test_wallet(Some(PreHashMap::default())))),
+            MassaMetrics::new(
+                false,
+                "0.0.0.0:9898".parse().unwrap(),
+                32,
+                std::time::Duration::from_secs(5),
+            )
+            .0,
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the sender_keypair and recipient_address
+
+        let sender_keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+        let (recipient_address, _keypair) = get_random_address_full();
+
+        // open a view before the transaction is executed
+        let sender_balance = sample_state
+            .read()
+            .ledger
+            .get_balance(&sender_address)
+            .unwrap();
+        let view = controller.open_state_view().unwrap();
+
+        // create the operation
+        let operation = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        // create the block containing the transaction operation
+        storage.store_operations(vec![operation.clone()]);
+        let block = create_block(
+            KeyPair::generate(0).unwrap(),
+            vec![operation],
+            vec![],
+            Slot::new(1, 0),
+        )
+        .unwrap();
+        // store the block in storage
+        storage.store_block(block.clone());
+        // set our block as a final block so the transaction is processed
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_metadata: PreHashMap<BlockId, ExecutionBlockMetadata> = Default::default();
+        block_metadata.insert(
+            block.id,
+            ExecutionBlockMetadata {
+                same_thread_parent_creator: Some(get_random_address()),
+                storage: Some(storage),
+            },
+        );
+        controller.update_blockclique_status(
+            finalized_blocks,
+            Default::default(),
+            block_metadata.clone(),
+        );
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(sample_state
+            .read()
+            .ledger
+            .get_balance(&recipient_address)
+            .is_some());
+
+        // the view still sees the ledger before the transaction
+        let query = |view_id, requests| {
+            controller
+                .query_state(ExecutionQueryRequest { requests, view_id })
+                .responses
+        };
+        let responses = query(
+            Some(view.view_id),
+            vec![
+                ExecutionQueryRequestItem::AddressBalanceFinal(sender_address),
+                ExecutionQueryRequestItem::AddressBalanceCandidate(sender_address),
+                ExecutionQueryRequestItem::AddressExistsFinal(recipient_address),
+                ExecutionQueryRequestItem::AddressRollsFinal(sender_address),
+            ],
+        );
+        assert!(matches!(
+            responses[0],
+            Ok(ExecutionQueryResponseItem::Amount(balance)) if balance == sender_balance
+        ));
+        assert!(matches!(
+            responses[1],
+            Ok(ExecutionQueryResponseItem::Amount(balance)) if balance == sender_balance
+        ));
+        assert!(matches!(
+            responses[2],
+            Ok(ExecutionQueryResponseItem::Boolean(false))
+        ));
+        assert!(matches!(
+            responses[3],
+            Err(ExecutionQueryError::NotSupportedInStateView)
+        ));
+
+        // a closed view cannot be queried anymore
+        controller.close_state_view(view.view_id);
+        let responses = query(
+            Some(view.view_id),
+            vec![ExecutionQueryRequestItem::AddressBalanceFinal(
+                sender_address,
+            )],
+        );
+        assert!(matches!(
+            responses[0],
+            Err(ExecutionQueryError::StateViewNotFound(_))
+        ));
+        let responses = query(
+            None,
+            vec![ExecutionQueryRequestItem::AddressExistsFinal(
+                recipient_address,
+            )],
+        );
+        assert!(matches!(
+            responses[0],
+            Ok(ExecutionQueryResponseItem::Boolean(true))
+        ));
+
+        // the final ledger before the transaction can still be queried at the final slot of the view
+        let responses = controller
+            .query_state_at_slot(
+                view.final_cursor,
+                vec![
+                    ExecutionQueryRequestItem::AddressBalanceFinal(sender_address),
+                    ExecutionQueryRequestItem::AddressExistsFinal(recipient_address),
+                ],
+            )
+            .responses;
+        assert!(matches!(
+            responses[0],
+            Ok(ExecutionQueryResponseItem::Amount(balance)) if balance == sender_balance
+        ));
+        assert!(matches!(
+            responses[1],
+            Ok(ExecutionQueryResponseItem::Boolean(false))
+        ));
+        let responses = controller
+            .query_state_at_slot(
+                Slot::new(100, 0),
+                vec![ExecutionQueryRequestItem::AddressExistsFinal(
+                    recipient_address,
+                )],
+            )
+            .responses;
+        assert!(matches!(
+            responses[0],
+            Err(ExecutionQueryError::SlotNotInHistory(_))
+        ));
+        // stop the execution controller
+        manager.stop();
+    }
+
     #[test]
     #[serial]
     pub fn roll_buy() {
@@ -1413,6 +1686,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -1523,6 +1797,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -1699,6 +1974,132 @@ mod tests {
         manager.stop();
     }
 
+    #[test]
+    #[serial]
+    pub fn roll_sell_cooldown() {
+        // Try to sell 10 rolls (operation 1) then 1 roll (operation 2) in the same cycle
+        // with a cooldown of 1 cycle between sales: only the first sale is executed
+
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: MassaTime::from_millis(100),
+            cursor_delay: MassaTime::from_millis(0),
+            periods_per_cycle: 2,
+            thread_count: 2,
+            last_start_period: 2,
+            max_miss_ratio: Ratio::new(1, 1),
+            roll_sale_cooldown_cycles: 1,
+            ..Default::default()
+        };
+
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state(2).unwrap();
+
+        // init the MIP store
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            warn_announced_version_ratio: Ratio::new_raw(30, 100),
+        };
+        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+
+        let channels = ExecutionChannels {
+            slot_execution_output_sender: broadcast::channel(5000).0,
+            pos_event_sender: broadcast::channel(100).0,
+        };
+
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            mip_store,
+            channels,
+            Arc::new(RwLock::new(create_test_wallet(Some(PreHashMap::default())))),
+            MassaMetrics::new(
+                false,
+                "0.0.0.0:9898".parse().unwrap(),
+                32,
+                std::time::Duration::from_secs(5),
+            )
+            .0,
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        // generate the keypair and its corresponding address
+        let keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+        let address = Address::from_public_key(&keypair.get_public_key());
+
+        // get initial roll count
+        let roll_count_initial = sample_state.read().pos_state.get_rolls_for(&address);
+        let roll_sell_1 = 10;
+        let roll_sell_2 = 1;
+
+        // create the roll sell operations
+        let operations: Vec<_> = [roll_sell_1, roll_sell_2]
+            .into_iter()
+            .map(|roll_count| {
+                Operation::new_verifiable(
+                    Operation {
+                        fee: Amount::zero(),
+                        expire_period: 10,
+                        op: OperationType::RollSell { roll_count },
+                    },
+                    OperationSerializer::new(),
+                    &keypair,
+                )
+                .unwrap()
+            })
+            .collect();
+        storage.store_operations(operations.clone());
+        let block = create_block(
+            KeyPair::generate(0).unwrap(),
+            operations.clone(),
+            vec![],
+            Slot::new(3, 0),
+        )
+        .unwrap();
+        // store the block in storage
+        storage.store_block(block.clone());
+        // set the block as final so the sales are processed
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_metadata: PreHashMap<BlockId, ExecutionBlockMetadata> = Default::default();
+        block_metadata.insert(
+            block.id,
+            ExecutionBlockMetadata {
+                same_thread_parent_creator: Some(get_random_address()),
+                storage: Some(storage),
+            },
+        );
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_metadata);
+        std::thread::sleep(Duration::from_millis(1000));
+
+        // only the first sale was executed
+        assert_eq!(
+            sample_state.read().pos_state.get_rolls_for(&address),
+            roll_count_initial - roll_sell_1
+        );
+        let operation_ids: Vec<_> = operations.iter().map(|op| op.id).collect();
+        assert_eq!(
+            controller.get_ops_exec_status(&operation_ids),
+            vec![(Some(true), Some(true)), (Some(false), Some(false))]
+        );
+        // the sale is recorded in the final state
+        assert_eq!(
+            sample_state
+                .read()
+                .pos_state
+                .get_address_last_roll_sale(&address),
+            Some(Slot::new(3, 0))
+        );
+
+        // stop the execution controller
+        manager.stop();
+    }
+
     #[test]
     #[serial]
     pub fn roll_slash() {
@@ -1739,6 +2140,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -1912,6 +2314,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -2065,6 +2468,110 @@ mod tests {
         manager.stop();
     }
 
+    #[test]
+    #[serial]
+    fn block_reward_recipient() {
+        let exec_cfg = ExecutionConfig {
+            t0: MassaTime::from_millis(100),
+            cursor_delay: MassaTime::from_millis(0),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+
+        // init the MIP store
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            warn_announced_version_ratio: Ratio::new_raw(30, 100),
+        };
+        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+
+        let channels = ExecutionChannels {
+            slot_execution_output_sender: broadcast::channel(5000).0,
+            pos_event_sender: broadcast::channel(100).0,
+        };
+
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            mip_store,
+            channels,
+            Arc::new(RwLock::new(create_test_wallet(Some(PreHashMap::default())))),
+            MassaMetrics::new(
+                false,
+                "0.0.0.0:9898".parse().unwrap(),
+                32,
+                std::time::Duration::from_secs(5),
+            )
+            .0,
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+
+        // create an empty block redirecting its rewards to another address
+        let creator_keypair = KeyPair::generate(0).unwrap();
+        let creator = Address::from_public_key(&creator_keypair.get_public_key());
+        let recipient = get_random_address();
+        let header = BlockHeader::new_verifiable(
+            BlockHeader {
+                current_version: BLOCK_HEADER_REWARD_RECIPIENT_VERSION,
+                announced_version: None,
+                slot: Slot::new(1, 0),
+                parents: vec![],
+                operation_merkle_root: Hash::compute_from(&[]),
+                endorsements: vec![],
+                denunciations: vec![],
+                reward_recipient: Some(recipient),
+            },
+            BlockHeaderSerializer::new(),
+            &creator_keypair,
+        )
+        .unwrap();
+        let block = Block::new_verifiable(
+            Block {
+                header,
+                operations: vec![],
+            },
+            BlockSerializer::new(),
+            &creator_keypair,
+        )
+        .unwrap();
+        // store the block in storage
+        storage.store_block(block.clone());
+        // set our block as a final block
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_metadata: PreHashMap<BlockId, ExecutionBlockMetadata> = Default::default();
+        block_metadata.insert(
+            block.id,
+            ExecutionBlockMetadata {
+                same_thread_parent_creator: Some(get_random_address()),
+                storage: Some(storage),
+            },
+        );
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_metadata);
+        std::thread::sleep(Duration::from_millis(100));
+
+        // the block reward went to the recipient instead of the creator
+        let balances = controller.get_final_and_candidate_balance(&[recipient, creator]);
+        assert_eq!(
+            balances[0].1,
+            Some(
+                exec_cfg
+                    .block_reward
+                    .saturating_sub(exec_cfg.storage_costs_constants.ledger_entry_base_cost)
+            )
+        );
+        assert_eq!(balances[1].1, None);
+        // stop the execution controller
+        manager.stop();
+    }
+
     #[test]
     #[serial]
     fn sc_execution_error() {
@@ -2092,6 +2599,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -2192,6 +2700,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -2289,6 +2798,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -2386,6 +2896,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -2551,6 +3062,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         let (mut manager, controller) = start_execution_worker(
@@ -2678,6 +3190,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -2838,6 +3351,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
@@ -2945,6 +3459,7 @@ mod tests {
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: broadcast::channel(100).0,
         };
 
         // start the execution worker
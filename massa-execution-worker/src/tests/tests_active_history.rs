@@ -47,17 +47,23 @@ mod tests {
             state_changes: StateChanges {
                 ledger_changes: Default::default(),
                 async_pool_changes: Default::default(),
+                deferred_call_changes: Default::default(),
                 pos_changes: PoSChanges {
                     seed_bits: Default::default(),
                     roll_changes: Default::default(),
                     production_stats: Default::default(),
                     deferred_credits: credits,
+                    roll_sales: Default::default(),
                 },
                 executed_ops_changes: Default::default(),
                 executed_denunciations_changes: Default::default(),
                 execution_trail_hash_change: Default::default(),
             },
             events: Default::default(),
+            trace: Default::default(),
+            operation_receipts: Default::default(),
+            transfers: Default::default(),
+            executed_async_message_count: 0,
         };
 
         let active_history = ActiveHistory {
@@ -0,0 +1,111 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+#[cfg(test)]
+mod tests {
+    use crate::speculative_ledger::SpeculativeLedger;
+    use crate::tests::mock::get_sample_state;
+    use massa_execution_exports::{ExecutionConfig, ExecutionError};
+    use massa_models::address::Address;
+    use massa_models::bytecode::Bytecode;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_max_datastore_keys_per_address() {
+        let config = ExecutionConfig::default();
+        let (final_state, _tempfile, _tempdir) = get_sample_state(0).unwrap();
+        let mut ledger = SpeculativeLedger::new(
+            final_state,
+            Default::default(),
+            config.max_datastore_key_length,
+            config.max_bytecode_size,
+            config.max_datastore_value_size,
+            config.storage_costs_constants,
+        );
+        ledger.set_max_datastore_keys_per_address(Some(2));
+        // address of the initial ledger
+        let keypair =
+            KeyPair::from_str("S18r2i8oJJyhF7Kprx98zwxAc3W4szf7RKuVMX6JydZz8zSxHeC").unwrap();
+        let addr = Address::from_public_key(&keypair.get_public_key());
+
+        ledger
+            .set_data_entry(&addr, &addr, b"a".to_vec(), b"1".to_vec())
+            .unwrap();
+        ledger
+            .set_data_entry(&addr, &addr, b"b".to_vec(), b"2".to_vec())
+            .unwrap();
+        let snapshot = ledger.get_snapshot();
+
+        // a third key is refused, but existing keys can still be overwritten
+        assert!(ledger
+            .set_data_entry(&addr, &addr, b"c".to_vec(), b"3".to_vec())
+            .is_err());
+        ledger
+            .set_data_entry(&addr, &addr, b"a".to_vec(), b"4".to_vec())
+            .unwrap();
+
+        // deleting a key frees a slot
+        ledger.delete_data_entry(&addr, &addr, b"b").unwrap();
+        ledger
+            .set_data_entry(&addr, &addr, b"c".to_vec(), b"3".to_vec())
+            .unwrap();
+
+        // the count follows the snapshots
+        ledger.reset_to_snapshot(snapshot);
+        assert!(ledger
+            .set_data_entry(&addr, &addr, b"c".to_vec(), b"3".to_vec())
+            .is_err());
+        assert_eq!(
+            ledger.get_keys(&addr, &[]).unwrap().len(),
+            2,
+            "the refused key must not be written"
+        );
+
+        // the keys are not counted before the network version capping them
+        ledger.set_max_datastore_keys_per_address(None);
+        assert!(ledger
+            .set_data_entry(&addr, &addr, b"c".to_vec(), b"3".to_vec())
+            .unwrap());
+    }
+
+    #[test]
+    fn test_datastore_and_bytecode_size_limits() {
+        let (final_state, _tempfile, _tempdir) = get_sample_state(0).unwrap();
+        let config = ExecutionConfig::default();
+        let mut ledger = SpeculativeLedger::new(
+            final_state,
+            Default::default(),
+            4,
+            16,
+            8,
+            config.storage_costs_constants,
+        );
+        // address of the initial ledger
+        let keypair =
+            KeyPair::from_str("S18r2i8oJJyhF7Kprx98zwxAc3W4szf7RKuVMX6JydZz8zSxHeC").unwrap();
+        let addr = Address::from_public_key(&keypair.get_public_key());
+
+        assert!(matches!(
+            ledger.set_data_entry(&addr, &addr, vec![], b"1".to_vec()),
+            Err(ExecutionError::DatastoreKeyLength { length: 0, max: 4 })
+        ));
+        assert!(matches!(
+            ledger.set_data_entry(&addr, &addr, b"12345".to_vec(), b"1".to_vec()),
+            Err(ExecutionError::DatastoreKeyLength { length: 5, max: 4 })
+        ));
+        assert!(matches!(
+            ledger.set_data_entry(&addr, &addr, b"key".to_vec(), vec![0; 9]),
+            Err(ExecutionError::DatastoreValueTooLarge { size: 9, max: 8 })
+        ));
+        assert!(matches!(
+            ledger.set_bytecode(&addr, &addr, Bytecode(vec![0; 17])),
+            Err(ExecutionError::BytecodeTooLarge { size: 17, max: 16 })
+        ));
+
+        // entries at the limits are accepted and nothing else was written
+        ledger
+            .set_data_entry(&addr, &addr, b"1234".to_vec(), vec![0; 8])
+            .unwrap();
+        assert_eq!(ledger.get_keys(&addr, &[]).unwrap().len(), 1);
+    }
+}
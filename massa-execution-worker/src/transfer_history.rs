@@ -0,0 +1,152 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! History of the coin transfers of the final slots, indexed by address.
+//!
+//! Every coin transfer executed in a final slot (operation transfers and fees, transfers initiated by smart contracts,
+//! asynchronous messages, deferred calls and credits) is appended to the history of the addresses it debits or credits.
+//! Only the transfers of the last `max_transfer_history_slots` final slots are kept.
+
+use massa_execution_exports::TransferInfo;
+use massa_models::address::Address;
+use massa_models::prehash::{PreHashMap, PreHashSet};
+use massa_models::slot::Slot;
+use std::collections::hash_map::Entry;
+use std::collections::VecDeque;
+
+/// Coin transfers of the last final slots, indexed by address
+pub(crate) struct TransferHistory {
+    /// max number of final slots kept, 0 disables the history
+    max_slots: usize,
+    /// final slots in the history, oldest first, with the addresses debited or credited at each of them
+    slots: VecDeque<(Slot, PreHashSet<Address>)>,
+    /// transfers debiting or crediting each address, oldest first
+    transfers: PreHashMap<Address, VecDeque<TransferInfo>>,
+}
+
+impl TransferHistory {
+    /// Creates an empty history keeping the transfers of at most `max_slots` final slots
+    pub(crate) fn new(max_slots: usize) -> Self {
+        Self {
+            max_slots,
+            slots: Default::default(),
+            transfers: Default::default(),
+        }
+    }
+
+    /// Appends the transfers of a final slot, dropping the oldest slots if the history is full
+    pub(crate) fn extend(&mut self, slot: Slot, transfers: &[TransferInfo]) {
+        if self.max_slots == 0 {
+            return;
+        }
+        let mut addresses = PreHashSet::default();
+        for transfer in transfers {
+            let mut involved: Vec<Address> = transfer.from.into_iter().chain(transfer.to).collect();
+            involved.dedup();
+            for address in involved {
+                self.transfers
+                    .entry(address)
+                    .or_default()
+                    .push_back(transfer.clone());
+                addresses.insert(address);
+            }
+        }
+        self.slots.push_back((slot, addresses));
+
+        while self.slots.len() > self.max_slots {
+            let Some((pruned_slot, addresses)) = self.slots.pop_front() else {
+                break;
+            };
+            for address in addresses {
+                if let Entry::Occupied(mut entry) = self.transfers.entry(address) {
+                    let transfers = entry.get_mut();
+                    while transfers
+                        .front()
+                        .map_or(false, |transfer| transfer.slot <= pruned_slot)
+                    {
+                        transfers.pop_front();
+                    }
+                    if transfers.is_empty() {
+                        entry.remove();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets the kept transfers debiting or crediting an address, in execution order,
+    /// from slot `start` (included) to slot `end` (excluded)
+    pub(crate) fn get(
+        &self,
+        address: &Address,
+        start: Option<Slot>,
+        end: Option<Slot>,
+    ) -> Vec<TransferInfo> {
+        let Some(transfers) = self.transfers.get(address) else {
+            return Vec::new();
+        };
+        let first = start.map_or(0, |start| {
+            transfers.partition_point(|transfer| transfer.slot < start)
+        });
+        let last = end.map_or(transfers.len(), |end| {
+            transfers.partition_point(|transfer| transfer.slot < end)
+        });
+        transfers.range(first..last.max(first)).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::amount::Amount;
+    use massa_signature::KeyPair;
+
+    fn transfer(slot: Slot, from: Option<Address>, to: Option<Address>) -> TransferInfo {
+        TransferInfo {
+            slot,
+            index: 0,
+            from,
+            to,
+            amount: Amount::from_raw(1),
+            operation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_transfer_history() {
+        let alice = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let bob = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let slots: Vec<Slot> = (0..3).map(|period| Slot::new(period, 0)).collect();
+        let mut history = TransferHistory::new(2);
+
+        history.extend(
+            slots[0],
+            &[
+                transfer(slots[0], Some(alice), Some(bob)),
+                transfer(slots[0], Some(alice), None),
+            ],
+        );
+        history.extend(slots[1], &[transfer(slots[1], None, Some(bob))]);
+        assert_eq!(history.get(&alice, None, None).len(), 2);
+        assert_eq!(history.get(&bob, None, None).len(), 2);
+        assert_eq!(
+            history.get(&bob, Some(slots[1]), None),
+            vec![transfer(slots[1], None, Some(bob))]
+        );
+        assert_eq!(
+            history.get(&bob, None, Some(slots[1])),
+            vec![transfer(slots[0], Some(alice), Some(bob))]
+        );
+
+        // the oldest slot is dropped
+        history.extend(slots[2], &[]);
+        assert!(history.get(&alice, None, None).is_empty());
+        assert_eq!(history.get(&bob, None, None).len(), 1);
+
+        // a transfer of an address to itself is recorded once
+        history.extend(
+            Slot::new(3, 0),
+            &[transfer(Slot::new(3, 0), Some(alice), Some(alice))],
+        );
+        assert_eq!(history.get(&alice, None, None).len(), 1);
+    }
+}
@@ -147,7 +147,12 @@ impl ExecutionThread {
 
             // Compute when the next slot will be
             // This is useful to wait for the next speculative miss to append to active slots.
-            let wakeup_deadline = self.slot_sequencer.get_next_slot_deadline();
+            let Some(wakeup_deadline) = self.slot_sequencer.get_next_slot_deadline() else {
+                // The slot sequence is not initialized yet: there is nothing to do until consensus
+                // notifies the initial final blocks, so sleep until the next notification without deadline.
+                self.input_data.0.wait(&mut input_data_lock);
+                continue;
+            };
             let now = MassaTime::now().expect("could not get current time");
             if wakeup_deadline <= now {
                 // next slot is right now: the loop needs to iterate
@@ -272,9 +277,11 @@ pub fn start_execution_worker(
     ));
 
     // create a controller
+    let offchain_tasks = execution_state.read().offchain_tasks.clone();
     let controller = ExecutionControllerImpl {
         input_data: input_data.clone(),
         execution_state: execution_state.clone(),
+        offchain_tasks,
     };
 
     // launch the execution thread
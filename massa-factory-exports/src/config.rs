@@ -2,6 +2,7 @@
 
 //! This file defines the factory settings
 
+use massa_models::address::Address;
 use massa_time::MassaTime;
 
 /// Structure defining the settings of the factory
@@ -29,4 +30,8 @@ pub struct FactoryConfig {
     pub denunciation_expire_periods: u64,
     /// choose whether to stop production when zero connections on protocol
     pub stop_production_when_zero_connections: bool,
+    /// announce produced blocks to peers right after signing, before consensus validates them
+    pub optimistic_block_announce: bool,
+    /// address credited with the rewards and fees of the produced blocks instead of their creators, if any
+    pub block_reward_recipient: Option<Address>,
 }
@@ -10,4 +10,8 @@ pub trait FactoryManager {
     /// because it is not allowed to move out of `Box<dyn FactoryManager>`
     /// This will improve if the `unsized_fn_params` feature stabilizes enough to be safely usable.
     fn stop(&mut self);
+
+    /// Returns true if a worker thread exited before the component was stopped,
+    /// which happens when it panics
+    fn is_finished(&self) -> bool;
 }
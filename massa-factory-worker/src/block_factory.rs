@@ -6,6 +6,7 @@ use massa_models::{
     block::{Block, BlockSerializer},
     block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
     block_id::BlockId,
+    config::BLOCK_HEADER_REWARD_RECIPIENT_VERSION,
     endorsement::SecureShareEndorsement,
     operation::{compute_operations_hash, OperationIdSerializer},
     secure_share::SecureShareContent,
@@ -211,6 +212,12 @@ impl BlockFactoryWorker {
         // create header
         let current_version = self.mip_store.get_network_version_current();
         let announced_version = self.mip_store.get_network_version_to_announce();
+        // the reward recipient only exists in headers from the network version introducing it
+        let reward_recipient = if current_version >= BLOCK_HEADER_REWARD_RECIPIENT_VERSION {
+            self.cfg.block_reward_recipient
+        } else {
+            None
+        };
         let header: SecuredHeader = BlockHeader::new_verifiable::<BlockHeaderSerializer, BlockId>(
             BlockHeader {
                 current_version,
@@ -220,6 +227,7 @@ impl BlockFactoryWorker {
                 operation_merkle_root: compute_operations_hash(&op_ids, &self.op_id_serializer),
                 endorsements,
                 denunciations: self.channels.pool.get_block_denunciations(&slot),
+                reward_recipient,
             },
             BlockHeaderSerializer::new(), // TODO reuse self.block_header_serializer
             block_producer_keypair,
@@ -247,6 +255,37 @@ impl BlockFactoryWorker {
             block_id, slot, block_producer_addr
         );
 
+        // track the acknowledgements of the block by our peers, to announce it again to the slow ones.
+        // The retraction is signed now, as protocol does not hold the producer keys
+        let retraction_signature = block_producer_keypair
+            .sign(&block_id.get_retraction_hash())
+            .expect("error while signing block retraction");
+        if let Err(err) = self
+            .channels
+            .protocol
+            .produced_block(block_id, retraction_signature)
+        {
+            warn!(
+                "block factory could not notify protocol of produced block {}: {}",
+                block_id, err
+            );
+        }
+
+        // announce the block to peers without waiting for consensus to validate it.
+        // If consensus later finds it invalid, protocol retracts it to the peers it was announced to.
+        if self.cfg.optimistic_block_announce {
+            if let Err(err) = self
+                .channels
+                .protocol
+                .integrated_block(block_id, block_storage.clone())
+            {
+                warn!(
+                    "block factory could not optimistically announce block {}: {}",
+                    block_id, err
+                );
+            }
+        }
+
         // send full block to consensus
         self.channels
             .consensus
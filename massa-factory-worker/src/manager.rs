@@ -37,4 +37,12 @@ impl FactoryManager for FactoryManagerImpl {
         }
         info!("factory stopped");
     }
+
+    /// checks whether a factory thread exited
+    fn is_finished(&self) -> bool {
+        [&self.block_worker, &self.endorsement_worker]
+            .into_iter()
+            .flatten()
+            .any(|(_, join_handle)| join_handle.is_finished())
+    }
 }
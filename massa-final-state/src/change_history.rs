@@ -0,0 +1,384 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines the history of the final state changes kept on disk.
+//!
+//! The database only keeps the changes of its latest slots in memory, so a bootstrap client whose cursor
+//! falls behind them has to restart its bootstrap from scratch. The changes of each final slot are therefore
+//! also written to a file of the history directory, keeping only the latest slots, and can be retrieved
+//! page by page from a cursor slot until the client is back within the changes kept in memory.
+//!
+//! The slots of the history and the size of their changes are indexed in memory: the directory is only listed
+//! when the history is opened, and the pages are read from a clone of the history without locking the final state.
+
+use crate::error::FinalStateError;
+use massa_db_exports::DBBatch;
+use massa_models::slot::Slot;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Write};
+use std::ops::Bound::{Excluded, Unbounded};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Tag identifying the format of the history files
+const HISTORY_MAGIC: &[u8] = b"MASSA_CHANGE_HISTORY_V1";
+/// Extension of the history files
+const HISTORY_EXTENSION: &str = "changes";
+
+/// Changes of the final state following a cursor slot
+#[derive(Debug, Clone)]
+pub struct ChangeHistoryPage {
+    /// slot at the output of which the changes put the state, cursor of the next page
+    pub slot: Slot,
+    /// changes of the state column
+    pub state_changes: DBBatch,
+    /// changes of the versioning column
+    pub versioning_changes: DBBatch,
+}
+
+/// Directory of the changes of the latest final slots.
+/// The clones of a history share its index.
+#[derive(Debug, Clone)]
+pub struct FinalChangeHistory {
+    /// directory of the history files
+    dir: PathBuf,
+    /// number of slots kept, 0 disables the writing of the history
+    length: usize,
+    /// size in bytes of the changes after which a page stops including further slots
+    page_max_bytes: u64,
+    /// thread count
+    thread_count: u8,
+    /// size in bytes of the history file of each slot
+    slots: Arc<RwLock<BTreeMap<Slot, u64>>>,
+}
+
+fn io_error(context: &str, path: &Path, err: std::io::Error) -> FinalStateError {
+    FinalStateError::ChangeHistoryError(format!(
+        "could not {} {}: {}",
+        context,
+        path.display(),
+        err
+    ))
+}
+
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    writer.write_all(bytes)
+}
+
+fn read_bytes(reader: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let mut bytes = vec![0u8; u32::from_be_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Writes the changes of a column, ended by a 0 tag
+fn write_changes(writer: &mut impl Write, changes: &DBBatch) -> std::io::Result<()> {
+    for (key, value) in changes.iter() {
+        match value {
+            Some(value) => {
+                writer.write_all(&[1])?;
+                write_bytes(writer, key)?;
+                write_bytes(writer, value)?;
+            }
+            None => {
+                writer.write_all(&[2])?;
+                write_bytes(writer, key)?;
+            }
+        }
+    }
+    writer.write_all(&[0])
+}
+
+/// Reads the changes of a column
+fn read_changes(reader: &mut impl Read) -> std::io::Result<DBBatch> {
+    let mut changes = DBBatch::new();
+    loop {
+        let mut tag = [0u8; 1];
+        reader.read_exact(&mut tag)?;
+        match tag[0] {
+            0 => return Ok(changes),
+            1 => {
+                let key = read_bytes(reader)?;
+                changes.insert(key, Some(read_bytes(reader)?));
+            }
+            2 => {
+                changes.insert(read_bytes(reader)?, None);
+            }
+            tag => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid change tag {}", tag),
+                ))
+            }
+        }
+    }
+}
+
+impl FinalChangeHistory {
+    /// Opens the history of the given directory, keeping the changes of the `length` latest slots
+    /// and serving pages of at most `page_max_bytes` bytes, unless a single slot is larger.
+    /// The files already in the directory are indexed.
+    pub fn new(
+        dir: &Path,
+        length: usize,
+        page_max_bytes: u64,
+        thread_count: u8,
+    ) -> Result<Self, FinalStateError> {
+        let history = FinalChangeHistory {
+            dir: dir.to_path_buf(),
+            length,
+            page_max_bytes,
+            thread_count,
+            slots: Default::default(),
+        };
+        *history.slots.write() = history.list()?;
+        Ok(history)
+    }
+
+    /// Writes the changes of a final slot, then removes the oldest slots.
+    /// The history is cleared first if the slot does not come just after its latest slot, so that it never has gaps.
+    /// The changes are written to a temporary file which is then renamed, so that they are never partially written.
+    pub fn write(
+        &self,
+        slot: Slot,
+        state_changes: &DBBatch,
+        versioning_changes: &DBBatch,
+    ) -> Result<(), FinalStateError> {
+        if self.length == 0 {
+            return Ok(());
+        }
+        let mut slots = self.slots.write();
+
+        if let Some((latest, _)) = slots.last_key_value() {
+            if latest.get_next_slot(self.thread_count).ok() != Some(slot) {
+                for old_slot in std::mem::take(&mut *slots).into_keys() {
+                    let path = self.get_path(old_slot);
+                    fs::remove_file(&path).map_err(|err| io_error("remove", &path, err))?;
+                }
+            }
+        }
+        if slots.is_empty() {
+            fs::create_dir_all(&self.dir).map_err(|err| io_error("create", &self.dir, err))?;
+        }
+
+        let mut bytes = HISTORY_MAGIC.to_vec();
+        write_changes(&mut bytes, state_changes)
+            .and_then(|_| write_changes(&mut bytes, versioning_changes))
+            .map_err(|err| {
+                FinalStateError::ChangeHistoryError(format!(
+                    "could not serialize the changes of slot {}: {}",
+                    slot, err
+                ))
+            })?;
+        let path = self.get_path(slot);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, &bytes).map_err(|err| io_error("write", &tmp_path, err))?;
+        fs::rename(&tmp_path, &path).map_err(|err| io_error("rename", &tmp_path, err))?;
+        slots.insert(slot, bytes.len() as u64);
+
+        // remove the oldest slots
+        while slots.len() > self.length {
+            if let Some((oldest, _)) = slots.pop_first() {
+                let oldest_path = self.get_path(oldest);
+                fs::remove_file(&oldest_path)
+                    .map_err(|err| io_error("remove", &oldest_path, err))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the changes of the slots following the `cursor` slot, merged slot after slot until
+    /// the next slot would take the page above its maximum size or the latest slot of the history is reached.
+    /// A page always holds the slot following the cursor, if any.
+    ///
+    /// Fails if the cursor slot is no longer in the history, as changes following it could be missing.
+    pub fn get_page(&self, cursor: Slot) -> Result<ChangeHistoryPage, FinalStateError> {
+        // only the index is locked, the files are read afterwards
+        let page_slots: Vec<Slot> = {
+            let slots = self.slots.read();
+            if !slots.contains_key(&cursor) {
+                return Err(FinalStateError::ChangeHistoryError(format!(
+                    "slot {} is not in the change history",
+                    cursor
+                )));
+            }
+            let mut page_bytes: u64 = 0;
+            slots
+                .range((Excluded(cursor), Unbounded))
+                .take_while(|(_, bytes)| {
+                    let full =
+                        page_bytes > 0 && page_bytes.saturating_add(**bytes) > self.page_max_bytes;
+                    page_bytes = page_bytes.saturating_add(**bytes);
+                    !full
+                })
+                .map(|(slot, _)| *slot)
+                .collect()
+        };
+
+        let mut page = ChangeHistoryPage {
+            slot: cursor,
+            state_changes: DBBatch::new(),
+            versioning_changes: DBBatch::new(),
+        };
+        for slot in page_slots {
+            let (state_changes, versioning_changes) = self.read(&self.get_path(slot))?;
+            page.state_changes.extend(state_changes);
+            page.versioning_changes.extend(versioning_changes);
+            page.slot = slot;
+        }
+        Ok(page)
+    }
+
+    /// Reads the changes of the state and versioning columns from a history file
+    fn read(&self, path: &Path) -> Result<(DBBatch, DBBatch), FinalStateError> {
+        let file = File::open(path).map_err(|err| io_error("open", path, err))?;
+        let read = || -> std::io::Result<(DBBatch, DBBatch)> {
+            let mut reader = BufReader::new(file);
+            let mut magic = vec![0u8; HISTORY_MAGIC.len()];
+            reader.read_exact(&mut magic)?;
+            if magic != HISTORY_MAGIC {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "unknown format",
+                ));
+            }
+            Ok((read_changes(&mut reader)?, read_changes(&mut reader)?))
+        };
+        read().map_err(|err| io_error("read", path, err))
+    }
+
+    /// Path of the changes of a given slot
+    fn get_path(&self, slot: Slot) -> PathBuf {
+        self.dir.join(format!(
+            "changes_{}_{}.{}",
+            slot.period, slot.thread, HISTORY_EXTENSION
+        ))
+    }
+
+    /// Lists the files of the directory, with the size of the changes of each slot
+    fn list(&self) -> Result<BTreeMap<Slot, u64>, FinalStateError> {
+        let mut slots = BTreeMap::new();
+        if !self.dir.exists() {
+            return Ok(slots);
+        }
+        let entries = fs::read_dir(&self.dir).map_err(|err| io_error("read", &self.dir, err))?;
+        for entry in entries {
+            let entry = entry.map_err(|err| io_error("read", &self.dir, err))?;
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(HISTORY_EXTENSION) {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let parts: Vec<&str> = name.split('_').collect();
+            if let ["changes", period, thread] = parts.as_slice() {
+                if let (Ok(period), Ok(thread)) = (period.parse(), thread.parse()) {
+                    let bytes = entry
+                        .metadata()
+                        .map_err(|err| io_error("read", &path, err))?
+                        .len();
+                    slots.insert(Slot::new(period, thread), bytes);
+                }
+            }
+        }
+        Ok(slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn changes(period: u64) -> (DBBatch, DBBatch) {
+        let mut state_changes = DBBatch::new();
+        state_changes.insert(period.to_be_bytes().to_vec(), Some(vec![period as u8; 10]));
+        state_changes.insert(b"deleted".to_vec(), None);
+        let mut versioning_changes = DBBatch::new();
+        versioning_changes.insert(b"mip".to_vec(), Some(period.to_be_bytes().to_vec()));
+        (state_changes, versioning_changes)
+    }
+
+    fn indexed_slots(history: &FinalChangeHistory) -> Vec<Slot> {
+        history.slots.read().keys().copied().collect()
+    }
+
+    #[test]
+    fn test_change_history() {
+        let history_dir = TempDir::new().expect("cannot create temp directory");
+        let history = FinalChangeHistory::new(history_dir.path(), 4, 1_000, 1).unwrap();
+
+        // write 5 slots: only the 4 latest ones are kept
+        for period in 1..=5u64 {
+            let (state_changes, versioning_changes) = changes(period);
+            history
+                .write(Slot::new(period, 0), &state_changes, &versioning_changes)
+                .unwrap();
+        }
+        assert_eq!(
+            indexed_slots(&history),
+            vec![
+                Slot::new(2, 0),
+                Slot::new(3, 0),
+                Slot::new(4, 0),
+                Slot::new(5, 0)
+            ]
+        );
+        assert_eq!(history.list().unwrap(), *history.slots.read());
+        assert!(history.get_page(Slot::new(1, 0)).is_err());
+
+        // a large page holds the changes of all the following slots
+        let page = history.get_page(Slot::new(3, 0)).unwrap();
+        assert_eq!(page.slot, Slot::new(5, 0));
+        assert_eq!(page.state_changes.len(), 3);
+        assert_eq!(
+            page.state_changes.get(&5u64.to_be_bytes().to_vec()),
+            Some(&Some(vec![5u8; 10]))
+        );
+        assert_eq!(page.state_changes.get(&b"deleted".to_vec()), Some(&None));
+        assert_eq!(
+            page.versioning_changes.get(&b"mip".to_vec()),
+            Some(&Some(5u64.to_be_bytes().to_vec()))
+        );
+
+        // a history reopened from the same directory indexes its files.
+        // Its small pages are retrieved slot after slot from their cursor
+        let small_pages = FinalChangeHistory::new(history_dir.path(), 4, 1, 1).unwrap();
+        assert_eq!(indexed_slots(&small_pages), indexed_slots(&history));
+        let page = small_pages.get_page(Slot::new(3, 0)).unwrap();
+        assert_eq!(page.slot, Slot::new(4, 0));
+        let page = small_pages.get_page(page.slot).unwrap();
+        assert_eq!(page.slot, Slot::new(5, 0));
+        let page = small_pages.get_page(page.slot).unwrap();
+        assert_eq!(page.slot, Slot::new(5, 0));
+        assert!(page.state_changes.is_empty());
+
+        // a page stops before the slot that would take it above its maximum size
+        let slot_bytes = history.slots.read()[&Slot::new(3, 0)];
+        let two_slots_pages =
+            FinalChangeHistory::new(history_dir.path(), 4, 2 * slot_bytes, 1).unwrap();
+        let page = two_slots_pages.get_page(Slot::new(2, 0)).unwrap();
+        assert_eq!(page.slot, Slot::new(4, 0));
+
+        // a gap clears the history
+        let (state_changes, versioning_changes) = changes(7);
+        history
+            .write(Slot::new(7, 0), &state_changes, &versioning_changes)
+            .unwrap();
+        assert_eq!(indexed_slots(&history), vec![Slot::new(7, 0)]);
+        assert_eq!(history.list().unwrap().len(), 1);
+
+        // a disabled history writes nothing
+        let disabled_dir = history_dir.path().join("disabled");
+        let disabled = FinalChangeHistory::new(&disabled_dir, 0, 1_000, 1).unwrap();
+        disabled
+            .write(Slot::new(1, 0), &state_changes, &versioning_changes)
+            .unwrap();
+        assert!(!disabled_dir.exists());
+    }
+}
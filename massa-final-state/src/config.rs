@@ -3,6 +3,7 @@
 //! This file defines a configuration structure containing all settings for final state management
 
 use massa_async_pool::AsyncPoolConfig;
+use massa_deferred_calls::DeferredCallsConfig;
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
 use massa_ledger_exports::LedgerConfig;
 use massa_pos_exports::PoSConfig;
@@ -16,6 +17,8 @@ pub struct FinalStateConfig {
     pub ledger_config: LedgerConfig,
     /// asynchronous pool configuration
     pub async_pool_config: AsyncPoolConfig,
+    /// deferred call registry configuration
+    pub deferred_calls_config: DeferredCallsConfig,
     /// proof-of-stake configuration
     pub pos_config: PoSConfig,
     /// executed operations configuration
@@ -43,4 +46,17 @@ pub struct FinalStateConfig {
     pub t0: MassaTime,
     /// TODO
     pub genesis_timestamp: MassaTime,
+    /// path of the write-ahead log tracking the final slot being applied (disabled if None)
+    pub final_state_wal_path: Option<PathBuf>,
+    /// directory of the snapshots of the final state written at the end of each cycle (disabled if None)
+    pub cycle_snapshot_path: Option<PathBuf>,
+    /// number of cycle snapshots kept on disk, 0 disables their writing.
+    /// A snapshot is written while finalizing the last slot of a cycle, pausing the application of final slots
+    pub cycle_snapshot_retention: usize,
+    /// directory of the history of the final state changes kept on disk for lagging bootstrap clients (disabled if None)
+    pub change_history_path: Option<PathBuf>,
+    /// number of final slots whose changes are kept in the history on disk, 0 disables its writing
+    pub change_history_length: usize,
+    /// size in bytes above which a page of the history served to a lagging bootstrap client stops including further slots
+    pub change_history_page_max_bytes: u64,
 }
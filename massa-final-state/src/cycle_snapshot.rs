@@ -0,0 +1,320 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines the snapshots of the final state written at the end of each cycle.
+//!
+//! A snapshot holds all the entries of the state and versioning columns of the database
+//! (ledger, asynchronous pool, proof-of-stake state, executed operations and denunciations, MIP store)
+//! at the output of the last slot of a cycle, compressed with zstd.
+//! Only the latest snapshots are kept on disk.
+//!
+//! A restarting node loads its latest snapshot and then only needs the final state changes since the slot
+//! of the snapshot from a bootstrap server, as long as those changes are still in the changes history of the server.
+
+use crate::error::FinalStateError;
+use crate::state_snapshot::FinalStateSnapshot;
+use massa_db_exports::{
+    MassaIteratorMode, ShareableMassaDBController, StreamBatch, STATE_CF, VERSIONING_CF,
+};
+use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
+use massa_models::slot::Slot;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Tag identifying the format of the snapshot files
+const SNAPSHOT_MAGIC: &[u8] = b"MASSA_CYCLE_SNAPSHOT_V1";
+/// Extension of the snapshot files
+const SNAPSHOT_EXTENSION: &str = "zst";
+/// zstd compression level of the snapshots
+const SNAPSHOT_COMPRESSION_LEVEL: i32 = 3;
+/// Number of entries written to the database at once when loading a snapshot
+const SNAPSHOT_LOAD_CHUNK_SIZE: usize = 10_000;
+
+/// Directory of the snapshots of the final state written at the end of each cycle
+#[derive(Debug, Clone)]
+pub struct CycleSnapshotStore {
+    /// directory of the snapshot files
+    dir: PathBuf,
+    /// number of snapshots kept, 0 disables the writing of snapshots
+    retention: usize,
+}
+
+fn io_error(context: &str, path: &Path, err: std::io::Error) -> FinalStateError {
+    FinalStateError::SnapshotError(format!("could not {} {}: {}", context, path.display(), err))
+}
+
+fn write_entry(writer: &mut impl Write, key: &[u8], value: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&[1])?;
+    writer.write_all(&(key.len() as u32).to_be_bytes())?;
+    writer.write_all(key)?;
+    writer.write_all(&(value.len() as u32).to_be_bytes())?;
+    writer.write_all(value)
+}
+
+fn read_bytes(reader: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let mut bytes = vec![0u8; u32::from_be_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads the next entry of a column, None at the end of the column
+fn read_entry(reader: &mut impl Read) -> std::io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    let mut tag = [0u8; 1];
+    reader.read_exact(&mut tag)?;
+    match tag[0] {
+        0 => Ok(None),
+        1 => Ok(Some((read_bytes(reader)?, read_bytes(reader)?))),
+        tag => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid entry tag {}", tag),
+        )),
+    }
+}
+
+impl CycleSnapshotStore {
+    /// Creates a store of snapshots in the given directory, keeping the `retention` latest ones
+    pub fn new(dir: &Path, retention: usize) -> Self {
+        CycleSnapshotStore {
+            dir: dir.to_path_buf(),
+            retention,
+        }
+    }
+
+    /// Writes a consistent snapshot of the final state to a file, then removes the oldest snapshots.
+    /// The snapshot is written to a temporary file which is then renamed, so that it is never partially written.
+    ///
+    /// # Returns
+    /// The path of the snapshot, None if the writing of snapshots is disabled
+    pub fn write(&self, snapshot: &FinalStateSnapshot) -> Result<Option<PathBuf>, FinalStateError> {
+        if self.retention == 0 {
+            return Ok(None);
+        }
+        fs::create_dir_all(&self.dir).map_err(|err| io_error("create", &self.dir, err))?;
+
+        let slot = snapshot.slot();
+        let path = self.get_path(slot);
+        let tmp_path = path.with_extension("tmp");
+        let file = File::create(&tmp_path).map_err(|err| io_error("create", &tmp_path, err))?;
+        let write = || -> std::io::Result<File> {
+            let mut encoder = zstd::Encoder::new(BufWriter::new(file), SNAPSHOT_COMPRESSION_LEVEL)?;
+            encoder.write_all(SNAPSHOT_MAGIC)?;
+            encoder.write_all(&slot.period.to_be_bytes())?;
+            encoder.write_all(&[slot.thread])?;
+            encoder.write_all(&snapshot.state_hash().to_bytes())?;
+            for column in [STATE_CF, VERSIONING_CF] {
+                for (key, value) in snapshot.iterator_cf(column, MassaIteratorMode::Start) {
+                    write_entry(&mut encoder, &key, &value)?;
+                }
+                encoder.write_all(&[0])?;
+            }
+            let file = encoder
+                .finish()?
+                .into_inner()
+                .map_err(|err| err.into_error())?;
+            file.sync_all()?;
+            Ok(file)
+        };
+        write().map_err(|err| io_error("write", &tmp_path, err))?;
+        fs::rename(&tmp_path, &path).map_err(|err| io_error("rename", &tmp_path, err))?;
+
+        // remove the oldest snapshots
+        let mut snapshots = self.list()?;
+        while snapshots.len() > self.retention {
+            if let Some((_, oldest_path)) = snapshots.pop_first() {
+                fs::remove_file(&oldest_path)
+                    .map_err(|err| io_error("remove", &oldest_path, err))?;
+            }
+        }
+        Ok(Some(path))
+    }
+
+    /// Gets the slot and path of the latest snapshot, if any
+    pub fn get_latest(&self) -> Result<Option<(Slot, PathBuf)>, FinalStateError> {
+        Ok(self.list()?.pop_last())
+    }
+
+    /// Loads a snapshot into an empty database, attaching it at the slot of the snapshot.
+    ///
+    /// # Returns
+    /// The slot of the snapshot
+    pub fn load(
+        &self,
+        path: &Path,
+        db: &ShareableMassaDBController,
+    ) -> Result<Slot, FinalStateError> {
+        let file = File::open(path).map_err(|err| io_error("open", path, err))?;
+        let invalid = |msg: String| {
+            FinalStateError::SnapshotError(format!("invalid snapshot {}: {}", path.display(), msg))
+        };
+        let mut decoder = zstd::Decoder::new(file).map_err(|err| io_error("read", path, err))?;
+        let mut header = vec![0u8; SNAPSHOT_MAGIC.len() + 9 + HASH_XOF_SIZE_BYTES];
+        decoder
+            .read_exact(&mut header)
+            .map_err(|err| io_error("read", path, err))?;
+        if &header[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+            return Err(invalid(String::from("unknown format")));
+        }
+        let header = &header[SNAPSHOT_MAGIC.len()..];
+        let slot = Slot::new(
+            u64::from_be_bytes(header[0..8].try_into().expect("invalid period slice")),
+            header[8],
+        );
+        let expected_hash = HashXof::<HASH_XOF_SIZE_BYTES>::from_bytes(
+            header[9..].try_into().expect("invalid hash slice"),
+        );
+
+        for column in [STATE_CF, VERSIONING_CF] {
+            loop {
+                let mut chunk = BTreeMap::new();
+                let mut finished = false;
+                while chunk.len() < SNAPSHOT_LOAD_CHUNK_SIZE {
+                    match read_entry(&mut decoder).map_err(|err| io_error("read", path, err))? {
+                        Some((key, value)) => {
+                            chunk.insert(key, value);
+                        }
+                        None => {
+                            finished = true;
+                            break;
+                        }
+                    }
+                }
+                let batch = |new_elements| StreamBatch {
+                    new_elements,
+                    updates_on_previous_elements: BTreeMap::new(),
+                    change_id: slot,
+                };
+                let (state_batch, versioning_batch) = if column == STATE_CF {
+                    (batch(chunk), batch(BTreeMap::new()))
+                } else {
+                    (batch(BTreeMap::new()), batch(chunk))
+                };
+                db.write()
+                    .write_batch_bootstrap_client(state_batch, versioning_batch)
+                    .map_err(|err| invalid(err.to_string()))?;
+                if finished {
+                    break;
+                }
+            }
+        }
+
+        let hash = db.read().get_xof_db_hash();
+        if hash != expected_hash {
+            return Err(invalid(format!(
+                "state hash {} does not match the expected hash {}",
+                hash, expected_hash
+            )));
+        }
+        Ok(slot)
+    }
+
+    /// Path of the snapshot of a given slot
+    fn get_path(&self, slot: Slot) -> PathBuf {
+        self.dir.join(format!(
+            "snapshot_{}_{}.{}",
+            slot.period, slot.thread, SNAPSHOT_EXTENSION
+        ))
+    }
+
+    /// Lists the snapshots of the directory by slot
+    fn list(&self) -> Result<BTreeMap<Slot, PathBuf>, FinalStateError> {
+        let mut snapshots = BTreeMap::new();
+        if !self.dir.exists() {
+            return Ok(snapshots);
+        }
+        let entries = fs::read_dir(&self.dir).map_err(|err| io_error("read", &self.dir, err))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|err| io_error("read", &self.dir, err))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(SNAPSHOT_EXTENSION) {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let parts: Vec<&str> = name.split('_').collect();
+            if let ["snapshot", period, thread] = parts.as_slice() {
+                if let (Ok(period), Ok(thread)) = (period.parse(), thread.parse()) {
+                    snapshots.insert(Slot::new(period, thread), path);
+                }
+            }
+        }
+        Ok(snapshots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_db_exports::{DBBatch, MassaDBConfig, MassaDBController};
+    use massa_db_worker::MassaDB;
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn create_db(temp_dir: &TempDir) -> ShareableMassaDBController {
+        let db_config = MassaDBConfig {
+            path: temp_dir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 2,
+            block_cache_size: 0,
+        };
+        Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ))
+    }
+
+    #[test]
+    fn test_cycle_snapshot() {
+        let snapshot_dir = TempDir::new().expect("cannot create temp directory");
+        let store = CycleSnapshotStore::new(snapshot_dir.path(), 2);
+        let db_dir = TempDir::new().expect("cannot create temp directory");
+        let db = create_db(&db_dir);
+
+        assert!(store.get_latest().unwrap().is_none());
+
+        // write 3 snapshots: only the 2 latest ones are kept
+        for period in 1..=3u64 {
+            let mut batch = DBBatch::new();
+            batch.insert(period.to_be_bytes().to_vec(), Some(vec![period as u8; 100]));
+            let mut versioning_batch = DBBatch::new();
+            versioning_batch.insert(b"mip".to_vec(), Some(period.to_be_bytes().to_vec()));
+            db.write()
+                .write_batch(batch, versioning_batch, Some(Slot::new(period, 1)));
+            store.write(&FinalStateSnapshot::new(&db).unwrap()).unwrap();
+        }
+        let (slot, path) = store.get_latest().unwrap().unwrap();
+        assert_eq!(slot, Slot::new(3, 1));
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        // the latest snapshot restores the database in another directory
+        let loaded_dir = TempDir::new().expect("cannot create temp directory");
+        let loaded_db = create_db(&loaded_dir);
+        assert_eq!(store.load(&path, &loaded_db).unwrap(), slot);
+        let loaded = loaded_db.read();
+        assert_eq!(loaded.get_change_id().unwrap(), slot);
+        assert_eq!(loaded.get_xof_db_hash(), db.read().get_xof_db_hash());
+        assert_eq!(
+            loaded
+                .get_cf(STATE_CF, 2u64.to_be_bytes().to_vec())
+                .unwrap(),
+            Some(vec![2u8; 100])
+        );
+        assert_eq!(
+            loaded.get_cf(VERSIONING_CF, b"mip".to_vec()).unwrap(),
+            Some(3u64.to_be_bytes().to_vec())
+        );
+
+        // a disabled store writes nothing
+        let disabled = CycleSnapshotStore::new(&snapshot_dir.path().join("disabled"), 0);
+        assert!(disabled
+            .write(&FinalStateSnapshot::new(&db).unwrap())
+            .unwrap()
+            .is_none());
+        assert!(disabled.get_latest().unwrap().is_none());
+    }
+}
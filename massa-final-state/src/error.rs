@@ -19,6 +19,10 @@ pub enum FinalStateError {
     PosError(String),
     /// Snapshot error: {0}
     SnapshotError(String),
+    /// Final slot write-ahead log error: {0}
+    WalError(String),
+    /// Change history error: {0}
+    ChangeHistoryError(String),
     /// ExtendFromDbError
     ExtendFromDbError(#[from] ExtendFromDbError),
     /// IsConsistentWithShutdownPeriodError
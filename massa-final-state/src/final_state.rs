@@ -5,17 +5,28 @@
 //! the output of a given final slot (the latest executed final slot),
 //! and need to be bootstrapped by nodes joining the network.
 
-use crate::{config::FinalStateConfig, error::FinalStateError, state_changes::StateChanges};
+use crate::{
+    change_history::FinalChangeHistory,
+    config::FinalStateConfig,
+    cycle_snapshot::CycleSnapshotStore,
+    error::FinalStateError,
+    state_changes::StateChanges,
+    state_snapshot::FinalStateSnapshot,
+    wal::{FinalSlotWal, FinalSlotWalStep},
+};
 
 use massa_async_pool::AsyncPool;
 use massa_db_exports::EXECUTION_TRAIL_HASH_PREFIX;
 use massa_db_exports::{
     DBBatch, MassaIteratorMode, ShareableMassaDBController, ASYNC_POOL_PREFIX,
-    CHANGE_ID_DESER_ERROR, CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX,
-    EXECUTED_DENUNCIATIONS_PREFIX, EXECUTED_OPS_PREFIX, LEDGER_PREFIX, MIP_STORE_PREFIX, STATE_CF,
+    CHANGE_ID_DESER_ERROR, CYCLE_HISTORY_PREFIX, DEFERRED_CALLS_PREFIX, DEFERRED_CREDITS_PREFIX,
+    EXECUTED_DENUNCIATIONS_PREFIX, EXECUTED_OPS_FILTER_PREFIX, EXECUTED_OPS_PREFIX, LEDGER_PREFIX,
+    MIP_STORE_PREFIX, STATE_CF, STATE_HASH_INITIAL_BYTES,
 };
+use massa_deferred_calls::DeferredCallRegistry;
 use massa_executed_ops::ExecutedDenunciations;
 use massa_executed_ops::ExecutedOps;
+use massa_hash::HashXof;
 use massa_ledger_exports::LedgerController;
 use massa_ledger_exports::SetOrKeep;
 use massa_models::slot::Slot;
@@ -24,10 +35,10 @@ use massa_versioning::versioning::MipStore;
 use tracing::{debug, info, warn};
 
 #[cfg(feature = "bootstrap_server")]
-use massa_models::config::PERIODS_BETWEEN_BACKUPS;
+use massa_models::config::{PERIODS_BETWEEN_BACKUPS, PRUNED_OPS_FILTER_VERSION};
 use massa_models::timeslots::get_block_slot_timestamp;
 
-/// Represents a final state `(ledger, async pool, executed_ops, executed_de and the state of the PoS)`
+/// Represents a final state `(ledger, async pool, deferred calls, executed_ops, executed_de and the state of the PoS)`
 pub struct FinalState {
     /// execution state configuration
     pub(crate) config: FinalStateConfig,
@@ -35,6 +46,8 @@ pub struct FinalState {
     pub ledger: Box<dyn LedgerController>,
     /// asynchronous pool containing messages sorted by priority and their data
     pub async_pool: AsyncPool,
+    /// deferred calls registered by smart contracts and not executed yet
+    pub deferred_call_registry: DeferredCallRegistry,
     /// proof of stake state containing cycle history and deferred credits
     pub pos_state: PoSFinalState,
     /// executed operations
@@ -55,6 +68,8 @@ pub struct FinalState {
     pub last_slot_before_downtime: Option<Slot>,
     /// the rocksdb instance used to write every final_state struct on disk
     pub db: ShareableMassaDBController,
+    /// history of the final state changes kept on disk for lagging bootstrap clients, if enabled
+    pub(crate) change_history: Option<FinalChangeHistory>,
 }
 
 impl FinalState {
@@ -98,6 +113,10 @@ impl FinalState {
         // create the async pool
         let async_pool = AsyncPool::new(config.async_pool_config.clone(), db.clone());
 
+        // create the deferred call registry
+        let deferred_call_registry =
+            DeferredCallRegistry::new(config.deferred_calls_config.clone(), db.clone());
+
         // create a default executed ops
         let executed_ops = ExecutedOps::new(config.executed_ops_config.clone(), db.clone());
 
@@ -105,9 +124,24 @@ impl FinalState {
         let executed_denunciations =
             ExecutedDenunciations::new(config.executed_denunciations_config.clone(), db.clone());
 
+        // open the history of the changes kept on disk
+        let change_history = config
+            .change_history_path
+            .as_deref()
+            .map(|path| {
+                FinalChangeHistory::new(
+                    path,
+                    config.change_history_length,
+                    config.change_history_page_max_bytes,
+                    config.thread_count,
+                )
+            })
+            .transpose()?;
+
         let mut final_state = FinalState {
             ledger,
             async_pool,
+            deferred_call_registry,
             pos_state,
             config,
             executed_ops,
@@ -116,6 +150,7 @@ impl FinalState {
             last_start_period: 0,
             last_slot_before_downtime: None,
             db,
+            change_history,
         };
 
         if reset_final_state {
@@ -126,9 +161,14 @@ impl FinalState {
                 .write()
                 .delete_prefix(EXECUTION_TRAIL_HASH_PREFIX, STATE_CF, None);
             final_state.async_pool.reset();
+            final_state.deferred_call_registry.reset();
             final_state.pos_state.reset();
             final_state.executed_ops.reset();
             final_state.executed_denunciations.reset();
+            // a slot interrupted in the previous final state is irrelevant after a reset
+            if let Some(wal) = final_state.get_wal() {
+                wal.clear()?;
+            }
         }
 
         info!(
@@ -260,6 +300,9 @@ impl FinalState {
 
         final_state.recompute_caches();
 
+        // Complete the last slot if its application was interrupted before the shutdown
+        final_state.recover_interrupted_slot()?;
+
         // We compute the draws here because we need to feed_cycles when interpolating
         final_state.compute_initial_draws()?;
 
@@ -540,9 +583,13 @@ impl FinalState {
             .feed_cycle_state_hash(cycle, final_state_hash);
 
         self.pos_state
-            .feed_selector(cycle.checked_add(2).ok_or_else(|| {
-                FinalStateError::PosError("cycle overflow when feeding selector".into())
-            })?)
+            .feed_selector(
+                cycle
+                    .checked_add(self.pos_state.config.lookback_cycles)
+                    .ok_or_else(|| {
+                        FinalStateError::PosError("cycle overflow when feeding selector".into())
+                    })?,
+            )
             .map_err(|_| {
                 FinalStateError::PosError("cycle overflow when feeding selector".into())
             })?;
@@ -557,6 +604,7 @@ impl FinalState {
         self.db.write().reset(slot);
         self.ledger.reset();
         self.async_pool.reset();
+        self.deferred_call_registry.reset();
         self.pos_state.reset();
         self.executed_ops.reset();
         self.executed_denunciations.reset();
@@ -577,8 +625,9 @@ impl FinalState {
     /// Applies changes to the execution state at a given slot, and settles that slot forever.
     /// Once this is called, the state is attached at the output of the provided slot.
     ///
+    /// Fails without changing the state if the write-ahead log of the slot could not be written.
     /// Panics if the new slot is not the one coming just after the current one.
-    pub fn finalize(&mut self, slot: Slot, changes: StateChanges) {
+    pub fn finalize(&mut self, slot: Slot, changes: StateChanges) -> Result<(), FinalStateError> {
         let cur_slot = self.db.read().get_change_id().expect(CHANGE_ID_DESER_ERROR);
         // check slot consistency
         let next_slot = cur_slot
@@ -591,6 +640,12 @@ impl FinalState {
             slot, cur_slot
         );
 
+        // log the slot before anything is changed
+        let wal = self.get_wal();
+        if let Some(wal) = &wal {
+            wal.write(slot, FinalSlotWalStep::StateChanges)?;
+        }
+
         let mut db_batch = DBBatch::new();
         let mut db_versioning_batch = DBBatch::new();
 
@@ -598,6 +653,8 @@ impl FinalState {
 
         self.async_pool
             .apply_changes_to_batch(&changes.async_pool_changes, &mut db_batch);
+        self.deferred_call_registry
+            .apply_changes_to_batch(&changes.deferred_call_changes, &mut db_batch);
         self.pos_state
             .apply_changes_to_batch(changes.pos_changes, slot, true, &mut db_batch)
             .expect("could not settle slot in final state proof-of-stake");
@@ -607,14 +664,6 @@ impl FinalState {
         // bootstrap again instead
         self.ledger
             .apply_changes_to_batch(changes.ledger_changes, &mut db_batch);
-        self.executed_ops
-            .apply_changes_to_batch(changes.executed_ops_changes, slot, &mut db_batch);
-
-        self.executed_denunciations.apply_changes_to_batch(
-            changes.executed_denunciations_changes,
-            slot,
-            &mut db_batch,
-        );
 
         let slot_ts = get_block_slot_timestamp(
             self.config.thread_count,
@@ -624,6 +673,22 @@ impl FinalState {
         )
         .expect("Cannot get timestamp from slot");
 
+        // the pruned operations filters are part of the hashed state: only record them once activated
+        let filter_pruned_ops =
+            self.mip_store.get_network_version_active_at(slot_ts) >= PRUNED_OPS_FILTER_VERSION;
+        self.executed_ops.apply_changes_to_batch(
+            changes.executed_ops_changes,
+            slot,
+            filter_pruned_ops,
+            &mut db_batch,
+        );
+
+        self.executed_denunciations.apply_changes_to_batch(
+            changes.executed_denunciations_changes,
+            slot,
+            &mut db_batch,
+        );
+
         let slot_prev_ts = get_block_slot_timestamp(
             self.config.thread_count,
             self.config.t0,
@@ -660,6 +725,17 @@ impl FinalState {
 
         let final_state_hash = self.db.read().get_xof_db_hash();
 
+        // Once the state changes are committed, failing to update the log is not an issue:
+        // the entry left is about the slot the state is at, whose recovery feeds its final state hash again
+        if let Some(wal) = &wal {
+            if let Err(err) = wal.write(slot, FinalSlotWalStep::CycleStateHash(final_state_hash)) {
+                warn!(
+                    "could not log the final state hash of slot {}: {}",
+                    slot, err
+                );
+            }
+        }
+
         // compute the final state hash
         info!("final_state hash at slot {}: {}", slot, final_state_hash);
 
@@ -690,6 +766,156 @@ impl FinalState {
         let cycle = slot.get_cycle(self.config.periods_per_cycle);
         self.pos_state
             .feed_cycle_state_hash(cycle, final_state_hash);
+
+        if let Some(wal) = &wal {
+            if let Err(err) = wal.clear() {
+                warn!("could not clear the log of slot {}: {}", slot, err);
+            }
+        }
+
+        // keep the changes of the slot on disk for the bootstrap clients lagging behind the changes kept in memory
+        if let Some(history) = &self.change_history {
+            let changes = self.db.read().get_changes_at(slot);
+            if let Some((state_changes, versioning_changes)) = changes {
+                if let Err(err) = history.write(slot, &state_changes, &versioning_changes) {
+                    warn!(
+                        "could not write the changes of slot {} to the change history: {}",
+                        slot, err
+                    );
+                }
+            }
+        }
+
+        // snapshot the final state at the end of each cycle, if enabled
+        if self.config.cycle_snapshot_retention > 0
+            && slot.is_last_of_cycle(self.config.periods_per_cycle, self.config.thread_count)
+        {
+            if let Some(store) = self.get_cycle_snapshot_store() {
+                match self.snapshot().and_then(|snapshot| store.write(&snapshot)) {
+                    Ok(Some(path)) => {
+                        info!(
+                            "final state at slot {} snapshotted to {}",
+                            slot,
+                            path.display()
+                        )
+                    }
+                    Ok(None) => {}
+                    Err(err) => warn!("could not snapshot final state at slot {}: {}", slot, err),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes a consistent snapshot of all the components of the final state at its current slot.
+    /// No final slot can be applied while the snapshot is alive.
+    pub fn snapshot(&self) -> Result<FinalStateSnapshot<'_>, FinalStateError> {
+        FinalStateSnapshot::new(&self.db)
+    }
+
+    /// Returns the store of the snapshots written at the end of each cycle, if enabled
+    fn get_cycle_snapshot_store(&self) -> Option<CycleSnapshotStore> {
+        self.config
+            .cycle_snapshot_path
+            .as_deref()
+            .map(|path| CycleSnapshotStore::new(path, self.config.cycle_snapshot_retention))
+    }
+
+    /// Resets the final state and loads the latest snapshot written at the end of a cycle, if any.
+    /// The caches of the final state are not recomputed.
+    ///
+    /// # Returns
+    /// The slot at which the final state is attached after loading the snapshot, None if there was no snapshot to load
+    pub fn load_latest_cycle_snapshot(&mut self) -> Result<Option<Slot>, FinalStateError> {
+        let Some(store) = self.get_cycle_snapshot_store() else {
+            return Ok(None);
+        };
+        let Some((slot, path)) = store.get_latest()? else {
+            return Ok(None);
+        };
+        info!(
+            "loading final state snapshot of slot {} from {}",
+            slot,
+            path.display()
+        );
+        self.reset();
+        if let Err(err) = store.load(&path, &self.db) {
+            self.reset();
+            return Err(err);
+        }
+        info!(
+            "final_state hash at slot {}: {}",
+            slot,
+            self.db.read().get_xof_db_hash()
+        );
+        Ok(Some(slot))
+    }
+
+    /// Returns the history of the final state changes kept on disk, if enabled.
+    /// Its pages can be read once the final state is unlocked, as the history shares its index with its clones.
+    pub fn get_change_history(&self) -> Option<FinalChangeHistory> {
+        self.change_history.clone()
+    }
+
+    /// Returns the write-ahead log of the final slot being applied, if enabled
+    fn get_wal(&self) -> Option<FinalSlotWal> {
+        self.config
+            .final_state_wal_path
+            .as_deref()
+            .map(FinalSlotWal::new)
+    }
+
+    /// Detects a final slot whose application was interrupted (e.g. by a crash) and completes or discards it.
+    ///
+    /// The state changes of a slot are committed atomically, so the slot is either fully applied
+    /// or not applied at all. Only the final state hash snapshot fed to the cycle history afterwards
+    /// might be missing, in which case it is written again.
+    pub fn recover_interrupted_slot(&self) -> Result<(), FinalStateError> {
+        let Some(wal) = self.get_wal() else {
+            return Ok(());
+        };
+        let Some((wal_slot, step)) = wal.read()? else {
+            return Ok(());
+        };
+        let db_slot =
+            self.db.read().get_change_id().map_err(|_| {
+                FinalStateError::InvalidSlot(String::from("Could not get slot in db"))
+            })?;
+        let cycle = wal_slot.get_cycle(self.config.periods_per_cycle);
+
+        match step {
+            FinalSlotWalStep::StateChanges if db_slot != wal_slot => {
+                warn!(
+                    "final slot {} was interrupted while writing its state changes: they were not committed (final state is at slot {}), the slot will be executed again",
+                    wal_slot, db_slot
+                );
+            }
+            FinalSlotWalStep::StateChanges => {
+                let final_state_hash = self.db.read().get_xof_db_hash();
+                warn!(
+                    "final slot {} was interrupted after committing its state changes: feeding final state hash {} to cycle {}",
+                    wal_slot, final_state_hash, cycle
+                );
+                self.pos_state
+                    .feed_cycle_state_hash(cycle, final_state_hash);
+            }
+            FinalSlotWalStep::CycleStateHash(final_state_hash) => {
+                if db_slot != wal_slot {
+                    return Err(FinalStateError::WalError(format!(
+                        "final slot {} was interrupted while feeding its final state hash but the final state is at slot {}",
+                        wal_slot, db_slot
+                    )));
+                }
+                warn!(
+                    "final slot {} was interrupted while feeding its final state hash: feeding final state hash {} to cycle {} again",
+                    wal_slot, final_state_hash, cycle
+                );
+                self.pos_state
+                    .feed_cycle_state_hash(cycle, final_state_hash);
+            }
+        }
+
+        wal.clear()
     }
 
     /// After bootstrap or load from disk, recompute all the caches.
@@ -698,6 +924,8 @@ impl FinalState {
         self.executed_ops.recompute_sorted_ops_and_op_exec_status();
         self.executed_denunciations.recompute_sorted_denunciations();
         self.pos_state.recompute_pos_state_caches();
+        self.ledger.recompute_commitment();
+        self.ledger.recompute_datastore_key_counts();
     }
 
     /// Deserialize the entire DB and check the data. Useful to check after bootstrap.
@@ -760,7 +988,20 @@ impl FinalState {
                     );
                     return false;
                 }
-            } else if serialized_key.starts_with(EXECUTED_OPS_PREFIX.as_bytes()) {
+            } else if serialized_key.starts_with(DEFERRED_CALLS_PREFIX.as_bytes()) {
+                if !self
+                    .deferred_call_registry
+                    .is_key_value_valid(&serialized_key, &serialized_value)
+                {
+                    warn!(
+                        "Wrong key/value for DEFERRED_CALLS PREFIX serialized_key: {:?}, serialized_value: {:?}",
+                        serialized_key, serialized_value
+                    );
+                    return false;
+                }
+            } else if serialized_key.starts_with(EXECUTED_OPS_PREFIX.as_bytes())
+                || serialized_key.starts_with(EXECUTED_OPS_FILTER_PREFIX.as_bytes())
+            {
                 if !self
                     .executed_ops
                     .is_key_value_valid(&serialized_key, &serialized_value)
@@ -803,6 +1044,21 @@ impl FinalState {
         true
     }
 
+    /// Recompute the state hash from all the entries of the DB and check that it matches
+    /// the hash maintained incrementally by the DB. Useful to check after bootstrap.
+    pub fn is_state_hash_valid(&self) -> bool {
+        let db = self.db.read();
+        let mut state_hash = HashXof(*STATE_HASH_INITIAL_BYTES);
+        for (serialized_key, serialized_value) in db.iterator_cf(STATE_CF, MassaIteratorMode::Start)
+        {
+            state_hash ^= HashXof::compute_from_tuple(&[
+                serialized_key.as_slice(),
+                serialized_value.as_slice(),
+            ]);
+        }
+        state_hash == db.get_xof_db_hash()
+    }
+
     /// Initialize the execution trail hash to zero.
     pub fn init_execution_trail_hash_to_batch(&mut self, batch: &mut DBBatch) {
         batch.insert(
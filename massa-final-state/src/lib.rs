@@ -25,6 +25,14 @@
 //! Defines a structure to list and prune previously executed operations.
 //! Used to detect operation reuse.
 //!
+//! ## `wal.rs`
+//! Defines a write-ahead log recording which component of the final state was being written
+//! when applying a final slot, so that an interrupted slot can be completed on restart.
+//!
+//! ## `cycle_snapshot.rs`
+//! Defines the compressed snapshots of the final state written at the end of each cycle,
+//! from which a restarting node can resume instead of bootstrapping the whole state.
+//!
 //! ## `bootstrap.rs`
 //! Provides serializable structures and tools for bootstrapping the final state.
 //!
@@ -87,17 +95,26 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 
+mod change_history;
 mod config;
+mod cycle_snapshot;
 mod error;
 mod final_state;
+#[cfg(feature = "grpc")]
 mod mapping_grpc;
 mod state_changes;
+mod state_snapshot;
+mod wal;
 
+pub use change_history::{ChangeHistoryPage, FinalChangeHistory};
 pub use config::FinalStateConfig;
+pub use cycle_snapshot::CycleSnapshotStore;
 pub use error::FinalStateError;
 pub use final_state::FinalState;
 use num as _;
 pub use state_changes::{StateChanges, StateChangesDeserializer, StateChangesSerializer};
+pub use state_snapshot::FinalStateSnapshot;
+pub use wal::{FinalSlotWal, FinalSlotWalStep};
 
 #[cfg(test)]
 mod tests;
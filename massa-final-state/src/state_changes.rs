@@ -5,6 +5,7 @@
 use massa_async_pool::{
     AsyncPoolChanges, AsyncPoolChangesDeserializer, AsyncPoolChangesSerializer,
 };
+use massa_deferred_calls::DeferredCallRegistryChanges;
 use massa_executed_ops::{
     ExecutedDenunciationsChanges, ExecutedDenunciationsChangesDeserializer,
     ExecutedDenunciationsChangesSerializer, ExecutedOpsChanges, ExecutedOpsChangesDeserializer,
@@ -31,6 +32,10 @@ pub struct StateChanges {
     pub ledger_changes: LedgerChanges,
     /// asynchronous pool changes
     pub async_pool_changes: AsyncPoolChanges,
+    /// deferred call registry changes.
+    /// Not serialized: no smart contract can register deferred calls yet,
+    /// so the serialized state changes keep their format until deferred calls are exposed to them.
+    pub deferred_call_changes: DeferredCallRegistryChanges,
     /// roll state changes
     pub pos_changes: PoSChanges,
     /// executed operations changes
@@ -296,6 +301,7 @@ impl Deserializer<StateChanges> for StateChangesDeserializer {
             )| StateChanges {
                 ledger_changes,
                 async_pool_changes,
+                deferred_call_changes: Default::default(),
                 pos_changes,
                 executed_ops_changes,
                 executed_denunciations_changes,
@@ -312,6 +318,8 @@ impl StateChanges {
         use massa_ledger_exports::Applicable;
         self.ledger_changes.apply(changes.ledger_changes);
         self.async_pool_changes.apply(changes.async_pool_changes);
+        self.deferred_call_changes
+            .apply(changes.deferred_call_changes);
         self.pos_changes.extend(changes.pos_changes);
         self.executed_ops_changes
             .extend(changes.executed_ops_changes);
@@ -0,0 +1,133 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines consistent read views of the final state.
+//!
+//! The ledger, asynchronous pool, deferred calls, proof-of-stake state, executed operations and denunciations
+//! and MIP store are all stored in the same database, and each final slot writes the changes of all of them
+//! in a single batch. A snapshot holds a read lock on that database: as long as it is alive no final slot
+//! can be applied, so every part read through it belongs to the state at the output of the same slot.
+//!
+//! Everything read while the snapshot is alive must go through it: locking the database again
+//! from the same thread could deadlock with a pending write.
+
+use crate::error::FinalStateError;
+use massa_db_exports::{
+    Key, MassaDBController, MassaDBError, MassaIteratorMode, ShareableMassaDBController,
+    StreamBatch, Value,
+};
+use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
+use massa_models::{slot::Slot, streaming_step::StreamingStep};
+use parking_lot::RwLockReadGuard;
+
+/// Read view of all the components of the final state at the output of a given final slot
+pub struct FinalStateSnapshot<'a> {
+    /// read lock on the database, preventing any final slot from being applied
+    db: RwLockReadGuard<'a, Box<dyn MassaDBController>>,
+    /// slot at the output of which the snapshot is taken
+    slot: Slot,
+}
+
+impl<'a> FinalStateSnapshot<'a> {
+    /// Takes a snapshot of the final state stored in the given database, at its current slot
+    pub fn new(db: &'a ShareableMassaDBController) -> Result<Self, FinalStateError> {
+        let db = db.read();
+        let slot = db
+            .get_change_id()
+            .map_err(|_| FinalStateError::InvalidSlot(String::from("Could not get slot in db")))?;
+        Ok(FinalStateSnapshot { db, slot })
+    }
+
+    /// Slot at the output of which the snapshot is taken
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    /// Hash of the final state at the slot of the snapshot
+    pub fn state_hash(&self) -> HashXof<HASH_XOF_SIZE_BYTES> {
+        self.db.get_xof_db_hash()
+    }
+
+    /// Iterates over a column of the database
+    pub fn iterator_cf(
+        &self,
+        handle_cf: &str,
+        mode: MassaIteratorMode,
+    ) -> Box<dyn Iterator<Item = (Key, Value)> + '_> {
+        self.db.iterator_cf(handle_cf, mode)
+    }
+
+    /// Gets the next batch of the state column to stream to a bootstrap client, attached to the slot of the snapshot
+    pub fn get_batch_to_stream(
+        &self,
+        last_state_step: &StreamingStep<Vec<u8>>,
+        last_slot: Option<Slot>,
+    ) -> Result<StreamBatch<Slot>, MassaDBError> {
+        self.db.get_batch_to_stream(last_state_step, last_slot)
+    }
+
+    /// Gets the next batch of the versioning column to stream to a bootstrap client, attached to the slot of the snapshot
+    pub fn get_versioning_batch_to_stream(
+        &self,
+        last_versioning_step: &StreamingStep<Vec<u8>>,
+        last_slot: Option<Slot>,
+    ) -> Result<StreamBatch<Slot>, MassaDBError> {
+        self.db
+            .get_versioning_batch_to_stream(last_versioning_step, last_slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_db_exports::{DBBatch, MassaDBConfig};
+    use massa_db_worker::MassaDB;
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_final_state_snapshot_blocks_writes() {
+        let temp_dir = TempDir::new().expect("cannot create temp directory");
+        let db_config = MassaDBConfig {
+            path: temp_dir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 2,
+            block_cache_size: 0,
+        };
+        let db: ShareableMassaDBController = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>,
+        ));
+        db.write()
+            .write_batch(DBBatch::new(), DBBatch::new(), Some(Slot::new(1, 0)));
+
+        let snapshot = FinalStateSnapshot::new(&db).unwrap();
+        let hash = snapshot.state_hash();
+        let writer = {
+            let db = db.clone();
+            std::thread::spawn(move || {
+                let mut batch = DBBatch::new();
+                batch.insert(b"key".to_vec(), Some(b"value".to_vec()));
+                db.write()
+                    .write_batch(batch, DBBatch::new(), Some(Slot::new(1, 1)));
+            })
+        };
+
+        // the slot cannot be applied while the snapshot is alive
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(snapshot.slot(), Slot::new(1, 0));
+        assert_eq!(snapshot.state_hash(), hash);
+        assert_eq!(
+            snapshot
+                .iterator_cf(massa_db_exports::STATE_CF, MassaIteratorMode::Start)
+                .count(),
+            0
+        );
+
+        drop(snapshot);
+        writer.join().unwrap();
+        let snapshot = FinalStateSnapshot::new(&db).unwrap();
+        assert_eq!(snapshot.slot(), Slot::new(1, 1));
+        assert_ne!(snapshot.state_hash(), hash);
+    }
+}
@@ -5,6 +5,7 @@ use massa_async_pool::AsyncPool;
 use massa_db_exports::{
     MassaIteratorMode, ShareableMassaDBController, METADATA_CF, STATE_CF, STATE_HASH_KEY,
 };
+use massa_deferred_calls::DeferredCallRegistry;
 use massa_executed_ops::{ExecutedDenunciations, ExecutedOps};
 use massa_ledger_exports::LedgerController;
 use massa_pos_exports::PoSFinalState;
@@ -24,6 +25,10 @@ pub fn create_final_state(
     db: ShareableMassaDBController,
 ) -> FinalState {
     FinalState {
+        deferred_call_registry: DeferredCallRegistry::new(
+            config.deferred_calls_config.clone(),
+            db.clone(),
+        ),
         config,
         ledger,
         async_pool,
@@ -34,6 +39,7 @@ pub fn create_final_state(
         last_start_period: 0,
         last_slot_before_downtime: None,
         db,
+        change_history: None,
     }
 }
 
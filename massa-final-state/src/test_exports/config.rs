@@ -9,6 +9,7 @@ use num::rational::Ratio;
 use crate::{FinalState, FinalStateConfig};
 use massa_async_pool::{AsyncPool, AsyncPoolConfig};
 use massa_db_exports::ShareableMassaDBController;
+use massa_deferred_calls::{DeferredCallRegistry, DeferredCallsConfig};
 use massa_executed_ops::{
     ExecutedDenunciations, ExecutedDenunciationsConfig, ExecutedOps, ExecutedOpsConfig,
 };
@@ -16,11 +17,15 @@ use massa_ledger_exports::LedgerConfig;
 use massa_ledger_worker::FinalLedger;
 use massa_models::config::{
     DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, GENESIS_TIMESTAMP,
-    KEEP_EXECUTED_HISTORY_EXTRA_PERIODS, MAX_DEFERRED_CREDITS_LENGTH,
-    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_DENUNCIATION_CHANGES_LENGTH,
-    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, T0,
+    KEEP_EXECUTED_HISTORY_EXTRA_PERIODS, MAX_DEFERRED_CALL_CHANGES, MAX_DEFERRED_CREDITS_LENGTH,
+    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_DENUNCIATION_CHANGES_LENGTH, MAX_FUNCTION_NAME_LENGTH,
+    MAX_PARAMETERS_SIZE, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
+    PRUNED_OPS_FILTER_CAPACITY, PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+    PRUNED_OPS_FILTER_RANGE_COUNT, PRUNED_OPS_FILTER_RANGE_PERIODS, T0,
+};
+use massa_models::config::{
+    PERIODS_PER_CYCLE, POS_LOOKBACK_CYCLES, POS_SAVED_CYCLES, THREAD_COUNT,
 };
-use massa_models::config::{PERIODS_PER_CYCLE, POS_SAVED_CYCLES, THREAD_COUNT};
 use massa_pos_exports::{PoSConfig, PoSFinalState};
 use massa_versioning::versioning::{MipStatsConfig, MipStore};
 
@@ -34,6 +39,10 @@ impl FinalState {
         FinalState {
             ledger: Box::new(FinalLedger::new(config.ledger_config.clone(), db.clone())),
             async_pool: AsyncPool::new(config.async_pool_config.clone(), db.clone()),
+            deferred_call_registry: DeferredCallRegistry::new(
+                config.deferred_calls_config.clone(),
+                db.clone(),
+            ),
             pos_state,
             executed_ops: ExecutedOps::new(config.executed_ops_config.clone(), db.clone()),
             executed_denunciations: ExecutedDenunciations::new(
@@ -52,6 +61,7 @@ impl FinalState {
             last_start_period: 0,
             last_slot_before_downtime: None,
             db,
+            change_history: None,
         }
     }
 }
@@ -62,9 +72,19 @@ impl Default for FinalStateConfig {
         FinalStateConfig {
             ledger_config: LedgerConfig::default(),
             async_pool_config: AsyncPoolConfig::default(),
+            deferred_calls_config: DeferredCallsConfig {
+                thread_count: THREAD_COUNT,
+                max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+                max_parameters_size: MAX_PARAMETERS_SIZE,
+                max_deferred_call_changes: MAX_DEFERRED_CALL_CHANGES,
+            },
             executed_ops_config: ExecutedOpsConfig {
                 thread_count: THREAD_COUNT,
                 keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+                pruned_ops_filter_capacity: PRUNED_OPS_FILTER_CAPACITY,
+                pruned_ops_filter_false_positive_log2: PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+                pruned_ops_filter_range_periods: PRUNED_OPS_FILTER_RANGE_PERIODS,
+                pruned_ops_filter_range_count: PRUNED_OPS_FILTER_RANGE_COUNT,
             },
             executed_denunciations_config: ExecutedDenunciationsConfig {
                 denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
@@ -76,6 +96,7 @@ impl Default for FinalStateConfig {
                 periods_per_cycle: PERIODS_PER_CYCLE,
                 thread_count: THREAD_COUNT,
                 cycle_history_length: POS_SAVED_CYCLES,
+                lookback_cycles: POS_LOOKBACK_CYCLES,
                 max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
                 max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
                 max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
@@ -91,6 +112,12 @@ impl Default for FinalStateConfig {
             max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
             t0: T0,
             genesis_timestamp: *GENESIS_TIMESTAMP,
+            final_state_wal_path: None,
+            cycle_snapshot_path: None,
+            cycle_snapshot_retention: 0,
+            change_history_path: None,
+            change_history_length: 0,
+            change_history_page_max_bytes: 0,
         }
     }
 }
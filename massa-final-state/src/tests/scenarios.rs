@@ -7,19 +7,24 @@ use crate::{
 use massa_async_pool::{AsyncMessage, AsyncPoolChanges, AsyncPoolConfig};
 use massa_db_exports::{DBBatch, MassaDBConfig, MassaDBController};
 use massa_db_worker::MassaDB;
+use massa_deferred_calls::DeferredCallsConfig;
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
 use massa_ledger_exports::{
     LedgerChanges, LedgerConfig, LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete,
 };
 use massa_ledger_worker::FinalLedger;
+use massa_metrics::MassaMetrics;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
 use massa_models::bytecode::Bytecode;
 use massa_models::config::{
     DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, GENESIS_TIMESTAMP,
     KEEP_EXECUTED_HISTORY_EXTRA_PERIODS, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH,
-    MAX_DATASTORE_KEY_LENGTH, MAX_DEFERRED_CREDITS_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
-    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, POS_SAVED_CYCLES, T0,
+    MAX_DATASTORE_KEY_LENGTH, MAX_DEFERRED_CALL_CHANGES, MAX_DEFERRED_CREDITS_LENGTH,
+    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_FUNCTION_NAME_LENGTH, MAX_PARAMETERS_SIZE,
+    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, POS_LOOKBACK_CYCLES, POS_SAVED_CYCLES,
+    PRUNED_OPS_FILTER_CAPACITY, PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+    PRUNED_OPS_FILTER_RANGE_COUNT, PRUNED_OPS_FILTER_RANGE_PERIODS, T0, THREAD_COUNT,
 };
 use massa_models::{config::MAX_DATASTORE_VALUE_LENGTH, slot::Slot};
 use massa_pos_exports::{PoSConfig, SelectorConfig};
@@ -27,6 +32,7 @@ use massa_pos_worker::start_selector_worker;
 use parking_lot::RwLock;
 use std::collections::BTreeMap;
 use std::path::Path;
+use std::time::Duration;
 use std::{path::PathBuf, str::FromStr, sync::Arc};
 use tempfile::TempDir;
 
@@ -38,6 +44,7 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        block_cache_size: 0,
     };
     let db = Arc::new(RwLock::new(
         Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -51,9 +58,12 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
         ledger_config: LedgerConfig {
             thread_count,
             initial_ledger_path: "".into(),
+            initial_genesis_sc_path: None,
             disk_ledger_path: temp_dir.path().to_path_buf(),
+            max_initial_ledger_entries: 1_000_000,
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            commitment_enabled: false,
         },
         async_pool_config: AsyncPoolConfig {
             thread_count,
@@ -61,10 +71,17 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
             max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
             max_key_length: MAX_DATASTORE_KEY_LENGTH as u32,
         },
+        deferred_calls_config: DeferredCallsConfig {
+            thread_count,
+            max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+            max_parameters_size: MAX_PARAMETERS_SIZE,
+            max_deferred_call_changes: MAX_DEFERRED_CALL_CHANGES,
+        },
         pos_config: PoSConfig {
             periods_per_cycle,
             thread_count,
             cycle_history_length: POS_SAVED_CYCLES,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
             max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
             max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
             max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
@@ -73,6 +90,10 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
         executed_ops_config: ExecutedOpsConfig {
             thread_count,
             keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+            pruned_ops_filter_capacity: PRUNED_OPS_FILTER_CAPACITY,
+            pruned_ops_filter_false_positive_log2: PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+            pruned_ops_filter_range_periods: PRUNED_OPS_FILTER_RANGE_PERIODS,
+            pruned_ops_filter_range_count: PRUNED_OPS_FILTER_RANGE_COUNT,
         },
         executed_denunciations_config: ExecutedDenunciationsConfig {
             denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
@@ -90,6 +111,12 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
         max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
+        final_state_wal_path: None,
+        cycle_snapshot_path: None,
+        cycle_snapshot_retention: 0,
+        change_history_path: None,
+        change_history_length: 0,
+        change_history_page_max_bytes: 0,
     };
 
     // setup selector local config
@@ -100,8 +127,17 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
     };
 
     // start proof-of-stake selectors
-    let (mut _selector_manager, selector_controller) = start_selector_worker(selector_local_config)
-        .expect("could not start server selector controller");
+    let (mut _selector_manager, selector_controller) = start_selector_worker(
+        selector_local_config,
+        MassaMetrics::new(
+            false,
+            "0.0.0.0:31248".parse().unwrap(),
+            THREAD_COUNT,
+            Duration::from_secs(5),
+        )
+        .0,
+    )
+    .expect("could not start server selector controller");
 
     // MIP store
     let mip_store = MipStore::try_from((
@@ -206,7 +242,7 @@ fn test_final_state() {
         );
         state_changes.ledger_changes = ledger_changes;
 
-        fs.write().finalize(slot, state_changes);
+        fs.write().finalize(slot, state_changes).unwrap();
 
         hash = fs.read().db.read().get_xof_db_hash();
 
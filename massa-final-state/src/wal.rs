@@ -0,0 +1,147 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines a small write-ahead log tracking the application of a final slot.
+//!
+//! The state changes of a final slot are written to the database in a single atomic batch,
+//! but the final state hash snapshot of the cycle is written afterwards in a separate batch.
+//! The log records which of those writes was in progress so that an interrupted slot
+//! can be detected and completed on restart.
+
+use crate::error::FinalStateError;
+use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
+use massa_models::slot::Slot;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Size of the serialized slot and step tag of a log entry: period (8 bytes), thread (1 byte), step (1 byte)
+const WAL_HEADER_SIZE: usize = 10;
+
+/// Component being written during the application of a final slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalSlotWalStep {
+    /// the state changes of the slot are being written to the database
+    StateChanges,
+    /// the given final state hash is being fed to the cycle history
+    CycleStateHash(HashXof<HASH_XOF_SIZE_BYTES>),
+}
+
+/// Write-ahead log of the final slot being applied
+#[derive(Debug, Clone)]
+pub struct FinalSlotWal {
+    /// path of the log file
+    path: PathBuf,
+}
+
+impl FinalSlotWal {
+    /// Creates a write-ahead log stored at the given path
+    pub fn new(path: &Path) -> Self {
+        FinalSlotWal {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Records that a given step of the application of a slot is starting.
+    /// The entry is written to a temporary file which is then renamed, so that it is never partially written.
+    pub fn write(&self, slot: Slot, step: FinalSlotWalStep) -> Result<(), FinalStateError> {
+        let mut bytes = Vec::with_capacity(WAL_HEADER_SIZE + HASH_XOF_SIZE_BYTES);
+        bytes.extend(slot.period.to_be_bytes());
+        bytes.push(slot.thread);
+        match step {
+            FinalSlotWalStep::StateChanges => bytes.push(0),
+            FinalSlotWalStep::CycleStateHash(hash) => {
+                bytes.push(1);
+                bytes.extend(hash.to_bytes());
+            }
+        }
+
+        let tmp_path = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp_path).map_err(|err| {
+            FinalStateError::WalError(format!("could not create {}: {}", tmp_path.display(), err))
+        })?;
+        file.write_all(&bytes)
+            .and_then(|_| file.sync_all())
+            .map_err(|err| {
+                FinalStateError::WalError(format!(
+                    "could not write {}: {}",
+                    tmp_path.display(),
+                    err
+                ))
+            })?;
+        fs::rename(&tmp_path, &self.path).map_err(|err| {
+            FinalStateError::WalError(format!("could not rename {}: {}", tmp_path.display(), err))
+        })
+    }
+
+    /// Reads the pending entry of the log, if any
+    pub fn read(&self) -> Result<Option<(Slot, FinalSlotWalStep)>, FinalStateError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&self.path).map_err(|err| {
+            FinalStateError::WalError(format!("could not read {}: {}", self.path.display(), err))
+        })?;
+        let invalid_entry = || {
+            FinalStateError::WalError(format!(
+                "invalid entry of {} bytes in {}",
+                bytes.len(),
+                self.path.display()
+            ))
+        };
+        if bytes.len() < WAL_HEADER_SIZE {
+            return Err(invalid_entry());
+        }
+        let period = u64::from_be_bytes(bytes[0..8].try_into().expect("invalid period slice"));
+        let step = match (bytes[9], &bytes[WAL_HEADER_SIZE..]) {
+            (0, []) => FinalSlotWalStep::StateChanges,
+            (1, hash_bytes) => FinalSlotWalStep::CycleStateHash(HashXof::from_bytes(
+                hash_bytes.try_into().map_err(|_| invalid_entry())?,
+            )),
+            _ => return Err(invalid_entry()),
+        };
+        Ok(Some((Slot::new(period, bytes[8]), step)))
+    }
+
+    /// Clears the log once a slot is fully applied
+    pub fn clear(&self) -> Result<(), FinalStateError> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        fs::remove_file(&self.path).map_err(|err| {
+            FinalStateError::WalError(format!("could not remove {}: {}", self.path.display(), err))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_final_slot_wal() {
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let wal = FinalSlotWal::new(&tempdir.path().join("final_state.wal"));
+
+        assert_eq!(wal.read().unwrap(), None);
+
+        let slot = Slot::new(123456789, 31);
+        wal.write(slot, FinalSlotWalStep::StateChanges).unwrap();
+        assert_eq!(
+            wal.read().unwrap(),
+            Some((slot, FinalSlotWalStep::StateChanges))
+        );
+
+        let hash = HashXof::compute_from(b"final state");
+        wal.write(slot, FinalSlotWalStep::CycleStateHash(hash))
+            .unwrap();
+        assert_eq!(
+            wal.read().unwrap(),
+            Some((slot, FinalSlotWalStep::CycleStateHash(hash)))
+        );
+
+        wal.clear().unwrap();
+        assert_eq!(wal.read().unwrap(), None);
+        wal.clear().unwrap();
+    }
+}
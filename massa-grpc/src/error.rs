@@ -8,6 +8,7 @@ use massa_consensus_exports::error::ConsensusError;
 use massa_execution_exports::ExecutionError;
 use massa_hash::MassaHashError;
 use massa_models::error::ModelsError;
+use massa_models::error_code::{ErrorCategory, ErrorCode, ErrorCodeInfo};
 use massa_protocol_exports::ProtocolError;
 use massa_signature::MassaSignatureError;
 use massa_time::TimeError;
@@ -49,7 +50,7 @@ pub enum GrpcError {
 
 impl From<GrpcError> for tonic::Status {
     fn from(error: GrpcError) -> Self {
-        error!("{}", error);
+        error!("{}: {}", error.error_code(), error);
         match error {
             GrpcError::MassaHashError(e) => tonic::Status::internal(e.to_string()),
             GrpcError::MassaSignatureError(e) => tonic::Status::internal(e.to_string()),
@@ -68,6 +69,28 @@ impl From<GrpcError> for tonic::Status {
     }
 }
 
+impl ErrorCode for GrpcError {
+    fn error_code(&self) -> ErrorCodeInfo {
+        // the gRPC errors share the codes of the API errors
+        let index = match self {
+            GrpcError::ConsensusError(err) => return err.error_code(),
+            GrpcError::ExecutionError(err) => return err.error_code(),
+            GrpcError::ProtocolError(err) => return err.error_code(),
+            GrpcError::ModelsError(err) => return err.error_code(),
+            GrpcError::MassaHashError(_) => 3,
+            GrpcError::TimeError(_) => 4,
+            GrpcError::WalletError(_) => 5,
+            GrpcError::InternalServerError(_) => 12,
+            GrpcError::FactoryError(_) => 13,
+            GrpcError::MassaSignatureError(_) => 14,
+            GrpcError::ReflectionError(_) => 15,
+            GrpcError::InvalidArgument(_) => 16,
+            GrpcError::Unimplemented(_) => 17,
+        };
+        ErrorCodeInfo::new(ErrorCategory::Api, index)
+    }
+}
+
 /// returns the first IO error found
 pub fn match_for_io_error(err_status: &tonic::Status) -> Option<&std::io::Error> {
     let mut err: &(dyn Error + 'static) = err_status;
@@ -279,7 +279,10 @@ pub(crate) fn get_node_status(
             })?
     };
     let next_cycle_time = current_cycle_time.checked_add(cycle_duration)?;
-    let empty_request = ExecutionQueryRequest { requests: vec![] };
+    let empty_request = ExecutionQueryRequest {
+        requests: vec![],
+        view_id: None,
+    };
     let state = grpc.execution_controller.query_state(empty_request);
     let node_ip = grpc
         .protocol_config
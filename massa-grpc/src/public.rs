@@ -113,6 +113,7 @@ pub(crate) fn execute_read_only_call(
         max_gas: call.max_gas,
         call_stack,
         target,
+        coins: None,
         is_final: call.is_final,
     };
 
@@ -694,7 +695,10 @@ pub(crate) fn get_status(
     };
     let next_cycle_time = current_cycle_time.checked_add(cycle_duration)?;
     //TODO to be enhanced
-    let empty_request = ExecutionQueryRequest { requests: vec![] };
+    let empty_request = ExecutionQueryRequest {
+        requests: vec![],
+        view_id: None,
+    };
     let state = grpc.execution_controller.query_state(empty_request);
 
     let status = grpc_model::PublicStatus {
@@ -749,7 +753,10 @@ pub(crate) fn query_state(
 
     let response = grpc
         .execution_controller
-        .query_state(ExecutionQueryRequest { requests: queries });
+        .query_state(ExecutionQueryRequest {
+            requests: queries,
+            view_id: None,
+        });
 
     Ok(grpc_api::QueryStateResponse {
         final_cursor: Some(response.final_cursor.into()),
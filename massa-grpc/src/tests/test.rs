@@ -134,6 +134,7 @@ async fn test_start_grpc_server() {
         execution_controller: execution_ctrl.0.clone(),
         execution_channels: ExecutionChannels {
             slot_execution_output_sender,
+            pos_event_sender: tokio::sync::broadcast::channel(100).0,
         },
         pool_channels: PoolChannels {
             endorsement_sender,
@@ -184,6 +184,18 @@ impl Hash {
     }
 }
 
+impl std::ops::BitXorAssign for Hash {
+    /// XOR the bytes of another hash into this one.
+    /// Used to maintain order-independent fingerprints of sets that can be updated incrementally.
+    fn bitxor_assign(&mut self, rhs: Self) {
+        let mut bytes = self.into_bytes();
+        for (byte, rhs_byte) in bytes.iter_mut().zip(rhs.to_bytes().iter()) {
+            *byte ^= rhs_byte;
+        }
+        *self = Hash::from_bytes(&bytes);
+    }
+}
+
 impl TryFrom<&[u8]> for Hash {
     type Error = MassaHashError;
 
@@ -11,10 +11,16 @@ pub struct LedgerConfig {
     pub thread_count: u8,
     /// initial SCE ledger file
     pub initial_ledger_path: PathBuf,
+    /// file declaring the smart contracts deployed in the initial ledger
+    pub initial_genesis_sc_path: Option<PathBuf>,
     /// disk ledger db directory
     pub disk_ledger_path: PathBuf,
+    /// max number of entries in the initial ledger file
+    pub max_initial_ledger_entries: u64,
     /// max key length
     pub max_key_length: u8,
     /// max datastore value length
     pub max_datastore_value_length: u64,
+    /// whether a Merkle commitment over the ledger is maintained in RAM to produce proofs of its entries
+    pub commitment_enabled: bool,
 }
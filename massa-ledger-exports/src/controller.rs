@@ -1,4 +1,7 @@
-use massa_models::{address::Address, amount::Amount, bytecode::Bytecode};
+use massa_hash::Hash;
+use massa_models::{
+    address::Address, amount::Amount, bytecode::Bytecode, ledger_proof::LedgerEntryProof,
+};
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 
@@ -43,6 +46,31 @@ pub trait LedgerController: Send + Sync + Debug {
     /// A `BTreeSet` of the datastore keys
     fn get_datastore_keys(&self, addr: &Address, prefix: &[u8]) -> Option<BTreeSet<Vec<u8>>>;
 
+    /// Get the number of keys of the datastore of an address, without listing them.
+    /// The counts are maintained along with the ledger.
+    ///
+    /// # Returns
+    /// The number of keys, 0 if the ledger entry was not found
+    fn get_datastore_key_count(&self, addr: &Address) -> u64;
+
+    /// Get a page of the keys of the datastore for a given address, in ascending order,
+    /// so that a datastore can be listed without loading all its keys.
+    ///
+    /// # Arguments
+    /// * `prefix`: only the keys starting with this prefix are listed
+    /// * `cursor`: only the keys strictly greater than this key are listed, typically the last key of the previous page
+    /// * `limit`: max number of keys listed
+    ///
+    /// # Returns
+    /// The keys of the page, or `None` if the ledger entry was not found
+    fn get_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Option<Vec<Vec<u8>>>;
+
     /// Reset the ledger
     ///
     /// USED FOR BOOTSTRAP ONLY
@@ -50,6 +78,25 @@ pub trait LedgerController: Send + Sync + Debug {
 
     fn apply_changes_to_batch(&mut self, changes: LedgerChanges, ledger_batch: &mut DBBatch);
 
+    /// Recompute the Merkle commitment over the whole ledger, if enabled.
+    /// Useful after bootstrap, the commitment being updated incrementally afterwards.
+    fn recompute_commitment(&mut self);
+
+    /// Count the keys of every datastore of the ledger.
+    /// Useful after bootstrap, the counts being updated incrementally afterwards.
+    fn recompute_datastore_key_counts(&mut self);
+
+    /// Get a Merkle proof of the balance of an address, or of one of its datastore entries if `key` is given,
+    /// against the commitment over the ledger.
+    ///
+    /// # Returns
+    /// The root of the commitment and the proof, or `None` if the commitment is disabled
+    fn get_ledger_proof(
+        &self,
+        addr: &Address,
+        key: Option<&[u8]>,
+    ) -> Option<(Hash, LedgerEntryProof)>;
+
     /// Deserializes the key and value, useful after bootstrap
     fn is_key_value_valid(&self, serialized_key: &[u8], serialized_value: &[u8]) -> bool;
 
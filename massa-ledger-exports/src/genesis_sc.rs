@@ -0,0 +1,109 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines the smart contracts deployed in the initial ledger.
+//!
+//! They are declared in a JSON file listing, for each contract, its name, the path of its bytecode file
+//! (relative to the declaration file), its initial balance and its initial datastore.
+//! The address of a genesis smart contract is derived from its name only,
+//! so that it is known before the network starts.
+
+use crate::{LedgerConfig, LedgerEntry, LedgerError};
+use massa_hash::Hash;
+use massa_models::{
+    address::{Address, SCAddress},
+    amount::Amount,
+    bytecode::Bytecode,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Smart contract deployed in the initial ledger
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GenesisSmartContract {
+    /// name of the contract, from which its address is derived
+    pub name: String,
+    /// path of the bytecode file, relative to the declaration file
+    pub bytecode_path: PathBuf,
+    /// initial balance of the contract
+    #[serde(default)]
+    pub balance: Amount,
+    /// initial datastore of the contract, with UTF-8 keys
+    #[serde(default)]
+    pub datastore: BTreeMap<String, Vec<u8>>,
+}
+
+/// Address of the genesis smart contract named `name`
+pub fn genesis_sc_address(name: &str) -> Address {
+    Address::SC(SCAddress(Hash::compute_from_tuple(&[
+        "GENESIS_SC_ADDRESS".as_bytes(),
+        name.as_bytes(),
+    ])))
+}
+
+fn file_error(path: &Path, context: &str, err: impl std::fmt::Display) -> LedgerError {
+    LedgerError::FileError(format!("{} {}: {}", context, path.display(), err))
+}
+
+/// Loads the genesis smart contracts declared in `config.initial_genesis_sc_path`, if any.
+///
+/// # Returns
+/// The name, address and initial ledger entry of each contract, in declaration order
+pub fn load_genesis_smart_contracts(
+    config: &LedgerConfig,
+) -> Result<Vec<(String, Address, LedgerEntry)>, LedgerError> {
+    let Some(path) = &config.initial_genesis_sc_path else {
+        return Ok(Vec::new());
+    };
+    let contracts: Vec<GenesisSmartContract> = serde_json::from_str(
+        &std::fs::read_to_string(path)
+            .map_err(|err| file_error(path, "error loading genesis smart contracts file", err))?,
+    )
+    .map_err(|err| file_error(path, "error parsing genesis smart contracts file", err))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut names = BTreeSet::new();
+    let mut result = Vec::with_capacity(contracts.len());
+    for contract in contracts {
+        if !names.insert(contract.name.clone()) {
+            return Err(LedgerError::FileError(format!(
+                "genesis smart contract {} is declared twice in {}",
+                contract.name,
+                path.display()
+            )));
+        }
+        let bytecode_path = base_dir.join(&contract.bytecode_path);
+        let bytecode = std::fs::read(&bytecode_path).map_err(|err| {
+            file_error(&bytecode_path, "error loading genesis bytecode file", err)
+        })?;
+        if bytecode.len() as u64 > config.max_datastore_value_length {
+            return Err(LedgerError::FileError(format!(
+                "bytecode of genesis smart contract {} exceeds the maximum length of {} bytes",
+                contract.name, config.max_datastore_value_length
+            )));
+        }
+        let mut datastore = BTreeMap::new();
+        for (key, value) in contract.datastore {
+            if key.len() > config.max_key_length as usize
+                || value.len() as u64 > config.max_datastore_value_length
+            {
+                return Err(LedgerError::FileError(format!(
+                    "datastore entry {} of genesis smart contract {} exceeds the maximum key or value length",
+                    key, contract.name
+                )));
+            }
+            datastore.insert(key.into_bytes(), value);
+        }
+        let address = genesis_sc_address(&contract.name);
+        result.push((
+            contract.name,
+            address,
+            LedgerEntry {
+                balance: contract.balance,
+                bytecode: Bytecode(bytecode),
+                datastore,
+            },
+        ));
+    }
+    Ok(result)
+}
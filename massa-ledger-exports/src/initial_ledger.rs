@@ -0,0 +1,209 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines the loading of the initial ledger file.
+//!
+//! The file is either a JSON object associating addresses to ledger entries,
+//! or a zstd-compressed sequence of binary records.
+//! Each binary record is made of its length as a big-endian u32, followed by the serialized address and ledger entry.
+//! Both formats are read in a streaming way: entries are validated and handed over one by one,
+//! so that the whole file is never held in memory.
+
+use crate::{
+    LedgerConfig, LedgerEntry, LedgerEntryDeserializer, LedgerEntrySerializer, LedgerError,
+};
+use massa_models::address::{Address, AddressDeserializer, AddressSerializer};
+use massa_models::prehash::PreHashSet;
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use serde::de::{Error as _, MapAccess, Visitor};
+use serde::Deserializer as _;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+/// First bytes of a zstd frame, identifying the binary format
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// zstd compression level of the binary format
+const COMPRESSION_LEVEL: i32 = 3;
+
+fn file_error(path: &Path, context: &str, err: impl std::fmt::Display) -> LedgerError {
+    LedgerError::FileError(format!("{} {}: {}", context, path.display(), err))
+}
+
+/// Checks an entry of the initial ledger against the ledger limits
+fn check_entry(
+    config: &LedgerConfig,
+    addresses: &mut PreHashSet<Address>,
+    address: &Address,
+    entry: &LedgerEntry,
+) -> Result<(), String> {
+    if addresses.len() as u64 >= config.max_initial_ledger_entries {
+        return Err(format!(
+            "the initial ledger exceeds the maximum of {} entries",
+            config.max_initial_ledger_entries
+        ));
+    }
+    if !addresses.insert(*address) {
+        return Err(format!("address {} appears twice", address));
+    }
+    if entry.bytecode.0.len() as u64 > config.max_datastore_value_length {
+        return Err(format!(
+            "bytecode of address {} exceeds the maximum length of {} bytes",
+            address, config.max_datastore_value_length
+        ));
+    }
+    for (key, value) in &entry.datastore {
+        if key.len() > config.max_key_length as usize
+            || value.len() as u64 > config.max_datastore_value_length
+        {
+            return Err(format!(
+                "a datastore entry of address {} exceeds the maximum key or value length",
+                address
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Visits the JSON object of the initial ledger, one entry at a time
+struct InitialLedgerVisitor<'a, F> {
+    config: &'a LedgerConfig,
+    on_entry: F,
+}
+
+impl<'de, F: FnMut(Address, LedgerEntry)> Visitor<'de> for InitialLedgerVisitor<'_, F> {
+    type Value = PreHashSet<Address>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map of addresses to ledger entries")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut addresses = PreHashSet::default();
+        while let Some(address) = map.next_key::<Address>()? {
+            let entry: LedgerEntry = map.next_value()?;
+            check_entry(self.config, &mut addresses, &address, &entry).map_err(A::Error::custom)?;
+            (self.on_entry)(address, entry);
+        }
+        Ok(addresses)
+    }
+}
+
+/// Loads the initial ledger in JSON format.
+/// Errors report the line and column at which they were detected.
+fn load_json(
+    config: &LedgerConfig,
+    reader: impl Read,
+    on_entry: impl FnMut(Address, LedgerEntry),
+) -> Result<PreHashSet<Address>, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let addresses = deserializer.deserialize_map(InitialLedgerVisitor { config, on_entry })?;
+    deserializer.end()?;
+    Ok(addresses)
+}
+
+/// Loads the initial ledger in binary format.
+/// Errors report the index of the record at which they were detected.
+fn load_binary(
+    config: &LedgerConfig,
+    reader: impl Read,
+    mut on_entry: impl FnMut(Address, LedgerEntry),
+) -> Result<PreHashSet<Address>, String> {
+    let mut decoder = zstd::Decoder::new(reader).map_err(|err| err.to_string())?;
+    let address_deserializer = AddressDeserializer::new();
+    let entry_deserializer = LedgerEntryDeserializer::new(
+        u64::MAX,
+        config.max_key_length,
+        config.max_datastore_value_length,
+    );
+    let mut addresses = PreHashSet::default();
+    let mut record = Vec::new();
+    for index in 0u64.. {
+        let mut length = [0u8; 4];
+        match decoder.read_exact(&mut length) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(format!("record {}: {}", index, err)),
+        }
+        // read through `take` so that a corrupted length does not trigger a huge allocation
+        record.clear();
+        let length = u32::from_be_bytes(length) as u64;
+        (&mut decoder)
+            .take(length)
+            .read_to_end(&mut record)
+            .map_err(|err| format!("record {}: {}", index, err))?;
+        if record.len() as u64 != length {
+            return Err(format!("record {}: unexpected end of file", index));
+        }
+        let (rest, address) = address_deserializer
+            .deserialize::<DeserializeError>(&record)
+            .map_err(|err| format!("record {}: invalid address: {}", index, err))?;
+        let (rest, entry) = entry_deserializer
+            .deserialize::<DeserializeError>(rest)
+            .map_err(|err| format!("record {}: invalid ledger entry: {}", index, err))?;
+        if !rest.is_empty() {
+            return Err(format!("record {}: trailing bytes", index));
+        }
+        check_entry(config, &mut addresses, &address, &entry)
+            .map_err(|err| format!("record {}: {}", index, err))?;
+        on_entry(address, entry);
+    }
+    Ok(addresses)
+}
+
+/// Streams the entries of the initial ledger file `config.initial_ledger_path` to `on_entry`,
+/// after checking them against the ledger limits.
+/// The format of the file (JSON or compressed binary) is detected from its first bytes.
+///
+/// # Returns
+/// The addresses of the loaded entries
+pub fn load_initial_ledger_file(
+    config: &LedgerConfig,
+    on_entry: impl FnMut(Address, LedgerEntry),
+) -> Result<PreHashSet<Address>, LedgerError> {
+    let path = &config.initial_ledger_path;
+    let mut reader = BufReader::new(
+        File::open(path)
+            .map_err(|err| file_error(path, "error loading initial ledger file", err))?,
+    );
+    let is_binary = reader
+        .fill_buf()
+        .map_err(|err| file_error(path, "error loading initial ledger file", err))?
+        .starts_with(&ZSTD_MAGIC);
+    if is_binary {
+        load_binary(config, reader, on_entry)
+            .map_err(|err| file_error(path, "error parsing initial ledger file", err))
+    } else {
+        load_json(config, reader, on_entry)
+            .map_err(|err| file_error(path, "error parsing initial ledger file", err))
+    }
+}
+
+/// Writes ledger entries in the binary format of the initial ledger file
+pub fn write_initial_ledger_binary<'a>(
+    writer: impl Write,
+    entries: impl IntoIterator<Item = (&'a Address, &'a LedgerEntry)>,
+) -> Result<(), LedgerError> {
+    let io_error = |err: std::io::Error| {
+        LedgerError::FileError(format!("error writing initial ledger: {}", err))
+    };
+    let address_serializer = AddressSerializer::new();
+    let entry_serializer = LedgerEntrySerializer::new();
+    let mut encoder = zstd::Encoder::new(writer, COMPRESSION_LEVEL).map_err(io_error)?;
+    let mut record = Vec::new();
+    for (address, entry) in entries {
+        record.clear();
+        address_serializer
+            .serialize(address, &mut record)
+            .and_then(|_| entry_serializer.serialize(entry, &mut record))
+            .map_err(|err| {
+                LedgerError::FileError(format!("error serializing entry {}: {}", address, err))
+            })?;
+        let length = u32::try_from(record.len())
+            .map_err(|_| LedgerError::FileError(format!("entry {} is too large", address)))?;
+        encoder.write_all(&length.to_be_bytes()).map_err(io_error)?;
+        encoder.write_all(&record).map_err(io_error)?;
+    }
+    encoder.finish().map_err(io_error)?;
+    Ok(())
+}
@@ -5,15 +5,20 @@
 mod config;
 mod controller;
 mod error;
+mod genesis_sc;
+mod initial_ledger;
 mod key;
 mod ledger_changes;
 mod ledger_entry;
+#[cfg(feature = "grpc")]
 mod mapping_grpc;
 mod types;
 
 pub use config::LedgerConfig;
 pub use controller::LedgerController;
 pub use error::LedgerError;
+pub use genesis_sc::{genesis_sc_address, load_genesis_smart_contracts, GenesisSmartContract};
+pub use initial_ledger::{load_initial_ledger_file, write_initial_ledger_binary};
 pub use key::{
     datastore_prefix_from_address, Key, KeyDeserializer, KeySerializer, KeyType, BALANCE_IDENT,
     BYTECODE_IDENT, DATASTORE_IDENT, VERSION_IDENT,
@@ -25,8 +30,9 @@ pub use ledger_changes::{
 };
 pub use ledger_entry::{LedgerEntry, LedgerEntryDeserializer, LedgerEntrySerializer};
 pub use types::{
-    Applicable, SetOrDelete, SetOrKeep, SetOrKeepDeserializer, SetOrKeepSerializer,
-    SetUpdateOrDelete, SetUpdateOrDeleteDeserializer, SetUpdateOrDeleteSerializer,
+    Applicable, SetOrDelete, SetOrDeleteDeserializer, SetOrDeleteSerializer, SetOrKeep,
+    SetOrKeepDeserializer, SetOrKeepSerializer, SetUpdateOrDelete, SetUpdateOrDeleteDeserializer,
+    SetUpdateOrDeleteSerializer,
 };
 
 #[cfg(feature = "testing")]
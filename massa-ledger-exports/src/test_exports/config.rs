@@ -17,10 +17,13 @@ impl Default for LedgerConfig {
             // unused by the mock (you can use `LedgerConfig::sample()` to get
             // a NamedTempFile in addition)
             initial_ledger_path: "".into(),
+            initial_genesis_sc_path: None,
             disk_ledger_path: "".into(),
+            max_initial_ledger_entries: 1_000_000,
             thread_count: THREAD_COUNT,
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            commitment_enabled: false,
         }
     }
 }
@@ -39,10 +42,13 @@ impl LedgerConfig {
         (
             Self {
                 initial_ledger_path: initial_ledger.path().to_path_buf(),
+                initial_genesis_sc_path: None,
                 disk_ledger_path: disk_ledger.path().to_path_buf(),
+                max_initial_ledger_entries: 1_000_000,
                 max_key_length: MAX_DATASTORE_KEY_LENGTH,
                 thread_count: THREAD_COUNT,
                 max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+                commitment_enabled: false,
             },
             initial_ledger,
             disk_ledger,
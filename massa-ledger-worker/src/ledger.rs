@@ -2,18 +2,23 @@
 
 //! This file defines the final ledger associating addresses to their balances, bytecode and data.
 
+use crate::ledger_commitment::LedgerCommitment;
 use crate::ledger_db::{LedgerDB, LedgerSubEntry};
-use massa_db_exports::{DBBatch, ShareableMassaDBController};
+use massa_db_exports::{DBBatch, ShareableMassaDBController, LEDGER_PREFIX};
+use massa_hash::Hash;
 use massa_ledger_exports::{
-    LedgerChanges, LedgerConfig, LedgerController, LedgerEntry, LedgerError,
+    load_genesis_smart_contracts, load_initial_ledger_file, LedgerChanges, LedgerConfig,
+    LedgerController, LedgerError,
 };
 use massa_models::{
     address::Address,
     amount::{Amount, AmountDeserializer},
     bytecode::{Bytecode, BytecodeDeserializer},
+    ledger_proof::LedgerEntryProof,
+    prehash::PreHashMap,
 };
 use massa_serialization::{DeserializeError, Deserializer};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::BTreeSet;
 use std::ops::Bound::Included;
 
 /// Represents a final ledger associating addresses to their balances, bytecode and data.
@@ -26,6 +31,10 @@ pub struct FinalLedger {
     pub(crate) config: LedgerConfig,
     /// ledger tree, sorted by address
     pub(crate) sorted_ledger: LedgerDB,
+    /// Merkle commitment over the ledger, `None` if disabled or not computed yet
+    pub(crate) commitment: Option<LedgerCommitment>,
+    /// Number of keys of each non-empty datastore, maintained along with the ledger
+    pub(crate) datastore_key_counts: PreHashMap<Address, u64>,
 }
 
 impl FinalLedger {
@@ -43,37 +52,33 @@ impl FinalLedger {
         FinalLedger {
             sorted_ledger,
             config,
+            commitment: None,
+            datastore_key_counts: Default::default(),
         }
     }
 }
 
 impl LedgerController for FinalLedger {
-    /// Loads ledger from file
+    /// Loads ledger from file, along with the genesis smart contracts
     fn load_initial_ledger(&mut self) -> Result<(), LedgerError> {
-        // load the ledger tree from file
-        let initial_ledger: HashMap<Address, LedgerEntry> = serde_json::from_str(
-            &std::fs::read_to_string(&self.config.initial_ledger_path).map_err(|err| {
-                LedgerError::FileError(format!(
-                    "error loading initial ledger file {}: {}",
-                    self.config
-                        .initial_ledger_path
-                        .to_str()
-                        .unwrap_or("(non-utf8 path)"),
-                    err
-                ))
-            })?,
-        )
-        .map_err(|err| {
-            LedgerError::FileError(format!(
-                "error parsing initial ledger file {}: {}",
-                self.config
-                    .initial_ledger_path
-                    .to_str()
-                    .unwrap_or("(non-utf8 path)"),
-                err
-            ))
+        // stream the entries of the ledger file into a single batch
+        let mut batch = DBBatch::new();
+        let addresses = load_initial_ledger_file(&self.config, |address, entry| {
+            self.sorted_ledger.put_entry(&address, entry, &mut batch)
         })?;
-        self.sorted_ledger.load_initial_ledger(initial_ledger);
+        // deploy the genesis smart contracts at their derived addresses
+        for (name, address, entry) in load_genesis_smart_contracts(&self.config)? {
+            if addresses.contains(&address) {
+                return Err(LedgerError::FileError(format!(
+                    "address {} of genesis smart contract {} is already in the initial ledger",
+                    address, name
+                )));
+            }
+            self.sorted_ledger.put_entry(&address, entry, &mut batch);
+        }
+        self.sorted_ledger.write_initial_batch(batch);
+        self.recompute_commitment();
+        self.recompute_datastore_key_counts();
         Ok(())
     }
 
@@ -142,17 +147,99 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.get_datastore_keys(addr, prefix)
     }
 
+    /// Get the number of keys of the datastore of an address, without listing them.
+    ///
+    /// # Returns
+    /// The number of keys, 0 if the ledger entry was not found
+    fn get_datastore_key_count(&self, addr: &Address) -> u64 {
+        self.datastore_key_counts
+            .get(addr)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Get a page of the keys of the datastore for a given address, in ascending order.
+    ///
+    /// # Returns
+    /// The keys of the page
+    fn get_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Option<Vec<Vec<u8>>> {
+        self.sorted_ledger
+            .get_datastore_keys_page(addr, prefix, cursor, limit)
+    }
+
     /// Reset the disk ledger.
     ///
     /// USED FOR BOOTSTRAP ONLY
     fn reset(&mut self) {
         self.sorted_ledger.reset();
+        // the ledger is about to be bootstrapped: the commitment and the key counts are recomputed afterwards
+        self.commitment = None;
+        self.datastore_key_counts.clear();
     }
 
     /// Allows applying `LedgerChanges` to the final ledger
     fn apply_changes_to_batch(&mut self, changes: LedgerChanges, ledger_batch: &mut DBBatch) {
         self.sorted_ledger
             .apply_changes_to_batch(changes, ledger_batch);
+
+        // count the datastore keys created or deleted by the batch
+        self.sorted_ledger
+            .update_datastore_key_counts(ledger_batch, &mut self.datastore_key_counts);
+
+        // update the commitment with the final value of the ledger keys written by the batch
+        if let Some(commitment) = self.commitment.as_mut() {
+            for (key, value) in ledger_batch
+                .range(LEDGER_PREFIX.as_bytes().to_vec()..)
+                .take_while(|(key, _)| key.starts_with(LEDGER_PREFIX.as_bytes()))
+            {
+                commitment.update(key, value.as_deref());
+            }
+        }
+    }
+
+    /// Recompute the Merkle commitment over the whole ledger, if enabled
+    fn recompute_commitment(&mut self) {
+        if self.config.commitment_enabled {
+            self.commitment = Some(self.sorted_ledger.compute_commitment());
+        }
+    }
+
+    /// Count the keys of every datastore of the ledger
+    fn recompute_datastore_key_counts(&mut self) {
+        self.datastore_key_counts = self.sorted_ledger.compute_datastore_key_counts();
+    }
+
+    /// Get a Merkle proof of the balance of an address, or of one of its datastore entries
+    ///
+    /// # Returns
+    /// The root of the commitment and the proof, or `None` if the commitment is not available
+    fn get_ledger_proof(
+        &self,
+        addr: &Address,
+        key: Option<&[u8]>,
+    ) -> Option<(Hash, LedgerEntryProof)> {
+        let commitment = self.commitment.as_ref()?;
+        let sub_entry = || match key {
+            Some(key) => LedgerSubEntry::Datastore(key.to_vec()),
+            None => LedgerSubEntry::Balance,
+        };
+        let serialized_key = self.sorted_ledger.get_sub_entry_key(addr, sub_entry());
+        let value = self.sorted_ledger.get_sub_entry(addr, sub_entry());
+        let proof = commitment.prove(&serialized_key);
+        Some((
+            commitment.root_hash(),
+            LedgerEntryProof {
+                key: serialized_key,
+                value,
+                proof,
+            },
+        ))
     }
 
     /// Deserializes the key and value, useful after bootstrap
@@ -183,3 +270,334 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.get_entire_datastore(addr)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_db_exports::{MassaDBConfig, MassaDBController};
+    use massa_db_worker::MassaDB;
+    use massa_ledger_exports::{
+        genesis_sc_address, write_initial_ledger_binary, LedgerEntry, LedgerEntryUpdate,
+        SetOrDelete, SetOrKeep, SetUpdateOrDelete,
+    };
+    use massa_signature::KeyPair;
+    use parking_lot::RwLock;
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_genesis_smart_contracts() {
+        let temp_dir = TempDir::new().unwrap();
+        let initial_ledger_path = temp_dir.path().join("initial_ledger.json");
+        std::fs::write(
+            &initial_ledger_path,
+            r#"{
+                "AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x": {
+                    "balance": "1000", "datastore": {}, "bytecode": []
+                }
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("name_service.wasm"), [0, 97, 115, 109]).unwrap();
+        let genesis_sc_path = temp_dir.path().join("genesis_sc.json");
+        std::fs::write(
+            &genesis_sc_path,
+            r#"[{
+                "name": "name_service",
+                "bytecode_path": "name_service.wasm",
+                "balance": "10",
+                "datastore": { "owner": [1, 2, 3] }
+            }]"#,
+        )
+        .unwrap();
+
+        let db_config = MassaDBConfig {
+            path: temp_dir.path().join("db"),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 32,
+            block_cache_size: 0,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+        let config = LedgerConfig {
+            thread_count: 32,
+            initial_ledger_path,
+            initial_genesis_sc_path: Some(genesis_sc_path.clone()),
+            disk_ledger_path: temp_dir.path().join("db"),
+            max_initial_ledger_entries: 10,
+            max_key_length: 255,
+            max_datastore_value_length: 1000,
+            commitment_enabled: false,
+        };
+        let mut ledger = FinalLedger::new(config.clone(), db.clone());
+        ledger.load_initial_ledger().unwrap();
+
+        // the contract is deployed at the address derived from its name
+        let address = genesis_sc_address("name_service");
+        assert!(matches!(address, Address::SC(_)));
+        assert_eq!(
+            ledger.get_balance(&address),
+            Some(Amount::from_str("10").unwrap())
+        );
+        assert_eq!(
+            ledger.get_bytecode(&address).unwrap().0,
+            vec![0, 97, 115, 109]
+        );
+        assert_eq!(
+            ledger.get_data_entry(&address, b"owner"),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            ledger.get_balance(
+                &Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x")
+                    .unwrap()
+            ),
+            Some(Amount::from_str("1000").unwrap())
+        );
+
+        // a contract name cannot be declared twice
+        std::fs::write(
+            &genesis_sc_path,
+            r#"[
+                { "name": "dex", "bytecode_path": "name_service.wasm" },
+                { "name": "dex", "bytecode_path": "name_service.wasm" }
+            ]"#,
+        )
+        .unwrap();
+        let mut ledger = FinalLedger::new(config, db);
+        assert!(ledger.load_initial_ledger().is_err());
+    }
+
+    #[test]
+    fn test_load_initial_ledger_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let address_1 = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let address_2 = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let mut config = LedgerConfig {
+            thread_count: 32,
+            initial_ledger_path: temp_dir.path().join("initial_ledger.json"),
+            initial_genesis_sc_path: None,
+            disk_ledger_path: temp_dir.path().join("db"),
+            max_initial_ledger_entries: 2,
+            max_key_length: 255,
+            max_datastore_value_length: 1000,
+            commitment_enabled: false,
+        };
+        let load = |config: &LedgerConfig| {
+            let mut entries = Vec::new();
+            load_initial_ledger_file(config, |address, entry| entries.push((address, entry)))
+                .map(|_| entries)
+        };
+
+        // errors report the line at which they are detected
+        std::fs::write(
+            &config.initial_ledger_path,
+            format!(
+                "{{\n\"{0}\": {{ \"balance\": \"1\", \"datastore\": {{}}, \"bytecode\": [] }},\n\"{0}\": {{ \"balance\": \"2\", \"datastore\": {{}}, \"bytecode\": [] }}\n}}",
+                address_1
+            ),
+        )
+        .unwrap();
+        let err = load(&config).unwrap_err().to_string();
+        assert!(
+            err.contains("appears twice") && err.contains("line 3"),
+            "{}",
+            err
+        );
+
+        // the number of entries is bounded
+        let entries = std::collections::BTreeMap::from([
+            (address_1, LedgerEntry::default()),
+            (address_2, LedgerEntry::default()),
+        ]);
+        std::fs::write(
+            &config.initial_ledger_path,
+            serde_json::to_string(&entries).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(load(&config).unwrap().len(), 2);
+        config.max_initial_ledger_entries = 1;
+        assert!(load(&config).is_err());
+        config.max_initial_ledger_entries = 2;
+
+        // the binary format is detected and loaded
+        let entry = LedgerEntry {
+            balance: Amount::from_str("10").unwrap(),
+            datastore: std::collections::BTreeMap::from([(b"key".to_vec(), b"value".to_vec())]),
+            ..Default::default()
+        };
+        config.initial_ledger_path = temp_dir.path().join("initial_ledger.bin");
+        write_initial_ledger_binary(
+            std::fs::File::create(&config.initial_ledger_path).unwrap(),
+            [(&address_1, &entry), (&address_2, &LedgerEntry::default())],
+        )
+        .unwrap();
+        assert_eq!(
+            load(&config).unwrap(),
+            vec![(address_1, entry), (address_2, LedgerEntry::default())]
+        );
+    }
+
+    #[test]
+    fn test_ledger_commitment() {
+        let temp_dir = TempDir::new().unwrap();
+        let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let initial_ledger_path = temp_dir.path().join("initial_ledger.json");
+        std::fs::write(
+            &initial_ledger_path,
+            format!(
+                "{{ \"{}\": {{ \"balance\": \"1000\", \"datastore\": {{}}, \"bytecode\": [] }} }}",
+                address
+            ),
+        )
+        .unwrap();
+        let db_config = MassaDBConfig {
+            path: temp_dir.path().join("db"),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 32,
+            block_cache_size: 0,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+        let config = LedgerConfig {
+            thread_count: 32,
+            initial_ledger_path,
+            initial_genesis_sc_path: None,
+            disk_ledger_path: temp_dir.path().join("db"),
+            max_initial_ledger_entries: 10,
+            max_key_length: 255,
+            max_datastore_value_length: 1000,
+            commitment_enabled: true,
+        };
+        let mut ledger = FinalLedger::new(config, db.clone());
+        ledger.load_initial_ledger().unwrap();
+
+        // the balance is proven against the root of the commitment
+        let (root, proof) = ledger.get_ledger_proof(&address, None).unwrap();
+        assert!(proof.verify(&root));
+        let (_, balance) = AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX))
+            .deserialize::<DeserializeError>(proof.value.as_ref().unwrap())
+            .unwrap();
+        assert_eq!(balance, Amount::from_str("1000").unwrap());
+        // and so is the absence of a datastore entry
+        let (_, proof) = ledger.get_ledger_proof(&address, Some(b"key")).unwrap();
+        assert!(proof.value.is_none());
+        assert!(proof.verify(&root));
+
+        // the commitment follows the finalized changes
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            address,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_str("10").unwrap()),
+                datastore: std::collections::BTreeMap::from([(
+                    b"key".to_vec(),
+                    SetOrDelete::Set(b"value".to_vec()),
+                )]),
+                ..Default::default()
+            }),
+        );
+        let mut batch = DBBatch::new();
+        ledger.apply_changes_to_batch(changes, &mut batch);
+        db.write().write_batch(batch, Default::default(), None);
+        let (new_root, proof) = ledger.get_ledger_proof(&address, Some(b"key")).unwrap();
+        assert_ne!(new_root, root);
+        assert_eq!(proof.value, Some(b"value".to_vec()));
+        assert!(proof.verify(&new_root));
+        assert!(!proof.verify(&root));
+
+        // the incremental commitment matches the one computed from scratch
+        ledger.recompute_commitment();
+        assert_eq!(ledger.get_ledger_proof(&address, None).unwrap().0, new_root);
+    }
+
+    #[test]
+    fn test_datastore_key_counts() {
+        let temp_dir = TempDir::new().unwrap();
+        let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let initial_ledger_path = temp_dir.path().join("initial_ledger.json");
+        std::fs::write(
+            &initial_ledger_path,
+            format!(
+                "{{ \"{}\": {{ \"balance\": \"1000\", \"datastore\": {{}}, \"bytecode\": [] }} }}",
+                address
+            ),
+        )
+        .unwrap();
+        let db_config = MassaDBConfig {
+            path: temp_dir.path().join("db"),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 32,
+            block_cache_size: 0,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+        let config = LedgerConfig {
+            thread_count: 32,
+            initial_ledger_path,
+            initial_genesis_sc_path: None,
+            disk_ledger_path: temp_dir.path().join("db"),
+            max_initial_ledger_entries: 10,
+            max_key_length: 255,
+            max_datastore_value_length: 1000,
+            commitment_enabled: false,
+        };
+        let mut ledger = FinalLedger::new(config, db.clone());
+        ledger.load_initial_ledger().unwrap();
+        assert_eq!(ledger.get_datastore_key_count(&address), 0);
+
+        // the count follows the created, overwritten and deleted keys
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            address,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                datastore: std::collections::BTreeMap::from([
+                    (vec![1], SetOrDelete::Set(vec![1])),
+                    (vec![2], SetOrDelete::Set(vec![2])),
+                    (vec![3], SetOrDelete::Set(vec![3])),
+                    (vec![4], SetOrDelete::Delete),
+                ]),
+                ..Default::default()
+            }),
+        );
+        let mut batch = DBBatch::new();
+        ledger.apply_changes_to_batch(changes, &mut batch);
+        db.write().write_batch(batch, Default::default(), None);
+        assert_eq!(ledger.get_datastore_key_count(&address), 3);
+
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            address,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                datastore: std::collections::BTreeMap::from([
+                    (vec![1], SetOrDelete::Set(vec![4])),
+                    (vec![2], SetOrDelete::Delete),
+                ]),
+                ..Default::default()
+            }),
+        );
+        let mut batch = DBBatch::new();
+        ledger.apply_changes_to_batch(changes, &mut batch);
+        db.write().write_batch(batch, Default::default(), None);
+        assert_eq!(ledger.get_datastore_key_count(&address), 2);
+
+        // the incremental count matches the one computed from scratch
+        ledger.recompute_datastore_key_counts();
+        assert_eq!(ledger.get_datastore_key_count(&address), 2);
+
+        // deleting the entry deletes its datastore
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(address, SetUpdateOrDelete::Delete);
+        let mut batch = DBBatch::new();
+        ledger.apply_changes_to_batch(changes, &mut batch);
+        db.write().write_batch(batch, Default::default(), None);
+        assert_eq!(ledger.get_datastore_key_count(&address), 0);
+    }
+}
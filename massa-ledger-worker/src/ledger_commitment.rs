@@ -0,0 +1,220 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Merkle commitment over the final ledger, allowing to prove the value of its entries.
+//! See `massa_models::ledger_proof` for the structure of the tree and the verification of the proofs.
+
+use massa_hash::Hash;
+use massa_models::ledger_proof::{
+    empty_ledger_hash, ledger_key_bit, ledger_leaf_hash, ledger_node_hash, LedgerProof,
+};
+use std::fmt::Debug;
+use std::mem;
+
+/// Node of the compact sparse Merkle tree
+enum Node {
+    /// empty subtree
+    Empty,
+    /// subtree containing a single entry
+    Leaf { key_hash: Hash, value_hash: Hash },
+    /// subtree containing at least two entries
+    Internal {
+        hash: Hash,
+        children: Box<[Node; 2]>,
+    },
+}
+
+impl Node {
+    fn hash(&self) -> Hash {
+        match self {
+            Node::Empty => empty_ledger_hash(),
+            Node::Leaf {
+                key_hash,
+                value_hash,
+            } => ledger_leaf_hash(key_hash, value_hash),
+            Node::Internal { hash, .. } => *hash,
+        }
+    }
+
+    fn internal(children: [Node; 2]) -> Node {
+        Node::Internal {
+            hash: ledger_node_hash(&children[0].hash(), &children[1].hash()),
+            children: Box::new(children),
+        }
+    }
+
+    /// Insert or update an entry in the subtree rooted at `depth`
+    fn insert(self, key_hash: Hash, value_hash: Hash, depth: usize) -> Node {
+        match self {
+            Node::Empty => Node::Leaf {
+                key_hash,
+                value_hash,
+            },
+            Node::Leaf {
+                key_hash: leaf_key, ..
+            } if leaf_key == key_hash => Node::Leaf {
+                key_hash,
+                value_hash,
+            },
+            Node::Leaf {
+                key_hash: leaf_key,
+                value_hash: leaf_value,
+            } => {
+                // push the existing entry one level down, next to the inserted one
+                let mut children = [Node::Empty, Node::Empty];
+                children[ledger_key_bit(&leaf_key, depth) as usize] = Node::Leaf {
+                    key_hash: leaf_key,
+                    value_hash: leaf_value,
+                };
+                Node::Internal {
+                    hash: empty_ledger_hash(),
+                    children: Box::new(children),
+                }
+                .insert(key_hash, value_hash, depth)
+            }
+            Node::Internal { children, .. } => {
+                let mut children = *children;
+                let side = ledger_key_bit(&key_hash, depth) as usize;
+                children[side] = mem::replace(&mut children[side], Node::Empty).insert(
+                    key_hash,
+                    value_hash,
+                    depth + 1,
+                );
+                Node::internal(children)
+            }
+        }
+    }
+
+    /// Remove an entry, if present, from the subtree rooted at `depth`
+    fn remove(self, key_hash: &Hash, depth: usize) -> Node {
+        match self {
+            Node::Leaf {
+                key_hash: leaf_key, ..
+            } if &leaf_key == key_hash => Node::Empty,
+            Node::Internal { children, .. } => {
+                let mut children = *children;
+                let side = ledger_key_bit(key_hash, depth) as usize;
+                children[side] =
+                    mem::replace(&mut children[side], Node::Empty).remove(key_hash, depth + 1);
+                match children {
+                    // a subtree left with a single entry is represented by its leaf
+                    [Node::Empty, Node::Empty] => Node::Empty,
+                    [Node::Empty, leaf @ Node::Leaf { .. }]
+                    | [leaf @ Node::Leaf { .. }, Node::Empty] => leaf,
+                    children => Node::internal(children),
+                }
+            }
+            node => node,
+        }
+    }
+}
+
+/// Merkle commitment over the serialized keys and values of the ledger, updated incrementally
+pub struct LedgerCommitment {
+    root: Node,
+}
+
+impl Default for LedgerCommitment {
+    fn default() -> Self {
+        LedgerCommitment { root: Node::Empty }
+    }
+}
+
+impl Debug for LedgerCommitment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LedgerCommitment {{ root: {} }}", self.root_hash())
+    }
+}
+
+impl LedgerCommitment {
+    /// Root hash committing to the whole ledger
+    pub fn root_hash(&self) -> Hash {
+        self.root.hash()
+    }
+
+    /// Set the value of a serialized ledger key, or remove the key if `value` is `None`
+    pub fn update(&mut self, key: &[u8], value: Option<&[u8]>) {
+        let key_hash = Hash::compute_from(key);
+        let root = mem::replace(&mut self.root, Node::Empty);
+        self.root = match value {
+            Some(value) => root.insert(key_hash, Hash::compute_from(value), 0),
+            None => root.remove(&key_hash, 0),
+        };
+    }
+
+    /// Get the proof of the value, or of the absence, of a serialized ledger key
+    pub fn prove(&self, key: &[u8]) -> LedgerProof {
+        let key_hash = Hash::compute_from(key);
+        let mut siblings = Vec::new();
+        let mut node = &self.root;
+        loop {
+            match node {
+                Node::Internal { children, .. } => {
+                    let side = ledger_key_bit(&key_hash, siblings.len()) as usize;
+                    siblings.push(children[1 - side].hash());
+                    node = &children[side];
+                }
+                Node::Leaf {
+                    key_hash,
+                    value_hash,
+                } => {
+                    return LedgerProof {
+                        siblings,
+                        leaf: Some((*key_hash, *value_hash)),
+                    }
+                }
+                Node::Empty => {
+                    return LedgerProof {
+                        siblings,
+                        leaf: None,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_commitment_proofs() {
+        let mut commitment = LedgerCommitment::default();
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = (0u8..50)
+            .map(|i| (vec![i, i], vec![i; i as usize + 1]))
+            .collect();
+        for (key, value) in entries.iter() {
+            commitment.update(key, Some(value));
+        }
+        let root = commitment.root_hash();
+
+        for (key, value) in entries.iter() {
+            let proof = commitment.prove(key);
+            assert!(proof.verify(&root, key, Some(value)));
+            assert!(!proof.verify(&root, key, Some(&[42])));
+            assert!(!proof.verify(&root, key, None));
+        }
+        for i in 50u8..100 {
+            let key = vec![i, i];
+            let proof = commitment.prove(&key);
+            assert!(proof.verify(&root, &key, None));
+            assert!(!proof.verify(&root, &key, Some(&[i])));
+        }
+
+        // the root only depends on the content of the ledger
+        let mut other = LedgerCommitment::default();
+        for (key, value) in entries.iter().rev() {
+            other.update(&[0, 1], Some(&[2]));
+            other.update(key, Some(&[0]));
+            other.update(key, Some(value));
+        }
+        other.update(&[0, 1], None);
+        assert_eq!(other.root_hash(), root);
+
+        // removing all the entries empties the tree
+        for (key, _) in entries.iter() {
+            commitment.update(key, None);
+        }
+        assert_eq!(commitment.root_hash(), empty_ledger_hash());
+    }
+}
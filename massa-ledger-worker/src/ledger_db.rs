@@ -2,6 +2,7 @@
 
 //! Module to interact with the disk ledger
 
+use crate::ledger_commitment::LedgerCommitment;
 use massa_db_exports::{
     DBBatch, MassaDirection, MassaIteratorMode, ShareableMassaDBController, CRUD_ERROR,
     KEY_SER_ERROR, LEDGER_PREFIX, STATE_CF,
@@ -10,13 +11,14 @@ use massa_ledger_exports::*;
 use massa_models::amount::AmountDeserializer;
 use massa_models::bytecode::BytecodeDeserializer;
 use massa_models::datastore::get_prefix_bounds;
+use massa_models::prehash::PreHashMap;
 use massa_models::{
     address::Address, amount::AmountSerializer, bytecode::BytecodeSerializer, slot::Slot,
 };
 use massa_serialization::{
     DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{hash_map::Entry, BTreeSet, HashMap};
 use std::fmt::Debug;
 
 use massa_models::amount::Amount;
@@ -113,6 +115,11 @@ impl LedgerDB {
             self.put_entry(&address, entry, &mut batch);
         }
 
+        self.write_initial_batch(batch);
+    }
+
+    /// Writes a batch containing the entries of the initial ledger
+    pub fn write_initial_batch(&mut self, batch: DBBatch) {
         self.db.write().write_batch(
             batch,
             Default::default(),
@@ -159,12 +166,88 @@ impl LedgerDB {
     /// An Option of the sub-entry value as bytes
     pub fn get_sub_entry(&self, addr: &Address, ty: LedgerSubEntry) -> Option<Vec<u8>> {
         let db = self.db.read();
+        let serialized_key = self.get_sub_entry_key(addr, ty);
+        db.get_cf(STATE_CF, serialized_key).expect(CRUD_ERROR)
+    }
+
+    /// Get the serialized database key of the given sub-entry of a given address
+    pub fn get_sub_entry_key(&self, addr: &Address, ty: LedgerSubEntry) -> Vec<u8> {
         let key = ty.derive_key(addr);
         let mut serialized_key = Vec::new();
         self.key_serializer_db
             .serialize(&key, &mut serialized_key)
             .expect(KEY_SER_ERROR);
-        db.get_cf(STATE_CF, serialized_key).expect(CRUD_ERROR)
+        serialized_key
+    }
+
+    /// Compute the Merkle commitment over all the entries of the ledger
+    pub fn compute_commitment(&self) -> LedgerCommitment {
+        let db = self.db.read();
+        let mut commitment = LedgerCommitment::default();
+        for (key, value) in db
+            .prefix_iterator_cf(STATE_CF, LEDGER_PREFIX.as_bytes())
+            .take_while(|(key, _)| key.starts_with(LEDGER_PREFIX.as_bytes()))
+        {
+            commitment.update(&key, Some(&value));
+        }
+        commitment
+    }
+
+    /// Count the keys of every datastore of the ledger
+    pub fn compute_datastore_key_counts(&self) -> PreHashMap<Address, u64> {
+        let db = self.db.read();
+        let mut counts = PreHashMap::default();
+        for (serialized_key, _) in db
+            .prefix_iterator_cf(STATE_CF, LEDGER_PREFIX.as_bytes())
+            .take_while(|(key, _)| key.starts_with(LEDGER_PREFIX.as_bytes()))
+        {
+            let (_rest, key) = self
+                .key_deserializer_db
+                .deserialize::<DeserializeError>(&serialized_key)
+                .expect("could not deserialize ledger key from state db");
+            if let KeyType::DATASTORE(_) = key.key_type {
+                *counts.entry(key.address).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Update the number of keys of the datastores with the entries created or deleted by a batch,
+    /// before the batch is written to the disk ledger.
+    pub fn update_datastore_key_counts(
+        &self,
+        batch: &DBBatch,
+        counts: &mut PreHashMap<Address, u64>,
+    ) {
+        let db = self.db.read();
+        for (serialized_key, value) in batch
+            .range(LEDGER_PREFIX.as_bytes().to_vec()..)
+            .take_while(|(key, _)| key.starts_with(LEDGER_PREFIX.as_bytes()))
+        {
+            let (_rest, key) = self
+                .key_deserializer_db
+                .deserialize::<DeserializeError>(serialized_key)
+                .expect("could not deserialize ledger key from batch");
+            if !matches!(key.key_type, KeyType::DATASTORE(_)) {
+                continue;
+            }
+            let existed = db
+                .get_cf(STATE_CF, serialized_key.clone())
+                .expect(CRUD_ERROR)
+                .is_some();
+            match (existed, value.is_some()) {
+                (false, true) => *counts.entry(key.address).or_default() += 1,
+                (true, false) => {
+                    if let Entry::Occupied(mut count) = counts.entry(key.address) {
+                        *count.get_mut() = count.get().saturating_sub(1);
+                        if *count.get() == 0 {
+                            count.remove();
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
     }
 
     /// Get every key of the datastore for a given address.
@@ -172,6 +255,26 @@ impl LedgerDB {
     /// # Returns
     /// A `BTreeSet` of the datastore keys
     pub fn get_datastore_keys(&self, addr: &Address, prefix: &[u8]) -> Option<BTreeSet<Vec<u8>>> {
+        self.get_datastore_keys_page(addr, prefix, None, usize::MAX)
+            .map(|keys| keys.into_iter().collect())
+    }
+
+    /// Get a page of the keys of the datastore for a given address, in ascending order.
+    ///
+    /// # Arguments
+    /// * `prefix`: only the keys starting with this prefix are listed
+    /// * `cursor`: only the keys strictly greater than this key are listed, typically the last key of the previous page
+    /// * `limit`: max number of keys listed
+    ///
+    /// # Returns
+    /// The keys of the page, or `None` if the address does not exist
+    pub fn get_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Option<Vec<Vec<u8>>> {
         let db = self.db.read();
 
         // check if address exists, return None if it does not
@@ -184,13 +287,17 @@ impl LedgerDB {
             db.get_cf(STATE_CF, serialized_key).expect(CRUD_ERROR)?;
         }
 
-        // collect keys starting with prefix
+        // collect keys starting with prefix, from the cursor if it is after the prefix
         let start_prefix = datastore_prefix_from_address(addr, prefix);
         let end_prefix = end_prefix(&start_prefix);
+        let start_key = match cursor {
+            Some(cursor) if cursor >= prefix => datastore_prefix_from_address(addr, cursor),
+            _ => start_prefix,
+        };
         Some(
             db.iterator_cf(
                 STATE_CF,
-                MassaIteratorMode::From(&start_prefix, MassaDirection::Forward),
+                MassaIteratorMode::From(&start_key, MassaDirection::Forward),
             )
             .take_while(|(key, _)| match &end_prefix {
                 Some(end) => key < end,
@@ -206,6 +313,8 @@ impl LedgerDB {
                     _ => None,
                 }
             })
+            .filter(|key| cursor.map_or(true, |cursor| key.as_slice() > cursor))
+            .take(limit)
             .collect(),
         )
     }
@@ -283,7 +392,7 @@ impl LedgerDB {
     /// * `addr`: associated address
     /// * `ledger_entry`: complete entry to be added
     /// * `batch`: the given operation batch to update
-    fn put_entry(&self, addr: &Address, ledger_entry: LedgerEntry, batch: &mut DBBatch) {
+    pub(crate) fn put_entry(&self, addr: &Address, ledger_entry: LedgerEntry, batch: &mut DBBatch) {
         let db = self.db.read();
 
         // Version
@@ -600,6 +709,7 @@ mod tests {
             max_history_length: 10,
             max_new_elements: 100,
             thread_count: 32,
+            block_cache_size: 0,
         };
 
         let db = Arc::new(RwLock::new(
@@ -673,6 +783,39 @@ mod tests {
         assert!(ledger_db.get_entire_datastore(&addr).is_empty());
     }
 
+    #[test]
+    fn test_datastore_keys_page() {
+        let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let (ledger_db, _data) = init_test_ledger(addr);
+
+        assert_eq!(
+            ledger_db.get_datastore_keys_page(&addr, &[], None, 2),
+            Some(vec![b"1".to_vec(), b"2".to_vec()])
+        );
+        assert_eq!(
+            ledger_db.get_datastore_keys_page(&addr, &[], Some(b"2"), 2),
+            Some(vec![b"3".to_vec()])
+        );
+        // the cursor does not need to be an existing key
+        assert_eq!(
+            ledger_db.get_datastore_keys_page(&addr, &[], Some(b"1a"), 10),
+            Some(vec![b"2".to_vec(), b"3".to_vec()])
+        );
+        assert_eq!(
+            ledger_db.get_datastore_keys_page(&addr, b"3", Some(b"0"), 10),
+            Some(vec![b"3".to_vec()])
+        );
+        assert_eq!(
+            ledger_db.get_datastore_keys_page(&addr, b"1", Some(b"2"), 10),
+            Some(vec![])
+        );
+        let unknown = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        assert_eq!(
+            ledger_db.get_datastore_keys_page(&unknown, &[], None, 10),
+            None
+        );
+    }
+
     #[test]
     fn test_end_prefix() {
         assert_eq!(end_prefix(&[5, 6, 7]), Some(vec![5, 6, 8]));
@@ -19,6 +19,9 @@
 //! and can be manipulated using `LedgerChanges` (see `ledger_changes.rs`).
 //! The `FinalLedger` is bootstrapped using tooling available in bootstrap.rs
 //!
+//! ## `ledger_commitment.rs`
+//! Defines the Merkle commitment over the final ledger, producing proofs of its entries.
+//!
 //! ## `ledger_entry.rs`
 //! Represents an entry in the ledger for a given address.
 //! It contains balances, executable bytecode and an arbitrary datastore.
@@ -39,6 +42,7 @@
 #![warn(unused_crate_dependencies)]
 
 mod ledger;
+mod ledger_commitment;
 mod ledger_db;
 
 pub use ledger::FinalLedger;
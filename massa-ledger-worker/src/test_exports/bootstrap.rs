@@ -22,9 +22,15 @@ pub fn create_final_ledger(
         config.max_datastore_value_length,
     );
     ledger_db.load_initial_ledger(initial_ledger);
+    let commitment = config
+        .commitment_enabled
+        .then(|| ledger_db.compute_commitment());
+    let datastore_key_counts = ledger_db.compute_datastore_key_counts();
     FinalLedger {
         config,
         sorted_ledger: ledger_db,
+        commitment,
+        datastore_key_counts,
     }
 }
 
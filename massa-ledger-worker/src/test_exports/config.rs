@@ -20,6 +20,7 @@ impl Default for FinalLedger {
             max_history_length: 10,
             max_new_elements: 100,
             thread_count: THREAD_COUNT,
+            block_cache_size: 0,
         };
         let db = MassaDB::new(db_config);
         let db = LedgerDB::new(
@@ -31,6 +32,8 @@ impl Default for FinalLedger {
         FinalLedger {
             config: Default::default(),
             sorted_ledger: db,
+            commitment: None,
+            datastore_key_counts: Default::default(),
         }
     }
 }
@@ -13,7 +13,7 @@ use std::{
 };
 
 use lazy_static::lazy_static;
-use prometheus::{register_int_gauge, Gauge, Histogram, IntCounter, IntGauge};
+use prometheus::{register_int_gauge, Gauge, Histogram, IntCounter, IntCounterVec, IntGauge, Opts};
 use tokio::sync::oneshot::Sender;
 use tracing::warn;
 
@@ -83,6 +83,8 @@ pub struct MassaMetrics {
     stakers: IntGauge,
     /// number of rolls
     rolls: IntGauge,
+    /// last PoS cycle whose slots are all final
+    last_completed_cycle: IntGauge,
 
     // thread of actual slot
     current_time_thread: IntGauge,
@@ -98,6 +100,8 @@ pub struct MassaMetrics {
     endorsements_pool: IntGauge,
     /// number of elements in the denunciation pool
     denunciations_pool: IntGauge,
+    /// number of operations rejected because their sender had too many pending operations
+    operations_pool_sender_limit_rejected: IntCounter,
 
     // number of autonomous SCs messages in pool
     async_message_pool_size: IntGauge,
@@ -127,14 +131,48 @@ pub struct MassaMetrics {
     /// executed final slot with block (not miss)
     executed_final_slot_with_block: IntCounter,
 
+    /// compiled modules found in the RAM cache of the execution
+    module_cache_hits: IntCounter,
+    /// compiled modules missing from the RAM cache of the execution
+    module_cache_misses: IntCounter,
+
+    /// candidate slot outputs reused from the speculative cache of the execution
+    speculative_cache_hits: IntCounter,
+    /// candidate slots executed because their output was not in the speculative cache
+    speculative_cache_misses: IntCounter,
+    /// approximate memory size of the speculative cache of the execution
+    speculative_cache_memory: IntGauge,
+    /// number of candidate slots cancelled by each blockclique change
+    speculative_rebase_depth: Histogram,
+
     /// total bytes receive by peernet manager
     peernet_total_bytes_received: IntCounter,
     /// total bytes sent by peernet manager
     peernet_total_bytes_sent: IntCounter,
 
+    /// messages exchanged by the protocol and the bootstrap, per direction and message type
+    network_messages: IntCounterVec,
+    /// bytes exchanged by the protocol and the bootstrap, per direction and message type
+    network_message_bytes: IntCounterVec,
+
+    /// compressed size over uncompressed size of the compressed protocol messages sent
+    protocol_compression_ratio_sent: Gauge,
+    /// compressed size over uncompressed size of the compressed protocol messages received
+    protocol_compression_ratio_received: Gauge,
+    /// proportion of the protocol serialization buffers reused instead of allocated
+    protocol_buffer_pool_reuse_rate: Gauge,
+
     /// block slot delay
     block_slot_delay: Histogram,
 
+    // selector
+    /// number of cycles fed to the selector whose draws are not computed yet
+    selector_pending_cycles: IntGauge,
+    /// time between the feeding of a cycle to the selector and the availability of its draws
+    selector_draw_latency: Histogram,
+    /// time spent computing the draws of a cycle
+    selector_draw_duration: Histogram,
+
     /// active in connections peer
     active_in_connections: IntGauge,
     /// active out connections peer
@@ -208,6 +246,11 @@ impl MassaMetrics {
         // stakers
         let stakers = IntGauge::new("stakers", "number of stakers").unwrap();
         let rolls = IntGauge::new("rolls", "number of rolls").unwrap();
+        let last_completed_cycle = IntGauge::new(
+            "last_completed_cycle",
+            "last PoS cycle whose slots are all final",
+        )
+        .unwrap();
 
         let current_time_period =
             IntGauge::new("current_time_period", "period of actual slot").unwrap();
@@ -223,6 +266,41 @@ impl MassaMetrics {
         )
         .unwrap();
 
+        let module_cache_hits = IntCounter::new(
+            "module_cache_hits",
+            "number of compiled modules found in the RAM cache",
+        )
+        .unwrap();
+        let module_cache_misses = IntCounter::new(
+            "module_cache_misses",
+            "number of compiled modules loaded from the disk cache or compiled",
+        )
+        .unwrap();
+
+        let speculative_cache_hits = IntCounter::new(
+            "speculative_cache_hits",
+            "number of candidate slot outputs reused from the speculative cache",
+        )
+        .unwrap();
+        let speculative_cache_misses = IntCounter::new(
+            "speculative_cache_misses",
+            "number of candidate slots executed because their output was not in the speculative cache",
+        )
+        .unwrap();
+        let speculative_cache_memory = IntGauge::new(
+            "speculative_cache_memory",
+            "approximate memory size of the speculative cache (bytes)",
+        )
+        .unwrap();
+        let speculative_rebase_depth = Histogram::with_opts(
+            prometheus::HistogramOpts::new(
+                "speculative_rebase_depth",
+                "number of candidate slots cancelled by a blockclique change",
+            )
+            .buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0]),
+        )
+        .unwrap();
+
         let protocol_tester_success = IntCounter::new(
             "protocol_tester_success",
             "number of times we successfully tested someone",
@@ -250,6 +328,11 @@ impl MassaMetrics {
             "number of elements in the denunciation pool",
         )
         .unwrap();
+        let operations_pool_sender_limit_rejected = IntCounter::new(
+            "operations_pool_sender_limit_rejected",
+            "number of operations rejected because their sender had too many pending operations",
+        )
+        .unwrap();
 
         let async_message_pool_size = IntGauge::new(
             "async_message_pool_size",
@@ -397,6 +480,39 @@ impl MassaMetrics {
         let peernet_total_bytes_sent =
             IntCounter::new("peernet_total_bytes_sent", "total byte sent by peernet").unwrap();
 
+        let network_messages = IntCounterVec::new(
+            Opts::new(
+                "network_messages",
+                "number of protocol and bootstrap messages, per direction and message type",
+            ),
+            &["direction", "type"],
+        )
+        .unwrap();
+        let network_message_bytes = IntCounterVec::new(
+            Opts::new(
+                "network_message_bytes",
+                "bytes of the protocol and bootstrap messages, per direction and message type",
+            ),
+            &["direction", "type"],
+        )
+        .unwrap();
+
+        let protocol_compression_ratio_sent = Gauge::new(
+            "protocol_compression_ratio_sent",
+            "compression ratio of the protocol messages sent",
+        )
+        .unwrap();
+        let protocol_compression_ratio_received = Gauge::new(
+            "protocol_compression_ratio_received",
+            "compression ratio of the protocol messages received",
+        )
+        .unwrap();
+        let protocol_buffer_pool_reuse_rate = Gauge::new(
+            "protocol_buffer_pool_reuse_rate",
+            "proportion of the protocol serialization buffers reused instead of allocated",
+        )
+        .unwrap();
+
         let operations_final_counter =
             IntCounter::new("operations_final_counter", "total final operations").unwrap();
 
@@ -407,6 +523,28 @@ impl MassaMetrics {
         )
         .unwrap();
 
+        let selector_pending_cycles = IntGauge::new(
+            "selector_pending_cycles",
+            "number of cycles fed to the selector whose draws are not computed yet",
+        )
+        .unwrap();
+        let selector_draw_latency = Histogram::with_opts(
+            prometheus::HistogramOpts::new(
+                "selector_draw_latency",
+                "time between the feeding of a cycle to the selector and the availability of its draws (seconds)",
+            )
+            .buckets(vec![0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0]),
+        )
+        .unwrap();
+        let selector_draw_duration = Histogram::with_opts(
+            prometheus::HistogramOpts::new(
+                "selector_draw_duration",
+                "time spent computing the draws of a cycle (seconds)",
+            )
+            .buckets(vec![0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0]),
+        )
+        .unwrap();
+
         let mut stopper = MetricsStopper::default();
 
         if enabled {
@@ -437,13 +575,25 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(endorsement_cache_known_by_peer.clone()));
                 let _ = prometheus::register(Box::new(peernet_total_bytes_received.clone()));
                 let _ = prometheus::register(Box::new(peernet_total_bytes_sent.clone()));
+                let _ = prometheus::register(Box::new(network_messages.clone()));
+                let _ = prometheus::register(Box::new(network_message_bytes.clone()));
+                let _ = prometheus::register(Box::new(protocol_compression_ratio_sent.clone()));
+                let _ = prometheus::register(Box::new(protocol_compression_ratio_received.clone()));
+                let _ = prometheus::register(Box::new(protocol_buffer_pool_reuse_rate.clone()));
                 let _ = prometheus::register(Box::new(operations_final_counter.clone()));
                 let _ = prometheus::register(Box::new(stakers.clone()));
                 let _ = prometheus::register(Box::new(rolls.clone()));
+                let _ = prometheus::register(Box::new(last_completed_cycle.clone()));
                 let _ = prometheus::register(Box::new(know_peers.clone()));
                 let _ = prometheus::register(Box::new(banned_peers.clone()));
                 let _ = prometheus::register(Box::new(executed_final_slot.clone()));
                 let _ = prometheus::register(Box::new(executed_final_slot_with_block.clone()));
+                let _ = prometheus::register(Box::new(module_cache_hits.clone()));
+                let _ = prometheus::register(Box::new(module_cache_misses.clone()));
+                let _ = prometheus::register(Box::new(speculative_cache_hits.clone()));
+                let _ = prometheus::register(Box::new(speculative_cache_misses.clone()));
+                let _ = prometheus::register(Box::new(speculative_cache_memory.clone()));
+                let _ = prometheus::register(Box::new(speculative_rebase_depth.clone()));
                 let _ = prometheus::register(Box::new(active_history.clone()));
                 let _ = prometheus::register(Box::new(bootstrap_counter.clone()));
                 let _ = prometheus::register(Box::new(bootstrap_success.clone()));
@@ -452,6 +602,8 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(operations_pool.clone()));
                 let _ = prometheus::register(Box::new(endorsements_pool.clone()));
                 let _ = prometheus::register(Box::new(denunciations_pool.clone()));
+                let _ =
+                    prometheus::register(Box::new(operations_pool_sender_limit_rejected.clone()));
                 let _ = prometheus::register(Box::new(protocol_tester_success.clone()));
                 let _ = prometheus::register(Box::new(protocol_tester_failed.clone()));
                 let _ = prometheus::register(Box::new(sc_messages_final.clone()));
@@ -459,6 +611,9 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(current_time_period.clone()));
                 let _ = prometheus::register(Box::new(current_time_thread.clone()));
                 let _ = prometheus::register(Box::new(block_slot_delay.clone()));
+                let _ = prometheus::register(Box::new(selector_pending_cycles.clone()));
+                let _ = prometheus::register(Box::new(selector_draw_latency.clone()));
+                let _ = prometheus::register(Box::new(selector_draw_duration.clone()));
 
                 stopper = server::bind_metrics(addr);
             }
@@ -471,12 +626,14 @@ impl MassaMetrics {
                 consensus_vec,
                 stakers,
                 rolls,
+                last_completed_cycle,
                 current_time_thread,
                 current_time_period,
                 active_history,
                 operations_pool,
                 endorsements_pool,
                 denunciations_pool,
+                operations_pool_sender_limit_rejected,
                 async_message_pool_size,
                 sc_messages_final,
                 bootstrap_counter,
@@ -488,9 +645,23 @@ impl MassaMetrics {
                 protocol_banned_peers: banned_peers,
                 executed_final_slot,
                 executed_final_slot_with_block,
+                module_cache_hits,
+                module_cache_misses,
+                speculative_cache_hits,
+                speculative_cache_misses,
+                speculative_cache_memory,
+                speculative_rebase_depth,
                 peernet_total_bytes_received,
                 peernet_total_bytes_sent,
+                network_messages,
+                network_message_bytes,
+                protocol_compression_ratio_sent,
+                protocol_compression_ratio_received,
+                protocol_buffer_pool_reuse_rate,
                 block_slot_delay,
+                selector_pending_cycles,
+                selector_draw_latency,
+                selector_draw_duration,
                 active_in_connections,
                 active_out_connections,
                 operations_final_counter,
@@ -615,6 +786,44 @@ impl MassaMetrics {
         self.peernet_total_bytes_sent.inc_by(diff);
     }
 
+    /// Sets the number of protocol messages of a type exchanged in a direction ("sent" or "received")
+    /// since the node started, and their bytes
+    pub fn set_protocol_message_traffic(
+        &self,
+        direction: &str,
+        message_type: &str,
+        messages: u64,
+        bytes: u64,
+    ) {
+        let messages_counter = self
+            .network_messages
+            .with_label_values(&[direction, message_type]);
+        messages_counter.inc_by(messages.saturating_sub(messages_counter.get()));
+        let bytes_counter = self
+            .network_message_bytes
+            .with_label_values(&[direction, message_type]);
+        bytes_counter.inc_by(bytes.saturating_sub(bytes_counter.get()));
+    }
+
+    /// Counts the messages exchanged in a direction ("sent" or "received") during a bootstrap session, and their bytes
+    pub fn inc_bootstrap_traffic(&self, direction: &str, messages: u64, bytes: u64) {
+        self.network_messages
+            .with_label_values(&[direction, "bootstrap"])
+            .inc_by(messages);
+        self.network_message_bytes
+            .with_label_values(&[direction, "bootstrap"])
+            .inc_by(bytes);
+    }
+
+    pub fn set_protocol_compression_ratios(&self, sent: f64, received: f64) {
+        self.protocol_compression_ratio_sent.set(sent);
+        self.protocol_compression_ratio_received.set(received);
+    }
+
+    pub fn set_protocol_buffer_pool_reuse_rate(&self, rate: f64) {
+        self.protocol_buffer_pool_reuse_rate.set(rate);
+    }
+
     pub fn inc_operations_final_counter(&self, diff: u64) {
         self.operations_final_counter.inc_by(diff);
     }
@@ -635,6 +844,27 @@ impl MassaMetrics {
         self.executed_final_slot_with_block.inc();
     }
 
+    pub fn inc_module_cache_hits_and_misses(&self, hits: u64, misses: u64) {
+        self.module_cache_hits.inc_by(hits);
+        self.module_cache_misses.inc_by(misses);
+    }
+
+    pub fn inc_speculative_cache_hits(&self) {
+        self.speculative_cache_hits.inc();
+    }
+
+    pub fn inc_speculative_cache_misses(&self) {
+        self.speculative_cache_misses.inc();
+    }
+
+    pub fn set_speculative_cache_memory(&self, size: usize) {
+        self.speculative_cache_memory.set(size as i64);
+    }
+
+    pub fn observe_speculative_rebase_depth(&self, depth: usize) {
+        self.speculative_rebase_depth.observe(depth as f64);
+    }
+
     pub fn set_active_history(&self, nb: usize) {
         self.active_history.set(nb as i64);
     }
@@ -663,6 +893,10 @@ impl MassaMetrics {
         self.denunciations_pool.set(nb as i64);
     }
 
+    pub fn inc_operations_pool_sender_limit_rejected(&self, nb: usize) {
+        self.operations_pool_sender_limit_rejected.inc_by(nb as u64);
+    }
+
     pub fn inc_protocol_tester_success(&self) {
         self.protocol_tester_success.inc();
     }
@@ -679,6 +913,10 @@ impl MassaMetrics {
         self.rolls.set(nb as i64);
     }
 
+    pub fn set_last_completed_cycle(&self, cycle: u64) {
+        self.last_completed_cycle.set(cycle as i64);
+    }
+
     pub fn inc_sc_messages_final_by(&self, diff: usize) {
         self.sc_messages_final.inc_by(diff as u64);
     }
@@ -703,6 +941,16 @@ impl MassaMetrics {
         self.block_slot_delay.observe(delay);
     }
 
+    pub fn set_selector_pending_cycles(&self, nb: usize) {
+        self.selector_pending_cycles.set(nb as i64);
+    }
+
+    /// Records the latency and the computation time (in seconds) of the draws of a cycle
+    pub fn observe_selector_draws(&self, latency: f64, duration: f64) {
+        self.selector_draw_latency.observe(latency);
+        self.selector_draw_duration.observe(duration);
+    }
+
     /// Update the bandwidth metrics for all peers
     /// HashMap<peer_id, (tx, rx)>
     pub fn update_peers_tx_rx(&self, data: HashMap<String, (u64, u64)>) {
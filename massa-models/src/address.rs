@@ -202,10 +202,7 @@ impl<'de> ::serde::Deserialize<'de> for Address {
                 where
                     E: ::serde::de::Error,
                 {
-                    AddressDeserializer::new()
-                        .deserialize::<DeserializeError>(v)
-                        .map_err(E::custom)
-                        .map(|r| r.1)
+                    Address::from_prefixed_bytes(v).map_err(E::custom)
                 }
             }
 
@@ -261,6 +258,22 @@ impl Address {
             Address::SC(addr) => addr.to_prefixed_bytes(),
         }
     }
+
+    /// Deserialize an address written by `to_prefixed_bytes`.
+    /// Fails if the buffer contains anything else.
+    pub fn from_prefixed_bytes(data: &[u8]) -> Result<Address, ModelsError> {
+        let (rest, address): (&[u8], Address) = AddressDeserializer::new()
+            .deserialize::<DeserializeError>(data)
+            .map_err(|err| ModelsError::AddressParseError(err.to_string()))?;
+        if rest.is_empty() {
+            Ok(address)
+        } else {
+            Err(ModelsError::AddressParseError(format!(
+                "{} trailing bytes after the address",
+                rest.len()
+            )))
+        }
+    }
 }
 
 impl UserAddress {
@@ -658,6 +671,10 @@ pub struct ExecutionAddressCycleInfo {
     pub nok_count: u64,
     /// number of active rolls the address had at that cycle (if still available)
     pub active_rolls: Option<u64>,
+    /// number of additional misses in that cycle that would trigger the implicit sale of the rolls of the address,
+    /// 0 if the sale is already due. None if the cycle is final or if misses cannot trigger the sale
+    #[serde(default)]
+    pub misses_before_roll_sale: Option<u64>,
 }
 
 #[cfg(test)]
@@ -690,4 +707,26 @@ mod test {
 
         assert_ne!(thread_addr_0, thread_addr_1);
     }
+    #[test]
+    fn test_address_from_prefixed_bytes() {
+        let hash = massa_hash::Hash::compute_from("ADDR".as_bytes());
+        for address in [
+            Address::User(UserAddress::UserAddressV0(UserAddressV0(hash))),
+            Address::SC(SCAddress::SCAddressV0(SCAddressV0(hash))),
+        ] {
+            let mut bytes = address.to_prefixed_bytes();
+            assert_eq!(Address::from_prefixed_bytes(&bytes).unwrap(), address);
+            assert_eq!(Address::from_str(&address.to_string()).unwrap(), address);
+
+            bytes.push(0);
+            assert!(matches!(
+                Address::from_prefixed_bytes(&bytes),
+                Err(ModelsError::AddressParseError(_))
+            ));
+            assert!(matches!(
+                Address::from_prefixed_bytes(&bytes[..10]),
+                Err(ModelsError::AddressParseError(_))
+            ));
+        }
+    }
 }
@@ -15,6 +15,34 @@ use std::str::FromStr;
 pub const AMOUNT_DECIMAL_SCALE: u32 = 9;
 /// Decimals factor for the amount
 pub const AMOUNT_DECIMAL_FACTOR: u64 = 10u64.pow(AMOUNT_DECIMAL_SCALE);
+/// Expected format of the amounts written as strings
+const AMOUNT_FORMAT: &str =
+    "expected digits with an optional '.' decimal separator, optionally followed by MAS or nMAS";
+
+/// Units in which an amount can be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountUnit {
+    /// coins, the default unit
+    Mas,
+    /// 1/`AMOUNT_DECIMAL_FACTOR` coin, the smallest unit
+    NanoMas,
+}
+
+impl AmountUnit {
+    /// Suffix of the amounts written in this unit
+    pub const fn suffix(&self) -> &'static str {
+        match self {
+            AmountUnit::Mas => "MAS",
+            AmountUnit::NanoMas => "nMAS",
+        }
+    }
+}
+
+impl fmt::Display for AmountUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.suffix())
+    }
+}
 
 /// A structure representing a decimal Amount of coins with safe operations
 /// this allows ensuring that there is never an uncontrolled overflow or precision loss
@@ -123,6 +151,23 @@ impl Amount {
         Amount::from_decimal(res)
     }
 
+    /// Formats the amount in the given unit, followed by the unit suffix.
+    /// The decimal separator is always a dot and there is no thousands separator, whatever the locale.
+    ///
+    /// ```
+    /// # use massa_models::amount::{Amount, AmountUnit};
+    /// # use std::str::FromStr;
+    /// let amount = Amount::from_str("1.5").unwrap();
+    /// assert_eq!(amount.to_string_with_unit(AmountUnit::Mas), "1.5 MAS");
+    /// assert_eq!(amount.to_string_with_unit(AmountUnit::NanoMas), "1500000000 nMAS");
+    /// ```
+    pub fn to_string_with_unit(&self, unit: AmountUnit) -> String {
+        match unit {
+            AmountUnit::Mas => format!("{} {}", self, unit),
+            AmountUnit::NanoMas => format!("{} {}", self.0, unit),
+        }
+    }
+
     /// Obtains the underlying raw `u64` representation
     /// Warning: do not use this unless you know what you are doing
     /// because the raw value does not take the `AMOUNT_DECIMAL_FACTOR` into account.
@@ -278,26 +323,76 @@ impl fmt::Debug for Amount {
     }
 }
 
-/// build an Amount from decimal string form (like "10.33")
-/// note that this will fail if the string format is invalid
+/// build an Amount from decimal string form (like "10.33"), optionally followed by a unit suffix
+/// (like "1.5 MAS" or "1500000nMAS", see `AmountUnit`). Amounts without unit are in MAS.
+/// The format does not depend on the locale: the decimal separator is a dot,
+/// and signs, exponents, thousands separators and surrounding spaces are rejected.
+/// Note that this will fail if the string format is invalid
 /// or if the conversion would cause an overflow, underflow or precision loss
 ///
 /// ```
 /// # use massa_models::amount::Amount;
 /// # use std::str::FromStr;
 /// assert!(Amount::from_str("11.1").is_ok());
+/// assert_eq!(Amount::from_str("1.5 MAS").unwrap(), Amount::from_str("1.5").unwrap());
+/// assert_eq!(Amount::from_str("1500000 nMAS").unwrap(), Amount::from_str("0.0015").unwrap());
+/// assert_eq!(Amount::from_str("1500000nMAS").unwrap(), Amount::from_raw(1500000));
 /// assert!(Amount::from_str("11.1111111111111111111111").is_err());
 /// assert!(Amount::from_str("1111111111111111111111").is_err());
 /// assert!(Amount::from_str("-11.1").is_err());
 /// assert!(Amount::from_str("abc").is_err());
+/// assert!(Amount::from_str("1,5").is_err());
+/// assert!(Amount::from_str("1_000").is_err());
+/// assert!(Amount::from_str("1e3").is_err());
+/// assert!(Amount::from_str(".5").is_err());
+/// assert!(Amount::from_str("1.5 nMAS").is_err());
+/// assert!(Amount::from_str("1.5 mas").is_err());
 /// ```
 impl FromStr for Amount {
     type Err = ModelsError;
 
     fn from_str(str_amount: &str) -> Result<Self, Self::Err> {
-        let res = Decimal::from_str_exact(str_amount)
-            .map_err(|err| ModelsError::AmountParseError(err.to_string()))?;
-        Amount::from_decimal(res)
+        let invalid = |reason: &str| {
+            ModelsError::AmountParseError(format!("invalid amount \"{}\": {}", str_amount, reason))
+        };
+
+        // split the number from its unit suffix
+        let (number, unit) = match str_amount.find(|c: char| !c.is_ascii_digit() && c != '.') {
+            None => (str_amount, AmountUnit::Mas),
+            Some(index) => {
+                let (number, suffix) = str_amount.split_at(index);
+                let unit = match suffix.strip_prefix(' ').unwrap_or(suffix) {
+                    "MAS" => AmountUnit::Mas,
+                    "nMAS" => AmountUnit::NanoMas,
+                    _ => return Err(invalid(AMOUNT_FORMAT)),
+                };
+                (number, unit)
+            }
+        };
+
+        let mut parts = number.split('.');
+        let integer_part = parts.next().unwrap_or_default();
+        let decimal_part = parts.next();
+        if integer_part.is_empty() || decimal_part == Some("") || parts.next().is_some() {
+            return Err(invalid(AMOUNT_FORMAT));
+        }
+
+        match unit {
+            AmountUnit::Mas => {
+                let res = Decimal::from_str_exact(number)
+                    .map_err(|err| ModelsError::AmountParseError(err.to_string()))?;
+                Amount::from_decimal(res)
+            }
+            AmountUnit::NanoMas => {
+                if decimal_part.is_some() {
+                    return Err(invalid("nMAS amounts cannot have decimals"));
+                }
+                number
+                    .parse()
+                    .map(Amount)
+                    .map_err(|_| invalid("amount is too large to be represented as u64"))
+            }
+        }
     }
 }
 
@@ -420,7 +515,7 @@ impl<'de> serde::de::Visitor<'de> for AmountVisitor {
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
             formatter,
-            "an Amount type representing a fixed-point currency amount"
+            "an Amount type representing a fixed-point currency amount, optionally followed by MAS or nMAS"
         )
     }
 }
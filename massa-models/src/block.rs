@@ -186,6 +186,7 @@ impl Serializer<Block> for BlockSerializer {
     ///             .unwrap(),
     ///         ],
     ///         denunciations: Vec::new(),
+    ///         reward_recipient: None,
     ///     },
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
@@ -289,6 +290,7 @@ impl Deserializer<Block> for BlockDeserializer {
     ///             .unwrap(),
     ///         ],
     ///         denunciations: Vec::new(),
+    ///         reward_recipient: None,
     ///     },
     ///     BlockHeaderSerializer::new(),
     ///     &keypair,
@@ -482,6 +484,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![endo1, endo2],
                 denunciations: Vec::new(), // FIXME
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -566,6 +569,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -659,6 +663,7 @@ mod test {
                 )
                 .unwrap()],
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -716,6 +721,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -768,6 +774,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -823,6 +830,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -901,6 +909,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements,
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -958,6 +967,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1027,6 +1037,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements,
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1101,6 +1112,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![endo1],
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
@@ -1187,6 +1199,7 @@ mod test {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![endo1, endo2],
                 denunciations: vec![],
+                reward_recipient: None,
             },
             BlockHeaderSerializer::new(),
             &keypair,
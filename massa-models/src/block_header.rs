@@ -1,4 +1,6 @@
+use crate::address::{Address, AddressDeserializer, AddressSerializer};
 use crate::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
+use crate::config::BLOCK_HEADER_REWARD_RECIPIENT_VERSION;
 use crate::denunciation::{Denunciation, DenunciationDeserializer, DenunciationSerializer};
 use crate::endorsement::{
     Endorsement, EndorsementDeserializerLW, EndorsementId, EndorsementSerializer,
@@ -42,6 +44,8 @@ pub struct BlockHeader {
     pub endorsements: Vec<SecureShareEndorsement>,
     /// denunciations
     pub denunciations: Vec<Denunciation>,
+    /// address credited with the rewards and fees of the block instead of its creator, if any
+    pub reward_recipient: Option<Address>,
 }
 
 // TODO: gh-issue #3398
@@ -142,6 +146,7 @@ pub struct BlockHeaderSerializer {
     u32_serializer: U32VarIntSerializer,
     opt_serializer: OptionSerializer<u32, U32VarIntSerializer>,
     block_id_serializer: BlockIdSerializer,
+    reward_recipient_serializer: OptionSerializer<Address, AddressSerializer>,
 }
 
 impl BlockHeaderSerializer {
@@ -155,6 +160,7 @@ impl BlockHeaderSerializer {
             endorsement_content_serializer: EndorsementSerializerLW::new(),
             denunciation_serializer: DenunciationSerializer::new(),
             block_id_serializer: BlockIdSerializer::new(),
+            reward_recipient_serializer: OptionSerializer::new(AddressSerializer::new()),
         }
     }
 }
@@ -209,6 +215,7 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
     ///     .unwrap(),
     ///    ],
     ///   denunciations: vec![],
+    ///   reward_recipient: None,
     /// };
     /// let mut buffer = vec![];
     /// BlockHeaderSerializer::new().serialize(&header, &mut buffer).unwrap();
@@ -236,6 +243,17 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
         // operations merkle root
         buffer.extend(value.operation_merkle_root.to_bytes());
 
+        // reward recipient (only from the network version introducing it)
+        if value.current_version >= BLOCK_HEADER_REWARD_RECIPIENT_VERSION {
+            self.reward_recipient_serializer
+                .serialize(&value.reward_recipient, buffer)?;
+        } else if value.reward_recipient.is_some() {
+            return Err(SerializeError::GeneralError(format!(
+                "reward recipient not supported before network version {}",
+                BLOCK_HEADER_REWARD_RECIPIENT_VERSION
+            )));
+        }
+
         self.u32_serializer.serialize(
             &value.endorsements.len().try_into().map_err(|err| {
                 SerializeError::GeneralError(format!("too many endorsements: {}", err))
@@ -279,6 +297,7 @@ pub struct BlockHeaderDeserializer {
     network_versions_deserializer: U32VarIntDeserializer,
     opt_deserializer: OptionDeserializer<u32, U32VarIntDeserializer>,
     block_id_deserializer: BlockIdDeserializer,
+    reward_recipient_deserializer: OptionDeserializer<Address, AddressDeserializer>,
 }
 
 impl BlockHeaderDeserializer {
@@ -318,6 +337,7 @@ impl BlockHeaderDeserializer {
                 endorsement_count,
             ),
             block_id_deserializer: BlockIdDeserializer::new(),
+            reward_recipient_deserializer: OptionDeserializer::new(AddressDeserializer::new()),
             thread_count,
             endorsement_count,
             last_start_period,
@@ -369,6 +389,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
     ///     .unwrap(),
     ///    ],
     ///    denunciations: vec![],
+    ///    reward_recipient: None,
     /// };
     /// let mut buffer = vec![];
     /// BlockHeaderSerializer::new().serialize(&header, &mut buffer).unwrap();
@@ -383,9 +404,19 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
         &self,
         buffer: &'a [u8],
     ) -> IResult<&'a [u8], BlockHeader, E> {
-        let (rest, (current_version, announced_version, slot, parents, operation_merkle_root)): (
+        let (
+            rest,
+            (
+                current_version,
+                announced_version,
+                slot,
+                parents,
+                operation_merkle_root,
+                reward_recipient,
+            ),
+        ): (
             &[u8],
-            (u32, Option<u32>, Slot, Vec<BlockId>, Hash),
+            (u32, Option<u32>, Slot, Vec<BlockId>, Hash, Option<Address>),
         ) = context("Failed BlockHeader deserialization", |input| {
             let (rest, (current_version, announced_version, slot, parents)) = tuple((
                 context("Failed current_version deserialization", |input| {
@@ -438,9 +469,25 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
                 self.hash_deserializer.deserialize(input)
             })
             .parse(rest)?;
+            let (rest, reward_recipient) =
+                if current_version >= BLOCK_HEADER_REWARD_RECIPIENT_VERSION {
+                    context("Failed reward_recipient deserialization", |input| {
+                        self.reward_recipient_deserializer.deserialize(input)
+                    })
+                    .parse(rest)?
+                } else {
+                    (rest, None)
+                };
             Ok((
                 rest,
-                (current_version, announced_version, slot, parents, merkle),
+                (
+                    current_version,
+                    announced_version,
+                    slot,
+                    parents,
+                    merkle,
+                    reward_recipient,
+                ),
             ))
         })
         .parse(buffer)?;
@@ -454,6 +501,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
                 operation_merkle_root,
                 endorsements: Vec::new(),
                 denunciations: Vec::new(),
+                reward_recipient,
             };
 
             // TODO: gh-issue #3398
@@ -533,6 +581,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
             operation_merkle_root,
             endorsements,
             denunciations,
+            reward_recipient,
         };
 
         // TODO: gh-issue #3398
@@ -578,6 +627,9 @@ impl std::fmt::Display for BlockHeader {
         if self.endorsements.is_empty() {
             writeln!(f, "\tNo endorsements found")?;
         }
+        if let Some(reward_recipient) = self.reward_recipient {
+            writeln!(f, "\tReward recipient: {}", reward_recipient)?;
+        }
         Ok(())
     }
 }
@@ -622,6 +674,7 @@ mod test {
                 && self.operation_merkle_root == other.operation_merkle_root
                 && self.endorsements == other.endorsements
                 && self.denunciations == other.denunciations
+                && self.reward_recipient == other.reward_recipient
         }
     }
 
@@ -654,13 +707,16 @@ mod test {
         let de_b = Denunciation::try_from((&s_endo_1, &s_endo_2)).unwrap();
 
         let block_header_1 = BlockHeader {
-            current_version: 0,
+            current_version: BLOCK_HEADER_REWARD_RECIPIENT_VERSION,
             announced_version: None,
             slot,
             parents: parents_1,
             operation_merkle_root: Hash::compute_from("mno".as_bytes()),
             endorsements: vec![s_endorsement_1],
             denunciations: vec![de_a, de_b],
+            reward_recipient: Some(Address::from_public_key(
+                &KeyPair::generate(0).unwrap().get_public_key(),
+            )),
         };
 
         let mut buffer = Vec::new();
@@ -679,6 +735,60 @@ mod test {
         assert_eq!(block_header_1, block_header_der);
     }
 
+    #[test]
+    fn test_block_header_ser_der_legacy_layout() {
+        let slot = Slot::new(7, 1);
+        let parents: Vec<BlockId> = (0..THREAD_COUNT)
+            .map(|i| BlockId::generate_from_hash(Hash::compute_from(&[i])))
+            .collect();
+        let legacy_header = BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot,
+            parents: parents.clone(),
+            operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+            endorsements: vec![],
+            denunciations: vec![],
+            reward_recipient: None,
+        };
+        let recipient_header = BlockHeader {
+            current_version: BLOCK_HEADER_REWARD_RECIPIENT_VERSION,
+            ..legacy_header.clone()
+        };
+
+        let ser = BlockHeaderSerializer::new();
+        let der = BlockHeaderDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            None,
+        );
+
+        // legacy headers do not carry the reward recipient option byte
+        let mut legacy_buffer = Vec::new();
+        ser.serialize(&legacy_header, &mut legacy_buffer).unwrap();
+        let mut recipient_buffer = Vec::new();
+        ser.serialize(&recipient_header, &mut recipient_buffer)
+            .unwrap();
+        assert_eq!(legacy_buffer.len() + 1, recipient_buffer.len());
+
+        let (rem, legacy_header_der) = der.deserialize::<DeserializeError>(&legacy_buffer).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(legacy_header_der.current_version, 0);
+        assert_eq!(legacy_header, legacy_header_der);
+
+        // a legacy header cannot redirect its rewards
+        let legacy_header_with_recipient = BlockHeader {
+            reward_recipient: Some(Address::from_public_key(
+                &KeyPair::generate(0).unwrap().get_public_key(),
+            )),
+            ..legacy_header
+        };
+        assert!(ser
+            .serialize(&legacy_header_with_recipient, &mut Vec::new())
+            .is_err());
+    }
+
     #[test]
     fn test_verify_sig_batch() {
         let (_slot, _keypair, secured_header_1, secured_header_2, secured_header_3) =
@@ -52,6 +52,27 @@ impl BlockId {
         }
     }
 
+    /// Hash signed by the creator of the block to retract its announcement.
+    /// It is prefixed so that it cannot be mistaken for the hash of a signed block or header.
+    pub fn get_retraction_hash(&self) -> Hash {
+        let mut data = b"block retraction".to_vec();
+        data.extend_from_slice(self.get_hash().to_bytes());
+        Hash::compute_from(&data)
+    }
+
+    /// Builds a block id from its binary representation (version followed by hash),
+    /// as written by `BlockIdSerializer`. Fails if the buffer contains anything else.
+    pub fn from_bytes(data: &[u8]) -> Result<BlockId, ModelsError> {
+        let (rest, block_id) = BlockIdDeserializer::new()
+            .deserialize::<DeserializeError>(data)
+            .map_err(|_| ModelsError::BlockIdParseError)?;
+        if rest.is_empty() {
+            Ok(block_id)
+        } else {
+            Err(ModelsError::BlockIdParseError)
+        }
+    }
+
     /// Generate a version 0 block id from an hash used only for tests
     #[cfg(any(test, feature = "testing"))]
     pub fn generate_from_hash(hash: Hash) -> BlockId {
@@ -134,15 +155,7 @@ impl FromStr for BlockId {
                     .with_check(None)
                     .into_vec()
                     .map_err(|_| ModelsError::BlockIdParseError)?;
-                let block_id_deserializer = BlockIdDeserializer::new();
-                let (rest, block_id) = block_id_deserializer
-                    .deserialize::<DeserializeError>(&decoded_bs58_check[..])
-                    .map_err(|_| ModelsError::OperationIdParseError)?;
-                if rest.is_empty() {
-                    Ok(block_id)
-                } else {
-                    Err(ModelsError::OperationIdParseError)
-                }
+                BlockId::from_bytes(&decoded_bs58_check)
             }
             _ => Err(ModelsError::BlockIdParseError),
         }
@@ -164,11 +177,11 @@ impl FromStr for BlockId {
                 let block_id_deserializer = BlockIdDeserializer::new();
                 let (rest, block_id) = block_id_deserializer
                     .deserialize::<DeserializeError>(&decoded_bs58_check[..])
-                    .map_err(|_| ModelsError::OperationIdParseError)?;
+                    .map_err(|_| ModelsError::BlockIdParseError)?;
                 if rest.is_empty() {
                     Ok(block_id)
                 } else {
-                    Err(ModelsError::OperationIdParseError)
+                    Err(ModelsError::BlockIdParseError)
                 }
             }
             _ => Err(ModelsError::BlockIdParseError),
@@ -101,8 +101,14 @@ pub fn get_period_from_args() -> u64 {
 
 /// Price of a roll in the network
 pub const ROLL_PRICE: Amount = Amount::const_init(100, 0);
+/// Minimal number of cycles between two roll sales of the same address (0 for no cooldown).
+/// At most `POS_SAVED_CYCLES`: the sales are recorded in the cycle history, and forgotten with their cycle
+pub const ROLL_SALE_COOLDOWN_CYCLES: u64 = 0;
 /// Block reward is given for each block creation
 pub const BLOCK_REWARD: Amount = Amount::const_init(102, 2);
+/// Network version from which block headers carry a reward recipient (`MipComponent::Block` version 1).
+/// Headers of older versions keep the legacy layout without it.
+pub const BLOCK_HEADER_REWARD_RECIPIENT_VERSION: u32 = 1;
 /// Cost to store one byte in the ledger
 pub const LEDGER_COST_PER_BYTE: Amount = Amount::const_init(1, 4);
 /// Cost for a base entry default 0.01 MASSA
@@ -131,6 +137,18 @@ pub const MAX_ASYNC_MESSAGE_DATA: u64 = 1_000_000;
 pub const OPERATION_VALIDITY_PERIODS: u64 = 10;
 /// Number of periods of executed operation and denunciation history to keep
 pub const KEEP_EXECUTED_HISTORY_EXTRA_PERIODS: u64 = 10;
+/// Expected number of operations in each filter of pruned executed operations, 0 disables the filters.
+/// Disabled by default: pruned operations have expired, so the filters only add false positives.
+pub const PRUNED_OPS_FILTER_CAPACITY: u64 = 0;
+/// Network version from which pruned executed operations are recorded in the filters.
+/// The filters are part of the final state hash, so they cannot be written before all nodes do.
+pub const PRUNED_OPS_FILTER_VERSION: u32 = 1;
+/// The filters of pruned executed operations target a false positive rate of 2^-x
+pub const PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2: u32 = 20;
+/// Number of expiry periods covered by each filter of pruned executed operations
+pub const PRUNED_OPS_FILTER_RANGE_PERIODS: u64 = PERIODS_PER_CYCLE;
+/// Number of filters of pruned executed operations kept
+pub const PRUNED_OPS_FILTER_RANGE_COUNT: u64 = 4;
 /// cycle duration in periods
 pub const PERIODS_PER_CYCLE: u64 = 128;
 /// Number of periods between two backups
@@ -143,6 +161,10 @@ pub const MAX_BACKUPS_TO_KEEP: Option<usize> = Some(10);
 /// See https://github.com/massalabs/massa/pull/3871
 /// 1 for pruned cycle safety during bootstrap
 pub const POS_SAVED_CYCLES: usize = 7;
+/// Number of cycles between the cycle providing the seed of a draw and the drawn cycle.
+/// The roll distribution of a draw is looked up one cycle before its seed.
+/// `POS_SAVED_CYCLES` must be at least `POS_LOOKBACK_CYCLES + 3`
+pub const POS_LOOKBACK_CYCLES: u64 = 2;
 /// Number of cycle draws saved in the selector cache
 ///
 /// 5 to have a C-2 to C+2 range (6 cycles post-bootstrap give 5 cycle draws)
@@ -181,6 +203,13 @@ pub const MAX_LEDGER_CHANGES_COUNT: u64 =
     100_u32.saturating_mul(MAX_LEDGER_CHANGES_PER_SLOT) as u64;
 /// Maximum number of key/values in the datastore of a ledger entry
 pub const MAX_DATASTORE_ENTRY_COUNT: u64 = u64::MAX;
+/// Maximum number of keys an address can create in its datastore during execution
+pub const MAX_DATASTORE_KEYS_PER_ADDRESS: u64 = 100_000;
+/// Network version from which the number of keys of a datastore is capped by `MAX_DATASTORE_KEYS_PER_ADDRESS`.
+/// Refusing a key changes the outcome of the execution, so the cap cannot apply before all nodes enforce it.
+pub const MAX_DATASTORE_KEYS_PER_ADDRESS_VERSION: u32 = 1;
+/// Gas charged by the node for each datastore key created under the cap, covering the update of the key count
+pub const DATASTORE_KEY_COUNT_GAS_COST: u64 = 200;
 /// Maximum number of key/values in the datastore of a `ExecuteSC` operation
 pub const MAX_OPERATION_DATASTORE_ENTRY_COUNT: u64 = 128;
 /// Maximum length function name in call SC
@@ -240,6 +269,17 @@ pub const POOL_CONTROLLER_DENUNCIATIONS_CHANNEL_SIZE: usize = 1024;
 pub const MAX_GAS_PER_BLOCK: u64 = u32::MAX as u64;
 /// Maximum of GAS allowed for asynchronous messages execution on one slot
 pub const MAX_ASYNC_GAS: u64 = 1_000_000_000;
+/// Maximum of GAS of a single asynchronous message, at most `MAX_ASYNC_GAS`
+pub const MAX_GAS_PER_ASYNC_MESSAGE: u64 = 1_000_000_000;
+/// Maximum of GAS that can be booked by deferred calls in one slot
+pub const DEFERRED_CALL_MAX_SLOT_GAS: u64 = 1_000_000_000;
+/// Maximum number of periods between the registration of a deferred call and its target slot
+pub const DEFERRED_CALL_MAX_FUTURE_PERIODS: u64 = 10 * PERIODS_PER_CYCLE;
+/// Fee per booked GAS of a deferred call targeting an empty slot
+pub const DEFERRED_CALL_BASE_FEE_PER_GAS: Amount = Amount::const_init(1, 9);
+/// Network version from which the deferred calls registered for a slot are executed.
+/// The registration ABIs must be gated by the same version once smart contracts can call them.
+pub const DEFERRED_CALLS_VERSION: u32 = 1;
 /// Maximum event size in bytes
 pub const MAX_EVENT_DATA_SIZE: usize = 50_000;
 
@@ -320,6 +360,13 @@ pub const ROLL_COUNT_TO_SLASH_ON_DENUNCIATION: u64 = 1;
 /// Maximum size of executed denunciations
 pub const MAX_DENUNCIATION_CHANGES_LENGTH: u64 = 1_000;
 
+//
+// Constants for deferred calls
+//
+
+/// Maximum number of deferred call registry changes
+pub const MAX_DEFERRED_CALL_CHANGES: u64 = 100_000;
+
 // Some checks at compile time that should not be ignored!
 #[allow(clippy::assertions_on_constants)]
 const _: () = {
@@ -752,6 +752,7 @@ pub enum DenunciationIndex {
     BlockHeader {
         /// de slot
         slot: Slot,
+        reward_recipient: None,
     },
     /// Variant for Endorsement denunciation index
     Endorsement {
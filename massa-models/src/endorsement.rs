@@ -53,6 +53,21 @@ impl Id for EndorsementId {
     }
 }
 
+impl EndorsementId {
+    /// Builds an endorsement id from its binary representation (version followed by hash),
+    /// as written by `EndorsementIdSerializer`. Fails if the buffer contains anything else.
+    pub fn from_bytes(data: &[u8]) -> Result<EndorsementId, ModelsError> {
+        let (rest, endorsement_id) = EndorsementIdDeserializer::new()
+            .deserialize::<DeserializeError>(data)
+            .map_err(|_| ModelsError::EndorsementIdParseError)?;
+        if rest.is_empty() {
+            Ok(endorsement_id)
+        } else {
+            Err(ModelsError::EndorsementIdParseError)
+        }
+    }
+}
+
 #[transition::impl_version(versions("0"))]
 impl EndorsementId {
     fn get_hash(&self) -> &Hash {
@@ -115,15 +130,7 @@ impl FromStr for EndorsementId {
                     .with_check(None)
                     .into_vec()
                     .map_err(|_| ModelsError::EndorsementIdParseError)?;
-                let endorsement_id_deserializer = EndorsementIdDeserializer::new();
-                let (rest, endorsement_id) = endorsement_id_deserializer
-                    .deserialize::<DeserializeError>(&decoded_bs58_check[..])
-                    .map_err(|_| ModelsError::EndorsementIdParseError)?;
-                if rest.is_empty() {
-                    Ok(endorsement_id)
-                } else {
-                    Err(ModelsError::EndorsementIdParseError)
-                }
+                EndorsementId::from_bytes(&decoded_bs58_check)
             }
             _ => Err(ModelsError::EndorsementIdParseError),
         }
@@ -0,0 +1,137 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Registry of the stable error codes of the node.
+//!
+//! Each error is identified by a numeric code made of the base code of its category
+//! and of an index in that category, so that SDKs and support can tell failures apart
+//! whatever the version of the node and the wording of the messages.
+//! An index is never reused: a removed error keeps its index reserved.
+//! An error wrapping the error of another component reports the code of the wrapped error.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ModelsError;
+
+/// Component in which an error originates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// models, serialization and cryptography
+    Models,
+    /// peer-to-peer protocol
+    Protocol,
+    /// execution of operations and smart contracts
+    Execution,
+    /// block graph
+    Consensus,
+    /// operation and endorsement pool
+    Pool,
+    /// API servers
+    Api,
+}
+
+impl ErrorCategory {
+    /// First code of the category
+    pub fn base_code(self) -> u32 {
+        match self {
+            ErrorCategory::Models => 1000,
+            ErrorCategory::Protocol => 2000,
+            ErrorCategory::Execution => 3000,
+            ErrorCategory::Consensus => 4000,
+            ErrorCategory::Pool => 5000,
+            ErrorCategory::Api => 6000,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ErrorCategory::Models => "models",
+            ErrorCategory::Protocol => "protocol",
+            ErrorCategory::Execution => "execution",
+            ErrorCategory::Consensus => "consensus",
+            ErrorCategory::Pool => "pool",
+            ErrorCategory::Api => "api",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Error with a stable code
+pub trait ErrorCode {
+    /// Category and code of the error
+    fn error_code(&self) -> ErrorCodeInfo;
+}
+
+/// Stable code of an error, sent with the API error responses and written in the logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCodeInfo {
+    /// stable numeric code
+    pub code: u32,
+    /// component in which the error originates
+    pub category: ErrorCategory,
+}
+
+impl ErrorCodeInfo {
+    /// Code of the error of index `index` in `category`
+    pub fn new(category: ErrorCategory, index: u32) -> Self {
+        ErrorCodeInfo {
+            code: category.base_code() + index,
+            category,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCodeInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "E{} ({})", self.code, self.category)
+    }
+}
+
+impl ErrorCode for ModelsError {
+    fn error_code(&self) -> ErrorCodeInfo {
+        let index = match self {
+            ModelsError::HashError => 1,
+            ModelsError::SerializeError(_) => 2,
+            ModelsError::SerializationError(_) => 3,
+            ModelsError::DeserializeError(_) => 4,
+            ModelsError::BufferError(_) => 5,
+            ModelsError::MassaHashError(_) => 6,
+            ModelsError::MassaSignatureError(_) => 7,
+            ModelsError::ThreadOverflowError => 8,
+            ModelsError::PeriodOverflowError => 9,
+            ModelsError::AmountParseError(_) => 10,
+            ModelsError::AddressParseError(_) => 11,
+            ModelsError::NodeIdParseError => 12,
+            ModelsError::BlockIdParseError => 13,
+            ModelsError::OperationIdParseError => 14,
+            ModelsError::EndorsementIdParseError => 15,
+            ModelsError::CheckedOperationError(_) => 16,
+            ModelsError::InvalidVersionError(_) => 17,
+            ModelsError::InvalidLedgerChange(_) => 18,
+            ModelsError::TimeOverflowError => 19,
+            ModelsError::TimeError(_) => 20,
+            ModelsError::InvalidRollUpdate(_) => 21,
+            ModelsError::AmountOverflowError => 22,
+            ModelsError::WrongPrefix(_, _) => 23,
+            ModelsError::OperationPrefixJoinError => 24,
+            ModelsError::OutdatedBootstrapCursor => 25,
+            ModelsError::ErrorRaised(_) => 26,
+        };
+        ErrorCodeInfo::new(ErrorCategory::Models, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        let code = ModelsError::AddressParseError("AU".to_string()).error_code();
+        assert_eq!(code.code, 1011);
+        assert_eq!(code.category, ErrorCategory::Models);
+        assert_eq!(code.to_string(), "E1011 (models)");
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Verification of the proofs produced by the Merkle commitment over the final ledger.
+//!
+//! The commitment is a compact sparse Merkle tree indexed by the hash of the serialized ledger keys:
+//! a subtree containing a single entry is represented by the leaf of that entry,
+//! and an empty subtree by the all-zero hash.
+
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use serde::{Deserialize, Serialize};
+
+/// Hash of an empty subtree of the ledger commitment
+pub fn empty_ledger_hash() -> Hash {
+    Hash::from_bytes(&[0u8; HASH_SIZE_BYTES])
+}
+
+/// Hash of the leaf of a ledger entry, from the hashes of its serialized key and value
+pub fn ledger_leaf_hash(key_hash: &Hash, value_hash: &Hash) -> Hash {
+    Hash::compute_from_tuple(&[
+        [0u8].as_slice(),
+        key_hash.to_bytes().as_slice(),
+        value_hash.to_bytes().as_slice(),
+    ])
+}
+
+/// Hash of an internal node of the ledger commitment, from the hashes of its children
+pub fn ledger_node_hash(left: &Hash, right: &Hash) -> Hash {
+    Hash::compute_from_tuple(&[
+        [1u8].as_slice(),
+        left.to_bytes().as_slice(),
+        right.to_bytes().as_slice(),
+    ])
+}
+
+/// Side of the child to follow at the given depth on the path of a key hash: `false` for left, `true` for right
+pub fn ledger_key_bit(key_hash: &Hash, depth: usize) -> bool {
+    (key_hash.to_bytes()[depth / 8] >> (7 - depth % 8)) & 1 == 1
+}
+
+/// Merkle proof of the presence or absence of a key in the ledger commitment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerProof {
+    /// hashes of the siblings of the nodes on the path of the key, root side first
+    pub siblings: Vec<Hash>,
+    /// `(key hash, value hash)` of the leaf ending the path, if any:
+    /// the proven key for a proof of presence, another key sharing the path for a proof of absence
+    pub leaf: Option<(Hash, Hash)>,
+}
+
+impl LedgerProof {
+    /// Check that the serialized `key` has the serialized `value` in the ledger committed by `root`,
+    /// or that it is absent from it if `value` is `None`.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # use massa_hash::Hash;
+    /// # use massa_models::ledger_proof::{ledger_leaf_hash, LedgerProof};
+    /// // a ledger containing a single entry is committed by the leaf of that entry
+    /// let (key_hash, value_hash) = (Hash::compute_from(b"key"), Hash::compute_from(b"value"));
+    /// let root = ledger_leaf_hash(&key_hash, &value_hash);
+    /// let proof = LedgerProof {
+    ///     siblings: vec![],
+    ///     leaf: Some((key_hash, value_hash)),
+    /// };
+    /// assert!(proof.verify(&root, b"key", Some(&b"value"[..])));
+    /// assert!(!proof.verify(&root, b"key", Some(&b"other value"[..])));
+    /// assert!(!proof.verify(&root, b"key", None));
+    /// // the same proof shows that any other key is absent
+    /// assert!(proof.verify(&root, b"other key", None));
+    /// ```
+    pub fn verify(&self, root: &Hash, key: &[u8], value: Option<&[u8]>) -> bool {
+        if self.siblings.len() > HASH_SIZE_BYTES * 8 {
+            return false;
+        }
+        let key_hash = Hash::compute_from(key);
+        let mut node = match (value, &self.leaf) {
+            (Some(value), Some((leaf_key, leaf_value))) => {
+                if leaf_key != &key_hash || leaf_value != &Hash::compute_from(value) {
+                    return false;
+                }
+                ledger_leaf_hash(leaf_key, leaf_value)
+            }
+            (None, Some((leaf_key, leaf_value))) => {
+                // another key must be alone in the subtree where the proven key would be
+                if leaf_key == &key_hash
+                    || (0..self.siblings.len()).any(|depth| {
+                        ledger_key_bit(leaf_key, depth) != ledger_key_bit(&key_hash, depth)
+                    })
+                {
+                    return false;
+                }
+                ledger_leaf_hash(leaf_key, leaf_value)
+            }
+            (None, None) => empty_ledger_hash(),
+            (Some(_), None) => return false,
+        };
+        for (depth, sibling) in self.siblings.iter().enumerate().rev() {
+            node = if ledger_key_bit(&key_hash, depth) {
+                ledger_node_hash(sibling, &node)
+            } else {
+                ledger_node_hash(&node, sibling)
+            };
+        }
+        &node == root
+    }
+}
+
+/// Proof of the value of an entry of the ledger, or of its absence
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntryProof {
+    /// serialized ledger key
+    pub key: Vec<u8>,
+    /// serialized value, `None` if the key is absent from the ledger
+    pub value: Option<Vec<u8>>,
+    /// Merkle proof
+    pub proof: LedgerProof,
+}
+
+impl LedgerEntryProof {
+    /// Check the proof against a trusted root of the ledger commitment
+    pub fn verify(&self, root: &Hash) -> bool {
+        self.proof.verify(root, &self.key, self.value.as_deref())
+    }
+}
@@ -1,6 +1,8 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 //! All the structures that are used everywhere
 //!
+//! The conversions to the gRPC API types are behind the `grpc` feature, enabled by default.
+//!
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 
@@ -34,11 +36,16 @@ pub mod denunciation;
 pub mod endorsement;
 /// models error
 pub mod error;
+/// stable error codes
+pub mod error_code;
 /// execution related structures
 pub mod execution;
 /// ledger related structures
 pub mod ledger;
+/// Merkle proofs of ledger entries
+pub mod ledger_proof;
 /// mapping grpc
+#[cfg(feature = "grpc")]
 pub mod mapping_grpc;
 /// node related structure
 pub mod node;
@@ -54,6 +61,8 @@ pub mod rolls;
 pub mod secure_share;
 /// serialization
 pub mod serialization;
+/// human-readable previews of the objects to sign
+pub mod signing_preview;
 /// slots
 pub mod slot;
 /// various statistics
@@ -159,15 +159,7 @@ impl FromStr for OperationId {
                     .with_check(None)
                     .into_vec()
                     .map_err(|_| ModelsError::OperationIdParseError)?;
-                let operation_id_deserializer = OperationIdDeserializer::new();
-                let (rest, op_id) = operation_id_deserializer
-                    .deserialize::<DeserializeError>(&decoded_bs58_check[..])
-                    .map_err(|_| ModelsError::OperationIdParseError)?;
-                if rest.is_empty() {
-                    Ok(op_id)
-                } else {
-                    Err(ModelsError::OperationIdParseError)
-                }
+                OperationId::from_bytes(&decoded_bs58_check)
             }
             _ => Err(ModelsError::OperationIdParseError),
         }
@@ -233,6 +225,19 @@ impl From<&OperationPrefixId> for Vec<u8> {
 }
 
 impl OperationId {
+    /// Builds an operation id from its binary representation (version followed by hash),
+    /// as written by `OperationIdSerializer`. Fails if the buffer contains anything else.
+    pub fn from_bytes(data: &[u8]) -> Result<OperationId, ModelsError> {
+        let (rest, op_id) = OperationIdDeserializer::new()
+            .deserialize::<DeserializeError>(data)
+            .map_err(|_| ModelsError::OperationIdParseError)?;
+        if rest.is_empty() {
+            Ok(op_id)
+        } else {
+            Err(ModelsError::OperationIdParseError)
+        }
+    }
+
     /// convert the [`OperationId`] into a [`OperationPrefixId`]
     pub fn into_prefix(self) -> OperationPrefixId {
         match self {
@@ -1699,4 +1704,26 @@ mod tests {
 
         assert_eq!(op.get_validity_range(10), 40..=50);
     }
+    #[test]
+    fn test_operation_id_from_bytes() {
+        let op_id = OperationId::new(Hash::compute_from(b"op"));
+        let mut bytes = Vec::new();
+        OperationIdSerializer::new()
+            .serialize(&op_id, &mut bytes)
+            .unwrap();
+        assert_eq!(OperationId::from_bytes(&bytes).unwrap(), op_id);
+        assert_eq!(OperationId::from_str(&op_id.to_string()).unwrap(), op_id);
+
+        bytes.push(0);
+        assert!(matches!(
+            OperationId::from_bytes(&bytes),
+            Err(ModelsError::OperationIdParseError)
+        ));
+        assert!(matches!(
+            OperationId::from_bytes(&bytes[..10]),
+            Err(ModelsError::OperationIdParseError)
+        ));
+        // a block id string is not an operation id
+        assert!(OperationId::from_str(&op_id.to_string().replacen('O', "B", 1)).is_err());
+    }
 }
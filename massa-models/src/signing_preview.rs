@@ -0,0 +1,233 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Human-readable preview of an object before signing it.
+//!
+//! A signer only sees a hash, so it cannot tell what it is signing. The preview lists the fields of the object
+//! in a canonical, readable form, together with a digest of the hash that will be signed.
+//! The digest is separated per object type, so that the digest shown for an operation
+//! can never be the one of an endorsement or a block header.
+
+use crate::{
+    address::Address,
+    amount::Amount,
+    error::ModelsError,
+    operation::{Operation, OperationSerializer, OperationType},
+    secure_share::SecureShareContent,
+};
+use massa_hash::Hash;
+use massa_serialization::Serializer;
+use massa_signature::PublicKey;
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Type of a signed object, separating the signing digests of the different types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SigningDomain {
+    /// operation
+    Operation,
+    /// endorsement
+    Endorsement,
+    /// block header
+    BlockHeader,
+}
+
+impl SigningDomain {
+    /// Tag prepended to the signed hash when computing the digest
+    fn tag(&self) -> &'static [u8] {
+        match self {
+            SigningDomain::Operation => b"MASSA_SIGNING_OPERATION",
+            SigningDomain::Endorsement => b"MASSA_SIGNING_ENDORSEMENT",
+            SigningDomain::BlockHeader => b"MASSA_SIGNING_BLOCK_HEADER",
+        }
+    }
+
+    /// Digest of the hash signed for an object of this type, shown to the signer
+    ///
+    /// ## Example
+    /// ```rust
+    /// # use massa_hash::Hash;
+    /// # use massa_models::signing_preview::SigningDomain;
+    /// let signed_hash = Hash::compute_from(b"signed content");
+    /// assert_eq!(
+    ///     SigningDomain::Operation.digest(&signed_hash),
+    ///     SigningDomain::Operation.digest(&signed_hash)
+    /// );
+    /// assert_ne!(
+    ///     SigningDomain::Operation.digest(&signed_hash),
+    ///     SigningDomain::Endorsement.digest(&signed_hash)
+    /// );
+    /// ```
+    pub fn digest(&self, signed_hash: &Hash) -> Hash {
+        Hash::compute_from_tuple(&[self.tag(), signed_hash.to_bytes().as_slice()])
+    }
+}
+
+/// Canonical human-readable preview of an operation, shown before signing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationSigningPreview {
+    /// type of operation
+    pub operation_type: String,
+    /// address of the creator of the operation
+    pub creator: Address,
+    /// recipient of the coins or target smart contract
+    pub recipient: Option<Address>,
+    /// coins transferred to the recipient, or spent at most by a smart contract execution
+    pub amount: Option<Amount>,
+    /// number of rolls bought or sold
+    pub roll_count: Option<u64>,
+    /// function of the target smart contract
+    pub function: Option<String>,
+    /// size in bytes of the bytecode or of the call parameter
+    pub data_size: Option<usize>,
+    /// maximum gas
+    pub max_gas: Option<u64>,
+    /// fee
+    pub fee: Amount,
+    /// last period at which the operation can be included in a block
+    pub expire_period: u64,
+    /// estimated time of the expiry, if known
+    pub expire_timestamp: Option<MassaTime>,
+    /// digest of the hash that will be signed, in the operation signing domain
+    pub digest: Hash,
+}
+
+impl OperationSigningPreview {
+    /// Builds the preview of an operation to be signed with the given public key
+    pub fn new(operation: &Operation, public_key: &PublicKey) -> Result<Self, ModelsError> {
+        let mut serialized = Vec::new();
+        OperationSerializer::new().serialize(operation, &mut serialized)?;
+        let content_hash = operation.compute_hash(&serialized, public_key);
+        let signed_hash = operation.compute_signed_hash(public_key, &content_hash);
+
+        let mut preview = OperationSigningPreview {
+            operation_type: String::new(),
+            creator: Address::from_public_key(public_key),
+            recipient: None,
+            amount: None,
+            roll_count: None,
+            function: None,
+            data_size: None,
+            max_gas: None,
+            fee: operation.fee,
+            expire_period: operation.expire_period,
+            expire_timestamp: None,
+            digest: SigningDomain::Operation.digest(&signed_hash),
+        };
+        match &operation.op {
+            OperationType::Transaction {
+                recipient_address,
+                amount,
+            } => {
+                preview.operation_type = String::from("Transaction");
+                preview.recipient = Some(*recipient_address);
+                preview.amount = Some(*amount);
+            }
+            OperationType::RollBuy { roll_count } => {
+                preview.operation_type = String::from("RollBuy");
+                preview.roll_count = Some(*roll_count);
+            }
+            OperationType::RollSell { roll_count } => {
+                preview.operation_type = String::from("RollSell");
+                preview.roll_count = Some(*roll_count);
+            }
+            OperationType::ExecuteSC {
+                data,
+                max_gas,
+                max_coins,
+                ..
+            } => {
+                preview.operation_type = String::from("ExecuteSC");
+                preview.amount = Some(*max_coins);
+                preview.data_size = Some(data.len());
+                preview.max_gas = Some(*max_gas);
+            }
+            OperationType::CallSC {
+                target_addr,
+                target_func,
+                param,
+                max_gas,
+                coins,
+            } => {
+                preview.operation_type = String::from("CallSC");
+                preview.recipient = Some(*target_addr);
+                preview.amount = Some(*coins);
+                preview.function = Some(target_func.clone());
+                preview.data_size = Some(param.len());
+                preview.max_gas = Some(*max_gas);
+            }
+        }
+        Ok(preview)
+    }
+}
+
+impl Display for OperationSigningPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "type: {}", self.operation_type)?;
+        writeln!(f, "creator: {}", self.creator)?;
+        if let Some(recipient) = &self.recipient {
+            writeln!(f, "recipient: {}", recipient)?;
+        }
+        if let Some(amount) = &self.amount {
+            writeln!(f, "amount: {} MAS", amount)?;
+        }
+        if let Some(roll_count) = &self.roll_count {
+            writeln!(f, "roll count: {}", roll_count)?;
+        }
+        if let Some(function) = &self.function {
+            writeln!(f, "function: {}", function)?;
+        }
+        if let Some(data_size) = &self.data_size {
+            writeln!(f, "data size: {} bytes", data_size)?;
+        }
+        if let Some(max_gas) = &self.max_gas {
+            writeln!(f, "max gas: {}", max_gas)?;
+        }
+        writeln!(f, "fee: {} MAS", self.fee)?;
+        match &self.expire_timestamp {
+            Some(timestamp) => writeln!(
+                f,
+                "expiry: period {} ({})",
+                self.expire_period,
+                timestamp.format_instant()
+            )?,
+            None => writeln!(f, "expiry: period {}", self.expire_period)?,
+        }
+        writeln!(f, "digest: {}", self.digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_operation_signing_preview() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let recipient = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let operation = Operation {
+            fee: Amount::from_str("0.01").unwrap(),
+            expire_period: 10,
+            op: OperationType::Transaction {
+                recipient_address: recipient,
+                amount: Amount::from_str("1.5").unwrap(),
+            },
+        };
+        let preview = OperationSigningPreview::new(&operation, &keypair.get_public_key()).unwrap();
+        assert_eq!(preview.operation_type, "Transaction");
+        assert_eq!(preview.recipient, Some(recipient));
+        assert_eq!(preview.amount, Some(Amount::from_str("1.5").unwrap()));
+        assert!(preview.to_string().contains("amount: 1.5 MAS\n"));
+        assert!(preview.to_string().contains("expiry: period 10\n"));
+
+        // the digest is the one of the hash signed by the keypair
+        let signed =
+            Operation::new_verifiable(operation, OperationSerializer::new(), &keypair).unwrap();
+        assert_eq!(
+            preview.digest,
+            SigningDomain::Operation.digest(&signed.compute_signed_hash())
+        );
+    }
+}
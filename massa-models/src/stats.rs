@@ -16,14 +16,33 @@ pub struct ExecutionStats {
     pub final_block_count: usize,
     /// number of final executed operations in the time window
     pub final_executed_operations_count: usize,
+    /// number of final operations executed successfully in the time window
+    pub final_applied_operations_count: usize,
+    /// number of final operations whose effects were reverted in the time window
+    pub final_failed_operations_count: usize,
+    /// average gas used by the operations of the final blocks of the time window
+    pub average_gas_per_block: u64,
+    /// number of asynchronous messages executed in the final slots of the time window
+    pub final_executed_async_messages_count: usize,
+    /// ratio of the module loads of the time window served by the RAM cache, None if no module was loaded
+    pub module_cache_ram_hit_ratio: Option<f64>,
+    /// ratio of the RAM cache misses of the time window served by the disk cache, None if there was no miss
+    pub module_cache_hd_hit_ratio: Option<f64>,
     /// active execution cursor slot
     pub active_cursor: Slot,
     /// final execution cursor slot
     pub final_cursor: Slot,
+    /// earliest slot from which all the final events are still kept by the node
+    pub earliest_final_event_slot: Slot,
+    /// earliest slot from which the execution status of all the final executed operations is still kept
+    pub earliest_executed_op_slot: Slot,
 }
 
 impl std::fmt::Display for ExecutionStats {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let format_ratio = |ratio: &Option<f64>| {
+            ratio.map_or_else(|| "-".to_string(), |ratio| format!("{:.2}%", ratio * 100.0))
+        };
         writeln!(f, "Execution stats:")?;
         writeln!(
             f,
@@ -45,8 +64,39 @@ impl std::fmt::Display for ExecutionStats {
             "\tFinal executed operation count: {}",
             self.final_executed_operations_count
         )?;
+        writeln!(
+            f,
+            "\tFinal applied/failed operation count: {}/{}",
+            self.final_applied_operations_count, self.final_failed_operations_count
+        )?;
+        writeln!(
+            f,
+            "\tAverage gas per final block: {}",
+            self.average_gas_per_block
+        )?;
+        writeln!(
+            f,
+            "\tFinal executed async message count: {}",
+            self.final_executed_async_messages_count
+        )?;
+        writeln!(
+            f,
+            "\tModule cache hit ratio: RAM {} / disk {}",
+            format_ratio(&self.module_cache_ram_hit_ratio),
+            format_ratio(&self.module_cache_hd_hit_ratio)
+        )?;
         writeln!(f, "\tActive cursor: {}", self.active_cursor)?;
         writeln!(f, "\tFinal cursor: {}", self.final_cursor)?;
+        writeln!(
+            f,
+            "\tEarliest final event slot: {}",
+            self.earliest_final_event_slot
+        )?;
+        writeln!(
+            f,
+            "\tEarliest executed operation slot: {}",
+            self.earliest_executed_op_slot
+        )?;
         Ok(())
     }
 }
@@ -94,6 +94,7 @@ pub fn gen_block_headers_for_denunciation(
         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
         endorsements: vec![s_endorsement_1.clone()],
         denunciations: vec![],
+        reward_recipient: None,
     };
 
     // create header
@@ -112,6 +113,7 @@ pub fn gen_block_headers_for_denunciation(
         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
         endorsements: vec![s_endorsement_1.clone()],
         denunciations: vec![],
+        reward_recipient: None,
     };
 
     // create header
@@ -130,6 +132,7 @@ pub fn gen_block_headers_for_denunciation(
         operation_merkle_root: Hash::compute_from("mno".as_bytes()),
         endorsements: vec![s_endorsement_1],
         denunciations: vec![],
+        reward_recipient: None,
     };
 
     // create header
@@ -12,6 +12,8 @@ pub struct ModuleCacheConfig {
     pub compilation_gas: u64,
     /// Maximum number of entries we want to keep in the LRU cache
     pub lru_cache_size: u32,
+    /// Maximum memory in bytes we want the LRU cache entries to use, estimated from their bytecode size (0 for no limit)
+    pub lru_cache_max_memory: usize,
     /// Maximum number of entries we want to keep in the HD cache
     pub hd_cache_size: usize,
     /// Amount of entries removed when `hd_cache_size` is reached
@@ -5,8 +5,11 @@ use schnellru::{ByLength, LruMap};
 use tracing::{debug, info, warn};
 
 use crate::{
-    config::ModuleCacheConfig, error::CacheError, hd_cache::HDCache, lru_cache::LRUCache,
-    types::ModuleInfo,
+    config::ModuleCacheConfig,
+    error::CacheError,
+    hd_cache::HDCache,
+    lru_cache::LRUCache,
+    types::{ModuleCacheStats, ModuleInfo},
 };
 
 /// `LruMap` specialization for `PreHashed` keys
@@ -23,18 +26,21 @@ pub struct ModuleCache {
     /// Disk stored cache.
     /// See the `HDCache` documentation for more information.
     hd_cache: HDCache,
+    /// cache hits and misses of the module loads since the last call to `take_stats`
+    stats: ModuleCacheStats,
 }
 
 impl ModuleCache {
     /// Creates a new `ModuleCache`
     pub fn new(cfg: ModuleCacheConfig) -> Self {
         Self {
-            lru_cache: LRUCache::new(cfg.lru_cache_size),
+            lru_cache: LRUCache::new(cfg.lru_cache_size, cfg.lru_cache_max_memory),
             hd_cache: HDCache::new(
                 cfg.hd_cache_path.clone(),
                 cfg.hd_cache_size,
                 cfg.snip_amount,
             ),
+            stats: Default::default(),
             cfg,
         }
     }
@@ -66,7 +72,7 @@ impl ModuleCache {
                 .get(hash, self.cfg.compilation_gas, self.cfg.gas_costs.clone())
         {
             debug!("save_module: {} present in hd", hash);
-            self.lru_cache.insert(hash, hd_module_info);
+            self.lru_cache.insert(hash, hd_module_info, bytecode.len());
         } else if let Some(lru_module_info) = self.lru_cache.get(hash) {
             debug!("save_module: {} missing in hd but present in lru", hash);
             self.hd_cache.insert(hash, lru_module_info);
@@ -74,7 +80,7 @@ impl ModuleCache {
             debug!("save_module: {} missing", hash);
             let module_info = self.compile_cached(bytecode, hash);
             self.hd_cache.insert(hash, module_info.clone());
-            self.lru_cache.insert(hash, module_info);
+            self.lru_cache.insert(hash, module_info, bytecode.len());
         }
     }
 
@@ -105,23 +111,34 @@ impl ModuleCache {
         let hash = Hash::compute_from(bytecode);
         if let Some(lru_module_info) = self.lru_cache.get(hash) {
             debug!("load_module: {} present in lru", hash);
-            lru_module_info
-        } else if let Some(hd_module_info) =
+            self.stats.hits += 1;
+            return lru_module_info;
+        }
+        self.stats.misses += 1;
+        if let Some(hd_module_info) =
             self.hd_cache
                 .get(hash, self.cfg.compilation_gas, self.cfg.gas_costs.clone())
         {
             debug!("load_module: {} missing in lru but present in hd", hash);
-            self.lru_cache.insert(hash, hd_module_info.clone());
+            self.stats.hd_hits += 1;
+            self.lru_cache
+                .insert(hash, hd_module_info.clone(), bytecode.len());
             hd_module_info
         } else {
             debug!("load_module: {} missing", hash);
             let module_info = self.compile_cached(bytecode, hash);
             self.hd_cache.insert(hash, module_info.clone());
-            self.lru_cache.insert(hash, module_info.clone());
+            self.lru_cache
+                .insert(hash, module_info.clone(), bytecode.len());
             module_info
         }
     }
 
+    /// Returns the cache hits and misses of the module loads since the previous call
+    pub fn take_stats(&mut self) -> ModuleCacheStats {
+        std::mem::take(&mut self.stats)
+    }
+
     /// Load a cached module for execution and check its validity for execution
     pub fn load_module(
         &mut self,
@@ -9,21 +9,31 @@ use crate::types::ModuleInfo;
 pub type PreHashLruMap<K, V> = LruMap<K, V, ByLength, BuildHashMapper<K>>;
 
 /// RAM stored LRU cache.
-/// The LRU caching scheme is to remove the least recently used module when the cache is full.
+/// The LRU caching scheme is to remove the least recently used module when the cache is full,
+/// either in number of entries or in memory.
 ///
 /// It is composed of:
 /// * key: raw bytecode (which is hashed on insertion in LruMap)
-/// * value.0: corresponding compiled module
-/// * value.1: instance initialization cost
+/// * value.0: corresponding compiled module, and its instance initialization cost
+/// * value.1: estimated memory size of the module
 pub(crate) struct LRUCache {
-    cache: PreHashLruMap<Hash, ModuleInfo>,
+    cache: PreHashLruMap<Hash, (ModuleInfo, usize)>,
+    /// maximum number of entries
+    max_entries: usize,
+    /// maximum estimated memory size of the entries, 0 meaning unlimited
+    max_memory: usize,
+    /// estimated memory size of the entries
+    memory: usize,
 }
 
 impl LRUCache {
-    /// Create a new `LRUCache` with the given size
-    pub fn new(cache_size: u32) -> Self {
+    /// Create a new `LRUCache` with the given size and memory cap
+    pub fn new(cache_size: u32, max_memory: usize) -> Self {
         LRUCache {
             cache: LruMap::with_hasher(ByLength::new(cache_size), BuildHashMapper::default()),
+            max_entries: cache_size as usize,
+            max_memory,
+            memory: 0,
         }
     }
 
@@ -31,18 +41,42 @@ impl LRUCache {
     /// * retrieve a copy of it
     /// * move it up in the LRU cache
     pub fn get(&mut self, hash: Hash) -> Option<ModuleInfo> {
-        self.cache.get(&hash).cloned()
+        self.cache
+            .get(&hash)
+            .map(|(module_info, _)| module_info.clone())
     }
 
-    /// Save a module in the LRU cache
-    pub fn insert(&mut self, hash: Hash, module_info: ModuleInfo) {
-        self.cache.insert(hash, module_info);
-        debug!("(LRU insert) length is: {}", self.cache.len());
+    /// Save a module in the LRU cache, `size` being its estimated memory size.
+    /// The least recently used modules are removed until it fits.
+    pub fn insert(&mut self, hash: Hash, module_info: ModuleInfo, size: usize) {
+        if let Some((_, previous_size)) = self.cache.remove(&hash) {
+            self.memory -= previous_size;
+        }
+        if self.max_memory > 0 && size > self.max_memory {
+            debug!("(LRU insert) module {} exceeds the memory cap", hash);
+            return;
+        }
+        while self.cache.len() >= self.max_entries
+            || (self.max_memory > 0 && self.memory + size > self.max_memory)
+        {
+            match self.cache.pop_oldest() {
+                Some((_, (_, evicted_size))) => self.memory -= evicted_size,
+                None => break,
+            }
+        }
+        if self.cache.insert(hash, (module_info, size)) {
+            self.memory += size;
+        }
+        debug!(
+            "(LRU insert) length is: {}, estimated memory is: {}",
+            self.cache.len(),
+            self.memory
+        );
     }
 
     /// Set the initialization cost of a LRU cached module
     pub fn set_init_cost(&mut self, hash: Hash, init_cost: u64) {
-        if let Some(content) = self.cache.get(&hash) {
+        if let Some((content, _)) = self.cache.get(&hash) {
             match content {
                 ModuleInfo::Module(module) => {
                     *content = ModuleInfo::ModuleAndDelta((module.clone(), init_cost))
@@ -57,8 +91,47 @@ impl LRUCache {
 
     /// Set a module as invalid
     pub fn set_invalid(&mut self, hash: Hash) {
-        if let Some(content) = self.cache.get(&hash) {
+        if let Some((content, _)) = self.cache.get(&hash) {
             *content = ModuleInfo::Invalid;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(index: u8) -> Hash {
+        Hash::compute_from(&[index])
+    }
+
+    #[test]
+    fn test_lru_memory_cap() {
+        let mut cache = LRUCache::new(10, 100);
+
+        // the least recently used modules are removed until the new one fits in memory
+        cache.insert(hash(1), ModuleInfo::Invalid, 40);
+        cache.insert(hash(2), ModuleInfo::Invalid, 40);
+        assert!(cache.get(hash(1)).is_some());
+        cache.insert(hash(3), ModuleInfo::Invalid, 40);
+        assert!(cache.get(hash(1)).is_some());
+        assert!(cache.get(hash(2)).is_none());
+        assert_eq!(cache.memory, 80);
+
+        // replacing a module does not count it twice
+        cache.insert(hash(3), ModuleInfo::Invalid, 50);
+        assert_eq!(cache.memory, 90);
+
+        // a module larger than the cap is not kept
+        cache.insert(hash(4), ModuleInfo::Invalid, 101);
+        assert!(cache.get(hash(4)).is_none());
+        assert_eq!(cache.memory, 90);
+
+        // the entry count is still enforced
+        let mut cache = LRUCache::new(1, 0);
+        cache.insert(hash(1), ModuleInfo::Invalid, 40);
+        cache.insert(hash(2), ModuleInfo::Invalid, 40);
+        assert!(cache.get(hash(1)).is_none());
+        assert_eq!(cache.memory, 40);
+    }
+}
@@ -17,6 +17,17 @@ pub enum ModuleInfo {
     ModuleAndDelta((RuntimeModule, u64)),
 }
 
+/// Hits and misses of the RAM and disk caches when loading modules for execution
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleCacheStats {
+    /// modules found in the RAM cache
+    pub hits: u64,
+    /// modules loaded from the disk cache or compiled
+    pub misses: u64,
+    /// modules missing from the RAM cache but found in the disk cache
+    pub hd_hits: u64,
+}
+
 #[derive(PartialEq, Eq)]
 /// Metadata type
 pub enum ModuleMetadata {
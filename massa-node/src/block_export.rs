@@ -0,0 +1,140 @@
+//! Export of the finalized slots to an external message queue.
+//!
+//! A summary of each finalized slot, with its block and its execution output, is published to a sink.
+//! Indexers can then follow the chain without polling the API.
+//! The default sink writes one JSON summary per line to a TCP endpoint,
+//! which can be bridged to NATS, Kafka or any other message queue.
+
+use std::io::Write;
+use std::net::{SocketAddr, TcpStream};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use massa_execution_exports::{ExecutionOutput, SlotExecutionOutput};
+use massa_models::{
+    address::Address, block_id::BlockId, operation::OperationId, output_event::SCOutputEvent,
+    slot::Slot,
+};
+use serde::Serialize;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{info, warn};
+
+use crate::settings::BlockExportSettings;
+
+/// Summary of a finalized slot, as published to the sink
+#[derive(Debug, Clone, Serialize)]
+pub struct FinalizedSlotSummary {
+    /// finalized slot
+    pub slot: Slot,
+    /// block of the slot, None if the slot was missed
+    pub block_id: Option<BlockId>,
+    /// operations executed at the slot, with their success
+    pub executed_operations: Vec<(OperationId, bool)>,
+    /// addresses whose ledger entry changed
+    pub changed_addresses: Vec<Address>,
+    /// events emitted by the execution of the slot
+    pub events: Vec<SCOutputEvent>,
+}
+
+impl From<ExecutionOutput> for FinalizedSlotSummary {
+    fn from(output: ExecutionOutput) -> Self {
+        FinalizedSlotSummary {
+            slot: output.slot,
+            block_id: output.block_info.map(|info| info.block_id),
+            executed_operations: output
+                .state_changes
+                .executed_ops_changes
+                .into_iter()
+                .map(|(op_id, (success, _expiry_slot))| (op_id, success))
+                .collect(),
+            changed_addresses: output.state_changes.ledger_changes.0.into_keys().collect(),
+            events: output.events.0.into(),
+        }
+    }
+}
+
+/// Destination of the finalized slot summaries
+pub trait BlockExportSink: Send {
+    /// Publishes the summary of a finalized slot
+    fn publish(&mut self, summary: &FinalizedSlotSummary) -> std::io::Result<()>;
+}
+
+/// Sink writing one JSON summary per line to a TCP endpoint.
+/// The connection is reopened on the next summary after a failure.
+pub struct TcpJsonSink {
+    address: SocketAddr,
+    connect_timeout: Duration,
+    stream: Option<TcpStream>,
+}
+
+impl TcpJsonSink {
+    /// Creates a sink writing to `address`. The connection is opened on the first summary.
+    pub fn new(address: SocketAddr, connect_timeout: Duration) -> Self {
+        TcpJsonSink {
+            address,
+            connect_timeout,
+            stream: None,
+        }
+    }
+}
+
+impl BlockExportSink for TcpJsonSink {
+    fn publish(&mut self, summary: &FinalizedSlotSummary) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(summary)?;
+        line.push(b'\n');
+        // the stream is dropped if the write fails
+        let mut stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => TcpStream::connect_timeout(&self.address, self.connect_timeout)?,
+        };
+        stream.write_all(&line)?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+}
+
+/// Starts the thread publishing the finalized slots received on `receiver` to `sink`.
+/// The thread stops when the execution output channel is closed.
+pub fn start_block_export(
+    mut receiver: Receiver<SlotExecutionOutput>,
+    mut sink: Box<dyn BlockExportSink>,
+) -> JoinHandle<()> {
+    std::thread::Builder::new()
+        .name("block-export".to_string())
+        .spawn(move || loop {
+            match receiver.blocking_recv() {
+                Ok(SlotExecutionOutput::FinalizedSlot(output)) => {
+                    let summary = FinalizedSlotSummary::from(output);
+                    if let Err(err) = sink.publish(&summary) {
+                        warn!("could not export finalized slot {}: {}", summary.slot, err);
+                    }
+                }
+                Ok(SlotExecutionOutput::ExecutedSlot(_)) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "block export is lagging, {} execution outputs were not exported",
+                        skipped
+                    );
+                }
+                Err(RecvError::Closed) => {
+                    info!("Stop block export");
+                    return;
+                }
+            }
+        })
+        .expect("OS failed to start block export thread")
+}
+
+/// Starts the default TCP/JSON block export, if enabled in the settings.
+/// The export thread is not joined: it stops with the execution output channel.
+pub fn start_tcp_block_export(
+    settings: &BlockExportSettings,
+    receiver: Receiver<SlotExecutionOutput>,
+) {
+    if !settings.enabled {
+        return;
+    }
+    info!("Exporting the finalized slots to {}", settings.address);
+    let sink = TcpJsonSink::new(settings.address, settings.connect_timeout.to_duration());
+    start_block_export(receiver, Box::new(sink));
+}
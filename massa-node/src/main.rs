@@ -5,10 +5,14 @@
 #![warn(unused_crate_dependencies)]
 extern crate massa_logging;
 
+#[cfg(feature = "block_export")]
+use crate::block_export::start_tcp_block_export;
 #[cfg(feature = "op_spammer")]
 use crate::operation_injector::start_operation_injector;
 use crate::settings::SETTINGS;
+use crate::supervisor::{find_failed_component, Component, Decision, Supervisor};
 use crate::survey::MassaSurvey;
+use crate::verify_bootstrap::verify_bootstrap;
 
 use crossbeam_channel::TryRecvError;
 use dialoguer::Password;
@@ -27,6 +31,7 @@ use massa_consensus_exports::{ConsensusChannels, ConsensusConfig, ConsensusManag
 use massa_consensus_worker::start_consensus_worker;
 use massa_db_exports::{MassaDBConfig, MassaDBController};
 use massa_db_worker::MassaDB;
+use massa_deferred_calls::DeferredCallsConfig;
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
 use massa_execution_exports::{
     ExecutionChannels, ExecutionConfig, ExecutionManager, GasCosts, StorageCostsConstants,
@@ -37,44 +42,50 @@ use massa_factory_worker::start_factory;
 use massa_final_state::{FinalState, FinalStateConfig};
 use massa_grpc::config::{GrpcConfig, ServiceName};
 use massa_grpc::server::{MassaPrivateGrpc, MassaPublicGrpc};
-use massa_ledger_exports::LedgerConfig;
+use massa_ledger_exports::{load_genesis_smart_contracts, LedgerConfig};
 use massa_ledger_worker::FinalLedger;
 use massa_logging::massa_trace;
 use massa_metrics::{MassaMetrics, MetricsStopper};
 use massa_models::address::Address;
 use massa_models::config::constants::{
     BLOCK_REWARD, BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CHANNEL_SIZE, CONSENSUS_BOOTSTRAP_PART_SIZE,
+    DEFERRED_CALL_BASE_FEE_PER_GAS, DEFERRED_CALL_MAX_FUTURE_PERIODS, DEFERRED_CALL_MAX_SLOT_GAS,
     DELTA_F0, DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, END_TIMESTAMP, GENESIS_KEY,
     GENESIS_TIMESTAMP, INITIAL_DRAW_SEED, LEDGER_COST_PER_BYTE, LEDGER_ENTRY_BASE_COST,
     LEDGER_ENTRY_DATASTORE_BASE_SIZE, MAX_ADVERTISE_LENGTH, MAX_ASYNC_GAS, MAX_ASYNC_MESSAGE_DATA,
     MAX_ASYNC_POOL_LENGTH, MAX_BLOCK_SIZE, MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS,
     MAX_BOOTSTRAP_ERROR_LENGTH, MAX_BYTECODE_LENGTH, MAX_CONSENSUS_BLOCKS_IDS,
-    MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH,
-    MAX_DEFERRED_CREDITS_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
-    MAX_DENUNCIATION_CHANGES_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE, MAX_EXECUTED_OPS_CHANGES_LENGTH,
-    MAX_EXECUTED_OPS_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT,
-    MAX_LISTENERS_PER_PEER, MAX_OPERATIONS_PER_BLOCK, MAX_OPERATIONS_PER_MESSAGE,
-    MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
-    MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_OPERATION_STORAGE_TIME, MAX_PARAMETERS_SIZE,
-    MAX_PEERS_IN_ANNOUNCEMENT_LIST, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
-    MAX_SIZE_CHANNEL_COMMANDS_CONNECTIVITY, MAX_SIZE_CHANNEL_COMMANDS_PEERS,
-    MAX_SIZE_CHANNEL_COMMANDS_PEER_TESTERS, MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_BLOCKS,
+    MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEYS_PER_ADDRESS, MAX_DATASTORE_KEY_LENGTH,
+    MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CALL_CHANGES, MAX_DEFERRED_CREDITS_LENGTH,
+    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_DENUNCIATION_CHANGES_LENGTH,
+    MAX_ENDORSEMENTS_PER_MESSAGE, MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH,
+    MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_ASYNC_MESSAGE, MAX_GAS_PER_BLOCK,
+    MAX_LEDGER_CHANGES_COUNT, MAX_LISTENERS_PER_PEER, MAX_OPERATIONS_PER_BLOCK,
+    MAX_OPERATIONS_PER_MESSAGE, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+    MAX_OPERATION_STORAGE_TIME, MAX_PARAMETERS_SIZE, MAX_PEERS_IN_ANNOUNCEMENT_LIST,
+    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, MAX_SIZE_CHANNEL_COMMANDS_CONNECTIVITY,
+    MAX_SIZE_CHANNEL_COMMANDS_PEERS, MAX_SIZE_CHANNEL_COMMANDS_PEER_TESTERS,
+    MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_BLOCKS,
     MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_ENDORSEMENTS,
     MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_OPERATIONS, MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_BLOCKS,
     MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_ENDORSEMENTS,
     MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_OPERATIONS, MAX_SIZE_CHANNEL_NETWORK_TO_BLOCK_HANDLER,
     MAX_SIZE_CHANNEL_NETWORK_TO_ENDORSEMENT_HANDLER, MAX_SIZE_CHANNEL_NETWORK_TO_OPERATION_HANDLER,
     MAX_SIZE_CHANNEL_NETWORK_TO_PEER_HANDLER, MIP_STORE_STATS_BLOCK_CONSIDERED,
-    OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, POS_MISS_RATE_DEACTIVATION_THRESHOLD,
-    POS_SAVED_CYCLES, PROTOCOL_CONTROLLER_CHANNEL_SIZE, PROTOCOL_EVENT_CHANNEL_SIZE,
-    ROLL_COUNT_TO_SLASH_ON_DENUNCIATION, ROLL_PRICE, SELECTOR_DRAW_CACHE_SIZE, T0, THREAD_COUNT,
-    VERSION,
+    OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, POS_LOOKBACK_CYCLES,
+    POS_MISS_RATE_DEACTIVATION_THRESHOLD, POS_SAVED_CYCLES, PROTOCOL_CONTROLLER_CHANNEL_SIZE,
+    PROTOCOL_EVENT_CHANNEL_SIZE, ROLL_COUNT_TO_SLASH_ON_DENUNCIATION, ROLL_PRICE,
+    SELECTOR_DRAW_CACHE_SIZE, T0, THREAD_COUNT, VERSION,
 };
 use massa_models::config::{
     KEEP_EXECUTED_HISTORY_EXTRA_PERIODS, MAX_BOOTSTRAPPED_NEW_ELEMENTS, MAX_EVENT_DATA_SIZE,
     MAX_MESSAGE_SIZE, POOL_CONTROLLER_DENUNCIATIONS_CHANNEL_SIZE,
     POOL_CONTROLLER_ENDORSEMENTS_CHANNEL_SIZE, POOL_CONTROLLER_OPERATIONS_CHANNEL_SIZE,
+    PRUNED_OPS_FILTER_CAPACITY, PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+    PRUNED_OPS_FILTER_RANGE_COUNT, PRUNED_OPS_FILTER_RANGE_PERIODS, ROLL_SALE_COOLDOWN_CYCLES,
 };
+use massa_models::node::NodeId;
 use massa_models::slot::Slot;
 use massa_pool_exports::{PoolChannels, PoolConfig, PoolManager};
 use massa_pool_worker::start_pool_controller;
@@ -93,7 +104,10 @@ use num::rational::Ratio;
 use parking_lot::RwLock;
 use settings::GrpcSettings;
 use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Condvar, Mutex};
 use std::time::Duration;
@@ -104,10 +118,14 @@ use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::filter::{filter_fn, LevelFilter};
 
+#[cfg(feature = "block_export")]
+mod block_export;
 #[cfg(feature = "op_spammer")]
 mod operation_injector;
 mod settings;
+mod supervisor;
 mod survey;
+mod verify_bootstrap;
 
 async fn launch(
     args: &Args,
@@ -129,6 +147,7 @@ async fn launch(
     Option<massa_grpc::server::StopHandle>,
     MetricsStopper,
     MassaSurveyStopper,
+    ComponentStarters,
 ) {
     let now = MassaTime::now().expect("could not get now time");
     // Do not start if genesis is in the future. This is meant to prevent nodes
@@ -187,55 +206,8 @@ async fn launch(
     let shared_storage: Storage = Storage::create_root();
 
     // init final state
-    let ledger_config = LedgerConfig {
-        thread_count: THREAD_COUNT,
-        initial_ledger_path: SETTINGS.ledger.initial_ledger_path.clone(),
-        disk_ledger_path: SETTINGS.ledger.disk_ledger_path.clone(),
-        max_key_length: MAX_DATASTORE_KEY_LENGTH,
-        max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
-    };
-    let async_pool_config = AsyncPoolConfig {
-        max_length: MAX_ASYNC_POOL_LENGTH,
-        thread_count: THREAD_COUNT,
-        max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
-        max_key_length: MAX_DATASTORE_KEY_LENGTH as u32,
-    };
-    let pos_config = PoSConfig {
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        thread_count: THREAD_COUNT,
-        cycle_history_length: POS_SAVED_CYCLES,
-        max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
-        max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
-        max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
-        initial_deferred_credits_path: SETTINGS.ledger.initial_deferred_credits_path.clone(),
-    };
-    let executed_ops_config = ExecutedOpsConfig {
-        thread_count: THREAD_COUNT,
-        keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
-    };
-    let executed_denunciations_config = ExecutedDenunciationsConfig {
-        denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
-        thread_count: THREAD_COUNT,
-        endorsement_count: ENDORSEMENT_COUNT,
-        keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
-    };
-    let final_state_config = FinalStateConfig {
-        ledger_config: ledger_config.clone(),
-        async_pool_config,
-        pos_config,
-        executed_ops_config,
-        executed_denunciations_config,
-        final_history_length: SETTINGS.ledger.final_history_length,
-        thread_count: THREAD_COUNT,
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        initial_seed_string: INITIAL_DRAW_SEED.into(),
-        initial_rolls_path: SETTINGS.selector.initial_rolls_path.clone(),
-        endorsement_count: ENDORSEMENT_COUNT,
-        max_executed_denunciations_length: MAX_DENUNCIATION_CHANGES_LENGTH,
-        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
-        t0: T0,
-        genesis_timestamp: *GENESIS_TIMESTAMP,
-    };
+    let final_state_config = get_final_state_config();
+    let ledger_config = final_state_config.ledger_config.clone();
 
     // Start massa metrics
     let (massa_metrics, metrics_stopper) = MassaMetrics::new(
@@ -265,6 +237,7 @@ async fn launch(
         max_history_length: SETTINGS.ledger.final_history_length,
         max_new_elements: MAX_BOOTSTRAPPED_NEW_ELEMENTS as usize,
         thread_count: THREAD_COUNT,
+        block_cache_size: SETTINGS.ledger.db_block_cache_size,
     };
     let db = Arc::new(RwLock::new(
         Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -274,24 +247,12 @@ async fn launch(
     let ledger = FinalLedger::new(ledger_config.clone(), db.clone());
 
     // launch selector worker
-    let (selector_manager, selector_controller) = start_selector_worker(SelectorConfig {
-        max_draw_cache: SELECTOR_DRAW_CACHE_SIZE,
-        channel_size: CHANNEL_SIZE,
-        thread_count: THREAD_COUNT,
-        endorsement_count: ENDORSEMENT_COUNT,
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        genesis_address: Address::from_public_key(&GENESIS_KEY.get_public_key()),
-    })
-    .expect("could not start selector worker");
+    let (selector_manager, selector_controller) =
+        start_selector_worker(get_selector_config(), massa_metrics.clone())
+            .expect("could not start selector worker");
 
     // Creates an empty default store
-    let mip_stats_config = MipStatsConfig {
-        block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
-        warn_announced_version_ratio: Ratio::new(
-            u64::from(SETTINGS.versioning.mip_stats_warn_announced_version),
-            100,
-        ),
-    };
+    let mip_stats_config = get_mip_stats_config();
     // Ratio::new_raw(*SETTINGS.versioning.warn_announced_version_ratio, 100),
 
     // Create final state, either from a snapshot, or from scratch
@@ -343,61 +304,7 @@ async fn launch(
 
     let mip_store = final_state.read().mip_store.clone();
 
-    let bootstrap_config: BootstrapConfig = BootstrapConfig {
-        bootstrap_list: SETTINGS.bootstrap.bootstrap_list.clone(),
-        bootstrap_protocol: SETTINGS.bootstrap.bootstrap_protocol,
-        bootstrap_whitelist_path: SETTINGS.bootstrap.bootstrap_whitelist_path.clone(),
-        bootstrap_blacklist_path: SETTINGS.bootstrap.bootstrap_blacklist_path.clone(),
-        listen_addr: SETTINGS.bootstrap.bind,
-        connect_timeout: SETTINGS.bootstrap.connect_timeout,
-        bootstrap_timeout: SETTINGS.bootstrap.bootstrap_timeout,
-        read_timeout: SETTINGS.bootstrap.read_timeout,
-        write_timeout: SETTINGS.bootstrap.write_timeout,
-        read_error_timeout: SETTINGS.bootstrap.read_error_timeout,
-        write_error_timeout: SETTINGS.bootstrap.write_error_timeout,
-        retry_delay: SETTINGS.bootstrap.retry_delay,
-        max_ping: SETTINGS.bootstrap.max_ping,
-        max_clock_delta: SETTINGS.bootstrap.max_clock_delta,
-        cache_duration: SETTINGS.bootstrap.cache_duration,
-        keep_ledger: args.keep_ledger,
-        max_listeners_per_peer: MAX_LISTENERS_PER_PEER as u32,
-        max_simultaneous_bootstraps: SETTINGS.bootstrap.max_simultaneous_bootstraps,
-        per_ip_min_interval: SETTINGS.bootstrap.per_ip_min_interval,
-        ip_list_max_size: SETTINGS.bootstrap.ip_list_max_size,
-        rate_limit: SETTINGS.bootstrap.rate_limit,
-        max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
-        randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
-        thread_count: THREAD_COUNT,
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        endorsement_count: ENDORSEMENT_COUNT,
-        max_advertise_length: MAX_ADVERTISE_LENGTH,
-        max_bootstrap_blocks_length: MAX_BOOTSTRAP_BLOCKS,
-        max_bootstrap_error_length: MAX_BOOTSTRAP_ERROR_LENGTH,
-        max_new_elements: MAX_BOOTSTRAPPED_NEW_ELEMENTS,
-        max_async_pool_changes: MAX_BOOTSTRAP_ASYNC_POOL_CHANGES,
-        max_async_pool_length: MAX_ASYNC_POOL_LENGTH,
-        max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
-        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
-        max_datastore_entry_count: MAX_DATASTORE_ENTRY_COUNT,
-        max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
-        max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
-        max_ledger_changes_count: MAX_LEDGER_CHANGES_COUNT,
-        max_parameters_size: MAX_PARAMETERS_SIZE,
-        max_op_datastore_entry_count: MAX_OPERATION_DATASTORE_ENTRY_COUNT,
-        max_op_datastore_key_length: MAX_OPERATION_DATASTORE_KEY_LENGTH,
-        max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
-        max_changes_slot_count: SETTINGS.ledger.final_history_length as u64,
-        max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
-        max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
-        max_credits_length: MAX_DEFERRED_CREDITS_LENGTH,
-        max_executed_ops_length: MAX_EXECUTED_OPS_LENGTH,
-        max_ops_changes_length: MAX_EXECUTED_OPS_CHANGES_LENGTH,
-        consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
-        max_consensus_block_ids: MAX_CONSENSUS_BLOCKS_IDS,
-        mip_store_stats_block_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
-        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
-        max_denunciation_changes_length: MAX_DENUNCIATION_CHANGES_LENGTH,
-    };
+    let bootstrap_config = get_bootstrap_config(args.keep_ledger);
 
     let bootstrap_state = match get_state(
         &bootstrap_config,
@@ -464,14 +371,28 @@ async fn launch(
             .expect("Overflow when creating constant ledger_entry_datastore_base_size"),
     };
 
+    // the block export follows the finalized execution outputs broadcast by the execution
+    #[cfg(feature = "block_export")]
+    let execution_broadcast_enabled =
+        SETTINGS.api.enable_broadcast || SETTINGS.block_export.enabled;
+    #[cfg(not(feature = "block_export"))]
+    let execution_broadcast_enabled = SETTINGS.api.enable_broadcast;
+
     // launch execution module
     let execution_config = ExecutionConfig {
         max_final_events: SETTINGS.execution.max_final_events,
+        final_events_retention_periods: SETTINGS.execution.final_events_retention_periods,
+        max_final_events_size: SETTINGS.execution.max_final_events_size,
         readonly_queue_length: SETTINGS.execution.readonly_queue_length,
         cursor_delay: SETTINGS.execution.cursor_delay,
         max_async_gas: MAX_ASYNC_GAS,
+        max_deferred_call_gas_per_slot: DEFERRED_CALL_MAX_SLOT_GAS,
+        deferred_call_max_future_periods: DEFERRED_CALL_MAX_FUTURE_PERIODS,
+        deferred_call_base_fee_per_gas: DEFERRED_CALL_BASE_FEE_PER_GAS,
+        max_gas_per_async_message: MAX_GAS_PER_ASYNC_MESSAGE,
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         roll_price: ROLL_PRICE,
+        roll_sale_cooldown_cycles: ROLL_SALE_COOLDOWN_CYCLES,
         thread_count: THREAD_COUNT,
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
@@ -484,6 +405,7 @@ async fn launch(
         max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
         max_bytecode_size: MAX_BYTECODE_LENGTH,
         max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
+        max_datastore_keys_per_address: MAX_DATASTORE_KEYS_PER_ADDRESS,
         storage_costs_constants,
         max_read_only_gas: SETTINGS.execution.max_read_only_gas,
         gas_costs: GasCosts::new(
@@ -494,26 +416,58 @@ async fn launch(
         last_start_period: final_state.read().last_start_period,
         hd_cache_path: SETTINGS.execution.hd_cache_path.clone(),
         lru_cache_size: SETTINGS.execution.lru_cache_size,
+        lru_cache_max_memory: SETTINGS.execution.lru_cache_max_memory,
         hd_cache_size: SETTINGS.execution.hd_cache_size,
         snip_amount: SETTINGS.execution.snip_amount,
         roll_count_to_slash_on_denunciation: ROLL_COUNT_TO_SLASH_ON_DENUNCIATION,
         denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
-        broadcast_enabled: SETTINGS.api.enable_broadcast,
+        broadcast_enabled: execution_broadcast_enabled,
         broadcast_slot_execution_output_channel_capacity: SETTINGS
             .execution
             .broadcast_slot_execution_output_channel_capacity,
+        broadcast_pos_events_channel_capacity: SETTINGS
+            .execution
+            .broadcast_pos_events_channel_capacity,
         max_event_size: MAX_EVENT_DATA_SIZE,
+        max_offchain_tasks: SETTINGS.execution.max_offchain_tasks,
+        offchain_notification_queue_size: SETTINGS.execution.offchain_notification_queue_size,
+        offchain_task_timeout: SETTINGS.execution.offchain_task_timeout,
+        offchain_programs_dir: SETTINGS.execution.offchain_programs_dir.clone(),
+        speculative_cache_size: SETTINGS.execution.speculative_cache_size,
+        speculative_cache_max_slots_ahead: SETTINGS.execution.speculative_cache_max_slots_ahead,
+        speculative_cache_max_memory: SETTINGS.execution.speculative_cache_max_memory,
+        speculative_cache_eviction: SETTINGS.execution.speculative_cache_eviction,
+        execution_trace_path: SETTINGS.execution.execution_trace_path.clone(),
+        max_operation_receipts: SETTINGS.execution.max_operation_receipts,
+        max_state_views: SETTINGS.execution.max_state_views,
+        state_view_ttl: SETTINGS.execution.state_view_ttl,
+        max_historical_state_slots: SETTINGS.execution.max_historical_state_slots,
+        max_transfer_history_slots: SETTINGS.execution.max_transfer_history_slots,
         max_function_length: MAX_FUNCTION_NAME_LENGTH,
         max_parameter_length: MAX_PARAMETERS_SIZE,
     };
+    execution_config
+        .check()
+        .expect("Invalid gas configuration of the execution");
 
     let execution_channels = ExecutionChannels {
         slot_execution_output_sender: broadcast::channel(
             execution_config.broadcast_slot_execution_output_channel_capacity,
         )
         .0,
+        pos_event_sender: broadcast::channel(
+            execution_config.broadcast_pos_events_channel_capacity,
+        )
+        .0,
     };
 
+    // subscribe before the execution starts, so that no finalized slot is missed
+    #[cfg(feature = "block_export")]
+    start_tcp_block_export(
+        &SETTINGS.block_export,
+        execution_channels.slot_execution_output_sender.subscribe(),
+    );
+
     let (execution_manager, execution_controller) = start_execution_worker(
         execution_config,
         final_state.clone(),
@@ -535,6 +489,7 @@ async fn launch(
         max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
         max_operation_pool_size: SETTINGS.pool.max_operation_pool_size,
         max_operation_pool_excess_items: SETTINGS.pool.max_operation_pool_excess_items,
+        max_operations_per_sender: SETTINGS.pool.max_operations_per_sender,
         operation_pool_refresh_interval: SETTINGS.pool.operation_pool_refresh_interval,
         operation_max_future_start_delay: SETTINGS.pool.operation_max_future_start_delay,
         max_endorsements_pool_size_per_thread: SETTINGS.pool.max_endorsements_pool_size_per_thread,
@@ -567,6 +522,7 @@ async fn launch(
         &shared_storage,
         pool_channels.clone(),
         node_wallet.clone(),
+        massa_metrics.clone(),
     );
 
     // launch protocol controller
@@ -575,6 +531,17 @@ async fn launch(
     let protocol_config = ProtocolConfig {
         thread_count: THREAD_COUNT,
         ask_block_timeout: SETTINGS.protocol.ask_block_timeout,
+        block_arrival_gossip: SETTINGS.protocol.block_arrival_gossip,
+        min_ask_block_timeout: SETTINGS.protocol.min_ask_block_timeout,
+        peer_score_header_validity_weight: SETTINGS.protocol.peer_score_header_validity_weight,
+        peer_score_latency_weight: SETTINGS.protocol.peer_score_latency_weight,
+        peer_score_garbage_weight: SETTINGS.protocol.peer_score_garbage_weight,
+        peer_score_operation_usefulness_weight: SETTINGS
+            .protocol
+            .peer_score_operation_usefulness_weight,
+        peer_diversity_prefix_weight: SETTINGS.protocol.peer_diversity_prefix_weight,
+        peer_diversity_asn_weight: SETTINGS.protocol.peer_diversity_asn_weight,
+        asn_database_path: SETTINGS.protocol.asn_database_path.clone(),
         max_known_blocks_size: SETTINGS.protocol.max_known_blocks_size,
         max_node_known_blocks_size: SETTINGS.protocol.max_node_known_blocks_size,
         max_block_propagation_time: SETTINGS.protocol.max_block_propagation_time,
@@ -583,6 +550,9 @@ async fn launch(
         max_node_known_ops_size: SETTINGS.protocol.max_node_known_ops_size,
         max_known_endorsements_size: SETTINGS.protocol.max_known_endorsements_size,
         max_node_known_endorsements_size: SETTINGS.protocol.max_node_known_endorsements_size,
+        known_endorsements_filter_bits: SETTINGS.protocol.known_endorsements_filter_bits,
+        known_endorsements_window: SETTINGS.protocol.known_endorsements_window,
+        endorsement_fast_path_peers: SETTINGS.protocol.endorsement_fast_path_peers,
         max_simultaneous_ask_blocks_per_node: SETTINGS
             .protocol
             .max_simultaneous_ask_blocks_per_node,
@@ -603,6 +573,7 @@ async fn launch(
         endorsement_count: ENDORSEMENT_COUNT,
         max_message_size: MAX_MESSAGE_SIZE as usize,
         max_ops_kept_for_propagation: SETTINGS.protocol.max_ops_kept_for_propagation,
+        operation_source_ban_duration: SETTINGS.protocol.operation_source_ban_duration,
         max_operations_propagation_time: SETTINGS.protocol.max_operations_propagation_time,
         max_endorsements_propagation_time: SETTINGS.protocol.max_endorsements_propagation_time,
         last_start_period: final_state.read().last_start_period,
@@ -613,7 +584,33 @@ async fn launch(
         keypair_file: SETTINGS.protocol.keypair_file.clone(),
         max_blocks_kept_for_propagation: SETTINGS.protocol.max_blocks_kept_for_propagation,
         block_propagation_tick: SETTINGS.protocol.block_propagation_tick,
+        produced_block_reannounce_timeout: SETTINGS.protocol.produced_block_reannounce_timeout,
+        max_produced_block_reannounces: SETTINGS.protocol.max_produced_block_reannounces,
+        propagation_priority_max_wait: SETTINGS.protocol.propagation_priority_max_wait,
+        compact_block_relay: SETTINGS.protocol.compact_block_relay,
+        batch_block_asks: SETTINGS.protocol.batch_block_asks,
+        header_only_relay: SETTINGS.protocol.header_only_relay,
+        shutdown_drain_deadline: SETTINGS.protocol.shutdown_drain_deadline,
+        operation_expiry_horizon: SETTINGS.protocol.operation_expiry_horizon,
+        peer_ping_interval: SETTINGS.protocol.peer_ping_interval,
+        max_failed_pings: SETTINGS.protocol.max_failed_pings,
+        message_compression: SETTINGS.protocol.message_compression,
+        message_compression_level: SETTINGS.protocol.message_compression_level,
+        message_compression_threshold: SETTINGS.protocol.message_compression_threshold,
+        max_trace_events: SETTINGS.protocol.max_trace_events,
+        endorsement_subscription_threads: SETTINGS
+            .protocol
+            .endorsement_subscription_threads
+            .clone(),
+        peer_quarantine_duration: SETTINGS.protocol.peer_quarantine_duration,
+        serialization_buffer_pool_size: SETTINGS.protocol.serialization_buffer_pool_size,
         asked_operations_buffer_capacity: SETTINGS.protocol.asked_operations_buffer_capacity,
+        max_in_flight_pool_operation_batches: SETTINGS
+            .protocol
+            .max_in_flight_pool_operation_batches,
+        max_pending_pool_operation_batches: SETTINGS.protocol.max_pending_pool_operation_batches,
+        pool_operation_batch_retry_delay: SETTINGS.protocol.pool_operation_batch_retry_delay,
+        max_pool_operation_batch_retries: SETTINGS.protocol.max_pool_operation_batch_retries,
         thread_tester_count: SETTINGS.protocol.thread_tester_count,
         max_operation_storage_time: MAX_OPERATION_STORAGE_TIME,
         max_size_channel_commands_propagation_blocks: MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_BLOCKS,
@@ -662,6 +659,11 @@ async fn launch(
         try_connection_timer_same_peer: SETTINGS.protocol.try_connection_timer_same_peer,
         test_oldest_peer_cooldown: SETTINGS.protocol.test_oldest_peer_cooldown,
         rate_limit: SETTINGS.protocol.rate_limit,
+        max_peer_bytes_in_per_second: SETTINGS.protocol.max_peer_bytes_in_per_second,
+        max_peer_bytes_out_per_second: SETTINGS.protocol.max_peer_bytes_out_per_second,
+        ban_peers_exceeding_rate_limit: SETTINGS.protocol.ban_peers_exceeding_rate_limit,
+        // faults are only simulated in tests
+        fault_injection: None,
     };
 
     let (protocol_controller, protocol_channels) =
@@ -686,6 +688,7 @@ async fn launch(
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         channel_size: CHANNEL_SIZE,
         bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+        bootstrap_full_block_periods: SETTINGS.consensus.bootstrap_full_block_periods,
         broadcast_enabled: SETTINGS.api.enable_broadcast,
         broadcast_blocks_headers_channel_capacity: SETTINGS
             .consensus
@@ -698,6 +701,8 @@ async fn launch(
         force_keep_final_periods_without_ops: SETTINGS
             .consensus
             .force_keep_final_periods_without_ops,
+        parent_selection_rule: SETTINGS.consensus.parent_selection_rule,
+        parent_tie_break: SETTINGS.consensus.parent_tie_break,
     };
 
     let (consensus_event_sender, consensus_event_receiver) =
@@ -741,6 +746,13 @@ async fn launch(
     .expect("could not start protocol controller");
 
     // launch factory
+    // blocks redirecting their rewards to a smart contract are rejected by consensus
+    if let Some(recipient @ Address::SC(_)) = SETTINGS.factory.block_reward_recipient {
+        panic!(
+            "block reward recipient {} must be a user address, not a smart contract",
+            recipient
+        );
+    }
     let factory_config = FactoryConfig {
         thread_count: THREAD_COUNT,
         genesis_timestamp: *GENESIS_TIMESTAMP,
@@ -755,6 +767,8 @@ async fn launch(
         stop_production_when_zero_connections: SETTINGS
             .factory
             .stop_production_when_zero_connections,
+        optimistic_block_announce: SETTINGS.factory.optimistic_block_announce,
+        block_reward_recipient: SETTINGS.factory.block_reward_recipient,
     };
     let factory_channels = FactoryChannels {
         selector: selector_controller.clone(),
@@ -763,12 +777,19 @@ async fn launch(
         protocol: protocol_controller.clone(),
         storage: shared_storage.clone(),
     };
-    let factory_manager = start_factory(
-        factory_config,
-        node_wallet.clone(),
-        factory_channels,
-        mip_store.clone(),
-    );
+    let start_factory_component = {
+        let node_wallet = node_wallet.clone();
+        let mip_store = mip_store.clone();
+        move || {
+            start_factory(
+                factory_config.clone(),
+                node_wallet.clone(),
+                factory_channels.clone(),
+                mip_store.clone(),
+            )
+        }
+    };
+    let factory_manager = start_factory_component();
 
     let bootstrap_manager = bootstrap_config.listen_addr.map(|addr| {
         let (listener_stopper, listener) =
@@ -795,9 +816,12 @@ async fn launch(
 
     let api_config: APIConfig = APIConfig {
         bind_private: SETTINGS.api.bind_private,
+        bind_private_unix_socket: SETTINGS.api.bind_private_unix_socket.clone(),
+        unix_socket_permissions: SETTINGS.api.unix_socket_permissions,
         bind_public: SETTINGS.api.bind_public,
         bind_api: SETTINGS.api.bind_api,
         draw_lookahead_period_count: SETTINGS.api.draw_lookahead_period_count,
+        status_staking_summary: SETTINGS.api.status_staking_summary,
         max_arguments: SETTINGS.api.max_arguments,
         openrpc_spec_path: SETTINGS.api.openrpc_spec_path.clone(),
         bootstrap_whitelist_path: SETTINGS.bootstrap.bootstrap_whitelist_path.clone(),
@@ -828,23 +852,38 @@ async fn launch(
     };
 
     // spawn Massa API
-    let api = API::<ApiV2>::new(
-        consensus_controller.clone(),
-        consensus_channels.clone(),
-        execution_controller.clone(),
-        pool_channels.clone(),
-        api_config.clone(),
-        *VERSION,
-    );
-    let api_handle = api
-        .serve(&SETTINGS.api.bind_api, &api_config)
-        .await
-        .expect("failed to start MASSA API");
-
-    info!(
-        "API | EXPERIMENTAL JsonRPC | listening on: {}",
-        &SETTINGS.api.bind_api
-    );
+    let start_api: Box<dyn Fn() -> ApiStart> = {
+        let consensus_controller = consensus_controller.clone();
+        let consensus_channels = consensus_channels.clone();
+        let execution_controller = execution_controller.clone();
+        let execution_channels = execution_channels.clone();
+        let pool_channels = pool_channels.clone();
+        let api_config = api_config.clone();
+        Box::new(move || {
+            let api = API::<ApiV2>::new(
+                consensus_controller.clone(),
+                consensus_channels.clone(),
+                execution_controller.clone(),
+                execution_channels.clone(),
+                pool_channels.clone(),
+                api_config.clone(),
+                *VERSION,
+            );
+            let api_config = api_config.clone();
+            Box::pin(async move {
+                let api_handle = api
+                    .serve(&SETTINGS.api.bind_api, &api_config)
+                    .await
+                    .expect("failed to start MASSA API");
+                info!(
+                    "API | EXPERIMENTAL JsonRPC | listening on: {}",
+                    &SETTINGS.api.bind_api
+                );
+                api_handle
+            })
+        })
+    };
+    let api_handle = start_api().await;
 
     // Disable WebSockets for Private and Public API's
     let mut api_config = api_config.clone();
@@ -863,7 +902,7 @@ async fn launch(
             consensus_controller: consensus_controller.clone(),
             consensus_channels: consensus_channels.clone(),
             execution_controller: execution_controller.clone(),
-            execution_channels,
+            execution_channels: execution_channels.clone(),
             pool_channels,
             pool_controller: pool_controller.clone(),
             protocol_controller: protocol_controller.clone(),
@@ -944,49 +983,94 @@ async fn launch(
     );
 
     // spawn private API
-    let api_private = API::<Private>::new(
-        protocol_controller.clone(),
-        execution_controller.clone(),
-        api_config.clone(),
-        sig_int_toggled,
-        node_wallet,
-    );
-    let api_private_handle = api_private
-        .serve(&SETTINGS.api.bind_private, &api_config)
-        .await
-        .expect("failed to start PRIVATE API");
-    info!(
-        "API | PRIVATE JsonRPC | listening on: {}",
-        api_config.bind_private
-    );
+    let start_api_private: Box<dyn Fn() -> ApiStart> = {
+        let protocol_controller = protocol_controller.clone();
+        let execution_controller = execution_controller.clone();
+        let api_config = api_config.clone();
+        let node_wallet = node_wallet.clone();
+        Box::new(move || {
+            let api_private = API::<Private>::new(
+                protocol_controller.clone(),
+                execution_controller.clone(),
+                api_config.clone(),
+                sig_int_toggled.clone(),
+                node_wallet.clone(),
+            );
+            let api_config = api_config.clone();
+            Box::pin(async move {
+                if let Some(path) = &api_config.bind_private_unix_socket {
+                    let handle = api_private
+                        .serve_unix(path, &api_config)
+                        .await
+                        .expect("failed to start PRIVATE API");
+                    info!(
+                        "API | PRIVATE JsonRPC | listening on Unix socket: {}",
+                        path.display()
+                    );
+                    handle
+                } else {
+                    let handle = api_private
+                        .serve(&SETTINGS.api.bind_private, &api_config)
+                        .await
+                        .expect("failed to start PRIVATE API");
+                    info!(
+                        "API | PRIVATE JsonRPC | listening on: {}",
+                        api_config.bind_private
+                    );
+                    handle
+                }
+            })
+        })
+    };
+    let api_private_handle = start_api_private().await;
 
     // spawn public API
-    let api_public = API::<Public>::new(
-        consensus_controller.clone(),
-        execution_controller.clone(),
-        api_config.clone(),
-        selector_controller.clone(),
-        pool_controller.clone(),
-        protocol_controller.clone(),
-        protocol_config.clone(),
-        *VERSION,
-        node_id,
-        shared_storage.clone(),
-        mip_store.clone(),
-    );
-    let api_public_handle = api_public
-        .serve(&SETTINGS.api.bind_public, &api_config)
-        .await
-        .expect("failed to start PUBLIC API");
-    info!(
-        "API | PUBLIC JsonRPC | listening on: {}",
-        api_config.bind_public
-    );
+    let start_api_public: Box<dyn Fn() -> ApiStart> = {
+        let consensus_controller = consensus_controller.clone();
+        let execution_controller = execution_controller.clone();
+        let api_config = api_config.clone();
+        let selector_controller = selector_controller.clone();
+        let pool_controller = pool_controller.clone();
+        let protocol_controller = protocol_controller.clone();
+        let protocol_config = protocol_config.clone();
+        let shared_storage = shared_storage.clone();
+        let mip_store = mip_store.clone();
+        Box::new(move || {
+            let api_public = API::<Public>::new(
+                consensus_controller.clone(),
+                execution_controller.clone(),
+                api_config.clone(),
+                selector_controller.clone(),
+                pool_controller.clone(),
+                protocol_controller.clone(),
+                protocol_config.clone(),
+                *VERSION,
+                node_id,
+                shared_storage.clone(),
+                mip_store.clone(),
+                node_wallet.clone(),
+            );
+            let api_config = api_config.clone();
+            Box::pin(async move {
+                let handle = api_public
+                    .serve(&SETTINGS.api.bind_public, &api_config)
+                    .await
+                    .expect("failed to start PUBLIC API");
+                info!(
+                    "API | PUBLIC JsonRPC | listening on: {}",
+                    api_config.bind_public
+                );
+                handle
+            })
+        })
+    };
+    let api_public_handle = start_api_public().await;
 
     let massa_survey_stopper = MassaSurvey::run(
         SETTINGS.metrics.tick_delay.to_duration(),
         execution_controller,
         pool_controller,
+        execution_channels.pos_event_sender.subscribe(),
         massa_metrics,
         (
             api_config.thread_count,
@@ -1042,9 +1126,30 @@ async fn launch(
         grpc_public_handle,
         metrics_stopper,
         massa_survey_stopper,
+        ComponentStarters {
+            factory: Box::new(start_factory_component),
+            api: start_api,
+            api_private: start_api_private,
+            api_public: start_api_public,
+        },
     )
 }
 
+/// Future starting an API server
+type ApiStart = Pin<Box<dyn Future<Output = StopHandle>>>;
+
+/// Starters of the components that the supervisor restarts after a failure
+struct ComponentStarters {
+    /// starts the block and endorsement factory
+    factory: Box<dyn Fn() -> Box<dyn FactoryManager>>,
+    /// starts the experimental JSON-RPC API
+    api: Box<dyn Fn() -> ApiStart>,
+    /// starts the private JSON-RPC API
+    api_private: Box<dyn Fn() -> ApiStart>,
+    /// starts the public JSON-RPC API
+    api_public: Box<dyn Fn() -> ApiStart>,
+}
+
 // Get the configuration of the gRPC server
 fn configure_grpc(
     name: ServiceName,
@@ -1191,7 +1296,15 @@ async fn stop(
     factory_manager.stop();
 
     // stop protocol controller
-    protocol_manager.stop();
+    let protocol_shutdown_report = protocol_manager.stop();
+    if protocol_shutdown_report.is_clean() {
+        info!("Protocol stopped: {}", protocol_shutdown_report);
+    } else {
+        warn!(
+            "Protocol did not stop cleanly: {}",
+            protocol_shutdown_report
+        );
+    }
 
     // stop consensus
     consensus_manager.stop();
@@ -1224,6 +1337,19 @@ struct Args {
     #[structopt(long = "restart-from-snapshot-at-period")]
     restart_from_snapshot_at_period: Option<u64>,
 
+    /// Bootstrap from this server (ip:port) into a temporary directory,
+    /// check the received state, print a summary of it and exit without joining the network
+    #[structopt(long = "verify-bootstrap")]
+    verify_bootstrap: Option<SocketAddr>,
+
+    /// Node ID of the server checked with --verify-bootstrap, if it is not in the bootstrap list
+    #[structopt(long = "verify-bootstrap-node-id")]
+    verify_bootstrap_node_id: Option<NodeId>,
+
+    /// Print the addresses of the genesis smart contracts declared in the configuration and exit
+    #[structopt(long = "print-genesis-sc-addresses")]
+    print_genesis_sc_addresses: bool,
+
     #[cfg(feature = "op_spammer")]
     /// number of operations
     #[structopt(
@@ -1246,6 +1372,172 @@ struct Args {
     dl_interval: u64,
 }
 
+/// Prints the name and address of each genesis smart contract declared in the configuration
+fn print_genesis_sc_addresses() -> anyhow::Result<()> {
+    let ledger_config = get_final_state_config().ledger_config;
+    for (name, address, _) in load_genesis_smart_contracts(&ledger_config)? {
+        println!("{}: {}", name, address);
+    }
+    Ok(())
+}
+
+/// Configuration of the final state and of its components
+fn get_final_state_config() -> FinalStateConfig {
+    let ledger_config = LedgerConfig {
+        thread_count: THREAD_COUNT,
+        initial_ledger_path: SETTINGS.ledger.initial_ledger_path.clone(),
+        initial_genesis_sc_path: SETTINGS.ledger.initial_genesis_sc_path.clone(),
+        disk_ledger_path: SETTINGS.ledger.disk_ledger_path.clone(),
+        max_initial_ledger_entries: SETTINGS.ledger.max_initial_ledger_entries,
+        max_key_length: MAX_DATASTORE_KEY_LENGTH,
+        max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+        commitment_enabled: SETTINGS.ledger.commitment_enabled,
+    };
+    let async_pool_config = AsyncPoolConfig {
+        max_length: MAX_ASYNC_POOL_LENGTH,
+        thread_count: THREAD_COUNT,
+        max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
+        max_key_length: MAX_DATASTORE_KEY_LENGTH as u32,
+    };
+    let deferred_calls_config = DeferredCallsConfig {
+        thread_count: THREAD_COUNT,
+        max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+        max_parameters_size: MAX_PARAMETERS_SIZE,
+        max_deferred_call_changes: MAX_DEFERRED_CALL_CHANGES,
+    };
+    let pos_config = PoSConfig {
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        thread_count: THREAD_COUNT,
+        cycle_history_length: POS_SAVED_CYCLES,
+        lookback_cycles: POS_LOOKBACK_CYCLES,
+        max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+        max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+        max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+        initial_deferred_credits_path: SETTINGS.ledger.initial_deferred_credits_path.clone(),
+    };
+    let executed_ops_config = ExecutedOpsConfig {
+        thread_count: THREAD_COUNT,
+        keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+        pruned_ops_filter_capacity: PRUNED_OPS_FILTER_CAPACITY,
+        pruned_ops_filter_false_positive_log2: PRUNED_OPS_FILTER_FALSE_POSITIVE_LOG2,
+        pruned_ops_filter_range_periods: PRUNED_OPS_FILTER_RANGE_PERIODS,
+        pruned_ops_filter_range_count: PRUNED_OPS_FILTER_RANGE_COUNT,
+    };
+    let executed_denunciations_config = ExecutedDenunciationsConfig {
+        denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
+        thread_count: THREAD_COUNT,
+        endorsement_count: ENDORSEMENT_COUNT,
+        keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+    };
+    FinalStateConfig {
+        ledger_config,
+        async_pool_config,
+        deferred_calls_config,
+        pos_config,
+        executed_ops_config,
+        executed_denunciations_config,
+        final_history_length: SETTINGS.ledger.final_history_length,
+        thread_count: THREAD_COUNT,
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        initial_seed_string: INITIAL_DRAW_SEED.into(),
+        initial_rolls_path: SETTINGS.selector.initial_rolls_path.clone(),
+        endorsement_count: ENDORSEMENT_COUNT,
+        max_executed_denunciations_length: MAX_DENUNCIATION_CHANGES_LENGTH,
+        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        t0: T0,
+        genesis_timestamp: *GENESIS_TIMESTAMP,
+        final_state_wal_path: SETTINGS.ledger.final_state_wal_path.clone(),
+        cycle_snapshot_path: Some(SETTINGS.ledger.cycle_snapshot_path.clone()),
+        cycle_snapshot_retention: SETTINGS.ledger.cycle_snapshot_retention,
+        change_history_path: Some(SETTINGS.ledger.change_history_path.clone()),
+        change_history_length: SETTINGS.ledger.change_history_length,
+        change_history_page_max_bytes: SETTINGS.ledger.change_history_page_max_bytes,
+    }
+}
+
+/// Configuration of the selector worker
+fn get_selector_config() -> SelectorConfig {
+    SelectorConfig {
+        max_draw_cache: SELECTOR_DRAW_CACHE_SIZE,
+        channel_size: CHANNEL_SIZE,
+        thread_count: THREAD_COUNT,
+        endorsement_count: ENDORSEMENT_COUNT,
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        genesis_address: Address::from_public_key(&GENESIS_KEY.get_public_key()),
+        draw_thread_count: SETTINGS.selector.draw_thread_count,
+    }
+}
+
+/// Configuration of the statistics of the MIP store
+fn get_mip_stats_config() -> MipStatsConfig {
+    MipStatsConfig {
+        block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+        warn_announced_version_ratio: Ratio::new(
+            u64::from(SETTINGS.versioning.mip_stats_warn_announced_version),
+            100,
+        ),
+    }
+}
+
+/// Configuration of the bootstrap client and server
+fn get_bootstrap_config(keep_ledger: bool) -> BootstrapConfig {
+    BootstrapConfig {
+        bootstrap_list: SETTINGS.bootstrap.bootstrap_list.clone(),
+        bootstrap_protocol: SETTINGS.bootstrap.bootstrap_protocol,
+        bootstrap_whitelist_path: SETTINGS.bootstrap.bootstrap_whitelist_path.clone(),
+        bootstrap_blacklist_path: SETTINGS.bootstrap.bootstrap_blacklist_path.clone(),
+        listen_addr: SETTINGS.bootstrap.bind,
+        connect_timeout: SETTINGS.bootstrap.connect_timeout,
+        bootstrap_timeout: SETTINGS.bootstrap.bootstrap_timeout,
+        read_timeout: SETTINGS.bootstrap.read_timeout,
+        write_timeout: SETTINGS.bootstrap.write_timeout,
+        read_error_timeout: SETTINGS.bootstrap.read_error_timeout,
+        write_error_timeout: SETTINGS.bootstrap.write_error_timeout,
+        retry_delay: SETTINGS.bootstrap.retry_delay,
+        max_ping: SETTINGS.bootstrap.max_ping,
+        max_clock_delta: SETTINGS.bootstrap.max_clock_delta,
+        cache_duration: SETTINGS.bootstrap.cache_duration,
+        keep_ledger,
+        max_listeners_per_peer: MAX_LISTENERS_PER_PEER as u32,
+        max_simultaneous_bootstraps: SETTINGS.bootstrap.max_simultaneous_bootstraps,
+        per_ip_min_interval: SETTINGS.bootstrap.per_ip_min_interval,
+        ip_list_max_size: SETTINGS.bootstrap.ip_list_max_size,
+        rate_limit: SETTINGS.bootstrap.rate_limit,
+        max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+        randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+        thread_count: THREAD_COUNT,
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        endorsement_count: ENDORSEMENT_COUNT,
+        max_advertise_length: MAX_ADVERTISE_LENGTH,
+        max_bootstrap_blocks_length: MAX_BOOTSTRAP_BLOCKS,
+        max_bootstrap_error_length: MAX_BOOTSTRAP_ERROR_LENGTH,
+        max_new_elements: MAX_BOOTSTRAPPED_NEW_ELEMENTS,
+        max_async_pool_changes: MAX_BOOTSTRAP_ASYNC_POOL_CHANGES,
+        max_async_pool_length: MAX_ASYNC_POOL_LENGTH,
+        max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
+        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        max_datastore_entry_count: MAX_DATASTORE_ENTRY_COUNT,
+        max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+        max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+        max_ledger_changes_count: MAX_LEDGER_CHANGES_COUNT,
+        max_parameters_size: MAX_PARAMETERS_SIZE,
+        max_op_datastore_entry_count: MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        max_op_datastore_key_length: MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        max_changes_slot_count: SETTINGS.ledger.final_history_length as u64,
+        max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+        max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+        max_credits_length: MAX_DEFERRED_CREDITS_LENGTH,
+        max_executed_ops_length: MAX_EXECUTED_OPS_LENGTH,
+        max_ops_changes_length: MAX_EXECUTED_OPS_CHANGES_LENGTH,
+        consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+        max_consensus_block_ids: MAX_CONSENSUS_BLOCKS_IDS,
+        mip_store_stats_block_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        max_denunciation_changes_length: MAX_DENUNCIATION_CHANGES_LENGTH,
+    }
+}
+
 /// Load wallet, asking for passwords if necessary
 fn load_wallet(password: Option<String>, path: &Path) -> anyhow::Result<Arc<RwLock<Wallet>>> {
     let password = if path.is_dir() {
@@ -1308,21 +1600,28 @@ async fn run(args: Args) -> anyhow::Result<()> {
 
     // Setup panic handlers,
     // and when a panic occurs,
-    // run default handler,
-    // and then shutdown.
-    let default_panic = std::panic::take_hook();
-    std::panic::set_hook(Box::new(move |info| {
-        default_panic(info);
-        std::process::exit(1);
-    }));
+    // log it and run default handler,
+    // and then shutdown unless the panicking thread is supervised.
+    supervisor::install_panic_hook();
 
     info!("Node version : {}", *VERSION);
 
+    if let Some(server) = cur_args.verify_bootstrap {
+        return verify_bootstrap(server, cur_args.verify_bootstrap_node_id);
+    }
+
+    if cur_args.print_genesis_sc_addresses {
+        return print_genesis_sc_addresses();
+    }
+
     // load or create wallet, asking for password if necessary
     let node_wallet = load_wallet(
         cur_args.password.clone(),
         &SETTINGS.factory.staking_wallet_path,
     )?;
+    node_wallet
+        .write()
+        .add_watch_only_addresses(SETTINGS.factory.watch_only_addresses.clone());
 
     // interrupt signal listener
     let sig_int_toggled = Arc::new((Mutex::new(false), Condvar::new()));
@@ -1340,6 +1639,12 @@ async fn run(args: Args) -> anyhow::Result<()> {
     #[cfg(feature = "resync_check")]
     let mut resync_check = Some(std::time::Instant::now() + std::time::Duration::from_secs(10));
 
+    // watchdog of the node components, restarting the failed ones when possible
+    let mut supervisor = Supervisor::new(
+        SETTINGS.supervisor.max_restarts,
+        SETTINGS.supervisor.restart_window.to_duration(),
+    );
+
     loop {
         let (
             consensus_event_receiver,
@@ -1349,14 +1654,15 @@ async fn run(args: Args) -> anyhow::Result<()> {
             selector_manager,
             pool_manager,
             protocol_manager,
-            factory_manager,
-            api_private_handle,
-            api_public_handle,
-            api_handle,
+            mut factory_manager,
+            mut api_private_handle,
+            mut api_public_handle,
+            mut api_handle,
             grpc_private_handle,
             grpc_public_handle,
             metrics_stopper,
             massa_survey_stopper,
+            component_starters,
         ) = launch(&cur_args, node_wallet.clone(), Arc::clone(&sig_int_toggled)).await;
 
         // loop over messages
@@ -1393,6 +1699,29 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 info!("interrupt signal received");
                 break false;
             }
+            drop(wake);
+
+            // restart the components that stopped unexpectedly, or shut down the node
+            if let Some(component) = find_failed_component(
+                factory_manager.as_ref(),
+                &[&api_handle, &api_private_handle, &api_public_handle],
+            ) {
+                match (supervisor.on_failure(component), component) {
+                    (Decision::Restart, Component::Factory) => {
+                        factory_manager.stop();
+                        factory_manager = (component_starters.factory)();
+                    }
+                    (Decision::Restart, Component::Api) => {
+                        api_handle.stop().await;
+                        api_private_handle.stop().await;
+                        api_public_handle.stop().await;
+                        api_handle = (component_starters.api)().await;
+                        api_private_handle = (component_starters.api_private)().await;
+                        api_public_handle = (component_starters.api_public)().await;
+                    }
+                    _ => break false,
+                }
+            }
 
             // Elements of the system that involve stopping and restarting should be checked by forcing a relaunch.
             // This check allows the system to start up as normal, wait 10s, then force a relaunch. If Things take too long
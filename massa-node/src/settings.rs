@@ -4,7 +4,9 @@
 use std::{collections::HashMap, path::PathBuf};
 
 use massa_bootstrap::IpType;
-use massa_models::{config::build_massa_settings, node::NodeId};
+use massa_consensus_exports::parent_selection::{ParentSelectionRule, ParentTieBreak};
+use massa_execution_exports::SpeculativeCacheEviction;
+use massa_models::{address::Address, config::build_massa_settings, node::NodeId};
 use massa_protocol_exports::PeerCategoryInfo;
 use massa_time::MassaTime;
 use serde::Deserialize;
@@ -22,6 +24,8 @@ pub struct LoggingSettings {
 #[derive(Clone, Debug, Deserialize)]
 pub struct ExecutionSettings {
     pub max_final_events: usize,
+    pub final_events_retention_periods: u64,
+    pub max_final_events_size: usize,
     pub readonly_queue_length: usize,
     pub cursor_delay: MassaTime,
     pub stats_time_window_duration: MassaTime,
@@ -30,23 +34,65 @@ pub struct ExecutionSettings {
     pub wasm_gas_costs_file: PathBuf,
     pub hd_cache_path: PathBuf,
     pub lru_cache_size: u32,
+    pub lru_cache_max_memory: usize,
     pub hd_cache_size: usize,
     pub snip_amount: usize,
     /// slot execution outputs channel capacity
     pub broadcast_slot_execution_output_channel_capacity: usize,
+    /// PoS events channel capacity
+    pub broadcast_pos_events_channel_capacity: usize,
+    /// max number of off-chain tasks registered on the node
+    pub max_offchain_tasks: usize,
+    /// max number of off-chain task notifications waiting to be run
+    pub offchain_notification_queue_size: usize,
+    /// max duration of the run of an off-chain task action
+    pub offchain_task_timeout: MassaTime,
+    /// directory of the programs that off-chain tasks are allowed to run
+    pub offchain_programs_dir: Option<PathBuf>,
+    /// max number of candidate slot outputs cancelled by blockclique changes kept for reuse
+    pub speculative_cache_size: usize,
+    /// max number of slots ahead of the final cursor for which cancelled candidate outputs are kept
+    pub speculative_cache_max_slots_ahead: u64,
+    /// maximum approximate memory size of the cancelled candidate outputs kept, in bytes
+    pub speculative_cache_max_memory: usize,
+    /// outputs evicted first when the speculative cache is full
+    pub speculative_cache_eviction: SpeculativeCacheEviction,
+    /// file to which the trace of the final slots is written, for differential testing
+    pub execution_trace_path: Option<PathBuf>,
+    /// max number of final operation receipts kept in memory
+    pub max_operation_receipts: usize,
+    /// max number of state views opened through the API at the same time
+    pub max_state_views: usize,
+    /// duration after which a state view is closed
+    pub state_view_ttl: MassaTime,
+    /// max number of final slots whose reverse ledger changes are kept
+    pub max_historical_state_slots: usize,
+    /// max number of final slots whose coin transfers are indexed by address
+    pub max_transfer_history_slots: usize,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct SelectionSettings {
     pub initial_rolls_path: PathBuf,
+    pub draw_thread_count: usize,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct LedgerSettings {
     pub initial_ledger_path: PathBuf,
+    pub initial_genesis_sc_path: Option<PathBuf>,
     pub disk_ledger_path: PathBuf,
+    pub max_initial_ledger_entries: u64,
+    pub final_state_wal_path: Option<PathBuf>,
+    pub cycle_snapshot_path: PathBuf,
+    pub cycle_snapshot_retention: usize,
+    pub change_history_path: PathBuf,
+    pub change_history_length: usize,
+    pub change_history_page_max_bytes: u64,
     pub final_history_length: usize,
     pub initial_deferred_credits_path: Option<PathBuf>,
+    pub db_block_cache_size: usize,
+    pub commitment_enabled: bool,
 }
 
 /// Bootstrap configuration.
@@ -83,6 +129,12 @@ pub struct FactorySettings {
     pub staking_wallet_path: PathBuf,
     /// stop the production in case we are not connected to anyone
     pub stop_production_when_zero_connections: bool,
+    /// announce produced blocks to peers before consensus validates them
+    pub optimistic_block_announce: bool,
+    /// addresses staking on other machines, tracked without their keys
+    pub watch_only_addresses: Vec<Address>,
+    /// address receiving the rewards and fees of the produced blocks instead of the staking addresses
+    pub block_reward_recipient: Option<Address>,
 }
 
 /// Pool configuration, read from a file configuration
@@ -90,6 +142,7 @@ pub struct FactorySettings {
 pub struct PoolSettings {
     pub max_operation_pool_size: usize,
     pub max_operation_pool_excess_items: usize,
+    pub max_operations_per_sender: usize,
     pub operation_max_future_start_delay: MassaTime,
     pub operation_pool_refresh_interval: MassaTime,
     pub max_endorsements_pool_size_per_thread: usize,
@@ -104,7 +157,10 @@ pub struct PoolSettings {
 #[derive(Debug, Deserialize, Clone)]
 pub struct APISettings {
     pub draw_lookahead_period_count: u64,
+    pub status_staking_summary: bool,
     pub bind_private: SocketAddr,
+    pub bind_private_unix_socket: Option<PathBuf>,
+    pub unix_socket_permissions: u32,
     pub bind_public: SocketAddr,
     pub bind_api: SocketAddr,
     pub max_arguments: u64,
@@ -139,6 +195,9 @@ pub struct Settings {
     pub grpc: GrpcApiSettings,
     pub metrics: MetricsSettings,
     pub versioning: VersioningSettings,
+    pub supervisor: SupervisorSettings,
+    #[cfg(feature = "block_export")]
+    pub block_export: BlockExportSettings,
 }
 
 /// Consensus configuration
@@ -157,6 +216,8 @@ pub struct ConsensusSettings {
     pub force_keep_final_periods: u64,
     /// force keep at least this number of final periods without operations in RAM for each thread
     pub force_keep_final_periods_without_ops: u64,
+    /// final blocks older than this number of periods are sent to bootstrapping nodes without their body
+    pub bootstrap_full_block_periods: u64,
     /// old blocks are pruned every `block_db_prune_interval`
     pub block_db_prune_interval: MassaTime,
     /// blocks headers channel capacity
@@ -165,6 +226,10 @@ pub struct ConsensusSettings {
     pub broadcast_blocks_channel_capacity: usize,
     /// filled blocks channel capacity
     pub broadcast_filled_blocks_channel_capacity: usize,
+    /// rule selecting the best parent of each thread
+    pub parent_selection_rule: ParentSelectionRule,
+    /// rule choosing between best parent candidates with the same score
+    pub parent_tie_break: ParentTieBreak,
 }
 
 // TODO: Remove one date. Kept for retro compatibility.
@@ -174,6 +239,18 @@ pub struct NetworkSettings {
     pub routable_ip: Option<IpAddr>,
 }
 
+/// Export of the finalized slots to an external message queue
+#[cfg(feature = "block_export")]
+#[derive(Debug, Deserialize, Clone)]
+pub struct BlockExportSettings {
+    /// enable the export
+    pub enabled: bool,
+    /// TCP endpoint receiving one JSON summary of finalized slot per line
+    pub address: SocketAddr,
+    /// timeout when connecting to the endpoint
+    pub connect_timeout: MassaTime,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct MetricsSettings {
     /// enable prometheus metrics
@@ -184,17 +261,78 @@ pub struct MetricsSettings {
     pub tick_delay: MassaTime,
 }
 
+/// Watchdog of the restartable node components, read from toml user configuration file
+#[derive(Debug, Deserialize, Clone)]
+pub struct SupervisorSettings {
+    /// max number of automatic restarts of a failed component within `restart_window`
+    pub max_restarts: u32,
+    /// time window over which the restarts of a component are counted
+    pub restart_window: MassaTime,
+}
+
 /// Protocol Configuration, read from toml user configuration file
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProtocolSettings {
     /// after `ask_block_timeout` milliseconds we try to ask a block to another node
     pub ask_block_timeout: MassaTime,
+    /// Share the block arrival timings with the peers and adapt the block ask timeout to them
+    pub block_arrival_gossip: bool,
+    /// lower bound of the adaptive block ask timeout
+    pub min_ask_block_timeout: MassaTime,
+    /// weight of the ratio of valid headers in the score of a peer
+    pub peer_score_header_validity_weight: f64,
+    /// weight of the response time to block data requests in the score of a peer
+    pub peer_score_latency_weight: f64,
+    /// weight of the ratio of useful messages in the score of a peer
+    pub peer_score_garbage_weight: f64,
+    /// weight of the ratio of new operations in the score of a peer
+    pub peer_score_operation_usefulness_weight: f64,
+    /// penalty of a candidate outbound peer for each outbound peer in the same IP prefix
+    pub peer_diversity_prefix_weight: f64,
+    /// penalty of a candidate outbound peer for each outbound peer in the same autonomous system
+    pub peer_diversity_asn_weight: f64,
+    /// file mapping IP ranges to autonomous systems
+    pub asn_database_path: Option<PathBuf>,
     /// Max known blocks we keep during their propagation
     pub max_blocks_kept_for_propagation: usize,
     /// Time during which a block is expected to propagate
     pub max_block_propagation_time: MassaTime,
     /// Block propagation tick interval, useful for propagating blocks quickly to newly connected peers.
     pub block_propagation_tick: MassaTime,
+    /// Time after which the header of a block produced by our node is announced again to a peer that did not acknowledge it
+    pub produced_block_reannounce_timeout: MassaTime,
+    /// Max number of times the header of a block produced by our node is announced again to a peer, 0 disables it
+    pub max_produced_block_reannounces: u32,
+    /// Max time the propagation of endorsements and operations waits for the pending propagations of higher priority
+    pub propagation_priority_max_wait: MassaTime,
+    /// Propagate blocks as compact blocks (header and operation ID prefixes)
+    pub compact_block_relay: bool,
+    /// Send the block data requests to a same peer in a single message
+    pub batch_block_asks: bool,
+    /// Relay the headers without retrieving the blocks and operations (observer nodes)
+    pub header_only_relay: bool,
+    /// max time to wait for the protocol threads to stop on shutdown
+    pub shutdown_drain_deadline: MassaTime,
+    /// periods after their expiry after which the operations received from peers are dropped
+    pub operation_expiry_horizon: u64,
+    /// interval between two pings sent to each connected peer
+    pub peer_ping_interval: MassaTime,
+    /// consecutive unanswered pings after which a peer is disconnected
+    pub max_failed_pings: u32,
+    /// compress the block and operation messages with zstd for the peers that support it
+    pub message_compression: bool,
+    /// zstd compression level of the messages
+    pub message_compression_level: i32,
+    /// size in bytes above which the messages are compressed
+    pub message_compression_threshold: usize,
+    /// number of latest commands and events of the protocol worker kept in the trace (0 disables the trace)
+    pub max_trace_events: usize,
+    /// threads whose endorsements our node wants to receive from its peers, None for all of them
+    pub endorsement_subscription_threads: Option<Vec<u8>>,
+    /// duration of the quarantine of a misbehaving peer before it gets banned (0 bans it immediately)
+    pub peer_quarantine_duration: MassaTime,
+    /// max number of serialization buffers kept for reuse per size class (0 disables the reuse)
+    pub serialization_buffer_pool_size: usize,
     /// max known blocks our node keeps in its knowledge cache
     pub max_known_blocks_size: usize,
     /// max cache size for which blocks a foreign node knows about
@@ -205,12 +343,27 @@ pub struct ProtocolSettings {
     pub max_known_ops_size: usize,
     /// size of the buffer of asked operations
     pub asked_operations_buffer_capacity: usize,
+    /// Maximum number of operation batches submitted to the pool and not yet acknowledged
+    pub max_in_flight_pool_operation_batches: usize,
+    /// Maximum number of operation batches waiting to be submitted to the pool.
+    /// Dismiss the new batches if overflow
+    pub max_pending_pool_operation_batches: usize,
+    /// Delay before submitting again an operation batch refused by a saturated pool
+    pub pool_operation_batch_retry_delay: MassaTime,
+    /// Maximum number of submission retries of an operation batch before dropping it
+    pub max_pool_operation_batch_retries: u32,
     /// max known operations of foreign nodes we keep in memory (by node)
     pub max_node_known_ops_size: usize,
     /// max known endorsements by our node that we kept in memory
     pub max_known_endorsements_size: usize,
     /// max known endorsements of foreign nodes we keep in memory (by node)
     pub max_node_known_endorsements_size: usize,
+    /// number of bits of the filters of the endorsements known by foreign nodes (by node and time window)
+    pub known_endorsements_filter_bits: usize,
+    /// an endorsement known by a foreign node is remembered between one and two windows
+    pub known_endorsements_window: MassaTime,
+    /// max number of peers remembered for each block producer, to which the endorsements to include in its blocks are pushed first
+    pub endorsement_fast_path_peers: usize,
     /// we ask for the same block `max_simultaneous_ask_blocks_per_node` times at the same time
     pub max_simultaneous_ask_blocks_per_node: usize,
     /// Max wait time for sending a Network or Node event.
@@ -229,6 +382,8 @@ pub struct ProtocolSettings {
     pub max_operations_per_message: u64,
     /// MAx number of operations kept for propagation
     pub max_ops_kept_for_propagation: usize,
+    /// Duration during which the operations of a banned source address are ignored
+    pub operation_source_ban_duration: MassaTime,
     /// Time threshold after which operation are not propagated
     pub max_operations_propagation_time: MassaTime,
     /// Time threshold after which operation are not propagated
@@ -269,6 +424,12 @@ pub struct ProtocolSettings {
     pub test_oldest_peer_cooldown: MassaTime,
     /// Rate limitation to apply to the data stream (per second)
     pub rate_limit: u64,
+    /// Max bytes per second received from a peer at the protocol level, 0 for no limit
+    pub max_peer_bytes_in_per_second: u64,
+    /// Max bytes per second sent to a peer at the protocol level, 0 for no limit
+    pub max_peer_bytes_out_per_second: u64,
+    /// Ban the peers exceeding `max_peer_bytes_in_per_second` instead of only logging them
+    pub ban_peers_exceeding_rate_limit: bool,
 }
 
 /// gRPC settings
@@ -0,0 +1,134 @@
+//! Watchdog of the restartable node components: the factory and the APIs.
+//!
+//! A panic in a worker thread of the factory does not abort the node.
+//! Instead, the supervisor notices that the component stopped, restarts it if it has not been
+//! restarted too often, and triggers a clean shutdown of the node otherwise.
+//! The other components hold state the rest of the node depends on: a panic in one of them still aborts the node.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use massa_api::StopHandle;
+use massa_factory_exports::FactoryManager;
+use tracing::{error, warn};
+
+/// Names of the worker threads whose panics are handled by the supervisor instead of aborting the node
+const SUPERVISED_THREADS: [&str; 2] = ["block-factory", "endorsement-factory"];
+
+/// Node component monitored by the supervisor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component {
+    Factory,
+    Api,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Component::Factory => write!(f, "factory"),
+            Component::Api => write!(f, "API"),
+        }
+    }
+}
+
+/// What to do after the failure of a component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// restart the component
+    Restart,
+    /// stop the node
+    Shutdown,
+}
+
+/// Installs a panic hook logging the thread and location of the panic.
+/// The node is aborted, unless the panic happened in a supervised worker thread.
+pub fn install_panic_hook() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("<unnamed>");
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("<no message>");
+        let location = info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_else(|| "<unknown location>".to_string());
+        error!(
+            "thread '{}' panicked at {}: {}",
+            thread_name, location, payload
+        );
+        default_panic(info);
+        if !SUPERVISED_THREADS.contains(&thread_name) {
+            std::process::exit(1);
+        }
+    }));
+}
+
+/// Returns the first component that stopped without being asked to
+pub fn find_failed_component(
+    factory_manager: &dyn FactoryManager,
+    api_handles: &[&StopHandle],
+) -> Option<Component> {
+    if factory_manager.is_finished() {
+        Some(Component::Factory)
+    } else if api_handles.iter().any(|handle| handle.is_stopped()) {
+        Some(Component::Api)
+    } else {
+        None
+    }
+}
+
+/// Decides whether failed components are restarted, bounding the number of restarts over a time window
+pub struct Supervisor {
+    /// max number of restarts of a component within `restart_window`
+    max_restarts: u32,
+    /// time window over which the restarts are counted
+    restart_window: Duration,
+    /// instants of the recent restarts of each component
+    restarts: HashMap<Component, VecDeque<Instant>>,
+}
+
+impl Supervisor {
+    pub fn new(max_restarts: u32, restart_window: Duration) -> Self {
+        Supervisor {
+            max_restarts,
+            restart_window,
+            restarts: HashMap::new(),
+        }
+    }
+
+    /// Called when a component stopped unexpectedly
+    pub fn on_failure(&mut self, component: Component) -> Decision {
+        let now = Instant::now();
+        let restarts = self.restarts.entry(component).or_default();
+        while let Some(instant) = restarts.front() {
+            if now.saturating_duration_since(*instant) > self.restart_window {
+                restarts.pop_front();
+            } else {
+                break;
+            }
+        }
+        if restarts.len() >= self.max_restarts as usize {
+            error!(
+                "{} component stopped unexpectedly after {} restarts within {:?}, shutting down the node",
+                component,
+                restarts.len(),
+                self.restart_window
+            );
+            return Decision::Shutdown;
+        }
+        restarts.push_back(now);
+        warn!(
+            "{} component stopped unexpectedly, restarting it ({}/{})",
+            component,
+            restarts.len(),
+            self.max_restarts
+        );
+        Decision::Restart
+    }
+}
@@ -7,7 +7,9 @@ use massa_execution_exports::ExecutionController;
 use massa_metrics::MassaMetrics;
 use massa_models::{address::Address, slot::Slot, timeslots::get_latest_block_slot_at_timestamp};
 use massa_pool_exports::PoolController;
+use massa_pos_exports::PoSEvent;
 use massa_time::MassaTime;
+use tokio::sync::broadcast::{error::TryRecvError, Receiver};
 use tracing::info;
 // use std::time::Duration;
 use tracing::warn;
@@ -43,6 +45,7 @@ impl MassaSurvey {
         tick_delay: std::time::Duration,
         execution_controller: Box<dyn ExecutionController>,
         pool_controller: Box<dyn PoolController>,
+        pos_event_receiver: Receiver<PoSEvent>,
         massa_metrics: MassaMetrics,
         config: (u8, MassaTime, MassaTime, u64, u64),
     ) -> MassaSurveyStopper {
@@ -51,6 +54,9 @@ impl MassaSurvey {
             {
                 let mut data_sent = 0;
                 let mut data_received = 0;
+                let mut pos_event_receiver = pos_event_receiver;
+                // stakers / rolls are refreshed on the first tick and then at each cycle completion
+                let mut refresh_stakers = true;
                 let (tx_stop, rx_stop) =
                     MassaChannel::new("massa_survey_stop".to_string(), Some(1));
                 let update_tick = tick(tick_delay);
@@ -80,8 +86,18 @@ impl MassaSurvey {
                                     data_received = new_data_received;
                                 }
 
+                                loop {
+                                    match pos_event_receiver.try_recv() {
+                                        Ok(PoSEvent::CycleCompleted { cycle, .. }) => {
+                                            massa_metrics.set_last_completed_cycle(cycle);
+                                            refresh_stakers = true;
+                                        }
+                                        Err(TryRecvError::Lagged(_)) => refresh_stakers = true,
+                                        Err(_) => break,
+                                    }
+                                }
+
                                 {
-                                           // update stakers / rolls
                                     let now = match MassaTime::now() {
                                         Ok(now) => now,
                                         Err(e) => {
@@ -107,14 +123,18 @@ impl MassaSurvey {
                                             }
                                         };
 
-                                    let staker_vec = execution_controller
-                                        .get_cycle_active_rolls(curr_cycle)
-                                        .into_iter()
-                                        .collect::<Vec<(Address, u64)>>();
+                                    // update stakers / rolls
+                                    if refresh_stakers {
+                                        let staker_vec = execution_controller
+                                            .get_cycle_active_rolls(curr_cycle)
+                                            .into_iter()
+                                            .collect::<Vec<(Address, u64)>>();
 
-                                    massa_metrics.set_stakers(staker_vec.len());
-                                    let rolls_count = staker_vec.iter().map(|(_, r)| *r).sum::<u64>();
-                                    massa_metrics.set_rolls(rolls_count as usize);
+                                        massa_metrics.set_stakers(staker_vec.len());
+                                        let rolls_count = staker_vec.iter().map(|(_, r)| *r).sum::<u64>();
+                                        massa_metrics.set_rolls(rolls_count as usize);
+                                        refresh_stakers = false;
+                                    }
                                     let current_slot = get_latest_block_slot_at_timestamp(config.0, config.1, config.2, now).unwrap_or(None).unwrap_or(Slot::new(0, 0));
                                     massa_metrics.set_current_time_thread(current_slot.thread);
                                     massa_metrics.set_current_time_period(current_slot.period);
@@ -0,0 +1,178 @@
+//! Integrity check of a bootstrap server, without joining the network.
+//!
+//! The full state is bootstrapped from the server into a temporary directory,
+//! the received state is checked, a summary is printed and the node exits.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use massa_bootstrap::{get_state_from_server, DefaultConnector};
+use massa_db_exports::{MassaDBConfig, MassaDBController};
+use massa_db_worker::MassaDB;
+use massa_final_state::FinalState;
+use massa_ledger_worker::FinalLedger;
+use massa_metrics::MassaMetrics;
+use massa_models::amount::Amount;
+use massa_models::config::constants::{
+    MAX_BOOTSTRAPPED_NEW_ELEMENTS, PERIODS_PER_CYCLE, THREAD_COUNT, VERSION,
+};
+use massa_models::node::NodeId;
+use massa_pos_worker::start_selector_worker;
+use massa_versioning::mips::get_mip_list;
+use massa_versioning::versioning::MipStore;
+use parking_lot::RwLock;
+use tracing::info;
+
+use crate::settings::SETTINGS;
+use crate::{
+    get_bootstrap_config, get_final_state_config, get_mip_stats_config, get_selector_config,
+};
+
+/// Bootstraps from `server`, checks the received state and prints a summary of it.
+///
+/// If `node_id` is not given, the node ID of the server is looked up in the bootstrap list.
+/// Returns an error if the bootstrap failed or if the received state is invalid.
+pub fn verify_bootstrap(server: SocketAddr, node_id: Option<NodeId>) -> anyhow::Result<()> {
+    let node_id = match node_id {
+        Some(node_id) => node_id,
+        None => SETTINGS
+            .bootstrap
+            .bootstrap_list
+            .iter()
+            .find(|(addr, _)| *addr == server)
+            .map(|(_, node_id)| *node_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "{} is not in the bootstrap list, its node ID must be given with --verify-bootstrap-node-id",
+                    server
+                )
+            })?,
+    };
+
+    // bootstrap into a temporary directory to keep the state of the node untouched
+    let work_dir =
+        std::env::temp_dir().join(format!("massa_verify_bootstrap_{}", std::process::id()));
+    let result = bootstrap_and_check(server, node_id, &work_dir);
+    if work_dir.exists() {
+        std::fs::remove_dir_all(&work_dir)?;
+    }
+    result
+}
+
+fn bootstrap_and_check(
+    server: SocketAddr,
+    node_id: NodeId,
+    work_dir: &std::path::Path,
+) -> anyhow::Result<()> {
+    let mut final_state_config = get_final_state_config();
+    final_state_config.final_state_wal_path = None;
+    final_state_config.cycle_snapshot_path = None;
+    final_state_config.change_history_path = None;
+    let db = Arc::new(RwLock::new(Box::new(MassaDB::new(MassaDBConfig {
+        path: work_dir.join("ledger"),
+        max_history_length: SETTINGS.ledger.final_history_length,
+        max_new_elements: MAX_BOOTSTRAPPED_NEW_ELEMENTS as usize,
+        thread_count: THREAD_COUNT,
+        block_cache_size: SETTINGS.ledger.db_block_cache_size,
+    }))
+        as Box<(dyn MassaDBController + 'static)>));
+    let ledger = FinalLedger::new(final_state_config.ledger_config.clone(), db.clone());
+    // metrics are not exported during the check
+    let (massa_metrics, _) = MassaMetrics::new(
+        false,
+        SETTINGS.metrics.bind,
+        THREAD_COUNT,
+        SETTINGS.metrics.tick_delay.to_duration(),
+    );
+    let (mut selector_manager, selector_controller) =
+        start_selector_worker(get_selector_config(), massa_metrics)
+            .map_err(|err| anyhow!("could not start selector worker: {}", err))?;
+    let mip_store = MipStore::try_from((get_mip_list(), get_mip_stats_config()))
+        .map_err(|err| anyhow!("could not create MIP store: {}", err))?;
+    let final_state = Arc::new(RwLock::new(
+        FinalState::new(
+            db,
+            final_state_config,
+            Box::new(ledger),
+            selector_controller,
+            mip_store,
+            true,
+        )
+        .map_err(|err| anyhow!("could not init final state: {}", err))?,
+    ));
+
+    let result = get_state_from_server(
+        &get_bootstrap_config(false),
+        final_state.clone(),
+        DefaultConnector,
+        *VERSION,
+        &server,
+        &node_id,
+    )
+    .map_err(|err| anyhow!("bootstrap from {} failed: {}", server, err))
+    .and_then(|bootstrap_state| {
+        info!(
+            "Bootstrap from {} done, checking the received state",
+            server
+        );
+        let mut final_state = final_state.write();
+        if !final_state.is_db_valid() {
+            bail!("the bootstrapped state contains invalid entries");
+        }
+        if !final_state.is_state_hash_valid() {
+            bail!("the bootstrapped state does not match its state hash");
+        }
+        final_state.recompute_caches();
+        print_summary(
+            &final_state,
+            bootstrap_state
+                .graph
+                .map_or(0, |graph| graph.final_blocks.len()),
+            bootstrap_state.peers.map_or(0, |peers| peers.0.len()),
+        );
+        Ok(())
+    });
+    selector_manager.stop();
+    result
+}
+
+/// Prints a summary of the bootstrapped state
+fn print_summary(final_state: &FinalState, final_block_count: usize, peer_count: usize) {
+    let slot = final_state.get_slot();
+    let balances = final_state.ledger.get_every_address();
+    let total_balance = balances.values().fold(Amount::zero(), |total, balance| {
+        total.saturating_add(*balance)
+    });
+    let total_rolls: u64 = final_state
+        .pos_state
+        .get_all_roll_counts(slot.get_cycle(PERIODS_PER_CYCLE))
+        .values()
+        .sum();
+    let deferred_credit_count: usize = final_state
+        .pos_state
+        .get_deferred_credits()
+        .credits
+        .values()
+        .map(|credits| credits.len())
+        .sum();
+
+    println!("Bootstrapped state is valid");
+    println!("Final slot: {}", slot);
+    println!("Last start period: {}", final_state.last_start_period);
+    println!("State hash: {}", final_state.get_fingerprint());
+    println!(
+        "Execution trail hash: {}",
+        final_state.get_execution_trail_hash()
+    );
+    println!("Ledger addresses: {}", balances.len());
+    println!("Total balance: {}", total_balance);
+    println!("Total rolls: {}", total_rolls);
+    println!("Deferred credits: {}", deferred_credit_count);
+    println!(
+        "Async pool messages: {}",
+        final_state.async_pool.message_info_cache.len()
+    );
+    println!("Final blocks: {}", final_block_count);
+    println!("Bootstrap peers: {}", peer_count);
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    address::Address,
     block_id::BlockId,
     denunciation::{Denunciation, DenunciationPrecursor},
     endorsement::EndorsementId,
@@ -8,12 +9,23 @@ use massa_models::{
     slot::Slot,
 };
 use massa_storage::Storage;
+use std::sync::mpsc::Sender;
 
 /// Trait defining a pool controller
 pub trait PoolController: Send + Sync {
     /// Asynchronously add operations to pool. Simply print a warning on failure.
     fn add_operations(&mut self, ops: Storage);
 
+    /// Asynchronously add operations to pool without dropping them if the pool is saturated.
+    /// If provided, `done_signal` is notified once the pool has processed the operations.
+    ///
+    /// Returns the operations back if the pool input queue is full, so that they can be submitted again later.
+    fn try_add_operations(
+        &mut self,
+        ops: Storage,
+        done_signal: Option<Sender<()>>,
+    ) -> Result<(), Storage>;
+
     /// Asynchronously add endorsements to pool. Simply print a warning on failure.
     fn add_endorsements(&mut self, endorsements: Storage);
 
@@ -42,6 +54,9 @@ pub trait PoolController: Send + Sync {
     /// Get the number of operations in the pool
     fn get_operation_count(&self) -> usize;
 
+    /// Get the number of pending operations sent by an address in the pool
+    fn get_sender_pending_operation_count(&self, address: &Address) -> usize;
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool>;
 
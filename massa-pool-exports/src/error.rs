@@ -0,0 +1,36 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use displaydoc::Display;
+use massa_models::{
+    address::Address,
+    error_code::{ErrorCategory, ErrorCode, ErrorCodeInfo},
+    operation::OperationId,
+};
+use thiserror::Error;
+
+/// Pool result
+pub type PoolResult<T, E = PoolError> = core::result::Result<T, E>;
+
+/// Pool error
+#[non_exhaustive]
+#[derive(Display, Error, Debug, Clone)]
+pub enum PoolError {
+    /// Operation {operation_id} rejected: sender {sender} already has {limit} pending operations in the pool
+    SenderOperationLimitReached {
+        /// rejected operation
+        operation_id: OperationId,
+        /// sender of the operation
+        sender: Address,
+        /// max number of pending operations per sender
+        limit: usize,
+    },
+}
+
+impl ErrorCode for PoolError {
+    fn error_code(&self) -> ErrorCodeInfo {
+        let index = match self {
+            PoolError::SenderOperationLimitReached { .. } => 1,
+        };
+        ErrorCodeInfo::new(ErrorCategory::Pool, index)
+    }
+}
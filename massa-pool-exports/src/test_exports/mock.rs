@@ -12,7 +12,8 @@ use std::sync::{
 use massa_models::config::THREAD_COUNT;
 use massa_models::denunciation::{Denunciation, DenunciationPrecursor};
 use massa_models::{
-    block_id::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    address::Address, block_id::BlockId, endorsement::EndorsementId, operation::OperationId,
+    slot::Slot,
 };
 use massa_storage::Storage;
 use massa_time::MassaTime;
@@ -76,6 +77,13 @@ pub enum MockPoolControllerMessage {
         /// Response channel
         response_tx: mpsc::Sender<usize>,
     },
+    /// Get the number of pending operations of a sender
+    GetSenderPendingOperationCount {
+        /// Sender address
+        address: Address,
+        /// Response channel
+        response_tx: mpsc::Sender<usize>,
+    },
     /// Get denunciation count
     GetDenunciationCount {
         /// Response channel
@@ -170,6 +178,22 @@ impl PoolController for MockPoolController {
             .unwrap();
     }
 
+    fn try_add_operations(
+        &mut self,
+        operations: Storage,
+        done_signal: Option<mpsc::Sender<()>>,
+    ) -> Result<(), Storage> {
+        self.q
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::AddOperations { operations })
+            .unwrap();
+        if let Some(done_signal) = done_signal {
+            let _ = done_signal.send(());
+        }
+        Ok(())
+    }
+
     fn get_block_endorsements(
         &self,
         target_block: &BlockId,
@@ -221,6 +245,19 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn get_sender_pending_operation_count(&self, address: &Address) -> usize {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.q
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetSenderPendingOperationCount {
+                address: *address,
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let (response_tx, response_rx) = mpsc::channel();
         self.q
@@ -3,13 +3,14 @@
 //! Pool controller implementation
 
 use massa_models::{
-    block_id::BlockId, denunciation::Denunciation, denunciation::DenunciationPrecursor,
-    endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    address::Address, block_id::BlockId, denunciation::Denunciation,
+    denunciation::DenunciationPrecursor, endorsement::EndorsementId, operation::OperationId,
+    slot::Slot,
 };
 use massa_pool_exports::{PoolConfig, PoolController, PoolManager};
 use massa_storage::Storage;
 use parking_lot::RwLock;
-use std::sync::mpsc::TrySendError;
+use std::sync::mpsc::{Sender, TrySendError};
 use std::sync::{mpsc::SyncSender, Arc};
 use tracing::{info, warn};
 
@@ -23,6 +24,8 @@ use crate::{
 pub enum Command {
     /// Add items to the pool
     AddItems(Storage),
+    /// Add operations to the pool and notify the sender once they are processed
+    AddOperationsWithAck(Storage, Sender<()>),
     /// Add denunciation precursor to the pool
     AddDenunciationPrecursor(DenunciationPrecursor),
     /// Notify of new final consensus periods
@@ -69,6 +72,29 @@ impl PoolController for PoolControllerImpl {
         }
     }
 
+    /// Asynchronously add operations to pool, giving them back if the pool is saturated.
+    fn try_add_operations(
+        &mut self,
+        ops: Storage,
+        done_signal: Option<Sender<()>>,
+    ) -> Result<(), Storage> {
+        let command = match done_signal {
+            Some(done_signal) => Command::AddOperationsWithAck(ops, done_signal),
+            None => Command::AddItems(ops),
+        };
+        match self.operations_input_sender.try_send(command) {
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("Could not add operations to pool: worker is unreachable.");
+                Ok(())
+            }
+            Err(TrySendError::Full(
+                Command::AddItems(ops) | Command::AddOperationsWithAck(ops, _),
+            )) => Err(ops),
+            Err(TrySendError::Full(_)) => unreachable!("unexpected command given back by channel"),
+            Ok(_) => Ok(()),
+        }
+    }
+
     /// Asynchronously add endorsements to pool. Simply print a warning on failure.
     fn add_endorsements(&mut self, endorsements: Storage) {
         // Send endorsements to the denunciation pool - so we got unfiltered endorsements
@@ -204,6 +230,13 @@ impl PoolController for PoolControllerImpl {
         self.operation_pool.read().len()
     }
 
+    /// Get the number of pending operations sent by an address in the pool
+    fn get_sender_pending_operation_count(&self, address: &Address) -> usize {
+        self.operation_pool
+            .read()
+            .get_sender_operation_count(address)
+    }
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let lck = self.endorsement_pool.read();
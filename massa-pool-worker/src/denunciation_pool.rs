@@ -323,6 +323,7 @@ mod tests {
                 operation_merkle_root: Hash::compute_from("mno".as_bytes()),
                 endorsements: vec![],
                 denunciations: vec![],
+                reward_recipient: None,
             };
 
             // create header
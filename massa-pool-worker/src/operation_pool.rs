@@ -1,14 +1,16 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_metrics::MassaMetrics;
 use massa_models::{
     address::Address,
     amount::Amount,
+    error_code::ErrorCode,
     operation::OperationId,
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
     slot::Slot,
     timeslots::get_latest_block_slot_at_timestamp,
 };
-use massa_pool_exports::{PoolChannels, PoolConfig};
+use massa_pool_exports::{PoolChannels, PoolConfig, PoolError, PoolResult};
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use massa_wallet::Wallet;
@@ -25,6 +27,9 @@ pub struct OperationPool {
     /// operations map
     sorted_ops: Vec<OperationInfo>,
 
+    /// number of operations in `sorted_ops` per sender address
+    sender_op_counts: PreHashMap<Address, usize>,
+
     /// storage instance
     pub(crate) storage: Storage,
 
@@ -36,6 +41,9 @@ pub struct OperationPool {
 
     /// staking wallet, to know which addresses we are using to stake
     wallet: Arc<RwLock<Wallet>>,
+
+    /// metrics
+    massa_metrics: MassaMetrics,
 }
 
 impl OperationPool {
@@ -44,6 +52,7 @@ impl OperationPool {
         storage: &Storage,
         channels: PoolChannels,
         wallet: Arc<RwLock<Wallet>>,
+        massa_metrics: MassaMetrics,
     ) -> Self {
         OperationPool {
             sorted_ops: Vec::with_capacity(
@@ -51,11 +60,13 @@ impl OperationPool {
                     .max_operation_pool_size
                     .saturating_add(config.max_operation_pool_excess_items),
             ),
+            sender_op_counts: Default::default(),
             last_cs_final_periods: vec![0u64; config.thread_count as usize],
             config,
             storage: storage.clone_without_refs(),
             channels,
             wallet,
+            massa_metrics,
         }
     }
 
@@ -218,6 +229,52 @@ impl OperationPool {
         self.storage.drop_operation_refs(&removed);
     }
 
+    /// Eliminate the operations of senders exceeding the max number of pending operations per sender.
+    /// Assumes that the ops are sorted by descending score, so that the best ops of each sender are kept.
+    fn eliminate_sender_overflows(&mut self) {
+        let mut sender_counts: PreHashMap<Address, usize> = PreHashMap::default();
+        let mut removed = PreHashSet::default();
+        self.sorted_ops.retain(|op_info| {
+            let count = sender_counts.entry(op_info.creator_address).or_default();
+            if *count >= self.config.max_operations_per_sender {
+                removed.insert(op_info.id);
+                return false;
+            }
+            *count += 1;
+            true
+        });
+        // drop from storage
+        self.storage.drop_operation_refs(&removed);
+    }
+
+    /// Recompute the number of operations per sender from the container
+    fn recompute_sender_op_counts(&mut self) {
+        self.sender_op_counts.clear();
+        for op_info in &self.sorted_ops {
+            *self
+                .sender_op_counts
+                .entry(op_info.creator_address)
+                .or_default() += 1;
+        }
+    }
+
+    /// Checks that a sender can add one more operation to the pool
+    fn check_sender_op_count(&self, op_info: &OperationInfo) -> PoolResult<()> {
+        let count = self
+            .sender_op_counts
+            .get(&op_info.creator_address)
+            .copied()
+            .unwrap_or_default();
+        if count >= self.config.max_operations_per_sender {
+            return Err(PoolError::SenderOperationLimitReached {
+                operation_id: op_info.id,
+                sender: op_info.creator_address,
+                limit: self.config.max_operations_per_sender,
+            });
+        }
+        Ok(())
+    }
+
     /// Truncates the container to the max allowed size
     fn truncate_container(&mut self) {
         if self.sorted_ops.len() > self.config.max_operation_pool_size {
@@ -352,11 +409,17 @@ impl OperationPool {
                 .unwrap_or(Ordering::Equal)
         });
 
+        // eliminate per-sender count overflows in sorted ops
+        self.eliminate_sender_overflows();
+
         // eliminate balance overflows in sorted ops
         self.eliminate_balance_overflows(&sender_balances);
 
         // eliminate container size overflows
         self.truncate_container();
+
+        // refresh the per-sender counts
+        self.recompute_sender_op_counts();
     }
 
     /// Get the number of stored elements
@@ -364,6 +427,14 @@ impl OperationPool {
         self.sorted_ops.len()
     }
 
+    /// Get the number of stored operations sent by a given address
+    pub fn get_sender_operation_count(&self, address: &Address) -> usize {
+        self.sender_op_counts
+            .get(address)
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Checks whether an element is stored in the pool.
     pub fn contains(&self, id: &OperationId) -> bool {
         self.storage.get_op_refs().contains(id)
@@ -412,6 +483,7 @@ impl OperationPool {
         // Note that the added items are put at the end of the sorted ops
         // so that they can still be picked for block production before refresh but with low priority
         // because in that case we don't know anything about their quality.
+        let mut rejected_op_ids = PreHashSet::default();
         {
             let ops = ops_storage.read_operations();
             for new_op_id in &new_op_ids {
@@ -419,6 +491,20 @@ impl OperationPool {
                     .get(new_op_id)
                     .expect("operation not found in storage but listed as owned");
 
+                let op_info = OperationInfo::from_op(
+                    op,
+                    self.config.operation_validity_periods,
+                    self.config.roll_price,
+                    self.config.thread_count,
+                );
+
+                // Reject the operations of senders that already have too many pending operations.
+                if let Err(err) = self.check_sender_op_count(&op_info) {
+                    debug!("{}: {}", err.error_code(), err);
+                    rejected_op_ids.insert(*new_op_id);
+                    continue;
+                }
+
                 // Broadcast operations to active channel subscribers.
                 if self.config.broadcast_enabled {
                     if let Err(err) = self.channels.operation_sender.send(op.clone()) {
@@ -426,14 +512,22 @@ impl OperationPool {
                     }
                 }
 
-                self.sorted_ops.push(OperationInfo::from_op(
-                    op,
-                    self.config.operation_validity_periods,
-                    self.config.roll_price,
-                    self.config.thread_count,
-                ));
+                *self
+                    .sender_op_counts
+                    .entry(op_info.creator_address)
+                    .or_default() += 1;
+                self.sorted_ops.push(op_info);
             }
         }
+        if !rejected_op_ids.is_empty() {
+            warn!(
+                "Operation pool per-sender limit reached. Rejected {} operations.",
+                rejected_op_ids.len()
+            );
+            self.massa_metrics
+                .inc_operations_pool_sender_limit_rejected(rejected_op_ids.len());
+            new_op_ids.retain(|id| !rejected_op_ids.contains(id));
+        }
 
         // This will add the new ops to the storage without taking locks.
         // It just take the local references from `ops_storage` if they are not in `self.storage` yet.
@@ -7,6 +7,11 @@
 //! Function: [`test_add_operation`]
 //! Classic usage of internal `add_operations` function from the [`OperationPool`].
 //!
+//! # Add operations over the sender limit
+//! Function: [`test_add_operation_sender_limit`]
+//! Same as classic but all the operations come from the same sender, which
+//! exceeds the max number of pending operations per sender.
+//!
 //! # Add irrelevant operation
 //! Function: [`test_add_irrelevant_operation`]
 //! Same as classic but we try to add irrelevant operation. (See the definition
@@ -19,11 +24,17 @@
 //!
 use crate::tests::tools::OpGenerator;
 
-use super::tools::{create_some_operations, operation_pool_test, PoolTestBoilerPlate};
+use super::tools::{
+    create_all_slots_selector_controller, create_funded_execution_controller,
+    create_some_operations, operation_pool_test, PoolTestBoilerPlate,
+};
 use massa_execution_exports::MockExecutionController;
-use massa_models::{amount::Amount, config::ENDORSEMENT_COUNT, operation::OperationId, slot::Slot};
+use massa_models::{
+    address::Address, amount::Amount, config::ENDORSEMENT_COUNT, operation::OperationId, slot::Slot,
+};
 use massa_pool_exports::PoolConfig;
 use massa_pos_exports::{MockSelectorController, Selection};
+use massa_signature::KeyPair;
 use std::{collections::BTreeMap, time::Duration};
 
 #[test]
@@ -101,6 +112,39 @@ fn test_add_operation() {
     );
 }
 
+/// Test that the operations of a sender exceeding the per-sender limit are rejected.
+#[test]
+fn test_add_operation_sender_limit() {
+    let pool_config = PoolConfig {
+        max_operations_per_sender: 3,
+        ..Default::default()
+    };
+    operation_pool_test(
+        pool_config,
+        create_funded_execution_controller(),
+        create_all_slots_selector_controller(),
+        |mut operation_pool, mut storage| {
+            let keypair = KeyPair::generate(0).unwrap();
+            let creator_address = Address::from_public_key(&keypair.get_public_key());
+            let op_gen = OpGenerator::default().expirery(2).creator(keypair);
+            storage.store_operations(create_some_operations(10, &op_gen));
+            let (done_sender, done_receiver) = std::sync::mpsc::channel();
+            operation_pool
+                .try_add_operations(storage, Some(done_sender))
+                .expect("pool input queue full");
+            done_receiver
+                .recv_timeout(Duration::from_secs(10))
+                .expect("operations not processed by the pool");
+            // the excess operations of the sender are rejected
+            assert_eq!(operation_pool.get_operation_count(), 3);
+            assert_eq!(
+                operation_pool.get_sender_pending_operation_count(&creator_address),
+                3
+            );
+        },
+    );
+}
+
 /// Test if adding irrelevant operations make simply skip the add.
 /// # Initialization
 #[test]
@@ -1,15 +1,17 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use crate::start_pool_controller;
 use crossbeam_channel as _;
 use massa_execution_exports::MockExecutionController;
 use massa_hash::Hash;
+use massa_metrics::MassaMetrics;
 use massa_models::{
     address::Address,
     amount::Amount,
     block_id::BlockId,
+    config::ENDORSEMENT_COUNT,
     endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement},
     operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
     prehash::PreHashMap,
@@ -17,7 +19,7 @@ use massa_models::{
     slot::Slot,
 };
 use massa_pool_exports::{PoolChannels, PoolConfig, PoolController, PoolManager};
-use massa_pos_exports::MockSelectorController as AutoMockSelectorController;
+use massa_pos_exports::{MockSelectorController as AutoMockSelectorController, Selection};
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use massa_wallet::test_exports::create_test_wallet;
@@ -92,6 +94,67 @@ pub(crate) fn create_some_operations(n: usize, op_gen: &OpGenerator) -> Vec<Secu
     (0..n).map(|_| op_gen.generate()).collect()
 }
 
+/// Execution mock reporting unexecuted operations and senders able to pay for them
+pub(crate) fn create_funded_execution_controller() -> Box<MockExecutionController> {
+    let mut res = Box::new(MockExecutionController::new());
+    res.expect_clone_box().returning(|| {
+        let mut story = MockExecutionController::new();
+        story
+            .expect_get_ops_exec_status()
+            .returning(|ops| vec![(None, None); ops.len()]);
+        story
+            .expect_get_final_and_candidate_balance()
+            .returning(|addrs| {
+                vec![
+                    (
+                        // Operations need to be paid for
+                        Some(Amount::const_init(1_000_000_000, 0)),
+                        Some(Amount::const_init(1_000_000_000, 0)),
+                    );
+                    addrs.len()
+                ]
+            });
+
+        Box::new(story)
+    });
+    res
+}
+
+/// Selector mock selecting the filtered address for every slot of the first periods
+pub(crate) fn create_all_slots_selector_controller() -> Box<AutoMockSelectorController> {
+    let mut res = Box::new(AutoMockSelectorController::new());
+    res.expect_clone_box().times(2).returning(|| {
+        let mut story = AutoMockSelectorController::new();
+        story
+            .expect_get_available_selections_in_range()
+            .returning(|slot_range, opt_addrs| {
+                let mut all_slots = BTreeMap::new();
+                let addr = *opt_addrs
+                    .expect("No addresses filter given")
+                    .into_iter()
+                    .next()
+                    .expect("No addresses given");
+                for i in 0..15 {
+                    for j in 0..32 {
+                        let s = Slot::new(i, j);
+                        if slot_range.contains(&s) {
+                            all_slots.insert(
+                                s,
+                                Selection {
+                                    producer: addr,
+                                    endorsements: vec![addr; ENDORSEMENT_COUNT as usize],
+                                },
+                            );
+                        }
+                    }
+                }
+                Ok(all_slots)
+            });
+        Box::new(story)
+    });
+    res
+}
+
 pub struct PoolTestBoilerPlate {
     pub pool_manager: Box<dyn PoolManager>,
     pub pool_controller: Box<dyn PoolController>,
@@ -122,6 +185,13 @@ impl PoolTestBoilerPlate {
                 selector: selector_story,
             },
             wallet,
+            MassaMetrics::new(
+                false,
+                "0.0.0.0:9898".parse().unwrap(),
+                cfg.thread_count,
+                Duration::from_secs(1),
+            )
+            .0,
         );
 
         Self {
@@ -158,6 +228,13 @@ pub fn operation_pool_test<F>(
             selector,
         },
         wallet,
+        MassaMetrics::new(
+            false,
+            "0.0.0.0:9898".parse().unwrap(),
+            cfg.thread_count,
+            Duration::from_secs(1),
+        )
+        .0,
     );
     test(pool_controller, storage);
     pool_manager.stop();
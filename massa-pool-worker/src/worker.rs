@@ -6,6 +6,7 @@ use crate::controller_impl::{Command, PoolManagerImpl};
 use crate::denunciation_pool::DenunciationPool;
 use crate::operation_pool::OperationPool;
 use crate::{controller_impl::PoolControllerImpl, endorsement_pool::EndorsementPool};
+use massa_metrics::MassaMetrics;
 use massa_pool_exports::PoolConfig;
 use massa_pool_exports::{PoolChannels, PoolController, PoolManager};
 use massa_storage::Storage;
@@ -109,6 +110,11 @@ impl OperationPoolThread {
                     Ok(Command::AddItems(operations)) => {
                         self.operation_pool.write().add_operations(operations)
                     }
+                    Ok(Command::AddOperationsWithAck(operations, done_signal)) => {
+                        self.operation_pool.write().add_operations(operations);
+                        // the sender may have given up waiting
+                        let _ = done_signal.send(());
+                    }
                     Ok(Command::NotifyFinalCsPeriods(final_cs_periods)) => self
                         .operation_pool
                         .write()
@@ -175,6 +181,9 @@ impl DenunciationPoolThread {
                     .denunciation_pool
                     .write()
                     .notify_final_cs_periods(&final_cs_periods),
+                Ok(Command::AddOperationsWithAck(..)) => {
+                    warn!("DenunciationPoolThread received an unexpected command");
+                }
             };
         }
     }
@@ -187,6 +196,7 @@ pub fn start_pool_controller(
     storage: &Storage,
     channels: PoolChannels,
     wallet: Arc<RwLock<Wallet>>,
+    massa_metrics: MassaMetrics,
 ) -> (Box<dyn PoolManager>, Box<dyn PoolController>) {
     let (operations_input_sender, operations_input_receiver) =
         sync_channel(config.operations_channel_size);
@@ -199,6 +209,7 @@ pub fn start_pool_controller(
         storage,
         channels.clone(),
         wallet.clone(),
+        massa_metrics,
     )));
     let endorsement_pool = Arc::new(RwLock::new(EndorsementPool::init(
         config,
@@ -11,6 +11,8 @@ pub struct PoSConfig {
     pub thread_count: u8,
     /// number of saved cycle
     pub cycle_history_length: usize,
+    /// number of cycles between the seed lookback cycle and the drawn cycle
+    pub lookback_cycles: u64,
     /// maximum rolls length
     pub max_rolls_length: u64,
     /// maximum production stats length
@@ -33,8 +33,8 @@ pub trait SelectorController: Send + Sync {
     ///
     /// # Arguments
     /// * `cycle`: cycle number to be drawn
-    /// * `lookback_rolls`: look back rolls used for the draw (cycle - 3)
-    /// * `lookback_seed`: look back seed hash for the draw (cycle - 2)
+    /// * `lookback_rolls`: look back rolls used for the draw (cycle - lookback_cycles - 1)
+    /// * `lookback_seed`: look back seed hash for the draw (cycle - lookback_cycles)
     fn feed_cycle(
         &self,
         cycle: u64,
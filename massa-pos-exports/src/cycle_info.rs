@@ -1,18 +1,17 @@
+use crate::PoSChanges;
 use bitvec::vec::BitVec;
-use massa_hash::{HashXof, HashXofDeserializer, HashXofSerializer, HASH_XOF_SIZE_BYTES};
+use massa_db_exports::CYCLE_HISTORY_SER_ERROR;
+use massa_hash::{Hash, HashXof, HashXofDeserializer, HashXofSerializer, HASH_XOF_SIZE_BYTES};
 use massa_models::{
     address::{Address, AddressDeserializer, AddressSerializer},
     prehash::PreHashMap,
     serialization::{BitVecDeserializer, BitVecSerializer},
 };
 use massa_serialization::{
-    Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
-    U64VarIntDeserializer, U64VarIntSerializer,
+    Deserializer, MassaDeserialize, MassaSerialize, OptionDeserializer, OptionSerializer,
+    SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
 use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    combinator::value,
     error::{context, ContextError, ParseError},
     multi::length_count,
     sequence::tuple,
@@ -24,20 +23,55 @@ use std::collections::{BTreeMap, VecDeque};
 use std::ops::Bound::Included;
 
 /// State of a cycle for all threads
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, MassaSerialize, MassaDeserialize)]
+#[massa(deserializer_args(max_rolls_length: u64, max_production_stats_length: u64))]
 pub struct CycleInfo {
     /// cycle number
+    #[massa(varint)]
     pub cycle: u64,
     /// whether the cycle is complete (all slots final)
+    #[massa(bool)]
     pub complete: bool,
     /// number of rolls each staking address has
+    #[massa(length_prefixed(
+        max_length = max_rolls_length,
+        key(nested(serializer = AddressSerializer, deserializer = AddressDeserializer)),
+        value(varint),
+    ))]
     pub roll_counts: BTreeMap<Address, u64>,
     /// random seed bits of all slots in the cycle so far
+    #[massa(nested(serializer = BitVecSerializer, deserializer = BitVecDeserializer))]
     pub rng_seed: BitVec<u8>,
     /// Per-address production statistics
+    #[massa(nested(
+        serializer = ProductionStatsSerializer,
+        deserializer = ProductionStatsDeserializer::new(max_production_stats_length),
+    ))]
     pub production_stats: PreHashMap<Address, ProductionStats>,
+    /// XOR of the hashes of every roll count entry
+    #[massa(skip = CycleInfoHashComputer::new().compute_roll_counts_hash(&roll_counts))]
+    pub roll_counts_hash: Hash,
+    /// XOR of the hashes of every production stats entry
+    #[massa(skip = CycleInfoHashComputer::new().compute_production_stats_hash(&production_stats))]
+    pub production_stats_hash: Hash,
+    /// Hash of the cycle number, roll counts, rng seed and production stats
+    #[massa(skip = CycleInfoHashComputer::new().compute_cycle_hash(
+        cycle,
+        roll_counts_hash,
+        CycleInfoHashComputer::new().compute_rng_seed_hash(&rng_seed),
+        production_stats_hash,
+    ))]
+    pub cycle_global_hash: Hash,
     /// Snapshot of the final state hash
     /// Used for PoS selections
+    #[massa(nested(
+        serializer = OptionSerializer::<HashXof<HASH_XOF_SIZE_BYTES>, HashXofSerializer>::new(
+            HashXofSerializer::new()
+        ),
+        deserializer = OptionDeserializer::<HashXof<HASH_XOF_SIZE_BYTES>, HashXofDeserializer>::new(
+            HashXofDeserializer::new()
+        ),
+    ))]
     pub final_state_hash_snapshot: Option<HashXof<HASH_XOF_SIZE_BYTES>>,
 }
 
@@ -50,6 +84,16 @@ impl CycleInfo {
         rng_seed: BitVec<u8>,
         production_stats: PreHashMap<Address, ProductionStats>,
     ) -> Self {
+        let hash_computer = CycleInfoHashComputer::new();
+        let roll_counts_hash = hash_computer.compute_roll_counts_hash(&roll_counts);
+        let production_stats_hash = hash_computer.compute_production_stats_hash(&production_stats);
+        let cycle_global_hash = hash_computer.compute_cycle_hash(
+            cycle,
+            roll_counts_hash,
+            hash_computer.compute_rng_seed_hash(&rng_seed),
+            production_stats_hash,
+        );
+
         // create the new cycle
         CycleInfo {
             cycle,
@@ -57,141 +101,167 @@ impl CycleInfo {
             roll_counts,
             rng_seed,
             production_stats,
+            roll_counts_hash,
+            production_stats_hash,
+            cycle_global_hash,
             final_state_hash_snapshot: None,
         }
     }
+
+    /// Computes the canonical hash of the cycle from scratch.
+    ///
+    /// The result is equal to `cycle_global_hash` as long as the cycle was only modified
+    /// through `CycleInfo::new` and `CycleInfo::apply_changes`.
+    pub fn compute_hash(&self) -> Hash {
+        let hash_computer = CycleInfoHashComputer::new();
+        hash_computer.compute_cycle_hash(
+            self.cycle,
+            hash_computer.compute_roll_counts_hash(&self.roll_counts),
+            hash_computer.compute_rng_seed_hash(&self.rng_seed),
+            hash_computer.compute_production_stats_hash(&self.production_stats),
+        )
+    }
+
+    /// Applies the PoS changes of a slot to the cycle and updates its hashes incrementally.
+    ///
+    /// Deferred credits are not part of a cycle and are ignored.
+    pub fn apply_changes(&mut self, changes: &PoSChanges, complete: bool) {
+        let hash_computer = CycleInfoHashComputer::new();
+        self.complete = complete;
+
+        // extend seed bits
+        self.rng_seed.extend(changes.seed_bits.iter());
+
+        // extend roll counts, removing zeros
+        for (addr, roll_count) in changes.roll_changes.iter() {
+            if let Some(prev_count) = self.roll_counts.remove(addr) {
+                self.roll_counts_hash ^= hash_computer.compute_roll_entry_hash(addr, prev_count);
+            }
+            if *roll_count > 0 {
+                self.roll_counts.insert(*addr, *roll_count);
+                self.roll_counts_hash ^= hash_computer.compute_roll_entry_hash(addr, *roll_count);
+            }
+        }
+
+        // extend production stats
+        for (addr, stats) in changes.production_stats.iter() {
+            let entry = self.production_stats.entry(*addr).or_default();
+            if *entry != ProductionStats::default() {
+                self.production_stats_hash ^=
+                    hash_computer.compute_prod_stats_entry_hash(addr, entry);
+            }
+            entry.extend(stats);
+            self.production_stats_hash ^= hash_computer.compute_prod_stats_entry_hash(addr, entry);
+        }
+
+        self.cycle_global_hash = hash_computer.compute_cycle_hash(
+            self.cycle,
+            self.roll_counts_hash,
+            hash_computer.compute_rng_seed_hash(&self.rng_seed),
+            self.production_stats_hash,
+        );
+    }
 }
 
+/// Computes the hashes used to fingerprint a `CycleInfo`.
+///
+/// Roll counts and production stats are hashed as the XOR of the hashes of their entries,
+/// so that changing a single address does not require re-hashing the whole map.
 #[derive(Clone)]
-#[allow(missing_docs)]
-/// Serializer for `CycleInfo`
-pub struct CycleInfoSerializer {
-    pub u64_ser: U64VarIntSerializer,
-    pub bitvec_ser: BitVecSerializer,
-    pub production_stats_ser: ProductionStatsSerializer,
-    pub address_ser: AddressSerializer,
-    pub opt_hash_ser: OptionSerializer<HashXof<HASH_XOF_SIZE_BYTES>, HashXofSerializer>,
+pub struct CycleInfoHashComputer {
+    u64_ser: U64VarIntSerializer,
+    bitvec_ser: BitVecSerializer,
 }
 
-impl Default for CycleInfoSerializer {
+impl Default for CycleInfoHashComputer {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl CycleInfoSerializer {
-    /// Creates a new `CycleInfo` serializer
+impl CycleInfoHashComputer {
+    /// Creates a new `CycleInfoHashComputer`
     pub fn new() -> Self {
         Self {
             u64_ser: U64VarIntSerializer::new(),
             bitvec_ser: BitVecSerializer::new(),
-            production_stats_ser: ProductionStatsSerializer::new(),
-            address_ser: AddressSerializer::new(),
-            opt_hash_ser: OptionSerializer::new(HashXofSerializer::new()),
         }
     }
-}
-
-impl Serializer<CycleInfo> for CycleInfoSerializer {
-    fn serialize(&self, value: &CycleInfo, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
-        // cycle_info.cycle
-        self.u64_ser.serialize(&value.cycle, buffer)?;
 
-        // cycle_info.complete
-        buffer.push(u8::from(value.complete));
-
-        // cycle_info.roll_counts
+    fn serialize_u64(&self, value: u64, buffer: &mut Vec<u8>) {
         self.u64_ser
-            .serialize(&(value.roll_counts.len() as u64), buffer)?;
-        for (addr, count) in &value.roll_counts {
-            self.address_ser.serialize(addr, buffer)?;
-            self.u64_ser.serialize(count, buffer)?;
-        }
-
-        // cycle_info.rng_seed
-        self.bitvec_ser.serialize(&value.rng_seed, buffer)?;
-
-        // cycle_info.production_stats
-        self.production_stats_ser
-            .serialize(&value.production_stats, buffer)?;
+            .serialize(&value, buffer)
+            .expect(CYCLE_HISTORY_SER_ERROR);
+    }
 
-        // cycle_info.final_state_hash_snapshot
-        self.opt_hash_ser
-            .serialize(&value.final_state_hash_snapshot, buffer)?;
+    /// Computes the hash of a single roll count entry
+    pub fn compute_roll_entry_hash(&self, address: &Address, roll_count: u64) -> Hash {
+        let mut serialized_count = Vec::new();
+        self.serialize_u64(roll_count, &mut serialized_count);
+        Hash::compute_from_tuple(&[&address.to_prefixed_bytes(), &serialized_count])
+    }
 
-        Ok(())
+    /// Computes the hash of a single production stats entry
+    pub fn compute_prod_stats_entry_hash(
+        &self,
+        address: &Address,
+        stats: &ProductionStats,
+    ) -> Hash {
+        let mut serialized_stats = Vec::new();
+        self.serialize_u64(stats.block_success_count, &mut serialized_stats);
+        self.serialize_u64(stats.block_failure_count, &mut serialized_stats);
+        Hash::compute_from_tuple(&[&address.to_prefixed_bytes(), &serialized_stats])
     }
-}
 
-#[derive(Clone)]
-#[allow(missing_docs)]
-/// Deserializer for `CycleInfo`
-pub struct CycleInfoDeserializer {
-    pub u64_deser: U64VarIntDeserializer,
-    pub rolls_deser: RollsDeserializer,
-    pub bitvec_deser: BitVecDeserializer,
-    pub production_stats_deser: ProductionStatsDeserializer,
-    pub opt_hash_deser: OptionDeserializer<HashXof<HASH_XOF_SIZE_BYTES>, HashXofDeserializer>,
-}
+    /// Computes the hash of a whole roll distribution
+    pub fn compute_roll_counts_hash(&self, roll_counts: &BTreeMap<Address, u64>) -> Hash {
+        let mut hash = Hash::zero();
+        for (addr, count) in roll_counts.iter().filter(|(_, count)| **count > 0) {
+            hash ^= self.compute_roll_entry_hash(addr, *count);
+        }
+        hash
+    }
 
-impl CycleInfoDeserializer {
-    /// Creates a new `CycleInfo` deserializer
-    pub fn new(max_rolls_length: u64, max_production_stats_length: u64) -> CycleInfoDeserializer {
-        CycleInfoDeserializer {
-            u64_deser: U64VarIntDeserializer::new(Included(u64::MIN), Included(u64::MAX)),
-            rolls_deser: RollsDeserializer::new(max_rolls_length),
-            bitvec_deser: BitVecDeserializer::new(),
-            production_stats_deser: ProductionStatsDeserializer::new(max_production_stats_length),
-            opt_hash_deser: OptionDeserializer::new(HashXofDeserializer::new()),
+    /// Computes the hash of the production stats of all addresses
+    pub fn compute_production_stats_hash(
+        &self,
+        production_stats: &PreHashMap<Address, ProductionStats>,
+    ) -> Hash {
+        let mut hash = Hash::zero();
+        for (addr, stats) in production_stats
+            .iter()
+            .filter(|(_, stats)| **stats != ProductionStats::default())
+        {
+            hash ^= self.compute_prod_stats_entry_hash(addr, stats);
         }
+        hash
     }
-}
 
-impl Deserializer<CycleInfo> for CycleInfoDeserializer {
-    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+    /// Computes the hash of the rng seed bits
+    pub fn compute_rng_seed_hash(&self, rng_seed: &BitVec<u8>) -> Hash {
+        let mut serialized_seed = Vec::new();
+        self.bitvec_ser
+            .serialize(rng_seed, &mut serialized_seed)
+            .expect(CYCLE_HISTORY_SER_ERROR);
+        Hash::compute_from(&serialized_seed)
+    }
+
+    /// Combines the component hashes of a cycle into its global hash
+    pub fn compute_cycle_hash(
         &self,
-        buffer: &'a [u8],
-    ) -> IResult<&'a [u8], CycleInfo, E> {
-        context(
-            "cycle_history",
-            tuple((
-                context("cycle", |input| self.u64_deser.deserialize(input)),
-                context(
-                    "complete",
-                    alt((value(true, tag(&[1])), value(false, tag(&[0])))),
-                ),
-                context("roll_counts", |input| self.rolls_deser.deserialize(input)),
-                context("rng_seed", |input| self.bitvec_deser.deserialize(input)),
-                context("production_stats", |input| {
-                    self.production_stats_deser.deserialize(input)
-                }),
-                context("final_state_hash_snapshot", |input| {
-                    self.opt_hash_deser.deserialize(input)
-                }),
-            )),
-        )
-        .map(
-            #[allow(clippy::type_complexity)]
-            |(cycle, complete, roll_counts, rng_seed, production_stats, opt_hash): (
-                u64,                                  // cycle
-                bool,                                 // complete
-                Vec<(Address, u64)>,                  // roll_counts
-                BitVec<u8>,                           // rng_seed
-                PreHashMap<Address, ProductionStats>, // production_stats (address, n_success, n_fail)
-                Option<HashXof<HASH_XOF_SIZE_BYTES>>, // final_state_hash_snapshot
-            )| {
-                let mut cycle = CycleInfo::new(
-                    cycle,
-                    complete,
-                    roll_counts.into_iter().collect(),
-                    rng_seed,
-                    production_stats,
-                );
-                cycle.final_state_hash_snapshot = opt_hash;
-                cycle
-            },
-        )
-        .parse(buffer)
+        cycle: u64,
+        roll_counts_hash: Hash,
+        rng_seed_hash: Hash,
+        production_stats_hash: Hash,
+    ) -> Hash {
+        let mut serialized_cycle = Vec::new();
+        self.serialize_u64(cycle, &mut serialized_cycle);
+        Hash::compute_from_tuple(&[
+            &serialized_cycle,
+            roll_counts_hash.to_bytes(),
+            rng_seed_hash.to_bytes(),
+            production_stats_hash.to_bytes(),
+        ])
     }
 }
 
@@ -214,6 +284,27 @@ impl ProductionStats {
         &Ratio::new(self.block_failure_count, opportunities_count) <= max_miss_ratio
     }
 
+    /// Number of additional misses, without any additional success,
+    /// after which the production stats would no longer be satisfying and the rolls would be implicitly sold.
+    /// Returns 0 if the stats are already not satisfying, and None if misses can never trigger the sale.
+    pub fn misses_before_roll_sale(&self, max_miss_ratio: &Ratio<u64>) -> Option<u64> {
+        let (ratio_numer, ratio_denom) = (*max_miss_ratio.numer(), *max_miss_ratio.denom());
+        if ratio_numer >= ratio_denom {
+            return None;
+        }
+        // with k more misses the sale is triggered if (failures + k) * denom > numer * (opportunities + k),
+        // that is if k * (denom - numer) > numer * opportunities - failures * denom
+        let opportunities_count =
+            (self.block_success_count as u128) + (self.block_failure_count as u128);
+        let allowed = (ratio_numer as u128) * opportunities_count;
+        let failures = (self.block_failure_count as u128) * (ratio_denom as u128);
+        if failures > allowed {
+            return Some(0);
+        }
+        let misses = (allowed - failures) / ((ratio_denom - ratio_numer) as u128) + 1;
+        Some(u64::try_from(misses).unwrap_or(u64::MAX))
+    }
+
     /// Increment a production stat structure with another
     pub fn extend(&mut self, stats: &ProductionStats) {
         self.block_success_count = self
@@ -337,54 +428,6 @@ impl Deserializer<PreHashMap<Address, ProductionStats>> for ProductionStatsDeser
     }
 }
 
-#[derive(Clone)]
-#[allow(missing_docs)]
-/// Deserializer for rolls
-pub struct RollsDeserializer {
-    length_deserializer: U64VarIntDeserializer,
-    pub address_deserializer: AddressDeserializer,
-    pub u64_deserializer: U64VarIntDeserializer,
-}
-
-impl RollsDeserializer {
-    /// Creates a new rolls deserializer
-    pub fn new(max_rolls_length: u64) -> RollsDeserializer {
-        RollsDeserializer {
-            length_deserializer: U64VarIntDeserializer::new(
-                Included(u64::MIN),
-                Included(max_rolls_length),
-            ),
-            address_deserializer: AddressDeserializer::new(),
-            u64_deserializer: U64VarIntDeserializer::new(Included(u64::MIN), Included(u64::MAX)),
-        }
-    }
-}
-
-impl Deserializer<Vec<(Address, u64)>> for RollsDeserializer {
-    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
-        &self,
-        buffer: &'a [u8],
-    ) -> IResult<&'a [u8], Vec<(Address, u64)>, E> {
-        context(
-            "Failed rolls deserialization",
-            length_count(
-                context("Failed length deserialization", |input| {
-                    self.length_deserializer.deserialize(input)
-                }),
-                tuple((
-                    context("Failed address deserialization", |input| {
-                        self.address_deserializer.deserialize(input)
-                    }),
-                    context("Failed number deserialization", |input| {
-                        self.u64_deserializer.deserialize(input)
-                    }),
-                )),
-            ),
-        )
-        .parse(buffer)
-    }
-}
-
 #[derive(Clone)]
 #[allow(missing_docs)]
 /// Serializer for cycle history
@@ -471,3 +514,138 @@ impl Deserializer<Vec<CycleInfo>> for CycleHistoryDeserializer {
         .parse(buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_misses_before_roll_sale() {
+        let max_miss_ratio = Ratio::new(7, 10);
+        let stats = ProductionStats {
+            block_success_count: 9,
+            block_failure_count: 1,
+        };
+        let misses = stats.misses_before_roll_sale(&max_miss_ratio).unwrap();
+        assert_eq!(misses, 21);
+        let mut after_misses = stats;
+        after_misses.block_failure_count += misses - 1;
+        assert!(after_misses.is_satisfying(&max_miss_ratio));
+        after_misses.block_failure_count += 1;
+        assert!(!after_misses.is_satisfying(&max_miss_ratio));
+        assert_eq!(
+            after_misses.misses_before_roll_sale(&max_miss_ratio),
+            Some(0)
+        );
+
+        // a single miss triggers the sale of an address without any production opportunity yet
+        assert_eq!(
+            ProductionStats::default().misses_before_roll_sale(&max_miss_ratio),
+            Some(1)
+        );
+        // misses never trigger the sale when every block can be missed
+        assert_eq!(stats.misses_before_roll_sale(&Ratio::new(1, 1)), None);
+    }
+
+    #[test]
+    fn test_cycle_info_serialization() {
+        use massa_serialization::DeserializeError;
+        use massa_signature::KeyPair;
+
+        let addresses: Vec<Address> = (0..2)
+            .map(|_| Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key()))
+            .collect();
+        let cycle_info = CycleInfo::new(
+            3,
+            true,
+            addresses.iter().map(|addr| (*addr, 10)).collect(),
+            BitVec::repeat(true, 16),
+            addresses
+                .iter()
+                .map(|addr| {
+                    (
+                        *addr,
+                        ProductionStats {
+                            block_success_count: 4,
+                            block_failure_count: 1,
+                        },
+                    )
+                })
+                .collect(),
+        );
+        let mut buffer = Vec::new();
+        CycleInfoSerializer::new()
+            .serialize(&cycle_info, &mut buffer)
+            .unwrap();
+
+        let (rest, deserialized) = CycleInfoDeserializer::new(2, 2)
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, cycle_info);
+
+        // the roll counts and production stats are bounded
+        assert!(CycleInfoDeserializer::new(1, 2)
+            .deserialize::<DeserializeError>(&buffer)
+            .is_err());
+        assert!(CycleInfoDeserializer::new(2, 1)
+            .deserialize::<DeserializeError>(&buffer)
+            .is_err());
+    }
+
+    #[test]
+    fn test_cycle_info_wire_format() {
+        use bitvec::{bitvec, order::Lsb0};
+        use massa_serialization::DeserializeError;
+
+        // user address, version 0
+        let address_bytes = [&[0, 0][..], &[0x11; 32]].concat();
+        let addr = Address::from_prefixed_bytes(&address_bytes).unwrap();
+        let cycle_info = CycleInfo::new(
+            300,
+            true,
+            BTreeMap::from([(addr, 200)]),
+            bitvec![u8, Lsb0; 1, 0, 1, 0, 0, 0, 0, 0],
+            [(
+                addr,
+                ProductionStats {
+                    block_success_count: 2,
+                    block_failure_count: 1,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        // bytes written by the hand-written serializer the derived one replaced
+        let expected = [
+            // cycle, complete
+            &[0xac, 0x02, 0x01][..],
+            // roll_counts: length, address, rolls
+            &[0x01],
+            &address_bytes,
+            &[0xc8, 0x01],
+            // rng_seed: bit count, bits
+            &[0x08, 0x05],
+            // production_stats: length, address, successes, failures
+            &[0x01],
+            &address_bytes,
+            &[0x02, 0x01],
+            // final_state_hash_snapshot: none
+            &[b'0'],
+        ]
+        .concat();
+        let mut serialized = Vec::new();
+        CycleInfoSerializer::new()
+            .serialize(&cycle_info, &mut serialized)
+            .unwrap();
+        assert_eq!(serialized, expected);
+
+        // the hashes skipped by the encoding are computed again
+        let (rest, deserialized) = CycleInfoDeserializer::new(10, 10)
+            .deserialize::<DeserializeError>(&expected)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, cycle_info);
+    }
+}
@@ -0,0 +1,20 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_hash::Hash;
+use serde::{Deserialize, Serialize};
+
+/// Events emitted by the proof-of-stake final state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoSEvent {
+    /// The last slot of a cycle was finalized
+    CycleCompleted {
+        /// completed cycle
+        cycle: u64,
+        /// hash of the final roll distribution of the cycle
+        roll_snapshot_hash: Hash,
+        /// total number of rolls at the end of the cycle
+        total_rolls: u64,
+        /// number of addresses owning at least one roll at the end of the cycle
+        stakers_count: u64,
+    },
+}
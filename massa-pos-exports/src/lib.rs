@@ -11,6 +11,7 @@ mod controller_traits;
 mod cycle_info;
 mod deferred_credits;
 mod error;
+mod events;
 mod pos_changes;
 mod pos_final_state;
 mod settings;
@@ -22,6 +23,7 @@ pub use controller_traits::{Selection, SelectorController, SelectorManager};
 pub use cycle_info::*;
 pub use deferred_credits::*;
 pub use error::*;
+pub use events::PoSEvent;
 pub use pos_changes::*;
 pub use pos_final_state::*;
 pub use settings::SelectorConfig;
@@ -1,36 +1,69 @@
 use crate::{
-    DeferredCredits, DeferredCreditsDeserializer, DeferredCreditsSerializer, ProductionStats,
-    ProductionStatsDeserializer, ProductionStatsSerializer, RollsDeserializer,
+    DeferredCredits, DeferredCreditsDeserializer, DeferredCreditsSerializer, PosError, PosResult,
+    ProductionStats, ProductionStatsDeserializer, ProductionStatsSerializer,
 };
 use bitvec::prelude::*;
 use massa_models::{
-    address::{Address, AddressSerializer},
+    address::{Address, AddressDeserializer, AddressSerializer},
+    amount::Amount,
     prehash::PreHashMap,
     serialization::{BitVecDeserializer, BitVecSerializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer},
 };
-use massa_serialization::{Deserializer, SerializeError, Serializer, U64VarIntSerializer};
-use nom::{
-    error::{context, ContextError, ParseError},
-    sequence::tuple,
-    IResult, Parser,
-};
+use massa_serialization::{MassaDeserialize, MassaSerialize};
 use serde::{Deserialize, Serialize};
+use std::ops::Bound::{Excluded, Included};
 
 /// Recap of all PoS changes
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, MassaSerialize, MassaDeserialize)]
+#[massa(deserializer_args(
+    thread_count: u8,
+    max_rolls_length: u64,
+    max_production_stats_length: u64,
+    max_credits_length: u64,
+))]
 pub struct PoSChanges {
     /// extra block seed bits added
+    #[massa(nested(serializer = BitVecSerializer, deserializer = BitVecDeserializer))]
     pub seed_bits: BitVec<u8>,
 
     /// new roll counts for addresses (can be 0 to remove the address from the registry)
+    #[massa(length_prefixed(
+        max_length = max_rolls_length,
+        key(nested(serializer = AddressSerializer, deserializer = AddressDeserializer)),
+        value(varint),
+    ))]
     pub roll_changes: PreHashMap<Address, u64>,
 
     /// updated production statistics
+    #[massa(nested(
+        serializer = ProductionStatsSerializer,
+        deserializer = ProductionStatsDeserializer::new(max_production_stats_length),
+    ))]
     pub production_stats: PreHashMap<Address, ProductionStats>,
 
     /// set deferred credits indexed by target slot (can be set to 0 to cancel some, in case of slash)
     /// ordered structure to ensure slot iteration order is deterministic
+    #[massa(nested(
+        serializer = DeferredCreditsSerializer,
+        deserializer = DeferredCreditsDeserializer::new(thread_count, max_credits_length),
+    ))]
     pub deferred_credits: DeferredCredits,
+
+    /// slot of the latest roll sale of the addresses that sold rolls,
+    /// only recorded when a cooldown between roll sales is configured
+    #[massa(length_prefixed(
+        max_length = max_rolls_length,
+        key(nested(serializer = AddressSerializer, deserializer = AddressDeserializer)),
+        value(nested(
+            serializer = SlotSerializer,
+            deserializer = SlotDeserializer::new(
+                (Included(u64::MIN), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            ),
+        )),
+    ))]
+    pub roll_sales: PreHashMap<Address, Slot>,
 }
 
 impl Default for PoSChanges {
@@ -40,6 +73,7 @@ impl Default for PoSChanges {
             roll_changes: Default::default(),
             production_stats: Default::default(),
             deferred_credits: DeferredCredits::new(),
+            roll_sales: Default::default(),
         }
     }
 }
@@ -51,6 +85,7 @@ impl PoSChanges {
             && self.roll_changes.is_empty()
             && self.production_stats.is_empty()
             && self.deferred_credits.credits.is_empty()
+            && self.roll_sales.is_empty()
     }
 
     /// Extends the current `PosChanges` with another one
@@ -71,123 +106,422 @@ impl PoSChanges {
 
         // extend deferred credits
         self.deferred_credits.extend(other.deferred_credits);
+
+        // extend roll sales
+        self.roll_sales.extend(other.roll_sales);
     }
-}
 
-/// `PoSChanges` Serializer
-pub struct PoSChangesSerializer {
-    bit_vec_serializer: BitVecSerializer,
-    u64_serializer: U64VarIntSerializer,
-    production_stats_serializer: ProductionStatsSerializer,
-    address_serializer: AddressSerializer,
-    deferred_credits_serializer: DeferredCreditsSerializer,
-}
+    /// Removes the changes that have no effect when applied on top of a base state:
+    /// * roll changes setting the roll count the address already has
+    /// * empty production statistics
+    /// * deferred credits setting the amount already credited (including zeroed credits that cancel nothing)
+    ///
+    /// # Arguments
+    /// * `base_roll_count`: returns the roll count of an address in the base state
+    /// * `base_deferred_credit`: returns the deferred credit of an address at a slot in the base state, if any
+    pub fn compact<R, C>(&mut self, base_roll_count: R, base_deferred_credit: C)
+    where
+        R: Fn(&Address) -> u64,
+        C: Fn(&Slot, &Address) -> Option<Amount>,
+    {
+        // remove no-op roll changes
+        self.roll_changes
+            .retain(|addr, roll_count| *roll_count != base_roll_count(addr));
 
-impl Default for PoSChangesSerializer {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+        // remove empty production stats
+        self.production_stats
+            .retain(|_addr, stats| *stats != ProductionStats::default());
 
-impl PoSChangesSerializer {
-    /// Create a new `PoSChanges` Serializer
-    pub fn new() -> PoSChangesSerializer {
-        PoSChangesSerializer {
-            bit_vec_serializer: BitVecSerializer::new(),
-            u64_serializer: U64VarIntSerializer::new(),
-            production_stats_serializer: ProductionStatsSerializer::new(),
-            address_serializer: AddressSerializer::new(),
-            deferred_credits_serializer: DeferredCreditsSerializer::new(),
+        // remove no-op deferred credits and the slots left empty
+        for (slot, credits) in self.deferred_credits.credits.iter_mut() {
+            credits.retain(|addr, amount| {
+                *amount != base_deferred_credit(slot, addr).unwrap_or_default()
+            });
         }
+        self.deferred_credits
+            .credits
+            .retain(|_slot, credits| !credits.is_empty());
     }
-}
 
-impl Serializer<PoSChanges> for PoSChangesSerializer {
-    fn serialize(&self, value: &PoSChanges, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
-        // seed_bits
-        self.bit_vec_serializer
-            .serialize(&value.seed_bits, buffer)?;
-
-        // roll_changes
-        self.u64_serializer
-            .serialize(&(value.roll_changes.len() as u64), buffer)?;
-        for (addr, roll) in value.roll_changes.iter() {
-            self.address_serializer.serialize(addr, buffer)?;
-            self.u64_serializer.serialize(roll, buffer)?;
+    /// Computes the minimal changes that lead from the changes `base` to the changes `target`,
+    /// that is the changes `diff` such that extending `base` with `diff` is equivalent to `target`.
+    ///
+    /// Fails if `target` does not derive from `base`: if the seed bits of `base` are not a prefix of those of `target`,
+    /// if production statistics decrease, or if an entry changed by `base` is not changed by `target`.
+    pub fn diff(base: &PoSChanges, target: &PoSChanges) -> PosResult<PoSChanges> {
+        let mut diff = PoSChanges::default();
+
+        // seed bits: keep the bits added after the ones of base
+        if !target.seed_bits.starts_with(&base.seed_bits) {
+            return Err(PosError::ContainerInconsistency(
+                "base seed bits are not a prefix of target seed bits".into(),
+            ));
         }
+        diff.seed_bits = target.seed_bits[base.seed_bits.len()..].to_bitvec();
 
-        // production_stats
-        self.production_stats_serializer
-            .serialize(&value.production_stats, buffer)?;
+        // roll changes: keep the values that differ from base
+        if let Some(addr) = base
+            .roll_changes
+            .keys()
+            .find(|addr| !target.roll_changes.contains_key(addr))
+        {
+            return Err(PosError::ContainerInconsistency(format!(
+                "roll change of address {} is missing from target",
+                addr
+            )));
+        }
+        diff.roll_changes = target
+            .roll_changes
+            .iter()
+            .filter(|(addr, roll_count)| base.roll_changes.get(addr) != Some(roll_count))
+            .map(|(addr, roll_count)| (*addr, *roll_count))
+            .collect();
+
+        // production stats: keep the increments over base
+        for (addr, base_stats) in base.production_stats.iter() {
+            if !target.production_stats.contains_key(addr) && *base_stats != Default::default() {
+                return Err(PosError::ContainerInconsistency(format!(
+                    "production stats of address {} are missing from target",
+                    addr
+                )));
+            }
+        }
+        for (addr, target_stats) in target.production_stats.iter() {
+            let base_stats = base.production_stats.get(addr).copied().unwrap_or_default();
+            let (Some(block_success_count), Some(block_failure_count)) = (
+                target_stats
+                    .block_success_count
+                    .checked_sub(base_stats.block_success_count),
+                target_stats
+                    .block_failure_count
+                    .checked_sub(base_stats.block_failure_count),
+            ) else {
+                return Err(PosError::ContainerInconsistency(format!(
+                    "production stats of address {} decrease from base to target",
+                    addr
+                )));
+            };
+            let stats = ProductionStats {
+                block_success_count,
+                block_failure_count,
+            };
+            if stats != ProductionStats::default() {
+                diff.production_stats.insert(*addr, stats);
+            }
+        }
 
-        // deferred_credits
-        self.deferred_credits_serializer
-            .serialize(&value.deferred_credits, buffer)?;
+        // deferred credits: keep the amounts that differ from base
+        for (slot, credits) in base.deferred_credits.credits.iter() {
+            if let Some(addr) = credits.keys().find(|addr| {
+                target
+                    .deferred_credits
+                    .get_address_credits_for_slot(addr, slot)
+                    .is_none()
+            }) {
+                return Err(PosError::ContainerInconsistency(format!(
+                    "deferred credit of address {} at slot {} is missing from target",
+                    addr, slot
+                )));
+            }
+        }
+        for (slot, credits) in target.deferred_credits.credits.iter() {
+            for (addr, amount) in credits.iter() {
+                if base
+                    .deferred_credits
+                    .get_address_credits_for_slot(addr, slot)
+                    != Some(*amount)
+                {
+                    diff.deferred_credits.insert(*slot, *addr, *amount);
+                }
+            }
+        }
 
-        Ok(())
+        // roll sales: keep the ones that differ from base
+        if let Some(addr) = base
+            .roll_sales
+            .keys()
+            .find(|addr| !target.roll_sales.contains_key(addr))
+        {
+            return Err(PosError::ContainerInconsistency(format!(
+                "roll sale of address {} is missing from target",
+                addr
+            )));
+        }
+        diff.roll_sales = target
+            .roll_sales
+            .iter()
+            .filter(|(addr, slot)| base.roll_sales.get(addr) != Some(slot))
+            .map(|(addr, slot)| (*addr, *slot))
+            .collect();
+
+        Ok(diff)
     }
 }
 
-/// `PoSChanges` Deserializer
-pub struct PoSChangesDeserializer {
-    bit_vec_deserializer: BitVecDeserializer,
-    rolls_deserializer: RollsDeserializer,
-    production_stats_deserializer: ProductionStatsDeserializer,
-    deferred_credits_deserializer: DeferredCreditsDeserializer,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
 
-impl PoSChangesDeserializer {
-    /// Create a new `PoSChanges` Deserializer
-    pub fn new(
-        thread_count: u8,
-        max_rolls_length: u64,
-        max_production_stats_length: u64,
-        max_credits_length: u64,
-    ) -> PoSChangesDeserializer {
-        PoSChangesDeserializer {
-            bit_vec_deserializer: BitVecDeserializer::new(),
-            rolls_deserializer: RollsDeserializer::new(max_rolls_length),
-            production_stats_deserializer: ProductionStatsDeserializer::new(
-                max_production_stats_length,
-            ),
-            deferred_credits_deserializer: DeferredCreditsDeserializer::new(
-                thread_count,
-                max_credits_length,
-            ),
+    fn new_address() -> Address {
+        Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_pos_changes_compact_and_diff() {
+        let addr_a = new_address();
+        let addr_b = new_address();
+        let slot = Slot::new(10, 0);
+        let amount = Amount::from_str("5").unwrap();
+
+        // compact against a base state where A has 3 rolls and a deferred credit
+        let mut changes = PoSChanges::default();
+        changes.roll_changes.insert(addr_a, 3);
+        changes.roll_changes.insert(addr_b, 1);
+        changes
+            .production_stats
+            .insert(addr_a, ProductionStats::default());
+        changes
+            .deferred_credits
+            .insert(slot, addr_a, Amount::zero());
+        changes
+            .deferred_credits
+            .insert(slot, addr_b, Amount::zero());
+        changes
+            .deferred_credits
+            .insert(Slot::new(11, 0), addr_b, amount);
+        changes.compact(
+            |addr| if *addr == addr_a { 3 } else { 0 },
+            |credit_slot, addr| (*credit_slot == slot && *addr == addr_a).then_some(amount),
+        );
+        assert_eq!(changes.roll_changes.len(), 1);
+        assert_eq!(changes.roll_changes.get(&addr_b), Some(&1));
+        assert!(changes.production_stats.is_empty());
+        assert_eq!(
+            changes
+                .deferred_credits
+                .get_address_credits_for_slot(&addr_a, &slot),
+            Some(Amount::zero())
+        );
+        assert_eq!(
+            changes
+                .deferred_credits
+                .get_address_credits_for_slot(&addr_b, &slot),
+            None
+        );
+        assert_eq!(changes.deferred_credits.credits.len(), 2);
+
+        // diff between two accumulated change sets
+        let mut base = PoSChanges::default();
+        base.seed_bits.push(true);
+        base.roll_changes.insert(addr_a, 2);
+        base.production_stats.insert(
+            addr_a,
+            ProductionStats {
+                block_success_count: 1,
+                block_failure_count: 0,
+            },
+        );
+        base.deferred_credits.insert(slot, addr_a, amount);
+        base.roll_sales.insert(addr_a, Slot::new(1, 0));
+
+        let mut extension = PoSChanges::default();
+        extension.seed_bits.push(false);
+        extension.roll_changes.insert(addr_a, 2);
+        extension.roll_changes.insert(addr_b, 4);
+        extension.production_stats.insert(
+            addr_a,
+            ProductionStats {
+                block_success_count: 0,
+                block_failure_count: 1,
+            },
+        );
+        extension.deferred_credits.insert(slot, addr_a, amount);
+        extension.deferred_credits.insert(slot, addr_b, amount);
+        extension.roll_sales.insert(addr_b, Slot::new(2, 0));
+
+        let mut target = base.clone();
+        target.extend(extension);
+
+        let diff = PoSChanges::diff(&base, &target).unwrap();
+        assert_eq!(diff.seed_bits, bitvec![u8, Lsb0; 0]);
+        assert_eq!(diff.roll_changes.len(), 1);
+        assert_eq!(diff.roll_changes.get(&addr_b), Some(&4));
+        assert_eq!(
+            diff.production_stats.get(&addr_a),
+            Some(&ProductionStats {
+                block_success_count: 0,
+                block_failure_count: 1,
+            })
+        );
+        assert_eq!(diff.deferred_credits.credits.len(), 1);
+        assert_eq!(
+            diff.deferred_credits
+                .get_address_credits_for_slot(&addr_b, &slot),
+            Some(amount)
+        );
+        assert_eq!(diff.roll_sales.len(), 1);
+        assert_eq!(diff.roll_sales.get(&addr_b), Some(&Slot::new(2, 0)));
+
+        // extending base with the diff gives back the target
+        let mut rebuilt = base.clone();
+        rebuilt.extend(diff);
+        assert_eq!(rebuilt.seed_bits, target.seed_bits);
+        assert_eq!(rebuilt.roll_changes, target.roll_changes);
+        assert_eq!(rebuilt.production_stats, target.production_stats);
+        assert_eq!(
+            rebuilt.deferred_credits.credits,
+            target.deferred_credits.credits
+        );
+        assert_eq!(rebuilt.roll_sales, target.roll_sales);
+
+        // target not derived from base
+        assert!(PoSChanges::diff(&target, &base).is_err());
+    }
+
+    #[test]
+    fn test_pos_changes_serialization() {
+        use massa_serialization::{
+            DeserializeError, Deserializer, Serializer, U64VarIntSerializer,
+        };
+
+        let addr_a = new_address();
+        let addr_b = new_address();
+        let mut changes = PoSChanges::default();
+        changes.seed_bits.extend([true, false, true]);
+        changes.roll_changes.insert(addr_a, 3);
+        changes.roll_changes.insert(addr_b, 0);
+        changes.production_stats.insert(
+            addr_a,
+            ProductionStats {
+                block_success_count: 2,
+                block_failure_count: 1,
+            },
+        );
+        changes
+            .deferred_credits
+            .insert(Slot::new(10, 1), addr_b, Amount::from_str("5").unwrap());
+        changes.roll_sales.insert(addr_a, Slot::new(7, 1));
+
+        // the derived serializer keeps the encoding of the fields in declaration order
+        let mut expected = Vec::new();
+        BitVecSerializer::new()
+            .serialize(&changes.seed_bits, &mut expected)
+            .unwrap();
+        U64VarIntSerializer::new()
+            .serialize(&(changes.roll_changes.len() as u64), &mut expected)
+            .unwrap();
+        for (addr, rolls) in changes.roll_changes.iter() {
+            AddressSerializer::new()
+                .serialize(addr, &mut expected)
+                .unwrap();
+            U64VarIntSerializer::new()
+                .serialize(rolls, &mut expected)
+                .unwrap();
         }
+        ProductionStatsSerializer::new()
+            .serialize(&changes.production_stats, &mut expected)
+            .unwrap();
+        DeferredCreditsSerializer::new()
+            .serialize(&changes.deferred_credits, &mut expected)
+            .unwrap();
+        U64VarIntSerializer::new()
+            .serialize(&1, &mut expected)
+            .unwrap();
+        AddressSerializer::new()
+            .serialize(&addr_a, &mut expected)
+            .unwrap();
+        SlotSerializer::new()
+            .serialize(&Slot::new(7, 1), &mut expected)
+            .unwrap();
+        let mut serialized = Vec::new();
+        PoSChangesSerializer::new()
+            .serialize(&changes, &mut serialized)
+            .unwrap();
+        assert_eq!(serialized, expected);
+
+        let (rest, deserialized) = PoSChangesDeserializer::new(32, 10, 10, 10)
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized.seed_bits, changes.seed_bits);
+        assert_eq!(deserialized.roll_changes, changes.roll_changes);
+        assert_eq!(deserialized.production_stats, changes.production_stats);
+        assert_eq!(
+            deserialized.deferred_credits.credits,
+            changes.deferred_credits.credits
+        );
+        assert_eq!(deserialized.roll_sales, changes.roll_sales);
+
+        // the length of the roll changes is bounded
+        assert!(PoSChangesDeserializer::new(32, 1, 10, 10)
+            .deserialize::<DeserializeError>(&serialized)
+            .is_err());
     }
-}
 
-impl Deserializer<PoSChanges> for PoSChangesDeserializer {
-    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
-        &self,
-        buffer: &'a [u8],
-    ) -> IResult<&'a [u8], PoSChanges, E> {
-        context(
-            "Failed PoSChanges deserialization",
-            tuple((
-                context("Failed bit_vec deserialization", |input| {
-                    self.bit_vec_deserializer.deserialize(input)
-                }),
-                context("Failed rolls deserialization", |input| {
-                    self.rolls_deserializer.deserialize(input)
-                }),
-                context("Failed production_stats deserialization", |input| {
-                    self.production_stats_deserializer.deserialize(input)
-                }),
-                context("Failed deferred_credits deserialization", |input| {
-                    self.deferred_credits_deserializer.deserialize(input)
-                }),
-            )),
-        )
-        .map(
-            |(seed_bits, roll_changes, production_stats, deferred_credits)| PoSChanges {
-                seed_bits,
-                roll_changes: roll_changes.into_iter().collect(),
-                production_stats,
-                deferred_credits,
+    #[test]
+    fn test_pos_changes_wire_format() {
+        use massa_serialization::{DeserializeError, Deserializer, Serializer};
+
+        // user address, version 0
+        let address_bytes = [&[0, 0][..], &[0x11; 32]].concat();
+        let addr = Address::from_prefixed_bytes(&address_bytes).unwrap();
+        let mut changes = PoSChanges::default();
+        changes.seed_bits = bitvec![u8, Lsb0; 1, 0, 1, 0, 0, 0, 0, 0];
+        changes.roll_changes.insert(addr, 200);
+        changes.production_stats.insert(
+            addr,
+            ProductionStats {
+                block_success_count: 2,
+                block_failure_count: 1,
             },
-        )
-        .parse(buffer)
+        );
+        changes
+            .deferred_credits
+            .insert(Slot::new(10, 1), addr, Amount::from_raw(300));
+        changes.roll_sales.insert(addr, Slot::new(7, 1));
+
+        // bytes written by the hand-written serializer the derived one replaced,
+        // followed by the roll sales added since
+        let expected = [
+            // seed_bits: bit count, bits
+            &[0x08, 0x05][..],
+            // roll_changes: length, address, rolls
+            &[0x01],
+            &address_bytes,
+            &[0xc8, 0x01],
+            // production_stats: length, address, successes, failures
+            &[0x01],
+            &address_bytes,
+            &[0x02, 0x01],
+            // deferred_credits: slot count, period, thread, credit count, address, amount
+            &[0x01, 0x0a, 0x01, 0x01],
+            &address_bytes,
+            &[0xac, 0x02],
+            // roll_sales: length, address, period, thread
+            &[0x01],
+            &address_bytes,
+            &[0x07, 0x01],
+        ]
+        .concat();
+        let mut serialized = Vec::new();
+        PoSChangesSerializer::new()
+            .serialize(&changes, &mut serialized)
+            .unwrap();
+        assert_eq!(serialized, expected);
+
+        let (rest, deserialized) = PoSChangesDeserializer::new(32, 10, 10, 10)
+            .deserialize::<DeserializeError>(&expected)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized.seed_bits, changes.seed_bits);
+        assert_eq!(deserialized.roll_changes, changes.roll_changes);
+        assert_eq!(deserialized.production_stats, changes.production_stats);
+        assert_eq!(
+            deserialized.deferred_credits.credits,
+            changes.deferred_credits.credits
+        );
+        assert_eq!(deserialized.roll_sales, changes.roll_sales);
     }
 }
@@ -1,7 +1,7 @@
 use crate::{
-    CycleHistoryDeserializer, CycleHistorySerializer, CycleInfo, DeferredCreditsDeserializer,
-    DeferredCreditsSerializer, PoSChanges, PosError, PosResult, ProductionStats,
-    SelectorController,
+    CycleHistoryDeserializer, CycleHistorySerializer, CycleInfo, CycleInfoHashComputer,
+    DeferredCreditsDeserializer, DeferredCreditsSerializer, PoSChanges, PoSEvent, PosError,
+    PosResult, ProductionStats, SelectorController,
 };
 use crate::{DeferredCredits, PoSConfig};
 use bitvec::vec::BitVec;
@@ -27,6 +27,7 @@ const RNG_SEED_IDENT: u8 = 1u8;
 const FINAL_STATE_HASH_SNAPSHOT_IDENT: u8 = 2u8;
 const ROLL_COUNT_IDENT: u8 = 3u8;
 const PROD_STATS_IDENT: u8 = 4u8;
+const ROLL_SALE_IDENT: u8 = 5u8;
 const UPPER_LIMIT: u8 = u8::MAX;
 
 // Production stats idents
@@ -78,6 +79,19 @@ macro_rules! roll_count_key {
     };
 }
 
+/// Roll sale key formatting macro
+#[macro_export]
+macro_rules! roll_sale_key {
+    ($cycle_prefix:expr, $addr:expr) => {
+        [
+            &$cycle_prefix[..],
+            &[ROLL_SALE_IDENT],
+            &$addr.to_prefixed_bytes()[..],
+        ]
+        .concat()
+    };
+}
+
 /// Production stats prefix macro
 #[macro_export]
 macro_rules! prod_stats_prefix {
@@ -141,11 +155,15 @@ pub struct PoSFinalState {
     pub cycle_history_cache: VecDeque<(u64, bool)>,
     /// rng_seed cache to get rng_seed for the current cycle
     pub rng_seed_cache: Option<(u64, BitVec<u8>)>,
+    /// roll counts and production stats hashes of the current cycle: (cycle, roll_counts_hash, production_stats_hash)
+    pub cycle_hashes_cache: Option<(u64, Hash, Hash)>,
+    /// cycle info hash computer
+    pub cycle_info_hash_computer: CycleInfoHashComputer,
     /// selector controller
     pub selector: Box<dyn SelectorController>,
     /// initial rolls, used for negative cycle look back
     pub initial_rolls: BTreeMap<Address, u64>,
-    /// initial seeds, used for negative cycle look back (cycles -2, -1 in that order)
+    /// initial seeds, used for negative cycle look back (cycles -lookback_cycles to -1 in that order)
     pub initial_seeds: Vec<Hash>,
     /// deferred credits serializer
     pub deferred_credits_serializer: DeferredCreditsSerializer,
@@ -166,6 +184,22 @@ impl PoSFinalState {
         selector: Box<dyn SelectorController>,
         db: ShareableMassaDBController,
     ) -> Result<Self, PosError> {
+        // draws need the seed of an earlier cycle
+        if config.lookback_cycles == 0 {
+            return Err(PosError::InvalidConfig(
+                "lookback_cycles must be at least 1".into(),
+            ));
+        }
+        // the draws of the current and previous cycles (endorsements on the limit between 2 cycles)
+        // need the roll counts of the cycle preceding their seed cycle, down to C - lookback_cycles - 2
+        let min_history_length = config.lookback_cycles.saturating_add(3);
+        if (config.cycle_history_length as u64) < min_history_length {
+            return Err(PosError::InvalidConfig(format!(
+                "cycle_history_length ({}) must be at least lookback_cycles + 3 ({})",
+                config.cycle_history_length, min_history_length
+            )));
+        }
+
         // load get initial rolls from file
         let initial_rolls = serde_json::from_str::<BTreeMap<Address, u64>>(
             &std::fs::read_to_string(initial_rolls_path).map_err(|err| {
@@ -174,9 +208,9 @@ impl PoSFinalState {
         )
         .map_err(|err| PosError::RollsFileLoadingError(format!("error opening file: {}", err)))?;
 
-        // Seeds used as the initial seeds for negative cycles (-2 and -1 respectively)
-        let init_seed = Hash::compute_from(initial_seed_string.as_bytes());
-        let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
+        // Seeds used as the initial seeds for negative cycles
+        let initial_seeds =
+            PoSFinalState::compute_initial_seeds(initial_seed_string, config.lookback_cycles);
 
         let deferred_credits_deserializer =
             DeferredCreditsDeserializer::new(config.thread_count, config.max_credit_length);
@@ -191,6 +225,8 @@ impl PoSFinalState {
             db,
             cycle_history_cache: Default::default(),
             rng_seed_cache: None,
+            cycle_hashes_cache: None,
+            cycle_info_hash_computer: CycleInfoHashComputer::new(),
             selector,
             initial_rolls,
             initial_seeds,
@@ -203,6 +239,26 @@ impl PoSFinalState {
         Ok(pos_state)
     }
 
+    /// Derives the seeds of the negative cycles `-lookback_cycles..=-1` (in that order) from the initial seed string.
+    ///
+    /// The seed of cycle -1 is the hash of the initial seed string,
+    /// and the seed of each previous cycle is the hash of the seed of the next one.
+    pub fn compute_initial_seeds(initial_seed_string: &str, lookback_cycles: u64) -> Vec<Hash> {
+        let mut seeds = Vec::with_capacity(lookback_cycles as usize);
+        let mut seed = Hash::compute_from(initial_seed_string.as_bytes());
+        for _ in 0..lookback_cycles {
+            seeds.push(seed);
+            seed = Hash::compute_from(seed.to_bytes());
+        }
+        seeds.reverse();
+        seeds
+    }
+
+    /// Number of cycles between the roll lookback cycle and the drawn cycle
+    fn roll_lookback_cycles(&self) -> u64 {
+        self.config.lookback_cycles.saturating_add(1)
+    }
+
     /// Try load initial deferred credits from file
     pub fn load_initial_deferred_credits(&mut self, batch: &mut DBBatch) -> Result<(), PosError> {
         let Some(initial_deferred_credits_path) = &self.config.initial_deferred_credits_path else {
@@ -256,6 +312,26 @@ impl PoSFinalState {
         } else {
             self.rng_seed_cache = None;
         }
+
+        self.cycle_hashes_cache = self
+            .cycle_history_cache
+            .back()
+            .map(|(cycle, _)| self.compute_cycle_hashes(*cycle));
+    }
+
+    /// Computes the roll counts and production stats hashes of a cycle from the database
+    fn compute_cycle_hashes(&self, cycle: u64) -> (u64, Hash, Hash) {
+        let roll_counts = self.get_all_roll_counts(cycle);
+        let production_stats = self
+            .get_all_production_stats(cycle)
+            .unwrap_or(PreHashMap::default());
+        (
+            cycle,
+            self.cycle_info_hash_computer
+                .compute_roll_counts_hash(&roll_counts),
+            self.cycle_info_hash_computer
+                .compute_production_stats_hash(&production_stats),
+        )
     }
 
     /// Reset the state of the PoS final state
@@ -267,6 +343,7 @@ impl PoSFinalState {
         db.delete_prefix(DEFERRED_CREDITS_PREFIX, STATE_CF, None);
         self.cycle_history_cache = Default::default();
         self.rng_seed_cache = None;
+        self.cycle_hashes_cache = None;
     }
 
     /// Create the initial cycle based off the initial rolls.
@@ -359,7 +436,7 @@ impl PoSFinalState {
     /// Sends the current draw inputs (initial or bootstrapped) to the selector.
     /// Waits for the initial draws to be performed.
     pub fn compute_initial_draws(&mut self) -> PosResult<()> {
-        // if cycle_history starts at a cycle that is strictly higher than 0, do not feed cycles 0, 1 to selector
+        // if cycle_history starts at a cycle that is strictly higher than 0, do not feed the first cycles to selector
         let history_starts_late = self
             .cycle_history_cache
             .front()
@@ -368,9 +445,9 @@ impl PoSFinalState {
 
         let mut max_cycle = None;
 
-        // feed cycles 0, 1 to selector if necessary
+        // feed the cycles drawn from negative cycles to selector if necessary
         if !history_starts_late {
-            for draw_cycle in 0u64..=1 {
+            for draw_cycle in 0u64..self.config.lookback_cycles {
                 self.feed_selector(draw_cycle)?;
                 max_cycle = Some(draw_cycle);
             }
@@ -386,9 +463,12 @@ impl PoSFinalState {
                 // because the roll distribution which should be provided by the previous element is absent.
                 continue;
             }
-            let draw_cycle = hist_item.0.checked_add(2).ok_or_else(|| {
-                PosError::OverflowError("cycle overflow in give_selector_controller".into())
-            })?;
+            let draw_cycle = hist_item
+                .0
+                .checked_add(self.config.lookback_cycles)
+                .ok_or_else(|| {
+                    PosError::OverflowError("cycle overflow in give_selector_controller".into())
+                })?;
             self.feed_selector(draw_cycle)?;
             max_cycle = Some(draw_cycle);
         }
@@ -405,7 +485,8 @@ impl PoSFinalState {
     /// set `self.last_final_slot` = C
     /// if cycle C is absent from `self.cycle_history_cache`:
     ///     `push` a new empty `CycleInfo` on disk and reflect in `self.cycle_history_cache` and set its cycle = C
-    ///     `pop_front` from `cycle_history_cache` until front() represents cycle C-4 or later (not C-3 because we might need older endorsement draws on the limit between 2 cycles)
+    ///     `pop_front` from `cycle_history_cache` until it holds at most `cycle_history_length` cycles,
+    ///     which keeps cycle C - lookback_cycles - 2 or later (not C - lookback_cycles - 1 because we might need older endorsement draws on the limit between 2 cycles)
     ///     delete the removed cycles from disk
     /// for the cycle C entry in the db:
     ///     extend `seed_bits` with `changes.seed_bits`
@@ -417,7 +498,7 @@ impl PoSFinalState {
     ///         remove entries for which Amount = 0
     /// if slot S was the last of cycle C:
     ///     set complete=true for cycle C in the history
-    ///     compute the seed hash and notifies the `PoSDrawer` for cycle `C+3`
+    ///     compute the seed hash and notifies the `PoSDrawer` for cycle `C + lookback_cycles`
     ///
     pub fn apply_changes_to_batch(
         &mut self,
@@ -436,16 +517,22 @@ impl PoSFinalState {
         // compute the current cycle from the given slot
         let cycle = slot.get_cycle(self.config.periods_per_cycle);
 
+        // cycle from which the previous roll counts are read when updating the cycle hashes:
+        // the entries of a cycle created in this batch are not readable from the DB yet
+        let mut prev_rolls_cycle = cycle;
+
         // if cycle C is absent from self.cycle_history:
         // push a new empty CycleInfo at the back of self.cycle_history and set its cycle = C
-        // pop_front from cycle_history until front() represents cycle C-4 or later
-        // (not C-3 because we might need older endorsement draws on the limit between 2 cycles)
+        // pop_front from cycle_history until it holds at most cycle_history_length cycles,
+        // which is checked at creation to keep cycle C - lookback_cycles - 2 or later
+        // (not C - lookback_cycles - 1 because we might need older endorsement draws on the limit between 2 cycles)
         if let Some(info) = self.cycle_history_cache.back() {
             if cycle == info.0 && !info.1 {
                 // extend the last incomplete cycle
             } else if info.0.checked_add(1) == Some(cycle) && info.1 {
                 // the previous cycle is complete, push a new incomplete/empty one to extend
 
+                prev_rolls_cycle = info.0;
                 let roll_counts = self.get_all_roll_counts(info.0);
                 self.put_new_cycle_info(
                     &CycleInfo::new(
@@ -484,8 +571,23 @@ impl PoSFinalState {
         rng_seed.extend(changes.seed_bits);
         self.put_cycle_history_rng_seed(cycle, rng_seed.clone(), batch);
 
+        let (_, mut roll_counts_hash, mut production_stats_hash) = match self.cycle_hashes_cache {
+            Some(cached) if cached.0 == cycle => cached,
+            _ => self.compute_cycle_hashes(cycle),
+        };
+
         // extend roll counts
         for (addr, roll_count) in changes.roll_changes {
+            if let Some(prev_roll_count) = self.get_roll_count_for_cycle(prev_rolls_cycle, &addr) {
+                roll_counts_hash ^= self
+                    .cycle_info_hash_computer
+                    .compute_roll_entry_hash(&addr, prev_roll_count);
+            }
+            if roll_count > 0 {
+                roll_counts_hash ^= self
+                    .cycle_info_hash_computer
+                    .compute_roll_entry_hash(&addr, roll_count);
+            }
             self.put_cycle_history_address_entry(cycle, &addr, Some(&roll_count), None, batch);
         }
 
@@ -495,6 +597,12 @@ impl PoSFinalState {
             {
                 let mut new_production_stats = prev_production_stats;
                 new_production_stats.extend(&stats);
+                production_stats_hash ^= self
+                    .cycle_info_hash_computer
+                    .compute_prod_stats_entry_hash(&addr, &prev_production_stats);
+                production_stats_hash ^= self
+                    .cycle_info_hash_computer
+                    .compute_prod_stats_entry_hash(&addr, &new_production_stats);
                 self.put_cycle_history_address_entry(
                     cycle,
                     &addr,
@@ -503,10 +611,15 @@ impl PoSFinalState {
                     batch,
                 );
             } else {
+                production_stats_hash ^= self
+                    .cycle_info_hash_computer
+                    .compute_prod_stats_entry_hash(&addr, &stats);
                 self.put_cycle_history_address_entry(cycle, &addr, None, Some(&stats), batch);
             }
         }
 
+        self.cycle_hashes_cache = Some((cycle, roll_counts_hash, production_stats_hash));
+
         // if the cycle just completed, check that it has the right number of seed bits
         if complete && rng_seed.len() != slots_per_cycle {
             panic!(
@@ -523,17 +636,29 @@ impl PoSFinalState {
             }
         }
 
+        // record the roll sales in the cycle of their slot, so that they are forgotten with it
+        for (address, sale_slot) in changes.roll_sales.iter() {
+            let sale_cycle = sale_slot.get_cycle(self.config.periods_per_cycle);
+            if self.get_cycle_index(sale_cycle).is_some() {
+                self.put_roll_sale_entry(sale_cycle, address, sale_slot, batch);
+            }
+        }
+
         // feed the cycle if it is complete
         // notify the PoSDrawer about the newly ready draw data
-        // to draw cycle + 2, we use the rng data from cycle - 1 and the seed from cycle
+        // to draw cycle + lookback_cycles, we use the rolls from cycle - 1 and the seed from cycle
         debug!(
             "After slot {} PoS cycle list is {:?}",
             slot, self.cycle_history_cache
         );
         if complete && feed_selector {
-            self.feed_selector(cycle.checked_add(2).ok_or_else(|| {
-                PosError::OverflowError("cycle overflow when feeding selector".into())
-            })?)
+            self.feed_selector(
+                cycle
+                    .checked_add(self.config.lookback_cycles)
+                    .ok_or_else(|| {
+                        PosError::OverflowError("cycle overflow when feeding selector".into())
+                    })?,
+            )
         } else {
             Ok(())
         }
@@ -543,33 +668,34 @@ impl PoSFinalState {
     pub fn feed_selector(&self, draw_cycle: u64) -> PosResult<()> {
         // get roll lookback
 
-        let (lookback_rolls, lookback_state_hash) = match draw_cycle.checked_sub(3) {
-            // looking back in history
-            Some(c) => {
-                let index = self
-                    .get_cycle_index(c)
-                    .ok_or(PosError::CycleUnavailable(c))?;
-                let cycle_info = &self.cycle_history_cache[index];
-                if !cycle_info.1 {
-                    return Err(PosError::CycleUnfinished(c));
+        let (lookback_rolls, lookback_state_hash) =
+            match draw_cycle.checked_sub(self.roll_lookback_cycles()) {
+                // looking back in history
+                Some(c) => {
+                    let index = self
+                        .get_cycle_index(c)
+                        .ok_or(PosError::CycleUnavailable(c))?;
+                    let cycle_info = &self.cycle_history_cache[index];
+                    if !cycle_info.1 {
+                        return Err(PosError::CycleUnfinished(c));
+                    }
+                    // take the final_state_hash_snapshot at the roll lookback cycle
+                    // it will later be combined with rng_seed from the seed lookback cycle to determine the selection seed
+                    // do this here to avoid a potential attacker manipulating the selections
+                    let state_hash = self.get_cycle_history_final_state_hash_snapshot(cycle_info.0);
+                    (
+                        self.get_all_roll_counts(cycle_info.0),
+                        Some(state_hash.expect(
+                            "critical: a complete cycle must contain a final state hash snapshot",
+                        )),
+                    )
                 }
-                // take the final_state_hash_snapshot at cycle - 3
-                // it will later be combined with rng_seed from cycle - 2 to determine the selection seed
-                // do this here to avoid a potential attacker manipulating the selections
-                let state_hash = self.get_cycle_history_final_state_hash_snapshot(cycle_info.0);
-                (
-                    self.get_all_roll_counts(cycle_info.0),
-                    Some(state_hash.expect(
-                        "critical: a complete cycle must contain a final state hash snapshot",
-                    )),
-                )
-            }
-            // looking back to negative cycles
-            None => (self.initial_rolls.clone(), None),
-        };
+                // looking back to negative cycles
+                None => (self.initial_rolls.clone(), None),
+            };
 
         // get seed lookback
-        let lookback_seed = match draw_cycle.checked_sub(2) {
+        let lookback_seed = match draw_cycle.checked_sub(self.config.lookback_cycles) {
             // looking back in history
             Some(c) => {
                 let index = self
@@ -593,7 +719,12 @@ impl PoSFinalState {
                 Hash::compute_from(&seed)
             }
             // looking back to negative cycles
-            None => self.initial_seeds[draw_cycle as usize],
+            None => *self.initial_seeds.get(draw_cycle as usize).ok_or_else(|| {
+                PosError::ContainerInconsistency(format!(
+                    "no initial seed to draw cycle {}",
+                    draw_cycle
+                ))
+            })?,
         };
 
         // feed selector
@@ -641,8 +772,7 @@ impl PoSFinalState {
                     let (_, amount) = self
                         .cycle_info_deserializer
                         .cycle_info_deserializer
-                        .rolls_deser
-                        .u64_deserializer
+                        .roll_counts_value
                         .deserialize::<DeserializeError>(&serialized_value)
                         .expect(CYCLE_HISTORY_DESER_ERROR);
 
@@ -656,7 +786,7 @@ impl PoSFinalState {
 
     /// Retrieves the amount of rolls a given address has at a given cycle
     pub fn get_address_active_rolls(&self, addr: &Address, cycle: u64) -> Option<u64> {
-        match cycle.checked_sub(3) {
+        match cycle.checked_sub(self.roll_lookback_cycles()) {
             Some(lookback_cycle) => {
                 let key = roll_count_key!(self.cycle_history_cycle_prefix(lookback_cycle), addr);
                 let db = self.db.read();
@@ -666,8 +796,7 @@ impl PoSFinalState {
                     let (_, amount) = self
                         .cycle_info_deserializer
                         .cycle_info_deserializer
-                        .rolls_deser
-                        .u64_deserializer
+                        .roll_counts_value
                         .deserialize::<DeserializeError>(&serialized_value)
                         .expect(CYCLE_HISTORY_DESER_ERROR);
 
@@ -680,9 +809,31 @@ impl PoSFinalState {
         }
     }
 
+    /// Retrieves the roll count of a given address for each cycle of `from_cycle..=to_cycle`.
+    ///
+    /// Cycles that are absent from the history are omitted from the result.
+    pub fn get_roll_history(
+        &self,
+        addr: &Address,
+        from_cycle: u64,
+        to_cycle: u64,
+    ) -> BTreeMap<u64, u64> {
+        self.cycle_history_cache
+            .iter()
+            .filter(|(cycle, _)| (from_cycle..=to_cycle).contains(cycle))
+            .map(|(cycle, _)| {
+                (
+                    *cycle,
+                    self.get_roll_count_for_cycle(*cycle, addr)
+                        .unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
     /// Gets all active rolls for a given cycle
     pub fn get_all_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64> {
-        match cycle.checked_sub(3) {
+        match cycle.checked_sub(self.roll_lookback_cycles()) {
             Some(lookback_cycle) => {
                 // get rolls
                 self.get_all_roll_counts(lookback_cycle)
@@ -843,23 +994,21 @@ impl PoSFinalState {
             let (rest, _cycle) = self
                 .cycle_info_deserializer
                 .cycle_info_deserializer
-                .u64_deser
+                .cycle
                 .deserialize::<DeserializeError>(&serialized_key[CYCLE_HISTORY_PREFIX.len()..])
                 .expect(CYCLE_HISTORY_DESER_ERROR);
 
             let (_, address) = self
                 .cycle_info_deserializer
                 .cycle_info_deserializer
-                .rolls_deser
-                .address_deserializer
+                .roll_counts_key
                 .deserialize::<DeserializeError>(&rest[1..])
                 .expect(CYCLE_HISTORY_DESER_ERROR);
 
             let (_, amount) = self
                 .cycle_info_deserializer
                 .cycle_info_deserializer
-                .rolls_deser
-                .u64_deserializer
+                .roll_counts_value
                 .deserialize::<DeserializeError>(&serialized_value)
                 .expect(CYCLE_HISTORY_DESER_ERROR);
 
@@ -894,14 +1043,14 @@ impl PoSFinalState {
             let (rest, _cycle) = self
                 .cycle_info_deserializer
                 .cycle_info_deserializer
-                .u64_deser
+                .cycle
                 .deserialize::<DeserializeError>(&serialized_key[CYCLE_HISTORY_PREFIX.len()..])
                 .expect(CYCLE_HISTORY_DESER_ERROR);
 
             let (rest, address) = self
                 .cycle_info_deserializer
                 .cycle_info_deserializer
-                .production_stats_deser
+                .production_stats
                 .address_deserializer
                 .deserialize::<DeserializeError>(&rest[1..])
                 .expect(CYCLE_HISTORY_DESER_ERROR);
@@ -914,7 +1063,7 @@ impl PoSFinalState {
             let (_, value) = self
                 .cycle_info_deserializer
                 .cycle_info_deserializer
-                .production_stats_deser
+                .production_stats
                 .u64_deserializer
                 .deserialize::<DeserializeError>(&serialized_value)
                 .expect(CYCLE_HISTORY_DESER_ERROR);
@@ -957,7 +1106,7 @@ impl PoSFinalState {
         let (_, rng_seed) = self
             .cycle_info_deserializer
             .cycle_info_deserializer
-            .bitvec_deser
+            .rng_seed
             .deserialize::<DeserializeError>(&serialized_rng_seed)
             .expect(CYCLE_HISTORY_DESER_ERROR);
 
@@ -983,7 +1132,7 @@ impl PoSFinalState {
         let (_, state_hash) = self
             .cycle_info_deserializer
             .cycle_info_deserializer
-            .opt_hash_deser
+            .final_state_hash_snapshot
             .deserialize::<DeserializeError>(&serialized_state_hash)
             .expect(CYCLE_HISTORY_DESER_ERROR);
         state_hash
@@ -1026,7 +1175,7 @@ impl PoSFinalState {
                 let (_, cycle) = self
                     .cycle_info_deserializer
                     .cycle_info_deserializer
-                    .u64_deser
+                    .cycle
                     .deserialize::<DeserializeError>(&serialized_key[CYCLE_HISTORY_PREFIX.len()..])
                     .expect(CYCLE_HISTORY_DESER_ERROR);
 
@@ -1069,6 +1218,52 @@ impl PoSFinalState {
         Some(cycle_info)
     }
 
+    /// Builds the `CycleCompleted` event of a cycle from its final roll distribution.
+    ///
+    /// Returns `None` if the cycle is absent from history or is not complete yet.
+    pub fn get_cycle_completed_event(&self, cycle: u64) -> Option<PoSEvent> {
+        let index = self.get_cycle_index(cycle)?;
+        if !self.cycle_history_cache[index].1 {
+            return None;
+        }
+        let roll_counts = self.get_all_roll_counts(cycle);
+        Some(PoSEvent::CycleCompleted {
+            cycle,
+            roll_snapshot_hash: self
+                .cycle_info_hash_computer
+                .compute_roll_counts_hash(&roll_counts),
+            total_rolls: roll_counts
+                .values()
+                .fold(0u64, |acc, v| acc.saturating_add(*v)),
+            stakers_count: roll_counts.len() as u64,
+        })
+    }
+
+    /// Gets the canonical hash of a cycle, as computed by `CycleInfo::compute_hash`.
+    ///
+    /// The hash of the current cycle is derived from the incrementally updated cache.
+    pub fn get_cycle_hash(&self, cycle: u64) -> Option<Hash> {
+        if let (
+            Some((cached_cycle, roll_counts_hash, production_stats_hash)),
+            Some((seed_cycle, rng_seed)),
+        ) = (&self.cycle_hashes_cache, &self.rng_seed_cache)
+        {
+            if *cached_cycle == cycle && *seed_cycle == cycle {
+                return Some(
+                    self.cycle_info_hash_computer.compute_cycle_hash(
+                        cycle,
+                        *roll_counts_hash,
+                        self.cycle_info_hash_computer
+                            .compute_rng_seed_hash(rng_seed),
+                        *production_stats_hash,
+                    ),
+                );
+            }
+        }
+        self.get_cycle_info(cycle)
+            .map(|cycle_info| cycle_info.cycle_global_hash)
+    }
+
     /// Gets the deferred credits for a given address that will be credited at a given slot
     pub fn get_address_credits_for_slot(&self, addr: &Address, slot: &Slot) -> Option<Amount> {
         let db = self.db.read();
@@ -1098,6 +1293,42 @@ impl PoSFinalState {
         }
     }
 
+    /// Gets the roll count of a given address at a given cycle
+    fn get_roll_count_for_cycle(&self, cycle: u64, addr: &Address) -> Option<u64> {
+        let key = roll_count_key!(self.cycle_history_cycle_prefix(cycle), addr);
+        let serialized_value = self
+            .db
+            .read()
+            .get_cf(STATE_CF, key)
+            .expect(CYCLE_HISTORY_DESER_ERROR)?;
+        let (_, amount) = self
+            .cycle_info_deserializer
+            .cycle_info_deserializer
+            .roll_counts_value
+            .deserialize::<DeserializeError>(&serialized_value)
+            .expect(CYCLE_HISTORY_DESER_ERROR);
+        Some(amount)
+    }
+
+    /// Gets the slot of the latest roll sale of an address recorded in the cycle history, if any
+    pub fn get_address_last_roll_sale(&self, addr: &Address) -> Option<Slot> {
+        let db = self.db.read();
+        self.cycle_history_cache
+            .iter()
+            .rev()
+            .find_map(|(cycle, _)| {
+                let key = roll_sale_key!(self.cycle_history_cycle_prefix(*cycle), addr);
+                let serialized_value =
+                    db.get_cf(STATE_CF, key).expect(CYCLE_HISTORY_DESER_ERROR)?;
+                let (_, slot) = self
+                    .deferred_credits_deserializer
+                    .slot_deserializer
+                    .deserialize::<DeserializeError>(&serialized_value)
+                    .expect(CYCLE_HISTORY_DESER_ERROR);
+                Some(slot)
+            })
+    }
+
     /// Gets the production stats for a given address
     pub fn get_production_stats_for_address(
         &self,
@@ -1120,14 +1351,14 @@ impl PoSFinalState {
                 let (_, fail) = self
                     .cycle_info_deserializer
                     .cycle_info_deserializer
-                    .production_stats_deser
+                    .production_stats
                     .u64_deserializer
                     .deserialize::<DeserializeError>(serialized_fail)
                     .expect(CYCLE_HISTORY_DESER_ERROR);
                 let (_, success) = self
                     .cycle_info_deserializer
                     .cycle_info_deserializer
-                    .production_stats_deser
+                    .production_stats
                     .u64_deserializer
                     .deserialize::<DeserializeError>(serialized_success)
                     .expect(CYCLE_HISTORY_DESER_ERROR);
@@ -1189,6 +1420,11 @@ impl PoSFinalState {
         }
         self.cycle_history_cache
             .push_back((cycle_info.cycle, cycle_info.complete));
+        self.cycle_hashes_cache = Some((
+            cycle_info.cycle,
+            cycle_info.roll_counts_hash,
+            cycle_info.production_stats_hash,
+        ));
     }
 
     /// Helper function to put a the complete flag for a given cycle
@@ -1220,7 +1456,7 @@ impl PoSFinalState {
         let mut serialized_value = Vec::new();
         self.cycle_info_serializer
             .cycle_info_serializer
-            .opt_hash_ser
+            .final_state_hash_snapshot
             .serialize(&value, &mut serialized_value)
             .expect(CYCLE_HISTORY_SER_ERROR);
 
@@ -1240,7 +1476,7 @@ impl PoSFinalState {
         let mut serialized_value = Vec::new();
         self.cycle_info_serializer
             .cycle_info_serializer
-            .bitvec_ser
+            .rng_seed
             .serialize(&value, &mut serialized_value)
             .expect(CYCLE_HISTORY_SER_ERROR);
 
@@ -1269,7 +1505,7 @@ impl PoSFinalState {
             let mut serialized_roll_count = Vec::new();
             self.cycle_info_serializer
                 .cycle_info_serializer
-                .u64_ser
+                .roll_counts_value
                 .serialize(roll_count, &mut serialized_roll_count)
                 .expect(CYCLE_HISTORY_SER_ERROR);
             db.put_or_update_entry_value(
@@ -1284,6 +1520,7 @@ impl PoSFinalState {
             let mut serialized_prod_stats_fail = Vec::new();
             self.cycle_info_serializer
                 .cycle_info_serializer
+                .production_stats
                 .u64_ser
                 .serialize(
                     &production_stats.block_failure_count,
@@ -1300,6 +1537,7 @@ impl PoSFinalState {
             let mut serialized_prod_stats_success = Vec::new();
             self.cycle_info_serializer
                 .cycle_info_serializer
+                .production_stats
                 .u64_ser
                 .serialize(
                     &production_stats.block_success_count,
@@ -1314,6 +1552,19 @@ impl PoSFinalState {
         }
     }
 
+    /// Helper function to put the slot of the roll sale of an address in a given cycle
+    fn put_roll_sale_entry(&self, cycle: u64, address: &Address, slot: &Slot, batch: &mut DBBatch) {
+        let db = self.db.read();
+
+        let prefix = self.cycle_history_cycle_prefix(cycle);
+        let mut serialized_slot = Vec::new();
+        self.deferred_credits_serializer
+            .slot_ser
+            .serialize(slot, &mut serialized_slot)
+            .expect(CYCLE_HISTORY_SER_ERROR);
+        db.put_or_update_entry_value(batch, roll_sale_key!(prefix, address), &serialized_slot);
+    }
+
     /// Internal function to put an entry
     pub fn put_deferred_credits_entry(
         &self,
@@ -1362,7 +1613,7 @@ impl PoSFinalState {
         serialized_key.extend_from_slice(CYCLE_HISTORY_PREFIX.as_bytes());
         self.cycle_info_serializer
             .cycle_info_serializer
-            .u64_ser
+            .cycle
             .serialize(&cycle, &mut serialized_key)
             .expect(CYCLE_HISTORY_SER_ERROR);
         serialized_key
@@ -1378,11 +1629,11 @@ impl PoSFinalState {
             return false;
         }
 
-        let Ok((rest, _cycle)) = self
-            .cycle_info_deserializer
-            .cycle_info_deserializer
-            .u64_deser
-            .deserialize::<DeserializeError>(&serialized_key[CYCLE_HISTORY_PREFIX.len()..])
+        let Ok((rest, _cycle)) =
+            self.cycle_info_deserializer
+                .cycle_info_deserializer
+                .cycle
+                .deserialize::<DeserializeError>(&serialized_key[CYCLE_HISTORY_PREFIX.len()..])
         else {
             return false;
         };
@@ -1410,7 +1661,7 @@ impl PoSFinalState {
                 let Ok((rest, _rng_seed)) = self
                     .cycle_info_deserializer
                     .cycle_info_deserializer
-                    .bitvec_deser
+                    .rng_seed
                     .deserialize::<DeserializeError>(serialized_value)
                 else {
                     return false;
@@ -1426,7 +1677,7 @@ impl PoSFinalState {
                 let Ok((rest, _final_state_hash)) = self
                     .cycle_info_deserializer
                     .cycle_info_deserializer
-                    .opt_hash_deser
+                    .final_state_hash_snapshot
                     .deserialize::<DeserializeError>(serialized_value)
                 else {
                     return false;
@@ -1442,8 +1693,7 @@ impl PoSFinalState {
                 > = self
                     .cycle_info_deserializer
                     .cycle_info_deserializer
-                    .rolls_deser
-                    .address_deserializer
+                    .roll_counts_key
                     .deserialize::<DeserializeError>(&rest[1..])
                 else {
                     return false;
@@ -1454,8 +1704,7 @@ impl PoSFinalState {
                 let Ok((rest, _addr)) = self
                     .cycle_info_deserializer
                     .cycle_info_deserializer
-                    .rolls_deser
-                    .u64_deserializer
+                    .roll_counts_value
                     .deserialize::<DeserializeError>(serialized_value)
                 else {
                     return false;
@@ -1471,8 +1720,7 @@ impl PoSFinalState {
                 > = self
                     .cycle_info_deserializer
                     .cycle_info_deserializer
-                    .rolls_deser
-                    .address_deserializer
+                    .roll_counts_key
                     .deserialize::<DeserializeError>(&rest[1..])
                 else {
                     return false;
@@ -1486,7 +1734,7 @@ impl PoSFinalState {
                         let Ok((rest, _fail)) = self
                             .cycle_info_deserializer
                             .cycle_info_deserializer
-                            .production_stats_deser
+                            .production_stats
                             .u64_deserializer
                             .deserialize::<DeserializeError>(serialized_value)
                         else {
@@ -1500,7 +1748,7 @@ impl PoSFinalState {
                         let Ok((rest, _success)) = self
                             .cycle_info_deserializer
                             .cycle_info_deserializer
-                            .production_stats_deser
+                            .production_stats
                             .u64_deserializer
                             .deserialize::<DeserializeError>(serialized_value)
                         else {
@@ -1515,6 +1763,32 @@ impl PoSFinalState {
                     }
                 }
             }
+            ROLL_SALE_IDENT => {
+                let Ok((rest, _addr)): std::result::Result<
+                    (&[u8], Address),
+                    nom::Err<massa_serialization::DeserializeError<'_>>,
+                > = self
+                    .cycle_info_deserializer
+                    .cycle_info_deserializer
+                    .roll_counts_key
+                    .deserialize::<DeserializeError>(&rest[1..])
+                else {
+                    return false;
+                };
+                if !rest.is_empty() {
+                    return false;
+                }
+                let Ok((rest, _slot)) = self
+                    .deferred_credits_deserializer
+                    .slot_deserializer
+                    .deserialize::<DeserializeError>(serialized_value)
+                else {
+                    return false;
+                };
+                if !rest.is_empty() {
+                    return false;
+                }
+            }
             _ => {
                 return false;
             }
@@ -1625,7 +1899,7 @@ mod tests {
         use massa_db_worker::MassaDB;
         use massa_models::config::constants::{
             MAX_DEFERRED_CREDITS_LENGTH, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
-            POS_SAVED_CYCLES,
+            POS_LOOKBACK_CYCLES, POS_SAVED_CYCLES,
         };
         use parking_lot::RwLock;
         use std::str::FromStr;
@@ -1663,6 +1937,7 @@ mod tests {
             periods_per_cycle: 2,
             thread_count: 2,
             cycle_history_length: POS_SAVED_CYCLES,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
             max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
             max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
             max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
@@ -1676,6 +1951,7 @@ mod tests {
             max_history_length: 10,
             max_new_elements: 100,
             thread_count: 2,
+            block_cache_size: 0,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -1697,6 +1973,8 @@ mod tests {
             db: db.clone(),
             cycle_history_cache: Default::default(),
             rng_seed_cache: None,
+            cycle_hashes_cache: None,
+            cycle_info_hash_computer: CycleInfoHashComputer::new(),
             selector: selector_controller,
             initial_rolls: Default::default(),
             initial_seeds,
@@ -1764,7 +2042,7 @@ mod tests {
         use massa_db_worker::MassaDB;
         use massa_models::config::constants::{
             MAX_DEFERRED_CREDITS_LENGTH, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
-            POS_SAVED_CYCLES,
+            POS_LOOKBACK_CYCLES, POS_SAVED_CYCLES,
         };
         use parking_lot::RwLock;
         use std::sync::Arc;
@@ -1774,6 +2052,7 @@ mod tests {
             periods_per_cycle: 2,
             thread_count: 2,
             cycle_history_length: POS_SAVED_CYCLES,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
             max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
             max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
             max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
@@ -1787,6 +2066,7 @@ mod tests {
             max_history_length: 10,
             max_new_elements: 100,
             thread_count: 2,
+            block_cache_size: 0,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -1808,6 +2088,8 @@ mod tests {
             db: db.clone(),
             cycle_history_cache: Default::default(),
             rng_seed_cache: None,
+            cycle_hashes_cache: None,
+            cycle_info_hash_computer: CycleInfoHashComputer::new(),
             selector: selector_controller,
             initial_rolls: Default::default(),
             initial_seeds,
@@ -1876,7 +2158,7 @@ mod tests {
         use massa_db_worker::MassaDB;
         use massa_models::config::constants::{
             MAX_DEFERRED_CREDITS_LENGTH, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
-            POS_SAVED_CYCLES,
+            POS_LOOKBACK_CYCLES, POS_SAVED_CYCLES,
         };
         use massa_signature::KeyPair;
         use parking_lot::RwLock;
@@ -1887,6 +2169,7 @@ mod tests {
             periods_per_cycle: 2,
             thread_count: 2,
             cycle_history_length: POS_SAVED_CYCLES,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
             max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
             max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
             max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
@@ -1900,6 +2183,7 @@ mod tests {
             max_history_length: 10,
             max_new_elements: 100,
             thread_count: 2,
+            block_cache_size: 0,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -1921,6 +2205,8 @@ mod tests {
             db: db.clone(),
             cycle_history_cache: Default::default(),
             rng_seed_cache: None,
+            cycle_hashes_cache: None,
+            cycle_info_hash_computer: CycleInfoHashComputer::new(),
             selector: selector_controller,
             initial_rolls: Default::default(),
             initial_seeds,
@@ -1954,6 +2240,7 @@ mod tests {
             roll_changes: roll_changes.clone(),
             production_stats: production_stats.clone(),
             deferred_credits: DeferredCredits::new(),
+            roll_sales: Default::default(),
         };
 
         let mut batch = DBBatch::new();
@@ -1979,6 +2266,7 @@ mod tests {
             roll_changes: roll_changes.clone(),
             production_stats: production_stats.clone(),
             deferred_credits: DeferredCredits::new(),
+            roll_sales: Default::default(),
         };
 
         let mut batch = DBBatch::new();
@@ -2005,6 +2293,7 @@ mod tests {
             roll_changes,
             production_stats,
             deferred_credits: DeferredCredits::new(),
+            roll_sales: Default::default(),
         };
 
         let mut batch = DBBatch::new();
@@ -2039,5 +2328,494 @@ mod tests {
         );
 
         assert_eq!(cycle_info_a, cycle_info_b, "cycle_info mismatch");
+        assert_eq!(
+            cycle_info_a.cycle_global_hash,
+            cycle_info_b.compute_hash(),
+            "cycle hash mismatch"
+        );
+        assert_eq!(
+            pos_state.get_cycle_hash(0),
+            Some(cycle_info_b.compute_hash()),
+            "incrementally updated cycle hash mismatch"
+        );
+
+        let mut incremental_cycle_info = CycleInfo::new(
+            0,
+            false,
+            BTreeMap::default(),
+            bitvec![u8, Lsb0; 0, 0, 0, 1, 1, 0],
+            HashMap::default(),
+        );
+        incremental_cycle_info.apply_changes(
+            &PoSChanges {
+                seed_bits: bitvec![u8, Lsb0; 0, 1],
+                roll_changes: vec![(addr, 7)].into_iter().collect(),
+                production_stats: cycle_info_b.production_stats.clone(),
+                deferred_credits: DeferredCredits::new(),
+                roll_sales: Default::default(),
+            },
+            false,
+        );
+        incremental_cycle_info.apply_changes(
+            &PoSChanges {
+                roll_changes: vec![(addr, 0)].into_iter().collect(),
+                ..Default::default()
+            },
+            false,
+        );
+        assert_eq!(
+            incremental_cycle_info, cycle_info_b,
+            "incrementally updated cycle_info mismatch"
+        );
+    }
+
+    // This test checks that the initial seeds are derived backwards from the initial seed string
+    #[test]
+    fn test_initial_seeds_derivation() {
+        let init_seed = Hash::compute_from(b"genesis");
+        assert_eq!(
+            PoSFinalState::compute_initial_seeds("genesis", 2),
+            vec![Hash::compute_from(init_seed.to_bytes()), init_seed],
+            "initial seeds mismatch"
+        );
+
+        let seeds = PoSFinalState::compute_initial_seeds("genesis", 5);
+        assert_eq!(seeds.len(), 5, "wrong number of initial seeds");
+        assert_eq!(
+            seeds[3..],
+            PoSFinalState::compute_initial_seeds("genesis", 2)[..],
+            "deeper lookback should only prepend seeds"
+        );
+        for pair in seeds.windows(2) {
+            assert_eq!(
+                pair[0],
+                Hash::compute_from(pair[1].to_bytes()),
+                "seed is not derived from the next one"
+            );
+        }
+        assert!(PoSFinalState::compute_initial_seeds("genesis", 0).is_empty());
+    }
+
+    // This test checks that a PoS state cannot be created without any lookback cycle,
+    // or with a cycle history too short for its lookback
+    #[test]
+    fn test_lookback_cycles_validation() {
+        use crate::test_exports::MockSelectorController;
+        use crate::PoSFinalState;
+        use massa_db_exports::{MassaDBConfig, MassaDBController};
+        use massa_db_worker::MassaDB;
+        use massa_models::config::constants::{
+            MAX_DEFERRED_CREDITS_LENGTH, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
+            POS_SAVED_CYCLES,
+        };
+        use parking_lot::RwLock;
+        use std::sync::Arc;
+
+        let tempdir = tempfile::TempDir::new().expect("cannot create temp directory");
+        let db_config = MassaDBConfig {
+            path: tempdir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 2,
+            block_cache_size: 0,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+
+        // (lookback_cycles, cycle_history_length)
+        for (lookback_cycles, cycle_history_length) in [
+            (0, POS_SAVED_CYCLES),
+            (POS_SAVED_CYCLES as u64 - 2, POS_SAVED_CYCLES),
+        ] {
+            let pos_config = PoSConfig {
+                periods_per_cycle: 2,
+                thread_count: 2,
+                cycle_history_length,
+                lookback_cycles,
+                max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+                max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+                max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+                initial_deferred_credits_path: None,
+            };
+            let (selector_controller, _) = MockSelectorController::new_with_receiver();
+            let res = PoSFinalState::new(
+                pos_config,
+                "genesis",
+                &tempdir.path().join("initial_rolls.json"),
+                selector_controller,
+                db.clone(),
+            );
+            assert!(matches!(res, Err(PosError::InvalidConfig(_))));
+        }
+    }
+
+    // This test checks that a completion event is only built for complete cycles and reflects their rolls
+    #[test]
+    fn test_cycle_completed_event() {
+        use crate::test_exports::MockSelectorController;
+        use crate::PoSFinalState;
+        use massa_db_exports::{MassaDBConfig, MassaDBController};
+        use massa_db_worker::MassaDB;
+        use massa_models::config::constants::{
+            MAX_DEFERRED_CREDITS_LENGTH, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
+            POS_LOOKBACK_CYCLES, POS_SAVED_CYCLES,
+        };
+        use massa_signature::KeyPair;
+        use parking_lot::RwLock;
+        use std::sync::Arc;
+        use tempfile::TempDir;
+
+        let pos_config = PoSConfig {
+            periods_per_cycle: 2,
+            thread_count: 2,
+            cycle_history_length: POS_SAVED_CYCLES,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
+            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+            initial_deferred_credits_path: None,
+        };
+
+        // initialize the database and pos_state
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let db_config = MassaDBConfig {
+            path: tempdir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 2,
+            block_cache_size: 0,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+        let (selector_controller, _) = MockSelectorController::new_with_receiver();
+        let init_seed = Hash::compute_from(b"");
+        let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
+
+        let deferred_credits_deserializer =
+            DeferredCreditsDeserializer::new(pos_config.thread_count, pos_config.max_credit_length);
+        let cycle_info_deserializer = CycleHistoryDeserializer::new(
+            pos_config.cycle_history_length as u64,
+            pos_config.max_rolls_length,
+            pos_config.max_production_stats_length,
+        );
+
+        let mut pos_state = PoSFinalState {
+            config: pos_config,
+            db: db.clone(),
+            cycle_history_cache: Default::default(),
+            rng_seed_cache: None,
+            cycle_hashes_cache: None,
+            cycle_info_hash_computer: CycleInfoHashComputer::new(),
+            selector: selector_controller,
+            initial_rolls: Default::default(),
+            initial_seeds,
+            deferred_credits_serializer: DeferredCreditsSerializer::new(),
+            deferred_credits_deserializer,
+            cycle_info_serializer: CycleHistorySerializer::new(),
+            cycle_info_deserializer,
+        };
+
+        let addr_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let addr_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let roll_counts: BTreeMap<Address, u64> =
+            vec![(addr_a, 10), (addr_b, 5)].into_iter().collect();
+
+        let mut batch = DBBatch::new();
+        pos_state.put_new_cycle_info(
+            &CycleInfo::new(
+                0,
+                true,
+                roll_counts.clone(),
+                Default::default(),
+                Default::default(),
+            ),
+            &mut batch,
+        );
+        pos_state.put_new_cycle_info(
+            &CycleInfo::new(
+                1,
+                false,
+                roll_counts.clone(),
+                Default::default(),
+                Default::default(),
+            ),
+            &mut batch,
+        );
+        db.write().write_batch(batch, DBBatch::new(), None);
+
+        assert_eq!(
+            pos_state.get_cycle_completed_event(0),
+            Some(PoSEvent::CycleCompleted {
+                cycle: 0,
+                roll_snapshot_hash: CycleInfoHashComputer::new()
+                    .compute_roll_counts_hash(&roll_counts),
+                total_rolls: 15,
+                stakers_count: 2,
+            }),
+            "wrong cycle completed event"
+        );
+        assert_eq!(
+            pos_state.get_cycle_completed_event(1),
+            None,
+            "incomplete cycle should not produce an event"
+        );
+        assert_eq!(
+            pos_state.get_cycle_completed_event(2),
+            None,
+            "unknown cycle should not produce an event"
+        );
+    }
+
+    // This test checks the roll history of an address, from an empty history to a pruned one
+    #[test]
+    fn test_get_roll_history() {
+        use crate::test_exports::MockSelectorController;
+        use crate::PoSFinalState;
+        use bitvec::prelude::*;
+        use massa_db_exports::{MassaDBConfig, MassaDBController};
+        use massa_db_worker::MassaDB;
+        use massa_models::config::constants::{
+            MAX_DEFERRED_CREDITS_LENGTH, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
+            POS_LOOKBACK_CYCLES,
+        };
+        use massa_signature::KeyPair;
+        use parking_lot::RwLock;
+        use std::sync::Arc;
+        use tempfile::TempDir;
+
+        let pos_config = PoSConfig {
+            periods_per_cycle: 2,
+            thread_count: 2,
+            cycle_history_length: 3,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
+            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+            initial_deferred_credits_path: None,
+        };
+
+        // initialize the database and pos_state
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let db_config = MassaDBConfig {
+            path: tempdir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 2,
+            block_cache_size: 0,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+        let (selector_controller, _) = MockSelectorController::new_with_receiver();
+        let init_seed = Hash::compute_from(b"");
+        let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
+
+        let deferred_credits_deserializer =
+            DeferredCreditsDeserializer::new(pos_config.thread_count, pos_config.max_credit_length);
+        let cycle_info_deserializer = CycleHistoryDeserializer::new(
+            pos_config.cycle_history_length as u64,
+            pos_config.max_rolls_length,
+            pos_config.max_production_stats_length,
+        );
+
+        let mut pos_state = PoSFinalState {
+            config: pos_config,
+            db: db.clone(),
+            cycle_history_cache: Default::default(),
+            rng_seed_cache: None,
+            cycle_hashes_cache: None,
+            cycle_info_hash_computer: CycleInfoHashComputer::new(),
+            selector: selector_controller,
+            initial_rolls: Default::default(),
+            initial_seeds,
+            deferred_credits_serializer: DeferredCreditsSerializer::new(),
+            deferred_credits_deserializer,
+            cycle_info_serializer: CycleHistorySerializer::new(),
+            cycle_info_deserializer,
+        };
+
+        let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+
+        // empty history
+        assert!(
+            pos_state.get_roll_history(&addr, 0, 10).is_empty(),
+            "roll history should be empty without cycles"
+        );
+
+        let mut batch = DBBatch::new();
+        pos_state.create_initial_cycle(&mut batch);
+        db.write()
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 1)));
+
+        // the address has no rolls in the initial cycle
+        assert_eq!(
+            pos_state.get_roll_history(&addr, 0, 10),
+            vec![(0, 0)].into_iter().collect(),
+            "wrong roll history for an address without rolls"
+        );
+
+        // go through 6 cycles, the address holding `cycle + 1` rolls in each of them
+        let mut slot = Slot::new(1, 0);
+        while slot.get_cycle(2) < 6 {
+            let mut roll_changes = PreHashMap::default();
+            roll_changes.insert(addr, slot.get_cycle(2) + 1);
+            let changes = PoSChanges {
+                seed_bits: bitvec![u8, Lsb0; 0],
+                roll_changes,
+                production_stats: Default::default(),
+                deferred_credits: DeferredCredits::new(),
+                roll_sales: Default::default(),
+            };
+            let mut batch = DBBatch::new();
+            pos_state
+                .apply_changes_to_batch(changes, slot, false, &mut batch)
+                .unwrap();
+            db.write()
+                .write_batch(batch, Default::default(), Some(slot));
+            slot = slot.get_next_slot(2).unwrap();
+        }
+
+        // only the 3 last cycles are kept in the history
+        assert_eq!(
+            pos_state.get_roll_history(&addr, 0, 10),
+            vec![(3, 4), (4, 5), (5, 6)].into_iter().collect(),
+            "pruned cycles should be omitted from the roll history"
+        );
+        assert_eq!(
+            pos_state.get_roll_history(&addr, 4, 4),
+            vec![(4, 5)].into_iter().collect(),
+            "wrong roll history for a single cycle"
+        );
+        assert!(
+            pos_state.get_roll_history(&addr, 0, 2).is_empty(),
+            "roll history of pruned cycles should be empty"
+        );
+    }
+
+    #[test]
+    fn test_roll_sales() {
+        use crate::test_exports::MockSelectorController;
+        use crate::PoSFinalState;
+        use bitvec::prelude::*;
+        use massa_db_exports::{MassaDBConfig, MassaDBController};
+        use massa_db_worker::MassaDB;
+        use massa_models::config::constants::{
+            MAX_DEFERRED_CREDITS_LENGTH, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
+            POS_LOOKBACK_CYCLES,
+        };
+        use massa_signature::KeyPair;
+        use parking_lot::RwLock;
+        use std::sync::Arc;
+        use tempfile::TempDir;
+
+        let pos_config = PoSConfig {
+            periods_per_cycle: 2,
+            thread_count: 2,
+            cycle_history_length: 3,
+            lookback_cycles: POS_LOOKBACK_CYCLES,
+            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+            initial_deferred_credits_path: None,
+        };
+
+        // initialize the database and pos_state
+        let tempdir = TempDir::new().expect("cannot create temp directory");
+        let db_config = MassaDBConfig {
+            path: tempdir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 2,
+            block_cache_size: 0,
+        };
+        let db = Arc::new(RwLock::new(
+            Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        ));
+        let (selector_controller, _) = MockSelectorController::new_with_receiver();
+        let init_seed = Hash::compute_from(b"");
+        let initial_seeds = vec![Hash::compute_from(init_seed.to_bytes()), init_seed];
+
+        let deferred_credits_deserializer =
+            DeferredCreditsDeserializer::new(pos_config.thread_count, pos_config.max_credit_length);
+        let cycle_info_deserializer = CycleHistoryDeserializer::new(
+            pos_config.cycle_history_length as u64,
+            pos_config.max_rolls_length,
+            pos_config.max_production_stats_length,
+        );
+
+        let mut pos_state = PoSFinalState {
+            config: pos_config,
+            db: db.clone(),
+            cycle_history_cache: Default::default(),
+            rng_seed_cache: None,
+            cycle_hashes_cache: None,
+            cycle_info_hash_computer: CycleInfoHashComputer::new(),
+            selector: selector_controller,
+            initial_rolls: Default::default(),
+            initial_seeds,
+            deferred_credits_serializer: DeferredCreditsSerializer::new(),
+            deferred_credits_deserializer,
+            cycle_info_serializer: CycleHistorySerializer::new(),
+            cycle_info_deserializer,
+        };
+
+        let addr_a = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let addr_b = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let sale_a = Slot::new(1, 0);
+        let sale_b = Slot::new(4, 1);
+
+        let mut batch = DBBatch::new();
+        pos_state.create_initial_cycle(&mut batch);
+        db.write()
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 1)));
+
+        // go through the slots until a given cycle, A selling rolls in cycle 0 and B in cycle 2
+        let mut slot = Slot::new(1, 0);
+        let mut run_until_cycle = |pos_state: &mut PoSFinalState, cycle: u64| {
+            while slot.get_cycle(2) < cycle {
+                let mut changes = PoSChanges {
+                    seed_bits: bitvec![u8, Lsb0; 0],
+                    ..Default::default()
+                };
+                if slot == sale_a {
+                    changes.roll_sales.insert(addr_a, slot);
+                }
+                if slot == sale_b {
+                    changes.roll_sales.insert(addr_b, slot);
+                }
+                let mut batch = DBBatch::new();
+                pos_state
+                    .apply_changes_to_batch(changes, slot, false, &mut batch)
+                    .unwrap();
+                db.write()
+                    .write_batch(batch, Default::default(), Some(slot));
+                slot = slot.get_next_slot(2).unwrap();
+            }
+        };
+
+        run_until_cycle(&mut pos_state, 3);
+        assert_eq!(pos_state.get_address_last_roll_sale(&addr_a), Some(sale_a));
+        assert_eq!(pos_state.get_address_last_roll_sale(&addr_b), Some(sale_b));
+
+        // the recorded sales are valid cycle history entries
+        for (serialized_key, serialized_value) in db
+            .read()
+            .prefix_iterator_cf(STATE_CF, CYCLE_HISTORY_PREFIX.as_bytes())
+        {
+            if !serialized_key.starts_with(CYCLE_HISTORY_PREFIX.as_bytes()) {
+                break;
+            }
+            assert!(
+                pos_state.is_cycle_history_key_value_valid(&serialized_key, &serialized_value),
+                "invalid cycle history entry"
+            );
+        }
+
+        // the sales are forgotten with the cycles they were made in
+        run_until_cycle(&mut pos_state, 5);
+        assert_eq!(pos_state.get_address_last_roll_sale(&addr_a), None);
+        assert_eq!(pos_state.get_address_last_roll_sale(&addr_b), Some(sale_b));
     }
 }
@@ -16,4 +16,6 @@ pub struct SelectorConfig {
     pub genesis_address: Address,
     /// communication channel length
     pub channel_size: usize,
+    /// number of threads used to compute the draws of a cycle (0 to use one per logical CPU)
+    pub draw_thread_count: usize,
 }
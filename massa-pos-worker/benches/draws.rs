@@ -0,0 +1,65 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use massa_hash::Hash;
+use massa_metrics::MassaMetrics;
+use massa_models::address::Address;
+use massa_pos_exports::SelectorConfig;
+use massa_pos_worker::start_selector_worker;
+use massa_signature::KeyPair;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Measures the latency of computing the draws of a full cycle with a 32-thread configuration,
+/// with a single draw thread and with one draw thread per logical CPU.
+fn cycle_draws_benchmark(c: &mut Criterion) {
+    let lookback_rolls: BTreeMap<Address, u64> = (0..1000u64)
+        .map(|index| {
+            (
+                Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key()),
+                index % 100 + 1,
+            )
+        })
+        .collect();
+    let lookback_seed = Hash::compute_from(b"lookback seed");
+
+    let mut group = c.benchmark_group("cycle draws");
+    for draw_thread_count in [1, 0] {
+        let (mut selector_manager, selector_controller) = start_selector_worker(
+            SelectorConfig {
+                thread_count: 32,
+                periods_per_cycle: 128,
+                max_draw_cache: 2,
+                draw_thread_count,
+                ..Default::default()
+            },
+            MassaMetrics::new(
+                false,
+                "0.0.0.0:31248".parse().unwrap(),
+                32,
+                Duration::from_secs(5),
+            )
+            .0,
+        )
+        .unwrap();
+        let label = if draw_thread_count == 0 {
+            "all CPUs".to_string()
+        } else {
+            format!("{} thread", draw_thread_count)
+        };
+        // draws must be fed for consecutive cycles
+        let mut cycle = 0;
+        group.bench_function(BenchmarkId::from_parameter(label), |b| {
+            b.iter(|| {
+                selector_controller
+                    .feed_cycle(cycle, lookback_rolls.clone(), lookback_seed)
+                    .unwrap();
+                selector_controller.wait_for_draws(cycle).unwrap();
+                cycle += 1;
+            })
+        });
+        selector_manager.stop();
+    }
+    group.finish();
+}
+
+criterion_group!(benches, cycle_draws_benchmark);
+criterion_main!(benches);
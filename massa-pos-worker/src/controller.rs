@@ -5,13 +5,16 @@
 
 use std::collections::BTreeMap;
 
-use crate::{Command, DrawCachePtr};
+use crate::{Command, DrawCachePtr, PendingCyclesPtr};
 use massa_hash::Hash;
+use massa_metrics::MassaMetrics;
 use massa_models::{address::Address, prehash::PreHashSet, slot::Slot};
 use massa_pos_exports::{PosError, PosResult, Selection, SelectorController, SelectorManager};
 #[cfg(feature = "testing")]
 use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::Ordering;
 use std::sync::mpsc::SyncSender;
+use std::time::Instant;
 use tracing::{info, warn};
 
 #[derive(Clone)]
@@ -25,6 +28,10 @@ pub struct SelectorControllerImpl {
     pub(crate) cache: DrawCachePtr,
     /// MPSC to send commands to the selector thread
     pub(crate) input_mpsc: SyncSender<Command>,
+    /// number of cycles fed to the selector thread whose draws are not computed yet
+    pub(crate) pending_cycles: PendingCyclesPtr,
+    /// metrics
+    pub(crate) massa_metrics: MassaMetrics,
 }
 
 impl SelectorController for SelectorControllerImpl {
@@ -53,8 +60,8 @@ impl SelectorController for SelectorControllerImpl {
     ///
     /// # Arguments
     /// * `cycle`: cycle number to be drawn
-    /// * `lookback_rolls`: look back rolls used for the draw (cycle - 3)
-    /// * `lookback_seed`: look back seed hash for the draw (cycle - 2)
+    /// * `lookback_rolls`: look back rolls used for the draw (cycle - lookback_cycles - 1)
+    /// * `lookback_seed`: look back seed hash for the draw (cycle - lookback_cycles)
 
     /// * This a non-blocking function where the worker is separate,
     /// * so the feed is queued and not applied immediately and that's
@@ -74,14 +81,24 @@ impl SelectorController for SelectorControllerImpl {
             cache_lock.read().as_ref().map_err(|err| err.clone())?;
         }
 
+        // count the cycle as pending before sending it, as the thread may process it right away
+        let pending = self.pending_cycles.fetch_add(1, Ordering::Relaxed) + 1;
+        self.massa_metrics.set_selector_pending_cycles(pending);
+
         // send command
         self.input_mpsc
             .send(Command::DrawInput {
                 cycle,
                 lookback_rolls,
                 lookback_seed,
+                fed_at: Instant::now(),
             })
             .map_err(|_err| {
+                let pending = self
+                    .pending_cycles
+                    .fetch_sub(1, Ordering::Relaxed)
+                    .saturating_sub(1);
+                self.massa_metrics.set_selector_pending_cycles(pending);
                 PosError::ChannelDown(
                     "could not feed cycle to selector worker through channel".into(),
                 )
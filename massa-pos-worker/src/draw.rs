@@ -5,19 +5,23 @@ use massa_pos_exports::{PosError, PosResult, Selection, SelectorConfig};
 use rand::{distributions::Distribution, SeedableRng};
 use rand_distr::WeightedAliasIndex;
 use rand_xoshiro::Xoshiro256PlusPlus;
+use rayon::{prelude::*, ThreadPool};
 use std::collections::{BTreeMap, HashMap};
 use tracing::debug;
 
 /// Draws block and creators for a given cycle.
 /// Then prune the `cache` pointer if max cache is exceeded.
+/// The random sampling is sequential, but the selections of the slots
+/// are built in parallel on `draw_pool`.
 /// It uses BTreeMap to store the address <->rolls mapping.
 /// Although not efficient as a hashmap, it still
 /// is the best choice since the order has to be maintained.
 ///
 /// # Parameters
+/// * `draw_pool`: thread pool used to build the selections
 /// * `cycle`: Cycle to draw
-/// * `lookback_rolls`: Roll counts at look back (`cycle-3`)
-/// * `lookback_seed`: RNG seed at look back (`cycle-2`)
+/// * `lookback_rolls`: Roll counts at look back (`cycle - lookback_cycles - 1`)
+/// * `lookback_seed`: RNG seed at look back (`cycle - lookback_cycles`)
 ///
 /// # Result
 /// - The draws can throw the errors of the function `get_params` and from the
@@ -27,6 +31,7 @@ use tracing::debug;
 /// Otherwise, the draws return an empty success.
 pub(crate) fn perform_draws(
     cfg: &SelectorConfig,
+    draw_pool: &ThreadPool,
     cycle: u64,
     lookback_rolls: BTreeMap<Address, u64>,
     lookback_seed: Hash,
@@ -44,56 +49,92 @@ pub(crate) fn perform_draws(
         ))
     })?;
 
-    // perform cycle draws
-    let mut cur_slot = Slot::new_first_of_cycle(cycle, cfg.periods_per_cycle).map_err(|err| {
+    // get the slot range of the cycle
+    let first_slot = Slot::new_first_of_cycle(cycle, cfg.periods_per_cycle).map_err(|err| {
         PosError::OverflowError(format!("start slot overflow in perform_draws: {}", err))
     })?;
-    let last_slot = Slot::new_last_of_cycle(cycle, cfg.periods_per_cycle, cfg.thread_count)
-        .map_err(|err| {
-            PosError::OverflowError(format!("end slot overflow in perform_draws: {}", err))
-        })?;
-    let mut cycle_draws = CycleDraws {
-        cycle,
-        draws: HashMap::with_capacity(
-            (cfg.periods_per_cycle as usize) * (cfg.thread_count as usize),
-        ),
+    // only checks that the last slot of the cycle does not overflow
+    Slot::new_last_of_cycle(cycle, cfg.periods_per_cycle, cfg.thread_count).map_err(|err| {
+        PosError::OverflowError(format!("end slot overflow in perform_draws: {}", err))
+    })?;
+    let thread_count = cfg.thread_count as usize;
+    let slot_count = (cfg.periods_per_cycle as usize) * thread_count;
+    let slot_at = |index: usize| {
+        Slot::new(
+            first_slot.period + (index / thread_count) as u64,
+            (index % thread_count) as u8,
+        )
     };
 
-    let mut five_first_slots: Vec<(Slot, Selection)> = Vec::new();
-    let mut count = 0;
-    loop {
+    // sample the indices of the drawn addresses: the RNG stream is sequential,
+    // so this has to be done in order to keep the draws deterministic.
+    // Each slot takes one producer index followed by its endorser indices.
+    let stride = 1 + cfg.endorsement_count as usize;
+    let mut drawn_indices = Vec::with_capacity(slot_count * stride);
+    for index in 0..slot_count {
         // draw block creator
-        let producer = if cur_slot.period > 0 {
-            addresses[dist.sample(&mut rng)]
+        if slot_at(index).period > 0 {
+            drawn_indices.push(dist.sample(&mut rng));
         } else {
-            // force draws for genesis blocks
-            cfg.genesis_address
-        };
+            // the producer of genesis blocks is forced, nothing to draw
+            drawn_indices.push(0);
+        }
 
         // draw endorsement creators
-        let endorsements: Vec<_> = (0..cfg.endorsement_count)
-            .map(|_index| addresses[dist.sample(&mut rng)])
-            .collect();
+        drawn_indices.extend((0..cfg.endorsement_count).map(|_index| dist.sample(&mut rng)));
+    }
 
-        let selection = Selection {
-            producer,
-            endorsements,
-        };
-        if count < 5 {
-            five_first_slots.push((cur_slot, selection.clone()));
-            count += 1;
-        }
-        // add to draws
-        cycle_draws.draws.insert(cur_slot, selection);
+    // build the selections of the slots in parallel, one chunk of slots per thread
+    let chunk_slot_count = std::cmp::max(1, slot_count / draw_pool.current_num_threads());
+    let chunk_draws: Vec<HashMap<Slot, Selection>> = draw_pool.install(|| {
+        drawn_indices
+            .par_chunks(chunk_slot_count * stride)
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                chunk
+                    .chunks(stride)
+                    .enumerate()
+                    .map(|(index_in_chunk, slot_indices)| {
+                        let slot = slot_at(chunk_index * chunk_slot_count + index_in_chunk);
+                        let producer = if slot.period > 0 {
+                            addresses[slot_indices[0]]
+                        } else {
+                            // force draws for genesis blocks
+                            cfg.genesis_address
+                        };
+                        let endorsements = slot_indices[1..]
+                            .iter()
+                            .map(|address_index| addresses[*address_index])
+                            .collect();
+                        (
+                            slot,
+                            Selection {
+                                producer,
+                                endorsements,
+                            },
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    });
 
-        if cur_slot == last_slot {
-            break;
-        }
-        cur_slot = cur_slot.get_next_slot(cfg.thread_count).map_err(|err| {
-            PosError::OverflowError(format!("iteration slot overflow in perform_draws: {}", err))
-        })?;
+    // merge the draws of all chunks
+    let mut cycle_draws = CycleDraws {
+        cycle,
+        draws: HashMap::with_capacity(slot_count),
+    };
+    for draws in chunk_draws {
+        cycle_draws.draws.extend(draws);
     }
 
+    let five_first_slots: Vec<(Slot, Selection)> = (0..std::cmp::min(5, slot_count))
+        .map(|index| {
+            let slot = slot_at(index);
+            (slot, cycle_draws.draws[&slot].clone())
+        })
+        .collect();
+
     debug!(
         "Draws for cycle {} complete. Look_back seed was {:#?}. Five first selections is : {:#?}",
         cycle,
@@ -103,3 +144,78 @@ pub(crate) fn perform_draws(
 
     Ok(cycle_draws)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use rayon::ThreadPoolBuilder;
+
+    #[test]
+    fn test_draws_independent_of_thread_count() {
+        let cfg = SelectorConfig {
+            thread_count: 32,
+            periods_per_cycle: 16,
+            ..Default::default()
+        };
+        let lookback_rolls: BTreeMap<Address, u64> = (1..=10u64)
+            .map(|index| {
+                (
+                    Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key()),
+                    index,
+                )
+            })
+            .collect();
+        let lookback_seed = Hash::compute_from(b"lookback seed");
+
+        let draws: Vec<CycleDraws> = [1, 3, 8]
+            .into_iter()
+            .map(|num_threads| {
+                let draw_pool = ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap();
+                perform_draws(&cfg, &draw_pool, 0, lookback_rolls.clone(), lookback_seed).unwrap()
+            })
+            .collect();
+
+        let slot_count = (cfg.periods_per_cycle as usize) * (cfg.thread_count as usize);
+        assert_eq!(draws[0].draws.len(), slot_count);
+        assert_eq!(
+            draws[0].draws[&Slot::new(0, 0)].producer,
+            cfg.genesis_address
+        );
+        assert_eq!(
+            draws[0].draws[&Slot::new(1, 0)].endorsements.len(),
+            cfg.endorsement_count as usize
+        );
+        for other in &draws[1..] {
+            assert_eq!(draws[0].draws, other.draws);
+        }
+
+        // the draws are the ones of a single sequential RNG stream going through the slots in order
+        let mut rng = Xoshiro256PlusPlus::from_seed(*lookback_seed.to_bytes());
+        let (addresses, roll_counts): (Vec<_>, Vec<_>) = lookback_rolls.into_iter().unzip();
+        let dist = WeightedAliasIndex::new(roll_counts).unwrap();
+        for period in 0..cfg.periods_per_cycle {
+            for thread in 0..cfg.thread_count {
+                let slot = Slot::new(period, thread);
+                let producer = if period > 0 {
+                    addresses[dist.sample(&mut rng)]
+                } else {
+                    cfg.genesis_address
+                };
+                let endorsements: Vec<_> = (0..cfg.endorsement_count)
+                    .map(|_index| addresses[dist.sample(&mut rng)])
+                    .collect();
+                assert_eq!(
+                    draws[0].draws[&slot],
+                    Selection {
+                        producer,
+                        endorsements,
+                    }
+                );
+            }
+        }
+    }
+}
@@ -11,7 +11,8 @@ use massa_pos_exports::{PosResult, Selection};
 use parking_lot::{Condvar, Mutex, RwLock, RwLockReadGuard};
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
-    sync::Arc,
+    sync::{atomic::AtomicUsize, Arc},
+    time::Instant,
 };
 
 /// Enumeration of internal commands sent to the selector thread as input
@@ -22,6 +23,8 @@ pub(crate) enum Command {
         cycle: u64,
         lookback_rolls: BTreeMap<Address, u64>,
         lookback_seed: Hash,
+        /// instant at which the cycle was fed to the selector
+        fed_at: Instant,
     },
     /// Stop the thread (usually sent by the manager and pushed at the top
     /// of the command queue)
@@ -85,6 +88,9 @@ pub(crate) struct CycleDraws {
 /// Structure of the shared pointer to the computed draws, or error if the draw system failed.
 pub(crate) type DrawCachePtr = Arc<(RwLockCondvar, RwLock<PosResult<DrawCache>>)>;
 
+/// Number of cycles fed to the selector whose draws are not computed yet
+pub(crate) type PendingCyclesPtr = Arc<AtomicUsize>;
+
 /// Start thread selector
 pub use worker::start_selector_worker;
 
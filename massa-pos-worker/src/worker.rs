@@ -6,19 +6,23 @@ use crate::draw::perform_draws;
 use crate::CycleDraws;
 use crate::DrawCache;
 use crate::RwLockCondvar;
-use crate::{Command, DrawCachePtr};
+use crate::{Command, DrawCachePtr, PendingCyclesPtr};
+use massa_metrics::MassaMetrics;
 use massa_pos_exports::PosError;
 use massa_pos_exports::PosResult;
 use massa_pos_exports::SelectorConfig;
 use massa_pos_exports::SelectorController;
 use massa_pos_exports::SelectorManager;
 use parking_lot::RwLock;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
 use std::sync::mpsc::sync_channel;
 use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Instant;
 
 /// Structure gathering all elements needed by the selector thread
 #[allow(dead_code)]
@@ -29,6 +33,12 @@ pub(crate) struct SelectorThread {
     pub(crate) cache: DrawCachePtr,
     /// Configuration
     pub(crate) cfg: SelectorConfig,
+    /// Thread pool used to compute the draws
+    pub(crate) draw_pool: ThreadPool,
+    /// Number of cycles fed to the thread whose draws are not computed yet
+    pub(crate) pending_cycles: PendingCyclesPtr,
+    /// Metrics
+    pub(crate) massa_metrics: MassaMetrics,
 }
 
 impl SelectorThread {
@@ -38,14 +48,24 @@ impl SelectorThread {
         input_mpsc: Receiver<Command>,
         cache: DrawCachePtr,
         cfg: SelectorConfig,
+        pending_cycles: PendingCyclesPtr,
+        massa_metrics: MassaMetrics,
     ) -> JoinHandle<PosResult<()>> {
         let thread_builder = thread::Builder::new().name("selector".into());
         thread_builder
             .spawn(|| {
+                let draw_pool = ThreadPoolBuilder::new()
+                    .num_threads(cfg.draw_thread_count)
+                    .thread_name(|index| format!("selector-draw-{}", index))
+                    .build()
+                    .expect("failed to build thread pool : selector draws");
                 let this = Self {
                     input_mpsc,
                     cache,
                     cfg,
+                    draw_pool,
+                    pending_cycles,
+                    massa_metrics,
                 };
                 this.run()
             })
@@ -113,16 +133,36 @@ impl SelectorThread {
                 cycle,
                 lookback_rolls,
                 lookback_seed,
+                fed_at,
             }) = self.input_mpsc.recv()
             else {
                 break;
             };
 
             // perform draws
-            let draws_result = perform_draws(&self.cfg, cycle, lookback_rolls, lookback_seed);
+            let draw_start = Instant::now();
+            let draws_result = perform_draws(
+                &self.cfg,
+                &self.draw_pool,
+                cycle,
+                lookback_rolls,
+                lookback_seed,
+            );
 
             // add result to cache and notify waiters
-            self.process_draws_result(cycle, draws_result)?;
+            let process_result = self.process_draws_result(cycle, draws_result);
+
+            // the draws are available (or failed): update the metrics
+            let pending = self
+                .pending_cycles
+                .fetch_sub(1, Ordering::Relaxed)
+                .saturating_sub(1);
+            self.massa_metrics.set_selector_pending_cycles(pending);
+            self.massa_metrics.observe_selector_draws(
+                fed_at.elapsed().as_secs_f64(),
+                draw_start.elapsed().as_secs_f64(),
+            );
+            process_result?;
         }
         Ok(())
     }
@@ -131,7 +171,8 @@ impl SelectorThread {
 /// Launches a selector worker thread and returns a pair to interact with it.
 ///
 /// # parameters
-/// * none
+/// * `selector_config`: configuration of the selector
+/// * `massa_metrics`: metrics updated with the latency of the draws
 ///
 /// # Returns
 /// A pair `(selector_manager, selector_controller)` where:
@@ -139,6 +180,7 @@ impl SelectorThread {
 /// * `selector_controller`: allows sending requests and notifications to the worker
 pub fn start_selector_worker(
     selector_config: SelectorConfig,
+    massa_metrics: MassaMetrics,
 ) -> PosResult<(Box<dyn SelectorManager>, Box<dyn SelectorController>)> {
     let (input_sender, input_receiver) = sync_channel(selector_config.channel_size);
     let cache = Arc::new((
@@ -147,15 +189,24 @@ pub fn start_selector_worker(
             selector_config.max_draw_cache.saturating_add(1),
         )))),
     ));
+    let pending_cycles: PendingCyclesPtr = Default::default();
     let controller = SelectorControllerImpl {
         input_mpsc: input_sender.clone(),
         cache: cache.clone(),
         periods_per_cycle: selector_config.periods_per_cycle,
         thread_count: selector_config.thread_count,
+        pending_cycles: pending_cycles.clone(),
+        massa_metrics: massa_metrics.clone(),
     };
 
     // launch the selector thread
-    let thread_handle = SelectorThread::spawn(input_receiver, cache, selector_config);
+    let thread_handle = SelectorThread::spawn(
+        input_receiver,
+        cache,
+        selector_config,
+        pending_cycles,
+        massa_metrics,
+    );
 
     let manager = SelectorManagerImpl {
         thread_handle: Some(thread_handle),
@@ -0,0 +1,15 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+/// Outcome of a request to retrieve a block from a specific peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AskBlockFromPeerEvent {
+    /// the block was fully retrieved and sent to consensus
+    Retrieved,
+    /// the peer is not connected, the block is retrieved from any peer as usual
+    PeerNotConnected,
+    /// the peer did not provide the block in time, the block is now retrieved from any peer as usual
+    TimedOut,
+    /// the block was removed from the wish list or found invalid before being retrieved,
+    /// or was asked to another peer by a newer request
+    Abandoned,
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use serde::{Deserialize, Serialize};
+
+/// Number of bytes of each type of protocol message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageTypeBytes {
+    /// block headers, block info requests and replies
+    pub block: u64,
+    /// endorsements
+    pub endorsement: u64,
+    /// operation announcements, requests and operations
+    pub operation: u64,
+    /// peer lists and handshake related messages
+    pub peer_management: u64,
+}
+
+impl MessageTypeBytes {
+    /// Number of bytes of all the message types
+    pub fn total(&self) -> u64 {
+        self.block
+            .saturating_add(self.endorsement)
+            .saturating_add(self.operation)
+            .saturating_add(self.peer_management)
+    }
+}
+
+/// Bytes exchanged with a connected peer at the protocol level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerBandwidthStats {
+    /// bytes of the messages received from the peer
+    pub bytes_in: MessageTypeBytes,
+    /// bytes of the messages sent to the peer
+    pub bytes_out: MessageTypeBytes,
+    /// number of messages to the peer dropped because the outgoing rate limit was exceeded
+    pub throttled_messages: u64,
+    /// number of times the peer exceeded the incoming rate limit
+    pub rate_limit_violations: u64,
+}
+
+/// Number of messages of a type of protocol message, and their bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageTraffic {
+    /// number of messages
+    pub messages: u64,
+    /// bytes of the messages
+    pub bytes: u64,
+}
+
+/// Messages of each type of protocol message exchanged in one direction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageTypeTraffic {
+    /// block headers
+    pub block_headers: MessageTraffic,
+    /// block info requests and replies, compact blocks and arrival timings
+    pub block_data: MessageTraffic,
+    /// operation announcements
+    pub operation_announcements: MessageTraffic,
+    /// operation requests
+    pub operation_requests: MessageTraffic,
+    /// operations
+    pub operations: MessageTraffic,
+    /// endorsements
+    pub endorsements: MessageTraffic,
+    /// peer lists and handshake related messages
+    pub peer_management: MessageTraffic,
+}
+
+impl MessageTypeTraffic {
+    /// Messages of each message type, with the name of the type
+    pub fn by_type(&self) -> [(&'static str, MessageTraffic); 7] {
+        [
+            ("block_headers", self.block_headers),
+            ("block_data", self.block_data),
+            ("operation_announcements", self.operation_announcements),
+            ("operation_requests", self.operation_requests),
+            ("operations", self.operations),
+            ("endorsements", self.endorsements),
+            ("peer_management", self.peer_management),
+        ]
+    }
+
+    /// Messages of all the message types
+    pub fn total(&self) -> MessageTraffic {
+        self.by_type()
+            .iter()
+            .fold(MessageTraffic::default(), |total, (_, traffic)| {
+                MessageTraffic {
+                    messages: total.messages.saturating_add(traffic.messages),
+                    bytes: total.bytes.saturating_add(traffic.bytes),
+                }
+            })
+    }
+}
+
+/// Messages exchanged with all the peers at the protocol level since the node started
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolTrafficStats {
+    /// messages received from the peers
+    pub received: MessageTypeTraffic,
+    /// messages sent to the peers
+    pub sent: MessageTypeTraffic,
+}
@@ -4,13 +4,26 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 
 use crate::error::ProtocolError;
+use crate::AskBlockFromPeerEvent;
 use crate::BootstrapPeers;
+use crate::EndorsementDedupStats;
+use crate::OperationBatchStats;
+use crate::PeerBandwidthStats;
+use crate::ProtocolTrafficStats;
 
 use crate::PeerId;
+use crate::PeerRttStats;
+use crate::PeerScore;
+use crate::ProtocolTraceEvent;
+use crate::ShutdownReport;
+use massa_channel::receiver::MassaReceiver;
+use massa_models::address::Address;
 use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::stats::NetworkStats;
 use massa_models::{block_header::SecuredHeader, block_id::BlockId};
+use massa_signature::Signature;
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use peernet::peer::PeerConnectionType;
 
 #[cfg_attr(any(test, feature = "testing"), mockall::automock)]
@@ -26,6 +39,26 @@ pub trait ProtocolController: Send + Sync {
     /// * `storage`: Storage instance containing references to the block and all its dependencies
     fn integrated_block(&self, block_id: BlockId, storage: Storage) -> Result<(), ProtocolError>;
 
+    /// Propagate the header of a block right away, without waiting for the block to be integrated.
+    /// The peers receiving the header are not announced the block again once it is integrated.
+    ///
+    /// # Arguments
+    /// * `header`: header of the block
+    fn propagate_block_header(&self, header: SecuredHeader) -> Result<(), ProtocolError>;
+
+    /// Notify protocol that a block was produced by our node, before it is propagated.
+    /// Its header is announced again to the connected peers that do not acknowledge it in time.
+    ///
+    /// # Arguments
+    /// * `block_id`: ID of the block
+    /// * `retraction_signature`: signature of the retraction hash of the block by its creator,
+    ///   sent to the peers it was announced to if consensus finds the block invalid
+    fn produced_block(
+        &self,
+        block_id: BlockId,
+        retraction_signature: Signature,
+    ) -> Result<(), ProtocolError>;
+
     /// Notify to protocol an attack attempt.
     ///
     /// # Arguments
@@ -43,6 +76,25 @@ pub trait ProtocolController: Send + Sync {
         remove: PreHashSet<BlockId>,
     ) -> Result<(), ProtocolError>;
 
+    /// Ask a block to a specific peer, for example a peer that announced its header.
+    /// The block is added to the wish list and only asked to this peer until `timeout` expires,
+    /// then it is asked to any peer as usual.
+    ///
+    /// # Arguments
+    /// * `block_id`: ID of the block
+    /// * `header`: header of the block, if known
+    /// * `peer_id`: peer to ask the block to
+    /// * `timeout`: time given to the peer to provide the block
+    ///
+    /// Returns a receiver of the outcome of the request
+    fn ask_block_from_peer(
+        &self,
+        block_id: BlockId,
+        header: Option<SecuredHeader>,
+        peer_id: PeerId,
+        timeout: MassaTime,
+    ) -> Result<MassaReceiver<AskBlockFromPeerEvent>, ProtocolError>;
+
     /// Propagate a batch of operation (from pool).
     /// note: Full `OperationId` is replaced by a `OperationPrefixId` later by the worker.
     ///
@@ -50,6 +102,13 @@ pub trait ProtocolController: Send + Sync {
     /// * `operations`: operations to propagate
     fn propagate_operations(&self, operations: Storage) -> Result<(), ProtocolError>;
 
+    /// Temporarily ban the operations created by some addresses, for example because they systematically fail.
+    /// The operations of those addresses are neither propagated nor added to the pool until the ban expires.
+    ///
+    /// # Arguments:
+    /// * `addresses`: creator addresses of the operations to ban
+    fn ban_address_sources(&self, addresses: Vec<Address>) -> Result<(), ProtocolError>;
+
     /// Propagate a batch of endorsement (from pool).
     ///
     /// # Arguments:
@@ -69,6 +128,31 @@ pub trait ProtocolController: Send + Sync {
         ProtocolError,
     >;
 
+    /// Get the statistics of the operation batches received from each peer and submitted to the pool
+    fn get_operation_batch_stats(
+        &self,
+    ) -> Result<HashMap<PeerId, OperationBatchStats>, ProtocolError>;
+
+    /// Get the reputation score of each connected peer
+    fn get_peer_scores(&self) -> Result<HashMap<PeerId, PeerScore>, ProtocolError>;
+
+    /// Get the statistics of the endorsements not propagated because the peers already knew them
+    fn get_endorsement_dedup_stats(&self) -> Result<EndorsementDedupStats, ProtocolError>;
+
+    /// Get the bytes exchanged with each connected peer, per message type
+    fn get_peer_bandwidth_stats(
+        &self,
+    ) -> Result<HashMap<PeerId, PeerBandwidthStats>, ProtocolError>;
+
+    /// Get the messages exchanged with all the peers since the node started, per message type and direction
+    fn get_protocol_traffic_stats(&self) -> Result<ProtocolTrafficStats, ProtocolError>;
+
+    /// Get the round-trip times of the pings sent to each connected peer
+    fn get_peer_rtt_stats(&self) -> Result<HashMap<PeerId, PeerRttStats>, ProtocolError>;
+
+    /// Get the latest traced commands and events of the protocol worker, oldest first
+    fn get_protocol_trace(&self) -> Result<Vec<ProtocolTraceEvent>, ProtocolError>;
+
     /// Get a list of peers to be sent to someone that bootstrap to us
     fn get_bootstrap_peers(&self) -> Result<BootstrapPeers, ProtocolError>;
 
@@ -97,5 +181,8 @@ pub trait ProtocolManager {
     /// Note that we do not take self by value to consume it
     /// because it is not allowed to move out of Box<dyn ProtocolManager>
     /// This will improve if the `unsized_fn_params` feature stabilizes enough to be safely usable.
-    fn stop(&mut self);
+    ///
+    /// Waits for the protocol threads at most `shutdown_drain_deadline`
+    /// and reports the threads that did not stop in time.
+    fn stop(&mut self) -> ShutdownReport;
 }
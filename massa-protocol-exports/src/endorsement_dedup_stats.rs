@@ -0,0 +1,22 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+/// Statistics of the deduplication of the endorsements propagated to the peers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EndorsementDedupStats {
+    /// number of times an endorsement was not sent to a peer because the peer already knew it
+    pub known_endorsements: u64,
+    /// number of times an endorsement was sent to a peer
+    pub sent_endorsements: u64,
+}
+
+impl EndorsementDedupStats {
+    /// Ratio of the propagated endorsements that were not sent because the peer already knew them,
+    /// between 0 and 1
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.known_endorsements + self.sent_endorsements;
+        if total == 0 {
+            return 0.0;
+        }
+        self.known_endorsements as f64 / total as f64
+    }
+}
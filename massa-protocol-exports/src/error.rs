@@ -2,6 +2,7 @@
 
 use displaydoc::Display;
 use massa_models::error::ModelsError;
+use massa_models::error_code::{ErrorCategory, ErrorCode, ErrorCodeInfo};
 use massa_pos_exports::PosError;
 use massa_versioning::versioning_factory::FactoryError;
 use std::net::IpAddr;
@@ -74,3 +75,33 @@ pub enum NetworkConnectionErrorType {
     ToManyConnectionAttempt(IpAddr),
     ToManyConnectionFailure(IpAddr),
 }
+
+impl ErrorCode for ProtocolError {
+    fn error_code(&self) -> ErrorCodeInfo {
+        let index = match self {
+            ProtocolError::ModelsError(err) => return err.error_code(),
+            ProtocolError::WrongSignature => 1,
+            ProtocolError::GeneralProtocolError(_) => 2,
+            ProtocolError::InvalidBlock(_) => 3,
+            ProtocolError::ChannelError(_) => 4,
+            ProtocolError::PeerConnectionError(_) => 5,
+            ProtocolError::InvalidIpError(_) => 6,
+            ProtocolError::IOError(_) => 7,
+            ProtocolError::SerdeError(_) => 8,
+            ProtocolError::UnexpectedNodeCommandChannelClosure => 9,
+            ProtocolError::UnexpectedWriterClosure => 10,
+            ProtocolError::TimeError(_) => 11,
+            ProtocolError::MissingPeersError => 12,
+            ProtocolError::SendError(_) => 13,
+            ProtocolError::PeerDisconnected(_) => 14,
+            ProtocolError::ContainerInconsistencyError(_) => 15,
+            ProtocolError::InvalidOperationError(_) => 16,
+            ProtocolError::ListenerError(_) => 17,
+            ProtocolError::IncompatibleNetworkVersion { .. } => 18,
+            ProtocolError::OutdatedAnnouncedNetworkVersion { .. } => 19,
+            ProtocolError::FactoryError(_) => 20,
+            ProtocolError::PosError(_) => 21,
+        };
+        ErrorCodeInfo::new(ErrorCategory::Protocol, index)
+    }
+}
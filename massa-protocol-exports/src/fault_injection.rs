@@ -0,0 +1,44 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::collections::HashMap;
+
+use massa_time::MassaTime;
+use serde::Deserialize;
+
+use crate::PeerId;
+
+/// Faults applied to the messages sent to a peer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct PeerFaults {
+    /// probability to drop a message, between 0 and 1
+    pub drop_rate: f64,
+    /// delay added to each message
+    pub latency: MassaTime,
+    /// maximum random delay added on top of `latency`
+    pub latency_jitter: MassaTime,
+    /// probability to delay a message by an extra `reorder_delay`, between 0 and 1,
+    /// so that the next messages sent to the peer overtake it
+    pub reorder_rate: f64,
+    /// extra delay of the reordered messages
+    pub reorder_delay: MassaTime,
+}
+
+/// Simulated packet loss and latency on the messages sent to the peers.
+/// Only applied when the protocol worker is built with the `fault_injection` feature,
+/// to test the retry and backoff logic in simulations and integration tests.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FaultInjectionConfig {
+    /// faults applied to the peers that are not listed in `peers`
+    pub default_faults: PeerFaults,
+    /// faults applied to specific peers
+    pub peers: HashMap<PeerId, PeerFaults>,
+    /// seed of the random generator, to replay a simulation
+    pub seed: Option<u64>,
+}
+
+impl FaultInjectionConfig {
+    /// Gets the faults applied to the messages sent to a peer
+    pub fn get_peer_faults(&self, peer_id: &PeerId) -> &PeerFaults {
+        self.peers.get(peer_id).unwrap_or(&self.default_faults)
+    }
+}
@@ -1,18 +1,38 @@
+mod ask_block;
+mod bandwidth_stats;
 mod bootstrap_peers;
 mod controller_trait;
+mod endorsement_dedup_stats;
 mod error;
+mod fault_injection;
+mod operation_batch_stats;
 mod peer_id;
+mod peer_rtt_stats;
+mod peer_score;
+mod protocol_trace;
 mod settings;
+mod shutdown_report;
 
+pub use ask_block::AskBlockFromPeerEvent;
+pub use bandwidth_stats::{
+    MessageTraffic, MessageTypeBytes, MessageTypeTraffic, PeerBandwidthStats, ProtocolTrafficStats,
+};
 pub use bootstrap_peers::{
     BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, PeerData,
 };
 pub use controller_trait::{ProtocolController, ProtocolManager};
+pub use endorsement_dedup_stats::EndorsementDedupStats;
 pub use error::ProtocolError;
+pub use fault_injection::{FaultInjectionConfig, PeerFaults};
+pub use operation_batch_stats::OperationBatchStats;
 pub use peer_id::{PeerId, PeerIdDeserializer, PeerIdSerializer};
+pub use peer_rtt_stats::PeerRttStats;
+pub use peer_score::PeerScore;
 pub use peernet::peer::PeerConnectionType;
 pub use peernet::transports::TransportType;
+pub use protocol_trace::ProtocolTraceEvent;
 pub use settings::{PeerCategoryInfo, ProtocolConfig};
+pub use shutdown_report::ShutdownReport;
 
 #[cfg(feature = "testing")]
 pub mod test_exports;
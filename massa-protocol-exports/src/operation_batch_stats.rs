@@ -0,0 +1,22 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+/// Statistics about the batches of operations received from a peer and submitted to the pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationBatchStats {
+    /// number of batches accepted by the pool
+    pub submitted_batches: u64,
+    /// number of operations in the batches accepted by the pool
+    pub submitted_operations: u64,
+    /// number of batches whose processing was acknowledged by the pool
+    pub acknowledged_batches: u64,
+    /// number of submission attempts delayed because the pool was saturated
+    pub retried_batches: u64,
+    /// number of batches dropped because the pool stayed saturated or too many batches were pending
+    pub dropped_batches: u64,
+    /// number of operations in the dropped batches
+    pub dropped_operations: u64,
+    /// number of operations dropped because they expired more than `operation_expiry_horizon` periods ago
+    pub stale_operations: u64,
+    /// number of announcements of operations already dropped as stale
+    pub stale_announcements: u64,
+}
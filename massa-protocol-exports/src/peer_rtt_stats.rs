@@ -0,0 +1,18 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_time::MassaTime;
+
+/// Round-trip times of the application-level pings sent to a connected peer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerRttStats {
+    /// number of round-trip times the percentiles are computed on
+    pub samples: u64,
+    /// median round-trip time
+    pub p50: Option<MassaTime>,
+    /// 90th percentile of the round-trip times
+    pub p90: Option<MassaTime>,
+    /// 99th percentile of the round-trip times
+    pub p99: Option<MassaTime>,
+    /// number of consecutive pings the peer did not answer
+    pub failed_probes: u32,
+}
@@ -0,0 +1,27 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_time::MassaTime;
+
+/// Reputation of a connected peer, built from its past behavior
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PeerScore {
+    /// weighted combination of the components below, between 0 (worst) and 1 (best)
+    pub score: f64,
+    /// number of valid block headers received from the peer
+    pub valid_headers: u64,
+    /// number of invalid block headers received from the peer
+    pub invalid_headers: u64,
+    /// moving average of the time taken by the peer to answer our block data requests,
+    /// unanswered requests counting as `ask_block_timeout`
+    pub ask_block_latency: Option<MassaTime>,
+    /// number of block data requests the peer did not answer in time
+    pub ask_block_timeouts: u64,
+    /// number of useful messages received from the peer
+    pub useful_messages: u64,
+    /// number of messages received from the peer that were duplicated, unrequested or could not be deserialized
+    pub garbage_messages: u64,
+    /// number of operations received from the peer
+    pub received_operations: u64,
+    /// number of operations received from the peer that we did not know yet
+    pub new_operations: u64,
+}
@@ -0,0 +1,39 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use serde::{Deserialize, Serialize};
+
+/// Trace of a command or event of the protocol worker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolTraceEvent {
+    /// monotonically increasing sequence id, shared by all the commands and events
+    pub sequence: u64,
+    /// name of the command or event
+    pub name: String,
+    /// microseconds since the UNIX epoch at which the command was queued or the event happened
+    pub enqueued_at_us: u64,
+    /// microseconds since the UNIX epoch at which the command was taken by its handler,
+    /// equal to `enqueued_at_us` for events and None for the commands dropped because their channel was full
+    pub dequeued_at_us: Option<u64>,
+}
+
+impl ProtocolTraceEvent {
+    /// Microseconds spent in the queue, None if the command was dropped
+    pub fn queue_latency_us(&self) -> Option<u64> {
+        self.dequeued_at_us
+            .map(|dequeued_at_us| dequeued_at_us.saturating_sub(self.enqueued_at_us))
+    }
+}
+
+impl std::fmt::Display for ProtocolTraceEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{} {} queued at {}us",
+            self.sequence, self.name, self.enqueued_at_us
+        )?;
+        match self.queue_latency_us() {
+            Some(latency_us) => write!(f, ", taken after {}us", latency_us),
+            None => write!(f, ", dropped"),
+        }
+    }
+}
@@ -11,6 +11,8 @@ use massa_time::MassaTime;
 use peernet::transports::TransportType;
 use serde::Deserialize;
 
+use crate::FaultInjectionConfig;
+
 #[derive(Debug, Deserialize, Clone, Copy)]
 pub struct PeerCategoryInfo {
     pub allow_local_peers: bool,
@@ -30,12 +32,69 @@ pub struct ProtocolConfig {
     pub initial_peers: PathBuf,
     /// after `ask_block_timeout` milliseconds we try to ask a block to another node
     pub ask_block_timeout: MassaTime,
+    /// Share the arrival timings of blocks with the peers, and adapt the block ask timeout to the timings
+    /// of the node and of its peers, between `min_ask_block_timeout` and `ask_block_timeout`
+    pub block_arrival_gossip: bool,
+    /// lower bound of the block ask timeout adapted to the block arrival timings
+    pub min_ask_block_timeout: MassaTime,
+    /// weight of the ratio of valid headers in the score of a peer
+    pub peer_score_header_validity_weight: f64,
+    /// weight of the response time to block data requests in the score of a peer
+    pub peer_score_latency_weight: f64,
+    /// weight of the ratio of useful (not duplicated, requested and well-formed) messages in the score of a peer
+    pub peer_score_garbage_weight: f64,
+    /// weight of the ratio of new operations in the score of a peer
+    pub peer_score_operation_usefulness_weight: f64,
+    /// penalty of a candidate outbound peer for each outbound peer in the same IP prefix (/16 for IPv4, /32 for IPv6)
+    pub peer_diversity_prefix_weight: f64,
+    /// penalty of a candidate outbound peer for each outbound peer in the same autonomous system
+    pub peer_diversity_asn_weight: f64,
+    /// file mapping IP ranges to autonomous systems, one `<network>/<prefix length> <ASN>` per line
+    pub asn_database_path: Option<PathBuf>,
     /// Max known blocks we keep during their propagation
     pub max_blocks_kept_for_propagation: usize,
     /// Time during which a block is expected to propagate
     pub max_block_propagation_time: MassaTime,
     /// Block propagation tick interval, useful for propagating blocks quickly to newly connected peers.
     pub block_propagation_tick: MassaTime,
+    /// Time after which the header of a block produced by our node is announced again to a peer that did not acknowledge it
+    pub produced_block_reannounce_timeout: MassaTime,
+    /// Max number of times the header of a block produced by our node is announced again to a peer, 0 disables it
+    pub max_produced_block_reannounces: u32,
+    /// Max time the propagation of endorsements and operations waits for the pending propagations of higher priority
+    pub propagation_priority_max_wait: MassaTime,
+    /// Propagate blocks as their header and the prefixes of their operation IDs (compact blocks)
+    /// instead of their header only. All the peers must support compact blocks.
+    pub compact_block_relay: bool,
+    /// Send the block data requests to a same peer in a single message.
+    /// All the peers must support batched block data requests.
+    pub batch_block_asks: bool,
+    /// Relay and validate the headers and endorsements without ever retrieving the blocks and operations.
+    /// The requests of the peers for block data are refused.
+    pub header_only_relay: bool,
+    /// max time to wait for the protocol threads to stop on shutdown
+    pub shutdown_drain_deadline: MassaTime,
+    /// number of periods after their expiry period after which the operations sent or re-announced by peers are dropped
+    pub operation_expiry_horizon: u64,
+    /// interval between two application-level pings sent to each connected peer
+    pub peer_ping_interval: MassaTime,
+    /// number of consecutive unanswered pings after which a peer is disconnected
+    pub max_failed_pings: u32,
+    /// whether the block and operation messages are compressed with zstd for the peers that also support it
+    pub message_compression: bool,
+    /// zstd compression level of the messages
+    pub message_compression_level: i32,
+    /// size in bytes above which a block or operation message is compressed
+    pub message_compression_threshold: usize,
+    /// number of latest commands and events of the protocol worker kept in the trace (0 disables the trace)
+    pub max_trace_events: usize,
+    /// threads whose endorsements our node wants to receive from its peers, None for all of them
+    pub endorsement_subscription_threads: Option<Vec<u8>>,
+    /// duration of the quarantine of a misbehaving peer before it gets banned (0 bans it immediately).
+    /// A quarantined peer stays connected but what it sends is not propagated.
+    pub peer_quarantine_duration: MassaTime,
+    /// max number of serialization buffers kept for reuse per size class (0 disables the reuse)
+    pub serialization_buffer_pool_size: usize,
     /// max known blocks of current nodes we keep in memory
     pub max_known_blocks_size: usize,
     /// max known blocks of foreign nodes we keep in memory (by node)
@@ -50,6 +109,13 @@ pub struct ProtocolConfig {
     pub max_known_endorsements_size: usize,
     /// max known endorsements of foreign nodes we keep in memory (by node)
     pub max_node_known_endorsements_size: usize,
+    /// number of bits of the filters of the endorsements known by foreign nodes (by node and time window)
+    pub known_endorsements_filter_bits: usize,
+    /// an endorsement known by a foreign node is remembered between one and two windows
+    pub known_endorsements_window: MassaTime,
+    /// max number of peers remembered for each block producer (the ones that first sent us its blocks),
+    /// to which the endorsements to include in its blocks are pushed first. 0 disables the fast path
+    pub endorsement_fast_path_peers: usize,
     /// we ask for the same block `max_simultaneous_ask_blocks_per_node` times at the same time
     pub max_simultaneous_ask_blocks_per_node: usize,
     /// Max wait time for sending a Network or Node event.
@@ -64,6 +130,15 @@ pub struct ProtocolConfig {
     pub operation_batch_proc_period: MassaTime,
     /// Maximum number of asked operations in the memory buffer.
     pub asked_operations_buffer_capacity: usize,
+    /// Maximum number of operation batches submitted to the pool and not yet acknowledged
+    pub max_in_flight_pool_operation_batches: usize,
+    /// Maximum number of operation batches waiting to be submitted to the pool.
+    /// Dismiss the new batches if overflow
+    pub max_pending_pool_operation_batches: usize,
+    /// Delay before submitting again an operation batch refused by a saturated pool
+    pub pool_operation_batch_retry_delay: MassaTime,
+    /// Maximum number of submission retries of an operation batch before dropping it
+    pub max_pool_operation_batch_retries: u32,
     /// Interval at which operations are announced in batches.
     pub operation_announcement_interval: MassaTime,
     /// Maximum time we keep an operation in the storage
@@ -84,6 +159,8 @@ pub struct ProtocolConfig {
     pub genesis_timestamp: MassaTime,
     /// max number of operations kept in memory for propagation
     pub max_ops_kept_for_propagation: usize,
+    /// duration during which the operations of a banned source address are neither propagated nor added to the pool
+    pub operation_source_ban_duration: MassaTime,
     /// max time we propagate operations
     pub max_operations_propagation_time: MassaTime,
     /// max time we propagate endorsements
@@ -174,4 +251,14 @@ pub struct ProtocolConfig {
     pub test_oldest_peer_cooldown: MassaTime,
     /// Rate limit to apply on the data stream
     pub rate_limit: u64,
+    /// Max number of bytes per second received from a peer at the protocol level, 0 for no limit.
+    /// Peers exceeding it are reported to the peer handler
+    pub max_peer_bytes_in_per_second: u64,
+    /// Max number of bytes per second sent to a peer at the protocol level, 0 for no limit.
+    /// Beyond it, the low priority messages to the peer are dropped
+    pub max_peer_bytes_out_per_second: u64,
+    /// Ban the peers exceeding `max_peer_bytes_in_per_second` instead of only logging them
+    pub ban_peers_exceeding_rate_limit: bool,
+    /// Simulated packet loss and latency, only applied when built with the `fault_injection` feature
+    pub fault_injection: Option<FaultInjectionConfig>,
 }
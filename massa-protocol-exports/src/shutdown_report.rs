@@ -0,0 +1,44 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::time::Duration;
+
+/// Outcome of the shutdown of the protocol module
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// threads that stopped before the drain deadline
+    pub stopped_threads: Vec<String>,
+    /// threads still running at the drain deadline, left detached
+    pub timed_out_threads: Vec<String>,
+    /// threads that panicked
+    pub panicked_threads: Vec<String>,
+    /// number of peer connections closed during the shutdown
+    pub closed_connections: usize,
+    /// time taken by the shutdown
+    pub duration: Duration,
+}
+
+impl ShutdownReport {
+    /// Returns true if every thread stopped in time without panicking
+    pub fn is_clean(&self) -> bool {
+        self.timed_out_threads.is_empty() && self.panicked_threads.is_empty()
+    }
+}
+
+impl std::fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} threads stopped, {} connections closed in {:?}",
+            self.stopped_threads.len(),
+            self.closed_connections,
+            self.duration
+        )?;
+        if !self.timed_out_threads.is_empty() {
+            write!(f, ", timed out: {}", self.timed_out_threads.join(", "))?;
+        }
+        if !self.panicked_threads.is_empty() {
+            write!(f, ", panicked: {}", self.panicked_threads.join(", "))?;
+        }
+        Ok(())
+    }
+}
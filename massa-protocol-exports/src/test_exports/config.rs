@@ -15,9 +15,35 @@ impl Default for ProtocolConfig {
                 .path()
                 .to_path_buf(),
             ask_block_timeout: MassaTime::from_millis(500),
+            block_arrival_gossip: false,
+            min_ask_block_timeout: MassaTime::from_millis(100),
+            peer_score_header_validity_weight: 4.0,
+            peer_score_latency_weight: 2.0,
+            peer_score_garbage_weight: 1.0,
+            peer_score_operation_usefulness_weight: 1.0,
+            peer_diversity_prefix_weight: 1.0,
+            peer_diversity_asn_weight: 2.0,
+            asn_database_path: None,
             max_blocks_kept_for_propagation: 300,
             max_block_propagation_time: MassaTime::from_millis(40000),
             block_propagation_tick: MassaTime::from_millis(1000),
+            produced_block_reannounce_timeout: MassaTime::from_millis(1000),
+            max_produced_block_reannounces: 0,
+            propagation_priority_max_wait: MassaTime::from_millis(100),
+            compact_block_relay: false,
+            batch_block_asks: false,
+            header_only_relay: false,
+            shutdown_drain_deadline: MassaTime::from_millis(1000),
+            operation_expiry_horizon: 2,
+            peer_ping_interval: MassaTime::from_millis(60000),
+            max_failed_pings: 3,
+            message_compression: false,
+            message_compression_level: 3,
+            message_compression_threshold: 4096,
+            max_trace_events: 1000,
+            endorsement_subscription_threads: None,
+            peer_quarantine_duration: MassaTime::from_millis(0),
+            serialization_buffer_pool_size: 0,
             max_known_blocks_size: 100,
             max_node_known_blocks_size: 100,
             max_node_wanted_blocks_size: 100,
@@ -27,11 +53,18 @@ impl Default for ProtocolConfig {
             max_node_known_ops_size: 1000,
             max_known_endorsements_size: 1000,
             max_node_known_endorsements_size: 1000,
+            known_endorsements_filter_bits: 65536,
+            known_endorsements_window: MassaTime::from_millis(60000),
+            endorsement_fast_path_peers: 0,
             operation_batch_buffer_capacity: 1000,
             operation_announcement_buffer_capacity: 1000,
             max_operation_storage_time: MassaTime::from_millis(60000),
             operation_batch_proc_period: MassaTime::from_millis(200),
             asked_operations_buffer_capacity: 10000,
+            max_in_flight_pool_operation_batches: 100,
+            max_pending_pool_operation_batches: 1000,
+            pool_operation_batch_retry_delay: MassaTime::from_millis(100),
+            max_pool_operation_batch_retries: 5,
             operation_announcement_interval: MassaTime::from_millis(150),
             max_operations_per_message: 1024,
             max_operations_per_block: 5000,
@@ -42,6 +75,7 @@ impl Default for ProtocolConfig {
             genesis_timestamp: MassaTime::now().unwrap(),
             t0: MassaTime::from_millis(16000),
             max_ops_kept_for_propagation: 10000,
+            operation_source_ban_duration: MassaTime::from_millis(60000),
             max_operations_propagation_time: MassaTime::from_millis(30000),
             max_endorsements_propagation_time: MassaTime::from_millis(60000),
             initial_peers: NamedTempFile::new()
@@ -96,6 +130,10 @@ impl Default for ProtocolConfig {
             try_connection_timer_same_peer: MassaTime::from_millis(1000),
             test_oldest_peer_cooldown: MassaTime::from_millis(720000),
             rate_limit: 1024 * 1024 * 2,
+            max_peer_bytes_in_per_second: 0,
+            max_peer_bytes_out_per_second: 0,
+            ban_peers_exceeding_rate_limit: false,
+            fault_injection: None,
         }
     }
 }
@@ -34,6 +34,7 @@ pub fn create_block(keypair: &KeyPair) -> SecureShareBlock {
             operation_merkle_root: Hash::compute_from(&Vec::new()),
             endorsements: Vec::new(),
             denunciations: Vec::new(),
+            reward_recipient: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -78,6 +79,7 @@ pub fn create_block_with_operations(
             operation_merkle_root,
             endorsements: Vec::new(),
             denunciations: Vec::new(),
+            reward_recipient: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -118,6 +120,7 @@ pub fn create_block_with_endorsements(
             operation_merkle_root: Hash::compute_from(&Vec::new()),
             endorsements,
             denunciations: Vec::new(),
+            reward_recipient: None,
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -0,0 +1,465 @@
+//! Protocol-level accounting and rate limiting of the bytes exchanged with each peer.
+//!
+//! Peernet limits the data stream of each connection, but does not know the type of the messages.
+//! Here the messages received from and sent to each peer are counted per message type,
+//! and each direction can be limited per peer by a token bucket refilled at the configured rate,
+//! with a burst of two seconds of traffic:
+//! - a peer sending more than `max_peer_bytes_in_per_second` is reported to the peer handler,
+//!   once each time it exceeds the limit, and the peer handler decides whether to ban it;
+//! - beyond `max_peer_bytes_out_per_second`, the low priority messages to the peer are dropped.
+//!   High priority messages are always sent, but still consume the budget of the peer.
+//!
+//! The sent messages are serialized once more to measure their size, in a buffer taken from the buffer pool.
+//! Compressed messages are counted with their compressed size, under the type of their content.
+//!
+//! The messages exchanged with all the peers are also counted since the node started, with a finer split
+//! of the block and operation messages, to measure the bandwidth of each kind of message in each direction.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use massa_channel::sender::MassaSender;
+use massa_protocol_exports::{
+    MessageTraffic, MessageTypeBytes, MessageTypeTraffic, PeerBandwidthStats, PeerId,
+    ProtocolConfig, ProtocolError, ProtocolTrafficStats,
+};
+use parking_lot::Mutex;
+use peernet::{
+    messages::MessagesSerializer as PeerNetMessagesSerializer, peer::PeerConnectionType,
+    transports::TransportType,
+};
+use tracing::{debug, warn};
+
+use crate::{
+    buffer_pool::SharedBufferPool,
+    handlers::peer_handler::models::PeerManagementCmd,
+    messages::{Message, MessagesSerializer},
+    wrap_network::{ActiveConnectionsTrait, NetworkController},
+};
+
+/// Number of seconds of traffic a peer can send or receive in a burst
+const BURST_SECONDS: u64 = 2;
+
+/// Token bucket limiting the bytes per second in one direction
+struct TokenBucket {
+    /// bytes added per second
+    rate: u64,
+    /// available bytes, negative once the budget is overdrawn by received or high priority messages
+    tokens: f64,
+    /// last time the bucket was refilled
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket, or `None` if `rate` is 0 (no limit)
+    fn new(rate: u64, now: Instant) -> Option<Self> {
+        (rate > 0).then(|| Self {
+            rate,
+            tokens: rate.saturating_mul(BURST_SECONDS) as f64,
+            last_refill: now,
+        })
+    }
+
+    /// Adds the bytes earned since the last refill, up to the burst size
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let capacity = self.rate.saturating_mul(BURST_SECONDS) as f64;
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate as f64).min(capacity);
+        self.last_refill = now;
+    }
+}
+
+/// Counters and limits of a peer
+struct PeerBandwidth {
+    stats: PeerBandwidthStats,
+    bucket_in: Option<TokenBucket>,
+    bucket_out: Option<TokenBucket>,
+    /// whether the peer is above its incoming limit and was already reported
+    in_limit_exceeded: bool,
+}
+
+/// Kind of a protocol message, as counted by the bandwidth accounting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrafficType {
+    BlockHeader,
+    /// block info requests and replies, compact blocks and arrival timings
+    BlockData,
+    OperationAnnouncement,
+    OperationRequest,
+    Operations,
+    Endorsement,
+    PeerManagement,
+}
+
+/// Adds `bytes` to the counter of the message type of `traffic_type`
+fn add_bytes(counters: &mut MessageTypeBytes, traffic_type: TrafficType, bytes: u64) {
+    let counter = match traffic_type {
+        TrafficType::BlockHeader | TrafficType::BlockData => &mut counters.block,
+        TrafficType::Endorsement => &mut counters.endorsement,
+        TrafficType::OperationAnnouncement
+        | TrafficType::OperationRequest
+        | TrafficType::Operations => &mut counters.operation,
+        TrafficType::PeerManagement => &mut counters.peer_management,
+    };
+    *counter = counter.saturating_add(bytes);
+}
+
+/// Counts a message of `bytes` bytes in the counter of its traffic type
+fn add_message(counters: &mut MessageTypeTraffic, traffic_type: TrafficType, bytes: u64) {
+    let counter: &mut MessageTraffic = match traffic_type {
+        TrafficType::BlockHeader => &mut counters.block_headers,
+        TrafficType::BlockData => &mut counters.block_data,
+        TrafficType::OperationAnnouncement => &mut counters.operation_announcements,
+        TrafficType::OperationRequest => &mut counters.operation_requests,
+        TrafficType::Operations => &mut counters.operations,
+        TrafficType::Endorsement => &mut counters.endorsements,
+        TrafficType::PeerManagement => &mut counters.peer_management,
+    };
+    counter.messages = counter.messages.saturating_add(1);
+    counter.bytes = counter.bytes.saturating_add(bytes);
+}
+
+/// Bytes exchanged with each peer, shared between the messages handler and the sending side
+pub struct BandwidthAccounting {
+    peers: Mutex<HashMap<PeerId, PeerBandwidth>>,
+    /// messages exchanged with all the peers, kept when the peers disconnect
+    traffic: Mutex<ProtocolTrafficStats>,
+    max_bytes_in_per_second: u64,
+    max_bytes_out_per_second: u64,
+    /// reports the peers exceeding their incoming limit to the peer handler
+    peer_cmd_sender: MassaSender<PeerManagementCmd>,
+}
+
+impl BandwidthAccounting {
+    pub fn new(config: &ProtocolConfig, peer_cmd_sender: MassaSender<PeerManagementCmd>) -> Self {
+        Self {
+            peers: Mutex::new(HashMap::new()),
+            traffic: Mutex::new(ProtocolTrafficStats::default()),
+            max_bytes_in_per_second: config.max_peer_bytes_in_per_second,
+            max_bytes_out_per_second: config.max_peer_bytes_out_per_second,
+            peer_cmd_sender,
+        }
+    }
+
+    /// Runs `f` on the counters of a peer, creating them if needed
+    fn with_peer<R>(
+        &self,
+        peer_id: &PeerId,
+        now: Instant,
+        f: impl FnOnce(&mut PeerBandwidth) -> R,
+    ) -> R {
+        let mut peers = self.peers.lock();
+        let peer = peers
+            .entry(peer_id.clone())
+            .or_insert_with(|| PeerBandwidth {
+                stats: PeerBandwidthStats::default(),
+                bucket_in: TokenBucket::new(self.max_bytes_in_per_second, now),
+                bucket_out: TokenBucket::new(self.max_bytes_out_per_second, now),
+                in_limit_exceeded: false,
+            });
+        f(peer)
+    }
+
+    /// Counts a message received from a peer,
+    /// and reports the peer if it just exceeded its incoming limit
+    pub fn record_in(&self, peer_id: &PeerId, traffic_type: TrafficType, bytes: usize) {
+        let now = Instant::now();
+        add_message(
+            &mut self.traffic.lock().received,
+            traffic_type,
+            bytes as u64,
+        );
+        let newly_exceeded = self.with_peer(peer_id, now, |peer| {
+            add_bytes(&mut peer.stats.bytes_in, traffic_type, bytes as u64);
+            let Some(bucket) = peer.bucket_in.as_mut() else {
+                return false;
+            };
+            bucket.refill(now);
+            bucket.tokens -= bytes as f64;
+            if bucket.tokens >= 0.0 {
+                peer.in_limit_exceeded = false;
+                return false;
+            }
+            if peer.in_limit_exceeded {
+                return false;
+            }
+            peer.in_limit_exceeded = true;
+            peer.stats.rate_limit_violations = peer.stats.rate_limit_violations.saturating_add(1);
+            true
+        });
+        if newly_exceeded {
+            debug!(
+                "peer {} exceeded the incoming rate limit of {} bytes per second",
+                peer_id, self.max_bytes_in_per_second
+            );
+            if let Err(err) = self
+                .peer_cmd_sender
+                .try_send(PeerManagementCmd::RateLimitExceeded(peer_id.clone()))
+            {
+                warn!(
+                    "failed to report peer {} exceeding its rate limit: {}",
+                    peer_id, err
+                );
+            }
+        }
+    }
+
+    /// Counts a message sent to a peer.
+    /// Returns false if the message must be dropped because the outgoing limit of the peer is reached
+    /// and the message is not high priority.
+    pub fn try_record_out(
+        &self,
+        peer_id: &PeerId,
+        traffic_type: TrafficType,
+        bytes: usize,
+        high_priority: bool,
+    ) -> bool {
+        let now = Instant::now();
+        let sent = self.with_peer(peer_id, now, |peer| {
+            if let Some(bucket) = peer.bucket_out.as_mut() {
+                bucket.refill(now);
+                if !high_priority && bucket.tokens < bytes as f64 {
+                    peer.stats.throttled_messages = peer.stats.throttled_messages.saturating_add(1);
+                    return false;
+                }
+                bucket.tokens -= bytes as f64;
+            }
+            add_bytes(&mut peer.stats.bytes_out, traffic_type, bytes as u64);
+            true
+        });
+        if sent {
+            add_message(&mut self.traffic.lock().sent, traffic_type, bytes as u64);
+        }
+        sent
+    }
+
+    /// Forgets the peers that are not connected anymore
+    pub fn retain_peers(&self, connected: &HashSet<PeerId>) {
+        self.peers
+            .lock()
+            .retain(|peer_id, _| connected.contains(peer_id));
+    }
+
+    /// Counters of each peer
+    pub fn get_stats(&self) -> HashMap<PeerId, PeerBandwidthStats> {
+        self.peers
+            .lock()
+            .iter()
+            .map(|(peer_id, peer)| (peer_id.clone(), peer.stats))
+            .collect()
+    }
+
+    /// Messages exchanged with all the peers since the node started
+    pub fn get_traffic_stats(&self) -> ProtocolTrafficStats {
+        *self.traffic.lock()
+    }
+}
+
+pub type SharedBandwidthAccounting = Arc<BandwidthAccounting>;
+
+/// Network controller whose connections count and limit the messages sent to each peer
+pub(crate) struct BandwidthNetworkController {
+    network_controller: Box<dyn NetworkController>,
+    bandwidth: SharedBandwidthAccounting,
+    buffer_pool: SharedBufferPool,
+}
+
+impl BandwidthNetworkController {
+    pub fn new(
+        network_controller: Box<dyn NetworkController>,
+        bandwidth: SharedBandwidthAccounting,
+        buffer_pool: SharedBufferPool,
+    ) -> Self {
+        Self {
+            network_controller,
+            bandwidth,
+            buffer_pool,
+        }
+    }
+}
+
+impl NetworkController for BandwidthNetworkController {
+    fn get_active_connections(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(BandwidthActiveConnections {
+            active_connections: self.network_controller.get_active_connections(),
+            bandwidth: self.bandwidth.clone(),
+            buffer_pool: self.buffer_pool.clone(),
+        })
+    }
+
+    fn start_listener(
+        &mut self,
+        transport_type: TransportType,
+        addr: SocketAddr,
+    ) -> Result<(), ProtocolError> {
+        self.network_controller.start_listener(transport_type, addr)
+    }
+
+    fn stop_listener(
+        &mut self,
+        transport_type: TransportType,
+        addr: SocketAddr,
+    ) -> Result<(), ProtocolError> {
+        self.network_controller.stop_listener(transport_type, addr)
+    }
+
+    fn try_connect(&mut self, addr: SocketAddr, timeout: Duration) -> Result<(), ProtocolError> {
+        self.network_controller.try_connect(addr, timeout)
+    }
+
+    fn get_total_bytes_received(&self) -> u64 {
+        self.network_controller.get_total_bytes_received()
+    }
+
+    fn get_total_bytes_sent(&self) -> u64 {
+        self.network_controller.get_total_bytes_sent()
+    }
+}
+
+/// Active connections counting the messages sent to each peer and dropping them beyond the limit
+struct BandwidthActiveConnections {
+    active_connections: Box<dyn ActiveConnectionsTrait>,
+    bandwidth: SharedBandwidthAccounting,
+    buffer_pool: SharedBufferPool,
+}
+
+impl ActiveConnectionsTrait for BandwidthActiveConnections {
+    fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        high_priority: bool,
+    ) -> Result<(), ProtocolError> {
+        let message_size = {
+            let mut buffer = self.buffer_pool.acquire(0);
+            message_serializer
+                .serialize(&message, &mut buffer)
+                .map_err(|err| ProtocolError::SendError(err.to_string()))?;
+            buffer.len()
+        };
+        if !self.bandwidth.try_record_out(
+            peer_id,
+            message.traffic_type(),
+            message_size,
+            high_priority,
+        ) {
+            // throttled messages are not reported to the sender, they are retried like lost ones
+            return Ok(());
+        }
+        self.active_connections
+            .send_to_peer(peer_id, message_serializer, message, high_priority)
+    }
+
+    fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(BandwidthActiveConnections {
+            active_connections: self.active_connections.clone(),
+            bandwidth: self.bandwidth.clone(),
+            buffer_pool: self.buffer_pool.clone(),
+        })
+    }
+
+    fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+        self.active_connections.get_peer_ids_connected()
+    }
+
+    fn get_peers_connected(
+        &self,
+    ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+        self.active_connections.get_peers_connected()
+    }
+
+    fn get_peer_ids_out_connection_queue(&self) -> HashSet<SocketAddr> {
+        self.active_connections.get_peer_ids_out_connection_queue()
+    }
+
+    fn get_nb_out_connections(&self) -> usize {
+        self.active_connections.get_nb_out_connections()
+    }
+
+    fn get_nb_in_connections(&self) -> usize {
+        self.active_connections.get_nb_in_connections()
+    }
+
+    fn shutdown_connection(&mut self, peer_id: &PeerId) {
+        self.active_connections.shutdown_connection(peer_id)
+    }
+
+    fn get_peers_connections_bandwidth(&self) -> HashMap<String, (u64, u64)> {
+        self.active_connections.get_peers_connections_bandwidth()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_channel::MassaChannel;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_bandwidth_accounting_limits() {
+        let config = ProtocolConfig {
+            max_peer_bytes_in_per_second: 1000,
+            max_peer_bytes_out_per_second: 1000,
+            ..Default::default()
+        };
+        let (peer_cmd_sender, peer_cmd_receiver) =
+            MassaChannel::new("test_bandwidth".to_string(), Some(10));
+        let bandwidth = BandwidthAccounting::new(&config, peer_cmd_sender);
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+
+        // the burst is two seconds of traffic
+        assert!(bandwidth.try_record_out(&peer_id, TrafficType::Operations, 1500, false));
+        assert!(!bandwidth.try_record_out(&peer_id, TrafficType::Operations, 1000, false));
+        // high priority messages are always sent
+        assert!(bandwidth.try_record_out(&peer_id, TrafficType::BlockHeader, 1000, true));
+
+        // the peer is reported once while it stays above its incoming limit
+        bandwidth.record_in(&peer_id, TrafficType::Endorsement, 1500);
+        assert!(peer_cmd_receiver.try_recv().is_err());
+        bandwidth.record_in(&peer_id, TrafficType::Endorsement, 1500);
+        bandwidth.record_in(&peer_id, TrafficType::OperationAnnouncement, 100);
+        match peer_cmd_receiver.try_recv() {
+            Ok(PeerManagementCmd::RateLimitExceeded(reported)) => assert_eq!(reported, peer_id),
+            _ => panic!("the peer exceeding its limit was not reported"),
+        }
+        assert!(peer_cmd_receiver.try_recv().is_err());
+
+        let stats = bandwidth.get_stats()[&peer_id];
+        assert_eq!(stats.bytes_out.operation, 1500);
+        assert_eq!(stats.bytes_out.block, 1000);
+        assert_eq!(stats.bytes_in.endorsement, 3000);
+        assert_eq!(stats.bytes_in.total(), 3100);
+        assert_eq!(stats.throttled_messages, 1);
+        assert_eq!(stats.rate_limit_violations, 1);
+
+        // the throttled message is not counted in the traffic of the node
+        let traffic = bandwidth.get_traffic_stats();
+        assert_eq!(
+            traffic.sent.operations,
+            MessageTraffic {
+                messages: 1,
+                bytes: 1500
+            }
+        );
+        assert_eq!(traffic.sent.block_headers.bytes, 1000);
+        assert_eq!(traffic.received.endorsements.messages, 2);
+        assert_eq!(traffic.received.operation_announcements.bytes, 100);
+        assert_eq!(
+            traffic.received.total(),
+            MessageTraffic {
+                messages: 3,
+                bytes: 3100
+            }
+        );
+
+        // disconnected peers are forgotten, but not the traffic of the node
+        bandwidth.retain_peers(&HashSet::new());
+        assert!(bandwidth.get_stats().is_empty());
+        assert_eq!(bandwidth.get_traffic_stats(), traffic);
+    }
+}
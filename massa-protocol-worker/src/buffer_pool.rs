@@ -0,0 +1,176 @@
+//! Pool of reusable serialization buffers.
+//!
+//! The messages sent to peers are serialized in the protocol to measure their size and to compress them.
+//! Instead of allocating a buffer for each message, the buffers are taken from this pool
+//! and given back to it once the message is handled.
+//! The buffers given back are sorted in bins by capacity class, from `MIN_CLASS_CAPACITY` up to `MAX_POOLED_CAPACITY`,
+//! each bin keeping at most `serialization_buffer_pool_size` buffers. Larger buffers are freed.
+//! A buffer is taken from the smallest class able to hold the expected size, or from a smaller one if there is none.
+//!
+//! The proportion of buffers reused instead of allocated is reported in the metrics.
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use massa_protocol_exports::ProtocolConfig;
+use parking_lot::Mutex;
+
+/// Capacity of the smallest class
+const MIN_CLASS_CAPACITY: usize = 1024;
+/// Number of capacity classes, each one 4 times larger than the previous one
+const CLASS_COUNT: usize = 7;
+/// Capacity above which the buffers are not kept (4 MiB)
+const MAX_POOLED_CAPACITY: usize = MIN_CLASS_CAPACITY << (2 * CLASS_COUNT - 2);
+
+/// Class of the buffers able to hold `size` bytes
+fn class_for_size(size: usize) -> usize {
+    (0..CLASS_COUNT)
+        .find(|class| MIN_CLASS_CAPACITY << (2 * class) >= size)
+        .unwrap_or(CLASS_COUNT - 1)
+}
+
+/// Class in which a buffer of capacity `capacity` is kept: the largest one it can fully serve
+fn class_for_capacity(capacity: usize) -> usize {
+    (0..CLASS_COUNT)
+        .rev()
+        .find(|class| MIN_CLASS_CAPACITY << (2 * class) <= capacity)
+        .unwrap_or(0)
+}
+
+pub struct BufferPool {
+    /// max number of buffers kept in each bin, 0 disables the pool
+    max_buffers_per_class: usize,
+    bins: [Mutex<Vec<Vec<u8>>>; CLASS_COUNT],
+    acquired: AtomicU64,
+    reused: AtomicU64,
+}
+
+impl BufferPool {
+    pub fn new(config: &ProtocolConfig) -> Self {
+        Self {
+            max_buffers_per_class: config.serialization_buffer_pool_size,
+            bins: Default::default(),
+            acquired: AtomicU64::new(0),
+            reused: AtomicU64::new(0),
+        }
+    }
+
+    /// Takes an empty buffer expected to hold `size_hint` bytes
+    pub fn acquire(&self, size_hint: usize) -> PooledBuffer<'_> {
+        self.acquired.fetch_add(1, Ordering::Relaxed);
+        let class = class_for_size(size_hint);
+        let reused = (class..CLASS_COUNT)
+            .chain((0..class).rev())
+            .find_map(|class| self.bins[class].lock().pop());
+        let buffer = match reused {
+            Some(buffer) => {
+                self.reused.fetch_add(1, Ordering::Relaxed);
+                buffer
+            }
+            None => Vec::with_capacity(size_hint),
+        };
+        PooledBuffer { pool: self, buffer }
+    }
+
+    /// Keeps `buffer` for a later use, if its bin is not full
+    fn release(&self, mut buffer: Vec<u8>) {
+        let capacity = buffer.capacity();
+        if self.max_buffers_per_class == 0 || capacity == 0 || capacity > MAX_POOLED_CAPACITY {
+            return;
+        }
+        buffer.clear();
+        let mut bin = self.bins[class_for_capacity(capacity)].lock();
+        if bin.len() < self.max_buffers_per_class {
+            bin.push(buffer);
+        }
+    }
+
+    /// Proportion of the taken buffers that were reused instead of allocated, 0 if none was taken yet
+    pub fn get_reuse_rate(&self) -> f64 {
+        let acquired = self.acquired.load(Ordering::Relaxed);
+        if acquired == 0 {
+            return 0.0;
+        }
+        self.reused.load(Ordering::Relaxed) as f64 / acquired as f64
+    }
+}
+
+pub type SharedBufferPool = Arc<BufferPool>;
+
+/// Buffer taken from a `BufferPool`, given back to it when dropped
+pub struct PooledBuffer<'a> {
+    pool: &'a BufferPool,
+    buffer: Vec<u8>,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        self.pool.release(std::mem::take(&mut self.buffer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_pool() {
+        let config = ProtocolConfig {
+            serialization_buffer_pool_size: 1,
+            ..Default::default()
+        };
+        let pool = BufferPool::new(&config);
+
+        // the first buffer is allocated, then reused empty
+        let mut buffer = pool.acquire(0);
+        buffer.extend_from_slice(&[1; 100]);
+        drop(buffer);
+        let buffer = pool.acquire(0);
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 100);
+        assert_eq!(pool.get_reuse_rate(), 0.5);
+
+        // a large buffer goes to its own class and is preferred for large messages
+        let mut large = pool.acquire(100_000);
+        large.extend_from_slice(&[1; 100_000]);
+        drop(buffer);
+        drop(large);
+        assert!(pool.acquire(100_000).capacity() >= 100_000);
+        assert!(pool.acquire(0).capacity() < 100_000);
+
+        // bins keep at most `serialization_buffer_pool_size` buffers
+        let pool = BufferPool::new(&config);
+        let mut first = pool.acquire(0);
+        first.push(1);
+        let mut second = pool.acquire(0);
+        second.push(1);
+        drop(first);
+        drop(second);
+        assert_eq!(pool.bins[0].lock().len(), 1);
+
+        // a disabled pool never reuses buffers
+        let pool = BufferPool::new(&ProtocolConfig::default());
+        drop(pool.acquire(0));
+        drop(pool.acquire(0));
+        assert_eq!(pool.get_reuse_rate(), 0.0);
+    }
+}
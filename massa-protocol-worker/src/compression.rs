@@ -0,0 +1,363 @@
+//! Compression of the large protocol messages with zstd.
+//!
+//! Each node announces during the handshake whether it supports compressed messages,
+//! with a flag byte sent after its announcement (ignored by the nodes that do not know it).
+//! When both ends of a connection enabled `message_compression`, the block and operation messages
+//! larger than `message_compression_threshold` are sent compressed.
+//! A compressed message is sent with its own message type id, followed by the zstd frame
+//! of the original message, type id included.
+//!
+//! The sizes before and after compression are counted in each direction to measure the compression ratio.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use massa_protocol_exports::{PeerId, ProtocolConfig, ProtocolError};
+use parking_lot::RwLock;
+use peernet::{
+    error::{PeerNetError, PeerNetResult},
+    messages::MessagesSerializer as PeerNetMessagesSerializer,
+    peer::PeerConnectionType,
+    transports::TransportType,
+};
+use tracing::debug;
+
+use crate::{
+    buffer_pool::{BufferPool, SharedBufferPool},
+    messages::{Message, MessagesSerializer},
+    wrap_network::{ActiveConnectionsTrait, NetworkController},
+};
+
+/// Flag of the handshake capabilities byte telling that the node supports compressed messages
+pub(crate) const HANDSHAKE_FLAG_COMPRESSION: u8 = 0b1;
+
+/// Bytes of the compressed messages before and after compression, in one direction
+#[derive(Default)]
+struct CompressionCounters {
+    uncompressed_bytes: AtomicU64,
+    compressed_bytes: AtomicU64,
+}
+
+impl CompressionCounters {
+    fn add(&self, uncompressed_bytes: usize, compressed_bytes: usize) {
+        self.uncompressed_bytes
+            .fetch_add(uncompressed_bytes as u64, Ordering::Relaxed);
+        self.compressed_bytes
+            .fetch_add(compressed_bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Compressed size over uncompressed size, 1 if nothing was compressed yet
+    fn ratio(&self) -> f64 {
+        let uncompressed_bytes = self.uncompressed_bytes.load(Ordering::Relaxed);
+        if uncompressed_bytes == 0 {
+            return 1.0;
+        }
+        self.compressed_bytes.load(Ordering::Relaxed) as f64 / uncompressed_bytes as f64
+    }
+}
+
+/// Compression settings and peers with which the compression was negotiated
+pub struct MessageCompression {
+    enabled: bool,
+    level: i32,
+    threshold: usize,
+    max_message_size: usize,
+    /// peers that announced their support of compressed messages in the handshake
+    peers: RwLock<HashSet<PeerId>>,
+    sent: CompressionCounters,
+    received: CompressionCounters,
+}
+
+impl MessageCompression {
+    pub fn new(config: &ProtocolConfig) -> Self {
+        Self {
+            enabled: config.message_compression,
+            level: config.message_compression_level,
+            threshold: config.message_compression_threshold,
+            max_message_size: config.max_message_size,
+            peers: RwLock::new(HashSet::new()),
+            sent: CompressionCounters::default(),
+            received: CompressionCounters::default(),
+        }
+    }
+
+    /// Capabilities byte sent in the handshake
+    pub fn handshake_flags(&self) -> u8 {
+        if self.enabled {
+            HANDSHAKE_FLAG_COMPRESSION
+        } else {
+            0
+        }
+    }
+
+    /// Records the capabilities byte received from a peer in the handshake.
+    /// `None` if the peer did not send any, which is the case of the nodes not supporting compression.
+    pub fn on_handshake(&self, peer_id: &PeerId, flags: Option<u8>) {
+        let supported = flags.map_or(false, |flags| flags & HANDSHAKE_FLAG_COMPRESSION != 0);
+        if self.enabled && supported {
+            self.peers.write().insert(peer_id.clone());
+        } else {
+            self.peers.write().remove(peer_id);
+        }
+    }
+
+    /// Forgets the peers that are not connected anymore
+    pub fn retain_peers(&self, connected: &HashSet<PeerId>) {
+        self.peers
+            .write()
+            .retain(|peer_id| connected.contains(peer_id));
+    }
+
+    /// Compresses `message` if the compression was negotiated with `peer_id`,
+    /// the message is a block or operation message above the threshold, and it gets smaller.
+    /// Otherwise the message is returned as is.
+    /// The message is serialized in a buffer taken from `buffer_pool`.
+    fn compress(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        buffer_pool: &BufferPool,
+    ) -> Result<Message, ProtocolError> {
+        if !matches!(message, Message::Block(_) | Message::Operation(_))
+            || !self.peers.read().contains(peer_id)
+        {
+            return Ok(message);
+        }
+        let mut buffer = buffer_pool.acquire(0);
+        message_serializer
+            .serialize(&message, &mut buffer)
+            .map_err(|err| ProtocolError::SendError(err.to_string()))?;
+        if buffer.len() < self.threshold {
+            return Ok(message);
+        }
+        let data = match zstd::bulk::compress(&buffer, self.level) {
+            Ok(data) => data,
+            Err(err) => {
+                debug!("failed to compress a message to peer {}: {}", peer_id, err);
+                return Ok(message);
+            }
+        };
+        if data.len() >= buffer.len() {
+            return Ok(message);
+        }
+        self.sent.add(buffer.len(), data.len());
+        Ok(Message::Compressed {
+            traffic_type: message.traffic_type(),
+            data,
+        })
+    }
+
+    /// Decompresses the content of a compressed message, up to the maximum message size
+    pub fn decompress(&self, data: &[u8]) -> PeerNetResult<Vec<u8>> {
+        let decompressed = zstd::bulk::decompress(data, self.max_message_size).map_err(|err| {
+            PeerNetError::HandlerError.error(
+                "MessagesHandler",
+                Some(format!("Failed to decompress message: {}", err)),
+            )
+        })?;
+        self.received.add(decompressed.len(), data.len());
+        Ok(decompressed)
+    }
+
+    /// Compression ratios of the sent and received messages (compressed size over uncompressed size)
+    pub fn get_ratios(&self) -> (f64, f64) {
+        (self.sent.ratio(), self.received.ratio())
+    }
+}
+
+pub type SharedMessageCompression = Arc<MessageCompression>;
+
+/// Network controller whose connections compress the messages for the peers supporting it
+pub(crate) struct CompressionNetworkController {
+    network_controller: Box<dyn NetworkController>,
+    compression: SharedMessageCompression,
+    buffer_pool: SharedBufferPool,
+}
+
+impl CompressionNetworkController {
+    pub fn new(
+        network_controller: Box<dyn NetworkController>,
+        compression: SharedMessageCompression,
+        buffer_pool: SharedBufferPool,
+    ) -> Self {
+        Self {
+            network_controller,
+            compression,
+            buffer_pool,
+        }
+    }
+}
+
+impl NetworkController for CompressionNetworkController {
+    fn get_active_connections(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(CompressionActiveConnections {
+            active_connections: self.network_controller.get_active_connections(),
+            compression: self.compression.clone(),
+            buffer_pool: self.buffer_pool.clone(),
+        })
+    }
+
+    fn start_listener(
+        &mut self,
+        transport_type: TransportType,
+        addr: SocketAddr,
+    ) -> Result<(), ProtocolError> {
+        self.network_controller.start_listener(transport_type, addr)
+    }
+
+    fn stop_listener(
+        &mut self,
+        transport_type: TransportType,
+        addr: SocketAddr,
+    ) -> Result<(), ProtocolError> {
+        self.network_controller.stop_listener(transport_type, addr)
+    }
+
+    fn try_connect(&mut self, addr: SocketAddr, timeout: Duration) -> Result<(), ProtocolError> {
+        self.network_controller.try_connect(addr, timeout)
+    }
+
+    fn get_total_bytes_received(&self) -> u64 {
+        self.network_controller.get_total_bytes_received()
+    }
+
+    fn get_total_bytes_sent(&self) -> u64 {
+        self.network_controller.get_total_bytes_sent()
+    }
+}
+
+/// Active connections compressing the messages for the peers supporting it
+struct CompressionActiveConnections {
+    active_connections: Box<dyn ActiveConnectionsTrait>,
+    compression: SharedMessageCompression,
+    buffer_pool: SharedBufferPool,
+}
+
+impl ActiveConnectionsTrait for CompressionActiveConnections {
+    fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        high_priority: bool,
+    ) -> Result<(), ProtocolError> {
+        let message =
+            self.compression
+                .compress(peer_id, message_serializer, message, &self.buffer_pool)?;
+        self.active_connections
+            .send_to_peer(peer_id, message_serializer, message, high_priority)
+    }
+
+    fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(CompressionActiveConnections {
+            active_connections: self.active_connections.clone(),
+            compression: self.compression.clone(),
+            buffer_pool: self.buffer_pool.clone(),
+        })
+    }
+
+    fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+        self.active_connections.get_peer_ids_connected()
+    }
+
+    fn get_peers_connected(
+        &self,
+    ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+        self.active_connections.get_peers_connected()
+    }
+
+    fn get_peer_ids_out_connection_queue(&self) -> HashSet<SocketAddr> {
+        self.active_connections.get_peer_ids_out_connection_queue()
+    }
+
+    fn get_nb_out_connections(&self) -> usize {
+        self.active_connections.get_nb_out_connections()
+    }
+
+    fn get_nb_in_connections(&self) -> usize {
+        self.active_connections.get_nb_in_connections()
+    }
+
+    fn shutdown_connection(&mut self, peer_id: &PeerId) {
+        self.active_connections.shutdown_connection(peer_id)
+    }
+
+    fn get_peers_connections_bandwidth(&self) -> HashMap<String, (u64, u64)> {
+        self.active_connections.get_peers_connections_bandwidth()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bandwidth::TrafficType;
+    use crate::handlers::operation_handler::{OperationMessage, OperationMessageSerializer};
+    use massa_models::operation::{
+        OperationPrefixId, OperationPrefixIds, OPERATION_ID_PREFIX_SIZE_BYTES,
+    };
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_message_compression() {
+        let config = ProtocolConfig {
+            message_compression: true,
+            message_compression_threshold: 100,
+            ..Default::default()
+        };
+        let compression = MessageCompression::new(&config);
+        let buffer_pool = BufferPool::new(&config);
+        let serializer = MessagesSerializer::new()
+            .with_operation_message_serializer(OperationMessageSerializer::new());
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let announcement = || {
+            let prefixes: OperationPrefixIds = (0..100u8)
+                .map(|i| {
+                    let mut bytes = [0u8; OPERATION_ID_PREFIX_SIZE_BYTES];
+                    bytes[0] = i;
+                    OperationPrefixId::from(&bytes)
+                })
+                .collect();
+            Message::from(OperationMessage::OperationsAnnouncement(prefixes))
+        };
+
+        // the peer did not announce the compression
+        compression.on_handshake(&peer_id, None);
+        let message = compression
+            .compress(&peer_id, &serializer, announcement(), &buffer_pool)
+            .unwrap();
+        assert!(matches!(message, Message::Operation(_)));
+
+        compression.on_handshake(&peer_id, Some(HANDSHAKE_FLAG_COMPRESSION));
+        let mut original = Vec::new();
+        serializer
+            .serialize(&announcement(), &mut original)
+            .unwrap();
+        let Message::Compressed { traffic_type, data } = compression
+            .compress(&peer_id, &serializer, announcement(), &buffer_pool)
+            .unwrap()
+        else {
+            panic!("the message was not compressed");
+        };
+        assert_eq!(traffic_type, TrafficType::OperationAnnouncement);
+        assert!(data.len() < original.len());
+        assert_eq!(compression.decompress(&data).unwrap(), original);
+        let (sent_ratio, received_ratio) = compression.get_ratios();
+        assert!(sent_ratio < 1.0);
+        assert_eq!(sent_ratio, received_ratio);
+
+        // disconnected peers are forgotten
+        compression.retain_peers(&HashSet::new());
+        let message = compression
+            .compress(&peer_id, &serializer, announcement(), &buffer_pool)
+            .unwrap();
+        assert!(matches!(message, Message::Operation(_)));
+    }
+}
@@ -7,7 +7,9 @@ use massa_metrics::MassaMetrics;
 use massa_models::stats::NetworkStats;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
-use massa_protocol_exports::{PeerCategoryInfo, PeerId, ProtocolConfig, ProtocolError};
+use massa_protocol_exports::{
+    PeerCategoryInfo, PeerId, ProtocolConfig, ProtocolError, ShutdownReport,
+};
 use massa_storage::Storage;
 use massa_versioning::versioning::MipStore;
 use parking_lot::RwLock;
@@ -18,13 +20,19 @@ use std::{collections::HashMap, net::IpAddr};
 use std::{thread::JoinHandle, time::Duration};
 use tracing::{debug, warn};
 
+use crate::diversity::PeerDiversity;
 use crate::handlers::peer_handler::models::{ConnectionMetadata, PeerDB};
+use crate::peer_scores::PeerScores;
+use crate::shutdown::ShutdownDrain;
 use crate::{
     handlers::peer_handler::models::{InitialPeers, PeerState, SharedPeerDB},
     ip::to_canonical,
     worker::ProtocolChannels,
 };
-use crate::{handlers::peer_handler::PeerManagementHandler, messages::MessagesHandler};
+use crate::{
+    handlers::peer_handler::{PeerManagementHandler, PeerQuarantine},
+    messages::MessagesHandler,
+};
 use crate::{
     handlers::{
         block_handler::{cache::BlockCache, BlockHandler},
@@ -80,7 +88,7 @@ pub(crate) fn start_connectivity_thread(
     config: ProtocolConfig,
     mip_store: MipStore,
     massa_metrics: MassaMetrics,
-) -> Result<(MassaSender<ConnectivityCommand>, JoinHandle<()>), ProtocolError> {
+) -> Result<(MassaSender<ConnectivityCommand>, JoinHandle<ShutdownReport>), ProtocolError> {
     let handle = std::thread::Builder::new()
     .name("protocol-connectivity".to_string())
     .spawn({
@@ -88,6 +96,11 @@ pub(crate) fn start_connectivity_thread(
         let sender_blocks_retrieval_ext = protocol_channels.block_handler_retrieval.0.clone();
         let sender_blocks_propagation_ext = protocol_channels.block_handler_propagation.0.clone();
         let sender_operations_propagation_ext = protocol_channels.operation_handler_propagation.0.clone();
+        let bandwidth = protocol_channels.bandwidth.clone();
+        let compression = messages_handler.compression.clone();
+        let buffer_pool = messages_handler.buffer_pool.clone();
+        let endorsement_subscriptions = messages_handler.endorsement_subscriptions.clone();
+        let block_retractions = messages_handler.block_retractions.clone();
         move || {
             for (addr, transport) in &config.listeners {
                 network_controller
@@ -102,15 +115,15 @@ pub(crate) fn start_connectivity_thread(
             std::thread::sleep(Duration::from_millis(100));
 
             // Create cache outside of the op handler because it could be used by other handlers
-            let total_in_slots = config.peers_categories.values().map(|v| v.max_in_connections).sum::<usize>() + config.default_category_info.max_in_connections + 1;
-            let total_out_slots = config.peers_categories.values().map(| v| v.target_out_connections).sum::<usize>() + config.default_category_info.target_out_connections + 1;
             let operation_cache = Arc::new(RwLock::new(OperationCache::new(
                 config.max_known_ops_size.try_into().unwrap(),
                 config.max_node_known_ops_size.try_into().unwrap()
             )));
             let endorsement_cache = Arc::new(RwLock::new(EndorsementCache::new(
                 config.max_known_endorsements_size.try_into().unwrap(),
-                (total_in_slots + total_out_slots).try_into().unwrap()
+                config.known_endorsements_filter_bits,
+                config.known_endorsements_window.to_duration(),
+                config.endorsement_fast_path_peers,
             )));
 
             let block_cache = Arc::new(RwLock::new(BlockCache::new(
@@ -118,6 +131,15 @@ pub(crate) fn start_connectivity_thread(
                 config.max_node_known_blocks_size.try_into().unwrap(),
             )));
 
+            // Scores are shared because both the block and the operation handlers update them
+            let peer_scores = Arc::new(RwLock::new(PeerScores::new(&config)));
+
+            // The quarantine is decided by the peer handler and checked by the other handlers before propagating
+            let peer_quarantine = Arc::new(PeerQuarantine::new(&config));
+
+            // Spreads the outbound connections across networks
+            let peer_diversity = PeerDiversity::new(&config);
+
             // Start handlers
             let mut peer_management_handler = PeerManagementHandler::new(
                 initial_peers,
@@ -126,6 +148,7 @@ pub(crate) fn start_connectivity_thread(
                 channel_peers,
                 protocol_channels.peer_management_handler,
                 messages_handler,
+                peer_quarantine.clone(),
                 network_controller.get_active_connections(),
                 peer_categories.iter().map(|(key, value)|(key.clone(), (value.0.clone(), value.1.target_out_connections))).collect(),
                 config.default_category_info.target_out_connections,
@@ -145,6 +168,10 @@ pub(crate) fn start_connectivity_thread(
                 sender_operations_propagation_ext.clone(),
                 protocol_channels.operation_handler_propagation.1.clone(),
                 peer_management_handler.sender.command_sender.clone(),
+                peer_scores.clone(),
+                peer_quarantine.clone(),
+                protocol_channels.propagation_lanes.clone(),
+                protocol_channels.tracer.clone(),
                 massa_metrics.clone(),
             );
             let mut endorsement_handler = EndorsementHandler::new(
@@ -159,6 +186,10 @@ pub(crate) fn start_connectivity_thread(
                 protocol_channels.endorsement_handler_retrieval.1,
                 sender_endorsements_propagation_ext.clone(),
                 protocol_channels.endorsement_handler_propagation.1.clone(),
+                protocol_channels.propagation_lanes.clone(),
+                protocol_channels.tracer.clone(),
+                endorsement_subscriptions.clone(),
+                peer_quarantine.clone(),
                 peer_management_handler.sender.command_sender.clone(),
                 massa_metrics.clone(),
             );
@@ -179,8 +210,13 @@ pub(crate) fn start_connectivity_thread(
                 endorsement_cache,
                 operation_cache,
                 block_cache,
+                peer_scores,
+                peer_quarantine,
+                protocol_channels.propagation_lanes.clone(),
+                protocol_channels.tracer.clone(),
                 storage.clone_without_refs(),
                 mip_store,
+                block_retractions.clone(),
                 massa_metrics.clone(),
             );
 
@@ -188,6 +224,7 @@ pub(crate) fn start_connectivity_thread(
             let tick_try_connect = tick(config.try_connection_timer.to_duration());
             let tick_unban_everyone = tick(config.unban_everyone_timer.to_duration());
 
+            let mut shutdown_report = ShutdownReport::default();
             //Try to connect to peers
             loop {
                 select! {
@@ -197,16 +234,28 @@ pub(crate) fn start_connectivity_thread(
                         match msg {
                             Ok(ConnectivityCommand::Stop) => {
                                 println!("Stopping protocol");
+                                let mut drain = ShutdownDrain::new(config.shutdown_drain_deadline.to_duration());
+                                // stop accepting messages and commands
+                                operation_handler.stop_retrieval(&mut drain);
+                                endorsement_handler.stop_retrieval(&mut drain);
+                                block_handler.stop_retrieval(&mut drain);
+                                println!("Stopped retrieval threads");
+                                // the propagations queued before the stop are sent before the threads stop,
+                                // highest priority lanes first so that the others do not wait for them
+                                block_handler.stop_propagation(&mut drain);
+                                endorsement_handler.stop_propagation(&mut drain);
+                                operation_handler.stop_propagation(&mut drain);
+                                println!("Flushed propagation queues");
+                                // close the connections before stopping the network
+                                let mut active_connections = network_controller.get_active_connections();
+                                for peer_id in active_connections.get_peer_ids_connected() {
+                                    active_connections.shutdown_connection(&peer_id);
+                                    drain.connection_closed();
+                                }
+                                peer_management_handler.stop(&mut drain);
                                 drop(network_controller);
-                                println!("Stopped network controller");
-                                operation_handler.stop();
-                                println!("Stopped operation handler");
-                                endorsement_handler.stop();
-                                println!("Stopped endorsement handler");
-                                block_handler.stop();
-                                println!("Stopped block handler");
-                                peer_management_handler.stop();
-                                println!("Stopped peer handler");
+                                println!("Stopped peer handler and network controller");
+                                shutdown_report = drain.into_report();
                                 break;
                             },
                             Ok(ConnectivityCommand::GetStats { responder }) => {
@@ -242,6 +291,19 @@ pub(crate) fn start_connectivity_thread(
                         massa_metrics.set_active_connections(active_conn.get_nb_in_connections(), active_conn.get_nb_out_connections());
                         let peers_map = active_conn.get_peers_connections_bandwidth();
                         massa_metrics.update_peers_tx_rx(peers_map);
+                        bandwidth.retain_peers(&active_conn.get_peer_ids_connected());
+                        let traffic = bandwidth.get_traffic_stats();
+                        for (direction, traffic) in [("received", traffic.received), ("sent", traffic.sent)] {
+                            for (message_type, message_traffic) in traffic.by_type() {
+                                massa_metrics.set_protocol_message_traffic(direction, message_type, message_traffic.messages, message_traffic.bytes);
+                            }
+                        }
+                        compression.retain_peers(&active_conn.get_peer_ids_connected());
+                        endorsement_subscriptions.retain_peers(&active_conn.get_peer_ids_connected());
+                        block_retractions.retain_peers(&active_conn.get_peer_ids_connected());
+                        let (sent_ratio, received_ratio) = compression.get_ratios();
+                        massa_metrics.set_protocol_compression_ratios(sent_ratio, received_ratio);
+                        massa_metrics.set_protocol_buffer_pool_reuse_rate(buffer_pool.get_reuse_rate());
                         let peer_db_read = peer_db.read();
                         massa_metrics.set_known_peers(peer_db_read.peers.len());
                         massa_metrics.set_banned_peers(peer_db_read.get_banned_peer_count() as usize);
@@ -339,6 +401,14 @@ pub(crate) fn start_connectivity_thread(
                         // Sort addresses using the metadata
                         addresses_can_connect.sort_by(|a, b| a.1.cmp(&b.1));
 
+                        // Then try the least represented networks first, keeping the metadata order between equally represented ones
+                        let outbound_ips = peers_connected
+                            .values()
+                            .filter(|peer| peer.1 == PeerConnectionType::OUT)
+                            .map(|peer| peer.0.ip())
+                            .chain(peers_connection_queue.iter().map(|addr| addr.ip()));
+                        let addresses_can_connect = peer_diversity.order(addresses_can_connect, outbound_ips, |(addr, _, _)| addr.ip());
+
                         // Connect to the given addresses, trying to fill all the slots available
                         let mut addresses_connected = vec![];
                         for (addr, _, category) in addresses_can_connect.iter() {
@@ -391,6 +461,7 @@ pub(crate) fn start_connectivity_thread(
                     }
                 }
             }
+            shutdown_report
         }
     }).expect("OS failed to start connectivity thread");
 
@@ -1,17 +1,25 @@
 use std::{collections::HashMap, net::SocketAddr, time::Duration};
 
-use massa_channel::{sender::MassaSender, MassaChannel};
+use massa_channel::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
 use massa_models::{
+    address::Address,
     block_header::SecuredHeader,
     block_id::BlockId,
     prehash::{PreHashMap, PreHashSet},
     stats::NetworkStats,
 };
-use massa_protocol_exports::{BootstrapPeers, PeerId, ProtocolController, ProtocolError};
+use massa_protocol_exports::{
+    AskBlockFromPeerEvent, BootstrapPeers, EndorsementDedupStats, OperationBatchStats,
+    PeerBandwidthStats, PeerId, PeerRttStats, PeerScore, ProtocolController, ProtocolError,
+    ProtocolTraceEvent, ProtocolTrafficStats,
+};
+use massa_signature::Signature;
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use peernet::peer::PeerConnectionType;
 
 use crate::{
+    bandwidth::SharedBandwidthAccounting,
     connectivity::ConnectivityCommand,
     handlers::{
         block_handler::{
@@ -19,9 +27,14 @@ use crate::{
             commands_retrieval::BlockHandlerRetrievalCommand,
         },
         endorsement_handler::commands_propagation::EndorsementHandlerPropagationCommand,
-        operation_handler::commands_propagation::OperationHandlerPropagationCommand,
+        operation_handler::{
+            commands_propagation::OperationHandlerPropagationCommand,
+            commands_retrieval::OperationHandlerRetrievalCommand,
+        },
         peer_handler::models::PeerManagementCmd,
     },
+    propagation_lanes::{PropagationLane, SharedPropagationLanes},
+    trace::SharedProtocolTracer,
 };
 
 #[derive(Clone)]
@@ -34,9 +47,16 @@ pub struct ProtocolControllerImpl {
     pub sender_block_retrieval_handler: Option<MassaSender<BlockHandlerRetrievalCommand>>,
     pub sender_block_handler: Option<MassaSender<BlockHandlerPropagationCommand>>,
     pub sender_operation_handler: Option<MassaSender<OperationHandlerPropagationCommand>>,
+    pub sender_operation_retrieval_handler: Option<MassaSender<OperationHandlerRetrievalCommand>>,
     pub sender_endorsement_handler: Option<MassaSender<EndorsementHandlerPropagationCommand>>,
     pub sender_connectivity_thread: Option<MassaSender<ConnectivityCommand>>,
     pub sender_peer_management_thread: Option<MassaSender<PeerManagementCmd>>,
+    // Counts the queued block and endorsement propagations to prioritize them
+    pub propagation_lanes: SharedPropagationLanes,
+    // Bytes exchanged with each peer, updated by the network side
+    pub bandwidth: SharedBandwidthAccounting,
+    // Traces the commands sent to the handlers
+    pub tracer: SharedProtocolTracer,
 }
 
 impl ProtocolControllerImpl {
@@ -44,17 +64,25 @@ impl ProtocolControllerImpl {
         sender_block_retrieval_handler: MassaSender<BlockHandlerRetrievalCommand>,
         sender_block_handler: MassaSender<BlockHandlerPropagationCommand>,
         sender_operation_handler: MassaSender<OperationHandlerPropagationCommand>,
+        sender_operation_retrieval_handler: MassaSender<OperationHandlerRetrievalCommand>,
         sender_endorsement_handler: MassaSender<EndorsementHandlerPropagationCommand>,
         sender_connectivity_thread: MassaSender<ConnectivityCommand>,
         sender_peer_management_thread: MassaSender<PeerManagementCmd>,
+        propagation_lanes: SharedPropagationLanes,
+        bandwidth: SharedBandwidthAccounting,
+        tracer: SharedProtocolTracer,
     ) -> Self {
         ProtocolControllerImpl {
             sender_block_retrieval_handler: Some(sender_block_retrieval_handler),
             sender_block_handler: Some(sender_block_handler),
             sender_operation_handler: Some(sender_operation_handler),
+            sender_operation_retrieval_handler: Some(sender_operation_retrieval_handler),
             sender_endorsement_handler: Some(sender_endorsement_handler),
             sender_connectivity_thread: Some(sender_connectivity_thread),
             sender_peer_management_thread: Some(sender_peer_management_thread),
+            propagation_lanes,
+            bandwidth,
+            tracer,
         }
     }
 }
@@ -63,6 +91,7 @@ impl ProtocolController for ProtocolControllerImpl {
     fn stop(&mut self) {
         drop(self.sender_block_handler.take());
         drop(self.sender_operation_handler.take());
+        drop(self.sender_operation_retrieval_handler.take());
         drop(self.sender_endorsement_handler.take());
         drop(self.sender_block_retrieval_handler.take());
     }
@@ -73,22 +102,71 @@ impl ProtocolController for ProtocolControllerImpl {
     /// * `block_id`: ID of the block
     /// * `storage`: Storage instance containing references to the block and all its dependencies
     fn integrated_block(&self, block_id: BlockId, storage: Storage) -> Result<(), ProtocolError> {
+        let command = BlockHandlerPropagationCommand::IntegratedBlock { block_id, storage };
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.propagation_lanes.enqueue(PropagationLane::Blocks);
+        self.sender_block_handler
+            .as_ref()
+            .unwrap()
+            .try_send(command)
+            .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                self.propagation_lanes.done(PropagationLane::Blocks);
+                ProtocolError::ChannelError("integrated_block command send error".into())
+            })
+    }
+
+    /// Propagate the header of a block without waiting for the block to be integrated
+    fn propagate_block_header(&self, header: SecuredHeader) -> Result<(), ProtocolError> {
+        let command = BlockHandlerPropagationCommand::PropagateHeaderOnly(header);
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.propagation_lanes.enqueue(PropagationLane::Blocks);
+        self.sender_block_handler
+            .as_ref()
+            .unwrap()
+            .try_send(command)
+            .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                self.propagation_lanes.done(PropagationLane::Blocks);
+                ProtocolError::ChannelError("propagate_block_header command send error".into())
+            })
+    }
+
+    /// Track the acknowledgements of the header of a block produced by our node
+    fn produced_block(
+        &self,
+        block_id: BlockId,
+        retraction_signature: Signature,
+    ) -> Result<(), ProtocolError> {
+        let command = BlockHandlerPropagationCommand::ProducedBlock {
+            block_id,
+            retraction_signature,
+        };
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
         self.sender_block_handler
             .as_ref()
             .unwrap()
-            .try_send(BlockHandlerPropagationCommand::IntegratedBlock { block_id, storage })
-            .map_err(|_| ProtocolError::ChannelError("integrated_block command send error".into()))
+            .try_send(command)
+            .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                ProtocolError::ChannelError("produced_block command send error".into())
+            })
     }
 
     /// Notify to protocol an attack attempt.
     fn notify_block_attack(&self, block_id: BlockId) -> Result<(), ProtocolError> {
+        let command = BlockHandlerPropagationCommand::AttackBlockDetected(block_id);
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
         self.sender_block_handler
             .as_ref()
             .unwrap()
-            .try_send(BlockHandlerPropagationCommand::AttackBlockDetected(
-                block_id,
-            ))
+            .try_send(command)
             .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
                 ProtocolError::ChannelError("notify_block_attack command send error".into())
             })
     }
@@ -99,39 +177,93 @@ impl ProtocolController for ProtocolControllerImpl {
         new: PreHashMap<BlockId, Option<SecuredHeader>>,
         remove: PreHashSet<BlockId>,
     ) -> Result<(), ProtocolError> {
+        let command = BlockHandlerRetrievalCommand::WishlistDelta { new, remove };
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
         self.sender_block_retrieval_handler
             .as_ref()
             .unwrap()
-            .send(BlockHandlerRetrievalCommand::WishlistDelta { new, remove })
+            .send(command)
             .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
                 ProtocolError::ChannelError("send_wishlist_delta command send error".into())
             })
     }
 
+    fn ask_block_from_peer(
+        &self,
+        block_id: BlockId,
+        header: Option<SecuredHeader>,
+        peer_id: PeerId,
+        timeout: MassaTime,
+    ) -> Result<MassaReceiver<AskBlockFromPeerEvent>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("ask_block_from_peer".to_string(), Some(1));
+        let command = BlockHandlerRetrievalCommand::AskBlockFromPeer {
+            block_id,
+            header,
+            peer_id,
+            timeout,
+            responder: sender,
+        };
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.sender_block_retrieval_handler
+            .as_ref()
+            .unwrap()
+            .send(command)
+            .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                ProtocolError::ChannelError("ask_block_from_peer command send error".into())
+            })?;
+        Ok(receiver)
+    }
+
     /// Propagate a batch of operation ids (from pool).
     ///
     /// note: Full `OperationId` is replaced by a `OperationPrefixId` later by the worker.
     fn propagate_operations(&self, operations: Storage) -> Result<(), ProtocolError> {
+        let command = OperationHandlerPropagationCommand::PropagateOperations(operations);
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
         self.sender_operation_handler
             .as_ref()
             .unwrap()
-            .try_send(OperationHandlerPropagationCommand::PropagateOperations(
-                operations,
-            ))
+            .try_send(command)
             .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
                 ProtocolError::ChannelError("propagate_operations command send error".into())
             })
     }
 
+    /// Ban the operations created by some addresses for `operation_source_ban_duration`
+    fn ban_address_sources(&self, addresses: Vec<Address>) -> Result<(), ProtocolError> {
+        let command = OperationHandlerPropagationCommand::BanAddressSources(addresses);
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.sender_operation_handler
+            .as_ref()
+            .unwrap()
+            .try_send(command)
+            .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                ProtocolError::ChannelError("ban_address_sources command send error".into())
+            })
+    }
+
     /// propagate endorsements to connected node
     fn propagate_endorsements(&self, endorsements: Storage) -> Result<(), ProtocolError> {
+        let command = EndorsementHandlerPropagationCommand::PropagateEndorsements(endorsements);
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.propagation_lanes
+            .enqueue(PropagationLane::Endorsements);
         self.sender_endorsement_handler
             .as_ref()
             .unwrap()
-            .try_send(EndorsementHandlerPropagationCommand::PropagateEndorsements(
-                endorsements,
-            ))
+            .try_send(command)
             .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                self.propagation_lanes.done(PropagationLane::Endorsements);
                 ProtocolError::ChannelError("propagate_endorsements command send error".into())
             })
     }
@@ -186,6 +318,92 @@ impl ProtocolController for ProtocolControllerImpl {
         })
     }
 
+    fn get_peer_rtt_stats(&self) -> Result<HashMap<PeerId, PeerRttStats>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peer_rtt_stats".to_string(), Some(1));
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::GetRttStats { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peer_rtt_stats command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peer_rtt_stats command receive error".into())
+        })
+    }
+
+    fn get_operation_batch_stats(
+        &self,
+    ) -> Result<HashMap<PeerId, OperationBatchStats>, ProtocolError> {
+        let (sender, receiver) =
+            MassaChannel::new("get_operation_batch_stats".to_string(), Some(1));
+        let command = OperationHandlerRetrievalCommand::GetBatchStats { responder: sender };
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.sender_operation_retrieval_handler
+            .as_ref()
+            .unwrap()
+            .try_send(command)
+            .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                ProtocolError::ChannelError("get_operation_batch_stats command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_operation_batch_stats command receive error".into())
+        })
+    }
+
+    fn get_peer_scores(&self) -> Result<HashMap<PeerId, PeerScore>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peer_scores".to_string(), Some(1));
+        let command = BlockHandlerRetrievalCommand::GetPeerScores { responder: sender };
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.sender_block_retrieval_handler
+            .as_ref()
+            .unwrap()
+            .try_send(command)
+            .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                ProtocolError::ChannelError("get_peer_scores command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peer_scores command receive error".into())
+        })
+    }
+
+    fn get_endorsement_dedup_stats(&self) -> Result<EndorsementDedupStats, ProtocolError> {
+        let (sender, receiver) =
+            MassaChannel::new("get_endorsement_dedup_stats".to_string(), Some(1));
+        let command = EndorsementHandlerPropagationCommand::GetDedupStats { responder: sender };
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.sender_endorsement_handler
+            .as_ref()
+            .unwrap()
+            .try_send(command)
+            .map_err(|_| {
+                self.tracer.dropped(trace_name, sequence);
+                ProtocolError::ChannelError("get_endorsement_dedup_stats command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_endorsement_dedup_stats command receive error".into())
+        })
+    }
+
+    fn get_peer_bandwidth_stats(
+        &self,
+    ) -> Result<HashMap<PeerId, PeerBandwidthStats>, ProtocolError> {
+        Ok(self.bandwidth.get_stats())
+    }
+
+    fn get_protocol_traffic_stats(&self) -> Result<ProtocolTrafficStats, ProtocolError> {
+        Ok(self.bandwidth.get_traffic_stats())
+    }
+
+    fn get_protocol_trace(&self) -> Result<Vec<ProtocolTraceEvent>, ProtocolError> {
+        Ok(self.tracer.get_events())
+    }
+
     fn clone_box(&self) -> Box<dyn ProtocolController> {
         Box::new(self.clone())
     }
@@ -0,0 +1,287 @@
+//! Network diversity of the outbound connections.
+//!
+//! To make it harder for an attacker controlling many addresses of a single network or hosting provider
+//! to surround the node, the candidates for outbound connections are ordered so that the ones sharing
+//! an IP prefix (/16 for IPv4, /32 for IPv6) or an autonomous system with the peers we are already
+//! connected to come last.
+//!
+//! The autonomous systems are read from an optional file, with one `<network>/<prefix length> <ASN>` range per line.
+//! The ranges must not overlap. Empty lines and lines starting with `#` are ignored.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+
+use massa_protocol_exports::{ProtocolConfig, ProtocolError};
+use tracing::warn;
+
+use crate::ip::to_canonical;
+
+/// Network of an IP address, used to spread the outbound connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NetworkGroup {
+    /// /16 prefix of an IPv4 address
+    V4([u8; 2]),
+    /// /32 prefix of an IPv6 address
+    V6([u16; 2]),
+}
+
+impl NetworkGroup {
+    fn of(ip: IpAddr) -> Self {
+        match to_canonical(ip) {
+            IpAddr::V4(v4) => {
+                let octets = v4.octets();
+                NetworkGroup::V4([octets[0], octets[1]])
+            }
+            IpAddr::V6(v6) => {
+                let segments = v6.segments();
+                NetworkGroup::V6([segments[0], segments[1]])
+            }
+        }
+    }
+}
+
+/// Maps IP ranges to autonomous system numbers
+#[derive(Debug, Default)]
+pub(crate) struct AsnDatabase {
+    /// IPv4 ranges (first address, last address, ASN), sorted by first address
+    v4: Vec<(u32, u32, u32)>,
+    /// IPv6 ranges (first address, last address, ASN), sorted by first address
+    v6: Vec<(u128, u128, u32)>,
+}
+
+fn range_of(prefix: u128, prefix_len: u32, bits: u32) -> Option<(u128, u128)> {
+    if prefix_len > bits {
+        return None;
+    }
+    let host_bits = bits - prefix_len;
+    let host_mask = if host_bits == 128 {
+        u128::MAX
+    } else {
+        (1u128 << host_bits) - 1
+    };
+    let first = prefix & !host_mask;
+    Some((first, first | host_mask))
+}
+
+fn lookup<T: Copy + Ord>(ranges: &[(T, T, u32)], ip: T) -> Option<u32> {
+    let index = ranges.partition_point(|(first, _, _)| *first <= ip);
+    let (_, last, asn) = ranges.get(index.checked_sub(1)?)?;
+    (ip <= *last).then_some(*asn)
+}
+
+impl AsnDatabase {
+    /// Parses the content of an ASN file
+    pub(crate) fn parse(content: &str) -> Result<Self, String> {
+        let mut database = AsnDatabase::default();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |reason: &str| format!("line {}: {}", index + 1, reason);
+            let mut fields = line.split_whitespace();
+            let (Some(network), Some(asn), None) = (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(error("expected a network and an ASN"));
+            };
+            let asn: u32 = asn
+                .trim_start_matches("AS")
+                .parse()
+                .map_err(|_| error("invalid ASN"))?;
+            let Some((ip, prefix_len)) = network.split_once('/') else {
+                return Err(error("missing prefix length"));
+            };
+            let prefix_len: u32 = prefix_len
+                .parse()
+                .map_err(|_| error("invalid prefix length"))?;
+            match ip.parse::<IpAddr>().map_err(|_| error("invalid IP"))? {
+                IpAddr::V4(v4) => {
+                    let (first, last) = range_of(u32::from(v4) as u128, prefix_len, 32)
+                        .ok_or_else(|| error("invalid prefix length"))?;
+                    database.v4.push((first as u32, last as u32, asn));
+                }
+                IpAddr::V6(v6) => {
+                    let (first, last) = range_of(u128::from(v6), prefix_len, 128)
+                        .ok_or_else(|| error("invalid prefix length"))?;
+                    database.v6.push((first, last, asn));
+                }
+            }
+        }
+        database.v4.sort_unstable();
+        database.v6.sort_unstable();
+        Ok(database)
+    }
+
+    /// Loads an ASN file
+    pub(crate) fn load(path: &Path) -> Result<Self, ProtocolError> {
+        let content = std::fs::read_to_string(path).map_err(|err| {
+            ProtocolError::GeneralProtocolError(format!(
+                "error reading ASN file {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        AsnDatabase::parse(&content).map_err(|err| {
+            ProtocolError::GeneralProtocolError(format!(
+                "error parsing ASN file {}: {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+
+    /// Autonomous system of an IP address, if known
+    pub(crate) fn get_asn(&self, ip: IpAddr) -> Option<u32> {
+        match to_canonical(ip) {
+            IpAddr::V4(v4) => lookup(&self.v4, u32::from(v4)),
+            IpAddr::V6(v6) => lookup(&self.v6, u128::from(v6)),
+        }
+    }
+}
+
+/// Orders the candidates for outbound connections by network diversity
+pub(crate) struct PeerDiversity {
+    /// penalty of each outbound peer already in the same IP prefix
+    prefix_weight: f64,
+    /// penalty of each outbound peer already in the same autonomous system
+    asn_weight: f64,
+    /// autonomous systems of the IP ranges, if configured
+    asn_database: Option<AsnDatabase>,
+}
+
+impl PeerDiversity {
+    pub(crate) fn new(config: &ProtocolConfig) -> Self {
+        let asn_database =
+            config
+                .asn_database_path
+                .as_ref()
+                .and_then(|path| match AsnDatabase::load(path) {
+                    Ok(database) => Some(database),
+                    Err(err) => {
+                        warn!("{}, peer diversity ignores autonomous systems", err);
+                        None
+                    }
+                });
+        PeerDiversity {
+            prefix_weight: config.peer_diversity_prefix_weight,
+            asn_weight: config.peer_diversity_asn_weight,
+            asn_database,
+        }
+    }
+
+    /// Reorders `candidates` so that each one is the least represented among the outbound peers
+    /// (`outbound_ips`) and the candidates before it. Ties keep the original order.
+    pub(crate) fn order<T>(
+        &self,
+        candidates: Vec<T>,
+        outbound_ips: impl IntoIterator<Item = IpAddr>,
+        ip_of: impl Fn(&T) -> IpAddr,
+    ) -> Vec<T> {
+        if self.prefix_weight <= 0.0 && self.asn_weight <= 0.0 {
+            return candidates;
+        }
+        let asn_of = |ip: IpAddr| {
+            self.asn_database
+                .as_ref()
+                .and_then(|database| database.get_asn(ip))
+        };
+        let mut prefix_counts: HashMap<NetworkGroup, usize> = HashMap::new();
+        let mut asn_counts: HashMap<u32, usize> = HashMap::new();
+        for ip in outbound_ips {
+            *prefix_counts.entry(NetworkGroup::of(ip)).or_default() += 1;
+            if let Some(asn) = asn_of(ip) {
+                *asn_counts.entry(asn).or_default() += 1;
+            }
+        }
+
+        let mut remaining: Vec<(NetworkGroup, Option<u32>, T)> = candidates
+            .into_iter()
+            .map(|candidate| {
+                let ip = ip_of(&candidate);
+                (NetworkGroup::of(ip), asn_of(ip), candidate)
+            })
+            .collect();
+        let mut ordered = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let penalty = |(group, asn, _): &(NetworkGroup, Option<u32>, T)| {
+                let prefix_count = prefix_counts.get(group).copied().unwrap_or_default();
+                let asn_count = asn
+                    .and_then(|asn| asn_counts.get(&asn).copied())
+                    .unwrap_or_default();
+                self.prefix_weight * prefix_count as f64 + self.asn_weight * asn_count as f64
+            };
+            let mut best = 0;
+            let mut best_penalty = penalty(&remaining[0]);
+            for (index, candidate) in remaining.iter().enumerate().skip(1) {
+                let candidate_penalty = penalty(candidate);
+                if candidate_penalty < best_penalty {
+                    best = index;
+                    best_penalty = candidate_penalty;
+                }
+            }
+            let (group, asn, candidate) = remaining.remove(best);
+            *prefix_counts.entry(group).or_default() += 1;
+            if let Some(asn) = asn {
+                *asn_counts.entry(asn).or_default() += 1;
+            }
+            ordered.push(candidate);
+        }
+        ordered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diversity(asn_database: Option<AsnDatabase>) -> PeerDiversity {
+        PeerDiversity {
+            prefix_weight: 1.0,
+            asn_weight: 2.0,
+            asn_database,
+        }
+    }
+
+    #[test]
+    fn test_asn_database() {
+        let database = AsnDatabase::parse(
+            "# comment\n1.2.0.0/16 AS13335\n\n2001:db8::/32 64500\n1.3.0.0/24 64501\n",
+        )
+        .unwrap();
+        assert_eq!(database.get_asn("1.2.200.1".parse().unwrap()), Some(13335));
+        assert_eq!(database.get_asn("1.3.0.1".parse().unwrap()), Some(64501));
+        assert_eq!(database.get_asn("1.3.1.1".parse().unwrap()), None);
+        assert_eq!(
+            database.get_asn("2001:db8:1::1".parse().unwrap()),
+            Some(64500)
+        );
+        assert_eq!(
+            database.get_asn("::ffff:1.2.200.1".parse().unwrap()),
+            Some(13335)
+        );
+        assert!(AsnDatabase::parse("1.2.0.0/33 1").is_err());
+        assert!(AsnDatabase::parse("1.2.0.0 1").is_err());
+    }
+
+    #[test]
+    fn test_order_by_prefix_and_asn() {
+        let ips: Vec<IpAddr> = ["10.1.0.1", "10.1.0.2", "10.2.0.1", "10.3.0.1"]
+            .iter()
+            .map(|ip| ip.parse().unwrap())
+            .collect();
+
+        // the second address of a prefix comes after the other prefixes
+        let ordered = diversity(None).order(ips.clone(), [], |ip| *ip);
+        assert_eq!(ordered, vec![ips[0], ips[2], ips[3], ips[1]]);
+
+        // prefixes of the connected peers come last
+        let ordered = diversity(None).order(ips.clone(), [ips[2]], |ip| *ip);
+        assert_eq!(ordered, vec![ips[0], ips[3], ips[1], ips[2]]);
+
+        // autonomous systems weigh more than prefixes
+        let database = AsnDatabase::parse("10.3.0.0/16 1\n10.2.0.0/16 1").unwrap();
+        let ordered = diversity(Some(database)).order(ips.clone(), [], |ip| *ip);
+        assert_eq!(ordered, vec![ips[0], ips[2], ips[1], ips[3]]);
+    }
+}
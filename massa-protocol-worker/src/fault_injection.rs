@@ -0,0 +1,402 @@
+//! Simulated packet loss and latency on the messages sent to the peers.
+//!
+//! The fault injection layer wraps the network controller given to the protocol handlers:
+//! each message sent to a peer is dropped, delayed or reordered according to the
+//! `FaultInjectionConfig` of the protocol. Delayed messages are sent by a dedicated thread
+//! once their delay has elapsed. This layer is only meant for simulations and tests
+//! of the retry and backoff logic: it is never built in the node.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crossbeam::channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use massa_protocol_exports::{FaultInjectionConfig, PeerId, ProtocolConfig, ProtocolError};
+use parking_lot::Mutex;
+use peernet::{peer::PeerConnectionType, transports::TransportType};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tracing::debug;
+
+use crate::{
+    handlers::{
+        block_handler::BlockMessageSerializer, endorsement_handler::EndorsementMessageSerializer,
+        operation_handler::OperationMessageSerializer,
+        peer_handler::PeerManagementMessageSerializer,
+    },
+    messages::{Message, MessagesSerializer},
+    wrap_network::{ActiveConnectionsTrait, NetworkController},
+};
+
+/// Wraps a network controller in the fault injection layer if the config enables it
+pub(crate) fn wrap_network_controller(
+    network_controller: Box<dyn NetworkController>,
+    config: &ProtocolConfig,
+) -> Box<dyn NetworkController> {
+    match &config.fault_injection {
+        Some(fault_injection) => Box::new(FaultInjectionNetworkController::new(
+            network_controller,
+            fault_injection.clone(),
+        )),
+        None => network_controller,
+    }
+}
+
+/// Message waiting for its delay to elapse
+struct DelayedMessage {
+    send_at: Instant,
+    peer_id: PeerId,
+    message: Message,
+    high_priority: bool,
+}
+
+/// Draws the faults of the messages and queues the delayed ones
+struct FaultInjector {
+    config: FaultInjectionConfig,
+    rng: Mutex<StdRng>,
+    delayed_sender: Sender<DelayedMessage>,
+}
+
+impl FaultInjector {
+    /// Draws the delay of a message sent to a peer, `None` if the message is dropped
+    fn draw_delay(&self, peer_id: &PeerId) -> Option<Duration> {
+        let faults = self.config.get_peer_faults(peer_id);
+        let mut rng = self.rng.lock();
+        if rng.gen_bool(faults.drop_rate.clamp(0.0, 1.0)) {
+            return None;
+        }
+        let mut delay = faults.latency.to_duration();
+        let jitter = faults.latency_jitter.to_millis();
+        if jitter > 0 {
+            delay += Duration::from_millis(rng.gen_range(0..=jitter));
+        }
+        if rng.gen_bool(faults.reorder_rate.clamp(0.0, 1.0)) {
+            delay += faults.reorder_delay.to_duration();
+        }
+        Some(delay)
+    }
+}
+
+/// Network controller whose connections drop, delay and reorder the sent messages
+pub(crate) struct FaultInjectionNetworkController {
+    network_controller: Box<dyn NetworkController>,
+    injector: Arc<FaultInjector>,
+}
+
+impl FaultInjectionNetworkController {
+    /// Wraps a network controller and spawns the thread sending the delayed messages.
+    /// The thread stops once all the connections of the controller are dropped.
+    pub fn new(
+        network_controller: Box<dyn NetworkController>,
+        config: FaultInjectionConfig,
+    ) -> Self {
+        let (delayed_sender, delayed_receiver) = unbounded();
+        let active_connections = network_controller.get_active_connections();
+        std::thread::Builder::new()
+            .name("protocol-fault-injection".to_string())
+            .spawn(move || run_delayed_messages(delayed_receiver, active_connections))
+            .expect("failed to spawn thread : protocol-fault-injection");
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            network_controller,
+            injector: Arc::new(FaultInjector {
+                config,
+                rng: Mutex::new(rng),
+                delayed_sender,
+            }),
+        }
+    }
+}
+
+impl NetworkController for FaultInjectionNetworkController {
+    fn get_active_connections(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(FaultyActiveConnections {
+            active_connections: self.network_controller.get_active_connections(),
+            injector: self.injector.clone(),
+        })
+    }
+
+    fn start_listener(
+        &mut self,
+        transport_type: TransportType,
+        addr: SocketAddr,
+    ) -> Result<(), ProtocolError> {
+        self.network_controller.start_listener(transport_type, addr)
+    }
+
+    fn stop_listener(
+        &mut self,
+        transport_type: TransportType,
+        addr: SocketAddr,
+    ) -> Result<(), ProtocolError> {
+        self.network_controller.stop_listener(transport_type, addr)
+    }
+
+    fn try_connect(&mut self, addr: SocketAddr, timeout: Duration) -> Result<(), ProtocolError> {
+        self.network_controller.try_connect(addr, timeout)
+    }
+
+    fn get_total_bytes_received(&self) -> u64 {
+        self.network_controller.get_total_bytes_received()
+    }
+
+    fn get_total_bytes_sent(&self) -> u64 {
+        self.network_controller.get_total_bytes_sent()
+    }
+}
+
+/// Active connections dropping, delaying and reordering the sent messages
+struct FaultyActiveConnections {
+    active_connections: Box<dyn ActiveConnectionsTrait>,
+    injector: Arc<FaultInjector>,
+}
+
+impl ActiveConnectionsTrait for FaultyActiveConnections {
+    fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        high_priority: bool,
+    ) -> Result<(), ProtocolError> {
+        match self.injector.draw_delay(peer_id) {
+            // lost messages are not reported to the sender, as on a real network
+            None => Ok(()),
+            Some(delay) if delay.is_zero() => self.active_connections.send_to_peer(
+                peer_id,
+                message_serializer,
+                message,
+                high_priority,
+            ),
+            Some(delay) => self
+                .injector
+                .delayed_sender
+                .send(DelayedMessage {
+                    send_at: Instant::now() + delay,
+                    peer_id: peer_id.clone(),
+                    message,
+                    high_priority,
+                })
+                .map_err(|err| ProtocolError::SendError(err.to_string())),
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(FaultyActiveConnections {
+            active_connections: self.active_connections.clone(),
+            injector: self.injector.clone(),
+        })
+    }
+
+    fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+        self.active_connections.get_peer_ids_connected()
+    }
+
+    fn get_peers_connected(
+        &self,
+    ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+        self.active_connections.get_peers_connected()
+    }
+
+    fn get_peer_ids_out_connection_queue(&self) -> HashSet<SocketAddr> {
+        self.active_connections.get_peer_ids_out_connection_queue()
+    }
+
+    fn get_nb_out_connections(&self) -> usize {
+        self.active_connections.get_nb_out_connections()
+    }
+
+    fn get_nb_in_connections(&self) -> usize {
+        self.active_connections.get_nb_in_connections()
+    }
+
+    fn shutdown_connection(&mut self, peer_id: &PeerId) {
+        self.active_connections.shutdown_connection(peer_id)
+    }
+
+    fn get_peers_connections_bandwidth(&self) -> HashMap<String, (u64, u64)> {
+        self.active_connections.get_peers_connections_bandwidth()
+    }
+}
+
+/// Sends the delayed messages once their delay has elapsed, until all the senders are dropped
+fn run_delayed_messages(
+    receiver: Receiver<DelayedMessage>,
+    active_connections: Box<dyn ActiveConnectionsTrait>,
+) {
+    let message_serializer = MessagesSerializer::new()
+        .with_block_message_serializer(BlockMessageSerializer::new())
+        .with_endorsement_message_serializer(EndorsementMessageSerializer::new())
+        .with_operation_message_serializer(OperationMessageSerializer::new())
+        .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
+    // the counter keeps the sending order of the messages with the same deadline
+    let mut pending: BTreeMap<(Instant, u64), DelayedMessage> = BTreeMap::new();
+    let mut counter: u64 = 0;
+    loop {
+        let received = match pending.keys().next() {
+            Some((send_at, _)) => receiver.recv_deadline(*send_at),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(delayed) => {
+                pending.insert((delayed.send_at, counter), delayed);
+                counter += 1;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        let now = Instant::now();
+        while let Some(entry) = pending.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let delayed = entry.remove();
+            if let Err(err) = active_connections.send_to_peer(
+                &delayed.peer_id,
+                &message_serializer,
+                delayed.message,
+                delayed.high_priority,
+            ) {
+                debug!(
+                    "could not send a delayed message to peer {}: {}",
+                    delayed.peer_id, err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound::Included;
+
+    use massa_channel::MassaChannel;
+    use massa_protocol_exports::PeerFaults;
+    use massa_serialization::U64VarIntDeserializer;
+    use massa_signature::KeyPair;
+    use massa_time::MassaTime;
+    use parking_lot::RwLock;
+
+    use super::*;
+    use crate::{
+        bandwidth::BandwidthAccounting,
+        buffer_pool::BufferPool,
+        compression::MessageCompression,
+        handlers::{
+            block_handler::BlockRetractions,
+            endorsement_handler::EndorsementSubscriptions,
+            peer_handler::{models::PeerDB, PeerManagementMessage},
+        },
+        messages::MessagesHandler,
+        tests::mock_network::MockNetworkController,
+    };
+
+    fn list_peers_message(count: usize) -> Message {
+        let peers = (0..count)
+            .map(|_| {
+                (
+                    PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key()),
+                    HashMap::new(),
+                )
+            })
+            .collect();
+        Message::PeerManagement(Box::new(PeerManagementMessage::ListPeers(peers)))
+    }
+
+    fn peers_count(message: &Message) -> usize {
+        match message {
+            Message::PeerManagement(message) => match message.as_ref() {
+                PeerManagementMessage::ListPeers(peers) => peers.len(),
+                _ => panic!("unexpected message"),
+            },
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    #[test]
+    fn test_fault_injection() {
+        let messages_handler = MessagesHandler {
+            sender_blocks: MassaChannel::new("blocks".to_string(), None).0,
+            sender_endorsements: MassaChannel::new("endorsements".to_string(), None).0,
+            sender_operations: MassaChannel::new("operations".to_string(), None).0,
+            sender_peers: MassaChannel::new("peers".to_string(), None).0,
+            bandwidth: Arc::new(BandwidthAccounting::new(
+                &ProtocolConfig::default(),
+                MassaChannel::new("peer_cmd".to_string(), None).0,
+            )),
+            compression: Arc::new(MessageCompression::new(&ProtocolConfig::default())),
+            buffer_pool: Arc::new(BufferPool::new(&ProtocolConfig::default())),
+            endorsement_subscriptions: Arc::new(EndorsementSubscriptions::new(
+                &ProtocolConfig::default(),
+            )),
+            block_retractions: Arc::new(BlockRetractions::new()),
+            id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        };
+        let mut mock_network =
+            MockNetworkController::new(messages_handler, Arc::new(RwLock::new(PeerDB::default())));
+        let (lossy_peer, lossy_receiver) = mock_network.create_fake_connection(
+            PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key()),
+        );
+        let (slow_peer, slow_receiver) = mock_network.create_fake_connection(
+            PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key()),
+        );
+
+        let config = FaultInjectionConfig {
+            default_faults: PeerFaults {
+                latency: MassaTime::from_millis(300),
+                ..Default::default()
+            },
+            peers: HashMap::from([(
+                lossy_peer.clone(),
+                PeerFaults {
+                    drop_rate: 1.0,
+                    ..Default::default()
+                },
+            )]),
+            seed: Some(0),
+        };
+        let network_controller =
+            FaultInjectionNetworkController::new(Box::new(mock_network), config);
+        let active_connections = network_controller.get_active_connections();
+        let message_serializer = MessagesSerializer::new()
+            .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
+
+        // all the messages sent to the lossy peer are dropped
+        active_connections
+            .send_to_peer(
+                &lossy_peer,
+                &message_serializer,
+                list_peers_message(0),
+                false,
+            )
+            .unwrap();
+        assert!(lossy_receiver
+            .recv_timeout(Duration::from_millis(500))
+            .is_err());
+
+        // the messages sent to the slow peer are delayed, in their sending order
+        let start = Instant::now();
+        for count in 0..3 {
+            active_connections
+                .send_to_peer(
+                    &slow_peer,
+                    &message_serializer,
+                    list_peers_message(count),
+                    false,
+                )
+                .unwrap();
+        }
+        for count in 0..3 {
+            let message = slow_receiver
+                .recv_timeout(Duration::from_millis(2000))
+                .expect("delayed message not received");
+            assert_eq!(peers_count(&message), count);
+        }
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+}
@@ -0,0 +1,192 @@
+//! Gossip of block arrival timings.
+//!
+//! Each node measures the delay between the timestamp of a slot and the reception of the first header
+//! of that slot, and periodically shares its recent delays with its peers. The messages only contain
+//! slots and delays: neither the block IDs nor the peers the headers came from are shared.
+//!
+//! The delays measured locally and received from each peer are aggregated into the timeout used
+//! when asking blocks to peers, instead of the fixed `ask_block_timeout`:
+//! the median of the median delay of each source, multiplied by a safety margin,
+//! and kept between `min_ask_block_timeout` and `ask_block_timeout`.
+//! Taking the median over the sources limits the influence of a minority of peers sending wrong timings.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use massa_models::slot::Slot;
+use massa_protocol_exports::{PeerId, ProtocolConfig};
+use massa_time::MassaTime;
+
+/// Max number of timings in an arrival timings message
+pub(crate) const MAX_ARRIVAL_TIMINGS_PER_MESSAGE: u64 = 64;
+
+/// Number of recent timings kept for each source
+const MAX_TIMINGS_PER_SOURCE: usize = 64;
+
+/// Margin applied to the typical arrival delay of headers to get the block ask timeout,
+/// which also covers the transfer of the operations of the block
+const ARRIVAL_DELAY_TIMEOUT_FACTOR: u32 = 3;
+
+/// Recent block arrival timings of the node and of its peers
+pub(crate) struct BlockArrivalTimings {
+    /// timings measured locally and not gossiped yet
+    pending: Vec<(Slot, MassaTime)>,
+    /// recent delays measured locally
+    local: VecDeque<MassaTime>,
+    /// recent delays received from each peer
+    remote: HashMap<PeerId, VecDeque<MassaTime>>,
+    /// delays above this one are ignored (blocks received while syncing, or wrong timings)
+    max_delay: MassaTime,
+    /// lower bound of the timeout
+    min_timeout: Duration,
+    /// upper bound of the timeout, used when no timings are known
+    max_timeout: Duration,
+    /// current timeout, updated each time the timings change
+    timeout: Duration,
+}
+
+impl BlockArrivalTimings {
+    pub(crate) fn new(config: &ProtocolConfig) -> Self {
+        let max_timeout = config.ask_block_timeout.to_duration();
+        Self {
+            pending: Vec::new(),
+            local: VecDeque::new(),
+            remote: HashMap::new(),
+            max_delay: config.max_block_propagation_time,
+            min_timeout: config.min_ask_block_timeout.to_duration().min(max_timeout),
+            max_timeout,
+            timeout: max_timeout,
+        }
+    }
+
+    /// Current timeout of the block asks
+    pub(crate) fn ask_block_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Records the delay between the timestamp of a slot and the reception of its first header
+    pub(crate) fn record_local(&mut self, slot: Slot, delay: MassaTime) {
+        if delay > self.max_delay {
+            return;
+        }
+        if self.pending.len() < MAX_ARRIVAL_TIMINGS_PER_MESSAGE as usize {
+            self.pending.push((slot, delay));
+        }
+        push_bounded(&mut self.local, delay);
+        self.update_timeout();
+    }
+
+    /// Takes the local timings to gossip
+    pub(crate) fn take_pending(&mut self) -> Vec<(Slot, MassaTime)> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Records the timings gossiped by a peer
+    pub(crate) fn record_remote(&mut self, peer_id: &PeerId, timings: &[(Slot, MassaTime)]) {
+        let delays = self.remote.entry(peer_id.clone()).or_default();
+        for (_slot, delay) in timings {
+            if *delay <= self.max_delay {
+                push_bounded(delays, *delay);
+            }
+        }
+        self.update_timeout();
+    }
+
+    /// Forgets the timings of the disconnected peers
+    pub(crate) fn retain_peers(&mut self, connected_peers: &HashSet<PeerId>) {
+        let count = self.remote.len();
+        self.remote
+            .retain(|peer_id, _| connected_peers.contains(peer_id));
+        if self.remote.len() != count {
+            self.update_timeout();
+        }
+    }
+
+    fn update_timeout(&mut self) {
+        let mut medians: Vec<MassaTime> = std::iter::once(&self.local)
+            .chain(self.remote.values())
+            .filter_map(median)
+            .collect();
+        if medians.is_empty() {
+            self.timeout = self.max_timeout;
+            return;
+        }
+        medians.sort_unstable();
+        let delay = medians[medians.len() / 2].to_duration();
+        self.timeout = delay
+            .saturating_mul(ARRIVAL_DELAY_TIMEOUT_FACTOR)
+            .clamp(self.min_timeout, self.max_timeout);
+    }
+}
+
+fn push_bounded(delays: &mut VecDeque<MassaTime>, delay: MassaTime) {
+    if delays.len() >= MAX_TIMINGS_PER_SOURCE {
+        delays.pop_front();
+    }
+    delays.push_back(delay);
+}
+
+fn median(delays: &VecDeque<MassaTime>) -> Option<MassaTime> {
+    let mut sorted: Vec<MassaTime> = delays.iter().copied().collect();
+    sorted.sort_unstable();
+    sorted.get(sorted.len() / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    fn peer_id() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_block_arrival_timings_timeout() {
+        let config = ProtocolConfig {
+            ask_block_timeout: MassaTime::from_millis(10000),
+            min_ask_block_timeout: MassaTime::from_millis(500),
+            max_block_propagation_time: MassaTime::from_millis(40000),
+            ..Default::default()
+        };
+        let mut timings = BlockArrivalTimings::new(&config);
+        let slot = Slot::new(1, 0);
+
+        // no timings: fixed timeout
+        assert_eq!(timings.ask_block_timeout(), Duration::from_millis(10000));
+
+        // local timings
+        timings.record_local(slot, MassaTime::from_millis(1000));
+        assert_eq!(timings.ask_block_timeout(), Duration::from_millis(3000));
+        assert_eq!(
+            timings.take_pending(),
+            vec![(slot, MassaTime::from_millis(1000))]
+        );
+        assert!(timings.take_pending().is_empty());
+
+        // delays above the max propagation time are ignored
+        timings.record_local(slot, MassaTime::from_millis(50000));
+        assert_eq!(timings.ask_block_timeout(), Duration::from_millis(3000));
+        assert!(timings.take_pending().is_empty());
+
+        // a single peer with wrong timings does not move the median of the sources
+        let honest = peer_id();
+        let liar = peer_id();
+        timings.record_remote(&honest, &[(slot, MassaTime::from_millis(1200))]);
+        timings.record_remote(&liar, &[(slot, MassaTime::from_millis(1))]);
+        assert_eq!(timings.ask_block_timeout(), Duration::from_millis(3000));
+
+        // the timeout stays within its bounds
+        let mut timings = BlockArrivalTimings::new(&config);
+        timings.record_local(slot, MassaTime::from_millis(10));
+        assert_eq!(timings.ask_block_timeout(), Duration::from_millis(500));
+        timings.record_remote(&honest, &[(slot, MassaTime::from_millis(30000)); 10]);
+        assert_eq!(timings.ask_block_timeout(), Duration::from_millis(10000));
+
+        // the timings of disconnected peers are forgotten
+        timings.retain_peers(&HashSet::new());
+        assert_eq!(timings.ask_block_timeout(), Duration::from_millis(500));
+    }
+}
@@ -6,6 +6,7 @@ use std::{
 
 use massa_models::{block_header::SecuredHeader, block_id::BlockId};
 use massa_protocol_exports::PeerId;
+use massa_signature::Signature;
 use parking_lot::RwLock;
 use schnellru::{ByLength, LruMap};
 
@@ -17,6 +18,19 @@ pub struct BlockCache {
     pub blocks_known_by_peer: HashMap<PeerId, LruMap<BlockId, (bool, Instant)>>,
     /// max number of blocks known in peer knowledge cache
     pub max_known_blocks_by_peer: u32,
+    /// acknowledgements of the blocks produced by our node
+    pub produced_blocks: LruMap<BlockId, ProducedBlockAcks>,
+}
+
+/// Acknowledgements by our peers of the header of a block produced by our node
+#[derive(Default)]
+pub struct ProducedBlockAcks {
+    /// peers that showed they know the header, by announcing it or asking for the block
+    pub acknowledged_by: HashSet<PeerId>,
+    /// number of times the header was announced again to each peer that did not acknowledge it
+    pub reannounces: HashMap<PeerId, u32>,
+    /// signature of the retraction hash of the block by its creator
+    pub retraction_signature: Option<Signature>,
 }
 
 impl BlockCache {
@@ -43,6 +57,24 @@ impl BlockCache {
             known_blocks.insert(*block_id, (known, now));
         }
     }
+
+    /// Start tracking the acknowledgements of a block produced by our node
+    pub fn insert_produced_block(&mut self, block_id: BlockId, retraction_signature: Signature) {
+        if let Some(acks) = self
+            .produced_blocks
+            .get_or_insert(block_id, ProducedBlockAcks::default)
+        {
+            acks.retraction_signature = Some(retraction_signature);
+        }
+    }
+
+    /// Mark a peer as knowing the header of a block, if that block was produced by our node
+    pub fn acknowledge_produced_block(&mut self, peer_id: &PeerId, block_id: &BlockId) {
+        if let Some(acks) = self.produced_blocks.peek_mut(block_id) {
+            acks.acknowledged_by.insert(peer_id.clone());
+            acks.reannounces.remove(peer_id);
+        }
+    }
 }
 
 impl BlockCache {
@@ -51,6 +83,7 @@ impl BlockCache {
             checked_headers: LruMap::new(ByLength::new(max_known_blocks)),
             blocks_known_by_peer: HashMap::new(),
             max_known_blocks_by_peer,
+            produced_blocks: LruMap::new(ByLength::new(max_known_blocks)),
         }
     }
 
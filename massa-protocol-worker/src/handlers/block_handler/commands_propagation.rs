@@ -1,4 +1,5 @@
-use massa_models::block_id::BlockId;
+use massa_models::{block_header::SecuredHeader, block_id::BlockId};
+use massa_signature::Signature;
 use massa_storage::Storage;
 
 /// Commands that the block handler can process
@@ -12,6 +13,39 @@ pub enum BlockHandlerPropagationCommand {
         /// block storage
         storage: Storage,
     },
+    /// Relay a new valid header without its block (header-only relay mode).
+    RelayHeader(SecuredHeader),
+    /// Propagate a header produced locally right away, before its block is integrated.
+    PropagateHeaderOnly(SecuredHeader),
+    /// A block was produced by our node: its header is announced again to the peers that do not acknowledge it.
+    ProducedBlock {
+        /// block id
+        block_id: BlockId,
+        /// signature of the retraction hash of the block by its creator
+        retraction_signature: Signature,
+    },
     /// A block, or it's header, amounted to an attempted attack.
     AttackBlockDetected(BlockId),
 }
+
+impl BlockHandlerPropagationCommand {
+    /// Name of the command in the protocol trace
+    pub fn trace_name(&self) -> &'static str {
+        match self {
+            BlockHandlerPropagationCommand::Stop => "block_propagation.stop",
+            BlockHandlerPropagationCommand::IntegratedBlock { .. } => {
+                "block_propagation.integrated_block"
+            }
+            BlockHandlerPropagationCommand::RelayHeader(_) => "block_propagation.relay_header",
+            BlockHandlerPropagationCommand::PropagateHeaderOnly(_) => {
+                "block_propagation.propagate_header_only"
+            }
+            BlockHandlerPropagationCommand::ProducedBlock { .. } => {
+                "block_propagation.produced_block"
+            }
+            BlockHandlerPropagationCommand::AttackBlockDetected(_) => {
+                "block_propagation.attack_block_detected"
+            }
+        }
+    }
+}
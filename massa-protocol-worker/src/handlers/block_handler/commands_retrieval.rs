@@ -1,8 +1,13 @@
+use std::collections::HashMap;
+
+use massa_channel::sender::MassaSender;
 use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
     prehash::{PreHashMap, PreHashSet},
 };
+use massa_protocol_exports::{AskBlockFromPeerEvent, PeerId, PeerScore};
+use massa_time::MassaTime;
 
 #[derive(Clone)]
 pub enum BlockHandlerRetrievalCommand {
@@ -14,4 +19,35 @@ pub enum BlockHandlerRetrievalCommand {
         /// remove from wish list
         remove: PreHashSet<BlockId>,
     },
+    /// Ask a block to a specific peer
+    AskBlockFromPeer {
+        /// block to retrieve
+        block_id: BlockId,
+        /// header of the block, if known
+        header: Option<SecuredHeader>,
+        /// peer to ask the block to
+        peer_id: PeerId,
+        /// time given to the peer to provide the block
+        timeout: MassaTime,
+        /// receives the outcome of the request
+        responder: MassaSender<AskBlockFromPeerEvent>,
+    },
+    /// Get the score of each connected peer
+    GetPeerScores {
+        responder: MassaSender<HashMap<PeerId, PeerScore>>,
+    },
+}
+
+impl BlockHandlerRetrievalCommand {
+    /// Name of the command in the protocol trace
+    pub fn trace_name(&self) -> &'static str {
+        match self {
+            BlockHandlerRetrievalCommand::Stop => "block_retrieval.stop",
+            BlockHandlerRetrievalCommand::WishlistDelta { .. } => "block_retrieval.wishlist_delta",
+            BlockHandlerRetrievalCommand::AskBlockFromPeer { .. } => {
+                "block_retrieval.ask_block_from_peer"
+            }
+            BlockHandlerRetrievalCommand::GetPeerScores { .. } => "block_retrieval.get_peer_scores",
+        }
+    }
 }
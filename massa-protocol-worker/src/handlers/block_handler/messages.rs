@@ -2,21 +2,27 @@ use massa_models::{
     block_header::{BlockHeader, BlockHeaderDeserializer, SecuredHeader},
     block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer},
     operation::{
-        OperationId, OperationIdSerializer, OperationIdsDeserializer, OperationsDeserializer,
-        SecureShareOperation,
+        OperationId, OperationIdSerializer, OperationIdsDeserializer, OperationPrefixId,
+        OperationPrefixIdDeserializer, OperationsDeserializer, SecureShareOperation,
     },
     secure_share::{SecureShareDeserializer, SecureShareSerializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer},
 };
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
+use massa_signature::{Signature, SignatureDeserializer};
+use massa_time::MassaTime;
 use nom::{
     error::{context, ContextError, ParseError},
+    multi::length_count,
     sequence::tuple,
     IResult, Parser,
 };
 use num_enum::{IntoPrimitive, TryFromPrimitive};
-use std::ops::Bound::Included;
+use std::ops::Bound::{Excluded, Included};
+
+use super::arrival_timings::MAX_ARRIVAL_TIMINGS_PER_MESSAGE;
 
 /// Request block data
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -28,6 +34,9 @@ pub enum AskForBlockInfo {
     OperationIds,
     /// Ask for a subset of operations of the block
     Operations(Vec<OperationId>),
+    /// Ask for the list of operation IDs of the block, along with the operations of the block
+    /// whose ID prefix is not in the given list because the asking node does not have them
+    MissingOperations(Vec<OperationPrefixId>),
 }
 
 /// Reply to a block data request
@@ -40,6 +49,13 @@ pub enum BlockInfoReply {
     OperationIds(Vec<OperationId>),
     /// Requested full operations of the block
     Operations(Vec<SecureShareOperation>),
+    /// List of operation IDs within the block, with the operations the asking node does not have
+    MissingOperations {
+        /// IDs of the operations of the block
+        operation_ids: Vec<OperationId>,
+        /// full operations of the block whose ID prefix was not given in the request
+        operations: Vec<SecureShareOperation>,
+    },
     /// Block not found
     NotFound,
 }
@@ -64,6 +80,27 @@ pub enum BlockMessage {
         /// Block info reply.
         block_info: BlockInfoReply,
     },
+    /// Block header with the prefixes of the IDs of the block operations,
+    /// allowing the receiver to rebuild the block from the operations it already knows.
+    CompactBlock {
+        /// Block header
+        header: SecuredHeader,
+        /// Prefixes of the operation IDs of the block, in block order
+        operation_prefixes: Vec<OperationPrefixId>,
+    },
+    /// Message asking the peer for info on several blocks at once.
+    DataRequestBatch(Vec<(BlockId, AskForBlockInfo)>),
+    /// Recent block arrival timings of the sender: delays between the timestamps of slots
+    /// and the reception of their first header
+    ArrivalTimings(Vec<(Slot, MassaTime)>),
+    /// Retraction of a block announced by its producer before being validated,
+    /// that the producer then found invalid
+    Retraction {
+        /// ID of the retracted block
+        block_id: BlockId,
+        /// signature of the retraction hash of the block by its creator
+        signature: Signature,
+    },
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -72,6 +109,10 @@ pub enum MessageTypeId {
     Header,
     DataRequest,
     DataResponse,
+    CompactBlock,
+    ArrivalTimings,
+    DataRequestBatch,
+    Retraction,
 }
 
 impl From<&BlockMessage> for MessageTypeId {
@@ -80,6 +121,10 @@ impl From<&BlockMessage> for MessageTypeId {
             BlockMessage::Header(_) => MessageTypeId::Header,
             BlockMessage::DataRequest { .. } => MessageTypeId::DataRequest,
             BlockMessage::DataResponse { .. } => MessageTypeId::DataResponse,
+            BlockMessage::CompactBlock { .. } => MessageTypeId::CompactBlock,
+            BlockMessage::ArrivalTimings(_) => MessageTypeId::ArrivalTimings,
+            BlockMessage::DataRequestBatch(_) => MessageTypeId::DataRequestBatch,
+            BlockMessage::Retraction { .. } => MessageTypeId::Retraction,
         }
     }
 }
@@ -91,6 +136,7 @@ pub enum BlockInfoType {
     OperationIds = 1,
     Operations = 2,
     NotFound = 3,
+    MissingOperations = 4,
 }
 
 #[derive(Default, Clone)]
@@ -100,6 +146,8 @@ pub struct BlockMessageSerializer {
     length_serializer: U64VarIntSerializer,
     block_id_serializer: BlockIdSerializer,
     operation_id_serializer: OperationIdSerializer,
+    slot_serializer: SlotSerializer,
+    delay_serializer: U64VarIntSerializer,
 }
 
 impl BlockMessageSerializer {
@@ -110,7 +158,48 @@ impl BlockMessageSerializer {
             length_serializer: U64VarIntSerializer::new(),
             block_id_serializer: BlockIdSerializer::new(),
             operation_id_serializer: OperationIdSerializer::new(),
+            slot_serializer: SlotSerializer::new(),
+            delay_serializer: U64VarIntSerializer::new(),
+        }
+    }
+}
+
+impl BlockMessageSerializer {
+    fn serialize_ask_for_block_info(
+        &self,
+        block_info: &AskForBlockInfo,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        match block_info {
+            AskForBlockInfo::Header => {
+                self.id_serializer
+                    .serialize(&(BlockInfoType::Header as u64), buffer)?;
+            }
+            AskForBlockInfo::OperationIds => {
+                self.id_serializer
+                    .serialize(&(BlockInfoType::OperationIds as u64), buffer)?;
+            }
+            AskForBlockInfo::Operations(operations_ids) => {
+                self.id_serializer
+                    .serialize(&(BlockInfoType::Operations as u64), buffer)?;
+                self.length_serializer
+                    .serialize(&(operations_ids.len() as u64), buffer)?;
+                for operation_id in operations_ids {
+                    self.operation_id_serializer
+                        .serialize(operation_id, buffer)?;
+                }
+            }
+            AskForBlockInfo::MissingOperations(known_prefixes) => {
+                self.id_serializer
+                    .serialize(&(BlockInfoType::MissingOperations as u64), buffer)?;
+                self.length_serializer
+                    .serialize(&(known_prefixes.len() as u64), buffer)?;
+                for operation_prefix in known_prefixes {
+                    buffer.extend(Vec::<u8>::from(operation_prefix));
+                }
+            }
         }
+        Ok(())
     }
 }
 
@@ -135,25 +224,14 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
                 block_info,
             } => {
                 self.block_id_serializer.serialize(block_id, buffer)?;
-                match block_info {
-                    AskForBlockInfo::Header => {
-                        self.id_serializer
-                            .serialize(&(BlockInfoType::Header as u64), buffer)?;
-                    }
-                    AskForBlockInfo::OperationIds => {
-                        self.id_serializer
-                            .serialize(&(BlockInfoType::OperationIds as u64), buffer)?;
-                    }
-                    AskForBlockInfo::Operations(operations_ids) => {
-                        self.id_serializer
-                            .serialize(&(BlockInfoType::Operations as u64), buffer)?;
-                        self.length_serializer
-                            .serialize(&(operations_ids.len() as u64), buffer)?;
-                        for operation_id in operations_ids {
-                            self.operation_id_serializer
-                                .serialize(operation_id, buffer)?;
-                        }
-                    }
+                self.serialize_ask_for_block_info(block_info, buffer)?;
+            }
+            BlockMessage::DataRequestBatch(requests) => {
+                self.length_serializer
+                    .serialize(&(requests.len() as u64), buffer)?;
+                for (block_id, block_info) in requests {
+                    self.block_id_serializer.serialize(block_id, buffer)?;
+                    self.serialize_ask_for_block_info(block_info, buffer)?;
                 }
             }
             BlockMessage::DataResponse {
@@ -186,12 +264,57 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
                             self.secure_share_serializer.serialize(operation, buffer)?;
                         }
                     }
+                    BlockInfoReply::MissingOperations {
+                        operation_ids,
+                        operations,
+                    } => {
+                        self.id_serializer
+                            .serialize(&(BlockInfoType::MissingOperations as u64), buffer)?;
+                        self.length_serializer
+                            .serialize(&(operation_ids.len() as u64), buffer)?;
+                        for operation_id in operation_ids {
+                            self.operation_id_serializer
+                                .serialize(operation_id, buffer)?;
+                        }
+                        self.length_serializer
+                            .serialize(&(operations.len() as u64), buffer)?;
+                        for operation in operations {
+                            self.secure_share_serializer.serialize(operation, buffer)?;
+                        }
+                    }
                     BlockInfoReply::NotFound => {
                         self.id_serializer
                             .serialize(&(BlockInfoType::NotFound as u64), buffer)?;
                     }
                 }
             }
+            BlockMessage::CompactBlock {
+                header,
+                operation_prefixes,
+            } => {
+                self.secure_share_serializer.serialize(header, buffer)?;
+                self.length_serializer
+                    .serialize(&(operation_prefixes.len() as u64), buffer)?;
+                for operation_prefix in operation_prefixes {
+                    buffer.extend(Vec::<u8>::from(operation_prefix));
+                }
+            }
+            BlockMessage::ArrivalTimings(timings) => {
+                self.length_serializer
+                    .serialize(&(timings.len() as u64), buffer)?;
+                for (slot, delay) in timings {
+                    self.slot_serializer.serialize(slot, buffer)?;
+                    self.delay_serializer
+                        .serialize(&delay.to_millis(), buffer)?;
+                }
+            }
+            BlockMessage::Retraction {
+                block_id,
+                signature,
+            } => {
+                self.block_id_serializer.serialize(block_id, buffer)?;
+                buffer.extend(signature.to_bytes());
+            }
         }
         Ok(())
     }
@@ -203,6 +326,13 @@ pub struct BlockMessageDeserializer {
     block_id_deserializer: BlockIdDeserializer,
     operation_ids_deserializer: OperationIdsDeserializer,
     operations_deserializer: OperationsDeserializer,
+    operation_prefixes_length_deserializer: U64VarIntDeserializer,
+    operation_prefix_deserializer: OperationPrefixIdDeserializer,
+    arrival_timings_length_deserializer: U64VarIntDeserializer,
+    block_asks_length_deserializer: U64VarIntDeserializer,
+    slot_deserializer: SlotDeserializer,
+    delay_deserializer: U64VarIntDeserializer,
+    signature_deserializer: SignatureDeserializer,
 }
 
 pub struct BlockMessageDeserializerArgs {
@@ -217,6 +347,7 @@ pub struct BlockMessageDeserializerArgs {
     pub max_op_datastore_value_length: u64,
     pub max_denunciations_in_block_header: u32,
     pub last_start_period: Option<u64>,
+    pub max_block_asks_per_message: u64,
 }
 
 impl BlockMessageDeserializer {
@@ -242,6 +373,61 @@ impl BlockMessageDeserializer {
                 args.max_op_datastore_key_length,
                 args.max_op_datastore_value_length,
             ),
+            operation_prefixes_length_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(args.max_operations_per_block as u64),
+            ),
+            operation_prefix_deserializer: OperationPrefixIdDeserializer::new(),
+            block_asks_length_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(args.max_block_asks_per_message),
+            ),
+            arrival_timings_length_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_ARRIVAL_TIMINGS_PER_MESSAGE),
+            ),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(args.thread_count)),
+            ),
+            delay_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            signature_deserializer: SignatureDeserializer::new(),
+        }
+    }
+}
+
+impl BlockMessageDeserializer {
+    fn deserialize_ask_for_block_info<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], AskForBlockInfo, E> {
+        let (rest, raw_id) = self.id_deserializer.deserialize(buffer)?;
+        let info_type: BlockInfoType = raw_id.try_into().map_err(|_| {
+            nom::Err::Error(ParseError::from_error_kind(
+                buffer,
+                nom::error::ErrorKind::Digit,
+            ))
+        })?;
+        match info_type {
+            BlockInfoType::Header => Ok((rest, AskForBlockInfo::Header)),
+            BlockInfoType::OperationIds => Ok((rest, AskForBlockInfo::OperationIds)),
+            BlockInfoType::Operations => self
+                .operation_ids_deserializer
+                .deserialize(rest)
+                .map(|(rest, operation_ids)| (rest, AskForBlockInfo::Operations(operation_ids))),
+            BlockInfoType::MissingOperations => length_count(
+                |input: &'a [u8]| {
+                    self.operation_prefixes_length_deserializer
+                        .deserialize(input)
+                },
+                |input: &'a [u8]| self.operation_prefix_deserializer.deserialize(input),
+            )
+            .map(AskForBlockInfo::MissingOperations)
+            .parse(rest),
+            BlockInfoType::NotFound => Err(nom::Err::Error(ParseError::from_error_kind(
+                buffer,
+                nom::error::ErrorKind::Digit,
+            ))),
         }
     }
 }
@@ -274,31 +460,7 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                                 .map(|(rest, id)| (rest, id))
                         }),
                         context("Failed infos deserialization", |input| {
-                            let (rest, raw_id) = self.id_deserializer.deserialize(input)?;
-                            let info_type: BlockInfoType = raw_id.try_into().map_err(|_| {
-                                nom::Err::Error(ParseError::from_error_kind(
-                                    buffer,
-                                    nom::error::ErrorKind::Digit,
-                                ))
-                            })?;
-                            match info_type {
-                                BlockInfoType::Header => Ok((rest, AskForBlockInfo::Header)),
-                                BlockInfoType::OperationIds => {
-                                    Ok((rest, AskForBlockInfo::OperationIds))
-                                }
-                                BlockInfoType::Operations => self
-                                    .operation_ids_deserializer
-                                    .deserialize(rest)
-                                    .map(|(rest, operation_ids)| {
-                                        (rest, AskForBlockInfo::Operations(operation_ids))
-                                    }),
-                                BlockInfoType::NotFound => {
-                                    Err(nom::Err::Error(ParseError::from_error_kind(
-                                        buffer,
-                                        nom::error::ErrorKind::Digit,
-                                    )))
-                                }
-                            }
+                            self.deserialize_ask_for_block_info(input)
                         }),
                     )),
                 )
@@ -307,6 +469,24 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                     block_info,
                 })
                 .parse(buffer),
+                MessageTypeId::DataRequestBatch => context(
+                    "Failed BlockDataRequestBatch deserialization",
+                    length_count(
+                        context("Failed length deserialization", |input| {
+                            self.block_asks_length_deserializer.deserialize(input)
+                        }),
+                        tuple((
+                            context("Failed BlockId deserialization", |input| {
+                                self.block_id_deserializer.deserialize(input)
+                            }),
+                            context("Failed infos deserialization", |input| {
+                                self.deserialize_ask_for_block_info(input)
+                            }),
+                        )),
+                    ),
+                )
+                .map(BlockMessage::DataRequestBatch)
+                .parse(buffer),
                 MessageTypeId::DataResponse => context(
                     "Failed BlockDataResponse deserialization",
                     tuple((
@@ -340,6 +520,21 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                                     .map(|(rest, operations)| {
                                         (rest, BlockInfoReply::Operations(operations))
                                     }),
+                                BlockInfoType::MissingOperations => tuple((
+                                    |input: &'a [u8]| {
+                                        self.operation_ids_deserializer.deserialize(input)
+                                    },
+                                    |input: &'a [u8]| {
+                                        self.operations_deserializer.deserialize(input)
+                                    },
+                                ))
+                                .map(|(operation_ids, operations)| {
+                                    BlockInfoReply::MissingOperations {
+                                        operation_ids,
+                                        operations,
+                                    }
+                                })
+                                .parse(rest),
                                 BlockInfoType::NotFound => Ok((rest, BlockInfoReply::NotFound)),
                             }
                         }),
@@ -350,6 +545,67 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                     block_info,
                 })
                 .parse(buffer),
+                MessageTypeId::CompactBlock => context(
+                    "Failed CompactBlock deserialization",
+                    tuple((
+                        context("Failed BlockHeader deserialization", |input| {
+                            self.block_header_deserializer.deserialize(input)
+                        }),
+                        context(
+                            "Failed operation prefixes deserialization",
+                            length_count(
+                                context("Failed length deserialization", |input| {
+                                    self.operation_prefixes_length_deserializer
+                                        .deserialize(input)
+                                }),
+                                context("Failed OperationPrefixId deserialization", |input| {
+                                    self.operation_prefix_deserializer.deserialize(input)
+                                }),
+                            ),
+                        ),
+                    )),
+                )
+                .map(|(header, operation_prefixes)| BlockMessage::CompactBlock {
+                    header,
+                    operation_prefixes,
+                })
+                .parse(buffer),
+                MessageTypeId::ArrivalTimings => context(
+                    "Failed ArrivalTimings deserialization",
+                    length_count(
+                        context("Failed length deserialization", |input| {
+                            self.arrival_timings_length_deserializer.deserialize(input)
+                        }),
+                        tuple((
+                            context("Failed Slot deserialization", |input| {
+                                self.slot_deserializer.deserialize(input)
+                            }),
+                            context("Failed delay deserialization", |input| {
+                                self.delay_deserializer
+                                    .deserialize(input)
+                                    .map(|(rest, delay)| (rest, MassaTime::from_millis(delay)))
+                            }),
+                        )),
+                    ),
+                )
+                .map(BlockMessage::ArrivalTimings)
+                .parse(buffer),
+                MessageTypeId::Retraction => context(
+                    "Failed BlockRetraction deserialization",
+                    tuple((
+                        context("Failed block_id deserialization", |input| {
+                            self.block_id_deserializer.deserialize(input)
+                        }),
+                        context("Failed signature deserialization", |input| {
+                            self.signature_deserializer.deserialize(input)
+                        }),
+                    )),
+                )
+                .map(|(block_id, signature)| BlockMessage::Retraction {
+                    block_id,
+                    signature,
+                })
+                .parse(buffer),
             }
         })
         .parse(buffer)
@@ -9,7 +9,10 @@ use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
 use massa_versioning::versioning::MipStore;
 
-use crate::wrap_network::ActiveConnectionsTrait;
+use crate::{
+    peer_scores::SharedPeerScores, propagation_lanes::SharedPropagationLanes,
+    shutdown::ShutdownDrain, trace::SharedProtocolTracer, wrap_network::ActiveConnectionsTrait,
+};
 
 use self::{
     cache::SharedBlockCache, commands_propagation::BlockHandlerPropagationCommand,
@@ -17,14 +20,19 @@ use self::{
     retrieval::start_retrieval_thread,
 };
 
+mod arrival_timings;
 pub mod cache;
 pub mod commands_propagation;
 pub mod commands_retrieval;
 mod messages;
 mod propagation;
+mod retractions;
 mod retrieval;
 
-pub(crate) use messages::{BlockMessage, BlockMessageSerializer};
+pub(crate) use messages::{
+    BlockMessage, BlockMessageSerializer, MessageTypeId as BlockMessageTypeId,
+};
+pub(crate) use retractions::{BlockRetractions, SharedBlockRetractions};
 
 #[cfg(feature = "testing")]
 pub use messages::{
@@ -38,7 +46,10 @@ use super::{
     operation_handler::{
         cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
     },
-    peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+    peer_handler::{
+        models::{PeerManagementCmd, PeerMessageTuple},
+        SharedPeerQuarantine,
+    },
 };
 
 pub struct BlockHandler {
@@ -66,8 +77,13 @@ impl BlockHandler {
         endorsement_cache: SharedEndorsementCache,
         operation_cache: SharedOperationCache,
         cache: SharedBlockCache,
+        peer_scores: SharedPeerScores,
+        quarantine: SharedPeerQuarantine,
+        propagation_lanes: SharedPropagationLanes,
+        tracer: SharedProtocolTracer,
         storage: Storage,
         mip_store: MipStore,
+        block_retractions: SharedBlockRetractions,
         massa_metrics: MassaMetrics,
     ) -> Self {
         let block_retrieval_thread = start_retrieval_thread(
@@ -85,6 +101,10 @@ impl BlockHandler {
             endorsement_cache,
             operation_cache,
             cache.clone(),
+            peer_scores,
+            quarantine,
+            propagation_lanes.clone(),
+            tracer.clone(),
             storage.clone_without_refs(),
             mip_store,
             massa_metrics,
@@ -95,6 +115,9 @@ impl BlockHandler {
             peer_cmd_sender,
             config,
             cache,
+            propagation_lanes,
+            tracer,
+            block_retractions,
         );
         Self {
             block_retrieval_thread: Some((sender_ext, block_retrieval_thread)),
@@ -102,14 +125,19 @@ impl BlockHandler {
         }
     }
 
-    pub fn stop(&mut self) {
+    /// Stops receiving messages and commands
+    pub fn stop_retrieval(&mut self, drain: &mut ShutdownDrain) {
         if let Some((tx, thread)) = self.block_retrieval_thread.take() {
             let _ = tx.send(BlockHandlerRetrievalCommand::Stop);
-            thread.join().unwrap();
+            drain.join(thread);
         }
+    }
+
+    /// Stops the propagation once the propagation commands queued before are processed
+    pub fn stop_propagation(&mut self, drain: &mut ShutdownDrain) {
         if let Some((tx, thread)) = self.block_propagation_thread.take() {
             let _ = tx.send(BlockHandlerPropagationCommand::Stop);
-            thread.join().unwrap();
+            drain.join(thread);
         }
     }
 }
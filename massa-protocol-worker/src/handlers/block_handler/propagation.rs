@@ -2,30 +2,48 @@
 
 //! This file deals with the announcement of block headers to other nodes
 //! in order to propagate the blocks from our node to other nodes.
-//! It also manages peer banning for invalid blocks detected by consensus.
+//! It also quarantines the peers that sent invalid blocks detected by consensus.
+//! The invalid blocks produced by our node (announced before being validated) are retracted instead:
+//! the peers we announced them to receive a retraction signed by the block creator, if they support it.
 //!
 //! The block propagation system works in the following way:
 //! * a node announces the headers of blocks to its neighbor nodes
+//!   (with the prefixes of the block operation IDs if `compact_block_relay` is enabled)
 //! * the neighbor nodes that need that block then ask our Retrieval process for it
 //!
 //! Here we need to announce block headers to other nodes that haven't sene them,
 //! and keep the blocks alive long enough for our peers to be able to retrieve them from us.
+//!
+//! In header-only relay mode, the node never retrieves the blocks: the new valid headers it receives
+//! are announced to its peers as they are, and the peers retrieve the blocks from other nodes.
+//!
+//! The headers just signed by the node can also be announced before their block is integrated.
+//! The peers that received such a header are not announced the block again once it is integrated.
+//!
+//! The headers of the blocks produced by our node are announced again to the connected peers
+//! that did not acknowledge them (by announcing them back or asking for the block)
+//! within `produced_block_reannounce_timeout`, at most `max_produced_block_reannounces` times per peer,
+//! so that they reach the whole neighborhood even if an announcement was dropped.
 
 use super::{
     cache::SharedBlockCache, commands_propagation::BlockHandlerPropagationCommand,
-    BlockMessageSerializer,
+    BlockMessageSerializer, SharedBlockRetractions,
 };
 use crate::{
     handlers::{block_handler::BlockMessage, peer_handler::models::PeerManagementCmd},
     messages::MessagesSerializer,
+    propagation_lanes::{PropagationLane, SharedPropagationLanes},
+    trace::SharedProtocolTracer,
     wrap_network::ActiveConnectionsTrait,
 };
 use crossbeam::channel::RecvTimeoutError;
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
 use massa_models::block_header::SecuredHeader;
 use massa_models::block_id::BlockId;
+use massa_models::operation::OperationPrefixId;
 use massa_protocol_exports::PeerId;
 use massa_protocol_exports::{ProtocolConfig, ProtocolError};
+use massa_signature::Signature;
 use massa_storage::Storage;
 use schnellru::{ByLength, LruMap};
 use std::thread::JoinHandle;
@@ -37,9 +55,13 @@ struct BlockPropagationData {
     /// Time when propagation was initiated
     pub time_added: Instant,
     /// Storage holding the block and its dependencies during its propagation time
-    pub _storage: Storage,
+    /// (None for the headers relayed without their block)
+    pub _storage: Option<Storage>,
     /// Clone of the block header to avoid locking storage during propagation
     pub header: SecuredHeader,
+    /// Prefixes of the block operation IDs, in block order
+    /// (None for the headers relayed without their block)
+    pub operation_prefixes: Option<Vec<OperationPrefixId>>,
 }
 
 pub struct PropagationThread {
@@ -53,10 +75,16 @@ pub struct PropagationThread {
     stored_for_propagation: LruMap<BlockId, BlockPropagationData>,
     /// Shared access to the list of peers connected to us
     active_connections: Box<dyn ActiveConnectionsTrait>,
-    /// Channel to send commands to the peer management system (for banning peers)
+    /// Channel to send commands to the peer management system (for quarantining peers)
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     /// Serializer for block-related messages
     block_serializer: MessagesSerializer,
+    /// Pending propagations of each lane, to let the other lanes know when blocks are propagated
+    propagation_lanes: SharedPropagationLanes,
+    /// Traces the commands taken from the channel
+    tracer: SharedProtocolTracer,
+    /// Peers that can be sent block retractions
+    block_retractions: SharedBlockRetractions,
 }
 
 impl PropagationThread {
@@ -68,26 +96,35 @@ impl PropagationThread {
         loop {
             match self.receiver.recv_deadline(deadline) {
                 Ok(command) => {
+                    self.tracer.dequeue(command.trace_name());
                     match command {
                         // Message: the block was integrated and should be propagated
                         BlockHandlerPropagationCommand::IntegratedBlock { block_id, storage } => {
                             debug!("received IntegratedBlock({})", block_id);
 
-                            // get the block header
-                            let header = match storage
-                                .read_blocks()
-                                .get(&block_id)
-                                .map(|block| block.content.header.clone())
-                            {
-                                Some(h) => h,
-                                None => {
-                                    warn!(
-                                        "claimed block {} absent from storage on propagation",
-                                        block_id
-                                    );
-                                    continue;
-                                }
-                            };
+                            // get the block header and operation prefixes
+                            let (header, operation_prefixes) =
+                                match storage.read_blocks().get(&block_id).map(|block| {
+                                    (
+                                        block.content.header.clone(),
+                                        block
+                                            .content
+                                            .operations
+                                            .iter()
+                                            .map(|op_id| op_id.prefix())
+                                            .collect(),
+                                    )
+                                }) {
+                                    Some(data) => data,
+                                    None => {
+                                        warn!(
+                                            "claimed block {} absent from storage on propagation",
+                                            block_id
+                                        );
+                                        self.propagation_lanes.done(PropagationLane::Blocks);
+                                        continue;
+                                    }
+                                };
 
                             // Add the block and its dependencies to the propagation LRU
                             // to ensure they are stored for the time of the propagation.
@@ -95,22 +132,59 @@ impl PropagationThread {
                                 block_id,
                                 BlockPropagationData {
                                     time_added: Instant::now(),
-                                    _storage: storage,
+                                    _storage: Some(storage),
                                     header,
+                                    operation_prefixes: Some(operation_prefixes),
                                 },
                             );
 
                             // propagate everything that needs to be propagated
                             self.perform_propagations();
+                            self.propagation_lanes.done(PropagationLane::Blocks);
 
                             // renew tick because propagation propagations were updated
                             deadline = Instant::now()
                                 .checked_add(tick_interval)
                                 .expect("could not get time of next propagation tick");
                         }
+                        // Message: a new valid header was received in header-only relay mode,
+                        // or a header was just produced and should be propagated before its block
+                        BlockHandlerPropagationCommand::RelayHeader(header)
+                        | BlockHandlerPropagationCommand::PropagateHeaderOnly(header) => {
+                            debug!(
+                                "received header {} to propagate without its block",
+                                header.id
+                            );
+                            self.stored_for_propagation.insert(
+                                header.id,
+                                BlockPropagationData {
+                                    time_added: Instant::now(),
+                                    _storage: None,
+                                    header,
+                                    operation_prefixes: None,
+                                },
+                            );
+                            self.perform_propagations();
+                            self.propagation_lanes.done(PropagationLane::Blocks);
+                            deadline = Instant::now()
+                                .checked_add(tick_interval)
+                                .expect("could not get time of next propagation tick");
+                        }
+                        BlockHandlerPropagationCommand::ProducedBlock {
+                            block_id,
+                            retraction_signature,
+                        } => {
+                            debug!("received ProducedBlock({})", block_id);
+                            self.cache
+                                .write()
+                                .insert_produced_block(block_id, retraction_signature);
+                        }
                         BlockHandlerPropagationCommand::AttackBlockDetected(block_id) => {
                             debug!("received AttackBlockDetected({})", block_id);
-                            let peers_to_ban: Vec<PeerId> = self
+                            // stop propagating the block, in case it was announced before being validated
+                            self.stored_for_propagation.remove(&block_id);
+                            let produced = self.cache.write().produced_blocks.remove(&block_id);
+                            let peers_knowing_block: Vec<PeerId> = self
                                 .cache
                                 .read()
                                 .blocks_known_by_peer
@@ -122,7 +196,14 @@ impl PropagationThread {
                                     }
                                 })
                                 .collect();
-                            self.ban_peers(&peers_to_ban);
+                            if let Some(produced) = produced {
+                                // the peers got our own block from us: retract it instead of punishing them
+                                if let Some(signature) = produced.retraction_signature {
+                                    self.retract_block(&block_id, signature, &peers_knowing_block);
+                                }
+                            } else {
+                                self.quarantine_peers(&peers_knowing_block);
+                            }
                         }
                         BlockHandlerPropagationCommand::Stop => {
                             info!("Stop block propagation thread");
@@ -167,28 +248,63 @@ impl PropagationThread {
 
         // update caches based on currently connected peers
         let peers_connected = self.active_connections.get_peer_ids_connected();
+        let reannounce_timeout = self.config.produced_block_reannounce_timeout.to_duration();
         let mut cache_lock = self.cache.write();
         cache_lock.update_cache(&peers_connected);
-        'peer_loop: for (peer_id, known_by_peer) in cache_lock.blocks_known_by_peer.iter_mut() {
-            for (block_id, BlockPropagationData { header, .. }) in
-                self.stored_for_propagation.iter()
+        let cache = &mut *cache_lock;
+        'peer_loop: for (peer_id, known_by_peer) in cache.blocks_known_by_peer.iter_mut() {
+            for (
+                block_id,
+                BlockPropagationData {
+                    header,
+                    operation_prefixes,
+                    ..
+                },
+            ) in self.stored_for_propagation.iter()
             {
-                // if the peer already knows about the block, do not propagate it
-                if let Some((true, _)) = known_by_peer.peek(block_id) {
-                    continue;
+                // if the peer already knows about the block, do not propagate it,
+                // unless we produced it and the peer did not acknowledge it in time
+                let mut reannounced = None;
+                if let Some((true, announced_at)) = known_by_peer.peek(block_id) {
+                    match cache.produced_blocks.peek_mut(block_id) {
+                        Some(acks)
+                            if !acks.acknowledged_by.contains(peer_id)
+                                && now.saturating_duration_since(*announced_at)
+                                    >= reannounce_timeout =>
+                        {
+                            let count = acks.reannounces.entry(peer_id.clone()).or_default();
+                            if *count >= self.config.max_produced_block_reannounces {
+                                continue;
+                            }
+                            reannounced = Some(count);
+                        }
+                        _ => continue,
+                    }
                 }
 
                 // try to propagate
                 debug!("announcing header {} to peer {}", block_id, peer_id);
+                let message = match operation_prefixes {
+                    Some(operation_prefixes) if self.config.compact_block_relay => {
+                        BlockMessage::CompactBlock {
+                            header: header.clone(),
+                            operation_prefixes: operation_prefixes.clone(),
+                        }
+                    }
+                    _ => BlockMessage::Header(header.clone()),
+                };
                 match self.active_connections.send_to_peer(
                     peer_id,
                     &self.block_serializer,
-                    BlockMessage::Header(header.clone()).into(),
+                    message.into(),
                     true,
                 ) {
                     Ok(()) => {
                         // mark the block as known by the peer
                         known_by_peer.insert(*block_id, (true, now));
+                        if let Some(count) = reannounced {
+                            *count += 1;
+                        }
                     }
                     Err(err) => {
                         warn!(
@@ -202,14 +318,40 @@ impl PropagationThread {
         }
     }
 
-    /// try to ban a list of peers
-    fn ban_peers(&mut self, peer_ids: &[PeerId]) {
+    /// Tells the peers that were announced a block produced by our node that the block is invalid.
+    /// The peers that did not announce their support of retractions in the handshake are skipped.
+    fn retract_block(&self, block_id: &BlockId, signature: Signature, peer_ids: &[PeerId]) {
+        for peer_id in peer_ids {
+            if !self.block_retractions.is_supported(peer_id) {
+                continue;
+            }
+            debug!("retracting block {} to peer {}", block_id, peer_id);
+            if let Err(err) = self.active_connections.send_to_peer(
+                peer_id,
+                &self.block_serializer,
+                BlockMessage::Retraction {
+                    block_id: *block_id,
+                    signature,
+                }
+                .into(),
+                true,
+            ) {
+                warn!(
+                    "Error while retracting block {} to peer {} err: {:?}",
+                    block_id, peer_id, err
+                );
+            }
+        }
+    }
+
+    /// try to quarantine a list of peers, the ones already in quarantine get banned
+    fn quarantine_peers(&mut self, peer_ids: &[PeerId]) {
         if let Err(err) = self
             .peer_cmd_sender
-            .try_send(PeerManagementCmd::Ban(peer_ids.to_vec()))
+            .try_send(PeerManagementCmd::Quarantine(peer_ids.to_vec()))
             .map_err(|err| ProtocolError::SendError(err.to_string()))
         {
-            warn!("could not send Ban command to peer manager: {}", err);
+            warn!("could not send Quarantine command to peer manager: {}", err);
         }
     }
 }
@@ -220,6 +362,9 @@ pub fn start_propagation_thread(
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     config: ProtocolConfig,
     cache: SharedBlockCache,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
+    block_retractions: SharedBlockRetractions,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
         .name("protocol-block-handler-propagation".to_string())
@@ -239,6 +384,9 @@ pub fn start_propagation_thread(
                 peer_cmd_sender,
                 active_connections,
                 block_serializer,
+                propagation_lanes,
+                tracer,
+                block_retractions,
             };
             propagation_thread.run();
         })
@@ -0,0 +1,84 @@
+//! Support of block retractions by the peers.
+//!
+//! A node announcing the blocks it produces before validating them retracts the ones consensus then finds invalid.
+//! The retraction message is only known by the nodes announcing it with a flag of the handshake capabilities byte:
+//! the other peers are not sent any retraction, as they could not decode it.
+
+use std::{collections::HashSet, sync::Arc};
+
+use massa_protocol_exports::PeerId;
+use parking_lot::RwLock;
+
+/// Flag of the handshake capabilities byte telling that the node decodes block retractions
+pub(crate) const HANDSHAKE_FLAG_BLOCK_RETRACTION: u8 = 0b100;
+
+/// Peers that announced their support of block retractions in the handshake
+#[derive(Default)]
+pub struct BlockRetractions {
+    peers: RwLock<HashSet<PeerId>>,
+}
+
+impl BlockRetractions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flags of the handshake capabilities byte
+    pub fn handshake_flags(&self) -> u8 {
+        HANDSHAKE_FLAG_BLOCK_RETRACTION
+    }
+
+    /// Records the support of block retractions announced by a peer in the handshake.
+    /// `capabilities` are the bytes following the announcement, empty for the nodes not knowing them.
+    pub fn on_handshake(&self, peer_id: &PeerId, capabilities: &[u8]) {
+        let supported = capabilities
+            .first()
+            .map_or(false, |flags| flags & HANDSHAKE_FLAG_BLOCK_RETRACTION != 0);
+        if supported {
+            self.peers.write().insert(peer_id.clone());
+        } else {
+            self.peers.write().remove(peer_id);
+        }
+    }
+
+    /// Forgets the peers that are not connected anymore
+    pub fn retain_peers(&self, connected: &HashSet<PeerId>) {
+        self.peers
+            .write()
+            .retain(|peer_id| connected.contains(peer_id));
+    }
+
+    /// Whether a peer can be sent block retractions
+    pub fn is_supported(&self, peer_id: &PeerId) -> bool {
+        self.peers.read().contains(peer_id)
+    }
+}
+
+pub type SharedBlockRetractions = Arc<BlockRetractions>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_block_retractions() {
+        let retractions = BlockRetractions::new();
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+
+        // a peer not knowing the capabilities does not support retractions
+        retractions.on_handshake(&peer_id, &[]);
+        assert!(!retractions.is_supported(&peer_id));
+
+        // the flag is found among the other ones
+        retractions.on_handshake(&peer_id, &[0b1 | retractions.handshake_flags()]);
+        assert!(retractions.is_supported(&peer_id));
+        retractions.on_handshake(&peer_id, &[0b1]);
+        assert!(!retractions.is_supported(&peer_id));
+
+        // disconnected peers are forgotten
+        retractions.on_handshake(&peer_id, &[HANDSHAKE_FLAG_BLOCK_RETRACTION]);
+        retractions.retain_peers(&HashSet::new());
+        assert!(!retractions.is_supported(&peer_id));
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     thread::JoinHandle,
     time::Instant,
 };
@@ -14,9 +14,15 @@ use crate::{
         operation_handler::{
             cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
         },
-        peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+        peer_handler::{
+            models::{PeerManagementCmd, PeerMessageTuple},
+            SharedPeerQuarantine,
+        },
     },
     messages::{Message, MessagesSerializer},
+    peer_scores::SharedPeerScores,
+    propagation_lanes::{PropagationLane, SharedPropagationLanes},
+    trace::SharedProtocolTracer,
     wrap_network::ActiveConnectionsTrait,
 };
 use crossbeam::{
@@ -25,7 +31,6 @@ use crossbeam::{
 };
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
 use massa_consensus_exports::ConsensusController;
-use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::{
     block::{Block, BlockSerializer},
@@ -33,7 +38,8 @@ use massa_models::{
     block_id::BlockId,
     endorsement::EndorsementId,
     operation::{
-        compute_operations_hash, OperationId, OperationIdSerializer, SecureShareOperation,
+        compute_operations_hash, OperationId, OperationIdSerializer, OperationPrefixId,
+        SecureShareOperation,
     },
     prehash::{PreHashMap, PreHashSet},
     secure_share::SecureShare,
@@ -41,18 +47,21 @@ use massa_models::{
 };
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
-use massa_protocol_exports::PeerId;
+use massa_protocol_exports::{AskBlockFromPeerEvent, PeerId};
 use massa_protocol_exports::{ProtocolConfig, ProtocolError};
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_signature::Signature;
 use massa_storage::Storage;
-use massa_time::TimeError;
+use massa_time::{MassaTime, TimeError};
 use massa_versioning::versioning::MipStore;
 use rand::thread_rng;
 use rand::{seq::SliceRandom, Rng};
+use schnellru::{ByLength, LruMap};
 use tracing::{debug, info, warn};
 
 use super::{
     super::operation_handler::note_operations_from_peer,
+    arrival_timings::BlockArrivalTimings,
     cache::SharedBlockCache,
     commands_propagation::BlockHandlerPropagationCommand,
     commands_retrieval::BlockHandlerRetrievalCommand,
@@ -70,6 +79,10 @@ pub(crate) struct BlockInfo {
     pub(crate) header: Option<SecuredHeader>,
     /// Operations ids. None if not received yet
     pub(crate) operation_ids: Option<Vec<OperationId>>,
+    /// Prefixes of the block operations we already have, known from a compact block.
+    /// If not empty, they are sent along with the request for the operation IDs
+    /// so that the peer also sends the operations we miss.
+    pub(crate) known_operation_prefixes: Vec<OperationPrefixId>,
     /// Operations and endorsements contained in the block,
     /// if we've received them already, and none otherwise.
     pub(crate) storage: Storage,
@@ -80,29 +93,54 @@ impl BlockInfo {
         BlockInfo {
             header,
             operation_ids: None,
+            known_operation_prefixes: Vec::new(),
             storage,
         }
     }
 }
 
+/// Request to retrieve a block from a specific peer
+struct PeerAsk {
+    /// peer to ask the block to
+    peer_id: PeerId,
+    /// time after which the block is asked to any peer
+    deadline: Instant,
+    /// receives the outcome of the request
+    responder: MassaSender<AskBlockFromPeerEvent>,
+}
+
+/// Block data request, with the peers to send it to from the best to the worst
+type BlockAsk = (BlockId, AskForBlockInfo, VecDeque<PeerId>);
+
 pub struct RetrievalThread {
     active_connections: Box<dyn ActiveConnectionsTrait>,
     selector_controller: Box<dyn SelectorController>,
     consensus_controller: Box<dyn ConsensusController>,
     pool_controller: Box<dyn PoolController>,
     receiver_network: MassaReceiver<PeerMessageTuple>,
-    _announcement_sender: MassaSender<BlockHandlerPropagationCommand>,
+    announcement_sender: MassaSender<BlockHandlerPropagationCommand>,
     receiver: MassaReceiver<BlockHandlerRetrievalCommand>,
     block_message_serializer: MessagesSerializer,
     block_wishlist: PreHashMap<BlockId, BlockInfo>,
     asked_blocks: HashMap<PeerId, PreHashMap<BlockId, Instant>>,
+    /// blocks of the wish list asked to a specific peer
+    peer_asks: PreHashMap<BlockId, PeerAsk>,
+    /// operation prefixes of the compact blocks received before consensus wanted them
+    compact_blocks: LruMap<BlockId, Vec<OperationPrefixId>>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     sender_propagation_ops: MassaSender<OperationHandlerPropagationCommand>,
     sender_propagation_endorsements: MassaSender<EndorsementHandlerPropagationCommand>,
     endorsement_cache: SharedEndorsementCache,
     operation_cache: SharedOperationCache,
     next_timer_ask_block: Instant,
+    /// recent block arrival timings of the node and of its peers
+    arrival_timings: BlockArrivalTimings,
     cache: SharedBlockCache,
+    peer_scores: SharedPeerScores,
+    /// peers in quarantine, whose headers, operations and endorsements are not propagated
+    quarantine: SharedPeerQuarantine,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
     config: ProtocolConfig,
     storage: Storage,
     mip_store: MipStore,
@@ -125,9 +163,11 @@ impl RetrievalThread {
                 max_op_datastore_value_length: self.config.max_op_datastore_value_length,
                 max_denunciations_in_block_header: self.config.max_denunciations_in_block_header,
                 last_start_period: Some(self.config.last_start_period),
+                max_block_asks_per_message: self.config.max_simultaneous_ask_blocks_per_node as u64,
             });
 
         let tick_update_metrics = tick(self.massa_metrics.tick_delay);
+        let tick_arrival_timings = tick(self.config.t0.to_duration());
         loop {
             select! {
                 recv(self.receiver_network) -> msg => {
@@ -139,6 +179,7 @@ impl RetrievalThread {
                                 Ok((rest, message)) => (rest, message),
                                 Err(err) => {
                                     warn!("Error in deserializing block message: {:?}", err);
+                                    self.peer_scores.write().record_message(&peer_id, false);
                                     continue;
                                 }
                             };
@@ -150,6 +191,11 @@ impl RetrievalThread {
                                 BlockMessage::DataRequest{block_id, block_info} => {
                                     self.on_ask_for_block_info_received(peer_id.clone(), block_id, block_info);
                                 }
+                                BlockMessage::DataRequestBatch(requests) => {
+                                    for (block_id, block_info) in requests {
+                                        self.on_ask_for_block_info_received(peer_id.clone(), block_id, block_info);
+                                    }
+                                }
                                 BlockMessage::DataResponse{block_id, block_info} => {
                                    self.on_block_info_received(peer_id.clone(), block_id, block_info);
                                    self.update_block_retrieval();
@@ -158,6 +204,19 @@ impl RetrievalThread {
                                     self.on_block_header_received(peer_id.clone(), header);
                                     self.update_block_retrieval();
                                 }
+                                BlockMessage::CompactBlock { header, operation_prefixes } => {
+                                    self.on_compact_block_received(peer_id.clone(), header, operation_prefixes);
+                                    self.update_block_retrieval();
+                                }
+                                BlockMessage::ArrivalTimings(timings) => {
+                                    if self.config.block_arrival_gossip {
+                                        self.arrival_timings.record_remote(&peer_id, &timings);
+                                    }
+                                }
+                                BlockMessage::Retraction { block_id, signature } => {
+                                    self.on_block_retraction_received(&peer_id, &block_id, &signature);
+                                    self.update_block_retrieval();
+                                }
                             }
                         },
                         Err(_) => {
@@ -170,14 +229,18 @@ impl RetrievalThread {
                     self.receiver.update_metrics();
                     match msg {
                         Ok(command) => {
+                            self.tracer.dequeue(command.trace_name());
                             match command {
                                 BlockHandlerRetrievalCommand::WishlistDelta { new, remove } => {
-                                    massa_trace!("protocol.protocol_worker.process_command.wishlist_delta.begin", { "new": new, "remove": remove });
                                     for (block_id, header) in new.into_iter() {
                                         self.block_wishlist.insert(
                                             block_id,
                                             BlockInfo::new(header, self.storage.clone_without_refs()),
                                         );
+                                        // rebuild the block if we already received it as a compact block
+                                        if let Some(operation_prefixes) = self.compact_blocks.remove(&block_id) {
+                                            self.reconstruct_compact_block(&block_id, &operation_prefixes);
+                                        }
                                     }
                                     // Cleanup the knowledge that we asked this list of blocks to nodes.
                                     self.remove_asked_blocks(&remove);
@@ -185,11 +248,22 @@ impl RetrievalThread {
                                     // Remove from the wishlist.
                                     for block_id in remove.iter() {
                                         self.block_wishlist.remove(block_id);
+                                        self.compact_blocks.remove(block_id);
+                                        self.end_peer_ask(block_id, AskBlockFromPeerEvent::Abandoned);
                                     }
 
                                     // update block asking process
                                     self.update_block_retrieval();
                                 },
+                                BlockHandlerRetrievalCommand::AskBlockFromPeer { block_id, header, peer_id, timeout, responder } => {
+                                    massa_trace!("protocol.protocol_worker.process_command.ask_block_from_peer.begin", { "block_id": block_id, "peer_id": peer_id.to_string() });
+                                    self.on_ask_block_from_peer(block_id, header, peer_id, timeout, responder);
+                                },
+                                BlockHandlerRetrievalCommand::GetPeerScores { responder } => {
+                                    if let Err(err) = responder.try_send(self.peer_scores.read().get_all()) {
+                                        warn!("error sending peer scores: {:?}", err);
+                                    }
+                                },
                                 BlockHandlerRetrievalCommand::Stop => {
                                     info!("Stop block retrieval thread from command receiver (Stop)");
                                     return;
@@ -228,6 +302,11 @@ impl RetrievalThread {
                         );
                     }
                 }
+                recv(tick_arrival_timings) -> _ => {
+                    if self.config.block_arrival_gossip {
+                        self.gossip_arrival_timings();
+                    }
+                }
                 recv(at(self.next_timer_ask_block)) -> _ => {
                     self.update_block_retrieval();
                 }
@@ -239,6 +318,8 @@ impl RetrievalThread {
     ///
     /// We send the block's operation ids if the foreign node asked for `AskForBlockInfo::Info`
     /// or a subset of the full operations of the block if it asked for `AskForBlockInfo::Operations`.
+    /// If it asked for `AskForBlockInfo::MissingOperations`, we send both the operation ids
+    /// and the operations whose prefix the foreign node did not list.
     fn on_ask_for_block_info_received(
         &mut self,
         from_peer_id: PeerId,
@@ -249,6 +330,9 @@ impl RetrievalThread {
             "peer {} asked for block info on block {}: {:?}",
             &from_peer_id, block_id, &info_requested
         );
+        self.cache
+            .write()
+            .acknowledge_produced_block(&from_peer_id, &block_id);
 
         // updates on the remote peer's knowledge on blocks, operations and endorsements
         // only applied if the response is successfully sent to the peer
@@ -265,6 +349,19 @@ impl RetrievalThread {
         });
 
         let block_info_response = match (stored_header_op_ids, info_requested) {
+            // in header-only relay mode the blocks are never retrieved:
+            // only the relayed headers can be sent, the other requests are refused
+            (_, AskForBlockInfo::Header) if self.config.header_only_relay => {
+                match self.cache.read().checked_headers.peek(&block_id) {
+                    Some(header) => {
+                        block_knowledge_updates.insert(block_id);
+                        BlockInfoReply::Header(header.clone())
+                    }
+                    None => BlockInfoReply::NotFound,
+                }
+            }
+            (_, _) if self.config.header_only_relay => BlockInfoReply::NotFound,
+
             (None, _) => BlockInfoReply::NotFound,
 
             (Some((header, _)), AskForBlockInfo::Header) => {
@@ -327,6 +424,32 @@ impl RetrievalThread {
 
                 BlockInfoReply::Operations(returned_ops)
             }
+            (Some((_, block_op_ids)), AskForBlockInfo::MissingOperations(known_prefixes)) => {
+                // the peer asked for the operation IDs of the block and the operations it does not have
+                let known_prefixes: PreHashSet<OperationPrefixId> =
+                    known_prefixes.into_iter().collect();
+                let returned_ops: Vec<_> = {
+                    let op_storage_lock = self.storage.read_operations();
+                    block_op_ids
+                        .iter()
+                        .copied()
+                        .collect::<PreHashSet<OperationId>>()
+                        .into_iter()
+                        .filter(|id| !known_prefixes.contains(&id.prefix()))
+                        .filter_map(|id| op_storage_lock.get(&id))
+                        .cloned()
+                        .collect()
+                };
+
+                // once sent, the peer will know about those operations,
+                // no need to announce their IDs to that peer anymore
+                operation_knowledge_updates.extend(block_op_ids.iter().cloned());
+
+                BlockInfoReply::MissingOperations {
+                    operation_ids: block_op_ids,
+                    operations: returned_ops,
+                }
+            }
         };
 
         debug!(
@@ -389,6 +512,23 @@ impl RetrievalThread {
         block_id: BlockId,
         block_info: BlockInfoReply,
     ) {
+        // update the score of the peer: answers to our requests are useful, the others are not.
+        // The usefulness of headers is checked when noting them.
+        let ask_time = self
+            .asked_blocks
+            .get(&from_peer_id)
+            .and_then(|asked_blocks| asked_blocks.get(&block_id))
+            .copied();
+        {
+            let mut peer_scores = self.peer_scores.write();
+            if let Some(ask_time) = ask_time {
+                peer_scores.record_ask_block_response(&from_peer_id, ask_time.elapsed());
+            }
+            if !matches!(block_info, BlockInfoReply::Header(_)) {
+                peer_scores.record_message(&from_peer_id, ask_time.is_some());
+            }
+        }
+
         match block_info {
             BlockInfoReply::Header(header) => {
                 // Verify and send it consensus
@@ -407,6 +547,20 @@ impl RetrievalThread {
                 // and wait for them to have been procesed(i.e. added to storage).
                 self.on_block_full_operations_received(from_peer_id, block_id, operations);
             }
+            BlockInfoReply::MissingOperations {
+                operation_ids,
+                operations,
+            } => {
+                // The operations are only accepted if the operation list matches the header.
+                self.on_block_operation_list_received(
+                    from_peer_id.clone(),
+                    block_id,
+                    operation_ids,
+                );
+                if !operations.is_empty() {
+                    self.on_block_full_operations_received(from_peer_id, block_id, operations);
+                }
+            }
             BlockInfoReply::NotFound => {
                 // The peer doesn't know about the block. Mark it as such.
                 self.cache
@@ -421,6 +575,9 @@ impl RetrievalThread {
         debug!("received header {} from {}", header.id, from_peer_id);
 
         let block_id = header.id;
+        self.cache
+            .write()
+            .acknowledge_produced_block(&from_peer_id, &block_id);
 
         // Check header and update knowledge info
         let is_new = match self.note_header_from_peer(&header, &from_peer_id) {
@@ -430,13 +587,37 @@ impl RetrievalThread {
                     "peer {} sent us critically incorrect header: {}",
                     &from_peer_id, err
                 );
-                if let Err(err) = self.ban_peers(&[from_peer_id.clone()]) {
-                    warn!("Error while banning peer {} err: {:?}", &from_peer_id, err);
+                self.peer_scores.write().record_header(&from_peer_id, false);
+                if let Err(err) = self.quarantine_peers(&[from_peer_id.clone()]) {
+                    warn!(
+                        "Error while quarantining peer {} err: {:?}",
+                        &from_peer_id, err
+                    );
                 }
                 return;
             }
         };
 
+        if is_new && self.config.block_arrival_gossip {
+            self.record_header_arrival(&header);
+        }
+
+        if is_new && self.config.header_only_relay && !self.quarantine.is_quarantined(&from_peer_id)
+        {
+            self.relay_header(header.clone());
+        }
+
+        // a header is useful if it is new or if we were looking for it
+        {
+            let wanted = self
+                .block_wishlist
+                .get(&block_id)
+                .map_or(false, |info| info.header.is_none());
+            let mut peer_scores = self.peer_scores.write();
+            peer_scores.record_header(&from_peer_id, true);
+            peer_scores.record_message(&from_peer_id, is_new || wanted);
+        }
+
         if let Some(info) = self.block_wishlist.get_mut(&block_id) {
             // We are actively trying to get this block
 
@@ -459,6 +640,208 @@ impl RetrievalThread {
         }
     }
 
+    /// Hands a new header over to the propagation thread, in header-only relay mode
+    fn relay_header(&self, header: SecuredHeader) {
+        let block_id = header.id;
+        let command = BlockHandlerPropagationCommand::RelayHeader(header);
+        let trace_name = command.trace_name();
+        let sequence = self.tracer.enqueue(trace_name);
+        self.propagation_lanes.enqueue(PropagationLane::Blocks);
+        if let Err(err) = self.announcement_sender.try_send(command) {
+            self.tracer.dropped(trace_name, sequence);
+            self.propagation_lanes.done(PropagationLane::Blocks);
+            warn!("could not relay header {}: {}", block_id, err);
+        }
+    }
+
+    /// Records the delay between the timestamp of the slot of a new header and its reception
+    fn record_header_arrival(&mut self, header: &SecuredHeader) {
+        let slot = header.content.slot;
+        let (Ok(now), Ok(slot_timestamp)) = (
+            MassaTime::now(),
+            get_block_slot_timestamp(
+                self.config.thread_count,
+                self.config.t0,
+                self.config.genesis_timestamp,
+                slot,
+            ),
+        ) else {
+            return;
+        };
+        if now >= slot_timestamp {
+            self.arrival_timings
+                .record_local(slot, now.saturating_sub(slot_timestamp));
+        }
+    }
+
+    /// Sends the block arrival timings measured since the last gossip to all the connected peers
+    fn gossip_arrival_timings(&mut self) {
+        let timings = self.arrival_timings.take_pending();
+        if timings.is_empty() {
+            return;
+        }
+        for peer_id in self.active_connections.get_peer_ids_connected() {
+            if let Err(err) = self.active_connections.send_to_peer(
+                &peer_id,
+                &self.block_message_serializer,
+                BlockMessage::ArrivalTimings(timings.clone()).into(),
+                false,
+            ) {
+                debug!(
+                    "Failed to send block arrival timings to peer {}: {:?}",
+                    peer_id, err
+                );
+            }
+        }
+    }
+
+    /// On block retraction received from a node.
+    ///
+    /// The retraction is only accepted if it is signed by the creator of the block,
+    /// whose header must be known. The node does not vouch for the block anymore:
+    /// it is not asked the block, and it is not punished if the block turns out to be an attack.
+    fn on_block_retraction_received(
+        &mut self,
+        from_peer_id: &PeerId,
+        block_id: &BlockId,
+        signature: &Signature,
+    ) {
+        let Some(creator_public_key) = self
+            .cache
+            .read()
+            .checked_headers
+            .peek(block_id)
+            .map(|header| header.content_creator_pub_key)
+        else {
+            debug!(
+                "peer {} retracted block {} whose header is unknown, ignoring",
+                from_peer_id, block_id
+            );
+            return;
+        };
+        if let Err(err) =
+            creator_public_key.verify_signature(&block_id.get_retraction_hash(), signature)
+        {
+            debug!(
+                "peer {} sent a retraction of block {} not signed by its creator, ignoring: {}",
+                from_peer_id, block_id, err
+            );
+            return;
+        }
+        debug!(
+            "peer {} retracted the announcement of block {}",
+            from_peer_id, block_id
+        );
+        self.cache
+            .write()
+            .insert_peer_known_block(from_peer_id, &[*block_id], false);
+        if let Some(asked_blocks) = self.asked_blocks.get_mut(from_peer_id) {
+            asked_blocks.remove(block_id);
+        }
+    }
+
+    /// On compact block received from a node.
+    ///
+    /// The header is processed like an announced header. If we want the block,
+    /// its operation list is rebuilt from the operation prefixes so that only the missing operations are asked.
+    /// Otherwise the prefixes are kept until consensus wants the block.
+    fn on_compact_block_received(
+        &mut self,
+        from_peer_id: PeerId,
+        header: SecuredHeader,
+        operation_prefixes: Vec<OperationPrefixId>,
+    ) {
+        debug!(
+            "received compact block {} with {} operations from {}",
+            header.id,
+            operation_prefixes.len(),
+            from_peer_id
+        );
+        let block_id = header.id;
+        self.on_block_header_received(from_peer_id.clone(), header);
+        if self.cache.read().checked_headers.peek(&block_id).is_none() {
+            // the header was invalid
+            return;
+        }
+
+        // mark the sender as knowing the operations of the block
+        self.operation_cache
+            .write()
+            .insert_peer_known_ops(&from_peer_id, &operation_prefixes);
+
+        // the operations of the blocks are never retrieved in header-only relay mode
+        if self.config.header_only_relay {
+            return;
+        }
+
+        if self.block_wishlist.contains_key(&block_id) {
+            self.reconstruct_compact_block(&block_id, &operation_prefixes);
+        } else {
+            self.compact_blocks.insert(block_id, operation_prefixes);
+        }
+    }
+
+    /// Rebuilds the operation list of a wanted block from the operation prefixes of a compact block,
+    /// using the operations we already know.
+    ///
+    /// On success, only the operations missing from our storage will be asked.
+    /// If a prefix is unknown or ambiguous, or if the rebuilt list does not match the header,
+    /// the retrieval falls back to asking the full operation list of the block.
+    fn reconstruct_compact_block(
+        &mut self,
+        block_id: &BlockId,
+        operation_prefixes: &[OperationPrefixId],
+    ) {
+        let Some(wishlist_info) = self.block_wishlist.get_mut(block_id) else {
+            return;
+        };
+        let Some(header) = wishlist_info.header.as_ref() else {
+            return;
+        };
+        if wishlist_info.operation_ids.is_some() {
+            return;
+        }
+
+        let operation_ids: Vec<Option<OperationId>> = {
+            let stored_ops = self.storage.read_operations();
+            operation_prefixes
+                .iter()
+                .map(|prefix| match stored_ops.get_operations_by_prefix(prefix) {
+                    Some(ids) if ids.len() == 1 => ids.iter().next().copied(),
+                    _ => None,
+                })
+                .collect()
+        };
+        let Some(operation_ids) = operation_ids.iter().copied().collect::<Option<Vec<_>>>() else {
+            debug!(
+                "unknown operations in compact block {}, asking for its operation list and the missing operations",
+                block_id
+            );
+            // the peer will send the operations of the block we do not have along with the operation list
+            wishlist_info.known_operation_prefixes = operation_ids
+                .iter()
+                .flatten()
+                .map(|id| id.prefix())
+                .collect();
+            return;
+        };
+        if compute_operations_hash(&operation_ids, &self.operation_id_serializer)
+            != header.content.operation_merkle_root
+        {
+            debug!(
+                "rebuilt operation list of compact block {} does not match its header, asking for its operation list",
+                block_id
+            );
+            return;
+        }
+
+        debug!("rebuilt operation list of compact block {}", block_id);
+        wishlist_info.operation_ids = Some(operation_ids);
+
+        // the missing operations can be asked right away
+        self.remove_asked_blocks(&[*block_id].into_iter().collect());
+    }
+
     /// Check if the incoming header network version is compatible with the current node
     fn check_network_version_compatibility(
         &self,
@@ -580,7 +963,10 @@ impl RetrievalThread {
             self.selector_controller.as_ref(),
             &self.storage,
             &self.config,
+            !self.quarantine.is_quarantined(from_peer_id),
             &self.sender_propagation_endorsements,
+            &self.propagation_lanes,
+            &self.tracer,
             self.pool_controller.as_mut(),
         ) {
             return Err(ProtocolError::InvalidBlock(format!(
@@ -627,13 +1013,14 @@ impl RetrievalThread {
             }
         }
 
-        // mark the sender peer as knowing the endorsements in the block
+        // mark the sender peer as knowing the endorsements in the block,
+        // and as a peer close to the block producer since it is the first to send us its block
         {
             let endorsement_ids: Vec<_> =
                 header.content.endorsements.iter().map(|e| e.id).collect();
-            self.endorsement_cache
-                .write()
-                .insert_peer_known_endorsements(from_peer_id, &endorsement_ids);
+            let mut endorsement_cache = self.endorsement_cache.write();
+            endorsement_cache.insert_peer_known_endorsements(from_peer_id, &endorsement_ids);
+            endorsement_cache.insert_producer_peer(header.content_creator_address, from_peer_id);
         }
 
         {
@@ -653,10 +1040,10 @@ impl RetrievalThread {
         Ok(true)
     }
 
-    /// send a ban peer command to the peer handler
-    fn ban_peers(&mut self, peer_ids: &[PeerId]) -> Result<(), ProtocolError> {
+    /// send a quarantine peers command to the peer handler, which bans the peers already in quarantine
+    fn quarantine_peers(&mut self, peer_ids: &[PeerId]) -> Result<(), ProtocolError> {
         self.peer_cmd_sender
-            .try_send(PeerManagementCmd::Ban(peer_ids.to_vec()))
+            .try_send(PeerManagementCmd::Quarantine(peer_ids.to_vec()))
             .map_err(|err| ProtocolError::SendError(err.to_string()))
     }
 
@@ -680,27 +1067,105 @@ impl RetrievalThread {
             }
         }
 
-        // ban all peers that know about this block
-        let mut peers_to_ban = Vec::new();
+        // quarantine all peers that know about this block
+        let mut peers_to_quarantine = Vec::new();
         {
             let cache_read = self.cache.read();
             for (peer_id, peer_known_blocks) in cache_read.blocks_known_by_peer.iter() {
                 if peer_known_blocks.peek(block_id).is_some() {
-                    peers_to_ban.push(peer_id.clone());
+                    peers_to_quarantine.push(peer_id.clone());
                 }
             }
         }
-        if !peers_to_ban.is_empty() {
-            if let Err(err) = self.ban_peers(&peers_to_ban) {
+        if !peers_to_quarantine.is_empty() {
+            if let Err(err) = self.quarantine_peers(&peers_to_quarantine) {
                 warn!(
-                    "Error while banning peers {:?} err: {:?}",
-                    peers_to_ban, err
+                    "Error while quarantining peers {:?} err: {:?}",
+                    peers_to_quarantine, err
                 );
             }
         }
 
         // clear retrieval cache
         self.remove_asked_blocks(&[*block_id].into_iter().collect());
+        self.end_peer_ask(block_id, AskBlockFromPeerEvent::Abandoned);
+    }
+
+    /// Adds a block to the wish list and asks it to a specific peer until the timeout expires
+    fn on_ask_block_from_peer(
+        &mut self,
+        block_id: BlockId,
+        header: Option<SecuredHeader>,
+        peer_id: PeerId,
+        timeout: MassaTime,
+        responder: MassaSender<AskBlockFromPeerEvent>,
+    ) {
+        // the blocks are never retrieved in header-only relay mode
+        if self.config.header_only_relay {
+            if let Err(err) = responder.try_send(AskBlockFromPeerEvent::Abandoned) {
+                debug!("error sending ask block from peer outcome: {:?}", err);
+            }
+            return;
+        }
+
+        if !self
+            .active_connections
+            .get_peer_ids_connected()
+            .contains(&peer_id)
+        {
+            if let Err(err) = responder.try_send(AskBlockFromPeerEvent::PeerNotConnected) {
+                debug!("error sending ask block from peer outcome: {:?}", err);
+            }
+            return;
+        }
+
+        match self.block_wishlist.get_mut(&block_id) {
+            Some(info) => {
+                if info.header.is_none() {
+                    info.header = header;
+                }
+            }
+            None => {
+                self.block_wishlist.insert(
+                    block_id,
+                    BlockInfo::new(header, self.storage.clone_without_refs()),
+                );
+                // rebuild the block if we already received it as a compact block
+                if let Some(operation_prefixes) = self.compact_blocks.remove(&block_id) {
+                    self.reconstruct_compact_block(&block_id, &operation_prefixes);
+                }
+            }
+        }
+        if !self.block_wishlist.contains_key(&block_id) {
+            // the block was rebuilt from a compact block
+            if let Err(err) = responder.try_send(AskBlockFromPeerEvent::Retrieved) {
+                debug!("error sending ask block from peer outcome: {:?}", err);
+            }
+            return;
+        }
+
+        // a newer request replaces the previous one
+        self.end_peer_ask(&block_id, AskBlockFromPeerEvent::Abandoned);
+        self.peer_asks.insert(
+            block_id,
+            PeerAsk {
+                peer_id,
+                deadline: Instant::now() + timeout.to_duration(),
+                responder,
+            },
+        );
+        // ask the block to the peer right away, even if it was recently asked to another one
+        self.remove_asked_blocks(&[block_id].into_iter().collect());
+        self.update_block_retrieval();
+    }
+
+    /// Ends the request to retrieve a block from a specific peer, if any, with the given outcome
+    fn end_peer_ask(&mut self, block_id: &BlockId, event: AskBlockFromPeerEvent) {
+        if let Some(peer_ask) = self.peer_asks.remove(block_id) {
+            if let Err(err) = peer_ask.responder.try_send(event) {
+                debug!("error sending ask block from peer outcome: {:?}", err);
+            }
+        }
     }
 
     /// We received a list of operations for a block.
@@ -762,8 +1227,11 @@ impl RetrievalThread {
             != computed_operations_hash
         {
             warn!("Peer id {} sent us a operation list for block id {} but the hash in the header doesn't match.", from_peer_id, block_id);
-            if let Err(err) = self.ban_peers(&[from_peer_id.clone()]) {
-                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+            if let Err(err) = self.quarantine_peers(&[from_peer_id.clone()]) {
+                warn!(
+                    "Error while quarantining peer {} err: {:?}",
+                    from_peer_id, err
+                );
             }
             return;
         }
@@ -874,23 +1342,32 @@ impl RetrievalThread {
         // TODO: in the future if the validiy check fails for something non-malleable (eg. not sig verif),
         //       we should stop retrieving the block and ban everyone who knows it
         //       because we know for sure that this op's ID belongs to the block.
-        if let Err(err) = note_operations_from_peer(
+        match note_operations_from_peer(
             &self.storage,
             &mut self.operation_cache,
             &self.config,
             operations.values().cloned().collect(),
             &from_peer_id,
+            !self.quarantine.is_quarantined(&from_peer_id),
             &mut self.sender_propagation_ops,
-            &mut self.pool_controller,
+            &self.tracer,
         ) {
-            warn!(
-                "Peer id {} sent us operations for block id {} but they failed validity checks: {}",
-                from_peer_id, block_id, err
-            );
-            if let Err(err) = self.ban_peers(&[from_peer_id.clone()]) {
-                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+            // the operations of a wanted block are added to the pool right away
+            Ok(Some(new_ops)) => self.pool_controller.add_operations(new_ops),
+            Ok(None) => {}
+            Err(err) => {
+                warn!(
+                    "Peer id {} sent us operations for block id {} but they failed validity checks: {}",
+                    from_peer_id, block_id, err
+                );
+                if let Err(err) = self.quarantine_peers(&[from_peer_id.clone()]) {
+                    warn!(
+                        "Error while quarantining peer {} err: {:?}",
+                        from_peer_id, err
+                    );
+                }
+                return;
             }
-            return;
         }
 
         // add received operations to local storage and claim ref
@@ -922,20 +1399,29 @@ impl RetrievalThread {
 
     /// function that updates the global state of block retrieval
     pub(crate) fn update_block_retrieval(&mut self) {
-        let ask_block_timeout = self.config.ask_block_timeout.to_duration();
+        // Get conencted peer list
+        let connected_peers = self.active_connections.get_peer_ids_connected();
+
+        // the timeout adapts to the block arrival timings if they are gossiped
+        let ask_block_timeout = if self.config.block_arrival_gossip {
+            self.arrival_timings.retain_peers(&connected_peers);
+            self.arrival_timings.ask_block_timeout()
+        } else {
+            self.config.ask_block_timeout.to_duration()
+        };
 
         // Init timer for next tick
         let now = Instant::now();
         let mut next_tick = now
-            .checked_add(self.config.ask_block_timeout.into())
+            .checked_add(ask_block_timeout)
             .ok_or(TimeError::TimeOverflowError)
             .expect("could not compute next block retrieval timer tick");
 
-        // Get conencted peer list
-        let connected_peers = self.active_connections.get_peer_ids_connected();
-
         // Update cache
         self.cache.write().update_cache(&connected_peers);
+        self.peer_scores
+            .write()
+            .update_connected_peers(&connected_peers);
 
         // Cleanup asked_blocks from all disconnected peers and blocks that are not in the wishlist anymore.
         self.asked_blocks.retain(|peer_id, asked_blocks| {
@@ -946,8 +1432,32 @@ impl RetrievalThread {
             !asked_blocks.is_empty()
         });
 
-        // list of blocks that need to be asked
-        let mut to_ask: PreHashSet<BlockId> = self.block_wishlist.keys().copied().collect();
+        // End the requests to specific peers that expired or whose peer disconnected.
+        let ended_peer_asks: Vec<(BlockId, AskBlockFromPeerEvent)> = self
+            .peer_asks
+            .iter()
+            .filter_map(|(block_id, peer_ask)| {
+                if !connected_peers.contains(&peer_ask.peer_id) {
+                    Some((*block_id, AskBlockFromPeerEvent::PeerNotConnected))
+                } else if peer_ask.deadline <= now {
+                    Some((*block_id, AskBlockFromPeerEvent::TimedOut))
+                } else {
+                    next_tick = next_tick.min(peer_ask.deadline);
+                    None
+                }
+            })
+            .collect();
+        for (block_id, event) in ended_peer_asks {
+            self.end_peer_ask(&block_id, event);
+        }
+
+        // list of blocks that need to be asked (only the missing headers in header-only relay mode)
+        let mut to_ask: PreHashSet<BlockId> = self
+            .block_wishlist
+            .iter()
+            .filter(|(_, info)| !self.config.header_only_relay || info.header.is_none())
+            .map(|(block_id, _)| *block_id)
+            .collect();
         // the number of things already being asked to those peers
         let mut peer_loads: HashMap<PeerId, usize> = Default::default();
         for (peer_id, asked_blocks) in &mut self.asked_blocks {
@@ -959,6 +1469,7 @@ impl RetrievalThread {
                     .expect("could not compute block ask expiry");
                 if expiry <= now {
                     // the block has been asked for the block data a long time agp and did not respond
+                    self.peer_scores.write().record_ask_block_timeout(peer_id);
 
                     // we mark this peer as not knowing this block
                     self.cache
@@ -989,11 +1500,27 @@ impl RetrievalThread {
             }
         }
 
-        // for each block to ask, choose a peer to ask it from and perform the ask
+        // Rank the peers by score. Scores are rounded to the percent
+        // so that peers with close scores are prioritized by the other criteria.
+        let score_ranks: HashMap<PeerId, i64> = {
+            let peer_scores = self.peer_scores.read();
+            connected_peers
+                .iter()
+                .map(|peer_id| {
+                    (
+                        peer_id.clone(),
+                        -(peer_scores.get_score(peer_id) * 100.0).round() as i64,
+                    )
+                })
+                .collect()
+        };
+
+        // for each block to ask, rank the peers to ask it from and choose the best one
+        let mut asks: Vec<BlockAsk> = Vec::new();
         let mut to_ask = to_ask.into_iter().collect::<Vec<_>>();
         to_ask.shuffle(&mut thread_rng()); // shuffle ask order
         for block_id in to_ask {
-            // prioritize peers by (max knowledge, min knowledge age, min load, max random)
+            // prioritize peers by (max knowledge, max score, min knowledge age, min load, max random)
             let mut peer_scores: Vec<_> = connected_peers
                 .iter()
                 .filter_map(|peer_id| {
@@ -1003,6 +1530,7 @@ impl RetrievalThread {
                         // this peer is already loaded with too many asks
                         return None;
                     }
+                    let score_rank = score_ranks.get(peer_id).copied().unwrap_or_default();
                     // get peer knowledge info about that block
                     let peer_knowledge_of_block = self
                         .cache
@@ -1015,6 +1543,7 @@ impl RetrievalThread {
                             // we think that the peer doesn't know the block
                             Some((
                                 1i8,                                                               // worst knowledge
+                                score_rank, // the higher the score the better
                                 Some(-(now.saturating_duration_since(info_t).as_millis() as i64)), // the older the info the better
                                 peer_load,                 // the lower the load the better
                                 thread_rng().gen::<u64>(), // random tie breaker,
@@ -1025,6 +1554,7 @@ impl RetrievalThread {
                             // we don't know if the peer knows the block
                             Some((
                                 0i8,                       // medium knowledge
+                                score_rank,                // the higher the score the better
                                 None,                      // N/A
                                 peer_load,                 // the lower the load the better
                                 thread_rng().gen::<u64>(), // random tie breaker,
@@ -1035,6 +1565,7 @@ impl RetrievalThread {
                             // we think that the peer knows the block
                             Some((
                                 -1i8,                                                           // best knowledge
+                                score_rank, // the higher the score the better
                                 Some(now.saturating_duration_since(info_t).as_millis() as i64), // the newer the info the better
                                 peer_load,                 // the lower the load the better
                                 thread_rng().gen::<u64>(), // random tie breaker,
@@ -1059,7 +1590,13 @@ impl RetrievalThread {
             ) {
                 // ask for header
                 (false, false) => AskForBlockInfo::Header,
-                // ask for the list of operation IDs in the block
+                // ask for the list of operation IDs in the block,
+                // along with the operations we miss if we know some of them
+                (true, false) if !wishlist_info.known_operation_prefixes.is_empty() => {
+                    AskForBlockInfo::MissingOperations(
+                        wishlist_info.known_operation_prefixes.clone(),
+                    )
+                }
                 (true, false) => AskForBlockInfo::OperationIds,
                 // ask for missing operations in the block
                 (true, true) => {
@@ -1072,42 +1609,53 @@ impl RetrievalThread {
                 _ => panic!("invalid wishlist state"),
             };
 
-            // try to ask peers from best to worst
-            for (_, _, _, _, peer_id) in peer_scores {
-                debug!(
-                    "Sending ask for block {} data to {}: {:?}",
-                    block_id, peer_id, &request
-                );
-                if let Err(err) = self.active_connections.send_to_peer(
-                    &peer_id,
-                    &self.block_message_serializer,
-                    Message::Block(Box::new(BlockMessage::DataRequest {
-                        block_id,
-                        block_info: request.clone(),
-                    })),
-                    true,
-                ) {
-                    warn!(
-                        "Failed to send BlockDataRequest to peer {} err: {}",
-                        peer_id, err
-                    );
-                } else {
-                    // The request was sent.
+            // the load of the chosen peer is counted right away so that the next blocks are spread over the peers
+            let mut candidates: VecDeque<PeerId> = peer_scores
+                .into_iter()
+                .map(|(_, _, _, _, _, peer_id)| peer_id)
+                .collect();
+            // a block asked to a specific peer is only asked to that peer until the request ends
+            if let Some(peer_ask) = self.peer_asks.get(&block_id) {
+                candidates = VecDeque::from([peer_ask.peer_id.clone()]);
+            }
+            if let Some(peer_id) = candidates.front() {
+                peer_loads
+                    .entry(peer_id.clone())
+                    .and_modify(|v| *v += 1)
+                    .or_insert(1);
+                asks.push((block_id, request, candidates));
+            }
+        }
 
-                    // Update the asked_blocks list
-                    self.asked_blocks
+        // send the asks to the chosen peers, falling back to the next best peers of a block
+        // if the asks could not be sent to its chosen peer
+        let mut failed_peers: HashSet<PeerId> = HashSet::new();
+        while !asks.is_empty() {
+            let mut asks_by_peer: HashMap<PeerId, Vec<BlockAsk>> = HashMap::new();
+            for (block_id, request, mut candidates) in asks.drain(..) {
+                while candidates
+                    .front()
+                    .map_or(false, |peer_id| failed_peers.contains(peer_id))
+                {
+                    candidates.pop_front();
+                }
+                if let Some(peer_id) = candidates.front() {
+                    asks_by_peer
                         .entry(peer_id.clone())
-                        .or_insert_with(Default::default)
-                        .insert(block_id, now);
-
-                    // Increment the load of the peer.
-                    peer_loads
-                        .entry(peer_id)
-                        .and_modify(|v| *v += 1)
-                        .or_insert(1);
-
-                    // No need to look for other peers.
-                    break;
+                        .or_default()
+                        .push((block_id, request, candidates));
+                }
+            }
+            for (peer_id, peer_asks) in asks_by_peer {
+                if self.send_block_asks(&peer_id, &peer_asks) {
+                    // Update the asked_blocks list
+                    let asked_blocks = self.asked_blocks.entry(peer_id).or_default();
+                    for (block_id, _, _) in peer_asks {
+                        asked_blocks.insert(block_id, now);
+                    }
+                } else {
+                    failed_peers.insert(peer_id);
+                    asks.extend(peer_asks);
                 }
             }
         }
@@ -1116,6 +1664,41 @@ impl RetrievalThread {
         self.next_timer_ask_block = next_tick;
     }
 
+    /// Sends block data requests to a peer, in a single message if `batch_block_asks` is enabled.
+    /// Returns false if the requests could not be sent.
+    fn send_block_asks(&self, peer_id: &PeerId, asks: &[BlockAsk]) -> bool {
+        debug!("Sending {} block data requests to {}", asks.len(), peer_id);
+        let messages: Vec<BlockMessage> = if self.config.batch_block_asks && asks.len() > 1 {
+            vec![BlockMessage::DataRequestBatch(
+                asks.iter()
+                    .map(|(block_id, request, _)| (*block_id, request.clone()))
+                    .collect(),
+            )]
+        } else {
+            asks.iter()
+                .map(|(block_id, request, _)| BlockMessage::DataRequest {
+                    block_id: *block_id,
+                    block_info: request.clone(),
+                })
+                .collect()
+        };
+        for message in messages {
+            if let Err(err) = self.active_connections.send_to_peer(
+                peer_id,
+                &self.block_message_serializer,
+                Message::Block(Box::new(message)),
+                true,
+            ) {
+                warn!(
+                    "Failed to send BlockDataRequest to peer {} err: {}",
+                    peer_id, err
+                );
+                return false;
+            }
+        }
+        true
+    }
+
     // Gather all missing block operations.
     // Returns Some(ops) if there are missing ops to gather
     fn gather_missing_block_ops(&mut self, block_id: &BlockId) -> Option<Vec<OperationId>> {
@@ -1244,6 +1827,7 @@ impl RetrievalThread {
 
         // Remove from asked block history as it is not useful anymore
         self.remove_asked_blocks(&vec![*block_id].into_iter().collect());
+        self.end_peer_ask(block_id, AskBlockFromPeerEvent::Retrieved);
     }
 }
 
@@ -1255,7 +1839,7 @@ pub fn start_retrieval_thread(
     pool_controller: Box<dyn PoolController>,
     receiver_network: MassaReceiver<PeerMessageTuple>,
     receiver: MassaReceiver<BlockHandlerRetrievalCommand>,
-    _internal_sender: MassaSender<BlockHandlerPropagationCommand>,
+    internal_sender: MassaSender<BlockHandlerPropagationCommand>,
     sender_propagation_ops: MassaSender<OperationHandlerPropagationCommand>,
     sender_propagation_endorsements: MassaSender<EndorsementHandlerPropagationCommand>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
@@ -1263,6 +1847,10 @@ pub fn start_retrieval_thread(
     endorsement_cache: SharedEndorsementCache,
     operation_cache: SharedOperationCache,
     cache: SharedBlockCache,
+    peer_scores: SharedPeerScores,
+    quarantine: SharedPeerQuarantine,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
     storage: Storage,
     mip_store: MipStore,
     massa_metrics: MassaMetrics,
@@ -1278,18 +1866,30 @@ pub fn start_retrieval_thread(
                 consensus_controller,
                 pool_controller,
                 next_timer_ask_block: Instant::now() + config.ask_block_timeout.to_duration(),
+                arrival_timings: BlockArrivalTimings::new(&config),
                 block_wishlist: PreHashMap::default(),
                 asked_blocks: HashMap::default(),
+                peer_asks: PreHashMap::default(),
+                compact_blocks: LruMap::new(ByLength::new(
+                    config
+                        .max_known_blocks_size
+                        .try_into()
+                        .expect("max_known_blocks_size does not fit in u32"),
+                )),
                 peer_cmd_sender,
                 sender_propagation_ops,
                 sender_propagation_endorsements,
                 receiver_network,
                 block_message_serializer,
                 receiver,
-                _announcement_sender: _internal_sender,
+                announcement_sender: internal_sender,
                 cache,
                 endorsement_cache,
                 operation_cache,
+                peer_scores,
+                quarantine,
+                propagation_lanes,
+                tracer,
                 config,
                 storage,
                 mip_store,
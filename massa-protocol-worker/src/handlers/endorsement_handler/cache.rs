@@ -1,30 +1,54 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::Duration,
 };
 
-use massa_models::endorsement::EndorsementId;
-use massa_protocol_exports::PeerId;
+use massa_models::{address::Address, endorsement::EndorsementId};
+use massa_protocol_exports::{EndorsementDedupStats, PeerId};
 use parking_lot::RwLock;
 use schnellru::{ByLength, LruMap};
 
+use super::dedup_filter::KnownEndorsementsFilter;
+
+/// Max number of block producers whose peers are remembered
+const MAX_KNOWN_PRODUCERS: u32 = 4096;
+
 /// Cache of endorsements
 pub struct EndorsementCache {
     /// List of endorsements we checked recently
     pub checked_endorsements: LruMap<EndorsementId, ()>,
-    /// List of endorsements known by peers
-    pub endorsements_known_by_peer: HashMap<PeerId, LruMap<EndorsementId, ()>>,
-    /// Maximum number of endorsements known by a peer
-    pub max_known_endorsements_by_peer: u32,
+    /// Endorsements known by peers, remembered for a sliding time window
+    pub endorsements_known_by_peer: HashMap<PeerId, KnownEndorsementsFilter>,
+    /// Number of bits of the filters of the endorsements known by a peer
+    pub known_endorsements_filter_bits: usize,
+    /// Time window during which an endorsement known by a peer is remembered
+    pub known_endorsements_window: Duration,
+    /// Statistics of the endorsements not propagated because the peers already knew them
+    pub dedup_stats: EndorsementDedupStats,
+    /// Peers that first sent us the blocks of each producer, most recent first.
+    /// The endorsements to include in the blocks of a producer are pushed to them first.
+    pub producer_peers: LruMap<Address, Vec<PeerId>>,
+    /// Max number of peers remembered for each producer
+    pub max_peers_per_producer: usize,
 }
 
 impl EndorsementCache {
     /// Create a new EndorsementCache
-    pub fn new(max_known_endorsements: u32, max_known_endorsements_by_peer: u32) -> Self {
+    pub fn new(
+        max_known_endorsements: u32,
+        known_endorsements_filter_bits: usize,
+        known_endorsements_window: Duration,
+        max_peers_per_producer: usize,
+    ) -> Self {
         Self {
             checked_endorsements: LruMap::new(ByLength::new(max_known_endorsements)),
             endorsements_known_by_peer: HashMap::new(),
-            max_known_endorsements_by_peer,
+            known_endorsements_filter_bits,
+            known_endorsements_window,
+            dedup_stats: EndorsementDedupStats::default(),
+            producer_peers: LruMap::new(ByLength::new(MAX_KNOWN_PRODUCERS)),
+            max_peers_per_producer,
         }
     }
 
@@ -37,10 +61,28 @@ impl EndorsementCache {
         let known_endorsements = self
             .endorsements_known_by_peer
             .entry(peer_id.clone())
-            .or_insert_with(|| LruMap::new(ByLength::new(self.max_known_endorsements_by_peer)));
+            .or_insert_with(|| {
+                KnownEndorsementsFilter::new(
+                    self.known_endorsements_filter_bits,
+                    self.known_endorsements_window,
+                )
+            });
         for endorsement in endorsements {
-            known_endorsements.insert(*endorsement, ());
+            known_endorsements.insert(endorsement);
+        }
+    }
+
+    /// Note that a peer was the first to send us a block of a producer
+    pub fn insert_producer_peer(&mut self, producer: Address, peer_id: &PeerId) {
+        if self.max_peers_per_producer == 0 {
+            return;
         }
+        let Some(peers) = self.producer_peers.get_or_insert(producer, Vec::new) else {
+            return;
+        };
+        peers.retain(|known_peer_id| known_peer_id != peer_id);
+        peers.insert(0, peer_id.clone());
+        peers.truncate(self.max_peers_per_producer);
     }
 
     /// Mark an endorsement ID as checked by us
@@ -53,15 +95,19 @@ impl EndorsementCache {
         // Remove disconnected peers from cache
         self.endorsements_known_by_peer
             .retain(|peer_id, _| peers_connected.contains(peer_id));
+        for (_, peers) in self.producer_peers.iter_mut() {
+            peers.retain(|peer_id| peers_connected.contains(peer_id));
+        }
 
         // Add new connected peers to cache
         for peer_id in peers_connected {
             match self.endorsements_known_by_peer.entry(peer_id.clone()) {
                 std::collections::hash_map::Entry::Occupied(_) => {}
                 std::collections::hash_map::Entry::Vacant(entry) => {
-                    entry.insert(LruMap::new(ByLength::new(
-                        self.max_known_endorsements_by_peer,
-                    )));
+                    entry.insert(KnownEndorsementsFilter::new(
+                        self.known_endorsements_filter_bits,
+                        self.known_endorsements_window,
+                    ));
                 }
             }
         }
@@ -1,3 +1,5 @@
+use massa_channel::sender::MassaSender;
+use massa_protocol_exports::EndorsementDedupStats;
 use massa_storage::Storage;
 
 #[derive(Clone)]
@@ -5,4 +7,23 @@ pub enum EndorsementHandlerPropagationCommand {
     Stop,
     // Storage that contains endorsements to propagate
     PropagateEndorsements(Storage),
+    /// Get the statistics of the endorsements not propagated because the peers already knew them
+    GetDedupStats {
+        responder: MassaSender<EndorsementDedupStats>,
+    },
+}
+
+impl EndorsementHandlerPropagationCommand {
+    /// Name of the command in the protocol trace
+    pub fn trace_name(&self) -> &'static str {
+        match self {
+            EndorsementHandlerPropagationCommand::Stop => "endorsement_propagation.stop",
+            EndorsementHandlerPropagationCommand::PropagateEndorsements(_) => {
+                "endorsement_propagation.propagate_endorsements"
+            }
+            EndorsementHandlerPropagationCommand::GetDedupStats { .. } => {
+                "endorsement_propagation.get_dedup_stats"
+            }
+        }
+    }
 }
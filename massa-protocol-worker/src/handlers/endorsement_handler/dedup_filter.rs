@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+
+use massa_models::{endorsement::EndorsementId, secure_share::Id};
+
+/// Number of bits set in the filter for each endorsement
+const HASH_COUNT: u64 = 4;
+
+/// Endorsements known by a peer, remembered for a sliding time window.
+///
+/// This is a bloom filter split in two generations: new endorsements are added to the current one,
+/// and every `window` the current generation becomes the previous one and the oldest one is cleared.
+/// An endorsement is thus remembered between one and two windows, in constant memory.
+/// False positives are possible, so rarely an endorsement unknown by the peer is considered as known.
+pub struct KnownEndorsementsFilter {
+    /// bits of the current generation
+    current: Vec<u64>,
+    /// bits of the previous generation
+    previous: Vec<u64>,
+    /// number of endorsements added to the current generation
+    current_count: usize,
+    /// number of endorsements added to the previous generation
+    previous_count: usize,
+    /// start of the current generation
+    current_start: Instant,
+    /// duration of a generation
+    window: Duration,
+}
+
+impl KnownEndorsementsFilter {
+    /// Creates an empty filter of `bits` bits per generation
+    pub fn new(bits: usize, window: Duration) -> Self {
+        let words = ((bits + 63) / 64).max(1);
+        Self {
+            current: vec![0; words],
+            previous: vec![0; words],
+            current_count: 0,
+            previous_count: 0,
+            current_start: Instant::now(),
+            window,
+        }
+    }
+
+    /// Marks an endorsement as known
+    pub fn insert(&mut self, endorsement_id: &EndorsementId) {
+        self.slide(Instant::now());
+        let mut newly_inserted = false;
+        for (word, mask) in self.bit_positions(endorsement_id) {
+            if self.current[word] & mask == 0 {
+                self.current[word] |= mask;
+                newly_inserted = true;
+            }
+        }
+        if newly_inserted {
+            self.current_count += 1;
+        }
+    }
+
+    /// Returns true if the endorsement is known, or on a false positive
+    pub fn contains(&mut self, endorsement_id: &EndorsementId) -> bool {
+        self.slide(Instant::now());
+        self.is_set(endorsement_id)
+    }
+
+    /// Approximate number of remembered endorsements
+    pub fn count(&self) -> usize {
+        self.current_count + self.previous_count
+    }
+
+    /// Starts new generations if windows have elapsed since the start of the current one
+    fn slide(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.current_start);
+        if elapsed < self.window {
+            return;
+        }
+        if elapsed < self.window.saturating_mul(2) {
+            std::mem::swap(&mut self.current, &mut self.previous);
+            self.previous_count = self.current_count;
+            self.current_start += self.window;
+        } else {
+            // both generations are outdated
+            self.previous.fill(0);
+            self.previous_count = 0;
+            self.current_start = now;
+        }
+        self.current.fill(0);
+        self.current_count = 0;
+    }
+
+    /// Returns true if all the bits of the endorsement are set in one of the generations
+    fn is_set(&self, endorsement_id: &EndorsementId) -> bool {
+        let positions = self.bit_positions(endorsement_id);
+        positions
+            .iter()
+            .all(|(word, mask)| self.current[*word] & mask != 0)
+            || positions
+                .iter()
+                .all(|(word, mask)| self.previous[*word] & mask != 0)
+    }
+
+    /// Positions of the bits of an endorsement, as (word index, bit mask).
+    /// Endorsement ids are hashes, so their bytes are used directly as hash values (double hashing).
+    fn bit_positions(&self, endorsement_id: &EndorsementId) -> [(usize, u64); HASH_COUNT as usize] {
+        let bytes = endorsement_id.get_hash().to_bytes();
+        let h1 = u64::from_le_bytes(bytes[0..8].try_into().expect("hash is 32 bytes long"));
+        let h2 = u64::from_le_bytes(bytes[8..16].try_into().expect("hash is 32 bytes long")) | 1;
+        let bits = (self.current.len() * 64) as u64;
+        let mut positions = [(0, 0); HASH_COUNT as usize];
+        for (i, position) in positions.iter_mut().enumerate() {
+            let bit = h1.wrapping_add((i as u64).wrapping_mul(h2)) % bits;
+            *position = ((bit / 64) as usize, 1 << (bit % 64));
+        }
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+
+    #[test]
+    fn test_known_endorsements_filter_window() {
+        let window = Duration::from_secs(10);
+        let mut filter = KnownEndorsementsFilter::new(4096, window);
+        let old_id = EndorsementId::new(Hash::compute_from(b"old"));
+        let new_id = EndorsementId::new(Hash::compute_from(b"new"));
+        let start = filter.current_start;
+
+        filter.insert(&old_id);
+        assert!(filter.contains(&old_id));
+        assert!(!filter.contains(&new_id));
+        assert_eq!(filter.count(), 1);
+
+        // one window later, the endorsement is still remembered by the previous generation
+        filter.slide(start + window);
+        filter.insert(&new_id);
+        assert!(filter.contains(&old_id));
+        assert!(filter.contains(&new_id));
+        assert_eq!(filter.count(), 2);
+
+        // two windows later, only the endorsement of the previous window is remembered
+        filter.slide(start + window * 2);
+        assert!(!filter.is_set(&old_id));
+        assert!(filter.is_set(&new_id));
+
+        // long after, nothing is remembered
+        filter.slide(start + window * 10);
+        assert!(!filter.is_set(&new_id));
+        assert_eq!(filter.count(), 0);
+    }
+}
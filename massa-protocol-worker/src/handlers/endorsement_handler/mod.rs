@@ -7,7 +7,10 @@ use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
 
-use crate::wrap_network::ActiveConnectionsTrait;
+use crate::{
+    propagation_lanes::SharedPropagationLanes, shutdown::ShutdownDrain,
+    trace::SharedProtocolTracer, wrap_network::ActiveConnectionsTrait,
+};
 
 use self::{
     cache::SharedEndorsementCache, commands_propagation::EndorsementHandlerPropagationCommand,
@@ -18,14 +21,20 @@ use self::{
 pub mod cache;
 pub mod commands_propagation;
 pub mod commands_retrieval;
+mod dedup_filter;
 mod messages;
 mod propagation;
 mod retrieval;
+mod subscriptions;
 
 pub(crate) use messages::{EndorsementMessage, EndorsementMessageSerializer};
 pub(crate) use retrieval::note_endorsements_from_peer;
+pub(crate) use subscriptions::{EndorsementSubscriptions, SharedEndorsementSubscriptions};
 
-use super::peer_handler::models::{PeerManagementCmd, PeerMessageTuple};
+use super::peer_handler::{
+    models::{PeerManagementCmd, PeerMessageTuple},
+    SharedPeerQuarantine,
+};
 
 pub struct EndorsementHandler {
     pub endorsement_retrieval_thread: Option<(
@@ -52,6 +61,10 @@ impl EndorsementHandler {
         receiver_retrieval_ext: MassaReceiver<EndorsementHandlerRetrievalCommand>,
         local_sender: MassaSender<EndorsementHandlerPropagationCommand>,
         local_receiver: MassaReceiver<EndorsementHandlerPropagationCommand>,
+        propagation_lanes: SharedPropagationLanes,
+        tracer: SharedProtocolTracer,
+        subscriptions: SharedEndorsementSubscriptions,
+        quarantine: SharedPeerQuarantine,
         sender_peer_cmd: MassaSender<PeerManagementCmd>,
         massa_metrics: MassaMetrics,
     ) -> Self {
@@ -59,17 +72,28 @@ impl EndorsementHandler {
             receiver,
             receiver_retrieval_ext,
             local_sender.clone(),
+            propagation_lanes.clone(),
+            tracer.clone(),
+            quarantine,
             sender_peer_cmd,
             cache.clone(),
-            selector_controller,
+            selector_controller.clone(),
             pool_controller,
             config.clone(),
             storage.clone_without_refs(),
             massa_metrics,
         );
 
-        let endorsement_propagation_thread =
-            start_propagation_thread(local_receiver, cache, config, active_connections);
+        let endorsement_propagation_thread = start_propagation_thread(
+            local_receiver,
+            cache,
+            config,
+            active_connections,
+            selector_controller,
+            propagation_lanes,
+            tracer,
+            subscriptions,
+        );
         Self {
             endorsement_retrieval_thread: Some((
                 sender_retrieval_ext,
@@ -79,14 +103,19 @@ impl EndorsementHandler {
         }
     }
 
-    pub fn stop(&mut self) {
+    /// Stops receiving messages and commands
+    pub fn stop_retrieval(&mut self, drain: &mut ShutdownDrain) {
         if let Some((tx, thread)) = self.endorsement_retrieval_thread.take() {
             let _ = tx.send(EndorsementHandlerRetrievalCommand::Stop);
-            thread.join().unwrap();
+            drain.join(thread);
         }
+    }
+
+    /// Stops the propagation once the propagation commands queued before are processed
+    pub fn stop_propagation(&mut self, drain: &mut ShutdownDrain) {
         if let Some((tx, thread)) = self.endorsement_propagation_thread.take() {
             let _ = tx.send(EndorsementHandlerPropagationCommand::Stop);
-            thread.join().unwrap();
+            drain.join(thread);
         }
     }
 }
@@ -1,12 +1,22 @@
 use super::{
-    cache::SharedEndorsementCache, commands_propagation::EndorsementHandlerPropagationCommand,
-    messages::EndorsementMessageSerializer, EndorsementMessage,
+    cache::SharedEndorsementCache,
+    commands_propagation::EndorsementHandlerPropagationCommand,
+    messages::EndorsementMessageSerializer,
+    subscriptions::{is_subscribed, SharedEndorsementSubscriptions},
+    EndorsementMessage,
+};
+use crate::{
+    messages::MessagesSerializer,
+    propagation_lanes::{PropagationLane, SharedPropagationLanes},
+    trace::SharedProtocolTracer,
+    wrap_network::ActiveConnectionsTrait,
 };
-use crate::{messages::MessagesSerializer, wrap_network::ActiveConnectionsTrait};
 use massa_channel::receiver::MassaReceiver;
-use massa_protocol_exports::ProtocolConfig;
+use massa_models::{address::Address, prehash::PreHashSet, slot::Slot};
+use massa_pos_exports::SelectorController;
+use massa_protocol_exports::{PeerId, ProtocolConfig};
 use massa_storage::Storage;
-use std::thread::JoinHandle;
+use std::{collections::HashSet, thread::JoinHandle};
 use tracing::{info, log::warn};
 
 /// Endorsements need to propagate fast, so no buffering
@@ -15,7 +25,12 @@ struct PropagationThread {
     config: ProtocolConfig,
     cache: SharedEndorsementCache,
     active_connections: Box<dyn ActiveConnectionsTrait>,
+    selector_controller: Box<dyn SelectorController>,
     endorsement_serializer: MessagesSerializer,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
+    /// Threads whose endorsements each peer wants
+    subscriptions: SharedEndorsementSubscriptions,
 }
 
 impl PropagationThread {
@@ -26,7 +41,10 @@ impl PropagationThread {
             let msg = match next_message.take() {
                 Some(msg) => msg,
                 None => match self.receiver.recv() {
-                    Ok(msg) => msg,
+                    Ok(msg) => {
+                        self.tracer.dequeue(msg.trace_name());
+                        msg
+                    }
                     Err(_) => {
                         info!("Stop endorsement propagation thread");
                         return;
@@ -37,14 +55,17 @@ impl PropagationThread {
             match msg {
                 // endorsements to propagate
                 EndorsementHandlerPropagationCommand::PropagateEndorsements(mut endorsements) => {
+                    let mut commands_count = 1;
                     // also drain any remaining propagation messages that might have accumulated
                     while let Ok(msg) = self.receiver.try_recv() {
+                        self.tracer.dequeue(msg.trace_name());
                         match msg {
                             // we got more endorsements to propagate: extend the buffer
                             EndorsementHandlerPropagationCommand::PropagateEndorsements(
                                 new_endorsements,
                             ) => {
                                 endorsements.extend(new_endorsements);
+                                commands_count += 1;
                             }
                             // we grabbed a message that is not a propagation message, mark it for processing
                             other_msg => {
@@ -55,6 +76,15 @@ impl PropagationThread {
                     }
                     // propagate the endorsements
                     self.propagate_endorsements(endorsements);
+                    for _ in 0..commands_count {
+                        self.propagation_lanes.done(PropagationLane::Endorsements);
+                    }
+                }
+                EndorsementHandlerPropagationCommand::GetDedupStats { responder } => {
+                    let stats = self.cache.read().dedup_stats;
+                    if let Err(err) = responder.try_send(stats) {
+                        warn!("error sending endorsement dedup stats: {:?}", err);
+                    }
                 }
                 // stop the handler
                 EndorsementHandlerPropagationCommand::Stop => {
@@ -77,8 +107,26 @@ impl PropagationThread {
                 .collect()
         };
 
+        // get the producers of the blocks that can include those endorsements
+        let producers: PreHashSet<Address> = if self.config.endorsement_fast_path_peers > 0 {
+            endorsements
+                .iter()
+                .map(|endorsement| endorsement.content.slot)
+                .collect::<HashSet<Slot>>()
+                .into_iter()
+                .filter_map(|slot| self.selector_controller.get_producer(slot).ok())
+                .collect()
+        } else {
+            PreHashSet::default()
+        };
+
+        // let the pending block headers go first, without holding the cache lock
+        self.propagation_lanes
+            .wait_for_higher_lanes(PropagationLane::Endorsements);
+
         // get connected peers
         let peers_connected = self.active_connections.get_peer_ids_connected();
+        let peer_masks = self.subscriptions.get_peer_masks();
 
         // get a write lock on the cache
         let mut cache_write = self.cache.write();
@@ -91,6 +139,21 @@ impl PropagationThread {
         // Add peers that potentially don't exist in cache and remove the ones that disconnected
         cache_write.update_cache(&peers_connected);
 
+        // fast path: the peers that first sent us the blocks of the producers are likely close to them,
+        // so they are served first and with a high priority to improve the inclusion of the endorsements
+        let fast_path_peers: HashSet<PeerId> = producers
+            .iter()
+            .filter_map(|producer| cache_write.producer_peers.peek(producer))
+            .flatten()
+            .cloned()
+            .collect();
+        let mut peers_connected: Vec<PeerId> = peers_connected.into_iter().collect();
+        peers_connected.sort_by_key(|peer_id| !fast_path_peers.contains(peer_id));
+
+        // count the endorsements not sent because the peers already knew them
+        let mut known_endorsements: u64 = 0;
+        let mut sent_endorsements: u64 = 0;
+
         // Propagate to peers
         'peer_loop: for peer_id in peers_connected {
             // write access to the cache of which endorsements are known by the peer
@@ -99,11 +162,21 @@ impl PropagationThread {
                 .get_mut(&peer_id)
                 .expect("update_cache should have added connected peer to cache");
 
+            // get endorsements of the threads the peer subscribed to
+            let subscribed: Vec<_> = endorsements
+                .iter()
+                .filter(|endorsement| {
+                    is_subscribed(peer_masks.get(&peer_id), endorsement.content.slot.thread)
+                })
+                .collect();
+
             // get endorsements that are not known by the peer
-            let to_send: Vec<_> = endorsements
+            let to_send: Vec<_> = subscribed
                 .iter()
-                .filter(|endorsement| peer_knowledge.peek(&endorsement.id).is_none())
+                .copied()
+                .filter(|endorsement| !peer_knowledge.contains(&endorsement.id))
                 .collect();
+            known_endorsements += (subscribed.len() - to_send.len()) as u64;
 
             if to_send.is_empty() {
                 // nothing to send to that peer, try the next one
@@ -111,13 +184,14 @@ impl PropagationThread {
             }
 
             // send by chunks
+            let high_priority = fast_path_peers.contains(&peer_id);
             for chunk in to_send.chunks(self.config.max_endorsements_per_message as usize) {
                 if let Err(err) = self.active_connections.send_to_peer(
                     &peer_id,
                     &self.endorsement_serializer,
                     EndorsementMessage::Endorsements(chunk.iter().map(|&e| e.clone()).collect())
                         .into(),
-                    false,
+                    high_priority,
                 ) {
                     warn!(
                         "could not send endorsements batch to node {}: {}",
@@ -128,10 +202,14 @@ impl PropagationThread {
                 }
                 // sent successfully: mark peer as knowing the endorsements that were sent to it
                 for endorsement in chunk {
-                    peer_knowledge.insert(endorsement.id, ());
+                    peer_knowledge.insert(&endorsement.id);
                 }
+                sent_endorsements += chunk.len() as u64;
             }
         }
+
+        cache_write.dedup_stats.known_endorsements += known_endorsements;
+        cache_write.dedup_stats.sent_endorsements += sent_endorsements;
     }
 }
 
@@ -140,6 +218,10 @@ pub fn start_propagation_thread(
     cache: SharedEndorsementCache,
     config: ProtocolConfig,
     active_connections: Box<dyn ActiveConnectionsTrait>,
+    selector_controller: Box<dyn SelectorController>,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
+    subscriptions: SharedEndorsementSubscriptions,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
         .name("protocol-endorsement-handler-propagation".to_string())
@@ -150,8 +232,12 @@ pub fn start_propagation_thread(
                 receiver,
                 config,
                 active_connections,
+                selector_controller,
                 cache,
                 endorsement_serializer,
+                propagation_lanes,
+                tracer,
+                subscriptions,
             };
             propagation_thread.run();
         })
@@ -2,7 +2,6 @@ use std::thread::JoinHandle;
 
 use crossbeam::{channel::tick, select};
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
-use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::{
     endorsement::SecureShareEndorsement,
@@ -21,9 +20,14 @@ use tracing::{debug, info, warn};
 use crate::{
     handlers::{
         endorsement_handler::messages::EndorsementMessage,
-        peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+        peer_handler::{
+            models::{PeerManagementCmd, PeerMessageTuple},
+            SharedPeerQuarantine,
+        },
     },
+    propagation_lanes::{PropagationLane, PropagationLanes, SharedPropagationLanes},
     sig_verifier::verify_sigs_batch,
+    trace::{ProtocolTracer, SharedProtocolTracer},
 };
 
 use super::{
@@ -38,6 +42,9 @@ pub struct RetrievalThread {
     receiver_ext: MassaReceiver<EndorsementHandlerRetrievalCommand>,
     cache: SharedEndorsementCache,
     internal_sender: MassaSender<EndorsementHandlerPropagationCommand>,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
+    quarantine: SharedPeerQuarantine,
     selector_controller: Box<dyn SelectorController>,
     pool_controller: Box<dyn PoolController>,
     config: ProtocolConfig,
@@ -87,7 +94,7 @@ impl RetrievalThread {
                     let count = cache_lock
                         .endorsements_known_by_peer
                         .values()
-                        .map(|v| v.len())
+                        .map(|v| v.count())
                         .sum();
                     self.metrics
                         .set_endorsements_cache_metrics(cache_lock.checked_endorsements.len(), count);
@@ -125,7 +132,10 @@ impl RetrievalThread {
                     self.selector_controller.as_ref(),
                     &self.storage,
                     &self.config,
+                    !self.quarantine.is_quarantined(&peer_id),
                     &self.internal_sender,
+                    &self.propagation_lanes,
+                    &self.tracer,
                     self.pool_controller.as_mut(),
                 ) {
                     warn!(
@@ -134,25 +144,25 @@ impl RetrievalThread {
                         loss of sync between us and the remote node. Err = {}",
                         peer_id, err
                     );
-                    if let Err(err) = self.ban_peer(&peer_id) {
-                        warn!("Error while banning peer {} err: {:?}", peer_id, err);
+                    if let Err(err) = self.quarantine_peer(&peer_id) {
+                        warn!("Error while quarantining peer {} err: {:?}", peer_id, err);
                     }
                 }
             }
         }
     }
 
-    /// send a ban peer command to the peer handler
-    fn ban_peer(&mut self, peer_id: &PeerId) -> Result<(), ProtocolError> {
-        massa_trace!("ban node from retrieval thread", { "peer_id": peer_id.to_string() });
+    /// send a quarantine peer command to the peer handler, which bans the peer if it already was in quarantine
+    fn quarantine_peer(&mut self, peer_id: &PeerId) -> Result<(), ProtocolError> {
+        self.tracer.event("endorsement_retrieval.quarantine_peer");
         self.peer_cmd_sender
-            .try_send(PeerManagementCmd::Ban(vec![peer_id.clone()]))
+            .try_send(PeerManagementCmd::Quarantine(vec![peer_id.clone()]))
             .map_err(|err| ProtocolError::SendError(err.to_string()))
     }
 }
 
 /// Note endorsements coming from a given node,
-/// and propagate them when they were received outside of a header.
+/// and propagate them if `propagate` is set (it is not for the peers in quarantine).
 ///
 /// Caches knowledge of valid ones.
 ///
@@ -168,7 +178,10 @@ pub(crate) fn note_endorsements_from_peer(
     selector_controller: &dyn SelectorController,
     storage: &Storage,
     config: &ProtocolConfig,
+    propagate: bool,
     endorsement_propagation_sender: &MassaSender<EndorsementHandlerPropagationCommand>,
+    propagation_lanes: &PropagationLanes,
+    tracer: &ProtocolTracer,
     pool_controller: &mut dyn PoolController,
 ) -> Result<(), ProtocolError> {
     let mut new_endorsements = PreHashMap::with_capacity(endorsements.len());
@@ -266,13 +279,20 @@ pub(crate) fn note_endorsements_from_peer(
     endorsement_store.store_endorsements(new_endorsements.into_values().collect());
 
     // Propagate to other peers
-    if let Err(err) = endorsement_propagation_sender.try_send(
-        EndorsementHandlerPropagationCommand::PropagateEndorsements(endorsement_store.clone()),
-    ) {
-        warn!(
-            "Failed to send from retrieval thread of endorsement handler to propagation: {:?}",
-            err
-        );
+    if propagate {
+        let command =
+            EndorsementHandlerPropagationCommand::PropagateEndorsements(endorsement_store.clone());
+        let trace_name = command.trace_name();
+        let sequence = tracer.enqueue(trace_name);
+        propagation_lanes.enqueue(PropagationLane::Endorsements);
+        if let Err(err) = endorsement_propagation_sender.try_send(command) {
+            tracer.dropped(trace_name, sequence);
+            propagation_lanes.done(PropagationLane::Endorsements);
+            warn!(
+                "Failed to send from retrieval thread of endorsement handler to propagation: {:?}",
+                err
+            );
+        }
     }
 
     // Add to pool
@@ -286,6 +306,9 @@ pub fn start_retrieval_thread(
     receiver: MassaReceiver<PeerMessageTuple>,
     receiver_ext: MassaReceiver<EndorsementHandlerRetrievalCommand>,
     internal_sender: MassaSender<EndorsementHandlerPropagationCommand>,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
+    quarantine: SharedPeerQuarantine,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     cache: SharedEndorsementCache,
     selector_controller: Box<dyn SelectorController>,
@@ -309,6 +332,9 @@ pub fn start_retrieval_thread(
                 peer_cmd_sender,
                 cache,
                 internal_sender,
+                propagation_lanes,
+                tracer,
+                quarantine,
                 selector_controller,
                 pool_controller,
                 config,
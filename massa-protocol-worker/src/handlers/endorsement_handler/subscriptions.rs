@@ -0,0 +1,158 @@
+//! Endorsement subscriptions of the peers.
+//!
+//! A node may only want the endorsements of some threads, or none at all (explorers, for example).
+//! It announces it during the handshake with a flag of the capabilities byte,
+//! followed by the mask of the threads it subscribes to (one bit per thread, little endian u64).
+//! The peers that did not announce any subscription receive the endorsements of all the threads.
+//!
+//! The endorsements included in the block headers are not affected.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use massa_protocol_exports::{PeerId, ProtocolConfig};
+use parking_lot::RwLock;
+
+/// Flag of the handshake capabilities byte telling that a thread mask follows
+pub(crate) const HANDSHAKE_FLAG_ENDORSEMENT_SUBSCRIPTION: u8 = 0b10;
+
+/// Size of the thread mask following the capabilities byte
+const THREAD_MASK_SIZE: usize = 8;
+
+pub struct EndorsementSubscriptions {
+    /// threads whose endorsements our node wants, None for all of them
+    own_thread_mask: Option<u64>,
+    /// threads whose endorsements the peers want, absent for the peers wanting all of them
+    peers: RwLock<HashMap<PeerId, u64>>,
+}
+
+impl EndorsementSubscriptions {
+    pub fn new(config: &ProtocolConfig) -> Self {
+        Self {
+            own_thread_mask: config
+                .endorsement_subscription_threads
+                .as_ref()
+                .map(|threads| {
+                    threads.iter().fold(0, |mask, thread| {
+                        mask | 1u64.checked_shl(*thread as u32).unwrap_or(0)
+                    })
+                }),
+            peers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Flags of the handshake capabilities byte
+    pub fn handshake_flags(&self) -> u8 {
+        if self.own_thread_mask.is_some() {
+            HANDSHAKE_FLAG_ENDORSEMENT_SUBSCRIPTION
+        } else {
+            0
+        }
+    }
+
+    /// Writes the thread mask following the capabilities byte, if our node subscribes to some threads only
+    pub fn write_handshake_extension(&self, bytes: &mut Vec<u8>) {
+        if let Some(mask) = self.own_thread_mask {
+            bytes.extend_from_slice(&mask.to_le_bytes());
+        }
+    }
+
+    /// Records the subscription announced by a peer in the handshake.
+    /// `capabilities` are the bytes following the announcement, empty for the nodes not knowing them.
+    pub fn on_handshake(&self, peer_id: &PeerId, capabilities: &[u8]) {
+        let announced = capabilities.first().map_or(false, |flags| {
+            flags & HANDSHAKE_FLAG_ENDORSEMENT_SUBSCRIPTION != 0
+        });
+        let mask = capabilities
+            .get(1..1 + THREAD_MASK_SIZE)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes);
+        match mask {
+            Some(mask) if announced => {
+                self.peers.write().insert(peer_id.clone(), mask);
+            }
+            _ => {
+                self.peers.write().remove(peer_id);
+            }
+        }
+    }
+
+    /// Forgets the peers that are not connected anymore
+    pub fn retain_peers(&self, connected: &HashSet<PeerId>) {
+        self.peers
+            .write()
+            .retain(|peer_id, _| connected.contains(peer_id));
+    }
+
+    /// Thread masks of the peers that subscribed to some threads only
+    pub fn get_peer_masks(&self) -> HashMap<PeerId, u64> {
+        self.peers.read().clone()
+    }
+}
+
+/// Whether a peer with the subscription `mask` (None for all threads) wants the endorsements of `thread`
+pub(crate) fn is_subscribed(mask: Option<&u64>, thread: u8) -> bool {
+    match mask {
+        Some(mask) => 1u64
+            .checked_shl(thread as u32)
+            .map_or(false, |bit| mask & bit != 0),
+        None => true,
+    }
+}
+
+pub type SharedEndorsementSubscriptions = Arc<EndorsementSubscriptions>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_endorsement_subscriptions() {
+        let config = ProtocolConfig {
+            endorsement_subscription_threads: Some(vec![1, 3]),
+            ..Default::default()
+        };
+        let subscriptions = EndorsementSubscriptions::new(&config);
+        let mut capabilities = vec![subscriptions.handshake_flags()];
+        subscriptions.write_handshake_extension(&mut capabilities);
+        assert_eq!(capabilities.len(), 1 + THREAD_MASK_SIZE);
+
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        subscriptions.on_handshake(&peer_id, &capabilities);
+        let masks = subscriptions.get_peer_masks();
+        assert!(is_subscribed(masks.get(&peer_id), 1));
+        assert!(is_subscribed(masks.get(&peer_id), 3));
+        assert!(!is_subscribed(masks.get(&peer_id), 0));
+
+        // subscription to no thread at all
+        subscriptions.on_handshake(
+            &peer_id,
+            &[
+                HANDSHAKE_FLAG_ENDORSEMENT_SUBSCRIPTION,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ],
+        );
+        let masks = subscriptions.get_peer_masks();
+        assert!((0..32).all(|thread| !is_subscribed(masks.get(&peer_id), thread)));
+
+        // a peer not announcing any subscription wants everything
+        subscriptions.on_handshake(&peer_id, &[]);
+        assert!(subscriptions.get_peer_masks().is_empty());
+        assert!(is_subscribed(None, 5));
+
+        // disconnected peers are forgotten
+        subscriptions.on_handshake(&peer_id, &capabilities);
+        subscriptions.retain_peers(&HashSet::new());
+        assert!(subscriptions.get_peer_masks().is_empty());
+    }
+}
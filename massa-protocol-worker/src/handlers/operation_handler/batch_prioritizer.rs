@@ -0,0 +1,97 @@
+//! Order in which the operations of a propagation batch are announced.
+//!
+//! The announcements of a batch are split into several messages per peer,
+//! so the first operations of the batch reach the peers first.
+//! The default policy announces the operations paying the highest fee per byte first,
+//! so that the peers learn about the most valuable operations before the others.
+
+use std::cmp::Ordering;
+
+use massa_models::{operation::OperationId, prehash::PreHashSet};
+use massa_storage::Storage;
+
+/// Policy ordering the operations of a propagation batch
+pub trait BatchPrioritizer: Send {
+    /// Returns the operations of `batch`, the ones to announce first coming first.
+    /// `storage` holds the operations kept for propagation.
+    fn prioritize(&self, batch: PreHashSet<OperationId>, storage: &Storage) -> Vec<OperationId>;
+}
+
+/// Announces the operations by decreasing fee per byte.
+/// The operations missing from the storage are announced last.
+pub struct FeeDensityPrioritizer;
+
+impl BatchPrioritizer for FeeDensityPrioritizer {
+    fn prioritize(&self, batch: PreHashSet<OperationId>, storage: &Storage) -> Vec<OperationId> {
+        // (fee, size) of each operation
+        let mut operations: Vec<(OperationId, Option<(u64, usize)>)> = {
+            let stored_ops = storage.read_operations();
+            batch
+                .into_iter()
+                .map(|op_id| {
+                    let fee_and_size = stored_ops
+                        .get(&op_id)
+                        .map(|op| (op.content.fee.to_raw(), op.serialized_size().max(1)));
+                    (op_id, fee_and_size)
+                })
+                .collect()
+        };
+        operations.sort_unstable_by(|(_, a), (_, b)| match (a, b) {
+            // compare fee_a / size_a and fee_b / size_b without rounding
+            (Some((fee_a, size_a)), Some((fee_b, size_b))) => {
+                (*fee_b as u128 * *size_a as u128).cmp(&(*fee_a as u128 * *size_b as u128))
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        operations.into_iter().map(|(op_id, _)| op_id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::{
+        address::Address,
+        amount::Amount,
+        operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
+        secure_share::SecureShareContent,
+    };
+    use massa_signature::KeyPair;
+
+    fn create_operation(fee: u64, data_size: usize) -> SecureShareOperation {
+        let keypair = KeyPair::generate(0).unwrap();
+        let content = Operation {
+            fee: Amount::from_raw(fee),
+            op: OperationType::ExecuteSC {
+                data: vec![0; data_size],
+                max_gas: 0,
+                max_coins: Amount::zero(),
+                datastore: Default::default(),
+            },
+            expire_period: 10,
+        };
+        Operation::new_verifiable(content, OperationSerializer::new(), &keypair).unwrap()
+    }
+
+    #[test]
+    fn test_fee_density_prioritizer() {
+        let mut storage = Storage::create_root();
+        // same fee, the smaller operation first
+        let small = create_operation(1000, 10);
+        let large = create_operation(1000, 1000);
+        // higher fee but much larger
+        let larger = create_operation(2000, 10000);
+        let unknown = create_operation(1_000_000, 10).id;
+        storage.store_operations(vec![large.clone(), larger.clone(), small.clone()]);
+
+        let batch: PreHashSet<OperationId> = [unknown, larger.id, large.id, small.id]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            FeeDensityPrioritizer.prioritize(batch, &storage),
+            vec![small.id, large.id, larger.id, unknown]
+        );
+    }
+}
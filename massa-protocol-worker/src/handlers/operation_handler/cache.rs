@@ -1,9 +1,14 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::Instant,
 };
 
-use massa_models::operation::{OperationId, OperationPrefixId};
+use massa_models::{
+    address::Address,
+    operation::{OperationId, OperationPrefixId},
+    prehash::PreHashMap,
+};
 use massa_protocol_exports::PeerId;
 use parking_lot::RwLock;
 use schnellru::{ByLength, LruMap};
@@ -18,6 +23,8 @@ pub struct OperationCache {
     pub ops_known_by_peer: HashMap<PeerId, LruMap<OperationPrefixId, ()>>,
     /// Maximum number of operations known by a peer
     pub max_known_ops_by_peer: u32,
+    /// Creator addresses whose operations are ignored, with the expiry instant of their ban
+    pub banned_op_sources: PreHashMap<Address, Instant>,
 }
 
 impl OperationCache {
@@ -28,9 +35,30 @@ impl OperationCache {
             checked_operations_prefix: LruMap::new(ByLength::new(max_known_ops)),
             ops_known_by_peer: HashMap::new(),
             max_known_ops_by_peer,
+            banned_op_sources: PreHashMap::default(),
+        }
+    }
+
+    /// Ban the operations created by a list of addresses until a given instant
+    pub fn ban_op_sources(&mut self, addresses: &[Address], until: Instant) {
+        for address in addresses {
+            let expiry = self.banned_op_sources.entry(*address).or_insert(until);
+            *expiry = (*expiry).max(until);
         }
     }
 
+    /// Check whether the operations created by an address are currently banned
+    pub fn is_op_source_banned(&self, address: &Address, now: Instant) -> bool {
+        self.banned_op_sources
+            .get(address)
+            .map_or(false, |expiry| *expiry > now)
+    }
+
+    /// Remove the bans that expired
+    pub fn prune_banned_op_sources(&mut self, now: Instant) {
+        self.banned_op_sources.retain(|_, expiry| *expiry > now);
+    }
+
     /// Mark a list of operation ID prefixes as known by a peer
     pub fn insert_peer_known_ops(&mut self, peer_id: &PeerId, ops: &[OperationPrefixId]) {
         let known_ops = self
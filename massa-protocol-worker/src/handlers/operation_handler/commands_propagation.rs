@@ -1,3 +1,4 @@
+use massa_models::address::Address;
 use massa_storage::Storage;
 
 #[derive(Clone)]
@@ -5,4 +6,21 @@ pub enum OperationHandlerPropagationCommand {
     Stop,
     /// operations ids
     PropagateOperations(Storage),
+    /// temporarily ignore the operations created by these addresses
+    BanAddressSources(Vec<Address>),
+}
+
+impl OperationHandlerPropagationCommand {
+    /// Name of the command in the protocol trace
+    pub fn trace_name(&self) -> &'static str {
+        match self {
+            OperationHandlerPropagationCommand::Stop => "operation_propagation.stop",
+            OperationHandlerPropagationCommand::PropagateOperations(_) => {
+                "operation_propagation.propagate_operations"
+            }
+            OperationHandlerPropagationCommand::BanAddressSources(_) => {
+                "operation_propagation.ban_address_sources"
+            }
+        }
+    }
 }
@@ -1,4 +1,24 @@
+use std::collections::HashMap;
+
+use massa_channel::sender::MassaSender;
+use massa_protocol_exports::{OperationBatchStats, PeerId};
+
 #[derive(Clone)]
 pub enum OperationHandlerRetrievalCommand {
+    GetBatchStats {
+        responder: MassaSender<HashMap<PeerId, OperationBatchStats>>,
+    },
     Stop,
 }
+
+impl OperationHandlerRetrievalCommand {
+    /// Name of the command in the protocol trace
+    pub fn trace_name(&self) -> &'static str {
+        match self {
+            OperationHandlerRetrievalCommand::GetBatchStats { .. } => {
+                "operation_retrieval.get_batch_stats"
+            }
+            OperationHandlerRetrievalCommand::Stop => "operation_retrieval.stop",
+        }
+    }
+}
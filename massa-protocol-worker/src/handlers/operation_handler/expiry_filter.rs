@@ -0,0 +1,204 @@
+//! Filter dropping the operations received from peers long after their expiry.
+//!
+//! An operation whose expiry period is more than `operation_expiry_horizon` periods in the past
+//! can no longer be included in a block, so it is dropped before being checked or sent to the pool.
+//! The prefixes of the dropped operations are remembered so that their re-announcements
+//! are ignored instead of being asked again.
+
+use std::collections::HashMap;
+
+use massa_models::{
+    operation::{OperationPrefixId, OperationPrefixIds, SecureShareOperation},
+    slot::Slot,
+    timeslots::get_latest_block_slot_at_timestamp,
+};
+use massa_protocol_exports::{OperationBatchStats, PeerId, ProtocolConfig};
+use massa_time::MassaTime;
+use schnellru::{ByLength, LruMap};
+
+/// Maximum number of stale operation prefixes remembered
+const STALE_OPERATION_PREFIXES_CAPACITY: u32 = 100_000;
+
+/// Handle giving the current slot of the consensus
+#[derive(Clone)]
+pub(crate) struct ConsensusClock {
+    thread_count: u8,
+    t0: MassaTime,
+    genesis_timestamp: MassaTime,
+}
+
+impl ConsensusClock {
+    pub(crate) fn new(config: &ProtocolConfig) -> Self {
+        Self {
+            thread_count: config.thread_count,
+            t0: config.t0,
+            genesis_timestamp: config.genesis_timestamp,
+        }
+    }
+
+    /// Latest slot at `timestamp`, `None` before genesis
+    pub(crate) fn slot_at(&self, timestamp: MassaTime) -> Option<Slot> {
+        get_latest_block_slot_at_timestamp(
+            self.thread_count,
+            self.t0,
+            self.genesis_timestamp,
+            timestamp,
+        )
+        .ok()
+        .flatten()
+    }
+
+    /// Latest slot at the current time, `None` before genesis
+    pub(crate) fn current_slot(&self) -> Option<Slot> {
+        self.slot_at(MassaTime::now().ok()?)
+    }
+}
+
+/// Stale operation counts of a peer
+#[derive(Default)]
+struct StaleCounts {
+    operations: u64,
+    announcements: u64,
+}
+
+pub(crate) struct OperationExpiryFilter {
+    clock: ConsensusClock,
+    thread_count: u8,
+    /// number of periods after their expiry period after which the operations are dropped
+    horizon: u64,
+    stale_prefixes: LruMap<OperationPrefixId, ()>,
+    counts: HashMap<PeerId, StaleCounts>,
+}
+
+impl OperationExpiryFilter {
+    pub(crate) fn new(clock: ConsensusClock, config: &ProtocolConfig) -> Self {
+        Self {
+            clock,
+            thread_count: config.thread_count,
+            horizon: config.operation_expiry_horizon,
+            stale_prefixes: LruMap::new(ByLength::new(STALE_OPERATION_PREFIXES_CAPACITY)),
+            counts: HashMap::new(),
+        }
+    }
+
+    fn is_stale(&self, operation: &SecureShareOperation, current_slot: Slot) -> bool {
+        let last_slot = Slot::new(
+            operation.content.expire_period.saturating_add(self.horizon),
+            operation
+                .content_creator_address
+                .get_thread(self.thread_count),
+        );
+        last_slot < current_slot
+    }
+
+    /// Removes the stale operations sent by `peer_id` and remembers their prefixes
+    pub(crate) fn filter_operations(
+        &mut self,
+        operations: &mut Vec<SecureShareOperation>,
+        peer_id: &PeerId,
+    ) {
+        let Some(current_slot) = self.clock.current_slot() else {
+            return;
+        };
+        self.filter_operations_at(operations, peer_id, current_slot);
+    }
+
+    fn filter_operations_at(
+        &mut self,
+        operations: &mut Vec<SecureShareOperation>,
+        peer_id: &PeerId,
+        current_slot: Slot,
+    ) {
+        let count_before = operations.len();
+        let mut stale_prefixes = Vec::new();
+        operations.retain(|operation| {
+            if self.is_stale(operation, current_slot) {
+                stale_prefixes.push(operation.id.into_prefix());
+                false
+            } else {
+                true
+            }
+        });
+        for prefix in stale_prefixes {
+            self.stale_prefixes.insert(prefix, ());
+        }
+        let stale_count = (count_before - operations.len()) as u64;
+        if stale_count > 0 {
+            self.counts.entry(peer_id.clone()).or_default().operations += stale_count;
+        }
+    }
+
+    /// Removes from `announcement` the operations already dropped as stale
+    pub(crate) fn filter_announcement(
+        &mut self,
+        announcement: &mut OperationPrefixIds,
+        peer_id: &PeerId,
+    ) {
+        let count_before = announcement.len();
+        announcement.retain(|prefix| self.stale_prefixes.peek(prefix).is_none());
+        let stale_count = (count_before - announcement.len()) as u64;
+        if stale_count > 0 {
+            self.counts
+                .entry(peer_id.clone())
+                .or_default()
+                .announcements += stale_count;
+        }
+    }
+
+    /// Adds the stale counts of each peer to `stats`
+    pub(crate) fn add_stats(&self, stats: &mut HashMap<PeerId, OperationBatchStats>) {
+        for (peer_id, counts) in &self.counts {
+            let peer_stats = stats.entry(peer_id.clone()).or_default();
+            peer_stats.stale_operations += counts.operations;
+            peer_stats.stale_announcements += counts.announcements;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::{
+        amount::Amount,
+        operation::{Operation, OperationSerializer, OperationType},
+        secure_share::SecureShareContent,
+    };
+    use massa_signature::KeyPair;
+
+    fn create_operation(expire_period: u64) -> SecureShareOperation {
+        let keypair = KeyPair::generate(0).unwrap();
+        let content = Operation {
+            fee: Amount::zero(),
+            op: OperationType::RollBuy { roll_count: 1 },
+            expire_period,
+        };
+        Operation::new_verifiable(content, OperationSerializer::new(), &keypair).unwrap()
+    }
+
+    #[test]
+    fn test_operation_expiry_filter() {
+        let config = ProtocolConfig::default();
+        let mut filter = OperationExpiryFilter::new(ConsensusClock::new(&config), &config);
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let current_slot = Slot::new(10 + config.operation_expiry_horizon + 1, 0);
+
+        let stale = create_operation(9);
+        let fresh = create_operation(20);
+        let mut operations = vec![stale.clone(), fresh.clone()];
+        filter.filter_operations_at(&mut operations, &peer_id, current_slot);
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0].id, fresh.id);
+
+        let mut announcement: OperationPrefixIds = [stale.id.into_prefix(), fresh.id.into_prefix()]
+            .into_iter()
+            .collect();
+        filter.filter_announcement(&mut announcement, &peer_id);
+        assert_eq!(announcement.len(), 1);
+        assert!(announcement.contains(&fresh.id.into_prefix()));
+
+        let mut stats = HashMap::new();
+        filter.add_stats(&mut stats);
+        assert_eq!(stats[&peer_id].stale_operations, 1);
+        assert_eq!(stats[&peer_id].stale_announcements, 1);
+    }
+}
@@ -6,7 +6,10 @@ use massa_pool_exports::PoolController;
 use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
 
-use crate::wrap_network::ActiveConnectionsTrait;
+use crate::{
+    peer_scores::SharedPeerScores, propagation_lanes::SharedPropagationLanes,
+    shutdown::ShutdownDrain, trace::SharedProtocolTracer, wrap_network::ActiveConnectionsTrait,
+};
 
 use self::{
     cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
@@ -14,17 +17,25 @@ use self::{
     retrieval::start_retrieval_thread,
 };
 
+mod batch_prioritizer;
 pub mod cache;
 pub mod commands_propagation;
 pub mod commands_retrieval;
+mod expiry_filter;
 mod messages;
+mod pool_submission;
 mod propagation;
 mod retrieval;
 
-pub(crate) use messages::{OperationMessage, OperationMessageSerializer};
+pub(crate) use messages::{
+    MessageTypeId as OperationMessageTypeId, OperationMessage, OperationMessageSerializer,
+};
 pub(crate) use retrieval::note_operations_from_peer;
 
-use super::peer_handler::models::{PeerManagementCmd, PeerMessageTuple};
+use super::peer_handler::{
+    models::{PeerManagementCmd, PeerMessageTuple},
+    SharedPeerQuarantine,
+};
 
 pub struct OperationHandler {
     pub operation_retrieval_thread: Option<(
@@ -51,6 +62,10 @@ impl OperationHandler {
         local_sender: MassaSender<OperationHandlerPropagationCommand>,
         local_receiver: MassaReceiver<OperationHandlerPropagationCommand>,
         peer_cmd_sender: MassaSender<PeerManagementCmd>,
+        peer_scores: SharedPeerScores,
+        quarantine: SharedPeerQuarantine,
+        propagation_lanes: SharedPropagationLanes,
+        tracer: SharedProtocolTracer,
         massa_metrics: MassaMetrics,
     ) -> Self {
         let operation_retrieval_thread = start_retrieval_thread(
@@ -63,6 +78,9 @@ impl OperationHandler {
             receiver_retrieval_ext,
             local_sender.clone(),
             peer_cmd_sender,
+            peer_scores,
+            quarantine,
+            tracer.clone(),
             massa_metrics.clone(),
         );
 
@@ -72,6 +90,8 @@ impl OperationHandler {
             config,
             cache,
             storage.clone_without_refs(),
+            propagation_lanes,
+            tracer,
             massa_metrics,
         );
         Self {
@@ -80,14 +100,19 @@ impl OperationHandler {
         }
     }
 
-    pub fn stop(&mut self) {
+    /// Stops receiving messages and commands
+    pub fn stop_retrieval(&mut self, drain: &mut ShutdownDrain) {
         if let Some((tx, thread)) = self.operation_retrieval_thread.take() {
             let _ = tx.send(OperationHandlerRetrievalCommand::Stop);
-            thread.join().unwrap();
+            drain.join(thread);
         }
+    }
+
+    /// Stops the propagation once the propagation commands queued before are processed
+    pub fn stop_propagation(&mut self, drain: &mut ShutdownDrain) {
         if let Some((tx, thread)) = self.operation_propagation_thread.take() {
             let _ = tx.send(OperationHandlerPropagationCommand::Stop);
-            thread.join().unwrap();
+            drain.join(thread);
         }
     }
 }
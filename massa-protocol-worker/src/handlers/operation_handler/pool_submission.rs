@@ -0,0 +1,150 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::mpsc::{channel, Receiver, TryRecvError},
+    time::Instant,
+};
+
+use massa_pool_exports::PoolController;
+use massa_protocol_exports::{OperationBatchStats, PeerId, ProtocolConfig};
+use massa_storage::Storage;
+use tracing::{debug, warn};
+
+/// A batch of operations received from a peer and waiting to be submitted to the pool
+struct PendingOperationBatch {
+    /// operations of the batch
+    operations: Storage,
+    /// peer that sent us the operations
+    peer_id: PeerId,
+    /// number of submissions refused by the pool
+    attempts: u32,
+    /// instant of the next submission attempt
+    next_attempt: Instant,
+}
+
+/// Submits the operations received from the network to the pool.
+///
+/// Batches refused by a saturated pool are kept and submitted again after a delay
+/// instead of being silently dropped, and the number of batches submitted to the pool
+/// and not yet acknowledged is bounded so that a slow pool slows down the intake.
+pub struct OperationBatchSubmitter {
+    pool_controller: Box<dyn PoolController>,
+    /// batches waiting to be submitted, in reception order
+    pending: VecDeque<PendingOperationBatch>,
+    /// acknowledgment receivers of the batches submitted to the pool
+    in_flight: VecDeque<(Receiver<()>, PeerId)>,
+    /// submission statistics per peer
+    stats: HashMap<PeerId, OperationBatchStats>,
+    config: ProtocolConfig,
+}
+
+impl OperationBatchSubmitter {
+    pub fn new(pool_controller: Box<dyn PoolController>, config: ProtocolConfig) -> Self {
+        Self {
+            pool_controller,
+            pending: VecDeque::new(),
+            in_flight: VecDeque::new(),
+            stats: HashMap::new(),
+            config,
+        }
+    }
+
+    /// Queues a batch of operations received from `peer_id` and tries to submit the pending batches
+    pub fn submit(&mut self, operations: Storage, peer_id: &PeerId) {
+        let operation_count = operations.get_op_refs().len() as u64;
+        let stats = self.stats.entry(peer_id.clone()).or_default();
+        if self.pending.len() >= self.config.max_pending_pool_operation_batches {
+            warn!(
+                "Too many operation batches waiting for the pool, dropping {} operations from peer {}",
+                operation_count, peer_id
+            );
+            stats.dropped_batches += 1;
+            stats.dropped_operations += operation_count;
+            return;
+        }
+        stats.submitted_batches += 1;
+        stats.submitted_operations += operation_count;
+        self.pending.push_back(PendingOperationBatch {
+            operations,
+            peer_id: peer_id.clone(),
+            attempts: 0,
+            next_attempt: Instant::now(),
+        });
+        self.process();
+    }
+
+    /// Collects the acknowledgments of the pool and submits the pending batches that are ready
+    pub fn process(&mut self) {
+        self.collect_acknowledgments();
+
+        let now = Instant::now();
+        let mut not_ready = VecDeque::new();
+        while self.in_flight.len() < self.config.max_in_flight_pool_operation_batches {
+            let Some(mut batch) = self.pending.pop_front() else {
+                break;
+            };
+            if batch.next_attempt > now {
+                not_ready.push_back(batch);
+                continue;
+            }
+            let (ack_sender, ack_receiver) = channel();
+            match self
+                .pool_controller
+                .try_add_operations(batch.operations, Some(ack_sender))
+            {
+                Ok(()) => self.in_flight.push_back((ack_receiver, batch.peer_id)),
+                Err(operations) => {
+                    batch.operations = operations;
+                    batch.attempts += 1;
+                    let stats = self.stats.entry(batch.peer_id.clone()).or_default();
+                    if batch.attempts > self.config.max_pool_operation_batch_retries {
+                        warn!(
+                            "Pool saturated, dropping a batch of operations from peer {} after {} retries",
+                            batch.peer_id,
+                            self.config.max_pool_operation_batch_retries
+                        );
+                        stats.dropped_batches += 1;
+                        stats.dropped_operations += batch.operations.get_op_refs().len() as u64;
+                        continue;
+                    }
+                    debug!(
+                        "Pool saturated, retrying later a batch of operations from peer {}",
+                        batch.peer_id
+                    );
+                    stats.retried_batches += 1;
+                    batch.next_attempt =
+                        now + self.config.pool_operation_batch_retry_delay.to_duration();
+                    not_ready.push_back(batch);
+                    // the pool is saturated, there is no point in submitting the next batches now
+                    break;
+                }
+            }
+        }
+        // keep the reception order of the batches that were not submitted
+        while let Some(batch) = not_ready.pop_back() {
+            self.pending.push_front(batch);
+        }
+    }
+
+    /// Removes the acknowledged batches from the in-flight ones
+    fn collect_acknowledgments(&mut self) {
+        let stats = &mut self.stats;
+        self.in_flight
+            .retain(|(ack_receiver, peer_id)| match ack_receiver.try_recv() {
+                Ok(()) => {
+                    stats
+                        .entry(peer_id.clone())
+                        .or_default()
+                        .acknowledged_batches += 1;
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+                // the pool dropped the batch without acknowledging it (e.g. on shutdown)
+                Err(TryRecvError::Disconnected) => false,
+            });
+    }
+
+    /// Returns the submission statistics of each peer
+    pub fn get_stats(&self) -> HashMap<PeerId, OperationBatchStats> {
+        self.stats.clone()
+    }
+}
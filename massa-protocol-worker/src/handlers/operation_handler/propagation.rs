@@ -3,7 +3,6 @@ use std::{mem, thread::JoinHandle};
 
 use crossbeam::channel::RecvTimeoutError;
 use massa_channel::receiver::MassaReceiver;
-use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::operation::OperationId;
 use massa_models::prehash::CapacityAllocator;
@@ -15,12 +14,17 @@ use massa_storage::Storage;
 use tracing::{debug, info, log::warn};
 
 use crate::{
-    handlers::operation_handler::OperationMessage, messages::MessagesSerializer,
+    handlers::operation_handler::OperationMessage,
+    messages::MessagesSerializer,
+    propagation_lanes::{PropagationLane, SharedPropagationLanes},
+    trace::SharedProtocolTracer,
     wrap_network::ActiveConnectionsTrait,
 };
 
 use super::{
-    cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
+    batch_prioritizer::{BatchPrioritizer, FeeDensityPrioritizer},
+    cache::SharedOperationCache,
+    commands_propagation::OperationHandlerPropagationCommand,
     OperationMessageSerializer,
 };
 
@@ -31,9 +35,13 @@ struct PropagationThread {
     stored_for_propagation: VecDeque<(std::time::Instant, PreHashSet<OperationId>)>,
     op_storage: Storage,
     next_batch: PreHashSet<OperationId>,
+    /// order in which the operations of a batch are announced
+    batch_prioritizer: Box<dyn BatchPrioritizer>,
     config: ProtocolConfig,
     cache: SharedOperationCache,
     operation_message_serializer: MessagesSerializer,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
     _massa_metrics: MassaMetrics,
 }
 
@@ -45,8 +53,9 @@ impl PropagationThread {
         loop {
             match self.internal_receiver.recv_deadline(batch_deadline) {
                 Ok(internal_message) => {
+                    self.tracer.dequeue(internal_message.trace_name());
                     match internal_message {
-                        OperationHandlerPropagationCommand::PropagateOperations(operations) => {
+                        OperationHandlerPropagationCommand::PropagateOperations(mut operations) => {
                             // Note operations as checked.
                             {
                                 let mut cache_write = self.cache.write();
@@ -55,6 +64,10 @@ impl PropagationThread {
                                 }
                             }
 
+                            // do not propagate the operations of banned sources
+                            let banned_ops = self.get_banned_sources_ops(&operations);
+                            operations.drop_operation_refs(&banned_ops);
+
                             // add to propagation storage
                             let new_ops = operations.get_op_refs().clone();
                             self.stored_for_propagation
@@ -78,6 +91,19 @@ impl PropagationThread {
                                 }
                             }
                         }
+                        OperationHandlerPropagationCommand::BanAddressSources(addresses) => {
+                            let until = std::time::Instant::now()
+                                .checked_add(
+                                    self.config.operation_source_ban_duration.to_duration(),
+                                )
+                                .expect("Can't compute operation source ban expiry");
+                            debug!("Ban operations from {} source addresses", addresses.len());
+                            self.cache.write().ban_op_sources(&addresses, until);
+                            // stop propagating the operations of those sources we already hold
+                            let banned_ops = self.get_banned_sources_ops(&self.op_storage);
+                            self.op_storage.drop_operation_refs(&banned_ops);
+                            self.next_batch.retain(|op_id| !banned_ops.contains(op_id));
+                        }
                         OperationHandlerPropagationCommand::Stop => {
                             info!("Stop operation propagation thread");
                             return;
@@ -97,6 +123,26 @@ impl PropagationThread {
         }
     }
 
+    /// Get the operations of `operations` created by a banned source address
+    fn get_banned_sources_ops(&self, operations: &Storage) -> PreHashSet<OperationId> {
+        let now = std::time::Instant::now();
+        let cache_read = self.cache.read();
+        if cache_read.banned_op_sources.is_empty() {
+            return PreHashSet::default();
+        }
+        let stored_ops = operations.read_operations();
+        operations
+            .get_op_refs()
+            .iter()
+            .filter(|op_id| {
+                stored_ops.get(op_id).map_or(false, |op| {
+                    cache_read.is_op_source_banned(&op.content_creator_address, now)
+                })
+            })
+            .copied()
+            .collect()
+    }
+
     /// Prune the list of operations kept for propagation.
     fn prune_propagation_storage(&mut self) {
         let mut removed = PreHashSet::default();
@@ -142,54 +188,68 @@ impl PropagationThread {
         if self.next_batch.is_empty() {
             return;
         }
-        let operation_ids = mem::take(&mut self.next_batch);
-        massa_trace!("protocol.protocol_worker.announce_ops.begin", {
-            "operation_ids": operation_ids
-        });
-        {
+        // the operations announced first reach the peers first
+        let operation_ids = self
+            .batch_prioritizer
+            .prioritize(mem::take(&mut self.next_batch), &self.op_storage);
+        self.tracer
+            .event("operation_propagation.announce_operations");
+        // select the operations to announce to each peer
+        let announcements: Vec<(PeerId, Vec<OperationId>)> = {
             let mut cache_write = self.cache.write();
             let peers_connected = self.active_connections.get_peer_ids_connected();
             cache_write.update_cache(&peers_connected);
+            cache_write.prune_banned_op_sources(std::time::Instant::now());
 
-            // Propagate to peers
             let all_keys: Vec<PeerId> = cache_write.ops_known_by_peer.keys().cloned().collect();
-            for peer_id in all_keys {
-                let ops = cache_write.ops_known_by_peer.get_mut(&peer_id).unwrap();
-                let new_ops: Vec<OperationId> = operation_ids
-                    .iter()
-                    .filter(|id| ops.peek(&id.prefix()).is_none())
-                    .copied()
-                    .collect();
-                if !new_ops.is_empty() {
+            all_keys
+                .into_iter()
+                .filter_map(|peer_id| {
+                    let ops = cache_write.ops_known_by_peer.get_mut(&peer_id).unwrap();
+                    let new_ops: Vec<OperationId> = operation_ids
+                        .iter()
+                        .filter(|id| ops.peek(&id.prefix()).is_none())
+                        .copied()
+                        .collect();
+                    if new_ops.is_empty() {
+                        return None;
+                    }
                     for id in &new_ops {
                         ops.insert(id.prefix(), ());
                     }
-                    debug!(
-                        "Send operations announcement of len {} to {}",
-                        new_ops.len(),
-                        peer_id
+                    Some((peer_id, new_ops))
+                })
+                .collect()
+        };
+
+        // Propagate to peers, without holding the cache lock
+        for (peer_id, new_ops) in announcements {
+            debug!(
+                "Send operations announcement of len {} to {}",
+                new_ops.len(),
+                peer_id
+            );
+            for sub_list in new_ops.chunks(self.config.max_operations_per_message as usize) {
+                // let the pending block headers and endorsements go first
+                self.propagation_lanes
+                    .wait_for_higher_lanes(PropagationLane::Operations);
+                if let Err(err) = self.active_connections.send_to_peer(
+                    &peer_id,
+                    &self.operation_message_serializer,
+                    OperationMessage::OperationsAnnouncement(
+                        sub_list.iter().map(|id| id.into_prefix()).collect(),
+                    )
+                    .into(),
+                    false,
+                ) {
+                    warn!(
+                        "Failed to send OperationsAnnouncement message to peer: {}",
+                        err
                     );
-                    for sub_list in new_ops.chunks(self.config.max_operations_per_message as usize)
-                    {
-                        if let Err(err) = self.active_connections.send_to_peer(
-                            &peer_id,
-                            &self.operation_message_serializer,
-                            OperationMessage::OperationsAnnouncement(
-                                sub_list.iter().map(|id| id.into_prefix()).collect(),
-                            )
-                            .into(),
-                            false,
-                        ) {
-                            warn!(
-                                "Failed to send OperationsAnnouncement message to peer: {}",
-                                err
-                            );
-
-                            if let ProtocolError::PeerDisconnected(_) = err {
-                                // cache of this peer is removed in next call of cache_write.update_cache
-                                break;
-                            }
-                        }
+
+                    if let ProtocolError::PeerDisconnected(_) = err {
+                        // cache of this peer is removed in next call of cache_write.update_cache
+                        break;
                     }
                 }
             }
@@ -203,6 +263,8 @@ pub fn start_propagation_thread(
     config: ProtocolConfig,
     cache: SharedOperationCache,
     op_storage: Storage,
+    propagation_lanes: SharedPropagationLanes,
+    tracer: SharedProtocolTracer,
     massa_metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
@@ -220,8 +282,11 @@ pub fn start_propagation_thread(
                         .operation_announcement_buffer_capacity
                         .saturating_add(1),
                 ),
+                batch_prioritizer: Box::new(FeeDensityPrioritizer),
                 config,
                 cache,
+                propagation_lanes,
+                tracer,
                 _massa_metrics: massa_metrics,
                 operation_message_serializer: MessagesSerializer::new()
                     .with_operation_message_serializer(OperationMessageSerializer::new()),
@@ -2,7 +2,6 @@ use std::{collections::VecDeque, thread::JoinHandle, time::Instant};
 
 use crossbeam::{channel::tick, select};
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
-use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::{
     operation::{OperationPrefixId, OperationPrefixIds, SecureShareOperation},
@@ -20,9 +19,14 @@ use massa_time::{MassaTime, TimeError};
 use schnellru::{ByLength, LruMap};
 
 use crate::{
-    handlers::peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+    handlers::peer_handler::{
+        models::{PeerManagementCmd, PeerMessageTuple},
+        SharedPeerQuarantine,
+    },
     messages::MessagesSerializer,
+    peer_scores::SharedPeerScores,
     sig_verifier::verify_sigs_batch,
+    trace::{ProtocolTracer, SharedProtocolTracer},
     wrap_network::ActiveConnectionsTrait,
 };
 use tracing::{debug, info, warn};
@@ -31,7 +35,9 @@ use super::{
     cache::SharedOperationCache,
     commands_propagation::OperationHandlerPropagationCommand,
     commands_retrieval::OperationHandlerRetrievalCommand,
+    expiry_filter::{ConsensusClock, OperationExpiryFilter},
     messages::{OperationMessage, OperationMessageDeserializer, OperationMessageDeserializerArgs},
+    pool_submission::OperationBatchSubmitter,
     OperationMessageSerializer,
 };
 
@@ -49,7 +55,8 @@ pub struct OperationBatchItem {
 
 pub struct RetrievalThread {
     receiver: MassaReceiver<PeerMessageTuple>,
-    pool_controller: Box<dyn PoolController>,
+    pool_submitter: OperationBatchSubmitter,
+    expiry_filter: OperationExpiryFilter,
     cache: SharedOperationCache,
     asked_operations: LruMap<OperationPrefixId, (Instant, Vec<PeerId>)>,
     active_connections: Box<dyn ActiveConnectionsTrait>,
@@ -60,6 +67,9 @@ pub struct RetrievalThread {
     receiver_ext: MassaReceiver<OperationHandlerRetrievalCommand>,
     operation_message_serializer: MessagesSerializer,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_scores: SharedPeerScores,
+    quarantine: SharedPeerQuarantine,
+    tracer: SharedProtocolTracer,
     _massa_metrics: MassaMetrics,
 }
 
@@ -89,6 +99,7 @@ impl RetrievalThread {
                                     Ok((rest, message)) => (rest, message),
                                     Err(err) => {
                                         warn!("Error when deserializing message from peer {}: Err = {}", peer_id, err);
+                                        self.peer_scores.write().record_message(&peer_id, false);
                                         continue;
                                     }
                                 };
@@ -97,21 +108,40 @@ impl RetrievalThread {
                                 return;
                             }
                             match message {
-                                OperationMessage::Operations(ops) => {
+                                OperationMessage::Operations(mut ops) => {
                                     debug!("Received operation message: Operations from {}", peer_id);
-                                    if let Err(err) = note_operations_from_peer(
+                                    let received_count = ops.len() as u64;
+                                    self.expiry_filter.filter_operations(&mut ops, &peer_id);
+                                    match note_operations_from_peer(
                                         &self.storage,
                                         &mut self.cache,
                                         &self.config,
                                         ops,
                                         &peer_id,
+                                        !self.quarantine.is_quarantined(&peer_id),
                                         &mut self.internal_sender,
-                                        &mut self.pool_controller
+                                        &self.tracer,
                                     ) {
+                                        Ok(Some(new_ops)) => {
+                                            {
+                                                let mut peer_scores = self.peer_scores.write();
+                                                peer_scores.record_operations(&peer_id, received_count, new_ops.get_op_refs().len() as u64);
+                                                peer_scores.record_message(&peer_id, true);
+                                            }
+                                            self.pool_submitter.submit(new_ops, &peer_id);
+                                        }
+                                        Ok(None) => {
+                                            // none of the operations was new
+                                            let mut peer_scores = self.peer_scores.write();
+                                            peer_scores.record_operations(&peer_id, received_count, 0);
+                                            peer_scores.record_message(&peer_id, false);
+                                        }
+                                        Err(err) => {
                                         warn!("peer {} sent us critically incorrect operation, which may be an attack attempt by the remote peer or a loss of sync between us and the remote peer. Err = {}", peer_id, err);
 
-                                        if let Err(e) = self.ban_node(&peer_id) {
-                                            warn!("Error when banning node: {}", e);
+                                        if let Err(e) = self.quarantine_peer(&peer_id) {
+                                            warn!("Error when quarantining node: {}", e);
+                                        }
                                         }
                                     }
                                 }
@@ -140,12 +170,22 @@ impl RetrievalThread {
                 recv(self.receiver_ext) -> msg => {
                     self.receiver_ext.update_metrics();
                     match msg {
-                        Ok(cmd) => match cmd {
-                            OperationHandlerRetrievalCommand::Stop => {
-                                info!("Stop operation retrieval thread");
-                                return;
+                        Ok(cmd) => {
+                            self.tracer.dequeue(cmd.trace_name());
+                            match cmd {
+                                OperationHandlerRetrievalCommand::GetBatchStats { responder } => {
+                                    let mut stats = self.pool_submitter.get_stats();
+                                    self.expiry_filter.add_stats(&mut stats);
+                                    if let Err(err) = responder.try_send(stats) {
+                                        warn!("error sending operation batch stats: {:?}", err);
+                                    }
+                                }
+                                OperationHandlerRetrievalCommand::Stop => {
+                                    info!("Stop operation retrieval thread");
+                                    return;
+                                }
                             }
-                        },
+                        }
                         Err(_) => {
                             info!("Stop operation retrieval thread");
                             return;
@@ -153,6 +193,7 @@ impl RetrievalThread {
                     }
                 }
                 recv(tick_ask_operations) -> _ => {
+                    self.pool_submitter.process();
                     if let Err(err) = self.update_ask_operation() {
                         warn!("Error in update_ask_operation: {}", err);
                     };
@@ -200,6 +241,15 @@ impl RetrievalThread {
             .write()
             .insert_peer_known_ops(peer_id, &op_batch.iter().copied().collect::<Vec<_>>());
 
+        // the operations are never retrieved in header-only relay mode
+        if self.config.header_only_relay {
+            return Ok(());
+        }
+
+        // ignore the re-announcements of operations already dropped as stale
+        self.expiry_filter
+            .filter_announcement(&mut op_batch, peer_id);
+
         // filter out the operations that we already know about
         {
             let cache_read = self.cache.read();
@@ -246,7 +296,8 @@ impl RetrievalThread {
         } // EndOf for op_id in op_batch:
 
         if count_reask > 0 {
-            massa_trace!("re-ask operations.", { "count": count_reask });
+            debug!("re-asking {} operations", count_reask);
+            self.tracer.event("operation_retrieval.reask_operations");
         }
         if self.op_batch_buffer.len() < self.config.operation_batch_buffer_capacity
             && !future_set.is_empty()
@@ -351,29 +402,43 @@ impl RetrievalThread {
         Ok(())
     }
 
-    /// send a ban peer command to the peer handler
-    fn ban_node(&mut self, peer_id: &PeerId) -> Result<(), ProtocolError> {
-        massa_trace!("ban node from retrieval thread", { "peer_id": peer_id.to_string() });
+    /// send a quarantine peer command to the peer handler, which bans the peer if it already was in quarantine
+    fn quarantine_peer(&mut self, peer_id: &PeerId) -> Result<(), ProtocolError> {
+        self.tracer.event("operation_retrieval.quarantine_peer");
         self.peer_cmd_sender
-            .try_send(PeerManagementCmd::Ban(vec![peer_id.clone()]))
+            .try_send(PeerManagementCmd::Quarantine(vec![peer_id.clone()]))
             .map_err(|err| ProtocolError::SendError(err.to_string()))
     }
 }
 
+/// Checks the operations received from a peer, marks them as known and propagates the new ones
+/// if `propagate` is set (it is not for the peers in quarantine).
+///
+/// Returns the new operations, which still have to be added to the pool by the caller.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn note_operations_from_peer(
     base_storage: &Storage,
     operations_cache: &mut SharedOperationCache,
     config: &ProtocolConfig,
     operations: Vec<SecureShareOperation>,
     source_peer_id: &PeerId,
+    propagate: bool,
     ops_propagation_sender: &mut MassaSender<OperationHandlerPropagationCommand>,
-    pool_controller: &mut Box<dyn PoolController>,
-) -> Result<(), ProtocolError> {
-    massa_trace!("protocol.protocol_worker.note_operations_from_peer", { "peer": source_peer_id, "operations": operations });
+    tracer: &ProtocolTracer,
+) -> Result<Option<Storage>, ProtocolError> {
     let now = MassaTime::now().expect("could not get current time");
+    let now_instant = Instant::now();
 
     let mut new_operations = PreHashMap::with_capacity(operations.len());
     for operation in operations {
+        // ignore if the creator of the op is banned
+        if operations_cache
+            .read()
+            .is_op_source_banned(&operation.content_creator_address, now_instant)
+        {
+            continue;
+        }
+
         // ignore if op is too old
         let expire_period_timestamp = get_block_slot_timestamp(
             config.thread_count,
@@ -445,23 +510,26 @@ pub(crate) fn note_operations_from_peer(
         );
     }
 
-    if !new_operations.is_empty() {
-        // Store new operations, claim locally
-        let mut ops = base_storage.clone_without_refs();
-        ops.store_operations(new_operations.into_values().collect());
+    if new_operations.is_empty() {
+        return Ok(None);
+    }
 
-        // propagate new operations
-        if let Err(_err) = ops_propagation_sender.try_send(
-            OperationHandlerPropagationCommand::PropagateOperations(ops.clone()),
-        ) {
+    // Store new operations, claim locally
+    let mut ops = base_storage.clone_without_refs();
+    ops.store_operations(new_operations.into_values().collect());
+
+    // propagate new operations
+    if propagate {
+        let command = OperationHandlerPropagationCommand::PropagateOperations(ops.clone());
+        let trace_name = command.trace_name();
+        let sequence = tracer.enqueue(trace_name);
+        if let Err(_err) = ops_propagation_sender.try_send(command) {
+            tracer.dropped(trace_name, sequence);
             warn!("Error sending operations to propagation channel");
         }
-
-        // Add to pool
-        pool_controller.add_operations(ops);
     }
 
-    Ok(())
+    Ok(Some(ops))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -475,6 +543,9 @@ pub fn start_retrieval_thread(
     receiver_ext: MassaReceiver<OperationHandlerRetrievalCommand>,
     internal_sender: MassaSender<OperationHandlerPropagationCommand>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_scores: SharedPeerScores,
+    quarantine: SharedPeerQuarantine,
+    tracer: SharedProtocolTracer,
     massa_metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
@@ -482,7 +553,8 @@ pub fn start_retrieval_thread(
         .spawn(move || {
             let mut retrieval_thread = RetrievalThread {
                 receiver,
-                pool_controller,
+                pool_submitter: OperationBatchSubmitter::new(pool_controller, config.clone()),
+                expiry_filter: OperationExpiryFilter::new(ConsensusClock::new(&config), &config),
                 storage,
                 internal_sender,
                 receiver_ext,
@@ -499,6 +571,9 @@ pub fn start_retrieval_thread(
                     .with_operation_message_serializer(OperationMessageSerializer::new()),
                 op_batch_buffer: VecDeque::new(),
                 peer_cmd_sender,
+                peer_scores,
+                quarantine,
+                tracer,
                 _massa_metrics: massa_metrics,
             };
             retrieval_thread.run();
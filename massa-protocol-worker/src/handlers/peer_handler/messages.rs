@@ -22,6 +22,10 @@ pub enum PeerManagementMessage {
     NewPeerConnected((PeerId, HashMap<SocketAddr, TransportType>)),
     // Receive the ip addresses sent by a peer that is already connected.
     ListPeers(Vec<(PeerId, HashMap<SocketAddr, TransportType>)>),
+    // Probe of the connection health, to answer with a `Pong` carrying the same nonce.
+    Ping(u64),
+    // Answer to a `Ping`.
+    Pong(u64),
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -29,6 +33,8 @@ pub enum PeerManagementMessage {
 pub enum MessageTypeId {
     NewPeerConnected = 0,
     ListPeers = 1,
+    Ping = 2,
+    Pong = 3,
 }
 
 impl From<&PeerManagementMessage> for MessageTypeId {
@@ -36,6 +42,8 @@ impl From<&PeerManagementMessage> for MessageTypeId {
         match message {
             PeerManagementMessage::NewPeerConnected(_) => MessageTypeId::NewPeerConnected,
             PeerManagementMessage::ListPeers(_) => MessageTypeId::ListPeers,
+            PeerManagementMessage::Ping(_) => MessageTypeId::Ping,
+            PeerManagementMessage::Pong(_) => MessageTypeId::Pong,
         }
     }
 }
@@ -44,6 +52,7 @@ impl From<&PeerManagementMessage> for MessageTypeId {
 pub struct PeerManagementMessageSerializer {
     id_serializer: U64VarIntSerializer,
     length_serializer: U64VarIntSerializer,
+    nonce_serializer: U64VarIntSerializer,
     ip_addr_serializer: IpAddrSerializer,
     peer_id_serializer: PeerIdSerializer,
 }
@@ -53,6 +62,7 @@ impl PeerManagementMessageSerializer {
         Self {
             id_serializer: U64VarIntSerializer::new(),
             length_serializer: U64VarIntSerializer::new(),
+            nonce_serializer: U64VarIntSerializer::new(),
             ip_addr_serializer: IpAddrSerializer::new(),
             peer_id_serializer: PeerIdSerializer::new(),
         }
@@ -98,6 +108,9 @@ impl Serializer<PeerManagementMessage> for PeerManagementMessageSerializer {
                     }
                 }
             }
+            PeerManagementMessage::Ping(nonce) | PeerManagementMessage::Pong(nonce) => {
+                self.nonce_serializer.serialize(nonce, buffer)?;
+            }
         }
         Ok(())
     }
@@ -107,6 +120,7 @@ pub struct PeerManagementMessageDeserializer {
     id_deserializer: U64VarIntDeserializer,
     listeners_length_deserializer: U64VarIntDeserializer,
     peers_length_deserializer: U64VarIntDeserializer,
+    nonce_deserializer: U64VarIntDeserializer,
     ip_addr_deserializer: IpAddrDeserializer,
     peer_id_deserializer: PeerIdDeserializer,
 }
@@ -131,6 +145,7 @@ impl PeerManagementMessageDeserializer {
                 Included(0),
                 Included(limits.max_peers_per_announcement),
             ),
+            nonce_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
             ip_addr_deserializer: IpAddrDeserializer::new(),
             peer_id_deserializer: PeerIdDeserializer::new(),
         }
@@ -208,6 +223,16 @@ impl Deserializer<PeerManagementMessage> for PeerManagementMessageDeserializer {
                     PeerManagementMessage::ListPeers(data)
                 })
                 .parse(buffer),
+                MessageTypeId::Ping => context("Failed Ping deserialization", |buffer| {
+                    self.nonce_deserializer.deserialize(buffer)
+                })
+                .map(PeerManagementMessage::Ping)
+                .parse(buffer),
+                MessageTypeId::Pong => context("Failed Pong deserialization", |buffer| {
+                    self.nonce_deserializer.deserialize(buffer)
+                })
+                .map(PeerManagementMessage::Pong)
+                .parse(buffer),
             }
         })
         .parse(buffer)
@@ -367,4 +392,32 @@ mod tests {
             _ => panic!("Bad message deserialized"),
         }
     }
+
+    #[test]
+    fn test_ping_pong() {
+        let serializer = PeerManagementMessageSerializer::new();
+        let deserializer =
+            PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
+                max_listeners_per_peer: 1000,
+                max_peers_per_announcement: 1000,
+            });
+        for message in [
+            PeerManagementMessage::Ping(42),
+            PeerManagementMessage::Pong(u64::MAX),
+        ] {
+            let mut buffer = vec![];
+            serializer.serialize(&message, &mut buffer).unwrap();
+            let (rest, deserialized) = deserializer
+                .deserialize::<DeserializeError>(&buffer)
+                .unwrap();
+            assert!(rest.is_empty());
+            match (message, deserialized) {
+                (PeerManagementMessage::Ping(sent), PeerManagementMessage::Ping(received))
+                | (PeerManagementMessage::Pong(sent), PeerManagementMessage::Pong(received)) => {
+                    assert_eq!(sent, received)
+                }
+                _ => panic!("Bad message deserialized"),
+            }
+        }
+    }
 }
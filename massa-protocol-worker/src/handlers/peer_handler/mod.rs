@@ -1,5 +1,10 @@
 use std::net::IpAddr;
-use std::{collections::HashMap, net::SocketAddr, thread::JoinHandle, time::Duration};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
 
 use crossbeam::channel::tick;
 use crossbeam::select;
@@ -28,6 +33,7 @@ use tracing::log::{debug, error, info, warn};
 use crate::context::Context;
 use crate::handlers::peer_handler::models::PeerState;
 use crate::messages::{Message, MessagesHandler, MessagesSerializer};
+use crate::shutdown::ShutdownDrain;
 use crate::wrap_network::ActiveConnectionsTrait;
 
 use self::models::{ConnectionMetadata, PeerInfo};
@@ -35,6 +41,8 @@ use self::{
     models::{
         InitialPeers, PeerManagementChannel, PeerManagementCmd, PeerMessageTuple, SharedPeerDB,
     },
+    probing::PeerProber,
+    quarantine::OffenseOutcome,
     tester::Tester,
 };
 
@@ -52,9 +60,12 @@ use self::{
 mod announcement;
 mod messages;
 pub mod models;
+mod probing;
+mod quarantine;
 mod tester;
 
 pub(crate) use messages::{PeerManagementMessage, PeerManagementMessageSerializer};
+pub(crate) use quarantine::{PeerQuarantine, SharedPeerQuarantine};
 
 pub struct PeerManagementHandler {
     pub peer_db: SharedPeerDB,
@@ -78,6 +89,7 @@ impl PeerManagementHandler {
             MassaReceiver<PeerManagementCmd>,
         ),
         messages_handler: MessagesHandler,
+        quarantine: SharedPeerQuarantine,
         mut active_connections: Box<dyn ActiveConnectionsTrait>,
         target_out_connections: HashMap<String, (Vec<IpAddr>, usize)>,
         default_target_out_connections: usize,
@@ -101,6 +113,8 @@ impl PeerManagementHandler {
         .spawn({
             let peer_db = peer_db.clone();
             let ticker = tick(Duration::from_secs(10));
+            let ping_ticker = tick(config.peer_ping_interval.to_duration());
+            let mut prober = PeerProber::new(config.max_failed_pings);
             let config = config.clone();
             let message_serializer = MessagesSerializer::new()
                 .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
@@ -114,6 +128,10 @@ impl PeerManagementHandler {
                 loop {
                     select! {
                         recv(ticker) -> _ => {
+                            for peer_id in quarantine.release_expired(Instant::now()) {
+                                info!("peer {} released from quarantine", peer_id);
+                            }
+
                             let peers_to_send = peer_db.read().get_rand_peers_to_send(100);
                             if peers_to_send.is_empty() {
                                 continue;
@@ -128,6 +146,19 @@ impl PeerManagementHandler {
                                }
                             }
                         }
+                        recv(ping_ticker) -> _ => {
+                            let round = prober.probe(&active_connections.get_peer_ids_connected(), Instant::now());
+                            for peer_id in round.unresponsive_peers {
+                                warn!("peer {} did not answer {} consecutive pings, disconnecting", peer_id, config.max_failed_pings);
+                                active_connections.shutdown_connection(&peer_id);
+                            }
+                            for (peer_id, nonce) in round.pings {
+                                if let Err(e) = active_connections
+                                    .send_to_peer(&peer_id, &message_serializer, PeerManagementMessage::Ping(nonce).into(), false) {
+                                    debug!("error sending Ping message to peer: {:?}", e);
+                                }
+                            }
+                        }
                         recv(receiver_cmd) -> cmd => {
                             receiver_cmd.update_metrics();
                             // internal command
@@ -139,11 +170,26 @@ impl PeerManagementHandler {
 
                                     // update peer_db
                                     peer_db.write().ban_peer(&peer_id);
+                                    quarantine.remove(&peer_id);
                                 }
                             },
                              Ok(PeerManagementCmd::Unban(peer_ids)) => {
                                 for peer_id in peer_ids {
                                     peer_db.write().unban_peer(&peer_id);
+                                    quarantine.remove(&peer_id);
+                                }
+                            },
+                             Ok(PeerManagementCmd::Quarantine(peer_ids)) => {
+                                for peer_id in peer_ids {
+                                    match quarantine.on_offense(&peer_id, Instant::now()) {
+                                        OffenseOutcome::Quarantined => {
+                                            info!("peer {} quarantined for {} ms", peer_id, config.peer_quarantine_duration);
+                                        }
+                                        OffenseOutcome::Ban => {
+                                            active_connections.shutdown_connection(&peer_id);
+                                            peer_db.write().ban_peer(&peer_id);
+                                        }
+                                    }
                                 }
                             },
                              Ok(PeerManagementCmd::GetBootstrapPeers { responder }) => {
@@ -159,6 +205,19 @@ impl PeerManagementHandler {
                                     warn!("error sending bootstrap peers: {:?}", err);
                                 }
                              },
+                             Ok(PeerManagementCmd::GetRttStats { responder }) => {
+                                if let Err(err) = responder.try_send(prober.get_stats()) {
+                                    warn!("error sending peer rtt stats: {:?}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::RateLimitExceeded(rate_limited_peer_id)) => {
+                                if config.ban_peers_exceeding_rate_limit {
+                                    active_connections.shutdown_connection(&rate_limited_peer_id);
+                                    peer_db.write().ban_peer(&rate_limited_peer_id);
+                                } else {
+                                    warn!("peer {} exceeds the incoming rate limit", rate_limited_peer_id);
+                                }
+                             },
                              Ok(PeerManagementCmd::Stop) => {
                                 while let Ok(_msg) = test_receiver.try_recv() {
                                     // nothing to do just clean the channel
@@ -212,6 +271,15 @@ impl PeerManagementHandler {
                                         }
                                     }
                                 }
+                                PeerManagementMessage::Ping(nonce) => {
+                                    if let Err(e) = active_connections
+                                        .send_to_peer(&peer_id, &message_serializer, PeerManagementMessage::Pong(nonce).into(), false) {
+                                        debug!("error sending Pong message to peer: {:?}", e);
+                                    }
+                                }
+                                PeerManagementMessage::Pong(nonce) => {
+                                    prober.on_pong(&peer_id, nonce, Instant::now());
+                                }
                             }
                         }
                     }
@@ -241,16 +309,16 @@ impl PeerManagementHandler {
         }
     }
 
-    pub fn stop(&mut self) {
-        self.sender
-            .command_sender
-            .send(PeerManagementCmd::Stop)
-            .unwrap();
+    pub fn stop(&mut self, drain: &mut ShutdownDrain) {
+        let _ = self.sender.command_sender.send(PeerManagementCmd::Stop);
 
         // waiting for all threads to finish
+        if let Some(join_handle) = self.thread_join.take() {
+            drain.join(join_handle);
+        }
         self.testers.iter_mut().for_each(|tester| {
             if let Some(join_handle) = tester.handler.take() {
-                join_handle.join().expect("Failed to join tester thread");
+                drain.join(join_handle);
             }
         });
     }
@@ -349,6 +417,14 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     Some(format!("Failed to serialize announcement: {}", err)),
                 )
             })?;
+        // capabilities of our node, ignored by the nodes that do not know them
+        let subscriptions = &self.message_handlers.endorsement_subscriptions;
+        bytes.push(
+            self.message_handlers.compression.handshake_flags()
+                | subscriptions.handshake_flags()
+                | self.message_handlers.block_retractions.handshake_flags(),
+        );
+        subscriptions.write_handshake_extension(&mut bytes);
         endpoint.send::<PeerId>(&bytes)?;
         let received = endpoint.receive::<PeerId>()?;
         if received.len() < 32 {
@@ -409,7 +485,7 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
             )?;
             match id {
                 0 => {
-                    let (_, announcement) = self
+                    let (capabilities, announcement) = self
                         .announcement_deserializer
                         .deserialize::<DeserializeError>(
                             received.get(1..).ok_or(PeerNetError::HandshakeError.error(
@@ -495,6 +571,15 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                             PeerNetError::HandshakeError
                                 .error("Massa Handshake", Some(format!("Signature error {}", err)))
                         })?;
+                    self.message_handlers
+                        .compression
+                        .on_handshake(&peer_id, capabilities.first().copied());
+                    self.message_handlers
+                        .endorsement_subscriptions
+                        .on_handshake(&peer_id, capabilities);
+                    self.message_handlers
+                        .block_retractions
+                        .on_handshake(&peer_id, capabilities);
                     Ok((peer_id.clone(), Some(announcement)))
                 }
                 1 => {
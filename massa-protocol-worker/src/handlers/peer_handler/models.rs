@@ -1,5 +1,5 @@
 use massa_channel::sender::MassaSender;
-use massa_protocol_exports::{BootstrapPeers, PeerId, ProtocolError};
+use massa_protocol_exports::{BootstrapPeers, PeerId, PeerRttStats, ProtocolError};
 use massa_time::MassaTime;
 use parking_lot::RwLock;
 use peernet::transports::TransportType;
@@ -179,9 +179,16 @@ pub enum PeerState {
 pub enum PeerManagementCmd {
     Ban(Vec<PeerId>),
     Unban(Vec<PeerId>),
+    /// The peers misbehaved: they are quarantined, or banned if they already were
+    Quarantine(Vec<PeerId>),
     GetBootstrapPeers {
         responder: MassaSender<BootstrapPeers>,
     },
+    GetRttStats {
+        responder: MassaSender<HashMap<PeerId, PeerRttStats>>,
+    },
+    /// The peer sent more than `max_peer_bytes_in_per_second`
+    RateLimitExceeded(PeerId),
     Stop,
 }
 
@@ -0,0 +1,170 @@
+//! Application-level probing of the health of the connections.
+//!
+//! A ping is sent to each connected peer every `peer_ping_interval`, and the peer answers with a pong.
+//! TCP keepalive does not detect the peers that keep the connection open but stopped processing messages:
+//! those peers are disconnected after `max_failed_pings` consecutive unanswered pings.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::{PeerId, PeerRttStats};
+use massa_time::MassaTime;
+
+/// Number of round-trip times kept per peer to compute the percentiles
+const RTT_SAMPLES_PER_PEER: usize = 64;
+
+#[derive(Default)]
+struct PeerProbe {
+    /// nonce and sending instant of the ping waiting for its pong
+    pending: Option<(u64, Instant)>,
+    /// number of consecutive pings left unanswered
+    failed_probes: u32,
+    /// latest round-trip times, oldest first
+    rtts: VecDeque<Duration>,
+}
+
+/// Pings to send and peers to disconnect after a probe round
+#[derive(Default)]
+pub(crate) struct ProbeRound {
+    pub pings: Vec<(PeerId, u64)>,
+    pub unresponsive_peers: Vec<PeerId>,
+}
+
+pub(crate) struct PeerProber {
+    probes: HashMap<PeerId, PeerProbe>,
+    max_failed_pings: u32,
+    next_nonce: u64,
+}
+
+impl PeerProber {
+    pub(crate) fn new(max_failed_pings: u32) -> Self {
+        Self {
+            probes: HashMap::new(),
+            max_failed_pings,
+            next_nonce: 0,
+        }
+    }
+
+    /// Starts a new probe round: counts the pings of the previous round left unanswered,
+    /// and returns the pings to send to the connected peers and the peers to disconnect.
+    pub(crate) fn probe(&mut self, connected_peers: &HashSet<PeerId>, now: Instant) -> ProbeRound {
+        self.probes
+            .retain(|peer_id, _| connected_peers.contains(peer_id));
+        let mut round = ProbeRound::default();
+        for peer_id in connected_peers {
+            let probe = self.probes.entry(peer_id.clone()).or_default();
+            if probe.pending.take().is_some() {
+                probe.failed_probes = probe.failed_probes.saturating_add(1);
+                if probe.failed_probes >= self.max_failed_pings {
+                    round.unresponsive_peers.push(peer_id.clone());
+                    continue;
+                }
+            }
+            let nonce = self.next_nonce;
+            self.next_nonce = self.next_nonce.wrapping_add(1);
+            probe.pending = Some((nonce, now));
+            round.pings.push((peer_id.clone(), nonce));
+        }
+        for peer_id in &round.unresponsive_peers {
+            self.probes.remove(peer_id);
+        }
+        round
+    }
+
+    /// Records the pong sent by `peer_id`. Pongs not matching the pending ping are ignored.
+    pub(crate) fn on_pong(&mut self, peer_id: &PeerId, nonce: u64, now: Instant) {
+        let Some(probe) = self.probes.get_mut(peer_id) else {
+            return;
+        };
+        match probe.pending {
+            Some((pending_nonce, sent_at)) if pending_nonce == nonce => {
+                probe.pending = None;
+                probe.failed_probes = 0;
+                if probe.rtts.len() == RTT_SAMPLES_PER_PEER {
+                    probe.rtts.pop_front();
+                }
+                probe.rtts.push_back(now.saturating_duration_since(sent_at));
+            }
+            _ => {}
+        }
+    }
+
+    /// Round-trip time statistics of each probed peer
+    pub(crate) fn get_stats(&self) -> HashMap<PeerId, PeerRttStats> {
+        self.probes
+            .iter()
+            .map(|(peer_id, probe)| {
+                let mut rtts: Vec<Duration> = probe.rtts.iter().copied().collect();
+                rtts.sort_unstable();
+                let stats = PeerRttStats {
+                    samples: rtts.len() as u64,
+                    p50: percentile(&rtts, 50),
+                    p90: percentile(&rtts, 90),
+                    p99: percentile(&rtts, 99),
+                    failed_probes: probe.failed_probes,
+                };
+                (peer_id.clone(), stats)
+            })
+            .collect()
+    }
+}
+
+/// Nearest-rank percentile of sorted round-trip times
+fn percentile(sorted_rtts: &[Duration], percent: usize) -> Option<MassaTime> {
+    if sorted_rtts.is_empty() {
+        return None;
+    }
+    let rank = (sorted_rtts.len() * percent + 99) / 100;
+    let rtt = sorted_rtts[rank.saturating_sub(1)];
+    MassaTime::try_from(rtt).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_peer_prober() {
+        let peer_a = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let peer_b = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let connected: HashSet<PeerId> = [peer_a.clone(), peer_b.clone()].into_iter().collect();
+        let mut prober = PeerProber::new(2);
+        let start = Instant::now();
+
+        // peer a answers every ping, peer b never does
+        for round_index in 0..2u64 {
+            let now = start + Duration::from_millis(1000 * round_index);
+            let round = prober.probe(&connected, now);
+            assert!(round.unresponsive_peers.is_empty());
+            assert_eq!(round.pings.len(), 2);
+            let (_, nonce) = round
+                .pings
+                .iter()
+                .find(|(peer_id, _)| peer_id == &peer_a)
+                .unwrap();
+            // a pong with a wrong nonce is ignored
+            prober.on_pong(&peer_a, nonce + 100, now + Duration::from_millis(5));
+            prober.on_pong(
+                &peer_a,
+                *nonce,
+                now + Duration::from_millis(10 * (round_index + 1)),
+            );
+        }
+
+        let stats = prober.get_stats();
+        assert_eq!(stats[&peer_a].samples, 2);
+        assert_eq!(stats[&peer_a].p50, Some(MassaTime::from_millis(10)));
+        assert_eq!(stats[&peer_a].p99, Some(MassaTime::from_millis(20)));
+        assert_eq!(stats[&peer_b].samples, 0);
+        assert_eq!(stats[&peer_b].failed_probes, 1);
+
+        // second unanswered ping of peer b
+        let round = prober.probe(&connected, start + Duration::from_millis(2000));
+        assert_eq!(round.unresponsive_peers, vec![peer_b.clone()]);
+        assert_eq!(round.pings.len(), 1);
+        assert!(!prober.get_stats().contains_key(&peer_b));
+    }
+}
@@ -0,0 +1,141 @@
+//! Quarantine of the peers suspected of misbehaving.
+//!
+//! A peer sending invalid content is not banned right away: network glitches can make honest peers
+//! send outdated or incomplete data. Instead, it is quarantined for `peer_quarantine_duration`.
+//! A quarantined peer stays connected and its messages are still processed,
+//! but the operations, endorsements and headers it sends are not propagated to the other peers.
+//! A peer misbehaving again during its quarantine is banned,
+//! and a peer whose quarantine ends without a new offense is released.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::{PeerId, ProtocolConfig};
+use parking_lot::RwLock;
+
+/// What to do with a peer after an offense
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OffenseOutcome {
+    /// the peer was put in quarantine
+    Quarantined,
+    /// the peer has to be banned
+    Ban,
+}
+
+pub struct PeerQuarantine {
+    duration: Duration,
+    /// end of the quarantine of each quarantined peer
+    peers: RwLock<HashMap<PeerId, Instant>>,
+}
+
+impl PeerQuarantine {
+    pub fn new(config: &ProtocolConfig) -> Self {
+        Self {
+            duration: config.peer_quarantine_duration.to_duration(),
+            peers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Records an offense of `peer_id`: the peer is quarantined on its first offense,
+    /// and has to be banned if it was already quarantined or if the quarantine is disabled.
+    pub(crate) fn on_offense(&self, peer_id: &PeerId, now: Instant) -> OffenseOutcome {
+        if self.duration.is_zero() {
+            return OffenseOutcome::Ban;
+        }
+        let mut peers = self.peers.write();
+        match peers.get(peer_id) {
+            Some(end) if *end > now => {
+                peers.remove(peer_id);
+                OffenseOutcome::Ban
+            }
+            _ => {
+                peers.insert(peer_id.clone(), now + self.duration);
+                OffenseOutcome::Quarantined
+            }
+        }
+    }
+
+    /// Whether what `peer_id` sends must not be propagated
+    pub fn is_quarantined(&self, peer_id: &PeerId) -> bool {
+        self.is_quarantined_at(peer_id, Instant::now())
+    }
+
+    fn is_quarantined_at(&self, peer_id: &PeerId, now: Instant) -> bool {
+        self.peers
+            .read()
+            .get(peer_id)
+            .map_or(false, |end| *end > now)
+    }
+
+    /// Releases the peers whose quarantine ended, and returns them
+    pub(crate) fn release_expired(&self, now: Instant) -> Vec<PeerId> {
+        let mut released = Vec::new();
+        self.peers.write().retain(|peer_id, end| {
+            if *end > now {
+                true
+            } else {
+                released.push(peer_id.clone());
+                false
+            }
+        });
+        released
+    }
+
+    /// Lifts the quarantine of `peer_id`, when it gets banned or unbanned explicitly
+    pub(crate) fn remove(&self, peer_id: &PeerId) {
+        self.peers.write().remove(peer_id);
+    }
+}
+
+pub type SharedPeerQuarantine = Arc<PeerQuarantine>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use massa_time::MassaTime;
+
+    #[test]
+    fn test_peer_quarantine() {
+        let config = ProtocolConfig {
+            peer_quarantine_duration: MassaTime::from_millis(1000),
+            ..Default::default()
+        };
+        let quarantine = PeerQuarantine::new(&config);
+        let peer_a = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let peer_b = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let start = Instant::now();
+
+        // first offenses quarantine the peers
+        assert_eq!(
+            quarantine.on_offense(&peer_a, start),
+            OffenseOutcome::Quarantined
+        );
+        assert_eq!(
+            quarantine.on_offense(&peer_b, start),
+            OffenseOutcome::Quarantined
+        );
+        assert!(quarantine.is_quarantined_at(&peer_a, start));
+
+        // a new offense during the quarantine bans the peer
+        let later = start + Duration::from_millis(500);
+        assert_eq!(quarantine.on_offense(&peer_a, later), OffenseOutcome::Ban);
+        assert!(!quarantine.is_quarantined_at(&peer_a, later));
+
+        // the quarantine of peer b ends without a new offense
+        let end = start + Duration::from_millis(1000);
+        assert!(!quarantine.is_quarantined_at(&peer_b, end));
+        assert_eq!(quarantine.release_expired(end), vec![peer_b.clone()]);
+        assert_eq!(
+            quarantine.on_offense(&peer_b, end),
+            OffenseOutcome::Quarantined
+        );
+
+        // a disabled quarantine bans immediately
+        let quarantine = PeerQuarantine::new(&ProtocolConfig::default());
+        assert_eq!(quarantine.on_offense(&peer_a, start), OffenseOutcome::Ban);
+    }
+}
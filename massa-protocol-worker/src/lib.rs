@@ -1,11 +1,21 @@
+mod bandwidth;
+mod buffer_pool;
+mod compression;
 mod connectivity;
 mod context;
 mod controller;
+mod diversity;
+#[cfg(feature = "fault_injection")]
+mod fault_injection;
 mod handlers;
 mod ip;
 mod manager;
 mod messages;
+mod peer_scores;
+mod propagation_lanes;
+mod shutdown;
 mod sig_verifier;
+mod trace;
 mod worker;
 mod wrap_network;
 
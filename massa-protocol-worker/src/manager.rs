@@ -1,35 +1,57 @@
-use std::thread::JoinHandle;
+use std::{thread::JoinHandle, time::Duration};
 
 use massa_channel::sender::MassaSender;
-use massa_protocol_exports::ProtocolManager;
-use tracing::info;
+use massa_protocol_exports::{ProtocolManager, ShutdownReport};
+use tracing::{info, warn};
 
-use crate::connectivity::ConnectivityCommand;
+use crate::{connectivity::ConnectivityCommand, shutdown::ShutdownDrain};
+
+/// Extra time given to the connectivity thread to return its report
+/// after the drain deadline of the threads it stops
+const CONNECTIVITY_STOP_GRACE: Duration = Duration::from_millis(500);
 
 /// protocol manager used to stop the protocol
 pub struct ProtocolManagerImpl {
-    connectivity_thread: Option<(MassaSender<ConnectivityCommand>, JoinHandle<()>)>,
+    connectivity_thread: Option<(MassaSender<ConnectivityCommand>, JoinHandle<ShutdownReport>)>,
+    /// max time to wait for the protocol threads to stop
+    shutdown_drain_deadline: Duration,
 }
 
 impl ProtocolManagerImpl {
-    pub fn new(connectivity_thread: (MassaSender<ConnectivityCommand>, JoinHandle<()>)) -> Self {
+    pub fn new(
+        connectivity_thread: (MassaSender<ConnectivityCommand>, JoinHandle<ShutdownReport>),
+        shutdown_drain_deadline: Duration,
+    ) -> Self {
         Self {
             connectivity_thread: Some(connectivity_thread),
+            shutdown_drain_deadline,
         }
     }
 }
 
 impl ProtocolManager for ProtocolManagerImpl {
     /// Stop the protocol module
-    fn stop(&mut self) {
+    fn stop(&mut self) -> ShutdownReport {
         info!("stopping protocol module...");
-        if let Some((tx, join_handle)) = self.connectivity_thread.take() {
-            tx.send(ConnectivityCommand::Stop)
-                .expect("Failed to send stop command of protocol");
-            drop(tx);
-            join_handle
-                .join()
-                .expect("connectivity thread panicked on try to join");
+        let Some((tx, join_handle)) = self.connectivity_thread.take() else {
+            return ShutdownReport::default();
+        };
+        if tx.send(ConnectivityCommand::Stop).is_err() {
+            warn!("connectivity thread already stopped");
+        }
+        drop(tx);
+        let mut drain = ShutdownDrain::new(self.shutdown_drain_deadline + CONNECTIVITY_STOP_GRACE);
+        match drain.join(join_handle) {
+            Some(mut report) => {
+                let connectivity_report = drain.into_report();
+                report
+                    .stopped_threads
+                    .extend(connectivity_report.stopped_threads);
+                report.duration = connectivity_report.duration;
+                report
+            }
+            // the connectivity thread timed out or panicked
+            None => drain.into_report(),
         }
     }
 }
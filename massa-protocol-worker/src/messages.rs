@@ -12,12 +12,21 @@ use peernet::{
 };
 use tracing::debug;
 
-use crate::handlers::{
-    block_handler::{BlockMessage, BlockMessageSerializer},
-    endorsement_handler::{EndorsementMessage, EndorsementMessageSerializer},
-    operation_handler::{OperationMessage, OperationMessageSerializer},
-    peer_handler::{
-        models::PeerMessageTuple, PeerManagementMessage, PeerManagementMessageSerializer,
+use crate::{
+    bandwidth::{SharedBandwidthAccounting, TrafficType},
+    buffer_pool::SharedBufferPool,
+    compression::SharedMessageCompression,
+    handlers::{
+        block_handler::{
+            BlockMessage, BlockMessageSerializer, BlockMessageTypeId, SharedBlockRetractions,
+        },
+        endorsement_handler::{
+            EndorsementMessage, EndorsementMessageSerializer, SharedEndorsementSubscriptions,
+        },
+        operation_handler::{OperationMessage, OperationMessageSerializer, OperationMessageTypeId},
+        peer_handler::{
+            models::PeerMessageTuple, PeerManagementMessage, PeerManagementMessageSerializer,
+        },
     },
 };
 
@@ -27,15 +36,44 @@ pub enum Message {
     Endorsement(EndorsementMessage),
     Operation(OperationMessage),
     PeerManagement(Box<PeerManagementMessage>),
+    /// Message compressed with zstd, sent to the peers that support it.
+    /// `data` is the compressed serialization of the original message, whose traffic type is `traffic_type`.
+    Compressed {
+        traffic_type: TrafficType,
+        data: Vec<u8>,
+    },
+}
+
+impl Message {
+    /// Kind of the content of the message: the kind of the original message for a compressed message
+    pub fn traffic_type(&self) -> TrafficType {
+        match self {
+            Message::Block(message) => match message.as_ref() {
+                BlockMessage::Header(_) => TrafficType::BlockHeader,
+                _ => TrafficType::BlockData,
+            },
+            Message::Endorsement(_) => TrafficType::Endorsement,
+            Message::Operation(OperationMessage::OperationsAnnouncement(_)) => {
+                TrafficType::OperationAnnouncement
+            }
+            Message::Operation(OperationMessage::AskForOperations(_)) => {
+                TrafficType::OperationRequest
+            }
+            Message::Operation(OperationMessage::Operations(_)) => TrafficType::Operations,
+            Message::PeerManagement(_) => TrafficType::PeerManagement,
+            Message::Compressed { traffic_type, .. } => *traffic_type,
+        }
+    }
 }
 
-#[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
+#[derive(IntoPrimitive, Debug, Clone, Copy, Eq, PartialEq, TryFromPrimitive)]
 #[repr(u64)]
 pub enum MessageTypeId {
     Block = 0,
     Endorsement = 1,
     Operation = 2,
     PeerManagement = 3,
+    Compressed = 4,
 }
 
 impl From<&Message> for MessageTypeId {
@@ -45,6 +83,7 @@ impl From<&Message> for MessageTypeId {
             Message::Endorsement(_) => MessageTypeId::Endorsement,
             Message::Operation(_) => MessageTypeId::Operation,
             Message::PeerManagement(_) => MessageTypeId::PeerManagement,
+            Message::Compressed { .. } => MessageTypeId::Compressed,
         }
     }
 }
@@ -213,6 +252,10 @@ impl PeerNetMessagesSerializer<Message> for MessagesSerializer {
                     ))
                 }
             }
+            Message::Compressed { data, .. } => {
+                buffer.extend_from_slice(data);
+                Ok(())
+            }
         }
     }
 }
@@ -224,10 +267,69 @@ pub struct MessagesHandler {
     pub sender_endorsements: MassaSender<PeerMessageTuple>,
     pub sender_operations: MassaSender<PeerMessageTuple>,
     pub sender_peers: MassaSender<PeerMessageTuple>,
+    pub bandwidth: SharedBandwidthAccounting,
+    pub compression: SharedMessageCompression,
+    pub buffer_pool: SharedBufferPool,
+    pub endorsement_subscriptions: SharedEndorsementSubscriptions,
+    pub block_retractions: SharedBlockRetractions,
 }
 
 impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
     fn handle(&self, data: &[u8], peer_id: &PeerId) -> PeerNetResult<()> {
+        let message_size = data.len();
+        let (data, id) = self.deserialize_id(data)?;
+        if id == MessageTypeId::Compressed {
+            // the compressed message is counted with the type of its content,
+            // which cannot be another compressed message
+            let decompressed = self.compression.decompress(data)?;
+            let (data, id) = self.deserialize_id(&decompressed)?;
+            if let Some(traffic_type) = self.traffic_type(id, data) {
+                self.bandwidth
+                    .record_in(peer_id, traffic_type, message_size);
+            }
+            return self.dispatch(id, data, peer_id);
+        }
+        if let Some(traffic_type) = self.traffic_type(id, data) {
+            self.bandwidth
+                .record_in(peer_id, traffic_type, message_size);
+        }
+        self.dispatch(id, data, peer_id)
+    }
+}
+
+impl MessagesHandler {
+    /// Kind of a message of type `id`, read from the id of its content at the start of `data`.
+    /// `None` for a compressed message, which is counted with the type of its content.
+    fn traffic_type(&self, id: MessageTypeId, data: &[u8]) -> Option<TrafficType> {
+        let content_id = self
+            .id_deserializer
+            .deserialize::<DeserializeError>(data)
+            .ok()
+            .map(|(_, content_id)| content_id);
+        let traffic_type = match id {
+            MessageTypeId::Block if content_id == Some(BlockMessageTypeId::Header.into()) => {
+                TrafficType::BlockHeader
+            }
+            MessageTypeId::Block => TrafficType::BlockData,
+            MessageTypeId::Endorsement => TrafficType::Endorsement,
+            // the invalid messages are counted as operations, they are rejected by the operation handler
+            MessageTypeId::Operation => {
+                match content_id.and_then(|id| OperationMessageTypeId::try_from(id).ok()) {
+                    Some(OperationMessageTypeId::OperationsAnnouncement) => {
+                        TrafficType::OperationAnnouncement
+                    }
+                    Some(OperationMessageTypeId::AskForOperations) => TrafficType::OperationRequest,
+                    _ => TrafficType::Operations,
+                }
+            }
+            MessageTypeId::PeerManagement => TrafficType::PeerManagement,
+            MessageTypeId::Compressed => return None,
+        };
+        Some(traffic_type)
+    }
+
+    /// Reads the message type id at the start of `data`
+    fn deserialize_id<'a>(&self, data: &'a [u8]) -> PeerNetResult<(&'a [u8], MessageTypeId)> {
         let (data, raw_id) = self
             .id_deserializer
             .deserialize::<DeserializeError>(data)
@@ -243,6 +345,11 @@ impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
                 Some(String::from("Invalid message type id")),
             )
         })?;
+        Ok((data, id))
+    }
+
+    /// Sends the content of a message to the channel of its handler
+    fn dispatch(&self, id: MessageTypeId, data: &[u8], peer_id: &PeerId) -> PeerNetResult<()> {
         match id {
             // Blocks are high-priority: we block if the channel is full.
             // This means that the sender will be blocked until the message is sent.
@@ -282,6 +389,10 @@ impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
                 }
                 Ok(())
             }
+            MessageTypeId::Compressed => Err(PeerNetError::HandlerError.error(
+                "MessagesHandler",
+                Some(String::from("Compressed message cannot be dispatched")),
+            )),
         }
     }
 }
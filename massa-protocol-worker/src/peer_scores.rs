@@ -0,0 +1,192 @@
+//! Reputation of the connected peers.
+//!
+//! The score of a peer is a weighted average of several components, each between 0 (worst) and 1 (best):
+//! the ratio of valid headers it sent us, its response time to our block data requests,
+//! the ratio of useful messages it sent us and the ratio of new operations it sent us.
+//! Peers we know nothing about have a neutral score of 0.5.
+//! The scores are updated by the retrieval threads and used to choose which peers to ask blocks from.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use massa_protocol_exports::{PeerId, PeerScore, ProtocolConfig};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+
+/// Weight of a new sample in the moving average of the response time of a peer
+const LATENCY_SAMPLE_WEIGHT: f64 = 0.2;
+
+/// Scores of the connected peers
+pub struct PeerScores {
+    /// score and statistics of each peer
+    scores: HashMap<PeerId, PeerScore>,
+    /// weight of the ratio of valid headers
+    header_validity_weight: f64,
+    /// weight of the response time to block data requests
+    latency_weight: f64,
+    /// weight of the ratio of useful messages
+    garbage_weight: f64,
+    /// weight of the ratio of new operations
+    operation_usefulness_weight: f64,
+    /// response time counted for unanswered block data requests
+    ask_block_timeout: Duration,
+}
+
+impl PeerScores {
+    pub fn new(config: &ProtocolConfig) -> Self {
+        Self {
+            scores: HashMap::new(),
+            header_validity_weight: config.peer_score_header_validity_weight,
+            latency_weight: config.peer_score_latency_weight,
+            garbage_weight: config.peer_score_garbage_weight,
+            operation_usefulness_weight: config.peer_score_operation_usefulness_weight,
+            ask_block_timeout: config.ask_block_timeout.to_duration(),
+        }
+    }
+
+    /// Records that a peer sent us a valid or an invalid header
+    pub fn record_header(&mut self, peer_id: &PeerId, valid: bool) {
+        self.update(peer_id, |stats| {
+            if valid {
+                stats.valid_headers += 1;
+            } else {
+                stats.invalid_headers += 1;
+            }
+        });
+    }
+
+    /// Records the time taken by a peer to answer one of our block data requests
+    pub fn record_ask_block_response(&mut self, peer_id: &PeerId, latency: Duration) {
+        let latency = latency.min(self.ask_block_timeout);
+        self.update(peer_id, |stats| add_latency_sample(stats, latency));
+    }
+
+    /// Records that a peer did not answer one of our block data requests in time
+    pub fn record_ask_block_timeout(&mut self, peer_id: &PeerId) {
+        let latency = self.ask_block_timeout;
+        self.update(peer_id, |stats| {
+            stats.ask_block_timeouts += 1;
+            add_latency_sample(stats, latency);
+        });
+    }
+
+    /// Records that a peer sent us a useful message, or a duplicated, unrequested or malformed one
+    pub fn record_message(&mut self, peer_id: &PeerId, useful: bool) {
+        self.update(peer_id, |stats| {
+            if useful {
+                stats.useful_messages += 1;
+            } else {
+                stats.garbage_messages += 1;
+            }
+        });
+    }
+
+    /// Records that a peer sent us `received` operations, among which `new` ones we did not know yet
+    pub fn record_operations(&mut self, peer_id: &PeerId, received: u64, new: u64) {
+        self.update(peer_id, |stats| {
+            stats.received_operations += received;
+            stats.new_operations += new;
+        });
+    }
+
+    /// Gets the score of a peer, between 0 (worst) and 1 (best)
+    pub fn get_score(&self, peer_id: &PeerId) -> f64 {
+        match self.scores.get(peer_id) {
+            Some(stats) => stats.score,
+            None => self.compute_score(&PeerScore::default()),
+        }
+    }
+
+    /// Gets the score and statistics of each peer
+    pub fn get_all(&self) -> HashMap<PeerId, PeerScore> {
+        self.scores.clone()
+    }
+
+    /// Forgets the disconnected peers and gives a neutral score to the newly connected ones
+    pub fn update_connected_peers(&mut self, peers_connected: &HashSet<PeerId>) {
+        self.scores
+            .retain(|peer_id, _| peers_connected.contains(peer_id));
+        let neutral_score = self.compute_score(&PeerScore::default());
+        for peer_id in peers_connected {
+            self.scores
+                .entry(peer_id.clone())
+                .or_insert_with(|| PeerScore {
+                    score: neutral_score,
+                    ..Default::default()
+                });
+        }
+    }
+
+    /// Updates the statistics of a peer and recomputes its score
+    fn update<F: FnOnce(&mut PeerScore)>(&mut self, peer_id: &PeerId, f: F) {
+        let mut stats = self.scores.remove(peer_id).unwrap_or_default();
+        f(&mut stats);
+        stats.score = self.compute_score(&stats);
+        self.scores.insert(peer_id.clone(), stats);
+    }
+
+    /// Combines the components of the score of a peer
+    fn compute_score(&self, stats: &PeerScore) -> f64 {
+        let latency_component = match stats.ask_block_latency {
+            Some(latency) => {
+                1.0 - (latency.to_duration().as_secs_f64()
+                    / self.ask_block_timeout.as_secs_f64().max(f64::EPSILON))
+                .min(1.0)
+            }
+            None => 0.5,
+        };
+        let weighted_components = [
+            (
+                self.header_validity_weight,
+                smoothed_ratio(stats.valid_headers, stats.invalid_headers),
+            ),
+            (self.latency_weight, latency_component),
+            (
+                self.garbage_weight,
+                smoothed_ratio(stats.useful_messages, stats.garbage_messages),
+            ),
+            (
+                self.operation_usefulness_weight,
+                smoothed_ratio(
+                    stats.new_operations,
+                    stats
+                        .received_operations
+                        .saturating_sub(stats.new_operations),
+                ),
+            ),
+        ];
+        let total_weight: f64 = weighted_components.iter().map(|(w, _)| w).sum();
+        if total_weight <= 0.0 {
+            return 0.5;
+        }
+        weighted_components
+            .iter()
+            .map(|(weight, component)| weight * component)
+            .sum::<f64>()
+            / total_weight
+    }
+}
+
+/// Ratio of good events, starting from 0.5 when nothing was observed
+/// so that a single event does not make the ratio extreme
+fn smoothed_ratio(good: u64, bad: u64) -> f64 {
+    (good as f64 + 1.0) / ((good + bad) as f64 + 2.0)
+}
+
+/// Adds a sample to the moving average of the response time of a peer
+fn add_latency_sample(stats: &mut PeerScore, latency: Duration) {
+    let sample = latency.as_secs_f64();
+    let average = match stats.ask_block_latency {
+        Some(previous) => {
+            previous.to_duration().as_secs_f64() * (1.0 - LATENCY_SAMPLE_WEIGHT)
+                + sample * LATENCY_SAMPLE_WEIGHT
+        }
+        None => sample,
+    };
+    stats.ask_block_latency = Some(MassaTime::from_millis((average * 1000.0).round() as u64));
+}
+
+pub type SharedPeerScores = Arc<RwLock<PeerScores>>;
@@ -0,0 +1,124 @@
+//! Priority between the propagation of blocks, endorsements and operations.
+//!
+//! Each handler has its own propagation thread and command channel, but all of them send
+//! to the same peer connections. Under an operation flood, the announcements of operations
+//! would compete with the block headers, delaying the propagation of blocks.
+//!
+//! The propagation commands of blocks and endorsements are counted when they are queued,
+//! and until they are processed the lanes of lower priority wait before sending, without holding
+//! their cache lock, at most `propagation_priority_max_wait` so that they are never starved.
+//! The priority order is: blocks > endorsements > operations.
+
+use std::{sync::Arc, time::Duration};
+
+use parking_lot::{Condvar, Mutex};
+
+/// Number of propagation lanes
+const LANE_COUNT: usize = 3;
+
+/// Propagation lanes, from the highest priority to the lowest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropagationLane {
+    Blocks = 0,
+    Endorsements = 1,
+    Operations = 2,
+}
+
+/// Counts the pending propagation commands of each lane
+pub struct PropagationLanes {
+    /// number of queued and not yet processed propagation commands of each lane
+    pending: Mutex<[usize; LANE_COUNT]>,
+    /// notified each time a propagation command is processed
+    processed: Condvar,
+    /// max time a lane waits for the lanes of higher priority
+    max_wait: Duration,
+}
+
+impl PropagationLanes {
+    pub fn new(max_wait: Duration) -> Self {
+        Self {
+            pending: Mutex::new([0; LANE_COUNT]),
+            processed: Condvar::new(),
+            max_wait,
+        }
+    }
+
+    /// Counts a propagation command queued in a lane
+    pub fn enqueue(&self, lane: PropagationLane) {
+        self.pending.lock()[lane as usize] += 1;
+    }
+
+    /// Counts a propagation command of a lane as processed
+    /// (or as dropped, if it could not be queued after being counted)
+    pub fn done(&self, lane: PropagationLane) {
+        let mut pending = self.pending.lock();
+        pending[lane as usize] = pending[lane as usize].saturating_sub(1);
+        self.processed.notify_all();
+    }
+
+    /// Waits until the lanes of higher priority than `lane` have no pending command,
+    /// or until the max wait time elapsed.
+    /// Returns false if the wait timed out.
+    pub fn wait_for_higher_lanes(&self, lane: PropagationLane) -> bool {
+        let mut pending = self.pending.lock();
+        let higher_pending =
+            |pending: &[usize; LANE_COUNT]| pending[..lane as usize].iter().any(|count| *count > 0);
+        if !higher_pending(&pending) {
+            return true;
+        }
+        let deadline = std::time::Instant::now() + self.max_wait;
+        while higher_pending(&pending) {
+            if self
+                .processed
+                .wait_until(&mut pending, deadline)
+                .timed_out()
+            {
+                return !higher_pending(&pending);
+            }
+        }
+        true
+    }
+}
+
+pub type SharedPropagationLanes = Arc<PropagationLanes>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_propagation_lanes_priority() {
+        let lanes = Arc::new(PropagationLanes::new(Duration::from_millis(200)));
+
+        // nothing pending: no wait
+        assert!(lanes.wait_for_higher_lanes(PropagationLane::Operations));
+
+        // a pending block delays endorsements and operations, but not blocks
+        lanes.enqueue(PropagationLane::Blocks);
+        assert!(lanes.wait_for_higher_lanes(PropagationLane::Blocks));
+        let start = Instant::now();
+        assert!(!lanes.wait_for_higher_lanes(PropagationLane::Operations));
+        assert!(start.elapsed() >= Duration::from_millis(200));
+
+        // the wait ends as soon as the block is processed
+        let lanes_clone = lanes.clone();
+        let processing = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            lanes_clone.done(PropagationLane::Blocks);
+        });
+        assert!(lanes.wait_for_higher_lanes(PropagationLane::Endorsements));
+        processing.join().unwrap();
+
+        // pending endorsements only delay operations
+        lanes.enqueue(PropagationLane::Endorsements);
+        assert!(lanes.wait_for_higher_lanes(PropagationLane::Endorsements));
+        assert!(!lanes.wait_for_higher_lanes(PropagationLane::Operations));
+        lanes.done(PropagationLane::Endorsements);
+        assert!(lanes.wait_for_higher_lanes(PropagationLane::Operations));
+
+        // processing more commands than counted does not underflow
+        lanes.done(PropagationLane::Blocks);
+        assert!(lanes.wait_for_higher_lanes(PropagationLane::Operations));
+    }
+}
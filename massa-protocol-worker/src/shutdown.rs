@@ -0,0 +1,104 @@
+//! Bounded shutdown of the protocol threads.
+//!
+//! The threads are joined one after the other until a common deadline. A thread still running
+//! at the deadline (for example because a leaked sender keeps its channel open) is left detached
+//! instead of blocking the shutdown of the node, and is listed in the shutdown report.
+
+use std::{
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::ShutdownReport;
+use tracing::warn;
+
+/// Interval between two checks of the end of a thread
+const JOIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Joins the protocol threads until a deadline and records the outcome
+pub(crate) struct ShutdownDrain {
+    start: Instant,
+    deadline: Instant,
+    report: ShutdownReport,
+}
+
+impl ShutdownDrain {
+    pub(crate) fn new(drain_deadline: Duration) -> Self {
+        let start = Instant::now();
+        Self {
+            start,
+            deadline: start + drain_deadline,
+            report: ShutdownReport::default(),
+        }
+    }
+
+    /// Waits for the end of a thread, at most until the deadline.
+    /// Returns the value returned by the thread if it stopped in time without panicking.
+    pub(crate) fn join<T>(&mut self, handle: JoinHandle<T>) -> Option<T> {
+        let name = handle.thread().name().unwrap_or("unnamed").to_string();
+        while !handle.is_finished() {
+            let now = Instant::now();
+            if now >= self.deadline {
+                warn!("thread {} did not stop before the drain deadline", name);
+                self.report.timed_out_threads.push(name);
+                return None;
+            }
+            std::thread::sleep(JOIN_POLL_INTERVAL.min(self.deadline - now));
+        }
+        match handle.join() {
+            Ok(value) => {
+                self.report.stopped_threads.push(name);
+                Some(value)
+            }
+            Err(_) => {
+                warn!("thread {} panicked", name);
+                self.report.panicked_threads.push(name);
+                None
+            }
+        }
+    }
+
+    /// Counts a peer connection closed during the shutdown
+    pub(crate) fn connection_closed(&mut self) {
+        self.report.closed_connections += 1;
+    }
+
+    pub(crate) fn into_report(mut self) -> ShutdownReport {
+        self.report.duration = self.start.elapsed();
+        self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn<F: FnOnce() + Send + 'static>(name: &str, f: F) -> JoinHandle<()> {
+        std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(f)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_shutdown_drain_deadline() {
+        let mut drain = ShutdownDrain::new(Duration::from_millis(200));
+        let (_leaked_sender, receiver) = crossbeam::channel::bounded::<()>(1);
+
+        drain.join(spawn("stopping", || {}));
+        drain.join(spawn("panicking", || panic!("test panic")));
+        drain.join(spawn("blocked", move || {
+            let _ = receiver.recv();
+        }));
+        drain.connection_closed();
+
+        let report = drain.into_report();
+        assert_eq!(report.stopped_threads, vec!["stopping".to_string()]);
+        assert_eq!(report.panicked_threads, vec!["panicking".to_string()]);
+        assert_eq!(report.timed_out_threads, vec!["blocked".to_string()]);
+        assert_eq!(report.closed_connections, 1);
+        assert!(report.duration >= Duration::from_millis(200));
+        assert!(report.duration < Duration::from_secs(5));
+        assert!(!report.is_clean());
+    }
+}
@@ -20,7 +20,10 @@ use crate::{
     wrap_network::ActiveConnectionsTrait,
 };
 
-use super::{context::protocol_test, tools::assert_hash_asked_to_node};
+use super::{
+    context::{protocol_test, protocol_test_with_storage},
+    tools::assert_hash_asked_to_node,
+};
 
 #[test]
 #[serial]
@@ -301,6 +304,204 @@ fn test_protocol_bans_node_sending_header_with_invalid_signature() {
     )
 }
 
+#[test]
+#[serial]
+fn test_protocol_retracts_rejected_produced_block_without_banning() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create 2 nodes, only node A supports block retractions
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let node_b_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+            let (node_b_peer_id, node_b) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_b_keypair.get_public_key()));
+            network_controller.announce_block_retraction_support(&node_a_peer_id);
+
+            //2. Our node produces a block and announces it before consensus validates it
+            let producer_keypair = KeyPair::generate(0).unwrap();
+            let block = tools::create_block(&producer_keypair);
+            let retraction_signature = producer_keypair
+                .sign(&block.id.get_retraction_hash())
+                .unwrap();
+            protocol_controller
+                .produced_block(block.id, retraction_signature)
+                .unwrap();
+            storage.store_block(block.clone());
+            protocol_controller
+                .integrated_block(block.id, storage)
+                .unwrap();
+            for node in [&node_a, &node_b] {
+                match node
+                    .recv_timeout(Duration::from_millis(1500))
+                    .expect("the node should receive the header")
+                {
+                    Message::Block(message) => match *message {
+                        BlockMessage::Header(header)
+                        | BlockMessage::CompactBlock { header, .. } => {
+                            assert_eq!(header.id, block.id)
+                        }
+                        _ => panic!("the node should receive the header"),
+                    },
+                    _ => panic!("the node should receive the header"),
+                }
+            }
+
+            //3. Consensus rejects the block
+            protocol_controller.notify_block_attack(block.id).unwrap();
+
+            //4. Node A receives a retraction of the block signed by its creator, node B cannot decode it
+            match node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("node A should receive the retraction")
+            {
+                Message::Block(message) => match *message {
+                    BlockMessage::Retraction {
+                        block_id,
+                        signature,
+                    } => {
+                        assert_eq!(block_id, block.id);
+                        producer_keypair
+                            .get_public_key()
+                            .verify_signature(&block.id.get_retraction_hash(), &signature)
+                            .expect("the retraction should be signed by the block creator");
+                    }
+                    _ => panic!("node A should receive the retraction"),
+                },
+                _ => panic!("node A should receive the retraction"),
+            }
+            node_b
+                .recv_timeout(Duration::from_millis(500))
+                .expect_err("node B should not receive the retraction");
+
+            //5. No node is banned
+            std::thread::sleep(Duration::from_millis(1000));
+            assert_eq!(
+                network_controller
+                    .get_connections()
+                    .get_peer_ids_connected(),
+                [node_a_peer_id, node_b_peer_id]
+                    .into_iter()
+                    .collect::<HashSet<PeerId>>()
+            );
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_protocol_only_accepts_retractions_signed_by_block_creator() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create 2 nodes
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let node_b_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, _node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+            let (node_b_peer_id, _node_b) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_b_keypair.get_public_key()));
+
+            //2. Both nodes announce a block created by another node
+            let creator_keypair = KeyPair::generate(0).unwrap();
+            let block = tools::create_block(&creator_keypair);
+            for peer_id in [&node_a_peer_id, &node_b_peer_id] {
+                network_controller
+                    .send_from_peer(
+                        peer_id,
+                        Message::Block(Box::new(BlockMessage::Header(
+                            block.content.header.clone(),
+                        ))),
+                    )
+                    .unwrap();
+            }
+            consensus_event_receiver
+                .wait_command(MassaTime::from_millis(500), |evt| match evt {
+                    MockConsensusControllerMessage::RegisterBlockHeader { block_id, .. } => {
+                        assert_eq!(block_id, block.id);
+                        Some(())
+                    }
+                    _ => None,
+                })
+                .expect("Protocol should send block to consensus");
+
+            //3. Node A relays the retraction of the creator, node B forges its own
+            let retraction_hash = block.id.get_retraction_hash();
+            for (peer_id, signer) in [
+                (&node_a_peer_id, &creator_keypair),
+                (&node_b_peer_id, &node_b_keypair),
+            ] {
+                network_controller
+                    .send_from_peer(
+                        peer_id,
+                        Message::Block(Box::new(BlockMessage::Retraction {
+                            block_id: block.id,
+                            signature: signer.sign(&retraction_hash).unwrap(),
+                        })),
+                    )
+                    .unwrap();
+            }
+            std::thread::sleep(Duration::from_millis(500));
+
+            //4. Notify protocol of the attack: only node B, still vouching for the block, is banned
+            protocol_controller.notify_block_attack(block.id).unwrap();
+            std::thread::sleep(Duration::from_millis(1000));
+            assert_eq!(
+                network_controller
+                    .get_connections()
+                    .get_peer_ids_connected(),
+                [node_a_peer_id].into_iter().collect::<HashSet<PeerId>>()
+            );
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
 #[test]
 #[serial]
 fn test_protocol_does_not_asks_for_block_from_banned_node_who_propagated_header() {
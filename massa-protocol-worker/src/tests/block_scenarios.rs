@@ -13,8 +13,8 @@ use massa_models::operation::OperationId;
 use massa_models::prehash::PreHashSet;
 use massa_models::{block_id::BlockId, slot::Slot};
 use massa_protocol_exports::test_exports::tools;
-use massa_protocol_exports::PeerId;
 use massa_protocol_exports::ProtocolConfig;
+use massa_protocol_exports::{AskBlockFromPeerEvent, PeerId};
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serial_test::serial;
@@ -483,6 +483,87 @@ fn test_no_one_has_it() {
     )
 }
 
+#[test]
+#[serial]
+fn test_ask_block_from_peer() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create 2 nodes
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let node_b_keypair = KeyPair::generate(0).unwrap();
+            let (_node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+            let (node_b_peer_id, node_b) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_b_keypair.get_public_key()));
+
+            //2. Create a block coming from node a.
+            let block = tools::create_block(&node_a_keypair);
+            //end setup
+
+            //3. Ask the block to node b
+            let outcome = protocol_controller
+                .ask_block_from_peer(
+                    block.id,
+                    Some(block.content.header.clone()),
+                    node_b_peer_id,
+                    MassaTime::from_millis(2000),
+                )
+                .unwrap();
+
+            //4. Assert that the block is only asked to node b until the request expires
+            assert_hash_asked_to_node(&node_b, &block.id);
+            let _ = node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect_err("The block has been asked to node A while it was asked to node B.");
+            assert_eq!(
+                outcome.recv_timeout(Duration::from_millis(2000)).unwrap(),
+                AskBlockFromPeerEvent::TimedOut
+            );
+
+            //5. Assert that the block is then asked to the other node
+            assert_hash_asked_to_node(&node_a, &block.id);
+
+            //6. Asking the block to a peer that is not connected fails
+            let outcome = protocol_controller
+                .ask_block_from_peer(
+                    block.id,
+                    None,
+                    PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key()),
+                    MassaTime::from_millis(2000),
+                )
+                .unwrap();
+            assert_eq!(
+                outcome.recv_timeout(Duration::from_millis(1500)).unwrap(),
+                AskBlockFromPeerEvent::PeerNotConnected
+            );
+
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
 #[test]
 #[serial]
 fn test_multiple_blocks_without_a_priori() {
@@ -574,6 +655,83 @@ fn test_multiple_blocks_without_a_priori() {
     )
 }
 
+#[test]
+#[serial]
+fn test_batched_block_asks() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.batch_block_asks = true;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (_node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Create 2 blocks
+            let block_1 = tools::create_block(&node_a_keypair);
+            let block_2 = tools::create_block(&node_a_keypair);
+
+            //3. Send a wishlist that ask for the two blocks
+            protocol_controller
+                .send_wishlist_delta(
+                    vec![
+                        (block_1.id, Some(block_1.content.header.clone())),
+                        (block_2.id, Some(block_2.content.header.clone())),
+                    ]
+                    .into_iter()
+                    .collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .unwrap();
+
+            //4. Assert that the two blocks are asked to node a in a single message
+            let message = node_a.recv_timeout(Duration::from_millis(1500)).unwrap();
+            match message {
+                Message::Block(message) => {
+                    if let BlockMessage::DataRequestBatch(requests) = *message {
+                        let asked_blocks: HashSet<BlockId> = requests
+                            .into_iter()
+                            .map(|(block_id, block_info)| {
+                                assert_eq!(block_info, AskForBlockInfo::OperationIds);
+                                block_id
+                            })
+                            .collect();
+                        assert_eq!(
+                            asked_blocks,
+                            vec![block_1.id, block_2.id].into_iter().collect()
+                        );
+                    } else {
+                        panic!("Node didn't receive the batched ask for blocks message");
+                    }
+                }
+                _ => panic!("Node didn't receive the batched ask for blocks message"),
+            }
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
 #[test]
 #[serial]
 fn test_protocol_sends_blocks_when_asked_for() {
@@ -784,3 +942,650 @@ fn test_protocol_propagates_block_to_node_who_asked_for_operations_and_only_head
         },
     )
 }
+
+#[test]
+#[serial]
+fn test_compact_block_rebuilt_from_known_operations() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Create a block coming from node a, whose operations we already know
+            let op_1 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_2 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_thread = op_1
+                .content_creator_address
+                .get_thread(protocol_config.thread_count);
+            let block = tools::create_block_with_operations(
+                &node_a_keypair,
+                Slot::new(1, op_thread),
+                vec![op_1.clone(), op_2.clone()],
+            );
+            storage.store_operations(vec![op_1.clone(), op_2.clone()]);
+            //end setup
+
+            //3. Node A sends the compact block
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::CompactBlock {
+                        header: block.content.header.clone(),
+                        operation_prefixes: vec![op_1.id.prefix(), op_2.id.prefix()],
+                    })),
+                )
+                .unwrap();
+
+            //4. Assert that we register the block header to the consensus
+            loop {
+                match consensus_event_receiver.wait_command(
+                    MassaTime::from_millis(100),
+                    |command| match command {
+                        MockConsensusControllerMessage::RegisterBlockHeader {
+                            block_id, ..
+                        } => {
+                            assert_eq!(block_id, block.id);
+                            Some(())
+                        }
+                        _evt => None,
+                    },
+                ) {
+                    Some(()) => {
+                        break;
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+
+            //5. Send a wishlist that asks for the block
+            protocol_controller
+                .send_wishlist_delta(
+                    vec![(block.id, Some(block.content.header.clone()))]
+                        .into_iter()
+                        .collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .unwrap();
+
+            //6. Assert that the block is rebuilt and sent to consensus without asking anything
+            loop {
+                match consensus_event_receiver.wait_command(
+                    MassaTime::from_millis(100),
+                    |command| match command {
+                        MockConsensusControllerMessage::RegisterBlock {
+                            block_id,
+                            block_storage,
+                            ..
+                        } => {
+                            assert_eq!(block_id, block.id);
+                            let received_block =
+                                block_storage.read_blocks().get(&block_id).cloned().unwrap();
+                            assert_eq!(received_block.content.operations, block.content.operations);
+                            Some(())
+                        }
+                        _evt => None,
+                    },
+                ) {
+                    Some(()) => {
+                        break;
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+            let _ = node_a
+                .recv_timeout(Duration::from_millis(500))
+                .expect_err("Node a shouldn't be asked for block data");
+
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_compact_block_asks_only_missing_operations() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Create a block coming from node a, whose first operation only we already know
+            let op_1 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_2 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_thread = op_1
+                .content_creator_address
+                .get_thread(protocol_config.thread_count);
+            let block = tools::create_block_with_operations(
+                &node_a_keypair,
+                Slot::new(1, op_thread),
+                vec![op_1.clone(), op_2.clone()],
+            );
+            storage.store_operations(vec![op_1.clone()]);
+            //end setup
+
+            //3. Node A sends the compact block
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::CompactBlock {
+                        header: block.content.header.clone(),
+                        operation_prefixes: vec![op_1.id.prefix(), op_2.id.prefix()],
+                    })),
+                )
+                .unwrap();
+
+            //4. Assert that we register the block header to the consensus
+            loop {
+                match consensus_event_receiver.wait_command(
+                    MassaTime::from_millis(100),
+                    |command| match command {
+                        MockConsensusControllerMessage::RegisterBlockHeader {
+                            block_id, ..
+                        } => {
+                            assert_eq!(block_id, block.id);
+                            Some(())
+                        }
+                        _evt => None,
+                    },
+                ) {
+                    Some(()) => {
+                        break;
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+
+            //5. Send a wishlist that asks for the block
+            protocol_controller
+                .send_wishlist_delta(
+                    vec![(block.id, Some(block.content.header.clone()))]
+                        .into_iter()
+                        .collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .unwrap();
+
+            //6. Assert that we ask node A for the operations we miss, giving the ones we know
+            match assert_hash_asked_to_node(&node_a, &block.id) {
+                AskForBlockInfo::MissingOperations(known_prefixes) => {
+                    assert_eq!(known_prefixes, vec![op_1.id.prefix()]);
+                }
+                _ => panic!("Node A wasn't asked for the missing operations"),
+            }
+
+            //7. Node A answers with the operation IDs and the missing operation only
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::DataResponse {
+                        block_id: block.id,
+                        block_info: BlockInfoReply::MissingOperations {
+                            operation_ids: vec![op_1.id, op_2.id],
+                            operations: vec![op_2],
+                        },
+                    })),
+                )
+                .unwrap();
+
+            //8. Assert that the block is sent to consensus
+            loop {
+                match consensus_event_receiver.wait_command(
+                    MassaTime::from_millis(100),
+                    |command| match command {
+                        MockConsensusControllerMessage::RegisterBlock {
+                            block_id,
+                            block_storage,
+                            ..
+                        } => {
+                            assert_eq!(block_id, block.id);
+                            let received_block =
+                                block_storage.read_blocks().get(&block_id).cloned().unwrap();
+                            assert_eq!(received_block.content.operations, block.content.operations);
+                            Some(())
+                        }
+                        _evt => None,
+                    },
+                ) {
+                    Some(()) => {
+                        break;
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_protocol_propagates_compact_blocks() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.compact_block_relay = true;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (_node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Consensus inform us that a block with operations has been integrated
+            let op_1 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_2 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_thread = op_1
+                .content_creator_address
+                .get_thread(protocol_config.thread_count);
+            let block = tools::create_block_with_operations(
+                &node_a_keypair,
+                Slot::new(1, op_thread),
+                vec![op_1.clone(), op_2.clone()],
+            );
+            storage.store_operations(vec![op_1.clone(), op_2.clone()]);
+            storage.store_block(block.clone());
+            protocol_controller
+                .integrated_block(block.id, storage)
+                .unwrap();
+
+            //3. Check that node a receives the compact block
+            let msg = node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node a should receive the compact block");
+            match msg {
+                Message::Block(block_msg) => match *block_msg {
+                    BlockMessage::CompactBlock {
+                        header,
+                        operation_prefixes,
+                    } => {
+                        assert_eq!(header.id, block.id);
+                        assert_eq!(operation_prefixes, vec![op_1.id.prefix(), op_2.id.prefix()]);
+                    }
+                    _ => panic!("Node a should receive the compact block"),
+                },
+                _ => panic!("Node a should receive the compact block"),
+            }
+
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_header_only_relay() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.header_only_relay = true;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create 2 nodes
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let node_b_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+            let (node_b_peer_id, node_b) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_b_keypair.get_public_key()));
+
+            //2. Send a block header from node a
+            let block = tools::create_block(&node_a_keypair);
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::Header(block.content.header.clone()))),
+                )
+                .unwrap();
+
+            //3. Assert that the header is registered to consensus
+            loop {
+                if let Some(()) =
+                    consensus_event_receiver.wait_command(MassaTime::from_millis(100), |command| {
+                        match command {
+                            MockConsensusControllerMessage::RegisterBlockHeader {
+                                block_id,
+                                ..
+                            } => {
+                                assert_eq!(block_id, block.id);
+                                Some(())
+                            }
+                            _ => None,
+                        }
+                    })
+                {
+                    break;
+                }
+            }
+
+            //4. Assert that the header is relayed to node b
+            let msg = node_b
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node b should receive the relayed header");
+            match msg {
+                Message::Block(block_msg) => match *block_msg {
+                    BlockMessage::Header(header) => assert_eq!(header.id, block.id),
+                    _ => panic!("Node b should receive the relayed header"),
+                },
+                _ => panic!("Node b should receive the relayed header"),
+            }
+
+            //5. Consensus wants the block: it is never asked
+            protocol_controller
+                .send_wishlist_delta(
+                    vec![(block.id, Some(block.content.header.clone()))]
+                        .into_iter()
+                        .collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .unwrap();
+            while let Ok(msg) = node_a.recv_timeout(Duration::from_millis(1000)) {
+                if let Message::Block(block_msg) = msg {
+                    assert!(
+                        !matches!(
+                            *block_msg,
+                            BlockMessage::DataRequest { .. } | BlockMessage::DataRequestBatch(_)
+                        ),
+                        "the block should not be asked"
+                    );
+                }
+            }
+
+            //6. The requests of node b for the block data are refused, but the header is sent
+            for (block_info, expect_header) in [
+                (AskForBlockInfo::OperationIds, false),
+                (AskForBlockInfo::Header, true),
+            ] {
+                network_controller
+                    .send_from_peer(
+                        &node_b_peer_id,
+                        Message::Block(Box::new(BlockMessage::DataRequest {
+                            block_id: block.id,
+                            block_info,
+                        })),
+                    )
+                    .unwrap();
+                let msg = node_b
+                    .recv_timeout(Duration::from_millis(1500))
+                    .expect("Node b should receive a reply");
+                match msg {
+                    Message::Block(block_msg) => match *block_msg {
+                        BlockMessage::DataResponse {
+                            block_id,
+                            block_info: BlockInfoReply::Header(header),
+                        } if expect_header => {
+                            assert_eq!(block_id, block.id);
+                            assert_eq!(header.id, block.id);
+                        }
+                        BlockMessage::DataResponse {
+                            block_id,
+                            block_info: BlockInfoReply::NotFound,
+                        } if !expect_header => assert_eq!(block_id, block.id),
+                        _ => panic!("Node b received an unexpected reply"),
+                    },
+                    _ => panic!("Node b received an unexpected reply"),
+                }
+            }
+
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_propagate_block_header_only() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (_node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. A header is propagated before its block is integrated
+            let block = tools::create_block(&node_a_keypair);
+            protocol_controller
+                .propagate_block_header(block.content.header.clone())
+                .unwrap();
+
+            //3. Check that node a receives the header
+            let msg = node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node a should receive the header");
+            match msg {
+                Message::Block(block_msg) => match *block_msg {
+                    BlockMessage::Header(header) => assert_eq!(header.id, block.id),
+                    _ => panic!("Node a should receive the header"),
+                },
+                _ => panic!("Node a should receive the header"),
+            }
+
+            //4. The block is integrated: it is not announced again to node a
+            storage.store_block(block.clone());
+            protocol_controller
+                .integrated_block(block.id, storage)
+                .unwrap();
+            while let Ok(msg) = node_a.recv_timeout(Duration::from_millis(1000)) {
+                if let Message::Block(block_msg) = msg {
+                    assert!(
+                        !matches!(
+                            *block_msg,
+                            BlockMessage::Header(_) | BlockMessage::CompactBlock { .. }
+                        ),
+                        "the block should not be announced again"
+                    );
+                }
+            }
+
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_reannounce_produced_block() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_config.produced_block_reannounce_timeout = MassaTime::from_millis(1000);
+    protocol_config.max_produced_block_reannounces = 2;
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            let is_announce = |msg: Message, block_id: &BlockId| match msg {
+                Message::Block(block_msg) => match *block_msg {
+                    BlockMessage::Header(header) | BlockMessage::CompactBlock { header, .. } => {
+                        header.id == *block_id
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Our node produces a block, which is integrated
+            let block = tools::create_block(&node_a_keypair);
+            let retraction_signature = node_a_keypair
+                .sign(&block.id.get_retraction_hash())
+                .unwrap();
+            protocol_controller
+                .produced_block(block.id, retraction_signature)
+                .unwrap();
+            storage.store_block(block.clone());
+            protocol_controller
+                .integrated_block(block.id, storage)
+                .unwrap();
+
+            //3. Node a receives the header, then again as it does not acknowledge it
+            for _ in 0..2 {
+                let msg = node_a
+                    .recv_timeout(Duration::from_millis(2500))
+                    .expect("Node a should receive the header");
+                assert!(is_announce(msg, &block.id));
+            }
+
+            //4. Node a asks for the block: it is answered and the header is not announced anymore
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::DataRequest {
+                        block_id: block.id,
+                        block_info: AskForBlockInfo::OperationIds,
+                    })),
+                )
+                .unwrap();
+            while let Ok(msg) = node_a.recv_timeout(Duration::from_millis(2500)) {
+                assert!(
+                    !is_announce(msg, &block.id),
+                    "the block should not be announced again"
+                );
+            }
+
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
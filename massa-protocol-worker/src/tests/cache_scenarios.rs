@@ -4,6 +4,7 @@ use std::collections::HashSet;
 use std::time::Duration;
 
 use massa_consensus_exports::test_exports::MockConsensusControllerMessage;
+use massa_models::address::Address;
 use massa_models::operation::OperationId;
 use massa_models::{block_id::BlockId, prehash::PreHashSet, slot::Slot};
 use massa_protocol_exports::PeerId;
@@ -13,7 +14,10 @@ use massa_time::MassaTime;
 use serial_test::serial;
 
 use crate::{
-    handlers::block_handler::{AskForBlockInfo, BlockInfoReply, BlockMessage},
+    handlers::{
+        block_handler::{AskForBlockInfo, BlockInfoReply, BlockMessage},
+        endorsement_handler::cache::EndorsementCache,
+    },
     messages::Message,
 };
 
@@ -201,3 +205,34 @@ fn test_noting_block_does_not_panic_with_one_max_node_known_blocks_size() {
         },
     )
 }
+
+#[test]
+fn test_endorsement_cache_remembers_the_peers_of_block_producers() {
+    let mut cache = EndorsementCache::new(100, 1024, Duration::from_secs(60), 2);
+    let producer = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    let peers: Vec<PeerId> = (0..3)
+        .map(|_| PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key()))
+        .collect();
+
+    // only the most recent peers are kept, without duplicates
+    cache.insert_producer_peer(producer, &peers[0]);
+    cache.insert_producer_peer(producer, &peers[1]);
+    cache.insert_producer_peer(producer, &peers[0]);
+    assert_eq!(
+        cache.producer_peers.peek(&producer),
+        Some(&vec![peers[0].clone(), peers[1].clone()])
+    );
+    cache.insert_producer_peer(producer, &peers[2]);
+    assert_eq!(
+        cache.producer_peers.peek(&producer),
+        Some(&vec![peers[2].clone(), peers[0].clone()])
+    );
+
+    // disconnected peers are forgotten
+    let peers_connected: HashSet<PeerId> = [peers[0].clone()].into_iter().collect();
+    cache.update_cache(&peers_connected);
+    assert_eq!(
+        cache.producer_peers.peek(&producer),
+        Some(&vec![peers[0].clone()])
+    );
+}
@@ -1,10 +1,22 @@
 use std::{collections::HashMap, fs::read_to_string, sync::Arc};
 
 use crate::{
-    connectivity::start_connectivity_thread, create_protocol_controller,
-    handlers::peer_handler::models::PeerDB, manager::ProtocolManagerImpl,
-    messages::MessagesHandler, tests::mock_network::MockNetworkController,
+    bandwidth::BandwidthNetworkController,
+    buffer_pool::BufferPool,
+    compression::{CompressionNetworkController, MessageCompression},
+    connectivity::start_connectivity_thread,
+    create_protocol_controller,
+    handlers::{
+        block_handler::BlockRetractions, endorsement_handler::EndorsementSubscriptions,
+        peer_handler::models::PeerDB,
+    },
+    manager::ProtocolManagerImpl,
+    messages::MessagesHandler,
+    tests::mock_network::MockNetworkController,
 };
+
+#[cfg(feature = "fault_injection")]
+use crate::fault_injection::wrap_network_controller;
 use crossbeam::channel::Receiver;
 use massa_channel::MassaChannel;
 use massa_consensus_exports::{
@@ -89,17 +101,22 @@ pub fn start_protocol_controller_with_mock_network(
         Some(config.max_size_channel_network_to_peer_handler),
     );
 
+    let (controller, channels) = create_protocol_controller(config.clone());
+
     // Register channels for handlers
     let message_handlers: MessagesHandler = MessagesHandler {
         sender_blocks: sender_blocks.clone(),
         sender_endorsements: sender_endorsements.clone(),
         sender_operations: sender_operations.clone(),
         sender_peers: sender_peers.clone(),
+        bandwidth: channels.bandwidth.clone(),
+        compression: Arc::new(MessageCompression::new(&config)),
+        buffer_pool: Arc::new(BufferPool::new(&config)),
+        endorsement_subscriptions: Arc::new(EndorsementSubscriptions::new(&config)),
+        block_retractions: Arc::new(BlockRetractions::new()),
         id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
     };
 
-    let (controller, channels) = create_protocol_controller(config.clone());
-
     let network_controller = Box::new(MockNetworkController::new(
         message_handlers.clone(),
         peer_db.clone(),
@@ -111,10 +128,28 @@ pub fn start_protocol_controller_with_mock_network(
     };
     let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
 
+    // the messages sent by the protocol go through the simulated faults, if any
+    #[cfg(feature = "fault_injection")]
+    let connectivity_network_controller =
+        wrap_network_controller(network_controller.clone(), &config);
+    #[cfg(not(feature = "fault_injection"))]
+    let connectivity_network_controller = network_controller.clone();
+    let connectivity_network_controller = Box::new(BandwidthNetworkController::new(
+        connectivity_network_controller,
+        channels.bandwidth.clone(),
+        message_handlers.buffer_pool.clone(),
+    ));
+    let connectivity_network_controller = Box::new(CompressionNetworkController::new(
+        connectivity_network_controller,
+        message_handlers.compression.clone(),
+        message_handlers.buffer_pool.clone(),
+    ));
+
+    let shutdown_drain_deadline = config.shutdown_drain_deadline.to_duration();
     let connectivity_thread_handle = start_connectivity_thread(
         PeerId::from_public_key(keypair.get_public_key()),
         selector_controller,
-        network_controller.clone(),
+        connectivity_network_controller,
         consensus_controller,
         pool_controller,
         (sender_blocks, receiver_blocks),
@@ -144,7 +179,7 @@ pub fn start_protocol_controller_with_mock_network(
         .0,
     )?;
 
-    let manager = ProtocolManagerImpl::new(connectivity_thread_handle);
+    let manager = ProtocolManagerImpl::new(connectivity_thread_handle, shutdown_drain_deadline);
 
     Ok((network_controller, controller, Box::new(manager)))
 }
@@ -199,7 +234,7 @@ where
         selector_event_receiver,
     );
 
-    protocol_manager.stop()
+    protocol_manager.stop();
 }
 
 pub fn protocol_test_with_storage<F>(protocol_config: &ProtocolConfig, test: F)
@@ -255,7 +290,7 @@ where
         storage,
     );
 
-    protocol_manager.stop()
+    protocol_manager.stop();
 }
 
 // /// send a block and assert it has been propagate (or not)
@@ -364,3 +364,66 @@ fn test_protocol_propagates_endorsements_only_to_nodes_that_dont_know_about_it_b
         },
     )
 }
+
+#[test]
+#[serial]
+fn test_protocol_does_not_propagate_an_endorsement_twice_to_the_same_node() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (_node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Propagate the same endorsement twice
+            let endorsement = tools::create_endorsement();
+            storage.store_endorsements(vec![endorsement.clone()]);
+            protocol_controller
+                .propagate_endorsements(storage.clone())
+                .unwrap();
+            let msg = node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node A should receive the endorsement");
+            match msg {
+                Message::Endorsement(EndorsementMessage::Endorsements(endorsements)) => {
+                    assert_eq!(endorsements, vec![endorsement]);
+                }
+                _ => panic!("Unexpected message type"),
+            }
+            protocol_controller.propagate_endorsements(storage).unwrap();
+
+            //3. Check that the node received the endorsement only once
+            let _ = node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect_err("Node A should not receive the endorsement again");
+            let stats = protocol_controller.get_endorsement_dedup_stats().unwrap();
+            assert_eq!(stats.sent_endorsements, 1);
+            assert_eq!(stats.known_endorsements, 1);
+            assert_eq!(stats.hit_rate(), 0.5);
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
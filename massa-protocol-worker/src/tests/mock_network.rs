@@ -170,6 +170,12 @@ impl MockNetworkController {
         (peer_id, receiver)
     }
 
+    /// Simulate a peer that announced its support of block retractions in the handshake
+    pub fn announce_block_retraction_support(&mut self, peer_id: &PeerId) {
+        let block_retractions = &self.messages_handler.block_retractions;
+        block_retractions.on_handshake(peer_id, &[block_retractions.handshake_flags()]);
+    }
+
     pub fn remove_fake_connection(&mut self, peer_id: &PeerId) {
         self.connections.write().connections.remove(peer_id);
     }
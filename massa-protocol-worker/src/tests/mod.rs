@@ -24,6 +24,7 @@ mod in_block_operations_scenarios;
 mod mock_network;
 mod operations_scenarios;
 mod peer_priorization;
+mod peer_scores;
 mod tools;
 
 #[test]
@@ -3,7 +3,7 @@
 use std::time::Duration;
 
 use massa_consensus_exports::test_exports::MockConsensusControllerMessage;
-use massa_models::{block_id::BlockId, prehash::PreHashSet, slot::Slot};
+use massa_models::{address::Address, block_id::BlockId, prehash::PreHashSet, slot::Slot};
 use massa_pool_exports::test_exports::MockPoolControllerMessage;
 use massa_protocol_exports::PeerId;
 use massa_protocol_exports::{test_exports::tools, ProtocolConfig};
@@ -152,6 +152,68 @@ fn test_protocol_does_not_send_invalid_operations_it_receives_to_pool() {
     )
 }
 
+#[test]
+#[serial]
+fn test_protocol_does_not_send_operations_of_banned_sources_to_pool() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              mut pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create 1 node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, _node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Ban the creator of the ops
+            protocol_controller
+                .ban_address_sources(vec![Address::from_public_key(
+                    &node_a_keypair.get_public_key(),
+                )])
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+
+            //3. Node A send an op created by the banned address
+            let operation_1 = tools::create_operation_with_expire_period(&node_a_keypair, 1);
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Operation(OperationMessage::Operations(vec![operation_1])),
+                )
+                .unwrap();
+
+            //4. Check protocol didn't sent operations to pool.
+            if pool_event_receiver
+                .wait_command(MassaTime::from_millis(1000), |_| Some(()))
+                .is_some()
+            {
+                panic!("Unexpected protocol pool event.");
+            }
+
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
 #[test]
 #[serial]
 fn test_protocol_propagates_operations_to_active_nodes() {
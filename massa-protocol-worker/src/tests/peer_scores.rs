@@ -0,0 +1,77 @@
+// In these tests, peers with hand crafted behaviors are scored
+// and the scores have to reflect which peers behaved better.
+
+use std::{collections::HashSet, time::Duration};
+
+use massa_protocol_exports::{PeerId, ProtocolConfig};
+use massa_signature::KeyPair;
+
+use crate::peer_scores::PeerScores;
+
+fn new_peer_id() -> PeerId {
+    PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+}
+
+#[test]
+fn test_unknown_peer_has_neutral_score() {
+    let peer_scores = PeerScores::new(&ProtocolConfig::default());
+    assert_eq!(peer_scores.get_score(&new_peer_id()), 0.5);
+}
+
+#[test]
+fn test_peer_scores_reflect_behavior() {
+    let config = ProtocolConfig::default();
+    let mut peer_scores = PeerScores::new(&config);
+    let good_peer = new_peer_id();
+    let bad_peer = new_peer_id();
+
+    for _ in 0..10 {
+        peer_scores.record_header(&good_peer, true);
+        peer_scores.record_message(&good_peer, true);
+        peer_scores.record_ask_block_response(&good_peer, Duration::from_millis(10));
+        peer_scores.record_operations(&good_peer, 10, 10);
+
+        peer_scores.record_header(&bad_peer, false);
+        peer_scores.record_message(&bad_peer, false);
+        peer_scores.record_ask_block_timeout(&bad_peer);
+        peer_scores.record_operations(&bad_peer, 10, 0);
+    }
+
+    assert!(peer_scores.get_score(&good_peer) > 0.9);
+    assert!(peer_scores.get_score(&bad_peer) < 0.1);
+
+    let stats = peer_scores.get_all();
+    assert_eq!(stats[&good_peer].valid_headers, 10);
+    assert_eq!(stats[&bad_peer].ask_block_timeouts, 10);
+    assert_eq!(
+        stats[&bad_peer].ask_block_latency,
+        Some(config.ask_block_timeout)
+    );
+
+    // only one weighted component: the score is this component
+    let config = ProtocolConfig {
+        peer_score_header_validity_weight: 0.0,
+        peer_score_latency_weight: 0.0,
+        peer_score_garbage_weight: 0.0,
+        ..Default::default()
+    };
+    let mut peer_scores = PeerScores::new(&config);
+    peer_scores.record_header(&bad_peer, false);
+    peer_scores.record_operations(&bad_peer, 8, 8);
+    assert_eq!(peer_scores.get_score(&bad_peer), 0.9);
+}
+
+#[test]
+fn test_disconnected_peers_are_forgotten() {
+    let mut peer_scores = PeerScores::new(&ProtocolConfig::default());
+    let connected_peer = new_peer_id();
+    let disconnected_peer = new_peer_id();
+    peer_scores.record_header(&disconnected_peer, false);
+
+    peer_scores.update_connected_peers(&HashSet::from([connected_peer.clone()]));
+
+    let stats = peer_scores.get_all();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[&connected_peer].score, 0.5);
+    assert_eq!(peer_scores.get_score(&disconnected_peer), 0.5);
+}
@@ -0,0 +1,185 @@
+//! Trace of the commands and events of the protocol worker.
+//!
+//! Each command sent to a handler thread gets a sequence id and a timestamp when it is queued,
+//! and a second timestamp when the handler takes it from its channel.
+//! The commands of a given name are taken in the order they were queued, so the handler
+//! only needs the name of the command to match it with its pending trace.
+//! Events happening inside the handlers are traced with the same sequence ids.
+//!
+//! The latest `max_trace_events` traces are kept and can be queried to diagnose message latencies.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use massa_protocol_exports::ProtocolTraceEvent;
+use parking_lot::Mutex;
+use tracing::trace;
+
+#[derive(Default)]
+struct TraceState {
+    /// sequence id and queuing timestamp of the queued commands not taken yet, by command name
+    pending: HashMap<&'static str, VecDeque<(u64, u64)>>,
+    /// latest completed traces, oldest first
+    events: VecDeque<ProtocolTraceEvent>,
+}
+
+pub struct ProtocolTracer {
+    max_events: usize,
+    next_sequence: AtomicU64,
+    state: Mutex<TraceState>,
+}
+
+impl ProtocolTracer {
+    pub fn new(max_events: usize) -> Self {
+        Self {
+            max_events,
+            next_sequence: AtomicU64::new(0),
+            state: Mutex::new(TraceState::default()),
+        }
+    }
+
+    /// Traces a command queued to a handler, returns its sequence id
+    pub fn enqueue(&self, name: &'static str) -> u64 {
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        if self.max_events == 0 {
+            return sequence;
+        }
+        let mut state = self.state.lock();
+        let pending = state.pending.entry(name).or_default();
+        // the commands never taken (handler stopped) are forgotten
+        if pending.len() >= self.max_events {
+            pending.pop_front();
+        }
+        pending.push_back((sequence, now_us()));
+        sequence
+    }
+
+    /// Traces a command that could not be queued because its channel was full or closed
+    pub fn dropped(&self, name: &'static str, sequence: u64) {
+        if self.max_events == 0 {
+            return;
+        }
+        let mut state = self.state.lock();
+        let Some(pending) = state.pending.get_mut(name) else {
+            return;
+        };
+        let Some(index) = pending.iter().position(|(seq, _)| *seq == sequence) else {
+            return;
+        };
+        let (_, enqueued_at_us) = pending.remove(index).expect("index found above");
+        self.record(&mut state, sequence, name, enqueued_at_us, None);
+    }
+
+    /// Traces a command taken by its handler
+    pub fn dequeue(&self, name: &'static str) {
+        if self.max_events == 0 {
+            return;
+        }
+        let mut state = self.state.lock();
+        let Some((sequence, enqueued_at_us)) = state
+            .pending
+            .get_mut(name)
+            .and_then(|pending| pending.pop_front())
+        else {
+            return;
+        };
+        self.record(&mut state, sequence, name, enqueued_at_us, Some(now_us()));
+    }
+
+    /// Traces an event happening inside a handler
+    pub fn event(&self, name: &'static str) {
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        if self.max_events == 0 {
+            return;
+        }
+        let now = now_us();
+        self.record(&mut self.state.lock(), sequence, name, now, Some(now));
+    }
+
+    fn record(
+        &self,
+        state: &mut TraceState,
+        sequence: u64,
+        name: &'static str,
+        enqueued_at_us: u64,
+        dequeued_at_us: Option<u64>,
+    ) {
+        let event = ProtocolTraceEvent {
+            sequence,
+            name: name.to_string(),
+            enqueued_at_us,
+            dequeued_at_us,
+        };
+        trace!("protocol trace: {}", event);
+        if state.events.len() >= self.max_events {
+            state.events.pop_front();
+        }
+        state.events.push_back(event);
+    }
+
+    /// Latest traces, oldest first
+    pub fn get_events(&self) -> Vec<ProtocolTraceEvent> {
+        self.state.lock().events.iter().cloned().collect()
+    }
+}
+
+pub type SharedProtocolTracer = Arc<ProtocolTracer>;
+
+/// Microseconds since the UNIX epoch
+fn now_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_tracer() {
+        let tracer = ProtocolTracer::new(3);
+        let first = tracer.enqueue("propagate_operations");
+        let second = tracer.enqueue("propagate_operations");
+        let dropped = tracer.enqueue("integrated_block");
+        tracer.dropped("integrated_block", dropped);
+        tracer.event("ban_peer");
+        // nothing queued under this name
+        tracer.dequeue("wishlist_delta");
+        tracer.dequeue("propagate_operations");
+
+        let events = tracer.get_events();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event.sequence)
+                .collect::<Vec<_>>(),
+            vec![dropped, 3, first]
+        );
+        assert_eq!(events[0].dequeued_at_us, None);
+        assert_eq!(events[1].queue_latency_us(), Some(0));
+        assert!(events[2].dequeued_at_us.unwrap() >= events[2].enqueued_at_us);
+
+        // only the latest traces are kept
+        tracer.dequeue("propagate_operations");
+        let events = tracer.get_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].sequence, second);
+        assert_eq!(events[2].name, "propagate_operations");
+
+        // a disabled tracer keeps nothing
+        let tracer = ProtocolTracer::new(0);
+        let sequence = tracer.enqueue("propagate_operations");
+        tracer.dequeue("propagate_operations");
+        tracer.event("ban_peer");
+        assert_eq!(sequence, 0);
+        assert!(tracer.get_events().is_empty());
+    }
+}
@@ -26,17 +26,20 @@ use std::{collections::HashMap, fs::read_to_string, ops::Bound::Included, sync::
 use tracing::{debug, log::warn};
 
 use crate::{
+    bandwidth::{BandwidthAccounting, BandwidthNetworkController, SharedBandwidthAccounting},
+    buffer_pool::BufferPool,
+    compression::{CompressionNetworkController, MessageCompression},
     connectivity::{start_connectivity_thread, ConnectivityCommand},
     context::Context,
     controller::ProtocolControllerImpl,
     handlers::{
         block_handler::{
             commands_propagation::BlockHandlerPropagationCommand,
-            commands_retrieval::BlockHandlerRetrievalCommand,
+            commands_retrieval::BlockHandlerRetrievalCommand, BlockRetractions,
         },
         endorsement_handler::{
             commands_propagation::EndorsementHandlerPropagationCommand,
-            commands_retrieval::EndorsementHandlerRetrievalCommand,
+            commands_retrieval::EndorsementHandlerRetrievalCommand, EndorsementSubscriptions,
         },
         operation_handler::{
             commands_propagation::OperationHandlerPropagationCommand,
@@ -50,9 +53,14 @@ use crate::{
     ip::to_canonical,
     manager::ProtocolManagerImpl,
     messages::MessagesHandler,
-    wrap_network::NetworkControllerImpl,
+    propagation_lanes::{PropagationLanes, SharedPropagationLanes},
+    trace::{ProtocolTracer, SharedProtocolTracer},
+    wrap_network::{NetworkController, NetworkControllerImpl},
 };
 
+#[cfg(feature = "fault_injection")]
+use crate::fault_injection::wrap_network_controller;
+
 pub struct ProtocolChannels {
     pub operation_handler_retrieval: (
         MassaSender<OperationHandlerRetrievalCommand>,
@@ -86,6 +94,9 @@ pub struct ProtocolChannels {
         MassaSender<PeerManagementCmd>,
         MassaReceiver<PeerManagementCmd>,
     ),
+    pub propagation_lanes: SharedPropagationLanes,
+    pub bandwidth: SharedBandwidthAccounting,
+    pub tracer: SharedProtocolTracer,
 }
 
 /// This function exists because consensus need the protocol controller and we need consensus controller.
@@ -131,14 +142,26 @@ pub fn create_protocol_controller(
         "peer_management_ext".to_string(),
         Some(config.max_size_channel_commands_peers),
     );
+    let propagation_lanes = Arc::new(PropagationLanes::new(
+        config.propagation_priority_max_wait.to_duration(),
+    ));
+    let bandwidth = Arc::new(BandwidthAccounting::new(
+        &config,
+        sender_peer_management_ext.clone(),
+    ));
+    let tracer = Arc::new(ProtocolTracer::new(config.max_trace_events));
     (
         Box::new(ProtocolControllerImpl::new(
             sender_blocks_retrieval_ext.clone(),
             sender_blocks_propagation_ext.clone(),
             sender_operations_propagation_ext.clone(),
+            sender_operations_retrieval_ext.clone(),
             sender_endorsements_propagation_ext.clone(),
             sender_connectivity_ext.clone(),
             sender_peer_management_ext.clone(),
+            propagation_lanes.clone(),
+            bandwidth.clone(),
+            tracer.clone(),
         )),
         ProtocolChannels {
             operation_handler_retrieval: (
@@ -164,6 +187,9 @@ pub fn create_protocol_controller(
             ),
             connectivity_thread: (sender_connectivity_ext, receiver_connectivity_ext),
             peer_management_handler: (sender_peer_management_ext, receiver_peer_management_ext),
+            propagation_lanes,
+            bandwidth,
+            tracer,
         },
     )
 }
@@ -213,6 +239,11 @@ pub fn start_protocol_controller(
         sender_endorsements: sender_endorsements.clone(),
         sender_operations: sender_operations.clone(),
         sender_peers: sender_peers.clone(),
+        bandwidth: protocol_channels.bandwidth.clone(),
+        compression: Arc::new(MessageCompression::new(&config)),
+        buffer_pool: Arc::new(BufferPool::new(&config)),
+        endorsement_subscriptions: Arc::new(EndorsementSubscriptions::new(&config)),
+        block_retractions: Arc::new(BlockRetractions::new()),
         id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
     };
 
@@ -316,10 +347,29 @@ pub fn start_protocol_controller(
     };
     peernet_config.max_in_connections = config.max_in_connections;
 
-    let network_controller = Box::new(NetworkControllerImpl::new(PeerNetManager::new(
-        peernet_config,
-    )));
+    let network_controller: Box<dyn NetworkController> = Box::new(NetworkControllerImpl::new(
+        PeerNetManager::new(peernet_config),
+    ));
+    #[cfg(feature = "fault_injection")]
+    let network_controller = wrap_network_controller(network_controller, &config);
+    #[cfg(not(feature = "fault_injection"))]
+    if config.fault_injection.is_some() {
+        warn!("fault injection is configured but the protocol was built without the fault_injection feature, ignoring it");
+    }
+    let network_controller: Box<dyn NetworkController> = Box::new(BandwidthNetworkController::new(
+        network_controller,
+        protocol_channels.bandwidth.clone(),
+        message_handlers.buffer_pool.clone(),
+    ));
+    // messages are compressed before being counted, to count the bytes actually sent
+    let network_controller: Box<dyn NetworkController> =
+        Box::new(CompressionNetworkController::new(
+            network_controller,
+            message_handlers.compression.clone(),
+            message_handlers.buffer_pool.clone(),
+        ));
 
+    let shutdown_drain_deadline = config.shutdown_drain_deadline.to_duration();
     let connectivity_thread_handle = start_connectivity_thread(
         PeerId::from_public_key(keypair.get_public_key()),
         selector_controller,
@@ -371,7 +421,7 @@ pub fn start_protocol_controller(
         massa_metrics,
     )?;
 
-    let manager = ProtocolManagerImpl::new(connectivity_thread_handle);
+    let manager = ProtocolManagerImpl::new(connectivity_thread_handle, shutdown_drain_deadline);
 
     Ok((
         Box::new(manager),
@@ -19,11 +19,18 @@ use massa_api_exports::ApiRequest;
 use massa_api_exports::{
     address::AddressInfo,
     block::{BlockInfo, BlockSummary},
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
-    endorsement::EndorsementInfo,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
+    datastore::{
+        DatastoreEntryInput, DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput,
+        StateViewInfo,
+    },
+    endorsement::{EndorsementInfo, EndorsementsByCreatorFilter},
+    execution::{
+        ExecuteReadOnlyResponse, OffChainTask, OffChainTaskInfo, ReadOnlyBytecodeExecution,
+        ReadOnlyCall,
+    },
+    node::{NodeBandwidthInfo, NodeStatus, NodeTrafficInfo, ProductionReport, ProtocolTraceEvent},
     operation::{OperationInfo, OperationInput},
+    rolls::{RollHistory, RollHistoryInput},
     TimeInterval,
 };
 use massa_models::secure_share::SecureShare;
@@ -185,6 +192,30 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Add addresses staking on other machines, tracked without their keys.
+    pub async fn add_watch_only_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
+        self.http_client
+            .request("add_watch_only_addresses", rpc_params![addresses])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Stop tracking watch-only addresses.
+    pub async fn remove_watch_only_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
+        self.http_client
+            .request("remove_watch_only_addresses", rpc_params![addresses])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Return hash-set of watch-only addresses.
+    pub async fn get_watch_only_addresses(&self) -> RpcResult<PreHashSet<Address>> {
+        self.http_client
+            .request("get_watch_only_addresses", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Bans given ip address(es)
     /// No confirmation to expect.
     pub async fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
@@ -221,6 +252,55 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Registers a node-local task run when final events or datastore changes match its trigger.
+    /// Returns the id of the task.
+    pub async fn node_add_offchain_task(&self, task: OffChainTask) -> RpcResult<u64> {
+        self.http_client
+            .request("node_add_offchain_task", rpc_params![task])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Unregisters node-local tasks by id.
+    pub async fn node_remove_offchain_tasks(&self, ids: Vec<u64>) -> RpcResult<()> {
+        self.http_client
+            .request("node_remove_offchain_tasks", rpc_params![ids])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns the registered node-local tasks.
+    pub async fn node_get_offchain_tasks(&self) -> RpcResult<Vec<OffChainTaskInfo>> {
+        self.http_client
+            .request("node_get_offchain_tasks", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns the bytes exchanged with each connected node, per message type.
+    pub async fn node_get_peers_bandwidth(&self) -> RpcResult<Vec<NodeBandwidthInfo>> {
+        self.http_client
+            .request("node_get_peers_bandwidth", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns the messages exchanged with all the nodes since the node started, per message type and direction.
+    pub async fn node_get_protocol_traffic(&self) -> RpcResult<NodeTrafficInfo> {
+        self.http_client
+            .request("node_get_protocol_traffic", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns the latest traced commands and events of the protocol worker, with their queue timestamps.
+    pub async fn node_get_protocol_trace(&self) -> RpcResult<Vec<ProtocolTraceEvent>> {
+        self.http_client
+            .request("node_get_protocol_trace", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Returns node peers whitelist IP address(es).
     pub async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         self.http_client
@@ -302,6 +382,22 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Open a state view pinning the final and candidate datastores
+    pub async fn open_state_view(&self) -> RpcResult<StateViewInfo> {
+        self.http_client
+            .request("open_state_view", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Close a state view
+    pub async fn close_state_view(&self, view_id: u64) -> RpcResult<()> {
+        self.http_client
+            .request("close_state_view", rpc_params![view_id])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     ////////////////
     // public-api //
     ////////////////
@@ -355,6 +451,28 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Returns information about the endorsements endorsing a given list of block(s) ID(s)
+    pub async fn get_endorsements_by_endorsed_block(
+        &self,
+        block_ids: Vec<BlockId>,
+    ) -> RpcResult<Vec<EndorsementInfo>> {
+        self.http_client
+            .request("get_endorsements_by_endorsed_block", rpc_params![block_ids])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns information about the endorsements created by an address, optionally filtered by slot range and finality
+    pub async fn get_endorsements_by_creator(
+        &self,
+        filter: EndorsementsByCreatorFilter,
+    ) -> RpcResult<Vec<EndorsementInfo>> {
+        self.http_client
+            .request("get_endorsements_by_creator", rpc_params![filter])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Returns block(s) information associated to a given list of block(s) ID(s)
     pub async fn get_blocks(&self, block_ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
         self.http_client
@@ -394,6 +512,28 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Get the signed block production reports of addresses over the final cycles
+    pub async fn get_production_reports(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<ProductionReport>> {
+        self.http_client
+            .request("get_production_reports", rpc_params![addresses])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Get the final roll counts of addresses over ranges of cycles
+    pub async fn get_roll_history(
+        &self,
+        inputs: Vec<RollHistoryInput>,
+    ) -> RpcResult<Vec<RollHistory>> {
+        self.http_client
+            .request("get_roll_history", rpc_params![inputs])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Get datastore entries
     pub async fn get_datastore_entries(
         &self,
@@ -405,6 +545,17 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Get datastore keys
+    pub async fn get_datastore_keys(
+        &self,
+        input: Vec<DatastoreKeysInput>,
+    ) -> RpcResult<Vec<DatastoreKeysOutput>> {
+        self.http_client
+            .request("get_datastore_keys", rpc_params![input])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     // User (interaction with the node)
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
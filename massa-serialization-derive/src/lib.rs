@@ -0,0 +1,750 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! Derive macros generating the serializer and the deserializer of a struct.
+//!
+//! `#[derive(MassaSerialize)]` on a struct `Foo` generates a `FooSerializer` implementing
+//! `Serializer<Foo>`, and `#[derive(MassaDeserialize)]` generates a `FooDeserializer`
+//! implementing `Deserializer<Foo>`. The fields are serialized in declaration order,
+//! each one as described by its `#[massa(...)]` attribute:
+//!
+//! * `varint`: `u64` varint. With `varint(min = expr, max = expr)`, the inclusive bounds
+//!   are checked on deserialization.
+//! * `bool`: one byte, 0 or 1.
+//! * `nested(serializer = expr, deserializer = expr)`: delegates to another serializer and deserializer,
+//!   given either as a type built with its `new()` constructor or as a call to one of its constructors.
+//! * `length_prefixed(max_length = expr, item(...))`: the number of items as a `u64` varint, then the items
+//!   encoded as described in `item(...)`. For maps, the entries are encoded as described in `key(...)`
+//!   and `value(...)`. The collection is rebuilt with `FromIterator`.
+//! * `skip` or `skip = expr`: not serialized, and set to `Default::default()` or to `expr`
+//!   on deserialization. The expression can use the fields declared before.
+//!
+//! The arguments of the constructor of the deserializer are declared on the struct
+//! with `#[massa(deserializer_args(name: Type, ...))]`. They can be used in the bounds
+//! and in the expressions of the deserializers.
+//!
+//! The generated structs hold the serializers of the fields, with the visibility of the struct:
+//! `field` for a value, and `field_length` with `field_item` (or `field_key` and `field_value`)
+//! for a collection.
+//!
+//! ```ignore
+//! #[derive(MassaSerialize, MassaDeserialize)]
+//! #[massa(deserializer_args(max_rolls_length: u64))]
+//! pub struct Rolls {
+//!     #[massa(varint)]
+//!     pub cycle: u64,
+//!     #[massa(length_prefixed(
+//!         max_length = max_rolls_length,
+//!         key(nested(serializer = AddressSerializer, deserializer = AddressDeserializer)),
+//!         value(varint),
+//!     ))]
+//!     pub roll_counts: BTreeMap<Address, u64>,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    meta::ParseNestedMeta, parenthesized, parse_macro_input, punctuated::Punctuated, Data,
+    DeriveInput, Error, Expr, Fields, FnArg, Ident, Token, Type, Visibility,
+};
+
+/// Encoding of a value
+enum Encoding {
+    VarInt {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    Bool,
+    Nested {
+        serializer: Expr,
+        deserializer: Expr,
+    },
+    LengthPrefixed {
+        max_length: Expr,
+        items: Items,
+    },
+}
+
+/// Encoding of the items of a length-prefixed collection
+enum Items {
+    Sequence(Encoding),
+    Map { key: Encoding, value: Encoding },
+}
+
+enum FieldSpec {
+    Encoded(Encoding),
+    /// not serialized, deserialized as the given expression or as the default value
+    Skipped(Option<Expr>),
+}
+
+struct StructField {
+    ident: Ident,
+    ty: Type,
+    spec: FieldSpec,
+}
+
+struct StructSpec {
+    ident: Ident,
+    vis: Visibility,
+    fields: Vec<StructField>,
+    deserializer_args: Vec<FnArg>,
+}
+
+/// Serializer and deserializer of one encoded value, stored in the generated structs
+struct Slot {
+    name: Ident,
+    /// what the value is, used in the documentation of the slot
+    description: String,
+    serializer_type: TokenStream2,
+    serializer: TokenStream2,
+    deserializer_type: TokenStream2,
+    deserializer: TokenStream2,
+}
+
+/// Derives a `Serializer` for a struct, see the crate documentation
+#[proc_macro_derive(MassaSerialize, attributes(massa))]
+pub fn derive_massa_serialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    parse_struct(&input)
+        .and_then(|spec| expand_serializer(&spec))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives a `Deserializer` for a struct, see the crate documentation
+#[proc_macro_derive(MassaDeserialize, attributes(massa))]
+pub fn derive_massa_deserialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    parse_struct(&input)
+        .and_then(|spec| expand_deserializer(&spec))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn parse_struct(input: &DeriveInput) -> syn::Result<StructSpec> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "generic structs are not supported",
+        ));
+    }
+    let named_fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "only structs with named fields are supported",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "only structs are supported",
+            ))
+        }
+    };
+
+    let mut deserializer_args = Vec::new();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("massa"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("deserializer_args") {
+                return Err(meta.error("expected `deserializer_args`"));
+            }
+            let content;
+            parenthesized!(content in meta.input);
+            for arg in Punctuated::<FnArg, Token![,]>::parse_terminated(&content)? {
+                if let FnArg::Receiver(receiver) = arg {
+                    return Err(Error::new_spanned(receiver, "expected a typed argument"));
+                }
+                deserializer_args.push(arg);
+            }
+            Ok(())
+        })?;
+    }
+
+    let mut fields = Vec::with_capacity(named_fields.len());
+    for field in named_fields {
+        let ident = field.ident.clone().expect("named fields have an ident");
+        let mut spec = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("massa"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if spec.is_some() {
+                    return Err(meta.error("a field has a single encoding"));
+                }
+                if meta.path.is_ident("skip") {
+                    let value = if meta.input.peek(Token![=]) {
+                        Some(meta.value()?.parse()?)
+                    } else {
+                        None
+                    };
+                    spec = Some(FieldSpec::Skipped(value));
+                } else {
+                    spec = Some(FieldSpec::Encoded(parse_encoding(&meta)?));
+                }
+                Ok(())
+            })?;
+        }
+        let spec = spec.ok_or_else(|| {
+            Error::new_spanned(&ident, "missing `#[massa(...)]` encoding of the field")
+        })?;
+        fields.push(StructField {
+            ident,
+            ty: field.ty.clone(),
+            spec,
+        });
+    }
+
+    Ok(StructSpec {
+        ident: input.ident.clone(),
+        vis: input.vis.clone(),
+        fields,
+        deserializer_args,
+    })
+}
+
+fn parse_encoding(meta: &ParseNestedMeta) -> syn::Result<Encoding> {
+    if meta.path.is_ident("varint") {
+        let mut min: Option<Expr> = None;
+        let mut max: Option<Expr> = None;
+        if meta.input.peek(syn::token::Paren) {
+            meta.parse_nested_meta(|meta| {
+                if meta.path.is_ident("min") {
+                    min = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("max") {
+                    max = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `min` or `max`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(Encoding::VarInt { min, max })
+    } else if meta.path.is_ident("bool") {
+        Ok(Encoding::Bool)
+    } else if meta.path.is_ident("nested") {
+        let mut serializer: Option<Expr> = None;
+        let mut deserializer: Option<Expr> = None;
+        meta.parse_nested_meta(|meta| {
+            if meta.path.is_ident("serializer") {
+                serializer = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("deserializer") {
+                deserializer = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("expected `serializer` or `deserializer`"));
+            }
+            Ok(())
+        })?;
+        match (serializer, deserializer) {
+            (Some(serializer), Some(deserializer)) => Ok(Encoding::Nested {
+                serializer,
+                deserializer,
+            }),
+            _ => Err(meta.error("`nested` requires a `serializer` and a `deserializer`")),
+        }
+    } else if meta.path.is_ident("length_prefixed") {
+        let mut max_length: Option<Expr> = None;
+        let mut item = None;
+        let mut key = None;
+        let mut value = None;
+        meta.parse_nested_meta(|meta| {
+            if meta.path.is_ident("max_length") {
+                max_length = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("item") {
+                item = Some(parse_item_encoding(&meta)?);
+            } else if meta.path.is_ident("key") {
+                key = Some(parse_item_encoding(&meta)?);
+            } else if meta.path.is_ident("value") {
+                value = Some(parse_item_encoding(&meta)?);
+            } else {
+                return Err(meta.error("expected `max_length`, `item`, `key` or `value`"));
+            }
+            Ok(())
+        })?;
+        let max_length =
+            max_length.ok_or_else(|| meta.error("`length_prefixed` requires a `max_length`"))?;
+        let items = match (item, key, value) {
+            (Some(item), None, None) => Items::Sequence(item),
+            (None, Some(key), Some(value)) => Items::Map { key, value },
+            _ => return Err(meta.error(
+                "`length_prefixed` requires either `item(...)` or both `key(...)` and `value(...)`",
+            )),
+        };
+        Ok(Encoding::LengthPrefixed { max_length, items })
+    } else {
+        Err(meta.error("expected `varint`, `bool`, `nested`, `length_prefixed` or `skip`"))
+    }
+}
+
+/// Parses the encoding of the items of a collection, such as `item(varint)`
+fn parse_item_encoding(meta: &ParseNestedMeta) -> syn::Result<Encoding> {
+    let mut encoding = None;
+    meta.parse_nested_meta(|meta| {
+        if encoding.is_some() {
+            return Err(meta.error("the items have a single encoding"));
+        }
+        let item_encoding = parse_encoding(&meta)?;
+        if let Encoding::LengthPrefixed { .. } = item_encoding {
+            return Err(meta.error("collections of collections are not supported"));
+        }
+        encoding = Some(item_encoding);
+        Ok(())
+    })?;
+    encoding.ok_or_else(|| meta.error("missing encoding of the items"))
+}
+
+/// Type of a serializer given as `Type` or `Type::constructor(args)`, and the expression building it
+fn constructed_type(expr: &Expr) -> syn::Result<(TokenStream2, TokenStream2)> {
+    match expr {
+        Expr::Path(expr_path) => {
+            let path = &expr_path.path;
+            Ok((quote!(#path), quote!(#path::new())))
+        }
+        Expr::Call(call) => match &*call.func {
+            Expr::Path(func) if func.path.segments.len() > 1 => {
+                let leading_colon = &func.path.leading_colon;
+                let segments = func.path.segments.iter().take(func.path.segments.len() - 1);
+                Ok((quote!(#leading_colon #(#segments)::*), quote!(#expr)))
+            }
+            _ => Err(Error::new_spanned(
+                expr,
+                "expected a call to a constructor, such as `Type::new(...)`",
+            )),
+        },
+        _ => Err(Error::new_spanned(
+            expr,
+            "expected a type, or a call to one of its constructors",
+        )),
+    }
+}
+
+/// Serializer and deserializer of a value that is not a collection
+fn slot(name: Ident, description: String, encoding: &Encoding) -> syn::Result<Slot> {
+    match encoding {
+        Encoding::VarInt { min, max } => {
+            let min = min.as_ref().map_or(quote!(u64::MIN), |min| quote!(#min));
+            let max = max.as_ref().map_or(quote!(u64::MAX), |max| quote!(#max));
+            Ok(Slot {
+                name,
+                description,
+                serializer_type: quote!(::massa_serialization::U64VarIntSerializer),
+                serializer: quote!(::massa_serialization::U64VarIntSerializer::new()),
+                deserializer_type: quote!(::massa_serialization::U64VarIntDeserializer),
+                deserializer: quote!(::massa_serialization::U64VarIntDeserializer::new(
+                    ::std::ops::Bound::Included(#min),
+                    ::std::ops::Bound::Included(#max),
+                )),
+            })
+        }
+        Encoding::Bool => Ok(Slot {
+            name,
+            description,
+            serializer_type: quote!(::massa_serialization::BoolSerializer),
+            serializer: quote!(::massa_serialization::BoolSerializer::new()),
+            deserializer_type: quote!(::massa_serialization::BoolDeserializer),
+            deserializer: quote!(::massa_serialization::BoolDeserializer::new()),
+        }),
+        Encoding::Nested {
+            serializer,
+            deserializer,
+        } => {
+            let (serializer_type, serializer) = constructed_type(serializer)?;
+            let (deserializer_type, deserializer) = constructed_type(deserializer)?;
+            Ok(Slot {
+                name,
+                description,
+                serializer_type,
+                serializer,
+                deserializer_type,
+                deserializer,
+            })
+        }
+        Encoding::LengthPrefixed { .. } => {
+            unreachable!("collections are split into several slots")
+        }
+    }
+}
+
+/// Slots of a field: one for a value, or the length and the items for a collection
+fn field_slots(field: &StructField) -> syn::Result<Vec<Slot>> {
+    let ident = &field.ident;
+    match &field.spec {
+        FieldSpec::Skipped(_) => Ok(Vec::new()),
+        FieldSpec::Encoded(Encoding::LengthPrefixed { max_length, items }) => {
+            let mut slots = vec![slot(
+                format_ident!("{}_length", ident),
+                format!("the length of `{}`", ident),
+                &Encoding::VarInt {
+                    min: None,
+                    max: Some(max_length.clone()),
+                },
+            )?];
+            match items {
+                Items::Sequence(item) => slots.push(slot(
+                    format_ident!("{}_item", ident),
+                    format!("the items of `{}`", ident),
+                    item,
+                )?),
+                Items::Map { key, value } => {
+                    slots.push(slot(
+                        format_ident!("{}_key", ident),
+                        format!("the keys of `{}`", ident),
+                        key,
+                    )?);
+                    slots.push(slot(
+                        format_ident!("{}_value", ident),
+                        format!("the values of `{}`", ident),
+                        value,
+                    )?);
+                }
+            }
+            Ok(slots)
+        }
+        FieldSpec::Encoded(encoding) => {
+            Ok(vec![slot(ident.clone(), format!("`{}`", ident), encoding)?])
+        }
+    }
+}
+
+fn expand_serializer(spec: &StructSpec) -> syn::Result<TokenStream2> {
+    let name = &spec.ident;
+    let vis = &spec.vis;
+    let serializer = format_ident!("{}Serializer", name);
+    let struct_doc = format!(" `{}` Serializer", name);
+    let new_doc = format!(" Creates a new `{}` Serializer", name);
+
+    let mut slots = Vec::new();
+    let mut statements = Vec::new();
+    for field in &spec.fields {
+        let ident = &field.ident;
+        match &field.spec {
+            FieldSpec::Skipped(_) => {}
+            FieldSpec::Encoded(Encoding::LengthPrefixed { items, .. }) => {
+                let length = format_ident!("{}_length", ident);
+                let serialize_items = match items {
+                    Items::Sequence(_) => {
+                        let item = format_ident!("{}_item", ident);
+                        quote! {
+                            for item in value.#ident.iter() {
+                                ::massa_serialization::Serializer::serialize(&self.#item, item, buffer)?;
+                            }
+                        }
+                    }
+                    Items::Map { .. } => {
+                        let key = format_ident!("{}_key", ident);
+                        let value = format_ident!("{}_value", ident);
+                        quote! {
+                            for (entry_key, entry_value) in value.#ident.iter() {
+                                ::massa_serialization::Serializer::serialize(&self.#key, entry_key, buffer)?;
+                                ::massa_serialization::Serializer::serialize(&self.#value, entry_value, buffer)?;
+                            }
+                        }
+                    }
+                };
+                statements.push(quote! {
+                    ::massa_serialization::Serializer::serialize(
+                        &self.#length,
+                        &(value.#ident.len() as u64),
+                        buffer,
+                    )?;
+                    #serialize_items
+                });
+            }
+            FieldSpec::Encoded(_) => {
+                statements.push(quote! {
+                    ::massa_serialization::Serializer::serialize(&self.#ident, &value.#ident, buffer)?;
+                });
+            }
+        }
+        slots.extend(field_slots(field)?);
+    }
+    let slot_names: Vec<_> = slots.iter().map(|slot| &slot.name).collect();
+    let slot_docs = slots
+        .iter()
+        .map(|slot| format!(" Serializer of {}", slot.description));
+    let slot_types = slots.iter().map(|slot| &slot.serializer_type);
+    let slot_values = slots.iter().map(|slot| &slot.serializer);
+
+    Ok(quote! {
+        #[doc = #struct_doc]
+        #[derive(Clone)]
+        #vis struct #serializer {
+            #(
+                #[doc = #slot_docs]
+                #vis #slot_names: #slot_types,
+            )*
+        }
+
+        impl #serializer {
+            #[doc = #new_doc]
+            pub fn new() -> Self {
+                Self {
+                    #(#slot_names: #slot_values,)*
+                }
+            }
+        }
+
+        impl ::std::default::Default for #serializer {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl ::massa_serialization::Serializer<#name> for #serializer {
+            fn serialize(
+                &self,
+                value: &#name,
+                buffer: &mut ::std::vec::Vec<u8>,
+            ) -> ::std::result::Result<(), ::massa_serialization::SerializeError> {
+                #(#statements)*
+                Ok(())
+            }
+        }
+    })
+}
+
+fn expand_deserializer(spec: &StructSpec) -> syn::Result<TokenStream2> {
+    let nom = quote!(::massa_serialization::__private::nom);
+    let name = &spec.ident;
+    let vis = &spec.vis;
+    let deserializer = format_ident!("{}Deserializer", name);
+    let struct_doc = format!(" `{}` Deserializer", name);
+    let new_doc = format!(" Creates a new `{}` Deserializer", name);
+    let struct_context = format!("Failed {} deserialization", name);
+    let args = &spec.deserializer_args;
+
+    let mut slots = Vec::new();
+    let mut statements = Vec::new();
+    for field in &spec.fields {
+        let ident = &field.ident;
+        let ty = &field.ty;
+        let field_context = format!("Failed {} deserialization", ident);
+        match &field.spec {
+            FieldSpec::Skipped(value) => {
+                let value = value.as_ref().map_or(
+                    quote!(::std::default::Default::default()),
+                    |value| quote!(#value),
+                );
+                statements.push(quote! {
+                    let #ident: #ty = #value;
+                });
+            }
+            FieldSpec::Encoded(Encoding::LengthPrefixed { items, .. }) => {
+                let length = format_ident!("{}_length", ident);
+                let parse_item = match items {
+                    Items::Sequence(_) => {
+                        let item = format_ident!("{}_item", ident);
+                        quote! {
+                            #nom::error::context("Failed item deserialization", |input| {
+                                ::massa_serialization::Deserializer::deserialize(&self.#item, input)
+                            })
+                        }
+                    }
+                    Items::Map { .. } => {
+                        let key = format_ident!("{}_key", ident);
+                        let value = format_ident!("{}_value", ident);
+                        quote! {
+                            #nom::sequence::tuple((
+                                #nom::error::context("Failed key deserialization", |input| {
+                                    ::massa_serialization::Deserializer::deserialize(&self.#key, input)
+                                }),
+                                #nom::error::context("Failed value deserialization", |input| {
+                                    ::massa_serialization::Deserializer::deserialize(&self.#value, input)
+                                }),
+                            ))
+                        }
+                    }
+                };
+                statements.push(quote! {
+                    let (__input, #ident): (&'a [u8], #ty) = #nom::error::context::<_, E, _, _>(
+                        #field_context,
+                        #nom::Parser::map(
+                            #nom::multi::length_count(
+                                #nom::error::context("Failed length deserialization", |input| {
+                                    ::massa_serialization::Deserializer::deserialize(&self.#length, input)
+                                }),
+                                #parse_item,
+                            ),
+                            |items| items.into_iter().collect(),
+                        ),
+                    )(__input)?;
+                });
+            }
+            FieldSpec::Encoded(_) => {
+                statements.push(quote! {
+                    let (__input, #ident): (&'a [u8], #ty) = #nom::error::context::<_, E, _, _>(
+                        #field_context,
+                        |input| ::massa_serialization::Deserializer::deserialize(&self.#ident, input),
+                    )(__input)?;
+                });
+            }
+        }
+        slots.extend(field_slots(field)?);
+    }
+    let slot_names: Vec<_> = slots.iter().map(|slot| &slot.name).collect();
+    let slot_docs = slots
+        .iter()
+        .map(|slot| format!(" Deserializer of {}", slot.description));
+    let slot_types = slots.iter().map(|slot| &slot.deserializer_type);
+    let slot_values = slots.iter().map(|slot| &slot.deserializer);
+    let field_names = spec.fields.iter().map(|field| &field.ident);
+
+    Ok(quote! {
+        #[doc = #struct_doc]
+        #[derive(Clone)]
+        #vis struct #deserializer {
+            #(
+                #[doc = #slot_docs]
+                #vis #slot_names: #slot_types,
+            )*
+        }
+
+        impl #deserializer {
+            #[doc = #new_doc]
+            #[allow(clippy::too_many_arguments)]
+            pub fn new(#(#args),*) -> Self {
+                Self {
+                    #(#slot_names: #slot_values,)*
+                }
+            }
+        }
+
+        impl ::massa_serialization::Deserializer<#name> for #deserializer {
+            fn deserialize<'a, E>(&self, buffer: &'a [u8]) -> #nom::IResult<&'a [u8], #name, E>
+            where
+                E: #nom::error::ParseError<&'a [u8]> + #nom::error::ContextError<&'a [u8]>,
+            {
+                #nom::error::context(
+                    #struct_context,
+                    |__input: &'a [u8]| -> #nom::IResult<&'a [u8], #name, E> {
+                        #(#statements)*
+                        Ok((__input, #name { #(#field_names,)* }))
+                    },
+                )(buffer)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn parse_error(input: DeriveInput) -> String {
+        match parse_struct(&input) {
+            Ok(_) => panic!("the struct was expected to be rejected"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_struct() {
+        let spec = parse_struct(&parse_quote! {
+            #[massa(deserializer_args(max_length: u64))]
+            pub struct Foo {
+                #[massa(varint(max = 10))]
+                pub a: u64,
+                #[massa(length_prefixed(max_length = max_length, item(bool)))]
+                pub b: Vec<bool>,
+                #[massa(skip)]
+                pub c: u64,
+            }
+        })
+        .unwrap();
+        assert_eq!(spec.deserializer_args.len(), 1);
+        assert_eq!(spec.fields.len(), 3);
+        let slot_names: Vec<String> = spec
+            .fields
+            .iter()
+            .flat_map(|field| field_slots(field).unwrap())
+            .map(|slot| slot.name.to_string())
+            .collect();
+        assert_eq!(slot_names, ["a", "b_length", "b_item"]);
+        assert!(expand_serializer(&spec).is_ok());
+        assert!(expand_deserializer(&spec).is_ok());
+    }
+
+    #[test]
+    fn test_parse_struct_errors() {
+        assert_eq!(
+            parse_error(parse_quote! {
+                struct Foo<T> {
+                    #[massa(skip)]
+                    a: T,
+                }
+            }),
+            "generic structs are not supported"
+        );
+        assert_eq!(
+            parse_error(parse_quote! {
+                struct Foo(u64);
+            }),
+            "only structs with named fields are supported"
+        );
+        assert_eq!(
+            parse_error(parse_quote! {
+                struct Foo {
+                    a: u64,
+                }
+            }),
+            "missing `#[massa(...)]` encoding of the field"
+        );
+        assert_eq!(
+            parse_error(parse_quote! {
+                struct Foo {
+                    #[massa(varint, bool)]
+                    a: u64,
+                }
+            }),
+            "a field has a single encoding"
+        );
+        assert_eq!(
+            parse_error(parse_quote! {
+                struct Foo {
+                    #[massa(length_prefixed(item(varint)))]
+                    a: Vec<u64>,
+                }
+            }),
+            "`length_prefixed` requires a `max_length`"
+        );
+        assert_eq!(
+            parse_error(parse_quote! {
+                struct Foo {
+                    #[massa(length_prefixed(max_length = 1, key(varint)))]
+                    a: BTreeMap<u64, u64>,
+                }
+            }),
+            "`length_prefixed` requires either `item(...)` or both `key(...)` and `value(...)`"
+        );
+        assert_eq!(
+            parse_error(parse_quote! {
+                struct Foo {
+                    #[massa(nested(serializer = FooSerializer))]
+                    a: u64,
+                }
+            }),
+            "`nested` requires a `serializer` and a `deserializer`"
+        );
+    }
+
+    #[test]
+    fn test_constructed_type() {
+        let (ty, value) = constructed_type(&parse_quote!(FooSerializer)).unwrap();
+        assert_eq!(ty.to_string(), "FooSerializer");
+        assert_eq!(value.to_string(), "FooSerializer :: new ()");
+        let (ty, _) = constructed_type(&parse_quote!(foo::FooDeserializer::new(1))).unwrap();
+        assert_eq!(ty.to_string(), "foo :: FooDeserializer");
+        assert!(constructed_type(&parse_quote!(new(1))).is_err());
+        assert!(constructed_type(&parse_quote!(1 + 1)).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! Tests of the serializers and deserializers generated by the derive macros
+
+use massa_serialization::{
+    DeserializeError, Deserializer, MassaDeserialize, MassaSerialize, Serializer,
+    U32VarIntDeserializer, U32VarIntSerializer,
+};
+use std::collections::BTreeMap;
+use std::ops::Bound::Included;
+
+#[derive(Debug, Clone, PartialEq, Eq, MassaSerialize, MassaDeserialize)]
+#[massa(deserializer_args(max_index: u64, max_items: u64))]
+pub struct Sample {
+    #[massa(varint(min = 1, max = max_index))]
+    pub index: u64,
+    #[massa(bool)]
+    pub active: bool,
+    #[massa(nested(
+        serializer = U32VarIntSerializer,
+        deserializer = U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
+    ))]
+    pub weight: u32,
+    #[massa(length_prefixed(max_length = max_items, item(varint)))]
+    pub items: Vec<u64>,
+    #[massa(length_prefixed(max_length = max_items, key(varint), value(bool)))]
+    pub flags: BTreeMap<u64, bool>,
+    #[massa(skip = items.iter().sum())]
+    pub items_sum: u64,
+    #[massa(skip)]
+    pub cache: Option<u64>,
+}
+
+fn sample() -> Sample {
+    Sample {
+        index: 3,
+        active: true,
+        weight: 300,
+        items: vec![1, 200],
+        flags: BTreeMap::from([(5, false), (7, true)]),
+        items_sum: 201,
+        cache: None,
+    }
+}
+
+/// Encoding of `sample()`
+const SAMPLE_BYTES: &[u8] = &[
+    0x03, // index
+    0x01, // active
+    0xac, 0x02, // weight
+    0x02, 0x01, 0xc8, 0x01, // items: length, items
+    0x02, 0x05, 0x00, 0x07, 0x01, // flags: length, entries in key order
+];
+
+#[test]
+fn test_derive_wire_format() {
+    let mut buffer = Vec::new();
+    SampleSerializer::new()
+        .serialize(&sample(), &mut buffer)
+        .unwrap();
+    assert_eq!(buffer, SAMPLE_BYTES);
+}
+
+#[test]
+fn test_derive_round_trip() {
+    let mut value = sample();
+    // skipped fields are not serialized
+    value.cache = Some(1);
+    let mut buffer = Vec::new();
+    SampleSerializer::default()
+        .serialize(&value, &mut buffer)
+        .unwrap();
+    buffer.extend([0xff, 0xff]);
+
+    let (rest, deserialized) = SampleDeserializer::new(10, 2)
+        .deserialize::<DeserializeError>(&buffer)
+        .unwrap();
+    assert_eq!(rest, [0xff, 0xff]);
+    assert_eq!(deserialized, sample());
+
+    // empty collections
+    let empty = Sample {
+        items: Vec::new(),
+        flags: BTreeMap::new(),
+        items_sum: 0,
+        ..sample()
+    };
+    let mut buffer = Vec::new();
+    SampleSerializer::new()
+        .serialize(&empty, &mut buffer)
+        .unwrap();
+    let (rest, deserialized) = SampleDeserializer::new(10, 0)
+        .deserialize::<DeserializeError>(&buffer)
+        .unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(deserialized, empty);
+}
+
+#[test]
+fn test_derive_rejects_invalid_input() {
+    let deserializer = SampleDeserializer::new(10, 2);
+    let deserialize = |buffer: &[u8]| deserializer.deserialize::<DeserializeError>(buffer);
+    assert!(deserialize(SAMPLE_BYTES).is_ok());
+
+    // bounds of the varints
+    assert!(SampleDeserializer::new(2, 2)
+        .deserialize::<DeserializeError>(SAMPLE_BYTES)
+        .is_err());
+    let mut zero_index = SAMPLE_BYTES.to_vec();
+    zero_index[0] = 0x00;
+    assert!(deserialize(&zero_index).is_err());
+
+    // invalid bool
+    let mut invalid_bool = SAMPLE_BYTES.to_vec();
+    invalid_bool[1] = 0x02;
+    assert!(deserialize(&invalid_bool).is_err());
+    let mut invalid_map_bool = SAMPLE_BYTES.to_vec();
+    invalid_map_bool[10] = 0x02;
+    assert!(deserialize(&invalid_map_bool).is_err());
+
+    // maximum length of the collections
+    assert!(SampleDeserializer::new(10, 1)
+        .deserialize::<DeserializeError>(SAMPLE_BYTES)
+        .is_err());
+
+    // truncated input
+    for length in 0..SAMPLE_BYTES.len() {
+        assert!(deserialize(&SAMPLE_BYTES[..length]).is_err());
+    }
+}
@@ -17,6 +17,14 @@ use num::rational::Ratio;
 use num::Integer;
 use thiserror::Error;
 
+pub use massa_serialization_derive::{MassaDeserialize, MassaSerialize};
+
+/// Dependencies of the code generated by the derive macros
+#[doc(hidden)]
+pub mod __private {
+    pub use nom;
+}
+
 #[non_exhaustive]
 #[derive(Display, Error, Debug, Clone)]
 pub enum SerializeError {
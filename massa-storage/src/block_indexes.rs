@@ -59,6 +59,7 @@ impl BlockIndexes {
                     .insert(block.id);
             }
 
+            #[cfg(feature = "metrics")]
             massa_metrics::set_blocks_counter(self.blocks.len());
         }
     }
@@ -107,6 +108,7 @@ impl BlockIndexes {
                     }
                 }
             }
+            #[cfg(feature = "metrics")]
             massa_metrics::set_blocks_counter(self.blocks.len());
             return Some(b);
         }
@@ -167,6 +169,32 @@ impl BlockIndexes {
         )
     }
 
+    /// Iterate over the blocks of a slot range in slot order.
+    /// Slots are ordered by period then by thread, so the threads of a period are interleaved.
+    /// The blocks sharing a slot are ordered by ID.
+    /// Arguments:
+    /// - slot_range: the slot range of interest
+    ///
+    /// Returns:
+    /// - an iterator over the blocks, borrowing the indexes for as long as it lives
+    pub fn iter_by_slot<R>(&self, slot_range: R) -> impl Iterator<Item = &SecureShareBlock> + '_
+    where
+        R: RangeBounds<Slot>,
+    {
+        self.index_by_slot
+            .range(slot_range)
+            .flat_map(move |(_, block_ids)| {
+                let mut block_ids: Vec<&BlockId> = block_ids.iter().collect();
+                block_ids.sort_unstable();
+                block_ids.into_iter().map(move |block_id| {
+                    self.blocks
+                        .get(block_id)
+                        .expect("block in slot index should be stored")
+                        .as_ref()
+                })
+            })
+    }
+
     /// Get the block ids of the blocks containing a given operation.
     /// Arguments:
     /// - id: the ID of the operation
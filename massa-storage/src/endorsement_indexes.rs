@@ -2,6 +2,7 @@ use std::collections::hash_map::Entry;
 
 use massa_models::{
     address::Address,
+    block_id::BlockId,
     endorsement::{EndorsementId, SecureShareEndorsement},
     prehash::{PreHashMap, PreHashSet},
 };
@@ -14,6 +15,8 @@ pub struct EndorsementIndexes {
     endorsements: PreHashMap<EndorsementId, Box<SecureShareEndorsement>>,
     /// Structure mapping creators with the created endorsements
     index_by_creator: PreHashMap<Address, PreHashSet<EndorsementId>>,
+    /// Structure mapping endorsed blocks with the endorsements endorsing them
+    index_by_endorsed_block: PreHashMap<BlockId, PreHashSet<EndorsementId>>,
 }
 
 impl EndorsementIndexes {
@@ -28,7 +31,13 @@ impl EndorsementIndexes {
                 .entry(endorsement.content_creator_address)
                 .or_default()
                 .insert(endorsement.id);
+            // update endorsed block index
+            self.index_by_endorsed_block
+                .entry(endorsement.content.endorsed_block)
+                .or_default()
+                .insert(endorsement.id);
 
+            #[cfg(feature = "metrics")]
             massa_metrics::set_endorsements_counter(self.endorsements.len());
         }
     }
@@ -41,6 +50,7 @@ impl EndorsementIndexes {
         endorsement_id: &EndorsementId,
     ) -> Option<Box<SecureShareEndorsement>> {
         if let Some(e) = self.endorsements.remove(endorsement_id) {
+            #[cfg(feature = "metrics")]
             massa_metrics::set_endorsements_counter(self.endorsements.len());
 
             // update creator index
@@ -51,6 +61,15 @@ impl EndorsementIndexes {
                     occ.remove();
                 }
             }
+            // update endorsed block index
+            if let Entry::Occupied(mut occ) =
+                self.index_by_endorsed_block.entry(e.content.endorsed_block)
+            {
+                occ.get_mut().remove(&e.id);
+                if occ.get().is_empty() {
+                    occ.remove();
+                }
+            }
             return Some(e);
         }
         None
@@ -78,4 +97,17 @@ impl EndorsementIndexes {
     ) -> Option<&PreHashSet<EndorsementId>> {
         self.index_by_creator.get(address)
     }
+
+    /// Get endorsements endorsing a block
+    /// Arguments:
+    /// - block_id: the endorsed block
+    ///
+    /// Returns:
+    /// - optional reference to a set of endorsements endorsing that block
+    pub fn get_endorsements_by_endorsed_block(
+        &self,
+        block_id: &BlockId,
+    ) -> Option<&PreHashSet<EndorsementId>> {
+        self.index_by_endorsed_block.get(block_id)
+    }
 }
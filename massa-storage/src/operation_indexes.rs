@@ -36,6 +36,7 @@ impl OperationIndexes {
                 .or_default()
                 .insert(operation.id);
 
+            #[cfg(feature = "metrics")]
             massa_metrics::set_operations_counter(self.operations.len());
         }
     }
@@ -48,6 +49,7 @@ impl OperationIndexes {
         operation_id: &OperationId,
     ) -> Option<Box<SecureShareOperation>> {
         if let Some(o) = self.operations.remove(operation_id) {
+            #[cfg(feature = "metrics")]
             massa_metrics::set_operations_counter(self.operations.len());
 
             // update creator index
@@ -1,6 +1,12 @@
 use crate::Storage;
 use massa_factory_exports::test_exports::create_empty_block;
-use massa_models::{address::Address, slot::Slot};
+use massa_models::{
+    address::Address,
+    block_id::BlockId,
+    endorsement::{Endorsement, EndorsementSerializer},
+    secure_share::SecureShareContent,
+    slot::Slot,
+};
 use massa_signature::KeyPair;
 
 #[test]
@@ -16,6 +22,46 @@ fn test_block_index_slot() {
     assert_eq!(stored_blocks.get(&block.id).unwrap(), &block.id);
 }
 
+#[test]
+fn test_block_iter_by_slot() {
+    let mut storage = Storage::create_root();
+    let keypair = KeyPair::generate(0).unwrap();
+    let slots = [
+        Slot::new(2, 0),
+        Slot::new(1, 1),
+        Slot::new(1, 0),
+        Slot::new(2, 1),
+        Slot::new(3, 0),
+    ];
+    for slot in &slots {
+        storage.store_block(create_empty_block(&keypair, slot));
+    }
+    // a second block in slot (1, 1)
+    storage.store_block(create_empty_block(
+        &KeyPair::generate(0).unwrap(),
+        &Slot::new(1, 1),
+    ));
+
+    let blocks = storage.read_blocks();
+    let iterated: Vec<(Slot, BlockId)> = blocks
+        .iter_by_slot(Slot::new(1, 0)..Slot::new(3, 0))
+        .map(|block| (block.content.header.content.slot, block.id))
+        .collect();
+    let mut expected: Vec<(Slot, BlockId)> = blocks
+        .aggregate_blocks_by_slot_range(Slot::new(1, 0)..Slot::new(3, 0))
+        .into_iter()
+        .map(|block_id| {
+            (
+                blocks.get(&block_id).unwrap().content.header.content.slot,
+                block_id,
+            )
+        })
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(iterated.len(), 5);
+    assert_eq!(iterated, expected);
+}
+
 #[test]
 fn test_block_index_by_creator() {
     let mut storage = Storage::create_root();
@@ -46,3 +92,36 @@ fn test_block_fail_find() {
         .get_blocks_created_by(&Address::from_public_key(&keypair2.get_public_key()))
         .is_none());
 }
+
+#[test]
+fn test_endorsement_index_by_endorsed_block() {
+    let mut storage = Storage::create_root();
+    let keypair = KeyPair::generate(0).unwrap();
+    let block = create_empty_block(&keypair, &Slot::new(0, 0));
+    let endorsement = Endorsement::new_verifiable(
+        Endorsement {
+            slot: Slot::new(1, 0),
+            index: 0,
+            endorsed_block: block.id,
+        },
+        EndorsementSerializer::new(),
+        &keypair,
+    )
+    .unwrap();
+
+    storage.store_endorsements(vec![endorsement.clone()]);
+    {
+        let endorsements = storage.read_endorsements();
+        let stored_endorsements = endorsements
+            .get_endorsements_by_endorsed_block(&block.id)
+            .unwrap();
+        assert_eq!(stored_endorsements.len(), 1);
+        assert!(stored_endorsements.contains(&endorsement.id));
+    }
+
+    storage.drop_endorsement_refs(&[endorsement.id].into_iter().collect());
+    assert!(storage
+        .read_endorsements()
+        .get_endorsements_by_endorsed_block(&block.id)
+        .is_none());
+}
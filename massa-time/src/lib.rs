@@ -4,6 +4,7 @@
 #![warn(unused_crate_dependencies)]
 
 mod error;
+#[cfg(feature = "grpc")]
 mod mapping_grpc;
 pub use error::TimeError;
 use massa_serialization::{Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
@@ -73,6 +73,7 @@
 //! the versioning transition (e.g. User 1 has upgraded to network version 1 while User 2 has not yet upgraded)
 
 pub mod address_factory;
+#[cfg(feature = "grpc")]
 pub mod grpc_mapping;
 pub mod keypair_factory;
 pub mod mips;
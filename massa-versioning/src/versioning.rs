@@ -2474,6 +2474,7 @@ mod test {
             max_history_length: 100,
             max_new_elements: 100,
             thread_count: THREAD_COUNT,
+            block_cache_size: 0,
         };
         let db = Arc::new(RwLock::new(
             Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
@@ -13,6 +13,7 @@ use massa_models::composite::PubkeySig;
 use massa_models::operation::{Operation, OperationSerializer, SecureShareOperation};
 use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::secure_share::SecureShareContent;
+use massa_models::signing_preview::OperationSigningPreview;
 use massa_signature::{KeyPair, PublicKey};
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::Entry;
@@ -31,6 +32,10 @@ pub struct Wallet {
     wallet_path: PathBuf,
     /// Password
     password: String,
+    /// Addresses staking on other machines, tracked without their keys.
+    /// They are not persisted in the wallet files.
+    #[serde(default)]
+    watch_only_addresses: PreHashSet<Address>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -75,12 +80,14 @@ impl Wallet {
                 keys,
                 wallet_path: path,
                 password,
+                watch_only_addresses: PreHashSet::default(),
             })
         } else {
             let wallet = Wallet {
                 keys: PreHashMap::default(),
                 wallet_path: path,
                 password,
+                watch_only_addresses: PreHashSet::default(),
             };
             wallet.save()?;
             Ok(wallet)
@@ -153,6 +160,36 @@ impl Wallet {
         self.keys.keys().copied().collect()
     }
 
+    /// Adds addresses to track without their keys, returns true if some were not tracked yet
+    pub fn add_watch_only_addresses(&mut self, addresses: Vec<Address>) -> bool {
+        let mut changed = false;
+        for address in addresses {
+            changed |= self.watch_only_addresses.insert(address);
+        }
+        changed
+    }
+
+    /// Stops tracking watch-only addresses. Missing addresses are ignored.
+    pub fn remove_watch_only_addresses(&mut self, addresses: &[Address]) -> bool {
+        let mut changed = false;
+        for address in addresses {
+            changed |= self.watch_only_addresses.remove(address);
+        }
+        changed
+    }
+
+    /// Get the watch-only addresses
+    pub fn get_watch_only_address_list(&self) -> PreHashSet<Address> {
+        self.watch_only_addresses.clone()
+    }
+
+    /// Get the addresses whose staking is tracked: the wallet addresses and the watch-only ones
+    pub fn get_tracked_address_list(&self) -> PreHashSet<Address> {
+        let mut addresses = self.get_wallet_address_list();
+        addresses.extend(self.watch_only_addresses.iter().copied());
+        addresses
+    }
+
     /// Save the wallets in a directory, each wallet in a yaml file.
     pub fn save(&self) -> Result<(), WalletError> {
         let mut existing_keys: HashSet<PathBuf> = HashSet::new();
@@ -197,6 +234,18 @@ impl Wallet {
         &self.keys
     }
 
+    /// Builds the human-readable preview of an operation before signing it with the keypair corresponding to the given address
+    pub fn preview_operation(
+        &self,
+        content: &Operation,
+        address: Address,
+    ) -> Result<OperationSigningPreview, WalletError> {
+        let public_key = self
+            .find_associated_public_key(&address)
+            .ok_or_else(|| WalletError::MissingKeyError(address))?;
+        Ok(OperationSigningPreview::new(content, &public_key)?)
+    }
+
     /// Signs an operation with the keypair corresponding to the given address
     pub fn create_operation(
         &self,